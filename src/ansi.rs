@@ -0,0 +1,201 @@
+//! Colored output of other programs as ratatui text.
+//!
+//! `git diff --color=always`, `rg --color=always`, `bat --color=always` and
+//! friends mark their highlighting with SGR escapes (`ESC [ ... m`). Instead
+//! of asking them for plain text and coloring it again, previews can keep
+//! their output as it is and turn it into styled [`Line`]s here.
+//!
+//! Supported are the attributes (bold, dim, italic, underline, blink,
+//! reverse, crossed out) and their resets, the 16 basic colors, the 256 color
+//! palette and 24-bit colors, for foreground and background, with `;` or `:`
+//! between parameters. Every other escape sequence (cursor movement, OSC 8
+//! hyperlinks, window titles) and control character except tab is dropped,
+//! so it can't garble the screen.
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+/// The basic colors, normal (30-37) then bright (90-97)
+const BASIC: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// `text` as one line per line of input; a style set on one line carries on
+/// to the next until it is reset, as it would in a terminal
+pub fn to_lines(text: &str) -> Vec<Line<'static>> {
+    let mut style = Style::default();
+    text.lines().map(|line| parse_line(line, &mut style)).collect()
+}
+
+/// Split `line` into spans, starting from and updating `style`
+fn parse_line(line: &str, style: &mut Style) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut text = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if let Some(params) = skip_escape(&mut chars) {
+                if !text.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut text), *style));
+                }
+                apply_sgr(style, &params);
+            }
+        } else if !c.is_control() || c == '\t' {
+            text.push(c);
+        }
+    }
+    if !text.is_empty() {
+        spans.push(Span::styled(text, *style));
+    }
+    Line::from(spans)
+}
+
+/// Consume the escape sequence after an `ESC`; returns the parameters when
+/// it sets graphic attributes (SGR)
+fn skip_escape(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+    match chars.next()? {
+        // CSI: parameters and intermediates up to a final byte in @..~
+        '[' => {
+            let mut params = String::new();
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    return (c == 'm').then_some(params);
+                }
+                params.push(c);
+            }
+            None
+        }
+        // OSC, DCS, APC, PM: up to BEL or ST (`ESC \`)
+        ']' | 'P' | '_' | '^' => {
+            while let Some(c) = chars.next() {
+                if c == '\x07' {
+                    break;
+                }
+                if c == '\x1b' && chars.peek() == Some(&'\\') {
+                    chars.next();
+                    break;
+                }
+            }
+            None
+        }
+        // Two-character escapes like `ESC =` or `ESC (B`
+        '(' | ')' => {
+            chars.next();
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Apply the SGR parameters `params` (like `1;38;5;208`) to `style`
+fn apply_sgr(style: &mut Style, params: &str) {
+    let codes: Vec<u16> = params.split([';', ':']).map(|code| code.parse().unwrap_or(0)).collect();
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => *style = Style::default(),
+            1 => *style = style.add_modifier(Modifier::BOLD),
+            2 => *style = style.add_modifier(Modifier::DIM),
+            3 => *style = style.add_modifier(Modifier::ITALIC),
+            4 => *style = style.add_modifier(Modifier::UNDERLINED),
+            5 => *style = style.add_modifier(Modifier::SLOW_BLINK),
+            7 => *style = style.add_modifier(Modifier::REVERSED),
+            9 => *style = style.add_modifier(Modifier::CROSSED_OUT),
+            22 => *style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => *style = style.remove_modifier(Modifier::ITALIC),
+            24 => *style = style.remove_modifier(Modifier::UNDERLINED),
+            25 => *style = style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => *style = style.remove_modifier(Modifier::REVERSED),
+            29 => *style = style.remove_modifier(Modifier::CROSSED_OUT),
+            code @ 30..=37 => *style = style.fg(BASIC[usize::from(code - 30)]),
+            code @ 90..=97 => *style = style.fg(BASIC[usize::from(code - 90 + 8)]),
+            code @ 40..=47 => *style = style.bg(BASIC[usize::from(code - 40)]),
+            code @ 100..=107 => *style = style.bg(BASIC[usize::from(code - 100 + 8)]),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            code @ (38 | 48) => {
+                let (color, used) = extended_color(&codes[i + 1..]);
+                if let Some(color) = color {
+                    *style = if code == 38 { style.fg(color) } else { style.bg(color) };
+                }
+                i += used;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// The color after a `38` or `48`: `5;n` from the palette or `2;r;g;b`;
+/// returns it with the number of parameters it took
+fn extended_color(codes: &[u16]) -> (Option<Color>, usize) {
+    let byte = |index: usize| codes.get(index).map(|&code| code.min(255) as u8);
+    match codes.first() {
+        Some(5) => (byte(1).map(Color::Indexed), 2),
+        Some(2) => match (byte(1), byte(2), byte(3)) {
+            (Some(r), Some(g), Some(b)) => (Some(Color::Rgb(r, g, b)), 4),
+            _ => (None, codes.len()),
+        },
+        _ => (None, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn styled(line: &Line) -> Vec<(String, Style)> {
+        line.spans.iter().map(|span| (span.content.to_string(), span.style)).collect()
+    }
+
+    #[test]
+    fn test_to_line() {
+        let line = to_lines("\x1b[1;31m-old\x1b[m \x1b[38;5;208mx\x1b[38;2;1;2;3;48;5;4my\x1b[0m").remove(0);
+        assert_eq!(
+            styled(&line),
+            vec![
+                ("-old".to_string(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                (" ".to_string(), Style::default()),
+                ("x".to_string(), Style::default().fg(Color::Indexed(208))),
+                ("y".to_string(), Style::default().fg(Color::Rgb(1, 2, 3)).bg(Color::Indexed(4))),
+            ]
+        );
+        // Colon separators, and resets of single attributes
+        let line = to_lines("\x1b[4;38:5:10mab\x1b[24;39mc").remove(0);
+        assert_eq!(styled(&line)[0].1, Style::default().fg(Color::Indexed(10)).add_modifier(Modifier::UNDERLINED));
+        assert_eq!(styled(&line)[1].1, Style::default().remove_modifier(Modifier::UNDERLINED));
+    }
+
+    #[test]
+    fn test_other_escapes() {
+        // An OSC 8 hyperlink as `rg --hyperlink-format` writes it, a cursor
+        // movement and a carriage return
+        let text = "\x1b]8;;file:///src/main.rs\x1b\\main.rs\x1b]8;;\x1b\\:\x1b[K12\r";
+        assert_eq!(to_lines(text)[0].to_string(), "main.rs:12");
+    }
+
+    #[test]
+    fn test_to_lines() {
+        let lines = to_lines("\x1b[33mcommit abc\nstill yellow\x1b[0m\nplain");
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].spans[0].style.fg, Some(Color::Yellow));
+        assert_eq!(lines[2].spans[0].style, Style::default());
+    }
+}
@@ -0,0 +1,194 @@
+//! Read-only archive listing and extraction for the explorer.
+//!
+//! Archives are handled by shelling out to the standard tools rather than
+//! linking compression libraries, the same way other tools shell out to `git`
+//! or `rg`:
+//!
+//! - `.tar`, `.tar.gz`/`.tgz`, `.tar.bz2`, `.tar.xz`, `.tar.zst`: `tar` (compression auto-detected)
+//! - `.zip`, `.jar`: `unzip`
+//! - `.7z`: `7z`, `7zz` or `7za`
+//!
+//! Listings are normalized to `/`-separated inner paths without a leading
+//! `./`; directories end with `/`. Extraction never overwrites existing files.
+//! Both run in a [`Task`], so cancelling the job stops the tool.
+
+use crate::jobs::Task;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io,
+    path::Path,
+    process::Command,
+};
+
+/// Archive formats the explorer can browse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Tar,
+    Zip,
+    SevenZip,
+}
+
+/// Detect an archive from its file name
+pub fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    const TAR_SUFFIXES: [&str; 8] = [".tar", ".tar.gz", ".tgz", ".tar.bz2", ".tbz2", ".tar.xz", ".txz", ".tar.zst"];
+    if TAR_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+        Some(ArchiveKind::Tar)
+    } else if name.ends_with(".zip") || name.ends_with(".jar") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".7z") {
+        Some(ArchiveKind::SevenZip)
+    } else {
+        None
+    }
+}
+
+/// Immediate child of a directory inside an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveChild {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// Run an archive tool in a job, turning a missing binary or failure into a
+/// readable error
+fn run_tool(task: &Task, program: &str, args: &[&str]) -> io::Result<String> {
+    let output = task.output(Command::new(program).args(args)).map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            io::Error::new(io::ErrorKind::NotFound, format!("`{}` is not installed", program))
+        } else {
+            e
+        }
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or("unknown error");
+        return Err(io::Error::other(format!("{} failed: {}", program, message.trim())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// First available 7-Zip binary
+fn seven_zip() -> &'static str {
+    ["7z", "7zz", "7za"]
+        .into_iter()
+        .find(|program| Command::new(program).arg("i").output().is_ok())
+        .unwrap_or("7z")
+}
+
+/// List every entry in an archive
+pub fn list(task: &Task, path: &Path) -> io::Result<Vec<String>> {
+    let kind = archive_kind(path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not a supported archive"))?;
+    let archive = path.to_string_lossy();
+    let entries = match kind {
+        ArchiveKind::Tar => run_tool(task, "tar", &["-tf", &archive])?.lines().map(normalize).collect(),
+        ArchiveKind::Zip => run_tool(task, "unzip", &["-Z1", &archive])?.lines().map(normalize).collect(),
+        ArchiveKind::SevenZip => parse_7z_listing(&run_tool(task, seven_zip(), &["l", "-slt", "-ba", &archive])?),
+    };
+    Ok(entries.into_iter().filter(|entry: &String| !entry.is_empty()).collect())
+}
+
+/// Strip `./` prefixes so all tools produce comparable paths
+fn normalize(entry: &str) -> String {
+    entry.trim_start_matches("./").to_string()
+}
+
+/// Parse `7z l -slt` output: `Path = ...` records, `D` attribute for directories
+fn parse_7z_listing(output: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut current: Option<String> = None;
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("Path = ") {
+            entries.extend(current.take());
+            current = Some(normalize(&path.replace('\\', "/")));
+        } else if let Some(attributes) = line.strip_prefix("Attributes = ") {
+            if let Some(path) = current.as_mut() {
+                if attributes.starts_with('D') && !path.ends_with('/') {
+                    path.push('/');
+                }
+            }
+        }
+    }
+    entries.extend(current);
+    entries
+}
+
+/// Immediate children of `prefix` (empty or ending in `/`), directories first.
+///
+/// Directories that only appear implicitly, as parents of listed files, are
+/// included too since `tar` archives often omit them.
+pub fn children(entries: &[String], prefix: &str) -> Vec<ArchiveChild> {
+    let mut found: BTreeMap<&str, bool> = BTreeMap::new();
+    for entry in entries {
+        let Some(rest) = entry.strip_prefix(prefix) else {
+            continue;
+        };
+        let (name, is_dir) = match rest.split_once('/') {
+            Some((name, _)) => (name, true),
+            None => (rest, false),
+        };
+        if !name.is_empty() {
+            *found.entry(name).or_default() |= is_dir;
+        }
+    }
+
+    let mut children: Vec<ArchiveChild> = found
+        .into_iter()
+        .map(|(name, is_dir)| ArchiveChild { name: name.to_string(), is_dir })
+        .collect();
+    children.sort_by_key(|child| !child.is_dir);
+    children
+}
+
+/// Extract an archive into `destination`, creating it if needed
+pub fn extract(task: &Task, path: &Path, destination: &Path) -> io::Result<()> {
+    let kind = archive_kind(path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not a supported archive"))?;
+    fs::create_dir_all(destination)?;
+    let archive = path.to_string_lossy();
+    let target = destination.to_string_lossy();
+    match kind {
+        ArchiveKind::Tar => run_tool(task, "tar", &["-xkf", &archive, "-C", &target])?,
+        ArchiveKind::Zip => run_tool(task, "unzip", &["-n", "-q", &archive, "-d", &target])?,
+        ArchiveKind::SevenZip => run_tool(task, seven_zip(), &["x", "-aos", &format!("-o{}", target), &archive])?,
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_archive_kind() {
+        assert_eq!(archive_kind(&PathBuf::from("a/b.TAR.GZ")), Some(ArchiveKind::Tar));
+        assert_eq!(archive_kind(&PathBuf::from("b.tgz")), Some(ArchiveKind::Tar));
+        assert_eq!(archive_kind(&PathBuf::from("b.zip")), Some(ArchiveKind::Zip));
+        assert_eq!(archive_kind(&PathBuf::from("b.7z")), Some(ArchiveKind::SevenZip));
+        assert_eq!(archive_kind(&PathBuf::from("b.gz")), None);
+    }
+
+    #[test]
+    fn test_children_include_implicit_directories() {
+        let entries: Vec<String> = ["README", "src/main.rs", "src/lib/mod.rs", "docs/"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let top = children(&entries, "");
+        let names: Vec<(&str, bool)> = top.iter().map(|c| (c.name.as_str(), c.is_dir)).collect();
+        assert_eq!(names, vec![("docs", true), ("src", true), ("README", false)]);
+
+        let src = children(&entries, "src/");
+        let names: Vec<(&str, bool)> = src.iter().map(|c| (c.name.as_str(), c.is_dir)).collect();
+        assert_eq!(names, vec![("lib", true), ("main.rs", false)]);
+    }
+
+    #[test]
+    fn test_parse_7z_listing() {
+        let output = "Path = dir\nAttributes = D....\n\nPath = dir\\file.txt\nAttributes = A....\n";
+        assert_eq!(parse_7z_listing(output), vec!["dir/".to_string(), "dir/file.txt".to_string()]);
+    }
+}
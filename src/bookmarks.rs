@@ -0,0 +1,57 @@
+//! Bookmarked directories for the explorer.
+//!
+//! Bookmarks are stored one absolute path per line in
+//! `$XDG_CONFIG_HOME/terminal-tools/bookmarks` (default
+//! `~/.config/terminal-tools/bookmarks`) so they
+//! can be edited by hand or shared between machines. Blank lines and lines
+//! starting with `#` are ignored; the file keeps the order bookmarks were
+//! added in.
+
+use crate::platform;
+use std::{fs, io, path::PathBuf};
+
+fn bookmarks_file() -> Option<PathBuf> {
+    platform::config_dir().map(|dir| dir.join("bookmarks"))
+}
+
+fn parse(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Load saved bookmarks; a missing file yields an empty list
+pub fn load() -> Vec<PathBuf> {
+    bookmarks_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| parse(&content))
+        .unwrap_or_default()
+}
+
+/// Replace the saved bookmarks with `bookmarks`
+pub fn save(bookmarks: &[PathBuf]) -> io::Result<()> {
+    let path = bookmarks_file()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory for bookmarks"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content: String = bookmarks
+        .iter()
+        .map(|bookmark| format!("{}\n", bookmark.display()))
+        .collect();
+    fs::write(path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let bookmarks = parse("# saved by tt\n/home/me/projects\n\n  /etc  \n");
+        assert_eq!(bookmarks, vec![PathBuf::from("/home/me/projects"), PathBuf::from("/etc")]);
+    }
+}
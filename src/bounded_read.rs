@@ -0,0 +1,256 @@
+//! Reading just enough of a file to preview it, however large the file is.
+//!
+//! Previews never load a whole file: [`head`] reads the first bytes up to a
+//! limit, and a [`LineIndex`] reads the few lines around a search match.
+//! Text cut at a limit ends on a whole UTF-8 character, and
+//! [`Head::truncated`] tells whether the file goes on, so the preview can
+//! say it shows only part of it.
+//!
+//! A [`LineIndex`] remembers where every [`STRIDE`]th line of a file starts,
+//! as far as the file has been read. Going to another match in the same
+//! file seeks to the nearest line start before it and reads on from there,
+//! instead of reading the file from the top again; the index grows only
+//! when a match further down is shown, so a huge file is never scanned
+//! past the last match looked at.
+
+use std::{
+    fs::{File, Metadata},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::Path,
+    time::SystemTime,
+};
+
+/// Bytes kept of one line; longer lines are cut
+pub const LINE_LIMIT: usize = 4096;
+
+/// Lines between the line starts a [`LineIndex`] keeps
+pub const STRIDE: usize = 64;
+
+/// The start of a text file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Head {
+    pub text: String,
+    /// Whether the file goes on past `text`
+    pub truncated: bool,
+}
+
+/// The first `limit` bytes of the file at `path`, or None when they are not
+/// text
+pub fn head(path: &Path, limit: u64) -> io::Result<Option<Head>> {
+    // One byte more tells whether there is more, even for files whose size
+    // is not known up front
+    let mut bytes = Vec::new();
+    File::open(path)?.take(limit + 1).read_to_end(&mut bytes)?;
+    let truncated = bytes.len() as u64 > limit;
+    bytes.truncate(limit as usize);
+    Ok(decode(bytes, truncated).map(|text| Head { text, truncated }))
+}
+
+/// `bytes` as text; a character cut at the end is dropped when the bytes
+/// were cut there, anything else invalid means they are binary
+fn decode(bytes: Vec<u8>, cut: bool) -> Option<String> {
+    match String::from_utf8(bytes) {
+        Ok(text) => Some(text),
+        Err(e) if cut && e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).ok()
+        }
+        Err(_) => None,
+    }
+}
+
+/// Where the lines of a file start, as far as it has been read
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    /// Offsets of lines 0, [`STRIDE`], 2 × [`STRIDE`], ...
+    starts: Vec<u64>,
+    /// Whether `starts` reaches the end of the file
+    complete: bool,
+    /// The file as it was when indexed
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl LineIndex {
+    /// An index of the file `metadata` describes, with nothing read yet
+    pub fn new(metadata: &Metadata) -> Self {
+        LineIndex { starts: vec![0], complete: false, modified: metadata.modified().ok(), len: metadata.len() }
+    }
+
+    /// Whether the file is still the one indexed
+    pub fn is_current(&self, metadata: &Metadata) -> bool {
+        self.modified == metadata.modified().ok() && self.len == metadata.len()
+    }
+
+    /// Lines `first..first + count` (counted from 0) of the file at `path`,
+    /// fewer at its end; each is cut at [`LINE_LIMIT`] bytes and invalid
+    /// UTF-8 in it replaced
+    pub fn lines(&mut self, path: &Path, first: usize, count: usize) -> io::Result<Vec<String>> {
+        let mut reader = BufReader::new(File::open(path)?);
+        self.extend(&mut reader, first / STRIDE)?;
+        let known = (first / STRIDE).min(self.starts.len() - 1);
+        reader.seek(SeekFrom::Start(self.starts[known]))?;
+        skip_lines(&mut reader, first - known * STRIDE)?;
+        read_lines(&mut reader, count)
+    }
+
+    /// Read on from the last known line start until the start of line
+    /// `stride` × [`STRIDE`] is known, or the file ends
+    fn extend(&mut self, reader: &mut BufReader<File>, stride: usize) -> io::Result<()> {
+        if self.complete || stride < self.starts.len() {
+            return Ok(());
+        }
+        let mut offset = self.starts[self.starts.len() - 1];
+        reader.seek(SeekFrom::Start(offset))?;
+        // Lines read since the last start kept
+        let mut lines = 0;
+        while stride >= self.starts.len() {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                self.complete = true;
+                break;
+            }
+            let mut used = buffer.len();
+            for (at, _) in buffer.iter().enumerate().filter(|(_, &byte)| byte == b'\n') {
+                lines += 1;
+                if lines == STRIDE {
+                    lines = 0;
+                    self.starts.push(offset + at as u64 + 1);
+                    if stride < self.starts.len() {
+                        used = at + 1;
+                        break;
+                    }
+                }
+            }
+            offset += used as u64;
+            reader.consume(used);
+        }
+        Ok(())
+    }
+}
+
+/// Move `reader` past `count` lines without keeping them
+fn skip_lines(reader: &mut impl BufRead, mut count: usize) -> io::Result<()> {
+    while count > 0 {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        let used = match buffer.iter().position(|&byte| byte == b'\n') {
+            Some(end) => {
+                count -= 1;
+                end + 1
+            }
+            None => buffer.len(),
+        };
+        reader.consume(used);
+    }
+    Ok(())
+}
+
+/// The next `count` lines of `reader`, fewer at the end of the file
+fn read_lines(reader: &mut impl BufRead, count: usize) -> io::Result<Vec<String>> {
+    let mut lines = Vec::with_capacity(count);
+    while lines.len() < count {
+        match read_line(reader)? {
+            Some(line) => lines.push(line),
+            None => break,
+        }
+    }
+    Ok(lines)
+}
+
+/// The next line of `reader` without its line ending, or None at the end of
+/// the file
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut bytes = Vec::new();
+    let mut read_any = false;
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        read_any = true;
+        let (chunk, used, done) = match buffer.iter().position(|&byte| byte == b'\n') {
+            Some(end) => (&buffer[..end], end + 1, true),
+            None => (buffer, buffer.len(), false),
+        };
+        let room = LINE_LIMIT.saturating_sub(bytes.len());
+        bytes.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        reader.consume(used);
+        if done {
+            break;
+        }
+    }
+    if !read_any {
+        return Ok(None);
+    }
+    if bytes.last() == Some(&b'\r') {
+        bytes.pop();
+    }
+    Ok(Some(match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => {
+            let error = e.utf8_error();
+            let mut bytes = e.into_bytes();
+            // A character cut at the limit goes rather than showing as garbage
+            if bytes.len() == LINE_LIMIT && error.error_len().is_none() {
+                bytes.truncate(error.valid_up_to());
+            }
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, io::Cursor, process};
+
+    #[test]
+    fn test_head_cuts_on_char_boundary() {
+        let path = env::temp_dir().join(format!("tt-bounded-read-{}", process::id()));
+        fs::write(&path, "aé\nb").unwrap();
+        assert_eq!(head(&path, 2).unwrap(), Some(Head { text: "a".to_string(), truncated: true }));
+        assert_eq!(head(&path, 10).unwrap(), Some(Head { text: "aé\nb".to_string(), truncated: false }));
+
+        fs::write(&path, [b'a', 0xff, b'b']).unwrap();
+        assert_eq!(head(&path, 10).unwrap(), None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_line_index_seeks_to_lines() {
+        let path = env::temp_dir().join(format!("tt-line-index-{}", process::id()));
+        let text: String = (0..STRIDE * 3 + 5).map(|line| format!("line {}\n", line)).collect();
+        fs::write(&path, text).unwrap();
+
+        let mut index = LineIndex::new(&fs::metadata(&path).unwrap());
+        assert_eq!(index.lines(&path, STRIDE * 2 + 1, 2).unwrap(), vec![format!("line {}", STRIDE * 2 + 1), format!("line {}", STRIDE * 2 + 2)]);
+        assert_eq!(index.starts.len(), 3);
+        assert_eq!(index.lines(&path, 3, 1).unwrap(), vec!["line 3"]);
+        assert_eq!(index.lines(&path, STRIDE * 3 + 4, 5).unwrap(), vec![format!("line {}", STRIDE * 3 + 4)]);
+        assert!(!index.complete);
+        assert!(index.lines(&path, STRIDE * 9, 1).unwrap().is_empty());
+        assert!(index.complete);
+
+        fs::write(&path, "changed\n").unwrap();
+        assert!(!index.is_current(&fs::metadata(&path).unwrap()));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_line_window() {
+        let mut reader = Cursor::new("one\r\ntwo\nthree\nfour");
+        skip_lines(&mut reader, 1).unwrap();
+        assert_eq!(read_lines(&mut reader, 2).unwrap(), vec!["two", "three"]);
+        assert_eq!(read_lines(&mut reader, 5).unwrap(), vec!["four"]);
+
+        let long = format!("{}é\nnext", "x".repeat(LINE_LIMIT - 1));
+        let lines = read_lines(&mut Cursor::new(long), 2).unwrap();
+        assert_eq!(lines[0], "x".repeat(LINE_LIMIT - 1));
+        assert_eq!(lines[1], "next");
+    }
+}
@@ -12,6 +12,12 @@
 //! tt <SUBCOMMAND> [OPTIONS] [ARGS]
 //! ```
 //!
+//! A bare `tt` opens a menu of the interactive tools instead.
+//!
+//! With `--plain` (alias `--no-tui`) or `--json`, tools print their data
+//! instead of starting the TUI; so does any tool other than a picker like
+//! `tt dir` when stdout is redirected (see [`crate::plain`]).
+//!
 //! ## Available Commands
 //!
 //! - **find** - Fuzzy file finder with live preview and filtering
@@ -20,9 +26,24 @@
 //! - **git** - Git operations (log, branch, status, diff) with TUI
 //! - **explore** - File/directory explorer with image preview
 //! - **history** - Command history browser and executor
+//! - **snip** - Saved command snippets with {placeholder} prompts
 //! - **env** - Environment variable viewer and manager
 //! - **man** - Manual page browser with search
+//! - **log** - Log file viewer with follow, level colors and filters
+//! - **net** - Listening ports and connections with their owning processes
+//! - **systemd** - systemd services with journal preview and start/stop/restart
+//! - **json** - JSON and YAML explorer with a collapsible tree and jq paths
+//! - **hex** - Hex viewer for binary files with byte and text search
+//! - **sys** - System dashboard: CPU cores, memory, disks, network and top processes
+//! - **pkg** - Package search with details, dependencies and install/remove (apt, dnf, pacman, brew)
+//! - **todo** - TODO/FIXME/HACK/XXX comments of a project, grouped by tag and file
+//! - **diff** - Diff of two files, or of two directories file by file, unified or side by side
+//! - **workspace** - Several tools as tabs of one screen, switched with Alt-1..9
 //! - **recent** - Recent files tracker with MRU ordering
+//! - **du** - Disk usage analyzer with drill-down and delete
+//! - **z** - Frecency-ranked directory jumper (zoxide database or tt's own)
+//! - **init** - Shell integration: cd-on-exit from the explorer, a Ctrl-R history binding, command and recent-file hooks
+//! - **completions** - Tab completion scripts for bash, zsh, fish and PowerShell
 //!
 //! ## Usage Examples
 //!
@@ -30,6 +51,11 @@
 //! # File operations
 //! tt find /path/to/search --extensions "rs,toml" --search "main"
 //! tt dir /home/user/projects
+//! vim "$(tt dir --choose-file)"
+//! tt du ~/Downloads
+//! eval "$(tt init bash)"  # then `ttd` to cd with the explorer, `ttz` to jump
+//! eval "$(tt completions bash)"
+//! tt z proj
 //!
 //! # Content search
 //! tt search "pattern" --path /src --file-type rust --ignore-case
@@ -37,6 +63,18 @@
 //!
 //! # Process management  
 //! tt kill --filter "python"
+//! tt kill --user root --state zombie
+//! tt kill --ports --filter 3000
+//! tt kill --pattern "node .*dev-server" --signal KILL --yes
+//! tt net --listening --filter 8080
+//! tt systemd --failed
+//! curl -s https://api.github.com/repos/pbower/terminal-tools | tt json
+//! tt hex /bin/ls --offset 0x400
+//! tt sys
+//! tt pkg ripgrep
+//! tt todo src --tag fixme
+//! tt diff old/ new/ --side-by-side
+//! tt workspace dir search git-log
 //!
 //! # Git operations
 //! tt git log
@@ -45,9 +83,19 @@
 //!
 //! # System utilities
 //! tt hist --limit 50
+//! tt hist --shell all
+//! tt hist --record "make" --status 2 --duration 1400
 //! tt env --filter "PATH"
+//! eval "$(tt env --apply)"
 //! tt man --search "grep"
+//! tt log /var/log/syslog --level warn
+//! journalctl -f | tt log --include "sshd"
 //! tt recent --limit 20
+//!
+//! # Scripting
+//! tt find --extensions rs | wc -l
+//! tt kill --json | jq 'select(.cpu > 50) | .pid'
+//! tt git log --plain | cut -f1,4
 //! ```
 //!
 //! ## Design Principles
@@ -57,7 +105,14 @@
 //! - **Optional Arguments**: Most arguments are optional to enable interactive workflows
 //! - **Help Integration**: Comprehensive help text and examples for all commands
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use crate::diff;
+use crate::log_file::Severity;
+use crate::packages::Manager;
+use crate::process_info::ProcessState;
+use crate::shell_history::Shell;
+use crate::todos::Tag;
+use crate::tools::workspace::Kind;
 use std::path::PathBuf;
 
 /// Main CLI structure for the terminal-tools application.
@@ -70,9 +125,34 @@ use std::path::PathBuf;
 #[command(version = "0.1.0")]
 #[command(disable_help_subcommand = true)]
 pub struct Cli {
-    /// The specific tool/command to run
+    /// Color theme: auto, dark, light, solarized-dark or solarized-light
+    /// (default: `theme` in config.toml, then auto)
+    #[arg(long, global = true, value_name = "THEME")]
+    pub theme: Option<String>,
+    
+    /// Draw image previews as grayscale ASCII art, never as real images
+    /// (Kitty, iTerm2 or Sixel graphics) or colored half blocks
+    #[arg(long, global = true)]
+    pub ascii_only: bool,
+    
+    /// Print the tool's data as tab-separated lines instead of starting the
+    /// TUI; the default when stdout is not a terminal
+    #[arg(long, visible_alias = "no-tui", global = true)]
+    pub plain: bool,
+    
+    /// Print the tool's data as JSON Lines instead of starting the TUI
+    #[arg(long, global = true)]
+    pub json: bool,
+    
+    /// Start the tool afresh instead of where it was left last time in
+    /// this directory
+    #[arg(long, global = true)]
+    pub fresh: bool,
+    
+    /// The specific tool/command to run; without one, a menu of the tools
+    /// opens
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
 }
 
 /// All available terminal tools as CLI subcommands.
@@ -102,6 +182,34 @@ pub enum Commands {
         /// Filter processes by name
         #[arg(short, long)]
         filter: Option<String>,
+        
+        /// Auto-refresh interval in seconds (0 disables auto-refresh)
+        #[arg(short, long, default_value = "2")]
+        interval: u64,
+        
+        /// Only show processes owned by this user
+        #[arg(short, long)]
+        user: Option<String>,
+        
+        /// Only show processes in this state
+        #[arg(short, long, value_enum)]
+        state: Option<StateFilter>,
+        
+        /// Start in the listening ports view
+        #[arg(long)]
+        ports: bool,
+        
+        /// Kill every process whose command line matches this regex, without the TUI
+        #[arg(long)]
+        pattern: Option<String>,
+        
+        /// Signal sent in --pattern mode (name or number)
+        #[arg(long, default_value = "TERM", requires = "pattern")]
+        signal: String,
+        
+        /// Skip the confirmation prompt in --pattern mode
+        #[arg(short, long, requires = "pattern")]
+        yes: bool,
     },
     
     /// Git operations and history browser
@@ -112,30 +220,124 @@ pub enum Commands {
     
     /// Command history browser and executor
     Hist {
-        /// Number of recent commands to show
-        #[arg(short, long, default_value = "100")]
-        limit: usize,
+        /// Number of recent commands to show [default: 100, or `limit` in config.toml]
+        #[arg(short, long)]
+        limit: Option<usize>,
+        
+        /// History to read (default: the shell in $SHELL, or all of them if unknown)
+        #[arg(long, value_enum)]
+        shell: Option<HistShell>,
+        
+        /// Initial filter text
+        #[arg(short, long)]
+        query: Option<String>,
+        
+        /// Print the chosen command to stdout instead of running it (for the Ctrl-R binding, see `tt init`)
+        #[arg(long)]
+        print_selection: bool,
+        
+        /// Log a finished command with its directory, exit status and duration (used by the `tt init` hook)
+        #[arg(long, value_name = "COMMAND", requires = "status")]
+        record: Option<String>,
+        
+        /// Exit status of the recorded command
+        #[arg(long, requires = "record", allow_negative_numbers = true)]
+        status: Option<i32>,
+        
+        /// How long the recorded command ran, in milliseconds
+        #[arg(long, value_name = "MS", requires = "record")]
+        duration: Option<u64>,
+        
+        /// Directory the recorded command started in (default: the current directory)
+        #[arg(long, value_name = "DIR", requires = "record")]
+        cwd: Option<PathBuf>,
     },
     
+    /// Browse saved command snippets (star commands in `tt hist`), fill placeholders, run or print
+    Snip,
+    
     /// Interactive file/directory explorer
     Dir {
         /// Starting directory
         #[arg(short, long, default_value = ".")]
         path: PathBuf,
+        
+        /// Print the directory you quit in to stdout (for cd-on-exit, see `tt init`)
+        #[arg(long, conflicts_with = "choose_file")]
+        choose_dir: bool,
+        
+        /// Print the file chosen with Enter to stdout instead of opening it
+        #[arg(long)]
+        choose_file: bool,
+        
+        /// Browse the trash (home trash and PATH/.tt-trash) to restore or purge items
+        #[arg(long, conflicts_with_all = ["choose_dir", "choose_file"])]
+        trash: bool,
+    },
+    
+    /// Disk usage analyzer: directory sizes as a bar chart, with drill-down and delete
+    Du {
+        /// Directory to analyze
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    
+    /// Jump to a frequently used directory, printing the chosen path (see `tt init`)
+    Z {
+        /// Words the directory must match, in order (the last one in its name)
+        query: Vec<String>,
+        
+        /// Record a visit to this directory instead of opening the list
+        #[arg(long, value_name = "DIR", conflicts_with = "query")]
+        add: Option<PathBuf>,
+    },
+    
+    /// Print the shell integration: cd via `tt dir` / `tt z`, the Ctrl-R history binding, the command and recent-file hooks and completions
+    Init {
+        /// Shell to generate the integration for
+        #[arg(value_enum)]
+        shell: InitShell,
+    },
+    
+    /// Print a tab completion script for tt's subcommands and flags
+    Completions {
+        /// Shell to generate the completions for
+        #[arg(value_enum)]
+        shell: CompletionShell,
     },
     
     /// Environment variable viewer and manager
     Env {
-        /// Filter environment variables by name
+        /// Start with this search query (matches names and values)
         #[arg(short, long)]
         filter: Option<String>,
+        
+        /// Compare against a dotenv file, highlighting variables that differ or are not set
+        #[arg(long, value_name = "PATH", conflicts_with = "diff")]
+        file: Option<PathBuf>,
+        
+        /// Save the current environment under NAME and exit
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["filter", "file", "diff", "apply"])]
+        snapshot: Option<String>,
+        
+        /// Show what was added, changed or removed since the snapshot NAME
+        #[arg(long, value_name = "NAME")]
+        diff: Option<String>,
+        
+        /// Show values of token, password and key variables instead of masking them
+        #[arg(long)]
+        show_secrets: bool,
+        
+        /// Print your changes as shell statements on exit, for `eval "$(tt env --apply)"` (syntax from $SHELL unless given)
+        #[arg(long, value_enum, value_name = "SHELL")]
+        apply: Option<Option<InitShell>>,
     },
     
     /// Recent files browser with MRU tracking
     Recent {
-        /// Number of recent files to show
-        #[arg(short, long, default_value = "10")]
-        limit: usize,
+        /// Number of recent files to show [default: 10, or `limit` in config.toml]
+        #[arg(short, long)]
+        limit: Option<usize>,
     },
     
     
@@ -144,6 +346,147 @@ pub enum Commands {
         /// Search term for man pages
         #[arg(short, long)]
         search: Option<String>,
+        
+        /// Only list pages of this section (1 commands, 2 syscalls, 3 library functions, ... 8 admin)
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=8))]
+        section: Option<u8>,
+    },
+    
+    /// Log file viewer: follow new lines, color by level, filter and jump to a time
+    Log {
+        /// Log file to view (default: read standard input)
+        file: Option<PathBuf>,
+        
+        /// Only show lines matching this regex
+        #[arg(short, long)]
+        include: Option<String>,
+        
+        /// Hide lines matching this regex
+        #[arg(short = 'x', long)]
+        exclude: Option<String>,
+        
+        /// Only show lines logged at this level or above
+        #[arg(short, long, value_enum)]
+        level: Option<LogLevel>,
+        
+        /// Show the file as it is instead of following new lines
+        #[arg(long)]
+        no_follow: bool,
+    },
+    
+    /// Network browser: listening ports and connections with their owning processes
+    Net {
+        /// Filter by address, port, state, PID or process name
+        #[arg(short, long)]
+        filter: Option<String>,
+        
+        /// Only show listening sockets
+        #[arg(short, long, conflicts_with = "connected")]
+        listening: bool,
+        
+        /// Only show connected sockets
+        #[arg(short, long)]
+        connected: bool,
+        
+        /// Auto-refresh interval in seconds (0 disables auto-refresh)
+        #[arg(short, long, default_value = "2")]
+        interval: u64,
+    },
+    
+    /// systemd service browser: state, journal preview, start/stop/restart/enable/disable
+    Systemd {
+        /// Filter by unit name or description
+        #[arg(short, long)]
+        filter: Option<String>,
+        
+        /// Show the units of your user's service manager (systemctl --user)
+        #[arg(long)]
+        user: bool,
+        
+        /// Start with the failed units
+        #[arg(long, conflicts_with = "all")]
+        failed: bool,
+        
+        /// Start with every unit, not only services
+        #[arg(short, long)]
+        all: bool,
+    },
+    
+    /// JSON and YAML explorer: collapsible tree, value preview, jq paths and key search
+    Json {
+        /// JSON, JSON Lines or YAML file to explore (default: read standard input)
+        file: Option<PathBuf>,
+    },
+    
+    /// Hex viewer for binary files: offset, hex and ASCII columns with search
+    Hex {
+        /// File to view
+        file: PathBuf,
+        
+        /// Bytes per row
+        #[arg(short, long, default_value = "16", value_parser = clap::value_parser!(u8).range(1..=64))]
+        width: u8,
+        
+        /// Start at this offset (decimal or 0x hex)
+        #[arg(short, long)]
+        offset: Option<String>,
+    },
+    
+    /// System dashboard: CPU per core, memory and swap, disks, network throughput and top processes
+    Sys {
+        /// Refresh interval in seconds (0 disables auto-refresh)
+        #[arg(short, long, default_value = "1")]
+        interval: u64,
+    },
+    
+    /// Package browser for apt, dnf, pacman or Homebrew: fuzzy search, details and dependencies, install and remove
+    Pkg {
+        /// Search package names (fuzzy) and descriptions
+        query: Option<String>,
+        
+        /// Start with the installed packages only
+        #[arg(long)]
+        installed: bool,
+        
+        /// Package manager to use (default: the first found of apt, dnf, pacman and brew)
+        #[arg(short, long, value_enum)]
+        manager: Option<PkgManager>,
+    },
+    
+    /// TODO, FIXME, HACK and XXX comments in a tree, grouped by tag and file, opened in the editor at the line
+    Todo {
+        /// Directory to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        
+        /// Only notes with this tag (repeatable)
+        #[arg(short, long, value_enum)]
+        tag: Vec<TodoTag>,
+    },
+    
+    /// Diff two files, or two directories file by file, without git
+    Diff {
+        /// Old file or directory
+        old: PathBuf,
+        
+        /// New file or directory
+        new: PathBuf,
+        
+        /// Start side by side rather than unified
+        #[arg(short, long)]
+        side_by_side: bool,
+        
+        /// Lines of context around changes
+        #[arg(short = 'U', long, default_value_t = diff::CONTEXT)]
+        context: usize,
+    },
+    
+    /// Several tools as tabs of one screen, switched with Alt-1..9
+    #[command(visible_alias = "ws")]
+    Workspace {
+        /// Tools to open as tabs, in order
+        #[arg(value_enum, default_values_t = [WorkspaceTool::Dir, WorkspaceTool::Search, WorkspaceTool::GitLog])]
+        tools: Vec<WorkspaceTool>,
     },
     
     /// Content search with ripgrep integration
@@ -165,6 +508,52 @@ pub enum Commands {
     },
 }
 
+impl Commands {
+    /// Subcommand name, which is also the tool's table in config.toml
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::Find { .. } => "find",
+            Commands::Kill { .. } => "kill",
+            Commands::Git { .. } => "git",
+            Commands::Hist { .. } => "hist",
+            Commands::Snip => "snip",
+            Commands::Dir { .. } => "dir",
+            Commands::Du { .. } => "du",
+            Commands::Z { .. } => "z",
+            Commands::Init { .. } => "init",
+            Commands::Completions { .. } => "completions",
+            Commands::Env { .. } => "env",
+            Commands::Recent { .. } => "recent",
+            Commands::Man { .. } => "man",
+            Commands::Log { .. } => "log",
+            Commands::Net { .. } => "net",
+            Commands::Systemd { .. } => "systemd",
+            Commands::Json { .. } => "json",
+            Commands::Hex { .. } => "hex",
+            Commands::Sys { .. } => "sys",
+            Commands::Pkg { .. } => "pkg",
+            Commands::Todo { .. } => "todo",
+            Commands::Diff { .. } => "diff",
+            Commands::Workspace { .. } => "workspace",
+            Commands::Search { .. } => "search",
+        }
+    }
+    
+    /// Whether a redirected stdout asks for plain output. Pickers that
+    /// print the choice for the shell (`tt dir`, `tt hist`, `tt snip`,
+    /// `tt z`) draw on stderr instead and keep working inside `$(...)`;
+    /// commands without a TUI print anyway.
+    pub fn plain_when_piped(&self) -> bool {
+        match self {
+            Commands::Dir { trash, .. } => *trash,
+            Commands::Kill { pattern, .. } => pattern.is_none(),
+            Commands::Env { snapshot, apply, .. } => snapshot.is_none() && apply.is_none(),
+            Commands::Hist { .. } | Commands::Snip | Commands::Z { .. } | Commands::Init { .. } | Commands::Completions { .. } => false,
+            _ => true,
+        }
+    }
+}
+
 /// Git-specific subcommands for repository operations.
 ///
 /// These commands provide TUI interfaces for common git operations,
@@ -173,7 +562,10 @@ pub enum Commands {
 #[derive(Subcommand)]
 pub enum GitCommands {
     /// Browse git log with diff preview
-    Log,
+    Log {
+        /// Only the commits touching this file or directory
+        path: Option<PathBuf>,
+    },
     
     /// Switch branches interactively
     Branch,
@@ -183,4 +575,171 @@ pub enum GitCommands {
     
     /// Show git diff with file selection
     Diff,
-}
\ No newline at end of file
+}
+
+/// Shells supported by `tt init`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InitShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Shells supported by `tt completions`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    #[value(name = "powershell")]
+    Powershell,
+}
+
+impl CompletionShell {
+    /// Tab completion script for `tt`, generated from the [`Cli`] definition
+    pub fn script(self) -> String {
+        let shell = match self {
+            CompletionShell::Bash => clap_complete::Shell::Bash,
+            CompletionShell::Zsh => clap_complete::Shell::Zsh,
+            CompletionShell::Fish => clap_complete::Shell::Fish,
+            CompletionShell::Powershell => clap_complete::Shell::PowerShell,
+        };
+        let mut script = Vec::new();
+        clap_complete::generate(shell, &mut Cli::command(), "tt", &mut script);
+        String::from_utf8_lossy(&script).into_owned()
+    }
+}
+
+/// History sources selectable with `tt hist --shell`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HistShell {
+    Bash,
+    Zsh,
+    Fish,
+    #[value(name = "powershell")]
+    PowerShell,
+    /// Every shell's history, merged by timestamp
+    All,
+}
+
+impl HistShell {
+    /// Shells to read history from; `None` picks the user's shell
+    pub fn shells(choice: Option<HistShell>) -> Vec<Shell> {
+        match choice {
+            Some(HistShell::Bash) => vec![Shell::Bash],
+            Some(HistShell::Zsh) => vec![Shell::Zsh],
+            Some(HistShell::Fish) => vec![Shell::Fish],
+            Some(HistShell::PowerShell) => vec![Shell::Powershell],
+            Some(HistShell::All) => Shell::ALL.to_vec(),
+            None => Shell::detect().map(|shell| vec![shell]).unwrap_or_else(|| Shell::ALL.to_vec()),
+        }
+    }
+}
+
+/// Process states selectable with `tt kill --state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StateFilter {
+    Running,
+    Sleeping,
+    Stopped,
+    Zombie,
+}
+
+impl From<StateFilter> for ProcessState {
+    fn from(filter: StateFilter) -> Self {
+        match filter {
+            StateFilter::Running => ProcessState::Running,
+            StateFilter::Sleeping => ProcessState::Sleeping,
+            StateFilter::Stopped => ProcessState::Stopped,
+            StateFilter::Zombie => ProcessState::Zombie,
+        }
+    }
+}
+
+/// Package managers selectable with `tt pkg --manager`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PkgManager {
+    Apt,
+    Dnf,
+    Pacman,
+    Brew,
+}
+
+impl From<PkgManager> for Manager {
+    fn from(manager: PkgManager) -> Self {
+        match manager {
+            PkgManager::Apt => Manager::Apt,
+            PkgManager::Dnf => Manager::Dnf,
+            PkgManager::Pacman => Manager::Pacman,
+            PkgManager::Brew => Manager::Brew,
+        }
+    }
+}
+
+/// Tags selectable with `tt todo --tag`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TodoTag {
+    Todo,
+    Fixme,
+    Hack,
+    Xxx,
+}
+
+impl From<TodoTag> for Tag {
+    fn from(tag: TodoTag) -> Self {
+        match tag {
+            TodoTag::Todo => Tag::Todo,
+            TodoTag::Fixme => Tag::Fixme,
+            TodoTag::Hack => Tag::Hack,
+            TodoTag::Xxx => Tag::Xxx,
+        }
+    }
+}
+
+/// Tools that `tt workspace` opens as tabs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WorkspaceTool {
+    Dir,
+    Find,
+    Search,
+    GitLog,
+    GitDiff,
+    Recent,
+    Todo,
+}
+
+impl From<WorkspaceTool> for Kind {
+    fn from(tool: WorkspaceTool) -> Self {
+        match tool {
+            WorkspaceTool::Dir => Kind::Dir,
+            WorkspaceTool::Find => Kind::Find,
+            WorkspaceTool::Search => Kind::Search,
+            WorkspaceTool::GitLog => Kind::GitLog,
+            WorkspaceTool::GitDiff => Kind::GitDiff,
+            WorkspaceTool::Recent => Kind::Recent,
+            WorkspaceTool::Todo => Kind::Todo,
+        }
+    }
+}
+
+/// Levels selectable with `tt log --level`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl From<LogLevel> for Severity {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => Severity::Trace,
+            LogLevel::Debug => Severity::Debug,
+            LogLevel::Info => Severity::Info,
+            LogLevel::Warn => Severity::Warn,
+            LogLevel::Error => Severity::Error,
+        }
+    }
+}
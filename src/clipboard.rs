@@ -0,0 +1,97 @@
+//! Copying text to the system clipboard.
+//!
+//! tt has no clipboard library dependency; instead it pipes the text into the
+//! first clipboard tool that works for the session:
+//!
+//! - `pbcopy` on macOS
+//! - `clip.exe` on Windows (and WSL)
+//! - `wl-copy` under Wayland (`$WAYLAND_DISPLAY`)
+//! - `xclip` or `xsel` under X11 (`$DISPLAY`)
+//!
+//! When none of them is available (typically over SSH) the text is sent with
+//! the OSC 52 terminal escape, which most modern terminals (and tmux with
+//! `set-clipboard on`) turn into a clipboard write on the user's machine.
+//!
+//! Every tool copies its selection with `y` through [`copy_and_notify`],
+//! which reports the result as a toast: paths in `find` and `explore`,
+//! `file:line` in `search`, commit hashes in `git log`, PIDs in `kill` and
+//! values in `env`.
+
+use crate::image_preview;
+use crate::notify::Notifications;
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+/// Copy `text` to the clipboard, returning the name of the method used
+pub fn copy(text: &str) -> io::Result<&'static str> {
+    for (name, program, args) in candidates() {
+        if pipe_to(program, args, text).is_ok() {
+            return Ok(name);
+        }
+    }
+    osc52(text)?;
+    Ok("OSC 52")
+}
+
+/// Copy `text` and report it on `notifications`, describing it as `what`
+pub fn copy_and_notify(notifications: &mut Notifications, what: &str, text: &str) {
+    match copy(text) {
+        Ok(method) => notifications.success(format!("Copied {} ({})", what, method)),
+        Err(e) => notifications.error(format!("Could not copy {}: {}", what, e)),
+    }
+}
+
+/// Clipboard tools to try, in order, for the current session
+fn candidates() -> Vec<(&'static str, &'static str, &'static [&'static str])> {
+    let mut tools: Vec<(&'static str, &'static str, &'static [&'static str])> = Vec::new();
+    if cfg!(target_os = "macos") {
+        tools.push(("pbcopy", "pbcopy", &[]));
+    }
+    if cfg!(windows) || env::var_os("WSL_DISTRO_NAME").is_some() {
+        tools.push(("clip", "clip.exe", &[]));
+    }
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        tools.push(("wl-copy", "wl-copy", &[]));
+    }
+    if env::var_os("DISPLAY").is_some() {
+        tools.push(("xclip", "xclip", &["-selection", "clipboard"]));
+        tools.push(("xsel", "xsel", &["--clipboard", "--input"]));
+    }
+    tools
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> io::Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    if child.wait()?.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{} failed", program)))
+    }
+}
+
+/// Ask the terminal to set the clipboard, wrapped for tmux when inside it
+fn osc52(text: &str) -> io::Result<()> {
+    let sequence = format!("\x1b]52;c;{}\x07", image_preview::base64(text.as_bytes()));
+    let sequence = if env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    };
+    // The TUI may own stdout or stderr; the controlling terminal is safest
+    match OpenOptions::new().write(true).open("/dev/tty") {
+        Ok(mut tty) => tty.write_all(sequence.as_bytes()),
+        Err(_) => io::stderr().write_all(sequence.as_bytes()),
+    }
+}
@@ -0,0 +1,195 @@
+//! Structured command log written by the `tt init` shell hook.
+//!
+//! Shell history files only keep the command line (and sometimes a start
+//! time). The hook installed by `tt init` also records where each command ran,
+//! how it exited and how long it took by calling
+//! `tt hist --record <command> --status <code> --duration <ms>` after every
+//! command. Records are appended as JSON lines to
+//! `$XDG_STATE_HOME/terminal-tools/commands.jsonl` (default
+//! `~/.local/state/terminal-tools/commands.jsonl`):
+//!
+//! ```json
+//! {"command":"cargo test","cwd":"/home/me/src/tt","status":101,"duration_ms":5312,"time":1700000000,"shell":"zsh"}
+//! ```
+//!
+//! `tt hist` reads the log to colour failed commands and to filter by
+//! directory and exit status. Lines that fail to parse are skipped, so a
+//! record cut short by a crash does not hide the rest of the log.
+
+use crate::{file_ops, platform};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// One finished command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandRecord {
+    pub command: String,
+    /// Working directory the command was started in
+    pub cwd: PathBuf,
+    /// Exit status; non-zero means the command failed
+    pub status: i32,
+    pub duration_ms: u64,
+    /// When the command finished, in seconds since the Unix epoch
+    pub time: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+}
+
+impl CommandRecord {
+    pub fn failed(&self) -> bool {
+        self.status != 0
+    }
+}
+
+/// What the log knows about one command line across all its runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandStats {
+    /// The most recent run
+    pub last: CommandRecord,
+    /// Every directory the command ran in, most recent first
+    pub dirs: Vec<PathBuf>,
+    pub runs: usize,
+    pub failures: usize,
+}
+
+fn log_file() -> Option<PathBuf> {
+    platform::state_dir().map(|dir| dir.join("commands.jsonl"))
+}
+
+/// Append `record` to the log
+pub fn append(record: &CommandRecord) -> io::Result<()> {
+    let path = log_file()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory for the command log"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(record).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    line.push('\n');
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    // Commands can contain secrets; keep the log private like a history file
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    // A single write keeps lines from concurrent shells whole
+    options.open(path)?.write_all(line.as_bytes())
+}
+
+fn parse(content: &str) -> Vec<CommandRecord> {
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Every logged command, oldest first; a missing log yields an empty list
+pub fn load() -> Vec<CommandRecord> {
+    log_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| parse(&content))
+        .unwrap_or_default()
+}
+
+/// Remove the records whose command satisfies `matches`, or only the most
+/// recent one when `last_only`. Returns how many were removed.
+pub fn remove(matches: impl Fn(&str) -> bool, last_only: bool) -> io::Result<usize> {
+    let Some(content) = log_file().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Ok(0);
+    };
+    let (kept, removed) = without_records(&content, matches, last_only);
+    if removed > 0 {
+        if let Some(path) = log_file() {
+            file_ops::replace_contents(&path, kept.as_bytes())?;
+        }
+    }
+    Ok(removed)
+}
+
+/// `content` without the matching records, and how many were dropped.
+/// Unparseable lines are kept.
+fn without_records(content: &str, matches: impl Fn(&str) -> bool, last_only: bool) -> (String, usize) {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut doomed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| {
+            serde_json::from_str::<CommandRecord>(line)
+                .map(|record| matches(&record.command))
+                .unwrap_or(false)
+        })
+        .map(|(index, _)| index)
+        .collect();
+    if last_only {
+        doomed = doomed.split_off(doomed.len().saturating_sub(1));
+    }
+    let kept = lines
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !doomed.contains(index))
+        .map(|(_, line)| format!("{}\n", line))
+        .collect();
+    (kept, doomed.len())
+}
+
+/// Group `records` (oldest first) by command line
+pub fn stats(records: Vec<CommandRecord>) -> HashMap<String, CommandStats> {
+    let mut stats: HashMap<String, CommandStats> = HashMap::new();
+    for record in records {
+        let failed = record.failed();
+        let entry = stats.entry(record.command.clone()).or_insert_with(|| CommandStats {
+            last: record.clone(),
+            dirs: Vec::new(),
+            runs: 0,
+            failures: 0,
+        });
+        entry.dirs.retain(|dir| dir != &record.cwd);
+        entry.dirs.insert(0, record.cwd.clone());
+        entry.runs += 1;
+        entry.failures += usize::from(failed);
+        entry.last = record;
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_stats() {
+        let content = concat!(
+            r#"{"command":"make","cwd":"/a","status":2,"duration_ms":40,"time":10,"shell":"bash"}"#, "\n",
+            r#"{"command":"ls","cwd":"/a","status":0,"duration_ms":3,"time":11}"#, "\n",
+            r#"{"command":"make","cwd":"/b","status":0,"dur"#, "\n",
+            r#"{"command":"make","cwd":"/b","status":0,"duration_ms":900,"time":12}"#, "\n",
+            r#"{"command":"make","cwd":"/a","status":1,"duration_ms":50,"time":13}"#, "\n",
+        );
+        let records = parse(content);
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].shell.as_deref(), Some("bash"));
+
+        let stats = stats(records);
+        let make = &stats["make"];
+        assert_eq!((make.runs, make.failures, make.last.time), (3, 2, 13));
+        assert_eq!(make.dirs, vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+        assert!(!stats["ls"].last.failed());
+    }
+
+    #[test]
+    fn test_without_records() {
+        let content = concat!(
+            r#"{"command":"ls","cwd":"/a","status":0,"duration_ms":1,"time":1}"#, "\n",
+            "garbage\n",
+            r#"{"command":"ls","cwd":"/b","status":0,"duration_ms":1,"time":2}"#, "\n",
+        );
+        let (kept, removed) = without_records(content, |command| command == "ls", true);
+        assert_eq!(removed, 1);
+        assert_eq!(kept.lines().count(), 2);
+        assert!(kept.ends_with("garbage\n"));
+        assert_eq!(without_records(content, |command| command == "ls", false).1, 2);
+    }
+}
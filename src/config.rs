@@ -0,0 +1,286 @@
+//! User configuration from `$XDG_CONFIG_HOME/terminal-tools/config.toml`
+//! (default `~/.config/terminal-tools/config.toml`).
+//!
+//! Every setting is optional; a missing file gives the built-in defaults. The
+//! top-level values apply to every tool and a `[tools.<name>]` table overrides
+//! them for one tool:
+//!
+//! ```toml
+//! editor = "nvim"              # command that opens files, with any arguments
+//! theme = "auto"               # auto, dark, light, solarized-dark, solarized-light
+//! graphics = "auto"            # images: auto, kitty, iterm2, sixel, blocks, ascii
+//! page_size = 10               # rows moved by PgDn/PgUp
+//! preview_lines = 200          # lines shown in file previews
+//! ignore = ["*.min.js", "dist"]
+//! protected = ["postgres"]     # processes kill and net ask twice about
+//!
+//! [colors]                     # replace single colors of the theme
+//! primary = "#268bd2"
+//! muted = "244"
+//!
+//! [tools.find]
+//! ignore = ["*.lock"]          # added to the global patterns
+//! preview_lines = 80
+//!
+//! [tools.hist]
+//! limit = 500                  # default for --limit
+//! ```
+//!
+//! Ignore patterns are matched against file and directory names, with `*`
+//! standing for any run of characters and `?` for one character; an ignored
+//! directory hides everything below it. Tool names are the subcommand names
+//! (`find`, `search`, `dir`, `hist`, `recent`, ...).
+//!
+//! The file is read once in `main`, which hands each tool its resolved
+//! [`Settings`]. Unknown keys are rejected so typos don't go unnoticed.
+
+use crate::platform;
+use crate::theme::Palette;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Rows moved by PgDn/PgUp unless configured
+pub const DEFAULT_PAGE_SIZE: usize = 10;
+
+/// Preview lines unless configured
+pub const DEFAULT_PREVIEW_LINES: usize = 200;
+
+/// Editors tried in order when none is configured; Windows, which seldom
+/// has any of them, then opens the file with its default application
+const FALLBACK_EDITORS: &[&str] = &["nvim", "vim", "nano", "code"];
+
+/// Settings that can be given globally or per tool
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolConfig {
+    pub editor: Option<String>,
+    pub theme: Option<String>,
+    pub graphics: Option<String>,
+    pub page_size: Option<usize>,
+    pub preview_lines: Option<usize>,
+    pub ignore: Vec<String>,
+    pub protected: Vec<String>,
+    pub limit: Option<usize>,
+}
+
+/// The whole configuration file: the global [`ToolConfig`] values and the
+/// per-tool tables
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub editor: Option<String>,
+    pub theme: Option<String>,
+    pub graphics: Option<String>,
+    pub page_size: Option<usize>,
+    pub preview_lines: Option<usize>,
+    pub ignore: Vec<String>,
+    pub protected: Vec<String>,
+    pub limit: Option<usize>,
+    /// Colors replacing those of the theme, for every tool
+    pub colors: Palette,
+    pub tools: HashMap<String, ToolConfig>,
+}
+
+/// Settings of one tool, with its overrides applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Configured editor command; unset means trying [`FALLBACK_EDITORS`]
+    pub editor: Option<String>,
+    pub theme: Option<String>,
+    /// How images are drawn; see [`crate::image_preview::configure`]
+    pub graphics: Option<String>,
+    pub page_size: usize,
+    pub preview_lines: usize,
+    pub ignore: Vec<String>,
+    /// Process names added to [`crate::process_info::protected_names`]
+    pub protected: Vec<String>,
+    /// Default for the tool's `--limit`, if it has one
+    pub limit: Option<usize>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Config::default().tool("")
+    }
+}
+
+pub fn config_file() -> Option<PathBuf> {
+    platform::config_dir().map(|dir| dir.join("config.toml"))
+}
+
+impl Config {
+    /// Read the configuration file; a missing file gives the defaults
+    pub fn load() -> io::Result<Config> {
+        let Some(content) = config_file().and_then(|path| fs::read_to_string(path).ok()) else {
+            return Ok(Config::default());
+        };
+        Config::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> io::Result<Config> {
+        let config: Config = toml::from_str(content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("config.toml: {}", e)))?;
+        if config.page_size == Some(0) || config.tools.values().any(|tool| tool.page_size == Some(0)) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "config.toml: page_size must be at least 1"));
+        }
+        Ok(config)
+    }
+
+    /// Settings for the tool `name`: its own table over the global values,
+    /// with both sets of ignore patterns and protected names
+    pub fn tool(&self, name: &str) -> Settings {
+        let tool = self.tools.get(name).cloned().unwrap_or_default();
+        Settings {
+            editor: tool.editor.or_else(|| self.editor.clone()),
+            theme: tool.theme.or_else(|| self.theme.clone()),
+            graphics: tool.graphics.or_else(|| self.graphics.clone()),
+            page_size: tool.page_size.or(self.page_size).unwrap_or(DEFAULT_PAGE_SIZE),
+            preview_lines: tool.preview_lines.or(self.preview_lines).unwrap_or(DEFAULT_PREVIEW_LINES),
+            ignore: self.ignore.iter().cloned().chain(tool.ignore).collect(),
+            protected: self.protected.iter().cloned().chain(tool.protected).collect(),
+            limit: tool.limit.or(self.limit),
+        }
+    }
+}
+
+impl Settings {
+    /// Whether a file or directory called `name` matches an ignore pattern
+    pub fn is_ignored(&self, name: &str) -> bool {
+        self.ignore.iter().any(|pattern| wildcard_match(pattern, name))
+    }
+
+    /// Commands that open `path` (at `line`, when given) in an editor, to be
+    /// tried in order until one starts
+    pub fn editor_commands(&self, path: &Path, line: Option<usize>) -> Vec<Command> {
+        match &self.editor {
+            Some(editor) => editor_command(editor, path, line).into_iter().collect(),
+            None => FALLBACK_EDITORS
+                .iter()
+                .filter_map(|editor| editor_command(editor, path, line))
+                .chain(cfg!(windows).then(|| platform::open_command(path)))
+                .collect(),
+        }
+    }
+}
+
+/// `editor` split into words with the file appended; VS Code takes the line
+/// as `--goto file:line`, terminal editors as `+line`
+fn editor_command(editor: &str, path: &Path, line: Option<usize>) -> Option<Command> {
+    let mut words = editor.split_whitespace();
+    let program = words.next()?;
+    let mut command = Command::new(program);
+    command.args(words);
+    let name = Path::new(program).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    match line {
+        Some(line) if name == "code" || name == "codium" => {
+            command.arg("--goto").arg(format!("{}:{}", path.display(), line));
+        }
+        Some(line) => {
+            command.arg(format!("+{}", line)).arg(path);
+        }
+        None => {
+            command.arg(path);
+        }
+    }
+    Some(command)
+}
+
+/// Match `name` against `pattern`, where `*` is any run of characters and
+/// `?` any single character
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was seen, and the name position it matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // Let the last `*` swallow one more character
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, n));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_settings() {
+        let config = Config::parse(
+            r#"
+            editor = "hx"
+            page_size = 20
+            ignore = ["dist"]
+            protected = ["postgres"]
+
+            [tools.find]
+            editor = "code --wait"
+            ignore = ["*.lock"]
+            preview_lines = 80
+
+            [tools.hist]
+            limit = 500
+            "#,
+        )
+        .unwrap();
+
+        let find = config.tool("find");
+        assert_eq!(find.editor.as_deref(), Some("code --wait"));
+        assert_eq!(find.page_size, 20);
+        assert_eq!(find.preview_lines, 80);
+        assert_eq!(find.ignore, vec!["dist", "*.lock"]);
+        assert_eq!(find.limit, None);
+        assert_eq!(find.protected, vec!["postgres"]);
+
+        let hist = config.tool("hist");
+        assert_eq!(hist.editor.as_deref(), Some("hx"));
+        assert_eq!(hist.preview_lines, DEFAULT_PREVIEW_LINES);
+        assert_eq!(hist.limit, Some(500));
+
+        assert_eq!(Config::parse("").unwrap().tool("find"), Settings::default());
+        assert!(Config::parse("pagesize = 5").is_err());
+        assert!(Config::parse("[tools.z]\npage_size = 0").is_err());
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("dist", "dist"));
+        assert!(!wildcard_match("dist", "distro"));
+        assert!(wildcard_match("*.min.js", "app.min.js"));
+        assert!(!wildcard_match("*.min.js", "app.js"));
+        assert!(wildcard_match("*test*", "my_test_file"));
+        assert!(wildcard_match("file?.txt", "file1.txt"));
+        assert!(!wildcard_match("file?.txt", "file.txt"));
+        assert!(wildcard_match("*", ""));
+    }
+
+    #[test]
+    fn test_editor_command() {
+        let path = Path::new("src/main.rs");
+        let args = |command: Command| command.get_args().map(|arg| arg.to_string_lossy().to_string()).collect::<Vec<_>>();
+
+        let command = editor_command("code --wait", path, Some(12)).unwrap();
+        assert_eq!(command.get_program(), "code");
+        assert_eq!(args(command), vec!["--wait", "--goto", "src/main.rs:12"]);
+        assert_eq!(args(editor_command("nvim", path, Some(3)).unwrap()), vec!["+3", "src/main.rs"]);
+        assert_eq!(args(editor_command("nano", path, None).unwrap()), vec!["src/main.rs"]);
+        assert!(editor_command("  ", path, None).is_none());
+    }
+}
@@ -0,0 +1,885 @@
+//! Line diffs for `tt diff` and the git tools.
+//!
+//! [`compare`] diffs two texts with Myers' algorithm and [`parse_unified`]
+//! reads the unified diffs git prints; both give [`FileDiff`]s, shown by
+//! one [`DiffView`] either unified or side by side. [`compare_dirs`] lists
+//! the files two trees differ in.
+
+use crate::plain::Record;
+use crate::text_width;
+use crate::tui_common::colors;
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    Frame,
+};
+use serde_json::Value;
+use std::{
+    collections::BTreeSet,
+    fs::{self, File},
+    io::{self, BufRead, BufReader},
+    iter,
+    path::{Path, PathBuf},
+};
+
+/// Lines of context around changes, as in `diff -u`
+pub const CONTEXT: usize = 3;
+
+/// Edit distance past which the shortest edit isn't searched for any more
+/// and the rest of the lines are replaced wholesale, which bounds time and
+/// memory for files that have little in common
+const MAX_COST: usize = 2048;
+
+/// Bytes looked at for a NUL when telling binary files from text
+const BINARY_CHECK: usize = 8000;
+
+/// Follows a last line without a line break, as in `diff -u`
+pub const NO_NEWLINE: &str = "\\ No newline at end of file";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// A line of a hunk, with its numbers in the old and the new file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: Kind,
+    pub old: Option<usize>,
+    pub new: Option<usize>,
+    /// Without the final `\n`, as `diff -u` writes it
+    pub text: String,
+    /// Whether this is the last line of its file and has no line break
+    pub no_newline: bool,
+}
+
+impl DiffLine {
+    /// The line as `diff -u` writes it
+    pub fn unified(&self) -> String {
+        let sign = match self.kind {
+            Kind::Context => ' ',
+            Kind::Added => '+',
+            Kind::Removed => '-',
+        };
+        format!("{}{}", sign, self.text)
+    }
+}
+
+/// Changed lines with the context around them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    /// Text after the second `@@` in git's diffs, such as the enclosing function
+    pub heading: String,
+    pub lines: Vec<DiffLine>,
+}
+
+impl Hunk {
+    /// `@@ -1,4 +1,5 @@`, as in unified diffs
+    pub fn header(&self) -> String {
+        let header = format!("@@ -{},{} +{},{} @@", self.old_start, self.old_len, self.new_start, self.new_len);
+        if self.heading.is_empty() {
+            header
+        } else {
+            format!("{} {}", header, self.heading)
+        }
+    }
+}
+
+/// The changes to one file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileDiff {
+    pub path: String,
+    /// What there is to say besides hunks: a new or deleted file, a rename,
+    /// a mode change
+    pub notes: Vec<String>,
+    /// No lines to compare, only that the contents differ
+    pub binary: bool,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FileDiff {
+    /// Whether nothing differs
+    pub fn is_empty(&self) -> bool {
+        !self.binary && self.notes.is_empty() && self.hunks.is_empty()
+    }
+
+    /// Lines added and removed
+    pub fn stats(&self) -> (usize, usize) {
+        let lines = self.hunks.iter().flat_map(|hunk| &hunk.lines);
+        lines.fold((0, 0), |(added, removed), line| match line.kind {
+            Kind::Added => (added + 1, removed),
+            Kind::Removed => (added, removed + 1),
+            Kind::Context => (added, removed),
+        })
+    }
+
+    /// The diff for `--plain` and `--json`: a record per line of the unified
+    /// diff, notes and hunk headers included, each labelled with the `old`
+    /// and `new` paths compared
+    pub fn records(&self, old: &str, new: &str) -> Vec<Record> {
+        let record = |old_line: Option<usize>, new_line: Option<usize>, text: String| vec![
+            ("old", Value::from(old)),
+            ("new", Value::from(new)),
+            ("old_line", Value::from(old_line)),
+            ("new_line", Value::from(new_line)),
+            ("text", Value::from(text)),
+        ];
+        let mut records: Vec<Record> = self.notes.iter().map(|note| record(None, None, note.clone())).collect();
+        for hunk in &self.hunks {
+            records.push(record(None, None, hunk.header()));
+            for line in &hunk.lines {
+                records.push(record(line.old, line.new, line.unified()));
+                if line.no_newline {
+                    records.push(record(None, None, NO_NEWLINE.to_string()));
+                }
+            }
+        }
+        records
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Hunks turning `old` into `new`, with `context` lines around each change.
+///
+/// Lines are compared with their line breaks, so a last line that gains or
+/// loses one, or a `\r\n` that becomes `\n`, counts as changed.
+pub fn compare(old: &str, new: &str, context: usize) -> Vec<Hunk> {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let (mut old_line, mut new_line) = (0, 0);
+    let lines = edits(&old_lines, &new_lines)
+        .into_iter()
+        .map(|edit| {
+            let (kind, old, new, text) = match edit {
+                Edit::Equal => {
+                    old_line += 1;
+                    new_line += 1;
+                    (Kind::Context, Some(old_line), Some(new_line), old_lines[old_line - 1])
+                }
+                Edit::Delete => {
+                    old_line += 1;
+                    (Kind::Removed, Some(old_line), None, old_lines[old_line - 1])
+                }
+                Edit::Insert => {
+                    new_line += 1;
+                    (Kind::Added, None, Some(new_line), new_lines[new_line - 1])
+                }
+            };
+            let (text, no_newline) = match text.strip_suffix('\n') {
+                Some(text) => (text, false),
+                None => (text, true),
+            };
+            DiffLine { kind, old, new, text: text.to_string(), no_newline }
+        })
+        .collect();
+    group(lines, context)
+}
+
+/// The edits turning `old` into `new`: the lines both start and end with
+/// are kept, and Myers' algorithm finds the fewest edits for the middle
+fn edits(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+    let middle = myers(old, new).unwrap_or_else(|| {
+        iter::repeat_n(Edit::Delete, old.len()).chain(iter::repeat_n(Edit::Insert, new.len())).collect()
+    });
+    let mut edits = vec![Edit::Equal; prefix];
+    edits.extend(middle);
+    edits.extend(iter::repeat_n(Edit::Equal, suffix));
+    edits
+}
+
+/// Shortest edit script from `old` to `new` ("An O(ND) Difference Algorithm
+/// and Its Variations", Myers 1986), or None when it costs over [`MAX_COST`]
+fn myers(old: &[&str], new: &[&str]) -> Option<Vec<Edit>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = old.len() + new.len();
+    // `v[offset + k]` is the furthest x reached on diagonal k = x - y
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    // The diagonals -d - 1 ..= d + 1 before each step d, to walk the path back
+    let mut trace = Vec::new();
+    for d in 0..=max.min(MAX_COST) as isize {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) { v[i + 1] } else { v[i - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+/// The edits along the path [`myers`] found, from the end back to the start
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let previous_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) { k + 1 } else { k - 1 };
+        let previous_x = at(previous_k);
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            edits.push(Edit::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(if x == previous_x { Edit::Insert } else { Edit::Delete });
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    edits.reverse();
+    edits
+}
+
+/// Changes in `lines` with `context` lines around them, joined into one
+/// hunk where their context touches
+fn group(lines: Vec<DiffLine>, context: usize) -> Vec<Hunk> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (index, _) in lines.iter().enumerate().filter(|(_, line)| line.kind != Kind::Context) {
+        let (start, end) = (index.saturating_sub(context), (index + context + 1).min(lines.len()));
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let count = |lines: &[DiffLine], side: fn(&DiffLine) -> Option<usize>| {
+                lines.iter().filter(|line| side(line).is_some()).count()
+            };
+            let (old_before, new_before) = (count(&lines[..start], |line| line.old), count(&lines[..start], |line| line.new));
+            let hunk_lines = lines[start..end].to_vec();
+            let (old_len, new_len) = (count(&hunk_lines, |line| line.old), count(&hunk_lines, |line| line.new));
+            // An empty side starts at the line before, as in `diff -u`
+            Hunk {
+                old_start: old_before + usize::from(old_len > 0),
+                old_len,
+                new_start: new_before + usize::from(new_len > 0),
+                new_len,
+                heading: String::new(),
+                lines: hunk_lines,
+            }
+        })
+        .collect()
+}
+
+/// The files of a unified diff, such as `git diff` prints
+pub fn parse_unified(text: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    // Numbers of the next old and new line, and the lines left in the hunk
+    let (mut old_line, mut new_line) = (0, 0);
+    let (mut old_left, mut new_left) = (0usize, 0usize);
+    for line in text.lines() {
+        if old_left > 0 || new_left > 0 {
+            let Some(hunk) = files.last_mut().and_then(|file| file.hunks.last_mut()) else {
+                break;
+            };
+            let (kind, text) = match line.as_bytes().first() {
+                Some(b'+') => (Kind::Added, &line[1..]),
+                Some(b'-') => (Kind::Removed, &line[1..]),
+                Some(b' ') => (Kind::Context, &line[1..]),
+                Some(b'\\') => {
+                    if let Some(last) = hunk.lines.last_mut() {
+                        last.no_newline = true;
+                    }
+                    continue;
+                }
+                // A blank context line that lost its space
+                _ => (Kind::Context, line),
+            };
+            let old = (kind != Kind::Added).then(|| {
+                old_line += 1;
+                old_left = old_left.saturating_sub(1);
+                old_line - 1
+            });
+            let new = (kind != Kind::Removed).then(|| {
+                new_line += 1;
+                new_left = new_left.saturating_sub(1);
+                new_line - 1
+            });
+            hunk.lines.push(DiffLine { kind, old, new, text: text.to_string(), no_newline: false });
+        } else if let Some(paths) = line.strip_prefix("diff --git ") {
+            let path = paths.split_once(" b/").map_or(paths, |(_, new)| new);
+            files.push(FileDiff { path: path.to_string(), ..FileDiff::default() });
+        } else if let Some(header) = line.strip_prefix("@@ -") {
+            let Some(hunk) = parse_hunk_header(header) else {
+                continue;
+            };
+            (old_line, new_line, old_left, new_left) = (hunk.old_start.max(1), hunk.new_start.max(1), hunk.old_len, hunk.new_len);
+            if files.is_empty() {
+                files.push(FileDiff::default());
+            }
+            if let Some(file) = files.last_mut() {
+                file.hunks.push(hunk);
+            }
+        } else if let Some(path) = line.strip_prefix("--- ").or_else(|| line.strip_prefix("+++ ")) {
+            // Plain `diff -u` output starts a file here rather than at `diff --git`
+            if line.starts_with("--- ") && !files.last().is_some_and(|file| file.hunks.is_empty()) {
+                files.push(FileDiff::default());
+            }
+            let path = path.split('\t').next().unwrap_or(path);
+            if let Some(file) = files.last_mut().filter(|_| path != "/dev/null") {
+                let path = path.strip_prefix("a/").or_else(|| path.strip_prefix("b/")).unwrap_or(path);
+                file.path = path.to_string();
+            }
+        } else if let Some(file) = files.last_mut() {
+            if !line.starts_with("index ") {
+                file.binary |= line.starts_with("Binary files ");
+                file.notes.push(line.to_string());
+            }
+        }
+    }
+    files
+}
+
+/// A hunk without lines from the header after `@@ -`: `1,4 +1,5 @@ heading`
+fn parse_hunk_header(header: &str) -> Option<Hunk> {
+    let (ranges, heading) = header.split_once(" @@").unwrap_or((header, ""));
+    let (old, new) = ranges.split_once(" +")?;
+    // A length left out is 1
+    let range = |range: &str| -> Option<(usize, usize)> {
+        let (start, len) = range.split_once(',').unwrap_or((range, "1"));
+        Some((start.parse().ok()?, len.parse().ok()?))
+    };
+    let ((old_start, old_len), (new_start, new_len)) = (range(old)?, range(new)?);
+    Some(Hunk { old_start, old_len, new_start, new_len, heading: heading.trim().to_string(), lines: Vec::new() })
+}
+
+/// Contents of a text file, or None for a binary one
+fn read_text(path: &Path) -> io::Result<Option<String>> {
+    let bytes = fs::read(path)?;
+    if bytes[..bytes.len().min(BINARY_CHECK)].contains(&0) {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+/// The diff of two files, `None` standing for a file that doesn't exist
+/// on that side; `path` is shown as its name
+pub fn compare_files(old: Option<&Path>, new: Option<&Path>, path: &str, context: usize) -> io::Result<FileDiff> {
+    let read = |path: Option<&Path>| path.map_or(Ok(Some(String::new())), read_text);
+    let mut diff = FileDiff { path: path.to_string(), ..FileDiff::default() };
+    match (old, new) {
+        (None, _) => diff.notes.push("new file".to_string()),
+        (_, None) => diff.notes.push("deleted file".to_string()),
+        _ => {}
+    }
+    match (read(old)?, read(new)?) {
+        (Some(old), Some(new)) => diff.hunks = compare(&old, &new, context),
+        _ if old.zip(new).is_some_and(|(old, new)| same_contents(old, new).unwrap_or(false)) => {}
+        _ => {
+            diff.binary = true;
+            diff.notes.push("Binary files differ".to_string());
+        }
+    }
+    Ok(diff)
+}
+
+/// How a file differs between two trees, by importance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Modified,
+    Added,
+    Removed,
+}
+
+impl Status {
+    pub fn label(self) -> &'static str {
+        match self {
+            Status::Modified => "modified",
+            Status::Added => "added",
+            Status::Removed => "removed",
+        }
+    }
+
+    /// Letter in front of the path, as in `git status --short`
+    pub fn letter(self) -> char {
+        match self {
+            Status::Modified => 'M',
+            Status::Added => 'A',
+            Status::Removed => 'D',
+        }
+    }
+}
+
+/// A file that differs between two trees
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Relative to the roots of both trees
+    pub path: PathBuf,
+    pub status: Status,
+}
+
+/// The files that differ between the trees `old` and `new`, in path order,
+/// without entering directories `skip` is true for; subdirectories that
+/// can't be read are left out
+pub fn compare_dirs(old: &Path, new: &Path, skip: impl Fn(&str) -> bool) -> io::Result<Vec<Change>> {
+    let (mut old_files, mut new_files) = (BTreeSet::new(), BTreeSet::new());
+    walk(old, Path::new(""), &skip, &mut old_files)?;
+    walk(new, Path::new(""), &skip, &mut new_files)?;
+    let paths: BTreeSet<&PathBuf> = old_files.iter().chain(&new_files).collect();
+    Ok(paths
+        .into_iter()
+        .filter_map(|path| {
+            let status = match (old_files.contains(path), new_files.contains(path)) {
+                (true, false) => Status::Removed,
+                (false, true) => Status::Added,
+                // Unreadable files count as changed
+                _ if same_contents(&old.join(path), &new.join(path)).unwrap_or(false) => return None,
+                _ => Status::Modified,
+            };
+            Some(Change { path: path.clone(), status })
+        })
+        .collect())
+}
+
+/// Add the files below `root/relative` to `files`, relative to `root`
+fn walk(root: &Path, relative: &Path, skip: &impl Fn(&str) -> bool, files: &mut BTreeSet<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(root.join(relative))?.flatten() {
+        let name = entry.file_name();
+        if skip(&name.to_string_lossy()) {
+            continue;
+        }
+        let path = relative.join(&name);
+        // Links count as files, so link loops can't trap the walk
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            let _ = walk(root, &path, skip, files);
+        } else {
+            files.insert(path);
+        }
+    }
+    Ok(())
+}
+
+/// Whether two files hold the same bytes
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    if fs::metadata(a)?.len() != fs::metadata(b)?.len() {
+        return Ok(false);
+    }
+    let (mut a, mut b) = (BufReader::new(File::open(a)?), BufReader::new(File::open(b)?));
+    loop {
+        let (chunk, other) = (a.fill_buf()?, b.fill_buf()?);
+        let len = chunk.len().min(other.len());
+        if len == 0 {
+            return Ok(chunk.is_empty() && other.is_empty());
+        }
+        if chunk[..len] != other[..len] {
+            return Ok(false);
+        }
+        a.consume(len);
+        b.consume(len);
+    }
+}
+
+/// Lines drawn for diffs, with the rows the hunks start at
+#[derive(Default)]
+struct Rendered {
+    lines: Vec<Line<'static>>,
+    hunks: Vec<usize>,
+}
+
+/// Columns for the line numbers of `files`
+fn number_width(files: &[FileDiff]) -> usize {
+    let lines = files.iter().flat_map(|file| &file.hunks).flat_map(|hunk| &hunk.lines);
+    let last = lines.filter_map(|line| line.old.max(line.new)).max().unwrap_or(0);
+    last.to_string().len()
+}
+
+fn number(line: Option<usize>, width: usize) -> String {
+    line.map_or_else(|| " ".repeat(width), |line| format!("{:>width$}", line))
+}
+
+fn kind_style(kind: Kind) -> Style {
+    match kind {
+        Kind::Context => Style::default().fg(colors::text()),
+        Kind::Added => Style::default().fg(colors::success()),
+        Kind::Removed => Style::default().fg(colors::danger()),
+    }
+}
+
+/// The path with the lines added and removed, then the notes
+fn push_file_header(rendered: &mut Rendered, file: &FileDiff) {
+    if !rendered.lines.is_empty() {
+        rendered.lines.push(Line::default());
+    }
+    let (added, removed) = file.stats();
+    let mut header = vec![Span::styled(file.path.clone(), Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD))];
+    if added + removed > 0 {
+        header.push(Span::styled(format!("  +{}", added), Style::default().fg(colors::success())));
+        header.push(Span::styled(format!(" -{}", removed), Style::default().fg(colors::danger())));
+    }
+    rendered.lines.push(Line::from(header));
+    for note in &file.notes {
+        rendered.lines.push(Line::styled(note.clone(), Style::default().fg(colors::muted())));
+    }
+}
+
+fn push_hunk_header(rendered: &mut Rendered, hunk: &Hunk) {
+    rendered.hunks.push(rendered.lines.len());
+    rendered.lines.push(Line::styled(hunk.header(), Style::default().fg(colors::secondary())));
+}
+
+fn render_unified(files: &[FileDiff]) -> Rendered {
+    let width = number_width(files);
+    let mut rendered = Rendered::default();
+    for file in files {
+        push_file_header(&mut rendered, file);
+        for hunk in &file.hunks {
+            push_hunk_header(&mut rendered, hunk);
+            for line in &hunk.lines {
+                let sign = match line.kind {
+                    Kind::Context => ' ',
+                    Kind::Added => '+',
+                    Kind::Removed => '-',
+                };
+                rendered.lines.push(Line::from(vec![
+                    Span::styled(format!("{} {} ", number(line.old, width), number(line.new, width)), Style::default().fg(colors::muted())),
+                    Span::styled(format!("{}{}", sign, text_width::expand_tabs(&line.text)), kind_style(line.kind)),
+                ]));
+                if line.no_newline {
+                    rendered.lines.push(Line::styled(format!("{} {}", " ".repeat(width * 2 + 1), NO_NEWLINE), Style::default().fg(colors::muted())));
+                }
+            }
+        }
+    }
+    rendered
+}
+
+/// The old file on the left and the new one on the right in `width`
+/// columns, removed lines next to the lines added in their place
+fn render_side_by_side(files: &[FileDiff], width: usize) -> Rendered {
+    let numbers = number_width(files);
+    let column = width.saturating_sub(1) / 2;
+    let text_columns = column.saturating_sub(numbers + 1);
+    let side = |line: Option<&DiffLine>, number_of: fn(&DiffLine) -> Option<usize>| match line {
+        Some(line) => vec![
+            Span::styled(format!("{} ", number(number_of(line), numbers)), Style::default().fg(colors::muted())),
+            Span::styled(text_width::pad(&text_width::truncate(&text_width::expand_tabs(&line.text), text_columns), text_columns), kind_style(line.kind)),
+        ],
+        None => vec![Span::raw(" ".repeat(column))],
+    };
+    let mut rendered = Rendered::default();
+    for file in files {
+        push_file_header(&mut rendered, file);
+        for hunk in &file.hunks {
+            push_hunk_header(&mut rendered, hunk);
+            let mut lines = hunk.lines.iter().peekable();
+            while let Some(line) = lines.next() {
+                let rows = if line.kind == Kind::Context {
+                    vec![(Some(line), Some(line))]
+                } else {
+                    // A run of removed lines, then the added lines that took their place
+                    let mut removed = Vec::new();
+                    let mut added = Vec::new();
+                    match line.kind {
+                        Kind::Removed => removed.push(line),
+                        _ => added.push(line),
+                    }
+                    while let Some(line) = lines.next_if(|next| next.kind == Kind::Removed && added.is_empty()) {
+                        removed.push(line);
+                    }
+                    while let Some(line) = lines.next_if(|next| next.kind == Kind::Added) {
+                        added.push(line);
+                    }
+                    (0..removed.len().max(added.len())).map(|row| (removed.get(row).copied(), added.get(row).copied())).collect()
+                };
+                for (old, new) in rows {
+                    let mut spans = side(old, |line| line.old);
+                    spans.push(Span::styled("│", Style::default().fg(colors::muted())));
+                    spans.extend(side(new, |line| line.new));
+                    rendered.lines.push(Line::from(spans));
+                }
+            }
+        }
+    }
+    rendered
+}
+
+/// `files` drawn as a unified diff, for panes that show more than a diff
+pub fn unified_lines(files: &[FileDiff]) -> Vec<Line<'static>> {
+    render_unified(files).lines
+}
+
+/// Scrollable diff shared by `tt diff` and `tt git`: unified, or side by
+/// side after `s`
+#[derive(Default)]
+pub struct DiffView {
+    files: Vec<FileDiff>,
+    side_by_side: bool,
+    /// First row shown
+    scroll: usize,
+    /// Rows shown at the last draw, for paging
+    height: usize,
+    /// Lines drawn for the current files, with the layout and width they
+    /// were drawn for
+    rendered: Option<(bool, usize, Rendered)>,
+    /// Hunk to scroll to once the other layout is drawn
+    pending_hunk: Option<usize>,
+}
+
+impl DiffView {
+    pub fn new(side_by_side: bool) -> Self {
+        DiffView { side_by_side, ..DiffView::default() }
+    }
+
+    /// Show `files` from the top
+    pub fn set_files(&mut self, files: Vec<FileDiff>) {
+        self.files = files;
+        self.scroll = 0;
+        self.rendered = None;
+    }
+
+    pub fn files(&self) -> &[FileDiff] {
+        &self.files
+    }
+
+    /// Lines added and removed in all files
+    pub fn stats(&self) -> (usize, usize) {
+        self.files.iter().map(FileDiff::stats).fold((0, 0), |(added, removed), (a, r)| (added + a, removed + r))
+    }
+
+    /// First row shown
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Start at row `scroll`, or the last page if the diff is shorter
+    pub fn set_scroll(&mut self, scroll: usize) {
+        self.scroll = scroll;
+    }
+
+    fn last_scroll(&self) -> usize {
+        let rows = self.rendered.as_ref().map_or(0, |(_, _, rendered)| rendered.lines.len());
+        rows.saturating_sub(self.height)
+    }
+
+    /// Scroll on a movement key, go to the next or previous hunk on `]` and
+    /// `[`, or switch layouts on `s`; returns whether `code` was one of them
+    pub fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let page = self.height.max(1);
+        let hunks = self.rendered.as_ref().map_or(&[][..], |(_, _, rendered)| &rendered.hunks);
+        let scroll = match code {
+            KeyCode::Up | KeyCode::Char('k') => self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll + 1,
+            KeyCode::PageUp => self.scroll.saturating_sub(page),
+            KeyCode::Char('b') if ctrl => self.scroll.saturating_sub(page),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll + page,
+            KeyCode::Char('f') if ctrl => self.scroll + page,
+            KeyCode::Char('u') if ctrl => self.scroll.saturating_sub(page / 2),
+            KeyCode::Char('d') if ctrl => self.scroll + page / 2,
+            KeyCode::Home | KeyCode::Char('g') => 0,
+            KeyCode::End | KeyCode::Char('G') => self.last_scroll(),
+            KeyCode::Char(']') => hunks.iter().copied().find(|&row| row > self.scroll).unwrap_or(self.scroll),
+            KeyCode::Char('[') => hunks.iter().copied().rev().find(|&row| row < self.scroll).unwrap_or(0),
+            KeyCode::Char('s') => {
+                // Keep to the same hunk in the other layout
+                let hunk = hunks.iter().rposition(|&row| row <= self.scroll);
+                self.side_by_side = !self.side_by_side;
+                self.rendered = None;
+                self.scroll = 0;
+                self.pending_hunk = hunk;
+                return true;
+            }
+            _ => return false,
+        };
+        self.scroll = scroll.min(self.last_scroll());
+        true
+    }
+
+    /// Draw the diff in `block`, or a note that there are no differences
+    pub fn render(&mut self, f: &mut Frame, area: Rect, block: Block) {
+        let inner = block.inner(area);
+        let width = inner.width as usize;
+        self.height = inner.height as usize;
+        if self.files.is_empty() {
+            let empty = Line::styled("No differences", Style::default().fg(colors::muted()));
+            f.render_widget(Paragraph::new(empty).block(block), area);
+            return;
+        }
+        let fresh = matches!(&self.rendered, Some((side_by_side, drawn, _)) if *side_by_side == self.side_by_side && (!*side_by_side || *drawn == width));
+        if !fresh {
+            let rendered = if self.side_by_side { render_side_by_side(&self.files, width) } else { render_unified(&self.files) };
+            if let Some(hunk) = self.pending_hunk.take() {
+                self.scroll = rendered.hunks.get(hunk).copied().unwrap_or(0);
+            }
+            self.rendered = Some((self.side_by_side, width, rendered));
+        }
+        self.scroll = self.scroll.min(self.last_scroll());
+        let Some((_, _, rendered)) = &self.rendered else {
+            return;
+        };
+        let visible: Vec<Line> = rendered.lines.iter().skip(self.scroll).take(self.height).cloned().collect();
+        let total = rendered.lines.len();
+        f.render_widget(Paragraph::new(visible).block(block), area);
+        if total > self.height {
+            let mut state = ScrollbarState::new(total - self.height).position(self.scroll).viewport_content_length(self.height);
+            f.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                    .begin_symbol(None)
+                    .end_symbol(None)
+                    .track_symbol(Some("│"))
+                    .track_style(Style::default().fg(colors::muted()))
+                    .thumb_style(Style::default().fg(colors::primary())),
+                Rect { y: inner.y, height: inner.height, ..area },
+                &mut state,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// Hunk lines as `diff -u` writes them
+    fn unified(hunks: &[Hunk]) -> Vec<String> {
+        let mut lines = Vec::new();
+        for hunk in hunks {
+            lines.push(hunk.header());
+            lines.extend(hunk.lines.iter().map(DiffLine::unified));
+        }
+        lines
+    }
+
+    #[test]
+    fn test_compare() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        assert_eq!(unified(&compare(old, new, 1)), vec!["@@ -1,3 +1,3 @@", " a", "-b", "+B", " c", "@@ -10,1 +10,2 @@", " j", "+k"]);
+        // Context that touches joins the hunks
+        assert_eq!(compare(old, new, 4).len(), 1);
+        assert_eq!(unified(&compare("", "x\n", 3)), vec!["@@ -0,0 +1,1 @@", "+x"]);
+        assert_eq!(unified(&compare("x\ny\n", "y\n", 3)), vec!["@@ -1,2 +1,1 @@", "-x", " y"]);
+        assert!(compare(old, old, 3).is_empty());
+        // Line breaks are compared too
+        let hunks = compare("a\nb", "a\nb\n", 3);
+        assert_eq!(unified(&hunks), vec!["@@ -1,2 +1,2 @@", " a", "-b", "+b"]);
+        assert_eq!(hunks[0].lines.iter().map(|line| line.no_newline).collect::<Vec<_>>(), vec![false, true, false]);
+        assert_eq!(unified(&compare("a\r\n", "a\n", 3)), vec!["@@ -1,1 +1,1 @@", "-a\r", "+a"]);
+        // The fewest edits, with line numbers on both sides
+        let hunks = compare("a\nb\nc\na\nb\nb\na\n", "c\nb\na\nb\na\nc\n", 0);
+        let (added, removed) = FileDiff { hunks: hunks.clone(), ..FileDiff::default() }.stats();
+        assert_eq!(added + removed, 5);
+        let first = &hunks[0].lines[0];
+        assert_eq!((first.kind, first.old, first.new), (Kind::Removed, Some(1), None));
+    }
+
+    #[test]
+    fn test_parse_unified() {
+        let text = "diff --git a/src/main.rs b/src/main.rs\n\
+                    index 1111111..2222222 100644\n\
+                    --- a/src/main.rs\n\
+                    +++ b/src/main.rs\n\
+                    @@ -3,3 +3,4 @@ fn main() {\n \
+                    keep\n\
+                    -old\n\
+                    +new\n\
+                    +more\n\
+                    \\ No newline at end of file\n \
+                    end\n\
+                    diff --git a/logo.png b/logo.png\n\
+                    new file mode 100644\n\
+                    Binary files /dev/null and b/logo.png differ\n";
+        let files = parse_unified(text);
+        assert_eq!(files.len(), 2);
+        assert_eq!((files[0].path.as_str(), files[0].stats()), ("src/main.rs", (2, 1)));
+        let hunk = &files[0].hunks[0];
+        assert_eq!(hunk.header(), "@@ -3,3 +3,4 @@ fn main() {");
+        let numbers: Vec<_> = hunk.lines.iter().map(|line| (line.old, line.new)).collect();
+        assert_eq!(numbers, vec![(Some(3), Some(3)), (Some(4), None), (None, Some(4)), (None, Some(5)), (Some(5), Some(6))]);
+        assert!(hunk.lines[3].no_newline && !hunk.lines[4].no_newline);
+        assert_eq!(files[1].path, "logo.png");
+        assert!(files[1].binary && files[1].hunks.is_empty());
+        assert_eq!(files[1].notes[0], "new file mode 100644");
+        // Plain `diff -u` output
+        let files = parse_unified("--- old.txt\t2024-01-01\n+++ new.txt\t2024-01-02\n@@ -1 +1 @@\n-a\n+b\n");
+        assert_eq!((files[0].path.as_str(), files[0].stats()), ("new.txt", (1, 1)));
+    }
+
+    #[test]
+    fn test_compare_dirs() {
+        let dir = env::temp_dir().join(format!("tt-diff-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let (old, new) = (dir.join("old"), dir.join("new"));
+        for root in [&old, &new] {
+            fs::create_dir_all(root.join("sub")).unwrap();
+            fs::write(root.join("same"), "x").unwrap();
+            fs::create_dir_all(root.join("skipped")).unwrap();
+        }
+        fs::write(old.join("sub/changed"), "a\n").unwrap();
+        fs::write(new.join("sub/changed"), "b\n").unwrap();
+        fs::write(old.join("gone"), "").unwrap();
+        fs::write(new.join("sub/fresh"), "").unwrap();
+        fs::write(new.join("skipped/inner"), "").unwrap();
+
+        let changes = compare_dirs(&old, &new, |name| name == "skipped").unwrap();
+        let found: Vec<_> = changes.iter().map(|change| (change.path.to_string_lossy().into_owned(), change.status)).collect();
+        assert_eq!(found, vec![
+            ("gone".to_string(), Status::Removed),
+            ("sub/changed".to_string(), Status::Modified),
+            ("sub/fresh".to_string(), Status::Added),
+        ]);
+        let diff = compare_files(None, Some(&new.join("sub/fresh")), "sub/fresh", CONTEXT).unwrap();
+        assert_eq!((diff.notes[0].as_str(), diff.hunks.len()), ("new file", 0));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_records_of_two_files() {
+        let dir = env::temp_dir().join(format!("tt-diff-records-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (old, new) = (dir.join("a"), dir.join("b"));
+        fs::write(&old, "one\ntwo\n").unwrap();
+        fs::write(&new, "one\nthree\nfour\n").unwrap();
+
+        let diff = compare_files(Some(&old), Some(&new), "b", CONTEXT).unwrap();
+        let records = diff.records("a", "b");
+        let field = |record: &Record, name: &str| record.iter().find(|(key, _)| *key == name).unwrap().1.clone();
+        // Every row names both files
+        assert!(records.iter().all(|record| field(record, "old") == "a" && field(record, "new") == "b"));
+        let rows: Vec<(Value, Value, Value)> = records
+            .iter()
+            .map(|record| (field(record, "old_line"), field(record, "new_line"), field(record, "text")))
+            .collect();
+        assert_eq!(rows, vec![
+            (Value::Null, Value::Null, Value::from("@@ -1,2 +1,3 @@")),
+            (Value::from(1), Value::from(1), Value::from(" one")),
+            (Value::from(2), Value::Null, Value::from("-two")),
+            (Value::Null, Value::from(2), Value::from("+three")),
+            (Value::Null, Value::from(3), Value::from("+four")),
+        ]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
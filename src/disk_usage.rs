@@ -0,0 +1,158 @@
+//! Recursive disk usage scanning for the `tt du` analyzer.
+//!
+//! A scan walks the tree once and keeps every directory in memory as a
+//! [`DuNode`], so drilling down and deleting never rescan. Sizes are apparent
+//! sizes (the same numbers [`crate::file_ops::tree_size`] reports); symlinks
+//! are counted as links and never followed, and unreadable directories are
+//! flagged rather than aborting the scan.
+//!
+//! Scans run on a background thread and report progress over a channel, the
+//! same way copy and move do in [`crate::file_ops`].
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+/// Files between progress reports, to keep the channel quiet on big trees
+const PROGRESS_INTERVAL: u64 = 1000;
+
+/// A file or directory with its total size
+#[derive(Debug, Clone)]
+pub struct DuNode {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// Total bytes, including everything below a directory
+    pub size: u64,
+    /// Number of files, including everything below a directory
+    pub files: u64,
+    /// The directory (or something below it) could not be read
+    pub error: bool,
+    /// Children sorted largest first
+    pub children: Vec<DuNode>,
+}
+
+/// Events sent by a background scan
+#[derive(Debug)]
+pub enum ScanEvent {
+    Progress { files: u64, bytes: u64 },
+    Finished(DuNode),
+}
+
+/// Start scanning `root` on a background thread
+pub fn spawn_scan(root: PathBuf) -> Receiver<ScanEvent> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut files = 0;
+        let mut bytes = 0;
+        let node = scan(&root, &mut |size| {
+            files += 1;
+            bytes += size;
+            if files % PROGRESS_INTERVAL == 0 {
+                let _ = tx.send(ScanEvent::Progress { files, bytes });
+            }
+        });
+        let _ = tx.send(ScanEvent::Finished(node));
+    });
+    rx
+}
+
+/// Scan `path` recursively, calling `on_file` with the size of every file
+pub fn scan(path: &Path, on_file: &mut dyn FnMut(u64)) -> DuNode {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    let mut node = DuNode {
+        name,
+        path: path.to_path_buf(),
+        is_dir: false,
+        size: 0,
+        files: 0,
+        error: false,
+        children: Vec::new(),
+    };
+
+    let Ok(metadata) = path.symlink_metadata() else {
+        node.error = true;
+        return node;
+    };
+    if !metadata.is_dir() {
+        node.size = metadata.len();
+        node.files = 1;
+        on_file(node.size);
+        return node;
+    }
+
+    node.is_dir = true;
+    match fs::read_dir(path) {
+        Ok(entries) => {
+            for entry in entries {
+                match entry {
+                    Ok(entry) => node.children.push(scan(&entry.path(), on_file)),
+                    Err(_) => node.error = true,
+                }
+            }
+        }
+        Err(_) => node.error = true,
+    }
+    node.size = node.children.iter().map(|child| child.size).sum();
+    node.files = node.children.iter().map(|child| child.files).sum();
+    node.error |= node.children.iter().any(|child| child.error);
+    node.children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    node
+}
+
+impl DuNode {
+    /// The node reached by following child indices from this one
+    pub fn descendant(&self, indices: &[usize]) -> Option<&DuNode> {
+        indices.iter().try_fold(self, |node, &index| node.children.get(index))
+    }
+
+    /// Remove the node at `indices`, subtracting its size and file count
+    /// from every ancestor
+    pub fn remove(&mut self, indices: &[usize]) -> Option<DuNode> {
+        let (&first, rest) = indices.split_first()?;
+        let removed = if rest.is_empty() {
+            (first < self.children.len()).then(|| self.children.remove(first))
+        } else {
+            self.children.get_mut(first)?.remove(rest)
+        }?;
+        self.size -= removed.size;
+        self.files -= removed.files;
+        Some(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_scan_and_remove() {
+        let dir = env::temp_dir().join(format!("tt-disk-usage-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("big/inner")).unwrap();
+        fs::write(dir.join("big/inner/a"), vec![0u8; 300]).unwrap();
+        fs::write(dir.join("big/b"), vec![0u8; 100]).unwrap();
+        fs::write(dir.join("small"), vec![0u8; 50]).unwrap();
+
+        let mut seen = 0;
+        let mut root = scan(&dir, &mut |_| seen += 1);
+        assert_eq!((root.size, root.files, seen), (450, 3, 3));
+        let names: Vec<&str> = root.children.iter().map(|child| child.name.as_str()).collect();
+        assert_eq!(names, vec!["big", "small"]);
+        assert_eq!(root.descendant(&[0, 0]).map(|node| node.size), Some(300));
+
+        let removed = root.remove(&[0, 0]).unwrap();
+        assert_eq!(removed.name, "inner");
+        assert_eq!((root.size, root.files), (150, 2));
+        assert_eq!(root.children[0].size, 100);
+        assert!(root.remove(&[5]).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+//! Recently opened files remembered by editors, for `tt recent`.
+//!
+//! Besides tt's own MRU list ([`crate::mru`]) the recent-files browser reads
+//! the histories editors keep:
+//!
+//! - Neovim: `v:oldfiles`, printed by `nvim --headless` (its ShaDa file is
+//!   binary, so Neovim reads it for us)
+//! - Vim: the `> path` file marks in `~/.viminfo`
+//! - VS Code (and VSCodium): the recently opened list in the global
+//!   `storage.json`
+//!
+//! Every list is newest first. [`merge`] interleaves them by position so each
+//! source's most recent files come first, and a file listed by several
+//! sources appears once with all of their badges.
+
+use crate::{file_ops, help_text, platform};
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    process::Command,
+};
+
+/// Where a recent file was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Source {
+    Tt,
+    Neovim,
+    Vim,
+    VsCode,
+}
+
+impl Source {
+    pub const ALL: [Source; 4] = [Source::Tt, Source::Neovim, Source::Vim, Source::VsCode];
+
+    /// Short label shown next to a file
+    pub fn badge(self) -> &'static str {
+        match self {
+            Source::Tt => "tt",
+            Source::Neovim => "nvim",
+            Source::Vim => "vim",
+            Source::VsCode => "code",
+        }
+    }
+
+    /// The files this source remembers, newest first
+    pub fn load(self) -> Vec<PathBuf> {
+        match self {
+            Source::Tt => crate::mru::load(),
+            Source::Neovim => neovim_oldfiles(),
+            Source::Vim => home()
+                .and_then(|home| fs::read_to_string(home.join(".viminfo")).ok())
+                .map(|content| parse_viminfo(&content))
+                .unwrap_or_default(),
+            Source::VsCode => vscode_storage_files()
+                .iter()
+                .filter_map(|file| fs::read_to_string(file).ok())
+                .flat_map(|content| parse_vscode_storage(&content))
+                .collect(),
+        }
+    }
+}
+
+fn home() -> Option<PathBuf> {
+    platform::home_dir()
+}
+
+/// Expand a leading `~/`
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn neovim_oldfiles() -> Vec<PathBuf> {
+    let mut command = Command::new("nvim");
+    command.args([
+        "--headless",
+        "-c",
+        "lua io.stdout:write(table.concat(vim.v.oldfiles, '\\n') .. '\\n')",
+        "-c",
+        "qa!",
+    ]);
+    // Anything that is not an absolute path is a startup message
+    help_text::output_with_timeout(command, "nvim", help_text::TIMEOUT)
+        .map(|output| {
+            output
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with('/'))
+                .map(PathBuf::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// File marks (`> path` lines) from a viminfo file
+pub fn parse_viminfo(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .filter_map(|line| line.strip_prefix('>'))
+        .map(|path| expand_home(path.trim()))
+        .collect()
+}
+
+/// Possible `storage.json` files of VS Code and its builds
+fn vscode_storage_files() -> Vec<PathBuf> {
+    let Some(home) = home() else {
+        return Vec::new();
+    };
+    let config = platform::config_home().unwrap_or_else(|| home.join(".config"));
+    let bases = [config, home.join("Library/Application Support")];
+    bases
+        .iter()
+        .flat_map(|base| ["Code", "Code - Insiders", "VSCodium"].map(|app| base.join(app)))
+        .map(|dir| dir.join("User/globalStorage/storage.json"))
+        .filter(|file| file.is_file())
+        .collect()
+}
+
+/// Files (not folders) from the recently opened list in VS Code's
+/// `storage.json`
+pub fn parse_vscode_storage(content: &str) -> Vec<PathBuf> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    let list = &json["openedPathsList"];
+    // Newer versions keep `entries`, older ones `files2` (or `files`)
+    let uris: Vec<&str> = match list["entries"].as_array() {
+        Some(entries) => entries.iter().filter_map(|entry| entry["fileUri"].as_str()).collect(),
+        None => ["files2", "files"]
+            .iter()
+            .filter_map(|key| list[key].as_array())
+            .flatten()
+            .filter_map(|uri| uri.as_str())
+            .collect(),
+    };
+    uris.into_iter()
+        .filter_map(|uri| uri.strip_prefix("file://"))
+        .map(|path| PathBuf::from(file_ops::percent_decode(path)))
+        .collect()
+}
+
+/// Interleave newest-first lists, one file from each in turn, listing each
+/// path once with every source that knows it
+pub fn merge(lists: Vec<(Source, Vec<PathBuf>)>) -> Vec<(PathBuf, Vec<Source>)> {
+    let mut merged: Vec<(PathBuf, Vec<Source>)> = Vec::new();
+    let mut positions: HashMap<PathBuf, usize> = HashMap::new();
+    let longest = lists.iter().map(|(_, paths)| paths.len()).max().unwrap_or(0);
+    for index in 0..longest {
+        for (source, paths) in &lists {
+            let Some(path) = paths.get(index) else {
+                continue;
+            };
+            match positions.get(path) {
+                Some(&position) => {
+                    let sources = &mut merged[position].1;
+                    if !sources.contains(source) {
+                        sources.push(*source);
+                    }
+                }
+                None => {
+                    positions.insert(path.clone(), merged.len());
+                    merged.push((path.clone(), vec![*source]));
+                }
+            }
+        }
+    }
+    for (_, sources) in &mut merged {
+        sources.sort();
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_viminfo() {
+        let content = "# File marks:\n'0  12  0  ~/notes.md\n|4,48,12,0,1700000000,\"~/notes.md\"\n\n# History of marks within files (newest to oldest):\n\n> /etc/hosts\n\t*\t1700000000\t0\n\"\t1\t0\n\n> /tmp/a b.txt\n";
+        assert_eq!(parse_viminfo(content), vec![PathBuf::from("/etc/hosts"), PathBuf::from("/tmp/a b.txt")]);
+    }
+
+    #[test]
+    fn test_parse_vscode_storage() {
+        let content = r#"{"openedPathsList":{"entries":[{"folderUri":"file:///home/me/project"},{"fileUri":"file:///home/me/my%20notes.md"},{"fileUri":"vscode-remote://ssh/x"}]}}"#;
+        assert_eq!(parse_vscode_storage(content), vec![PathBuf::from("/home/me/my notes.md")]);
+        let old = r#"{"openedPathsList":{"workspaces3":[],"files2":["file:///etc/hosts"]}}"#;
+        assert_eq!(parse_vscode_storage(old), vec![PathBuf::from("/etc/hosts")]);
+        assert!(parse_vscode_storage("not json").is_empty());
+    }
+
+    #[test]
+    fn test_merge() {
+        let path = |p: &str| PathBuf::from(p);
+        let merged = merge(vec![
+            (Source::Tt, vec![path("/a"), path("/b"), path("/c")]),
+            (Source::Vim, vec![path("/b"), path("/d")]),
+        ]);
+        assert_eq!(
+            merged,
+            vec![
+                (path("/a"), vec![Source::Tt]),
+                (path("/b"), vec![Source::Tt, Source::Vim]),
+                (path("/d"), vec![Source::Vim]),
+                (path("/c"), vec![Source::Tt]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,546 @@
+//! Environment variable helpers for `tt env`.
+//!
+//! A child process cannot change its parent shell's environment, so `tt env`
+//! hands changes back as shell statements instead: with `--apply` it prints
+//! them on exit for the shell to evaluate,
+//!
+//! ```bash
+//! eval "$(tt env --apply)"          # bash / zsh
+//! tt env --apply fish | source      # fish
+//! ```
+//!
+//! or `tte` from `tt init` does it for you. Values are single-quoted, so
+//! nothing in them is expanded when the statements are evaluated.
+//!
+//! `PATH`-style values are split into their entries for inspection: each
+//! entry is checked for existence and repeated entries point back at the
+//! first occurrence, which is the one the shell actually uses.
+//!
+//! The grouped view sorts variables into `Shell` (session basics such as
+//! `HOME`, `TERM` and the history settings), `Locale` (`LANG`, `LC_*`), one
+//! group per name prefix shared by several variables (`XDG_*`, `CARGO_*`,
+//! ...) and `Other` for the rest.
+//!
+//! `tt env --file .env` reads a dotenv file: `KEY=value` lines with optional
+//! `export`, `#` comments, single-quoted values taken literally and
+//! double-quoted values with `\n`-style escapes, both of which may span
+//! lines. `$VAR` references are not expanded.
+//!
+//! Values of variables named like credentials (`GITHUB_TOKEN`,
+//! `AWS_SECRET_ACCESS_KEY`, `DB_PASSWORD`, ...) are masked until revealed, so
+//! screen-sharing with `tt env` open does not leak them. Names ending in
+//! `_PATH`, `_FILE` or `_DIR` point at a credential rather than hold one and
+//! are shown as is.
+//!
+//! "Where is this set?" searches the usual startup files (`~/.profile`,
+//! `~/.bashrc`, `~/.zshrc`, fish's `config.fish`, `~/.pam_environment`,
+//! systemd's `environment.d`, `/etc/environment`, `/etc/profile.d`, ...) for
+//! lines assigning the variable: `KEY=`, `KEY+=`, `export KEY`,
+//! `set -gx KEY`, `setenv KEY` and pam's `KEY DEFAULT=`. Commented-out lines
+//! are ignored.
+//!
+//! `tt env --snapshot NAME` saves the environment as a JSON object to
+//! `$XDG_STATE_HOME/terminal-tools/env/NAME.json` (private, since values may hold
+//! secrets) and `tt env --diff NAME` compares the current environment with it,
+//! e.g. before and after sourcing a script or activating a virtualenv.
+
+use crate::cli::InitShell;
+use crate::platform;
+use crate::shell_history::Shell;
+use regex::Regex;
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Separator between the entries of `PATH`-style values on this platform
+pub const PATH_SEPARATOR: char = if cfg!(windows) { ';' } else { ':' };
+
+/// Syntax of the statements printed for the parent shell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    /// `export KEY='value'` / `unset KEY` for bash, zsh and sh
+    Posix,
+    /// `set -gx KEY 'value'` / `set -e KEY`
+    Fish,
+}
+
+impl Syntax {
+    /// Syntax for `shell`, or for the shell in `$SHELL` when not given
+    pub fn for_shell(shell: Option<InitShell>) -> Self {
+        let fish = match shell {
+            Some(shell) => shell == InitShell::Fish,
+            None => Shell::detect() == Some(Shell::Fish),
+        };
+        if fish {
+            Syntax::Fish
+        } else {
+            Syntax::Posix
+        }
+    }
+
+    /// Statement setting `key` to `value`
+    pub fn set(self, key: &str, value: &str) -> String {
+        match self {
+            Syntax::Posix => format!("export {}='{}'", key, value.replace('\'', r"'\''")),
+            Syntax::Fish => format!("set -gx {} '{}'", key, value.replace('\\', r"\\").replace('\'', r"\'")),
+        }
+    }
+
+    /// Statement removing `key` from the environment
+    pub fn unset(self, key: &str) -> String {
+        match self {
+            Syntax::Posix => format!("unset {}", key),
+            Syntax::Fish => format!("set -e {}", key),
+        }
+    }
+}
+
+/// Whether `name` can be exported by a shell: a letter or `_` followed by
+/// letters, digits and `_`
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Variables describing the shell session itself
+const SHELL_VARS: &[&str] = &[
+    "SHELL", "SHLVL", "TERM", "COLORTERM", "PWD", "OLDPWD", "HOME", "USER", "LOGNAME",
+    "EDITOR", "VISUAL", "PAGER", "PS1", "PS2", "PROMPT_COMMAND", "HISTFILE", "HISTSIZE",
+    "HISTFILESIZE", "HISTCONTROL", "HISTTIMEFORMAT", "_",
+];
+
+/// Prefixes that get their own group even for a single variable
+const KNOWN_PREFIXES: &[&str] = &["XDG", "SSH"];
+
+/// Group `keys` for the grouped view: `(heading, indices into keys)` in
+/// display order, each group keeping the order of `keys`
+pub fn group(keys: &[&str]) -> Vec<(String, Vec<usize>)> {
+    fn prefix(key: &str) -> Option<&str> {
+        key.split_once('_').map(|(prefix, _)| prefix).filter(|prefix| !prefix.is_empty())
+    }
+    let fixed = |key: &str| -> Option<&'static str> {
+        if SHELL_VARS.contains(&key) {
+            Some("Shell")
+        } else if key == "LANG" || key == "LANGUAGE" || key.starts_with("LC_") {
+            Some("Locale")
+        } else {
+            None
+        }
+    };
+
+    let mut prefix_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for key in keys.iter().filter(|key| fixed(key).is_none()) {
+        if let Some(prefix) = prefix(key) {
+            *prefix_counts.entry(prefix).or_default() += 1;
+        }
+    }
+
+    let mut fixed_groups: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    let mut prefix_groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut other = Vec::new();
+    for (index, key) in keys.iter().enumerate() {
+        if let Some(name) = fixed(key) {
+            fixed_groups.entry(name).or_default().push(index);
+            continue;
+        }
+        match prefix(key) {
+            Some(prefix) if prefix_counts[prefix] >= 2 || KNOWN_PREFIXES.contains(&prefix) => {
+                prefix_groups.entry(format!("{}_*", prefix)).or_default().push(index);
+            }
+            _ => other.push(index),
+        }
+    }
+
+    let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+    for name in ["Shell", "Locale"] {
+        if let Some(indices) = fixed_groups.remove(name) {
+            groups.push((name.to_string(), indices));
+        }
+    }
+    groups.extend(prefix_groups);
+    if !other.is_empty() {
+        groups.push(("Other".to_string(), other));
+    }
+    groups
+}
+
+/// Whether `key` names a variable that likely holds a credential
+pub fn is_secret(key: &str) -> bool {
+    const MARKERS: &[&str] = &["SECRET", "TOKEN", "PASSWORD", "PASSWD", "PASSPHRASE", "CREDENTIAL", "APIKEY"];
+    let key = key.to_ascii_uppercase();
+    let words: Vec<&str> = key.split('_').collect();
+    if matches!(words.last(), Some(&("PATH" | "FILE" | "DIR"))) {
+        return false;
+    }
+    words
+        .iter()
+        .any(|word| *word == "KEY" || *word == "PASS" || MARKERS.iter().any(|marker| word.contains(marker)))
+}
+
+/// Placeholder shown instead of a secret value
+pub fn mask(value: &str) -> String {
+    format!("•••••• ({} chars)", value.chars().count())
+}
+
+/// Variables of a dotenv file in file order; malformed lines are skipped
+/// and a repeated key appears once per assignment
+pub fn parse_dotenv(content: &str) -> Vec<(String, String)> {
+    let mut vars = Vec::new();
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, rest)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if !is_valid_name(key) {
+            continue;
+        }
+        let rest = rest.trim_start();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut raw = rest[1..].to_string();
+                while closing_quote(&raw, quote).is_none() {
+                    let Some(next) = lines.next() else {
+                        break;
+                    };
+                    raw.push('\n');
+                    raw.push_str(next);
+                }
+                let raw = &raw[..closing_quote(&raw, quote).unwrap_or(raw.len())];
+                if quote == '"' {
+                    unescape(raw)
+                } else {
+                    raw.to_string()
+                }
+            }
+            // Unquoted values end at a comment
+            _ => rest.split(" #").next().unwrap_or_default().trim_end().to_string(),
+        };
+        vars.push((key.to_string(), value));
+    }
+    vars
+}
+
+/// Byte index of the quote closing a value opened with `quote`
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote == '"' {
+            escaped = true;
+        } else if c == quote {
+            return Some(index);
+        }
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(c @ ('\\' | '"' | '$')) => unescaped.push(c),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+fn snapshot_dir() -> io::Result<PathBuf> {
+    platform::state_dir()
+        .map(|dir| dir.join("env"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory for snapshots"))
+}
+
+fn snapshot_file(name: &str) -> io::Result<PathBuf> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid snapshot name '{}'", name)));
+    }
+    Ok(snapshot_dir()?.join(format!("{}.json", name)))
+}
+
+/// Save `vars` as the snapshot `name`, replacing an older one; returns its path
+pub fn save_snapshot(name: &str, vars: &BTreeMap<String, String>) -> io::Result<PathBuf> {
+    let path = snapshot_file(name)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(vars).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut options = OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(&path)?.write_all(json.as_bytes())?;
+    Ok(path)
+}
+
+/// Load the snapshot `name`; the error for a missing one lists the saved names
+pub fn load_snapshot(name: &str) -> io::Result<BTreeMap<String, String>> {
+    let path = snapshot_file(name)?;
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let saved = snapshot_names();
+            let hint = if saved.is_empty() {
+                "create one with tt env --snapshot NAME".to_string()
+            } else {
+                format!("saved: {}", saved.join(", "))
+            };
+            return Err(io::Error::new(e.kind(), format!("No snapshot '{}' ({})", name, hint)));
+        }
+        Err(e) => return Err(e),
+    };
+    serde_json::from_str(&content)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+}
+
+/// Names of the saved snapshots, sorted
+pub fn snapshot_names() -> Vec<String> {
+    let Ok(dir) = snapshot_dir() else {
+        return Vec::new();
+    };
+    files_in(&dir, "json")
+        .iter()
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect()
+}
+
+/// A startup file line that sets a variable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub path: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    pub text: String,
+}
+
+/// Shell startup and environment files that exist on this system, user
+/// files first
+pub fn startup_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Some(home) = platform::home_dir() {
+        let zdotdir = env::var_os("ZDOTDIR").map(PathBuf::from).unwrap_or_else(|| home.clone());
+        for name in [".profile", ".bash_profile", ".bash_login", ".bashrc", ".pam_environment"] {
+            files.push(home.join(name));
+        }
+        for name in [".zshenv", ".zprofile", ".zshrc", ".zlogin"] {
+            files.push(zdotdir.join(name));
+        }
+        let config = platform::config_home().unwrap_or_else(|| home.join(".config"));
+        files.push(config.join("fish/config.fish"));
+        files.extend(files_in(&config.join("fish/conf.d"), "fish"));
+        files.extend(files_in(&config.join("environment.d"), "conf"));
+    }
+    for path in ["/etc/environment", "/etc/profile", "/etc/bash.bashrc", "/etc/zshenv", "/etc/zsh/zshenv"] {
+        files.push(PathBuf::from(path));
+    }
+    files.extend(files_in(Path::new("/etc/profile.d"), "sh"));
+    files.extend(files_in(Path::new("/etc/environment.d"), "conf"));
+    files.retain(|path| path.is_file());
+    files
+}
+
+/// Files in `dir` with the extension `extension`, by name
+fn files_in(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Lines of the startup files that set `key`, and how many files were searched
+pub fn where_set(key: &str) -> (Vec<Assignment>, usize) {
+    let files = startup_files();
+    let mut assignments = Vec::new();
+    for path in &files {
+        // Unreadable files (permissions, binary content) are skipped
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        for (line, text) in assignment_lines(&content, key) {
+            assignments.push(Assignment { path: path.clone(), line, text: text.trim().to_string() });
+        }
+    }
+    (assignments, files.len())
+}
+
+/// 1-based numbers and text of the lines of `content` that set `key`
+fn assignment_lines<'a>(content: &'a str, key: &str) -> Vec<(usize, &'a str)> {
+    let pattern = format!(
+        r"(?:^|[^\w$]){key}\+?=|^\s*(?:export|set|setenv|declare|typeset)\s+(?:-\S+\s+)*{key}(?:\s|$)|^\s*{key}\s+(?:DEFAULT|OVERRIDE)=",
+        key = regex::escape(key)
+    );
+    let Ok(regex) = Regex::new(&pattern) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim_start().starts_with('#') && regex.is_match(line))
+        .map(|(index, line)| (index + 1, line))
+        .collect()
+}
+
+/// One entry of a `PATH`-style value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathEntry {
+    /// The entry as written; empty means the current directory
+    pub path: String,
+    pub exists: bool,
+    /// Index of the earlier identical entry this one repeats
+    pub duplicate_of: Option<usize>,
+}
+
+/// Whether `key`'s value is a list of paths: named like one (`PATH`,
+/// `MANPATH`, `XDG_DATA_DIRS`, ...) or made of at least two entries that
+/// all look like paths
+pub fn is_path_list(key: &str, value: &str) -> bool {
+    let entries: Vec<&str> = value.split(PATH_SEPARATOR).filter(|entry| !entry.is_empty()).collect();
+    let named = key.ends_with("PATH") || key.ends_with("_DIRS");
+    let min = if named { 1 } else { 2 };
+    entries.len() >= min && entries.iter().all(|entry| looks_like_path(entry))
+}
+
+fn looks_like_path(entry: &str) -> bool {
+    let bytes = entry.as_bytes();
+    let drive = bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    entry.starts_with(['/', '~', '.', '\\']) || (cfg!(windows) && drive)
+}
+
+/// Split a `PATH`-style value into entries, checking each with `exists`
+pub fn path_entries(value: &str, exists: impl Fn(&Path) -> bool) -> Vec<PathEntry> {
+    let mut entries: Vec<PathEntry> = Vec::new();
+    for path in value.split(PATH_SEPARATOR) {
+        let duplicate_of = entries.iter().position(|entry| entry.path == path);
+        let exists = path.is_empty() || exists(Path::new(path));
+        entries.push(PathEntry { path: path.to_string(), exists, duplicate_of });
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statements() {
+        assert_eq!(Syntax::Posix.set("GREETING", "it's $HOME"), r"export GREETING='it'\''s $HOME'");
+        assert_eq!(Syntax::Fish.set("GREETING", r"it's a\b"), r"set -gx GREETING 'it\'s a\\b'");
+        assert_eq!(Syntax::Posix.unset("OLD"), "unset OLD");
+        assert_eq!(Syntax::Fish.unset("OLD"), "set -e OLD");
+
+        assert!(is_valid_name("_PATH2"));
+        assert!(!is_valid_name("2PATH"));
+        assert!(!is_valid_name("MY-VAR"));
+        assert!(!is_valid_name(""));
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = concat!(
+            "# database\n",
+            "export DB_URL=postgres://localhost/app # local\n",
+            "\n",
+            "GREETING='hello $USER'\n",
+            "KEY=\"line one\\n\\\"two\\\"\"\n",
+            "CERT=\"-----BEGIN\n",
+            "abc\n",
+            "-----END\"\n",
+            "not a var\n",
+            "2BAD=x\n",
+            "EMPTY=\n",
+        );
+        assert_eq!(parse_dotenv(content), vec![
+            ("DB_URL".to_string(), "postgres://localhost/app".to_string()),
+            ("GREETING".to_string(), "hello $USER".to_string()),
+            ("KEY".to_string(), "line one\n\"two\"".to_string()),
+            ("CERT".to_string(), "-----BEGIN\nabc\n-----END".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ]);
+    }
+
+    #[test]
+    fn test_is_secret() {
+        for key in ["GITHUB_TOKEN", "AWS_SECRET_ACCESS_KEY", "DB_PASSWORD", "OPENAI_API_KEY", "npm_config_authToken"] {
+            assert!(is_secret(key), "{}", key);
+        }
+        for key in ["PATH", "PWD", "KEYMAP", "SSH_AUTH_SOCK", "GOOGLE_CREDENTIALS_FILE", "SSH_KEY_PATH"] {
+            assert!(!is_secret(key), "{}", key);
+        }
+        assert_eq!(mask("hunter2"), "•••••• (7 chars)");
+    }
+
+    #[test]
+    fn test_assignment_lines() {
+        let content = concat!(
+            "export PATH=\"$HOME/bin:$PATH\"\n",
+            "# export PATH=/old\n",
+            "echo $PATH\n",
+            "MANPATH=/usr/share/man\n",
+            "set -gx PATH $PATH ~/.cargo/bin\n",
+            "PATH DEFAULT=/usr/bin\n",
+            "PATH+=:/opt/bin\n",
+        );
+        let lines: Vec<usize> = assignment_lines(content, "PATH").into_iter().map(|(line, _)| line).collect();
+        assert_eq!(lines, vec![1, 5, 6, 7]);
+        assert_eq!(assignment_lines("export EDITOR=vim", "EDITOR"), vec![(1, "export EDITOR=vim")]);
+    }
+
+    #[test]
+    fn test_group() {
+        let keys = ["CARGO_HOME", "CARGO_TARGET_DIR", "EDITOR", "LANG", "LC_ALL", "NODE_ENV", "SSH_AUTH_SOCK", "TZ"];
+        let groups = group(&keys);
+        let names: Vec<(&str, &[usize])> = groups.iter().map(|(name, indices)| (name.as_str(), indices.as_slice())).collect();
+        assert_eq!(names, vec![
+            ("Shell", &[2][..]),
+            ("Locale", &[3, 4][..]),
+            ("CARGO_*", &[0, 1][..]),
+            ("SSH_*", &[6][..]),
+            ("Other", &[5, 7][..]),
+        ]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_path_entries() {
+        assert!(is_path_list("PATH", "/usr/bin"));
+        assert!(is_path_list("PYTHONSTARTUP_LIST", "/a:~/b"));
+        assert!(!is_path_list("URL", "http://example.com:80"));
+        assert!(!is_path_list("GREETING", "hello"));
+
+        let entries = path_entries("/usr/bin:/nope:/usr/bin:", |path| path != Path::new("/nope"));
+        assert_eq!(entries.len(), 4);
+        assert!(!entries[1].exists);
+        assert_eq!(entries[2].duplicate_of, Some(0));
+        assert_eq!((entries[3].path.as_str(), entries[3].exists), ("", true));
+    }
+}
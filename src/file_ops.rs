@@ -0,0 +1,773 @@
+//! File management operations used by the explorer.
+//!
+//! Copy and move run as [`crate::jobs`] jobs and report progress over a
+//! channel so the TUI stays responsive during large transfers, stopping
+//! between chunks when cancelled. Rename, create, delete and trash are
+//! renames or small writes and run inline; trash never copies, see below.
+//!
+//! ## Destination Rules
+//!
+//! - If the destination is an existing directory, sources are placed inside it
+//! - Otherwise the destination is used as the new path (single source only)
+//! - Copying onto the source itself produces `name copy.ext`, `name copy 2.ext`, ...
+//! - Existing files are never overwritten; the operation fails instead
+//!
+//! ## Permissions
+//!
+//! [`mode_bits`] and [`set_mode`] read and write Unix permission bits; other
+//! platforms only expose the read-only flag, which is mapped to `0o444` /
+//! `0o644`. [`chown`] shells out to `chown(1)` so ownership changes follow the
+//! system's own permission rules and error messages.
+//!
+//! ## Trash
+//!
+//! [`trash`] follows the freedesktop.org Trash specification: items are moved
+//! to `$XDG_DATA_HOME/Trash/files` with a matching `.trashinfo` record in
+//! `Trash/info` so desktop file managers can restore them. Items on another
+//! filesystem than the home trash go to that filesystem's own trash
+//! ([`volume_trash_dirs`]) so trashing stays a rename, and when neither is
+//! usable to a `.tt-trash` directory (same layout) next to them.
+//! [`list_trash`], [`restore`] and [`purge`] work on any of them.
+
+use crate::jobs::{Job, Jobs};
+use crate::{platform, time_util};
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::{self, Command},
+    sync::mpsc::{self, Receiver},
+};
+use walkdir::WalkDir;
+
+/// Buffer size for chunked copies; progress is reported after each chunk
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Kind of background transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transfer {
+    Copy,
+    Move,
+}
+
+impl Transfer {
+    /// Present participle for progress messages (e.g. "Copying")
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Transfer::Copy => "Copying",
+            Transfer::Move => "Moving",
+        }
+    }
+
+    /// Past tense for completion messages (e.g. "Copied")
+    pub fn past(&self) -> &'static str {
+        match self {
+            Transfer::Copy => "Copied",
+            Transfer::Move => "Moved",
+        }
+    }
+}
+
+/// How far a background transfer has got
+#[derive(Debug)]
+pub struct TransferProgress {
+    pub done: u64,
+    pub total: u64,
+    pub current: PathBuf,
+}
+
+/// Start copying or moving `sources` to `destination` as a job; progress
+/// also comes over the receiver, with the file being transferred
+pub fn spawn_transfer(
+    jobs: &Jobs,
+    kind: Transfer,
+    sources: Vec<PathBuf>,
+    destination: PathBuf,
+) -> (Job<Vec<(PathBuf, PathBuf)>>, Receiver<TransferProgress>) {
+    let (tx, rx) = mpsc::channel();
+    let label = match sources.as_slice() {
+        [source] => format!("{} {}", kind.verb(), source.file_name().unwrap_or_default().to_string_lossy()),
+        sources => format!("{} {} items", kind.verb(), sources.len()),
+    };
+    let job = jobs.spawn(label, move |task| {
+        transfer(kind, &sources, &destination, &mut |progress| {
+            task.progress(progress.done, Some(progress.total));
+            let _ = tx.send(progress);
+            task.check()
+        })
+    });
+    (job, rx)
+}
+
+/// Transfer `sources` to `destination`, reporting progress after each
+/// chunk; an error, or one from `report`, stops the transfer, removing what
+/// was copied of the item in hand
+fn transfer(
+    kind: Transfer,
+    sources: &[PathBuf],
+    destination: &Path,
+    report: &mut dyn FnMut(TransferProgress) -> io::Result<()>,
+) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+    if sources.len() > 1 && !destination.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a directory", destination.display()),
+        ));
+    }
+
+    let total: u64 = sources.iter().map(|source| tree_size(source)).sum();
+    let mut done = 0;
+    let mut transferred = Vec::new();
+
+    for source in sources {
+        if kind == Transfer::Move && source.parent() == Some(destination) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is already in {}", source.display(), destination.display()),
+            ));
+        }
+        let target = resolve_destination(source, destination)?;
+
+        if kind == Transfer::Move {
+            match fs::rename(source, &target) {
+                Ok(()) => {
+                    done += tree_size(&target);
+                    transferred.push((source.clone(), target.clone()));
+                    report(TransferProgress { done, total, current: target })?;
+                    continue;
+                }
+                // Only another filesystem calls for copy + delete
+                Err(e) if e.kind() != io::ErrorKind::CrossesDevices => return Err(e),
+                Err(_) => {}
+            }
+        }
+
+        // Copy (or move across filesystems via copy + delete)
+        let copied = copy_recursive(source, &target, &mut |bytes, current| {
+            done += bytes;
+            report(TransferProgress { done, total, current: current.to_path_buf() })
+        });
+        if let Err(e) = copied {
+            let _ = remove(&target);
+            return Err(e);
+        }
+        if kind == Transfer::Move {
+            remove(source)?;
+        }
+        transferred.push((source.clone(), target));
+    }
+
+    Ok(transferred)
+}
+
+/// Total size in bytes of a file or directory tree (symlinks not followed)
+pub fn tree_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Work out where `source` ends up when transferred to `destination`
+pub fn resolve_destination(source: &Path, destination: &Path) -> io::Result<PathBuf> {
+    let name = source
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Cannot transfer a root directory"))?;
+    let target = if destination.is_dir() {
+        destination.join(name)
+    } else {
+        destination.to_path_buf()
+    };
+
+    if target == source {
+        return Ok(unique_path(&target));
+    }
+    if target.starts_with(source) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Cannot copy {} into itself", source.display()),
+        ));
+    }
+    if target.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", target.display()),
+        ));
+    }
+    Ok(target)
+}
+
+/// First non-existing `name copy[ N].ext` sibling of `path`
+fn unique_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    let parent = path.parent().unwrap_or(Path::new(""));
+
+    (1..)
+        .map(|n| {
+            let suffix = if n == 1 { " copy".to_string() } else { format!(" copy {}", n) };
+            parent.join(format!("{}{}{}", stem, suffix, extension))
+        })
+        .find(|candidate| candidate.symlink_metadata().is_err())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Recursively copy a file, directory or symlink, reporting copied bytes
+fn copy_recursive(source: &Path, target: &Path, progress: &mut dyn FnMut(u64, &Path) -> io::Result<()>) -> io::Result<()> {
+    let metadata = source.symlink_metadata()?;
+
+    if metadata.file_type().is_symlink() {
+        let link = fs::read_link(source)?;
+        #[cfg(unix)]
+        return std::os::unix::fs::symlink(link, target);
+        #[cfg(not(unix))]
+        {
+            let _ = link;
+            fs::copy(source, target)?;
+            return Ok(());
+        }
+    }
+
+    if metadata.is_dir() {
+        fs::create_dir(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &target.join(entry.file_name()), progress)?;
+        }
+        fs::set_permissions(target, metadata.permissions())?;
+        return Ok(());
+    }
+
+    let mut reader = File::open(source)?;
+    let mut writer = File::create(target)?;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read])?;
+        progress(read as u64, source)?;
+    }
+    fs::set_permissions(target, metadata.permissions())
+}
+
+/// Rename a file or directory within its parent directory
+pub fn rename(path: &Path, new_name: &str) -> io::Result<PathBuf> {
+    if new_name.is_empty() || new_name.contains('/') {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid name '{}'", new_name)));
+    }
+    let target = path.with_file_name(new_name);
+    if target.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", target.display()),
+        ));
+    }
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+/// Create an empty file, failing if it already exists
+pub fn create_file(path: &Path) -> io::Result<()> {
+    fs::OpenOptions::new().write(true).create_new(true).open(path).map(|_| ())
+}
+
+/// Replace a file's contents in one step. The new contents go to a sibling
+/// temporary file with the original's permissions, which is then renamed over
+/// the (symlink-resolved) original, so readers never see a partial file.
+/// A missing file is created, along with its directory.
+pub fn replace_contents(path: &Path, content: &[u8]) -> io::Result<()> {
+    let (path, permissions) = match path.canonicalize() {
+        Ok(path) => {
+            let permissions = fs::metadata(&path)?.permissions();
+            (path, Some(permissions))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            (path.to_path_buf(), None)
+        }
+        Err(e) => return Err(e),
+    };
+    // The process id keeps concurrent writers off each other's temporary file
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tt-tmp", process::id()));
+    let temp = path.with_file_name(name);
+
+    let result = (|| {
+        let mut file = fs::OpenOptions::new().write(true).create(true).truncate(true).open(&temp)?;
+        if let Some(permissions) = permissions {
+            fs::set_permissions(&temp, permissions)?;
+        }
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&temp, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Create a symbolic link at `link` pointing to `target`.
+///
+/// If `link` is an existing directory the link is created inside it under
+/// `target`'s name. Returns the path of the new link.
+pub fn symlink(target: &Path, link: &Path) -> io::Result<PathBuf> {
+    let link = if link.is_dir() {
+        let name = target
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Cannot link to a root directory"))?;
+        link.join(name)
+    } else {
+        link.to_path_buf()
+    };
+    if link.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", link.display()),
+        ));
+    }
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(target, &link)?;
+    #[cfg(windows)]
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, &link)?;
+    } else {
+        std::os::windows::fs::symlink_file(target, &link)?;
+    }
+    #[cfg(not(any(unix, windows)))]
+    return Err(io::Error::new(io::ErrorKind::Unsupported, "Symlinks are not supported on this platform"));
+
+    Ok(link)
+}
+
+/// Permanently delete a file, symlink or directory tree
+pub fn remove(path: &Path) -> io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Permission bits (`0o7777`) of a file
+pub fn mode_bits(metadata: &fs::Metadata) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o7777
+    }
+    #[cfg(not(unix))]
+    {
+        if metadata.permissions().readonly() { 0o444 } else { 0o644 }
+    }
+}
+
+/// Change a file's permission bits
+pub fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+    }
+    #[cfg(not(unix))]
+    {
+        let mut permissions = fs::metadata(path)?.permissions();
+        permissions.set_readonly(mode & 0o200 == 0);
+        fs::set_permissions(path, permissions)
+    }
+}
+
+/// Render permission bits `ls`-style, e.g. `drwxr-xr-x` or `-rwsr-x--T`
+pub fn format_mode(mode: u32, is_dir: bool) -> String {
+    let mut text = String::with_capacity(10);
+    text.push(if is_dir { 'd' } else { '-' });
+    // (special bit, letter when executable, letter when not) for user/group/other
+    let specials = [(0o4000, 's', 'S'), (0o2000, 's', 'S'), (0o1000, 't', 'T')];
+    for (index, (special, set, unset)) in specials.into_iter().enumerate() {
+        let shift = 6 - index * 3;
+        let bits = (mode >> shift) & 0o7;
+        text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        text.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => set,
+            (false, true) => unset,
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    text
+}
+
+/// Parse an octal mode such as `755` or `0644`
+pub fn parse_octal_mode(text: &str) -> Option<u32> {
+    let text = text.trim();
+    if text.is_empty() || text.len() > 4 {
+        return None;
+    }
+    u32::from_str_radix(text, 8).ok()
+}
+
+/// Change owner and/or group with `chown(1)` (`user`, `user:group` or `:group`)
+pub fn chown(path: &Path, owner: &str) -> io::Result<()> {
+    let output = Command::new("chown").arg(owner).arg(path).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            stderr.trim().trim_start_matches("chown: ").to_string(),
+        ))
+    }
+}
+
+/// Home trash directory (`$XDG_DATA_HOME/Trash`, usually `~/.local/share/Trash`)
+pub fn trash_dir() -> Option<PathBuf> {
+    platform::data_home().map(|data| data.join("Trash"))
+}
+
+/// Trash directories of the filesystem holding `dir`, for items the home
+/// trash is on another filesystem from: `$topdir/.Trash/$uid` when an
+/// administrator set up a shared `.Trash`, then `$topdir/.Trash-$uid`, where
+/// `$topdir` is the filesystem's mount point
+#[cfg(unix)]
+pub fn volume_trash_dirs(dir: &Path) -> Vec<PathBuf> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    let Ok(device) = fs::metadata(dir).map(|metadata| metadata.dev()) else {
+        return Vec::new();
+    };
+    let top = dir
+        .ancestors()
+        .take_while(|ancestor| fs::metadata(ancestor).is_ok_and(|metadata| metadata.dev() == device))
+        .last()
+        .unwrap_or(dir);
+    // SAFETY: getuid has no preconditions and cannot fail
+    let uid = unsafe { libc::getuid() };
+    let mut dirs = Vec::new();
+    // A shared .Trash only counts as a real directory with the sticky bit set
+    let shared = top.join(".Trash");
+    if shared.symlink_metadata().is_ok_and(|metadata| metadata.is_dir() && metadata.permissions().mode() & 0o1000 != 0) {
+        dirs.push(shared.join(uid.to_string()));
+    }
+    dirs.push(top.join(format!(".Trash-{}", uid)));
+    dirs
+}
+
+#[cfg(not(unix))]
+pub fn volume_trash_dirs(_dir: &Path) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// Fallback trash used when no other is usable: `.tt-trash` next to the
+/// item, which is on the same filesystem so trashing is a rename
+pub fn fallback_trash_dir(path: &Path) -> PathBuf {
+    path.parent().unwrap_or(Path::new("/")).join(".tt-trash")
+}
+
+/// An item in a trash directory together with its `.trashinfo` record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashItem {
+    /// Where the item is stored now (`<trash>/files/<name>`)
+    pub path: PathBuf,
+    /// Its `.trashinfo` record (`<trash>/info/<name>.trashinfo`)
+    pub info: PathBuf,
+    /// Where it was deleted from
+    pub original: PathBuf,
+    /// Deletion time as recorded (`YYYY-MM-DDTHH:MM:SS`)
+    pub deleted: String,
+}
+
+/// Move a file or directory to the freedesktop trash.
+///
+/// Trashing is always a rename: an item the home trash cannot take, e.g.
+/// because it is on another filesystem, goes to its filesystem's trash
+/// ([`volume_trash_dirs`]), then to [`fallback_trash_dir`].
+pub fn trash(path: &Path) -> io::Result<TrashItem> {
+    // Canonicalize the parent only, so symlinks are trashed rather than their targets
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Cannot trash a root directory"))?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.canonicalize()?,
+        _ => env::current_dir()?,
+    };
+    let absolute = parent.join(name);
+    absolute.symlink_metadata()?;
+
+    for trash in trash_dir().into_iter().chain(volume_trash_dirs(&parent)) {
+        if let Ok(item) = trash_into(&trash, &absolute) {
+            return Ok(item);
+        }
+    }
+    trash_into(&fallback_trash_dir(&absolute), &absolute)
+}
+
+/// Move `absolute` into the trash directory `trash`, which must be on the
+/// same filesystem
+fn trash_into(trash: &Path, absolute: &Path) -> io::Result<TrashItem> {
+    let files = trash.join("files");
+    let info = trash.join("info");
+    create_private_dir(trash)?;
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+
+    let name = absolute
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // Reserve a unique name by creating the .trashinfo file atomically
+    let mut unique = name.clone();
+    let mut counter = 1;
+    let (mut info_file, info_path) = loop {
+        let info_path = info.join(format!("{}.trashinfo", unique));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&info_path) {
+            Ok(file) => break (file, info_path),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                counter += 1;
+                unique = format!("{}.{}", name, counter);
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    let deleted = time_util::format_iso(time_util::now_secs());
+    write!(
+        info_file,
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        percent_encode(&absolute.to_string_lossy()),
+        deleted
+    )?;
+
+    let target = files.join(&unique);
+    if let Err(e) = fs::rename(absolute, &target) {
+        let _ = fs::remove_file(&info_path);
+        return Err(e);
+    }
+    Ok(TrashItem {
+        path: target,
+        info: info_path,
+        original: absolute.to_path_buf(),
+        deleted,
+    })
+}
+
+/// Create `dir` readable by its owner only, as trash directories should be,
+/// unless it exists
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    if dir.is_dir() {
+        return Ok(());
+    }
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    match builder.create(dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Move a file or directory, falling back to copy + delete across filesystems
+pub fn move_path(source: &Path, target: &Path) -> io::Result<()> {
+    if fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(source, target, &mut |_, _| Ok(()))?;
+    remove(source)
+}
+
+/// List the items in a trash directory (home trash or `.tt-trash`), newest first
+pub fn list_trash(trash: &Path) -> Vec<TrashItem> {
+    let Ok(records) = fs::read_dir(trash.join("info")) else {
+        return Vec::new();
+    };
+    let mut items: Vec<TrashItem> = records
+        .flatten()
+        .filter_map(|record| {
+            let info = record.path();
+            let name = info.file_name()?.to_string_lossy().strip_suffix(".trashinfo")?.to_string();
+            let (original, deleted) = parse_trash_info(&fs::read_to_string(&info).ok()?)?;
+            let path = trash.join("files").join(name);
+            path.symlink_metadata().ok()?;
+            Some(TrashItem { path, info, original, deleted })
+        })
+        .collect();
+    items.sort_by(|a, b| b.deleted.cmp(&a.deleted));
+    items
+}
+
+/// Parse a `.trashinfo` record into (original path, deletion date)
+fn parse_trash_info(content: &str) -> Option<(PathBuf, String)> {
+    let mut original = None;
+    let mut deleted = String::new();
+    for line in content.lines() {
+        if let Some(path) = line.strip_prefix("Path=") {
+            original = Some(PathBuf::from(percent_decode(path)));
+        } else if let Some(date) = line.strip_prefix("DeletionDate=") {
+            deleted = date.to_string();
+        }
+    }
+    Some((original?, deleted))
+}
+
+/// Move a trashed item back to where it was deleted from
+pub fn restore(item: &TrashItem) -> io::Result<()> {
+    if item.original.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", item.original.display()),
+        ));
+    }
+    if let Some(parent) = item.original.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    move_path(&item.path, &item.original)?;
+    fs::remove_file(&item.info)
+}
+
+/// Permanently delete a trashed item and its record
+pub fn purge(item: &TrashItem) -> io::Result<()> {
+    remove(&item.path)?;
+    fs::remove_file(&item.info)
+}
+
+/// Percent-encode a path for `.trashinfo` files (RFC 2396, `/` kept)
+fn percent_encode(path: &str) -> String {
+    path.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes, as in `.trashinfo` paths and `file://` URIs
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("tt-file-ops-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_copy_into_directory_and_duplicate() {
+        let dir = scratch_dir("copy");
+        fs::create_dir(dir.join("src")).unwrap();
+        fs::write(dir.join("src/a.txt"), "hello").unwrap();
+        fs::create_dir(dir.join("dest")).unwrap();
+
+        let mut report = |_| Ok(());
+        transfer(Transfer::Copy, &[dir.join("src")], &dir.join("dest"), &mut report).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("dest/src/a.txt")).unwrap(), "hello");
+
+        // Copying a file onto itself creates "a copy.txt"
+        transfer(Transfer::Copy, &[dir.join("src/a.txt")], &dir.join("src"), &mut report).unwrap();
+        assert!(dir.join("src/a copy.txt").exists());
+
+        assert!(resolve_destination(&dir.join("src"), &dir.join("src/inner")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_replace_contents() {
+        let dir = scratch_dir("replace");
+        // A missing file is created with its directory
+        let file = dir.join("sub/list");
+        replace_contents(&file, b"one\n").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "one\n");
+
+        replace_contents(&file, b"two\n").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "two\n");
+        assert_eq!(fs::read_dir(dir.join("sub")).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_and_parse_mode() {
+        assert_eq!(format_mode(0o755, true), "drwxr-xr-x");
+        assert_eq!(format_mode(0o4750, false), "-rwsr-x---");
+        assert_eq!(format_mode(0o1776, true), "drwxrwxrwT");
+        assert_eq!(parse_octal_mode("0644"), Some(0o644));
+        assert_eq!(parse_octal_mode("789"), None);
+        assert_eq!(parse_octal_mode("12345"), None);
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("/home/me/my file.txt"), "/home/me/my%20file.txt");
+        assert_eq!(percent_decode("/home/me/my%20file%C3%A9.txt"), "/home/me/my fileé.txt");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn test_trash_list_and_restore() {
+        let dir = scratch_dir("trash");
+        fs::write(dir.join("a b.txt"), "hello").unwrap();
+        let trash = dir.join("Trash");
+
+        let item = trash_into(&trash, &dir.join("a b.txt")).unwrap();
+        assert!(!dir.join("a b.txt").exists());
+        assert_eq!(list_trash(&trash), vec![item.clone()]);
+
+        restore(&item).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("a b.txt")).unwrap(), "hello");
+        assert!(list_trash(&trash).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_volume_trash_dirs() {
+        use std::os::unix::fs::MetadataExt;
+        let dir = scratch_dir("volume-trash");
+        let dirs = volume_trash_dirs(&dir);
+        // SAFETY: getuid has no preconditions and cannot fail
+        let name = format!(".Trash-{}", unsafe { libc::getuid() });
+        let own = dirs.last().unwrap();
+        assert_eq!(own.file_name().unwrap().to_string_lossy(), name);
+        // Its top directory is the highest one on the same filesystem
+        let top = own.parent().unwrap();
+        assert!(dir.starts_with(top));
+        let device = |path: &Path| fs::metadata(path).unwrap().dev();
+        assert_eq!(device(top), device(&dir));
+        if let Some(above) = top.parent() {
+            assert_ne!(device(above), device(&dir));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,261 @@
+//! Frecency-ranked directories for `tt z`.
+//!
+//! When `zoxide` is installed its database is the source of truth: entries are
+//! read with `zoxide query --list --score` and visits are recorded with
+//! `zoxide add`, so `tt z` and the user's own `z` stay in sync. Without it, tt
+//! keeps its own store in `$XDG_STATE_HOME/terminal-tools/dirs` (default
+//! `~/.local/state/terminal-tools/dirs`) using the z.sh line format
+//! `path|rank|last_access`.
+//!
+//! The built-in store ranks like zoxide does: every visit adds 1 to a
+//! directory's rank, and the score weights the rank by how recently it was
+//! used (×4 within the hour, ×2 within the day, ×½ within the week, ×¼ after
+//! that). Once the ranks add up to more than [`MAX_TOTAL_RANK`] they are all
+//! aged by 10% and entries dropping below 1 are forgotten.
+//!
+//! `tt recent` ranks files the same way, keeping its store through
+//! [`parse_store`], [`format_store`] and [`record_visit`].
+
+use crate::{platform, time_util};
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Total rank at which the built-in store ages its entries
+const MAX_TOTAL_RANK: f64 = 10_000.0;
+
+/// Where ranked directories come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Zoxide,
+    Builtin,
+}
+
+impl Source {
+    /// Use zoxide when it can be run, otherwise the built-in store
+    pub fn detect() -> Self {
+        match Command::new("zoxide").arg("--version").output() {
+            Ok(output) if output.status.success() => Source::Zoxide,
+            _ => Source::Builtin,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::Zoxide => "zoxide",
+            Source::Builtin => "tt",
+        }
+    }
+}
+
+/// A directory with its frecency score
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedDir {
+    pub path: PathBuf,
+    pub score: f64,
+}
+
+/// An entry of the built-in store
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreEntry {
+    pub path: PathBuf,
+    pub rank: f64,
+    /// Unix time of the latest visit
+    pub last_access: i64,
+}
+
+fn store_file() -> Option<PathBuf> {
+    platform::state_dir().map(|dir| dir.join("dirs"))
+}
+
+/// Score of a rank last used at `last_access`, as seen at `now`
+pub fn frecency(rank: f64, last_access: i64, now: i64) -> f64 {
+    let age = now - last_access;
+    if age < 3600 {
+        rank * 4.0
+    } else if age < 86_400 {
+        rank * 2.0
+    } else if age < 604_800 {
+        rank / 2.0
+    } else {
+        rank / 4.0
+    }
+}
+
+/// Parse z.sh style `path|rank|last_access` lines, skipping malformed ones
+pub fn parse_store(content: &str) -> Vec<StoreEntry> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.rsplitn(3, '|');
+            let last_access = fields.next()?.trim().parse().ok()?;
+            let rank = fields.next()?.trim().parse().ok()?;
+            let path = fields.next().filter(|path| !path.is_empty())?;
+            Some(StoreEntry { path: PathBuf::from(path), rank, last_access })
+        })
+        .collect()
+}
+
+fn load_store() -> Vec<StoreEntry> {
+    store_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| parse_store(&content))
+        .unwrap_or_default()
+}
+
+fn save_store(entries: &[StoreEntry]) -> io::Result<()> {
+    let path = store_file()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory for the directory store"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, format_store(entries))
+}
+
+/// Entries as z.sh style lines
+pub fn format_store(entries: &[StoreEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| format!("{}|{}|{}\n", entry.path.display(), entry.rank, entry.last_access))
+        .collect()
+}
+
+/// Record a visit to `path` in the built-in store, aging it when it grows too large
+pub fn record_visit(entries: &mut Vec<StoreEntry>, path: &Path, now: i64) {
+    match entries.iter_mut().find(|entry| entry.path == path) {
+        Some(entry) => {
+            entry.rank += 1.0;
+            entry.last_access = now;
+        }
+        None => entries.push(StoreEntry { path: path.to_path_buf(), rank: 1.0, last_access: now }),
+    }
+
+    if entries.iter().map(|entry| entry.rank).sum::<f64>() > MAX_TOTAL_RANK {
+        for entry in entries.iter_mut() {
+            entry.rank *= 0.9;
+        }
+        entries.retain(|entry| entry.rank >= 1.0);
+    }
+}
+
+/// Parse `zoxide query --list --score` output: `<score> <path>` per line
+fn parse_zoxide(output: &str) -> Vec<RankedDir> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (score, path) = line.trim_start().split_once(' ')?;
+            Some(RankedDir { path: PathBuf::from(path), score: score.parse().ok()? })
+        })
+        .collect()
+}
+
+/// All known directories that still exist, best first
+pub fn load(source: Source) -> io::Result<Vec<RankedDir>> {
+    let mut dirs = match source {
+        Source::Zoxide => {
+            let output = Command::new("zoxide").args(["query", "--list", "--score"]).output()?;
+            parse_zoxide(&String::from_utf8_lossy(&output.stdout))
+        }
+        Source::Builtin => {
+            let now = time_util::now_secs();
+            load_store()
+                .into_iter()
+                .map(|entry| RankedDir { score: frecency(entry.rank, entry.last_access, now), path: entry.path })
+                .collect()
+        }
+    };
+    dirs.retain(|dir| dir.path.is_dir());
+    dirs.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(dirs)
+}
+
+/// Record a visit to `path`
+pub fn add(source: Source, path: &Path) -> io::Result<()> {
+    let path = path.canonicalize()?;
+    match source {
+        Source::Zoxide => run_zoxide(&["add"], &path),
+        Source::Builtin => {
+            let mut entries = load_store();
+            record_visit(&mut entries, &path, time_util::now_secs());
+            save_store(&entries)
+        }
+    }
+}
+
+/// Forget `path`
+pub fn remove(source: Source, path: &Path) -> io::Result<()> {
+    match source {
+        Source::Zoxide => run_zoxide(&["remove"], path),
+        Source::Builtin => {
+            let mut entries = load_store();
+            entries.retain(|entry| entry.path != path);
+            save_store(&entries)
+        }
+    }
+}
+
+fn run_zoxide(args: &[&str], path: &Path) -> io::Result<()> {
+    let status = Command::new("zoxide").args(args).arg(path).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("zoxide {} failed", args.join(" "))))
+    }
+}
+
+/// Whether `path` matches the query words the way zoxide matches them: every
+/// word appears in order (ignoring case) and the last one is in the final
+/// path component
+pub fn matches(words: &[&str], path: &Path) -> bool {
+    let path = path.to_string_lossy().to_lowercase();
+    let mut rest = path.as_str();
+    for word in words {
+        let word = word.to_lowercase();
+        match rest.find(&word) {
+            Some(index) => rest = &rest[index + word.len()..],
+            None => return false,
+        }
+    }
+    match words.last() {
+        Some(last) => {
+            let name = path.rsplit('/').next().unwrap_or_default();
+            name.contains(&last.to_lowercase())
+        }
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_store_and_record_visit() {
+        let mut entries = parse_store("/home/a|3|100\nbroken\n/home/with|pipe|2.5|50\n");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].path, PathBuf::from("/home/with|pipe"));
+
+        record_visit(&mut entries, Path::new("/home/a"), 200);
+        record_visit(&mut entries, Path::new("/new"), 200);
+        assert_eq!((entries[0].rank, entries[0].last_access), (4.0, 200));
+        assert_eq!(entries[2].rank, 1.0);
+
+        assert_eq!(frecency(2.0, 0, 60), 8.0);
+        assert_eq!(frecency(2.0, 0, 700_000), 0.5);
+    }
+
+    #[test]
+    fn test_parse_zoxide_and_matches() {
+        let dirs = parse_zoxide("  12.5 /home/me/src/tt\n   0.2 /tmp/with space\n");
+        assert_eq!(dirs[1], RankedDir { path: PathBuf::from("/tmp/with space"), score: 0.2 });
+
+        let path = Path::new("/home/me/Src/terminal-tools");
+        assert!(matches(&["src", "term"], path));
+        assert!(matches(&[], path));
+        assert!(!matches(&["term", "src"], path));
+        assert!(!matches(&["me"], path));
+    }
+}
@@ -0,0 +1,112 @@
+//! Fuzzy matching for interactive filters.
+//!
+//! A pattern matches when all of its characters appear in the candidate in
+//! order, ignoring case (`fzr` matches `fuzzy.rs`). Matches are scored so the
+//! most plausible candidates sort first:
+//!
+//! - Consecutive matched characters earn a bonus
+//! - Characters at the start of the candidate or of a word (after `_`, `-`,
+//!   `.`, `/`, a space or a lowercase → uppercase change) earn a bonus
+//! - Gaps between matched characters cost a small penalty
+//!
+//! The matcher is greedy rather than optimal, which is plenty for filtering
+//! directory listings and history entries while staying allocation-free.
+
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 24;
+const BOUNDARY_BONUS: i64 = 20;
+const START_BONUS: i64 = 32;
+const GAP_PENALTY: i64 = 1;
+
+/// Score `candidate` against `pattern`, or `None` if it does not match.
+///
+/// Higher scores are better; an empty pattern matches everything with 0.
+pub fn score(pattern: &str, candidate: &str) -> Option<i64> {
+    score_with(pattern, candidate, |_| {})
+}
+
+/// Score `candidate` like [`score`], also returning the char indices of the
+/// matched characters for highlighting
+pub fn match_positions(pattern: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let mut positions = Vec::new();
+    let score = score_with(pattern, candidate, |index| positions.push(index))?;
+    Some((score, positions))
+}
+
+/// Greedy matcher behind [`score`], reporting each matched char index
+fn score_with(pattern: &str, candidate: &str, mut on_match: impl FnMut(usize)) -> Option<i64> {
+    let mut pattern_chars = pattern.chars().flat_map(char::to_lowercase).peekable();
+    let mut total = 0;
+    let mut previous: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+
+    for (index, c) in candidate.chars().enumerate() {
+        let Some(&wanted) = pattern_chars.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(std::iter::once(wanted)) {
+            total += MATCH_SCORE;
+            match last_match {
+                Some(last) if last + 1 == index => total += CONSECUTIVE_BONUS,
+                Some(last) => total -= GAP_PENALTY * (index - last - 1) as i64,
+                None => total -= GAP_PENALTY * index as i64,
+            }
+            if index == 0 {
+                total += START_BONUS;
+            } else if is_boundary(previous, c) {
+                total += BOUNDARY_BONUS;
+            }
+            last_match = Some(index);
+            on_match(index);
+            pattern_chars.next();
+        }
+        previous = Some(c);
+    }
+
+    pattern_chars.peek().is_none().then_some(total)
+}
+
+/// Whether `c` starts a new word after `previous`
+fn is_boundary(previous: Option<char>, c: char) -> bool {
+    match previous {
+        None => true,
+        Some(p) => matches!(p, '_' | '-' | '.' | '/' | ' ') || (p.is_lowercase() && c.is_uppercase()),
+    }
+}
+
+/// Filter and rank `items` by their fuzzy score, best match first.
+///
+/// Ties keep their original order, so an already-sorted list stays stable.
+pub fn filter<T, F>(pattern: &str, items: impl IntoIterator<Item = T>, key: F) -> Vec<T>
+where
+    F: Fn(&T) -> &str,
+{
+    let mut scored: Vec<(i64, T)> = items
+        .into_iter()
+        .filter_map(|item| score(pattern, key(&item)).map(|s| (s, item)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_matches_subsequence() {
+        assert!(score("fzr", "fuzzy.rs").is_some());
+        assert!(score("FZR", "fuzzy.rs").is_some());
+        assert_eq!(score("", "anything"), Some(0));
+        assert!(score("zf", "fuzzy.rs").is_none());
+        assert!(score("rsx", "fuzzy.rs").is_none());
+        assert_eq!(match_positions("fzr", "fuzzy.rs").map(|(_, p)| p), Some(vec![0, 2, 6]));
+    }
+
+    #[test]
+    fn test_filter_ranks_better_matches_first() {
+        let names = ["src_main.rs", "cargo.toml", "main.rs", "domain.rs"];
+        let ranked = filter("main", names, |name| *name);
+        assert_eq!(ranked, vec!["main.rs", "src_main.rs", "domain.rs"]);
+    }
+}
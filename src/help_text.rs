@@ -0,0 +1,213 @@
+//! `--help` output as a stand-in for missing man pages.
+//!
+//! Many command-line tools (most installed with `cargo`, `npm` or `pip`) ship
+//! no man page but print a usage summary for `--help`. `tt man` runs that for
+//! a command found on `PATH` and shows the result like a man page.
+//!
+//! Running an arbitrary program is kept harmless as far as possible: stdin is
+//! closed so nothing can wait for input, pagers are set to `cat`, colours are
+//! turned off, and the process is killed if it has not finished within
+//! [`TIMEOUT`]. Commands that act before looking at their arguments, or that
+//! prompt for passwords, are never run. A program that rejects `--help` is
+//! tried again with `-h`.
+//!
+//! The text is then given the structure of a man page: unindented headings
+//! such as `Usage:`, `Options:` or `COMMANDS` are indexed as sections, and
+//! flags at the start of indented lines are shown in bold.
+
+use crate::man_page::{self, Format, Page};
+use std::{
+    env,
+    io::Read,
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long a command may take to print its help
+pub const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Commands whose `--help` is not trusted to be side-effect free
+const NEVER_RUN: &[&str] = &[
+    "reboot", "shutdown", "halt", "poweroff", "init", "telinit",
+    "sudo", "su", "doas", "login", "passwd", "logout",
+];
+
+/// Path of the executable `name` on `PATH`
+pub fn find_command(name: &str) -> Option<PathBuf> {
+    if name.is_empty() || name.starts_with('-') || name.contains(['/', '\\']) || name.contains(char::is_whitespace) {
+        return None;
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &std::path::Path) -> bool {
+    path.is_file()
+}
+
+/// The `--help` (or `-h`) output of `name`, laid out for `width` columns
+pub fn run(name: &str, width: u16) -> Result<String, String> {
+    if NEVER_RUN.contains(&name) {
+        return Err(format!("{} is not run to ask for its help", name));
+    }
+    let path = find_command(name).ok_or_else(|| format!("{}: command not found", name))?;
+    let mut last_error = String::new();
+    for flag in ["--help", "-h"] {
+        match run_with_timeout(&path, flag, width) {
+            Ok(text) if !text.trim().is_empty() => return Ok(text),
+            Ok(_) => last_error = format!("{} {} printed nothing", name, flag),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// Output of `path flag`
+fn run_with_timeout(path: &PathBuf, flag: &str, width: u16) -> Result<String, String> {
+    let mut command = Command::new(path);
+    command
+        .arg(flag)
+        .env("PAGER", "cat")
+        .env("MANPAGER", "cat")
+        .env("GIT_PAGER", "cat")
+        .env("NO_COLOR", "1")
+        .env("TERM", "dumb")
+        .env("COLUMNS", width.to_string());
+    output_with_timeout(command, &format!("{} {}", path.display(), flag), TIMEOUT)
+}
+
+/// Output of `command` with no input, stdout and stderr together since many
+/// programs print their usage on stderr; `what` names it in errors
+pub fn output_with_timeout(mut command: Command, what: &str, timeout: Duration) -> Result<String, String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run {}: {}", what, e))?;
+
+    // Read both pipes on their own threads so a full one cannot stall the child
+    let readers: Vec<_> = [
+        child.stdout.take().map(|pipe| Box::new(pipe) as Box<dyn Read + Send>),
+        child.stderr.take().map(|pipe| Box::new(pipe) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|mut pipe| {
+        thread::spawn(move || {
+            let mut bytes = Vec::new();
+            let _ = pipe.read_to_end(&mut bytes);
+            bytes
+        })
+    })
+    .collect();
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < timeout => thread::sleep(Duration::from_millis(20)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{} did not finish within {} seconds", what, timeout.as_secs()));
+            }
+        }
+    }
+
+    let mut text = String::new();
+    for reader in readers {
+        let bytes = reader.join().unwrap_or_default();
+        text.push_str(&String::from_utf8_lossy(&bytes));
+    }
+    Ok(text)
+}
+
+/// Lay out help text as a [`Page`] with sections and bold flags
+pub fn parse(text: &str) -> Page {
+    let mut page = Page::parse(text);
+    page.sections.clear();
+    for index in 0..page.lines.len() {
+        // Programs that format their own help keep their styling
+        if page.lines[index].iter().any(|(_, format)| *format != Format::Plain) {
+            continue;
+        }
+        let line = page.line_text(index);
+        if let Some(heading) = heading(&line) {
+            let rest = line[heading.len()..].to_string();
+            page.sections.push((heading.trim_end_matches(':').to_string(), index));
+            page.lines[index] = vec![(heading.to_string(), Format::Bold)];
+            if !rest.is_empty() {
+                page.lines[index].push((rest, Format::Plain));
+            }
+        } else if let Some((indent, flags, rest)) = man_page::split_flags(&line) {
+            page.lines[index] = [(indent, Format::Plain), (flags, Format::Bold), (rest, Format::Plain)]
+                .into_iter()
+                .filter(|(text, _)| !text.is_empty())
+                .map(|(text, format)| (text.to_string(), format))
+                .collect();
+        }
+    }
+    page
+}
+
+/// The heading at the start of `line`: `Options:`, `Usage:` before a usage
+/// line, or a man-style upper-case heading
+fn heading(line: &str) -> Option<&str> {
+    if line.starts_with(char::is_whitespace) || !line.starts_with(char::is_alphabetic) {
+        return None;
+    }
+    if let Some(colon) = line.find(':') {
+        let head = &line[..colon];
+        let rest = &line[colon + 1..];
+        if head.split_whitespace().count() <= 3 && (rest.is_empty() || rest.starts_with(' ')) {
+            return Some(&line[..=colon]);
+        }
+    }
+    man_page::is_heading(line).then_some(line.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let page = parse("A tool\n\nUsage: tool [OPTIONS] <PATH>\n\nOptions:\n  -v, --verbose  Print more\n      --color <WHEN>\n          When to colour\n  - not a flag\nNote: see also\n");
+        assert_eq!(
+            page.sections,
+            vec![("Usage".to_string(), 2), ("Options".to_string(), 4), ("Note".to_string(), 9)]
+        );
+        assert_eq!(
+            page.lines[2],
+            vec![("Usage:".to_string(), Format::Bold), (" tool [OPTIONS] <PATH>".to_string(), Format::Plain)]
+        );
+        assert_eq!(
+            page.lines[5],
+            vec![
+                ("  ".to_string(), Format::Plain),
+                ("-v, --verbose".to_string(), Format::Bold),
+                ("  Print more".to_string(), Format::Plain),
+            ]
+        );
+        assert_eq!(page.lines[6][1], ("--color <WHEN>".to_string(), Format::Bold));
+        assert_eq!(page.lines[8], vec![("  - not a flag".to_string(), Format::Plain)]);
+    }
+
+    #[test]
+    fn test_find_command() {
+        assert_eq!(find_command("../sh"), None);
+        assert_eq!(find_command("-rf"), None);
+        assert_eq!(find_command(""), None);
+    }
+}
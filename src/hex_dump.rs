@@ -0,0 +1,263 @@
+//! Hex dumps of binary files for `tt hex` and the binary file previews of
+//! `tt find` and `tt explore`.
+//!
+//! A dump row shows the offset of its first byte, the bytes in hex (grouped
+//! by eight) and the same bytes as ASCII, with `.` for anything that is not
+//! printable:
+//!
+//! ```text
+//! 00000000  7f 45 4c 46 02 01 01 00  00 00 00 00 00 00 00 00  .ELF............
+//! ```
+//!
+//! Files are read a window at a time, so multi-gigabyte files open at once;
+//! [`find`] scans them in chunks.
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// Bytes per row `tt hex` cycles through
+pub const WIDTHS: [usize; 4] = [8, 16, 24, 32];
+
+/// Bytes scanned at a time when searching
+const CHUNK: usize = 1 << 20;
+
+/// Kinds of bytes, colored apart in the viewer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteClass {
+    Null,
+    /// Printable ASCII
+    Printable,
+    /// Spaces, tabs and line breaks
+    Whitespace,
+    /// Other ASCII control characters
+    Control,
+    /// Bytes above 0x7f
+    High,
+}
+
+impl ByteClass {
+    pub fn of(byte: u8) -> ByteClass {
+        match byte {
+            0 => ByteClass::Null,
+            b' ' | b'\t' | b'\n' | b'\r' => ByteClass::Whitespace,
+            0x21..=0x7e => ByteClass::Printable,
+            0x80..=0xff => ByteClass::High,
+            _ => ByteClass::Control,
+        }
+    }
+}
+
+/// A byte in the ASCII column
+pub fn ascii_char(byte: u8) -> char {
+    if (0x20..=0x7e).contains(&byte) { byte as char } else { '.' }
+}
+
+/// The offset column, at least eight hex digits
+pub fn offset_text(offset: u64) -> String {
+    format!("{:08x}", offset)
+}
+
+/// The hex column of a row of `width` bytes: pairs separated by spaces, with
+/// an extra space every eight bytes; short rows are padded
+pub fn hex_text(bytes: &[u8], width: usize) -> String {
+    let mut text = String::new();
+    for index in 0..width {
+        if index > 0 {
+            text.push_str(if index % 8 == 0 { "  " } else { " " });
+        }
+        match bytes.get(index) {
+            Some(byte) => text.push_str(&format!("{:02x}", byte)),
+            None => text.push_str("  "),
+        }
+    }
+    text
+}
+
+/// The ASCII column of a row
+pub fn ascii_text(bytes: &[u8]) -> String {
+    bytes.iter().map(|&byte| ascii_char(byte)).collect()
+}
+
+/// One dump row: offset, hex and ASCII columns
+pub fn row_text(offset: u64, bytes: &[u8], width: usize) -> String {
+    format!("{}  {}  {}", offset_text(offset), hex_text(bytes, width), ascii_text(bytes))
+}
+
+/// Columns a row of `width` bytes takes
+pub fn row_width(width: usize) -> usize {
+    // Offset, two spaces, three columns per byte plus one per group, ASCII
+    8 + 2 + width * 3 + (width.saturating_sub(1)) / 8 + 1 + width
+}
+
+/// The widest of [`WIDTHS`] whose rows fit in `columns`, or the narrowest
+pub fn fit_width(columns: usize) -> usize {
+    WIDTHS.iter().rev().copied().find(|&width| row_width(width) <= columns).unwrap_or(WIDTHS[0])
+}
+
+/// Up to `len` bytes of `source` from `offset`
+pub fn read_at<R: Read + Seek>(source: &mut R, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+    source.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::with_capacity(len);
+    source.take(len as u64).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// The dump of the first `rows` rows of `path`, as the preview of a binary
+/// file
+pub fn preview(path: &Path, width: usize, rows: usize) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let bytes = read_at(&mut file, 0, width * rows)?;
+    let mut lines = vec![format!("Binary file, {} bytes", len), String::new()];
+    lines.extend(bytes.chunks(width).enumerate().map(|(row, chunk)| row_text((row * width) as u64, chunk, width)));
+    if len > bytes.len() as u64 {
+        lines.push(format!("... {} more bytes (tt hex to see all)", len - bytes.len() as u64));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Parse hex bytes like `de ad be ef`, `DEADBEEF` or `0x7f 0x45`
+pub fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text
+        .split_whitespace()
+        .map(|token| token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).unwrap_or(token))
+        .collect();
+    if digits.is_empty() {
+        return Err("no bytes given".to_string());
+    }
+    if digits.len() % 2 != 0 {
+        return Err(format!("odd number of hex digits in '{}'", text.trim()));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&digits[index..index + 2], 16).map_err(|_| format!("not hex: '{}'", text.trim())))
+        .collect()
+}
+
+/// Parse an offset to go to: decimal, `0x` hex, or relative to `current`
+/// with a leading `+` or `-`. The result is clamped to the file.
+pub fn parse_offset(text: &str, current: u64, len: u64) -> Result<u64, String> {
+    let text = text.trim();
+    let (sign, number) = match text.as_bytes().first() {
+        Some(b'+') => (1, &text[1..]),
+        Some(b'-') => (-1, &text[1..]),
+        _ => (0, text),
+    };
+    let number = number.trim();
+    let value = match number.strip_prefix("0x").or_else(|| number.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => number.parse(),
+    }
+    .map_err(|_| format!("not an offset: '{}' (use 1024, 0x400, +16 or -16)", text))?;
+    let offset = match sign {
+        1 => current.saturating_add(value),
+        -1 => current.saturating_sub(value),
+        _ => value,
+    };
+    Ok(offset.min(len.saturating_sub(1)))
+}
+
+/// The offset of the first match of `pattern` after `from` (or the last
+/// before it, going backward), wrapping around the end of the file
+pub fn find<R: Read + Seek>(source: &mut R, len: u64, pattern: &[u8], from: u64, forward: bool) -> io::Result<Option<u64>> {
+    if pattern.is_empty() || (pattern.len() as u64) > len {
+        return Ok(None);
+    }
+    let found = if forward {
+        match find_forward(source, len, pattern, from.saturating_add(1))? {
+            Some(offset) => Some(offset),
+            None => find_forward(source, len, pattern, 0)?.filter(|&offset| offset <= from),
+        }
+    } else {
+        match find_backward(source, pattern, from)? {
+            Some(offset) => Some(offset),
+            None => find_backward(source, pattern, len)?.filter(|&offset| offset >= from),
+        }
+    };
+    Ok(found)
+}
+
+/// The first match starting at or after `start`
+fn find_forward<R: Read + Seek>(source: &mut R, len: u64, pattern: &[u8], start: u64) -> io::Result<Option<u64>> {
+    let mut offset = start;
+    while offset < len {
+        // Chunks overlap so a match across their boundary is found
+        let bytes = read_at(source, offset, CHUNK + pattern.len() - 1)?;
+        if let Some(index) = position(&bytes, pattern) {
+            return Ok(Some(offset + index as u64));
+        }
+        if bytes.len() < pattern.len() {
+            break;
+        }
+        offset += CHUNK as u64;
+    }
+    Ok(None)
+}
+
+/// The last match starting before `end`
+fn find_backward<R: Read + Seek>(source: &mut R, pattern: &[u8], end: u64) -> io::Result<Option<u64>> {
+    let mut end = end;
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK as u64);
+        let bytes = read_at(source, start, (end - start) as usize + pattern.len() - 1)?;
+        let last = bytes
+            .windows(pattern.len())
+            .enumerate()
+            .rev()
+            .find(|(index, window)| start + (*index as u64) < end && *window == pattern)
+            .map(|(index, _)| index);
+        if let Some(index) = last {
+            return Ok(Some(start + index as u64));
+        }
+        end = start;
+    }
+    Ok(None)
+}
+
+fn position(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_row_text() {
+        let row = row_text(16, b"\x7fELF\x02\x01\x01\x00\x00", 16);
+        assert_eq!(row, "00000010  7f 45 4c 46 02 01 01 00  00                       .ELF.....");
+        assert_eq!(row_text(0, &[0; 16], 16).len(), row_width(16));
+        assert_eq!(fit_width(80), 16);
+        assert_eq!(fit_width(20), 8);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_hex("de ad BEEF"), Ok(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(parse_hex("0x7f 0x45"), Ok(vec![0x7f, 0x45]));
+        assert!(parse_hex("abc").is_err());
+        assert_eq!(parse_offset("0x400", 0, 4096), Ok(1024));
+        assert_eq!(parse_offset("-16", 10, 4096), Ok(0));
+        assert_eq!(parse_offset("+16", 10, 4096), Ok(26));
+        assert_eq!(parse_offset("99999", 0, 4096), Ok(4095));
+        assert!(parse_offset("x", 0, 10).is_err());
+    }
+
+    #[test]
+    fn test_find() {
+        let data = b"abcXYabcXYabc".to_vec();
+        let len = data.len() as u64;
+        let mut source = Cursor::new(data);
+        assert_eq!(find(&mut source, len, b"XY", 0, true).unwrap(), Some(3));
+        assert_eq!(find(&mut source, len, b"XY", 3, true).unwrap(), Some(8));
+        // Wraps around to the start
+        assert_eq!(find(&mut source, len, b"XY", 8, true).unwrap(), Some(3));
+        assert_eq!(find(&mut source, len, b"XY", 8, false).unwrap(), Some(3));
+        assert_eq!(find(&mut source, len, b"XY", 3, false).unwrap(), Some(8));
+        assert_eq!(find(&mut source, len, b"zz", 0, true).unwrap(), None);
+    }
+}
@@ -0,0 +1,222 @@
+//! Image previews generated in the background and cached.
+//!
+//! Decoding a large photo takes long enough to make a file list stutter, so
+//! [`ImagePreviews::preview`] never decodes on the caller's thread. It
+//! returns a cached preview, or a "Rendering…" placeholder while one worker
+//! thread generates it; the tool calls [`ImagePreviews::poll`] on every tick
+//! and shows the preview once it is ready.
+//!
+//! The worker only renders the newest request: moving quickly through a
+//! directory of photos skips the ones passed over instead of decoding each.
+//!
+//! Previews are keyed by path, modification time, size, the way they are
+//! drawn and the pane size they fill, so an edited file or a resized pane
+//! gets a fresh one. The last [`MEMORY_LIMIT`] are kept in memory and every
+//! one is written to `~/.cache/terminal-tools/images/`, which makes the second visit to
+//! a directory instant even in a new run.
+
+use crate::image_preview::{self, TextArt};
+use crate::platform;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::UNIX_EPOCH,
+};
+
+/// Previews kept in memory
+pub const MEMORY_LIMIT: usize = 64;
+
+/// One generated preview: the file as it is now and how it was drawn
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    path: PathBuf,
+    /// Modification time in nanoseconds since the Unix epoch
+    modified: u128,
+    size: u64,
+    art: TextArt,
+    /// Cells the preview fills
+    columns: u16,
+    rows: u16,
+}
+
+impl CacheKey {
+    fn of(path: &Path, columns: u16, rows: u16) -> Option<CacheKey> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+        Some(CacheKey {
+            path: path.to_path_buf(),
+            modified,
+            size: metadata.len(),
+            art: image_preview::text_art(),
+            columns,
+            rows,
+        })
+    }
+
+    /// File the preview is stored in on disk
+    fn cache_file(&self) -> Option<PathBuf> {
+        let key = format!(
+            "{}\0{}\0{}\0{:?}\0{}x{}",
+            self.path.display(),
+            self.modified,
+            self.size,
+            self.art,
+            self.columns,
+            self.rows
+        );
+        platform::cache_dir().map(|dir| dir.join("images").join(format!("{:016x}.txt", fnv1a(key.as_bytes()))))
+    }
+}
+
+/// 64-bit FNV-1a, stable across runs and Rust versions unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3))
+}
+
+/// Background generator and cache of image previews for one tool
+#[derive(Debug)]
+pub struct ImagePreviews {
+    memory: HashMap<CacheKey, String>,
+    /// Keys in `memory`, oldest first
+    order: VecDeque<CacheKey>,
+    pending: HashSet<CacheKey>,
+    /// Requests for the worker, started with the first one
+    jobs: Option<Sender<CacheKey>>,
+    results_tx: Sender<(CacheKey, Option<String>)>,
+    results_rx: Receiver<(CacheKey, Option<String>)>,
+}
+
+impl Default for ImagePreviews {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImagePreviews {
+    pub fn new() -> Self {
+        let (results_tx, results_rx) = mpsc::channel();
+        ImagePreviews {
+            memory: HashMap::new(),
+            order: VecDeque::new(),
+            pending: HashSet::new(),
+            jobs: None,
+            results_tx,
+            results_rx,
+        }
+    }
+
+    /// The preview of the image at `path` filling `columns` x `rows` cells if
+    /// it is ready, else a placeholder while it is generated
+    pub fn preview(&mut self, path: &Path, columns: u16, rows: u16) -> String {
+        let placeholder = format!(
+            "🖼️ Image: {}\n⏳ Rendering…",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        if columns == 0 || rows == 0 {
+            // Not laid out yet
+            return placeholder;
+        }
+        let Some(key) = CacheKey::of(path, columns, rows) else {
+            // Unreadable: fails fast with the reason
+            return image_preview::generate_image_preview(path, columns, rows);
+        };
+        if let Some(preview) = self.memory.get(&key) {
+            return preview.clone();
+        }
+        if self.pending.insert(key.clone()) {
+            let results = self.results_tx.clone();
+            let jobs = self.jobs.get_or_insert_with(|| {
+                let (tx, rx) = mpsc::channel();
+                thread::spawn(move || work(rx, results));
+                tx
+            });
+            let _ = jobs.send(key);
+        }
+        placeholder
+    }
+
+    /// Collect the previews finished since the last call; returns whether
+    /// there were any
+    pub fn poll(&mut self) -> bool {
+        let mut finished = false;
+        while let Ok((key, preview)) = self.results_rx.try_recv() {
+            self.pending.remove(&key);
+            if let Some(preview) = preview {
+                self.remember(key, preview);
+                finished = true;
+            }
+        }
+        finished
+    }
+
+    fn remember(&mut self, key: CacheKey, preview: String) {
+        if self.order.len() == MEMORY_LIMIT {
+            if let Some(oldest) = self.order.pop_front() {
+                self.memory.remove(&oldest);
+            }
+        }
+        self.order.push_back(key.clone());
+        self.memory.insert(key, preview);
+    }
+}
+
+/// Render the newest request, answering skipped ones with `None`
+fn work(jobs: Receiver<CacheKey>, results: Sender<(CacheKey, Option<String>)>) {
+    while let Ok(mut key) = jobs.recv() {
+        while let Ok(newer) = jobs.try_recv() {
+            let _ = results.send((key, None));
+            key = newer;
+        }
+        let preview = load_or_generate(&key);
+        if results.send((key, Some(preview))).is_err() {
+            return;
+        }
+    }
+}
+
+fn load_or_generate(key: &CacheKey) -> String {
+    let file = key.cache_file();
+    if let Some(preview) = file.as_ref().and_then(|file| fs::read_to_string(file).ok()) {
+        return preview;
+    }
+    let preview = image_preview::generate_image_preview(&key.path, key.columns, key.rows);
+    if let Some(file) = file {
+        // Best effort: without a cache the preview is just generated again
+        if let Some(dir) = file.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = fs::write(file, &preview);
+    }
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys() {
+        let key = |modified| CacheKey {
+            path: PathBuf::from("/photos/a.jpg"),
+            modified,
+            size: 10,
+            art: TextArt::Ascii,
+            columns: 40,
+            rows: 20,
+        };
+        assert_ne!(key(1).cache_file(), key(2).cache_file());
+        assert_ne!(key(1).cache_file(), CacheKey { rows: 21, ..key(1) }.cache_file());
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+
+        let mut previews = ImagePreviews::new();
+        for i in 0..=MEMORY_LIMIT as u128 {
+            previews.remember(key(i), i.to_string());
+        }
+        assert_eq!(previews.memory.len(), MEMORY_LIMIT);
+        assert!(!previews.memory.contains_key(&key(0)));
+        assert_eq!(previews.memory[&key(1)], "1");
+    }
+}
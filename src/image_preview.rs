@@ -5,8 +5,12 @@
 //!
 //! ## Features
 //!
-//! - **Format Support**: JPG, PNG, GIF, BMP (conservative format selection for stability)
+//! - **Format Support**: JPG, PNG, GIF, BMP, WebP, TIFF, ICO and SVG; SVGs are
+//!   rasterized with resvg, animated GIFs show their first frame and the
+//!   number of frames
 //! - **ASCII Art Generation**: Converts images to text representation using grayscale mapping
+//! - **Photo Details**: camera, capture time and GPS position from EXIF data,
+//!   with the EXIF orientation applied so rotated phone photos show upright
 //! - **Safety Checks**: Handles large, corrupted, or invalid images gracefully
 //! - **Performance**: Optimized for terminal display with reasonable size limits
 //!
@@ -36,33 +40,603 @@
 //! ## ASCII Art Generation
 //!
 //! Images are converted to ASCII art using:
-//! 1. Resize to terminal-appropriate dimensions (40x15)
+//! 1. Resize to fill the preview pane below the details, keeping the aspect
+//!    ratio; a cell is about twice as tall as it is wide (the exact ratio
+//!    comes from the terminal's pixel size when it reports one)
 //! 2. Convert to grayscale using standard RGB weights
 //! 3. Map grayscale values to ASCII characters (" .:-=+*#%@")
 //! 4. Generate text representation suitable for terminal display
+//!
+//! ## Half-Block Rendering
+//!
+//! Terminals with 24-bit color (`COLORTERM=truecolor` or `24bit`) get the
+//! image in color instead: every cell shows two pixels, the upper one as the
+//! foreground of `▀` and the lower one as its background. That is twice the
+//! rows of ASCII art, each pixel in its own color. The colors are SGR escape
+//! sequences in the preview text, which [`crate::ansi`] turns into styles.
+//!
+//! ## Graphics Protocols
+//!
+//! Terminals that can display real pixels get a thumbnail instead of ASCII
+//! art. [`detect_protocol`] picks one from the environment:
+//!
+//! - **Kitty** graphics protocol: kitty, Ghostty
+//! - **iTerm2** inline images: iTerm2, WezTerm
+//! - **Sixel**: foot, mlterm, contour and any `TERM` mentioning `sixel`
+//!
+//! When the environment names none of these, the terminal itself is asked:
+//! a Kitty graphics query, answered `OK` by terminals that implement the
+//! protocol, and the device attributes, which list `4` when Sixel works.
+//! Inside tmux or screen no protocol is used, since they drop the escapes
+//! unless passthrough is configured.
+//!
+//! The choice can be made instead of detected, as `auto`, `kitty`,
+//! `iterm2`, `sixel`, `blocks` (half blocks, never real images) or `ascii`:
+//! `--ascii-only` wins over `TT_GRAPHICS`, which wins over `graphics` in
+//! `config.toml` (see [`configure`]).
+//! [`encode_graphics`] produces the escape sequence for an image scaled to
+//! fit a block of terminal cells; the caller positions the cursor and writes
+//! it after drawing the frame.
+
+use crate::platform;
+use std::{env, error::Error, fmt::Write, io::{self, Cursor}, path::Path, sync::OnceLock};
+use image::{DynamicImage, GenericImageView, ImageFormat, RgbImage};
+
+/// Graphics choices accepted by `TT_GRAPHICS` and `config.toml`
+pub const GRAPHICS_NAMES: &[&str] = &["auto", "kitty", "iterm2", "sixel", "blocks", "ascii"];
+
+/// Terminal image protocol used for real thumbnails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+}
+
+/// How images are drawn as text, when no graphics protocol is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextArt {
+    /// Grayscale characters
+    Ascii,
+    /// Colored `▀`, two pixels per cell
+    HalfBlocks,
+}
+
+/// Kitty command deleting every image placed on screen
+pub const KITTY_CLEAR: &str = "\x1b_Ga=d,d=A,q=2\x1b\\";
+
+/// Cell size assumed when the terminal does not report its pixel size
+const DEFAULT_CELL_SIZE: (u32, u32) = (8, 16);
+
+/// Base64 chunk size allowed per Kitty escape sequence
+const KITTY_CHUNK: usize = 4096;
+
+/// Kitty graphics query for a 1x1 image that is never stored; terminals
+/// with the protocol reply `ESC _ G i=31;OK`
+const KITTY_QUERY: &[u8] = b"\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\";
+
+static CHOICE: OnceLock<String> = OnceLock::new();
+static DETECTED: OnceLock<Option<GraphicsProtocol>> = OnceLock::new();
+
+/// Pick how images are drawn: `ascii` when `ascii_only`, else `TT_GRAPHICS`,
+/// else `setting` (from `config.toml`), else `auto`; checked here so
+/// mistakes are reported before any tool starts
+pub fn configure(ascii_only: bool, setting: Option<&str>) -> io::Result<()> {
+    let choice = if ascii_only {
+        "ascii".to_string()
+    } else {
+        env::var("TT_GRAPHICS")
+            .ok()
+            .filter(|choice| !choice.is_empty())
+            .or_else(|| setting.map(str::to_string))
+            .unwrap_or_else(|| "auto".to_string())
+            .to_lowercase()
+    };
+    if !GRAPHICS_NAMES.contains(&choice.as_str()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown graphics '{}' (expected one of: {})", choice, GRAPHICS_NAMES.join(", ")),
+        ));
+    }
+    let _ = CHOICE.set(choice);
+    Ok(())
+}
+
+/// Graphics protocol to draw images with, `None` for ASCII art; detected
+/// once per run, which may query the terminal, so the first call must
+/// happen before the TUI starts
+pub fn detect_protocol() -> Option<GraphicsProtocol> {
+    *DETECTED.get_or_init(|| match CHOICE.get().map_or("auto", String::as_str) {
+        "kitty" => Some(GraphicsProtocol::Kitty),
+        "iterm2" => Some(GraphicsProtocol::Iterm2),
+        "sixel" => Some(GraphicsProtocol::Sixel),
+        "ascii" | "blocks" => None,
+        _ => protocol_from_env(|key| env::var(key).ok())
+            .unwrap_or_else(|| platform::query_terminal(KITTY_QUERY).and_then(|reply| protocol_from_reply(&reply))),
+    })
+}
+
+/// How images are drawn as text: half blocks when chosen, or by default on
+/// terminals with 24-bit color
+pub fn text_art() -> TextArt {
+    text_art_for(CHOICE.get().map_or("auto", String::as_str), env::var("COLORTERM").ok().as_deref())
+}
+
+fn text_art_for(choice: &str, colorterm: Option<&str>) -> TextArt {
+    match choice {
+        "ascii" => TextArt::Ascii,
+        "blocks" => TextArt::HalfBlocks,
+        _ if matches!(colorterm, Some("truecolor" | "24bit")) => TextArt::HalfBlocks,
+        _ => TextArt::Ascii,
+    }
+}
+
+/// What the environment tells about graphics support: a protocol, `Some(None)`
+/// when it rules graphics out, or `None` when it can't tell
+fn protocol_from_env(var: impl Fn(&str) -> Option<String>) -> Option<Option<GraphicsProtocol>> {
+    if var("TMUX").is_some() || var("STY").is_some() {
+        return Some(None);
+    }
+
+    let term = var("TERM").unwrap_or_default();
+    let program = var("TERM_PROGRAM").unwrap_or_default();
+    if var("KITTY_WINDOW_ID").is_some() || term == "xterm-kitty" || term == "xterm-ghostty" || program == "ghostty" {
+        Some(Some(GraphicsProtocol::Kitty))
+    } else if program == "iTerm.app" || program == "WezTerm" || var("LC_TERMINAL").as_deref() == Some("iTerm2") {
+        Some(Some(GraphicsProtocol::Iterm2))
+    } else if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") || program == "contour" {
+        Some(Some(GraphicsProtocol::Sixel))
+    } else if term == "dumb" || term == "linux" {
+        Some(None)
+    } else {
+        None
+    }
+}
+
+/// Protocol supported according to the terminal's replies to [`KITTY_QUERY`]
+/// and the device attributes query (`ESC [ ? 62 ; 4 ; ... c`)
+fn protocol_from_reply(reply: &str) -> Option<GraphicsProtocol> {
+    if reply.contains("\x1b_Gi=31;OK") {
+        return Some(GraphicsProtocol::Kitty);
+    }
+    let start = reply.rfind("\x1b[?")? + 3;
+    let attributes = &reply[start..start + reply[start..].find('c')?];
+    attributes.split(';').any(|attribute| attribute == "4").then_some(GraphicsProtocol::Sixel)
+}
+
+/// Name and dimensions of an image, shown above a graphics-protocol thumbnail
+pub fn image_details(path: &Path) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let dimensions = if is_svg(path) {
+        svg_tree(path).map(|tree| (tree.size.width().round() as u32, tree.size.height().round() as u32))
+    } else {
+        image::image_dimensions(path).map_err(Into::into)
+    };
+    let photo = photo_info(path);
+    match dimensions {
+        Ok((width, height)) => {
+            // Sideways orientations swap the sides as displayed
+            let (width, height) = if photo.as_ref().is_some_and(|photo| photo.orientation >= 5) {
+                (height, width)
+            } else {
+                (width, height)
+            };
+            let mut details = format!("🖼️ Image: {}\n📐 Dimensions: {}x{}", name, width, height);
+            if let Some(frames) = animation_frames(path) {
+                details.push_str(&frames_line(frames));
+            }
+            if let Some(photo) = photo {
+                details.push_str(&photo.lines());
+            }
+            details
+        }
+        Err(e) => format!("🖼️ Image file: {}\n❌ Error loading image: {}", name, e),
+    }
+}
+
+/// Largest side of a rasterized SVG, in pixels
+const SVG_SIZE: f32 = 1024.0;
+
+/// Decode the image at `path`: the first frame of an animation, photos
+/// turned upright, and SVGs rasterized to fit [`SVG_SIZE`]; also returns the
+/// size the image declares
+pub fn load_image(path: &Path) -> Result<(DynamicImage, (u32, u32)), Box<dyn Error>> {
+    if !is_svg(path) {
+        let mut img = image::open(path)?;
+        if let Some(photo) = photo_info(path) {
+            img = apply_orientation(img, photo.orientation);
+        }
+        let dimensions = img.dimensions();
+        return Ok((img, dimensions));
+    }
+
+    let tree = svg_tree(path)?;
+    let (width, height) = (tree.size.width(), tree.size.height());
+    let scale = SVG_SIZE / width.max(height);
+    let (columns, rows) = ((width * scale).round().max(1.0) as u32, (height * scale).round().max(1.0) as u32);
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(columns, rows).ok_or("SVG has zero dimensions")?;
+    resvg::render(&tree, resvg::tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    // tiny-skia stores premultiplied alpha
+    let pixels = pixmap.pixels().iter().flat_map(|pixel| {
+        let color = pixel.demultiply();
+        [color.red(), color.green(), color.blue(), color.alpha()]
+    });
+    let img = image::RgbaImage::from_raw(columns, rows, pixels.collect()).ok_or("SVG could not be rasterized")?;
+    Ok((DynamicImage::ImageRgba8(img), (width.round() as u32, height.round() as u32)))
+}
+
+fn is_svg(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("svg") || ext.eq_ignore_ascii_case("svgz"))
+}
+
+/// Parsed SVG, ready to render; text is left out, as that needs fonts
+fn svg_tree(path: &Path) -> Result<resvg::usvg::Tree, Box<dyn Error>> {
+    use resvg::usvg::{PostProcessingSteps, TreeParsing, TreePostProc};
+
+    let data = std::fs::read(path)?;
+    let mut tree = resvg::usvg::Tree::from_data(&data, &resvg::usvg::Options::default())?;
+    tree.postprocess(PostProcessingSteps::default());
+    Ok(tree)
+}
+
+/// Camera, capture time, position and orientation from a photo's EXIF data
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhotoInfo {
+    pub camera: Option<String>,
+    /// As `YYYY-MM-DD HH:MM:SS`, in the camera's local time
+    pub taken: Option<String>,
+    /// Latitude and longitude in degrees, negative to the south and west
+    pub location: Option<(f64, f64)>,
+    /// EXIF orientation, from 1 (upright) to 8
+    pub orientation: u32,
+}
+
+impl PhotoInfo {
+    /// Lines for the preview header, each starting with a line break
+    fn lines(&self) -> String {
+        let mut lines = String::new();
+        if let Some(camera) = &self.camera {
+            let _ = write!(lines, "\n📷 Camera: {}", camera);
+        }
+        if let Some(taken) = &self.taken {
+            let _ = write!(lines, "\n🕒 Taken: {}", taken);
+        }
+        if let Some((latitude, longitude)) = self.location {
+            let _ = write!(lines, "\n📍 Location: {}", format_location(latitude, longitude));
+        }
+        if self.orientation != 1 {
+            let _ = write!(lines, "\n🔄 Orientation: {}", orientation_name(self.orientation));
+        }
+        lines
+    }
+}
+
+/// EXIF data of the photo at `path`, if it has any (JPEG, TIFF, PNG, WebP)
+fn photo_info(path: &Path) -> Option<PhotoInfo> {
+    use exif::{In, Tag, Value};
+
+    let file = std::fs::File::open(path).ok()?;
+    let exif = exif::Reader::new().read_from_container(&mut io::BufReader::new(file)).ok()?;
+    let text = |tag| match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(parts) => {
+            let text = String::from_utf8_lossy(parts.first()?).trim_end_matches('\0').trim().to_string();
+            (!text.is_empty()).then_some(text)
+        }
+        _ => None,
+    };
+    let degrees = |tag, reference| match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(dms) if dms.len() == 3 => {
+            Some(to_degrees([dms[0].to_f64(), dms[1].to_f64(), dms[2].to_f64()], &text(reference)?))
+        }
+        _ => None,
+    };
+
+    Some(PhotoInfo {
+        camera: camera_name(text(Tag::Make), text(Tag::Model)),
+        taken: text(Tag::DateTimeOriginal).or_else(|| text(Tag::DateTime)).map(|taken| format_exif_date(&taken)),
+        location: degrees(Tag::GPSLatitude, Tag::GPSLatitudeRef).zip(degrees(Tag::GPSLongitude, Tag::GPSLongitudeRef)),
+        orientation: exif
+            .get_field(Tag::Orientation, In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0))
+            .filter(|orientation| (1..=8).contains(orientation))
+            .unwrap_or(1),
+    })
+}
+
+/// Make and model as one name; most models already start with the make
+fn camera_name(make: Option<String>, model: Option<String>) -> Option<String> {
+    match (make, model) {
+        (Some(make), Some(model)) if model.to_lowercase().starts_with(&make.to_lowercase()) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => make.or(model),
+    }
+}
+
+/// `2023:06:01 14:22:05` as `2023-06-01 14:22:05`
+fn format_exif_date(date: &str) -> String {
+    match date.split_once(' ') {
+        Some((day, time)) => format!("{} {}", day.replace(':', "-"), time),
+        None => date.replace(':', "-"),
+    }
+}
+
+/// Degrees, minutes and seconds with an `N`/`S`/`E`/`W` reference as signed
+/// degrees
+fn to_degrees([degrees, minutes, seconds]: [f64; 3], reference: &str) -> f64 {
+    let value = degrees + minutes / 60.0 + seconds / 3600.0;
+    if reference == "S" || reference == "W" {
+        -value
+    } else {
+        value
+    }
+}
+
+fn format_location(latitude: f64, longitude: f64) -> String {
+    format!(
+        "{:.5}° {}, {:.5}° {}",
+        latitude.abs(),
+        if latitude < 0.0 { "S" } else { "N" },
+        longitude.abs(),
+        if longitude < 0.0 { "W" } else { "E" }
+    )
+}
+
+fn orientation_name(orientation: u32) -> &'static str {
+    match orientation {
+        2 => "mirrored",
+        3 => "rotated 180°",
+        4 => "upside down, mirrored",
+        5 => "rotated 90° clockwise, mirrored",
+        6 => "rotated 90° clockwise",
+        7 => "rotated 90° counter-clockwise, mirrored",
+        8 => "rotated 90° counter-clockwise",
+        _ => "upright",
+    }
+}
+
+/// Turn an image stored with EXIF `orientation` upright
+fn apply_orientation(img: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+/// Number of frames of an animated GIF; `None` for other files and for GIFs
+/// with a single frame
+fn animation_frames(path: &Path) -> Option<usize> {
+    if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gif")) {
+        return None;
+    }
+    gif_frame_count(&std::fs::read(path).ok()?).filter(|&frames| frames > 1)
+}
+
+fn frames_line(frames: usize) -> String {
+    format!("\n🎞️ Frames: {} (showing the first)", frames)
+}
+
+/// Count the images in GIF data by walking its blocks, without decoding
+fn gif_frame_count(data: &[u8]) -> Option<usize> {
+    if !data.starts_with(b"GIF") || data.len() < 13 {
+        return None;
+    }
+    let color_table = |packed: u8| if packed & 0x80 != 0 { 3 << ((packed & 7) + 1) } else { 0 };
+    // Skips data sub-blocks (a length byte, then that many bytes) up to the
+    // empty one ending them; returns the position after it
+    let skip_sub_blocks = |mut at: usize| loop {
+        let len = *data.get(at)? as usize;
+        at += 1 + len;
+        if len == 0 {
+            return Some(at);
+        }
+    };
+
+    let mut at = 13 + color_table(data[10]);
+    let mut frames = 0;
+    loop {
+        match data.get(at)? {
+            // Extension: introducer, label, sub-blocks
+            0x21 => at = skip_sub_blocks(at + 2)?,
+            // Image descriptor: 9 bytes, local color table, LZW code size
+            0x2c => {
+                let packed = *data.get(at + 9)?;
+                frames += 1;
+                at = skip_sub_blocks(at + 10 + color_table(packed) + 1)?;
+            }
+            // Trailer
+            0x3b => return Some(frames),
+            _ => return (frames > 0).then_some(frames),
+        }
+    }
+}
+
+/// Pixel size of one terminal cell
+fn cell_size() -> (u32, u32) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns).max(1) as u32,
+            (size.height / size.rows).max(1) as u32,
+        ),
+        _ => DEFAULT_CELL_SIZE,
+    }
+}
+
+/// Cells (columns, rows) an image occupies when scaled down to fit
+/// `columns` x `rows` cells, keeping its aspect ratio
+fn fit_cells(image: (u32, u32), columns: u16, rows: u16, cell: (u32, u32)) -> (u16, u16) {
+    let (width, height) = (image.0.max(1) as f64, image.1.max(1) as f64);
+    let box_width = columns as f64 * cell.0 as f64;
+    let box_height = rows as f64 * cell.1 as f64;
+    let scale = (box_width / width).min(box_height / height).min(1.0);
+    let fit_columns = ((width * scale) / cell.0 as f64).ceil() as u16;
+    let fit_rows = ((height * scale) / cell.1 as f64).ceil() as u16;
+    (fit_columns.clamp(1, columns.max(1)), fit_rows.clamp(1, rows.max(1)))
+}
+
+/// Escape sequence drawing the image at `path` within `columns` x `rows`
+/// cells, starting at the cursor
+pub fn encode_graphics(
+    path: &Path,
+    protocol: GraphicsProtocol,
+    columns: u16,
+    rows: u16,
+) -> Result<String, Box<dyn Error>> {
+    let (img, _) = load_image(path)?;
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return Err("Image has zero dimensions".into());
+    }
+    if width > 10000 || height > 10000 {
+        return Err("Image too large for preview".into());
+    }
+
+    let cell = cell_size();
+    let (fit_columns, fit_rows) = fit_cells((width, height), columns, rows, cell);
+    let thumbnail = img.thumbnail(fit_columns as u32 * cell.0, fit_rows as u32 * cell.1);
+
+    match protocol {
+        GraphicsProtocol::Kitty => {
+            let mut png = Vec::new();
+            thumbnail.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+            let data = base64(&png);
+            let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK).collect();
+            let mut out = String::new();
+            for (index, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(index + 1 < chunks.len());
+                let chunk = std::str::from_utf8(chunk)?;
+                if index == 0 {
+                    write!(out, "\x1b_Ga=T,f=100,q=2,C=1,c={},r={},m={};{}\x1b\\", fit_columns, fit_rows, more, chunk)?;
+                } else {
+                    write!(out, "\x1b_Gm={};{}\x1b\\", more, chunk)?;
+                }
+            }
+            Ok(out)
+        }
+        GraphicsProtocol::Iterm2 => {
+            let mut png = Vec::new();
+            thumbnail.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+            Ok(format!(
+                "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+                png.len(),
+                fit_columns,
+                fit_rows,
+                base64(&png)
+            ))
+        }
+        GraphicsProtocol::Sixel => Ok(encode_sixel(&thumbnail.to_rgb8())),
+    }
+}
+
+/// Standard base64 with padding, for the image protocols and OSC 52
+/// clipboard writes
+pub fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let n = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Encode an image as Sixel using a 6x6x6 color cube palette
+fn encode_sixel(img: &RgbImage) -> String {
+    let (width, height) = img.dimensions();
+    let mut out = format!("\x1bPq\"1;1;{};{}", width, height);
+    for color in 0..216 {
+        let _ = write!(out, "#{};2;{};{};{}", color, color / 36 * 20, color / 6 % 6 * 20, color % 6 * 20);
+    }
 
-use std::path::Path;
-use image::GenericImageView;
+    let level = |c: u8| (c as usize * 5 + 127) / 255;
+    let indices: Vec<usize> = img.pixels().map(|p| level(p[0]) * 36 + level(p[1]) * 6 + level(p[2])).collect();
+    let at = |x: u32, y: u32| indices[(y * width + x) as usize];
+
+    for band in (0..height).step_by(6) {
+        let band_rows = (height - band).min(6);
+        let mut used = [false; 216];
+        for y in band..band + band_rows {
+            for x in 0..width {
+                used[at(x, y)] = true;
+            }
+        }
+
+        let mut first = true;
+        for color in (0..216).filter(|&color| used[color]) {
+            if !first {
+                // Return to the start of the band for the next color
+                out.push('$');
+            }
+            first = false;
+            let _ = write!(out, "#{}", color);
+
+            let mut run: Option<(char, usize)> = None;
+            for x in 0..width {
+                let bits = (0..band_rows)
+                    .filter(|&dy| at(x, band + dy) == color)
+                    .fold(0u8, |bits, dy| bits | 1 << dy);
+                let sixel = (63 + bits) as char;
+                run = match run {
+                    Some((c, len)) if c == sixel => Some((c, len + 1)),
+                    Some((c, len)) => {
+                        push_sixel_run(&mut out, c, len);
+                        Some((sixel, 1))
+                    }
+                    None => Some((sixel, 1)),
+                };
+            }
+            if let Some((c, len)) = run {
+                push_sixel_run(&mut out, c, len);
+            }
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Append a run of identical sixels, run-length encoded when worthwhile
+fn push_sixel_run(out: &mut String, c: char, len: usize) {
+    if len > 3 {
+        let _ = write!(out, "!{}{}", len, c);
+    } else {
+        out.extend(std::iter::repeat_n(c, len));
+    }
+}
 
 /// Check if a file is a supported image format
 pub fn is_image_file(path: &Path) -> bool {
     if let Some(extension) = path.extension() {
         let ext = extension.to_string_lossy().to_lowercase();
-        // Be more conservative with supported formats to avoid issues
-        matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "gif" | "bmp")
+        matches!(
+            ext.as_str(),
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "tif" | "tiff" | "ico" | "svg" | "svgz"
+        )
     } else {
         false
     }
 }
 
-/// Generate image preview text for terminal display
-pub fn generate_image_preview(path: &Path) -> String {
+/// Generate image preview text for terminal display, fitting `columns` x
+/// `rows` cells
+pub fn generate_image_preview(path: &Path, columns: u16, rows: u16) -> String {
     // Add a panic handler to catch any issues
     std::panic::catch_unwind(|| {
         // Try to get image metadata first
-        match image::open(path) {
-            Ok(img) => {
-                let (width, height) = img.dimensions();
+        match load_image(path) {
+            Ok((img, (width, height))) => {
                 
                 // Additional safety check for very large images
                 if width > 50000 || height > 50000 {
@@ -80,10 +654,23 @@ pub fn generate_image_preview(path: &Path) -> String {
                     path.file_name().unwrap_or_default().to_string_lossy()
                 );
                 preview.push_str(&format!("📐 Dimensions: {}x{}\n", width, height));
-                preview.push_str(&format!("🎨 Channels: {}\n", format));
+                preview.push_str(&format!("🎨 Channels: {}", format));
+                if let Some(frames) = animation_frames(path) {
+                    preview.push_str(&frames_line(frames));
+                }
+                if let Some(photo) = photo_info(path) {
+                    preview.push_str(&photo.lines());
+                }
+                preview.push('\n');
+                
+                // The art goes below the details and its heading
+                let art_rows = (rows as usize).saturating_sub(preview.lines().count() + 2);
+                if art_rows < 2 {
+                    return preview;
+                }
                 
                 // Try to render a small terminal preview, but don't fail the whole preview if it doesn't work
-                match render_image_to_terminal(path) {
+                match render_image_to_terminal(&img, columns as u32, art_rows as u32) {
                     Ok(terminal_preview) => {
                         if !terminal_preview.trim().is_empty() {
                             preview.push_str("\n📺 Terminal Preview:\n");
@@ -114,42 +701,39 @@ pub fn generate_image_preview(path: &Path) -> String {
     })
 }
 
-/// Render image to terminal using viuer
-fn render_image_to_terminal(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
-    use viuer::Config;
+/// Render a decoded image as text art as large as fits `columns` x `rows`
+/// cells
+pub fn render_image_to_terminal(img: &DynamicImage, columns: u32, rows: u32) -> Result<String, Box<dyn std::error::Error>> {
+    let (width, height) = img.dimensions();
     
-    // Create a configuration for small terminal preview
-    let _config = Config {
-        // Make it small to fit in preview pane
-        width: Some(40),
-        height: Some(20),
-        absolute_offset: false,
-        ..Default::default()
-    };
+    // Validate image dimensions before processing
+    if width == 0 || height == 0 {
+        return Err("Image has zero dimensions".into());
+    }
     
-    // Capture the viuer output
-    // Note: viuer prints directly to terminal, so we'll return a placeholder
-    // In a real implementation, we'd need to capture the ANSI output
-    match image::open(path) {
-        Ok(img) => {
-            let (_width, _height) = img.dimensions();
-            
-            // Validate image dimensions before processing
-            if _width == 0 || _height == 0 {
-                return Err("Image has zero dimensions".into());
-            }
-            
-            if _width > 10000 || _height > 10000 {
-                return Err("Image too large for preview".into());
-            }
-            
-            // For now, return ASCII art representation
-            generate_ascii_preview(&img, 40, 15)
-        }
-        Err(e) => Err(e.into()),
+    if width > 10000 || height > 10000 {
+        return Err("Image too large for preview".into());
+    }
+    
+    let cell = cell_size();
+    let (columns, rows) = text_art_cells((width, height), columns, rows, cell.1 as f64 / cell.0 as f64);
+    match text_art() {
+        TextArt::Ascii => generate_ascii_preview(img, columns, rows),
+        TextArt::HalfBlocks => Ok(generate_half_block_preview(img, columns, rows)),
     }
 }
 
+/// Cells (columns, rows) text art of an image takes when scaled to fill
+/// `columns` x `rows` cells that are `aspect` times as tall as wide, keeping
+/// its aspect ratio; small images are scaled up
+fn text_art_cells(image: (u32, u32), columns: u32, rows: u32, aspect: f64) -> (u32, u32) {
+    let (width, height) = (image.0.max(1) as f64, image.1.max(1) as f64);
+    let scale = (columns as f64 / width).min(rows as f64 * aspect / height);
+    let fit_columns = (width * scale).round() as u32;
+    let fit_rows = (height * scale / aspect).round() as u32;
+    (fit_columns.clamp(1, columns.max(1)), fit_rows.clamp(1, rows.max(1)))
+}
+
 /// Generate simple ASCII art preview
 fn generate_ascii_preview(
     img: &image::DynamicImage, 
@@ -159,11 +743,12 @@ fn generate_ascii_preview(
     use image::imageops::FilterType;
     
     // Ensure reasonable dimensions to prevent issues
-    let safe_width = target_width.min(200).max(1);
-    let safe_height = target_height.min(100).max(1);
+    let safe_width = target_width.clamp(1, 200);
+    let safe_height = target_height.clamp(1, 100);
     
-    // Resize image to target dimensions
-    let resized = img.resize(safe_width, safe_height, FilterType::Nearest);
+    // Resize image to target dimensions; one character per cell, so the
+    // caller has already corrected for the cell aspect ratio
+    let resized = img.resize_exact(safe_width, safe_height, FilterType::Nearest);
     let rgb_img = resized.to_rgb8();
     
     let mut ascii_art = String::new();
@@ -204,11 +789,55 @@ fn generate_ascii_preview(
     Ok(ascii_art)
 }
 
+/// Colored preview of `columns` x `rows` cells, two pixels per cell;
+/// transparent pixels leave the terminal background showing
+fn generate_half_block_preview(img: &image::DynamicImage, columns: u32, rows: u32) -> String {
+    use image::imageops::FilterType;
+
+    let resized = img.resize_exact(columns.clamp(1, 200), rows.clamp(1, 100) * 2, FilterType::Triangle).to_rgba8();
+    let (width, height) = resized.dimensions();
+    let pixel = |x: u32, y: u32| {
+        (y < height).then(|| resized.get_pixel(x, y).0).filter(|[_, _, _, alpha]| *alpha >= 128)
+    };
+
+    let mut out = String::new();
+    for y in (0..height).step_by(2) {
+        // Colors already set on this line, to skip repeating them
+        let (mut fg, mut bg) = (None, None);
+        for x in 0..width {
+            let (glyph, top, bottom) = match (pixel(x, y), pixel(x, y + 1)) {
+                (Some(top), bottom) => ('▀', Some(top), bottom),
+                (None, Some(bottom)) => ('▄', Some(bottom), None),
+                (None, None) => (' ', None, None),
+            };
+            let top = top.map(|[r, g, b, _]| (r, g, b));
+            let bottom = bottom.map(|[r, g, b, _]| (r, g, b));
+            if glyph != ' ' && top != fg {
+                if let Some((r, g, b)) = top {
+                    let _ = write!(out, "\x1b[38;2;{};{};{}m", r, g, b);
+                }
+                fg = top;
+            }
+            if bottom != bg {
+                match bottom {
+                    Some((r, g, b)) => {
+                        let _ = write!(out, "\x1b[48;2;{};{};{}m", r, g, b);
+                    }
+                    None => out.push_str("\x1b[49m"),
+                }
+                bg = bottom;
+            }
+            out.push(glyph);
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
 /// Get image info without rendering
 #[allow(dead_code)]
 pub fn get_image_info(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
-    let img = image::open(path)?;
-    let (width, height) = img.dimensions();
+    let (img, (width, height)) = load_image(path)?;
     
     Ok(format!(
         "🖼️  Image: {}\n📐 Size: {}x{}\n🎨 Format: {:?}",
@@ -232,6 +861,67 @@ mod tests {
         assert!(is_image_file(Path::new("test.gif")));
         assert!(is_image_file(Path::new("test.bmp")));
         assert!(is_image_file(Path::new("TEST.JPG"))); // case insensitive
+        assert!(is_image_file(Path::new("test.webp")));
+        assert!(is_image_file(Path::new("test.tiff")));
+        assert!(is_image_file(Path::new("favicon.ico")));
+        assert!(is_image_file(Path::new("logo.svg")));
+    }
+
+    #[test]
+    fn test_photo_info() {
+        let photo = PhotoInfo {
+            camera: camera_name(Some("Apple".to_string()), Some("iPhone 12".to_string())),
+            taken: Some(format_exif_date("2023:06:01 14:22:05")),
+            location: Some((to_degrees([48.0, 51.0, 29.1], "N"), to_degrees([2.0, 17.0, 40.2], "E"))),
+            orientation: 6,
+        };
+        assert_eq!(
+            photo.lines(),
+            "\n📷 Camera: Apple iPhone 12\n🕒 Taken: 2023-06-01 14:22:05\n📍 Location: 48.85808° N, 2.29450° E\n🔄 Orientation: rotated 90° clockwise"
+        );
+        assert_eq!(camera_name(Some("Canon".to_string()), Some("Canon EOS R5".to_string())).unwrap(), "Canon EOS R5");
+        assert_eq!(format_location(to_degrees([33.0, 52.0, 0.0], "S"), to_degrees([151.0, 12.0, 0.0], "E")), "33.86667° S, 151.20000° E");
+    }
+
+    #[test]
+    fn test_apply_orientation() {
+        // Stored sideways: 2 wide, 1 tall, left pixel white
+        let mut img = RgbImage::new(2, 1);
+        img.put_pixel(0, 0, image::Rgb([255, 255, 255]));
+        let upright = apply_orientation(DynamicImage::ImageRgb8(img.clone()), 6);
+        assert_eq!(upright.dimensions(), (1, 2));
+        assert_eq!(upright.get_pixel(0, 0).0, [255, 255, 255, 255]);
+        let upright = apply_orientation(DynamicImage::ImageRgb8(img), 8);
+        assert_eq!(upright.get_pixel(0, 1).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_gif_frame_count() {
+        // 1x1 with a two-color global table, then per frame a graphic
+        // control extension and an image with one data sub-block
+        let mut gif = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\x00\x00\x00\xff\xff\xff".to_vec();
+        let frame = b"\x21\xf9\x04\x00\x0a\x00\x00\x00\x2c\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00";
+        gif.extend_from_slice(frame);
+        assert_eq!(gif_frame_count(&[gif.as_slice(), b"\x3b"].concat()), Some(1));
+        gif.extend_from_slice(frame);
+        gif.extend_from_slice(frame);
+        assert_eq!(gif_frame_count(&[gif.as_slice(), b"\x3b"].concat()), Some(3));
+        // Truncated after the frames
+        assert_eq!(gif_frame_count(&gif), None);
+        assert_eq!(gif_frame_count(b"\x89PNG"), None);
+    }
+
+    #[test]
+    fn test_load_svg() {
+        let path = std::env::temp_dir().join(format!("tt-image-preview-{}.svg", std::process::id()));
+        std::fs::write(&path, r##"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10"><rect width="10" height="10" fill="#ff0000"/></svg>"##).unwrap();
+        let (img, dimensions) = load_image(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(dimensions, (20, 10));
+        assert_eq!(img.dimensions(), (1024, 512));
+        // Red on the left, transparent on the right
+        assert_eq!(img.get_pixel(10, 10).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(1000, 10).0[3], 0);
     }
 
     #[test]
@@ -252,12 +942,90 @@ mod tests {
 
     #[test]
     fn test_generate_image_preview_nonexistent_file() {
-        let preview = generate_image_preview(Path::new("nonexistent.jpg"));
+        let preview = generate_image_preview(Path::new("nonexistent.jpg"), 40, 20);
         assert!(preview.contains("🖼️ Image"));
         assert!(preview.contains("nonexistent.jpg"));
         assert!(preview.contains("Error loading image") || preview.contains("Panic occurred"));
     }
 
+    #[test]
+    fn test_protocol_from_env() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |key: &str| pairs.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+        };
+        assert_eq!(protocol_from_env(env(&[("TERM", "xterm-kitty")])), Some(Some(GraphicsProtocol::Kitty)));
+        assert_eq!(protocol_from_env(env(&[("TERM_PROGRAM", "iTerm.app")])), Some(Some(GraphicsProtocol::Iterm2)));
+        assert_eq!(protocol_from_env(env(&[("TERM", "foot")])), Some(Some(GraphicsProtocol::Sixel)));
+        assert_eq!(protocol_from_env(env(&[("TERM", "xterm-kitty"), ("TMUX", "1")])), Some(None));
+        // Unknown terminals are asked
+        assert_eq!(protocol_from_env(env(&[("TERM", "xterm-256color")])), None);
+    }
+
+    #[test]
+    fn test_protocol_from_reply() {
+        assert_eq!(protocol_from_reply("\x1b_Gi=31;OK\x1b\\\x1b[?62;22c"), Some(GraphicsProtocol::Kitty));
+        assert_eq!(protocol_from_reply("\x1b[?63;1;2;4;6;9;15;22c"), Some(GraphicsProtocol::Sixel));
+        assert_eq!(protocol_from_reply("\x1b[?62;1;22c"), None);
+        assert_eq!(protocol_from_reply(""), None);
+    }
+
+    #[test]
+    fn test_text_art_for() {
+        assert_eq!(text_art_for("auto", Some("truecolor")), TextArt::HalfBlocks);
+        assert_eq!(text_art_for("auto", None), TextArt::Ascii);
+        assert_eq!(text_art_for("ascii", Some("24bit")), TextArt::Ascii);
+        assert_eq!(text_art_for("blocks", None), TextArt::HalfBlocks);
+    }
+
+    #[test]
+    fn test_half_block_preview() {
+        use image::{DynamicImage, Rgba, RgbaImage};
+
+        // Red over blue, then a transparent pixel over green
+        let mut img = RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]));
+        img.put_pixel(0, 1, Rgba([0, 0, 255, 255]));
+        img.put_pixel(1, 0, Rgba([0, 0, 0, 0]));
+        img.put_pixel(1, 1, Rgba([0, 255, 0, 255]));
+        let preview = generate_half_block_preview(&DynamicImage::ImageRgba8(img), 2, 1);
+        assert_eq!(
+            preview,
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀\x1b[38;2;0;255;0m\x1b[49m▄\x1b[0m\n"
+        );
+        assert_eq!(crate::ansi::to_lines(&preview)[0].to_string(), "▀▄");
+    }
+
+    #[test]
+    fn test_text_art_cells() {
+        // 800x400 into 40x20 cells twice as tall as wide: width-bound
+        assert_eq!(text_art_cells((800, 400), 40, 20, 2.0), (40, 10));
+        // A tall image is height-bound, and a small one scaled up
+        assert_eq!(text_art_cells((100, 400), 40, 20, 2.0), (10, 20));
+        assert_eq!(text_art_cells((16, 16), 40, 20, 2.0), (40, 20));
+        assert_eq!(text_art_cells((16, 16), 0, 0, 2.0), (1, 1));
+    }
+
+    #[test]
+    fn test_fit_cells_keeps_aspect_ratio() {
+        // 800x400 into 40x20 cells of 8x16 px: width-bound, 320x160 px
+        assert_eq!(fit_cells((800, 400), 40, 20, (8, 16)), (40, 10));
+        // Small images are not scaled up
+        assert_eq!(fit_cells((16, 16), 40, 20, (8, 16)), (2, 1));
+    }
+
+    #[test]
+    fn test_base64_and_sixel() {
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64("PATH=/usr/bin ✓".as_bytes()), "UEFUSD0vdXNyL2JpbiDinJM=");
+
+        let sixel = encode_sixel(&RgbImage::from_pixel(8, 6, image::Rgb([255, 255, 255])));
+        assert!(sixel.starts_with("\x1bPq\"1;1;8;6"));
+        // One white band: all six bits set (`~`) run-length encoded
+        assert!(sixel.ends_with("#215!8~-\x1b\\"));
+    }
+
     #[test]
     fn test_generate_ascii_preview_bounds() {
         // Test that the function handles edge cases safely
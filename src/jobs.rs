@@ -0,0 +1,287 @@
+//! Background jobs for long-running work such as `rg`, `git`, directory
+//! walks and file copies, so tools keep responding while it runs.
+//!
+//! A tool starts a job with [`Jobs::spawn`], giving it a label and a closure
+//! that runs on a worker thread, and polls the returned [`Job`] on each tick
+//! until the result arrives. The closure gets a [`Task`] to report progress
+//! and to notice cancellation; commands run through [`Task::output`] are
+//! killed when the job is cancelled instead of being waited for.
+//!
+//! Every tool's [`Jobs`] lives in its [`crate::notify::Notifications`], so
+//! the status bar shows a spinner while jobs run and `Ctrl-J` lists them
+//! with their progress, where the selected one can be cancelled. Dropping a
+//! [`Job`] cancels it too, which is how a search typed over a slower one
+//! stops the old `rg`.
+
+use std::{
+    fmt,
+    io::{self, Read},
+    process::{Command, Output, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How often a running command is checked for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Frames of the status bar spinner, one per [`SPINNER_INTERVAL`]
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What the worker and the lists of jobs share about one job
+#[derive(Debug)]
+struct State {
+    id: u64,
+    label: String,
+    started: Instant,
+    cancelled: AtomicBool,
+    /// Done and total units (bytes, files) as last reported
+    progress: Mutex<Option<(u64, Option<u64>)>>,
+}
+
+/// A running job as listed by the jobs popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobInfo {
+    pub id: u64,
+    pub label: String,
+    pub elapsed: Duration,
+    pub progress: Option<(u64, Option<u64>)>,
+    pub cancelled: bool,
+}
+
+impl JobInfo {
+    /// Share done, when the total is known
+    pub fn fraction(&self) -> Option<f64> {
+        match self.progress? {
+            (done, Some(total)) if total > 0 => Some((done as f64 / total as f64).min(1.0)),
+            _ => None,
+        }
+    }
+}
+
+/// The running jobs of one tool; clones share the list
+#[derive(Clone, Default)]
+pub struct Jobs {
+    running: Arc<Mutex<Vec<Arc<State>>>>,
+}
+
+impl fmt::Debug for Jobs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Jobs").field("running", &self.list().len()).finish()
+    }
+}
+
+/// Ids of jobs across all tools
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+impl Jobs {
+    /// Run `work` on a worker thread, listed as `label` until it finishes
+    pub fn spawn<T, F>(&self, label: impl Into<String>, work: F) -> Job<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Task) -> io::Result<T> + Send + 'static,
+    {
+        let state = Arc::new(State {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            label: label.into(),
+            started: Instant::now(),
+            cancelled: AtomicBool::new(false),
+            progress: Mutex::new(None),
+        });
+        lock(&self.running).push(Arc::clone(&state));
+
+        let (tx, rx) = mpsc::channel();
+        let task = Task { state: Arc::clone(&state) };
+        let running = Arc::clone(&self.running);
+        thread::spawn(move || {
+            // Whatever a cancelled job made of it, it was cancelled
+            let result = work(&task).and_then(|value| task.check().map(|_| value));
+            lock(&running).retain(|job| job.id != task.state.id);
+            let _ = tx.send(result);
+        });
+        Job { receiver: rx, state }
+    }
+
+    /// The running jobs, oldest first
+    pub fn list(&self) -> Vec<JobInfo> {
+        lock(&self.running)
+            .iter()
+            .map(|state| JobInfo {
+                id: state.id,
+                label: state.label.clone(),
+                elapsed: state.started.elapsed(),
+                progress: *lock(&state.progress),
+                cancelled: state.cancelled.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Ask the job with `id` to stop
+    pub fn cancel(&self, id: u64) {
+        if let Some(state) = lock(&self.running).iter().find(|state| state.id == id) {
+            state.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// The spinner frame to show while jobs run, or None when none do
+    pub fn spinner(&self) -> Option<char> {
+        let oldest = lock(&self.running).iter().map(|state| state.started).min()?;
+        let frame = oldest.elapsed().as_millis() / SPINNER_INTERVAL.as_millis();
+        Some(SPINNER[frame as usize % SPINNER.len()])
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The error of a cancelled job
+fn cancelled() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "Cancelled")
+}
+
+/// Whether `error` means the job was cancelled, which is no failure to
+/// report
+pub fn is_cancelled(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::Interrupted
+}
+
+/// Handle to a job's result; dropping it cancels the job
+pub struct Job<T> {
+    receiver: Receiver<io::Result<T>>,
+    state: Arc<State>,
+}
+
+impl<T> Job<T> {
+    /// The result once the job is done, without waiting
+    pub fn poll(&mut self) -> Option<io::Result<T>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(io::Error::other("Job stopped"))),
+        }
+    }
+
+    /// Wait for the result, for plain output where there is no screen to
+    /// keep responsive
+    pub fn wait(self) -> io::Result<T> {
+        self.receiver
+            .recv()
+            .unwrap_or_else(|_| Err(io::Error::other("Job stopped")))
+    }
+
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<T> Drop for Job<T> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// What a job's closure gets to report progress and notice cancellation
+pub struct Task {
+    state: Arc<State>,
+}
+
+impl Task {
+    pub fn cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Err when cancelled, to stop with `?` between steps
+    pub fn check(&self) -> io::Result<()> {
+        if self.cancelled() {
+            return Err(cancelled());
+        }
+        Ok(())
+    }
+
+    /// Report `done` units of `total`, if known
+    pub fn progress(&self, done: u64, total: Option<u64>) {
+        *lock(&self.state.progress) = Some((done, total));
+    }
+
+    /// Run `command` to completion like [`Command::output`], killing it if
+    /// the job is cancelled
+    pub fn output(&self, command: &mut Command) -> io::Result<Output> {
+        self.check()?;
+        let mut child = command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+        // Read both pipes as the command writes, so it never blocks on a
+        // full one
+        let read = |pipe: Option<Box<dyn Read + Send>>| {
+            thread::spawn(move || {
+                let mut bytes = Vec::new();
+                if let Some(mut pipe) = pipe {
+                    let _ = pipe.read_to_end(&mut bytes);
+                }
+                bytes
+            })
+        };
+        let stdout = read(child.stdout.take().map(|pipe| Box::new(pipe) as Box<dyn Read + Send>));
+        let stderr = read(child.stderr.take().map(|pipe| Box::new(pipe) as Box<dyn Read + Send>));
+
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if self.cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(cancelled());
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+        Ok(Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_result_and_list() {
+        let jobs = Jobs::default();
+        let (go, wait) = mpsc::channel::<()>();
+        let job = jobs.spawn("count", move |task| {
+            task.progress(1, Some(4));
+            let _ = wait.recv();
+            Ok(42)
+        });
+        thread::sleep(Duration::from_millis(50));
+        let list = jobs.list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].label, "count");
+        assert_eq!(list[0].fraction(), Some(0.25));
+        assert!(jobs.spinner().is_some());
+
+        go.send(()).unwrap();
+        assert_eq!(job.wait().unwrap(), 42);
+        assert!(jobs.list().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cancel_kills_command() {
+        let jobs = Jobs::default();
+        let job = jobs.spawn("sleep", |task| task.output(Command::new("sleep").arg("10")));
+        thread::sleep(Duration::from_millis(50));
+        let started = Instant::now();
+        jobs.cancel(jobs.list()[0].id);
+        let error = job.wait().unwrap_err();
+        assert!(is_cancelled(&error));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,283 @@
+//! Structured data as a collapsible tree for `tt json`.
+//!
+//! Documents are read as JSON, as JSON Lines (one value per line, shown as
+//! an array) or as YAML, and held as a [`serde_json::Value`] with keys in
+//! document order. YAML needs the `yaml` feature (on by default); without
+//! it YAML documents are reported as unsupported. A node is addressed by its path of object keys and array
+//! indices, written the way jq would select it (`.items[0].name`).
+//!
+//! The tree shows one row per node whose ancestors are all expanded;
+//! [`rows`] walks the document to list them.
+
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// The formats a document is read in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Json,
+    JsonLines,
+    Yaml,
+}
+
+impl DataFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            DataFormat::Json => "JSON",
+            DataFormat::JsonLines => "JSON Lines",
+            DataFormat::Yaml => "YAML",
+        }
+    }
+
+    /// The format a file name suggests, if any
+    pub fn from_extension(extension: &str) -> Option<DataFormat> {
+        match extension.to_ascii_lowercase().as_str() {
+            "json" | "geojson" => Some(DataFormat::Json),
+            "jsonl" | "ndjson" => Some(DataFormat::JsonLines),
+            "yaml" | "yml" => Some(DataFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Parse `text` in `format`, or when that is not known as JSON, then JSON
+/// Lines, then YAML; the error is that of the format asked for, or of JSON
+pub fn parse(text: &str, format: Option<DataFormat>) -> Result<(Value, DataFormat), String> {
+    match format {
+        Some(format) => parse_as(text, format).map(|value| (value, format)),
+        None => parse_as(text, DataFormat::Json)
+            .map(|value| (value, DataFormat::Json))
+            .or_else(|error| {
+                [DataFormat::JsonLines, DataFormat::Yaml]
+                    .into_iter()
+                    .find_map(|format| parse_as(text, format).ok().map(|value| (value, format)))
+                    .ok_or(error)
+            }),
+    }
+}
+
+fn parse_as(text: &str, format: DataFormat) -> Result<Value, String> {
+    match format {
+        DataFormat::Json => serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e)),
+        DataFormat::JsonLines => {
+            let values = text
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(index, line)| serde_json::from_str(line).map_err(|e| format!("invalid JSON on line {}: {}", index + 1, e)))
+                .collect::<Result<Vec<Value>, String>>()?;
+            // A single document is plain JSON, and YAML flow style often
+            // parses as one
+            if values.len() < 2 {
+                return Err("not JSON Lines".to_string());
+            }
+            Ok(Value::Array(values))
+        }
+        #[cfg(feature = "yaml")]
+        DataFormat::Yaml => {
+            // A plain word is valid YAML too; only mappings and sequences count
+            let value: Value = serde_yaml::from_str(text).map_err(|e| format!("invalid YAML: {}", e))?;
+            if value.is_object() || value.is_array() {
+                Ok(value)
+            } else {
+                Err("not a YAML mapping or sequence".to_string())
+            }
+        }
+        #[cfg(not(feature = "yaml"))]
+        DataFormat::Yaml => Err("YAML support is not built in (the `yaml` feature)".to_string()),
+    }
+}
+
+/// One step from a node to a child
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// The path from the root to a node
+pub type Path = Vec<Segment>;
+
+/// `path` as jq writes it: `.`, `.name`, `.items[0]`, `.items["odd key"]`
+pub fn jq_path(path: &[Segment]) -> String {
+    if path.is_empty() {
+        return ".".to_string();
+    }
+    let mut text = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) if is_identifier(key) => {
+                text.push('.');
+                text.push_str(key);
+            }
+            // Brackets follow the previous segment directly, or `.` at the root
+            Segment::Key(key) => text.push_str(&format!("{}[{}]", root_dot(&text), Value::from(key.as_str()))),
+            Segment::Index(index) => text.push_str(&format!("{}[{}]", root_dot(&text), index)),
+        }
+    }
+    text
+}
+
+fn root_dot(text: &str) -> &'static str {
+    if text.is_empty() { "." } else { "" }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The node at `path`
+pub fn get<'a>(value: &'a Value, path: &[Segment]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |node, segment| match (node, segment) {
+        (Value::Object(map), Segment::Key(key)) => map.get(key),
+        (Value::Array(items), Segment::Index(index)) => items.get(*index),
+        _ => None,
+    })
+}
+
+/// Whether a node has children to expand
+pub fn has_children(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+/// The children of a node with the segment leading to each
+fn children(value: &Value) -> Vec<(Segment, &Value)> {
+    match value {
+        Value::Object(map) => map.iter().map(|(key, child)| (Segment::Key(key.clone()), child)).collect(),
+        Value::Array(items) => items.iter().enumerate().map(|(index, child)| (Segment::Index(index), child)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A node shown in the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub path: Path,
+    pub depth: usize,
+}
+
+/// The nodes shown when the containers in `expanded` are open, in document
+/// order, starting with the root
+pub fn rows(value: &Value, expanded: &HashSet<Path>) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut path = Vec::new();
+    push_rows(value, expanded, &mut path, &mut rows);
+    rows
+}
+
+fn push_rows(value: &Value, expanded: &HashSet<Path>, path: &mut Path, rows: &mut Vec<Row>) {
+    rows.push(Row { path: path.clone(), depth: path.len() });
+    if !expanded.contains(path) {
+        return;
+    }
+    for (segment, child) in children(value) {
+        path.push(segment);
+        push_rows(child, expanded, path, rows);
+        path.pop();
+    }
+}
+
+/// Every path in the document, containers before their children
+pub fn all_paths(value: &Value) -> Vec<Path> {
+    let mut paths = Vec::new();
+    let mut stack = vec![(Vec::new(), value)];
+    while let Some((path, node)) = stack.pop() {
+        for (segment, child) in children(node).into_iter().rev() {
+            let mut child_path = path.clone();
+            child_path.push(segment);
+            stack.push((child_path, child));
+        }
+        paths.push(path);
+    }
+    paths
+}
+
+/// Paths of every container down to `depth` levels below the root, to
+/// expand them
+pub fn containers(value: &Value, depth: usize) -> Vec<Path> {
+    all_paths(value)
+        .into_iter()
+        .filter(|path| path.len() < depth && get(value, path).is_some_and(has_children))
+        .collect()
+}
+
+/// The type of a value as jq's `type` names it
+pub fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// A value on one line for the tree: scalars as JSON, containers by size
+pub fn summary(value: &Value) -> String {
+    match value {
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(map) if map.len() == 1 => "{1 key}".to_string(),
+        Value::Object(map) => format!("{{{} keys}}", map.len()),
+        Value::Array(items) if items.is_empty() => "[]".to_string(),
+        Value::Array(items) if items.len() == 1 => "[1 item]".to_string(),
+        Value::Array(items) => format!("[{} items]", items.len()),
+        scalar => scalar.to_string(),
+    }
+}
+
+/// A value to copy or print: strings unquoted, everything else as JSON
+pub fn raw_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let (value, format) = parse(r#"{"b": 1, "a": [true, null]}"#, None).unwrap();
+        assert_eq!(format, DataFormat::Json);
+        assert_eq!(value.as_object().unwrap().keys().collect::<Vec<_>>(), ["b", "a"]);
+        let (value, format) = parse("{\"id\": 1}\n{\"id\": 2}\n", None).unwrap();
+        assert_eq!((format, value[1]["id"].as_i64()), (DataFormat::JsonLines, Some(2)));
+        if cfg!(feature = "yaml") {
+            let (value, format) = parse("name: tt\ntags:\n  - cli\n  - tui\n", None).unwrap();
+            assert_eq!((format, value["tags"][1].as_str()), (DataFormat::Yaml, Some("tui")));
+        } else {
+            assert!(parse("a: 1", Some(DataFormat::Yaml)).is_err());
+        }
+        assert!(parse("just words", None).unwrap_err().starts_with("invalid JSON"));
+    }
+
+    #[test]
+    fn test_jq_path() {
+        let path = vec![Segment::Key("items".into()), Segment::Index(0), Segment::Key("first name".into())];
+        assert_eq!(jq_path(&path), r#".items[0]["first name"]"#);
+        assert_eq!(jq_path(&[Segment::Index(2)]), ".[2]");
+        assert_eq!(jq_path(&[]), ".");
+    }
+
+    #[test]
+    fn test_rows() {
+        let value: Value = serde_json::from_str(r#"{"a": {"b": 1}, "c": [1, 2]}"#).unwrap();
+        let mut expanded = HashSet::new();
+        expanded.insert(Vec::new());
+        assert_eq!(rows(&value, &expanded).len(), 3);
+        expanded.insert(vec![Segment::Key("c".into())]);
+        let shown: Vec<String> = rows(&value, &expanded).iter().map(|row| jq_path(&row.path)).collect();
+        assert_eq!(shown, [".", ".a", ".c", ".c[0]", ".c[1]"]);
+        assert_eq!(all_paths(&value).len(), 6);
+        assert_eq!(get(&value, &[Segment::Key("a".into()), Segment::Key("b".into())]), Some(&Value::from(1)));
+        assert_eq!(summary(&value["c"]), "[2 items]");
+    }
+}
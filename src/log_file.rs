@@ -0,0 +1,468 @@
+//! Reading and parsing log files for `tt log`.
+//!
+//! Lines are parsed for a severity level and a timestamp as they are read.
+//! The level is a keyword like `ERROR` or `WARNING` written in capitals, in
+//! brackets (`[error]`) or after a label (`level=error`, `"level":"warn"`).
+//! Timestamps are recognised in ISO 8601 form (`2024-05-01T14:30:05Z`,
+//! `2024-05-01 14:30:05,123`), syslog form (`May  1 14:30:05`, in the
+//! current year) and the common log format of web servers
+//! (`[01/May/2024:14:30:05 +0000]`); those without an offset are local time.
+//! In a log whose entries are timestamped, a line with neither a time nor a
+//! level continues the entry above (a stack trace, a wrapped message) and
+//! takes its level and time.
+//!
+//! A background thread reads the file or standard input and sends the
+//! parsed lines over a channel, like the scans of [`crate::disk_usage`].
+//! When following, it polls the file for new lines the way `tail -F` does,
+//! starting over when the file is truncated or replaced by log rotation.
+
+use crate::time_util::{self, DateTime};
+use regex::{Captures, Regex};
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        OnceLock,
+    },
+    thread,
+    time::Duration,
+};
+
+/// How often a followed file is checked for new lines
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Bytes of a followed file read at first; older lines are skipped like
+/// `tail` does
+pub const INITIAL_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Lines sent at most in one event
+const BATCH_SIZE: usize = 5000;
+
+/// Bytes at the start of a line searched for a timestamp
+const TIMESTAMP_SPAN: usize = 100;
+
+/// How important a log line is, least first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Trace => "TRACE",
+            Severity::Debug => "DEBUG",
+            Severity::Info => "INFO",
+            Severity::Warn => "WARN",
+            Severity::Error => "ERROR",
+        }
+    }
+
+    /// The severity a level keyword names, in any case
+    fn from_keyword(word: &str) -> Option<Severity> {
+        match word.to_ascii_uppercase().as_str() {
+            "ERROR" | "ERR" | "FATAL" | "CRITICAL" | "CRIT" | "PANIC" | "SEVERE" | "EMERG" | "ALERT" => Some(Severity::Error),
+            "WARN" | "WARNING" => Some(Severity::Warn),
+            "INFO" | "NOTICE" => Some(Severity::Info),
+            "DEBUG" => Some(Severity::Debug),
+            "TRACE" | "VERBOSE" => Some(Severity::Trace),
+            _ => None,
+        }
+    }
+}
+
+/// The level a line is logged at, if it names one
+pub fn severity(line: &str) -> Option<Severity> {
+    let bytes = line.as_bytes();
+    let mut previous = "";
+    let mut start = None;
+    for index in 0..=bytes.len() {
+        if bytes.get(index).is_some_and(u8::is_ascii_alphabetic) {
+            start.get_or_insert(index);
+            continue;
+        }
+        let Some(begin) = start.take() else {
+            continue;
+        };
+        let word = &line[begin..index];
+        if let Some(severity) = Severity::from_keyword(word) {
+            // Lowercase words need context: "no error" is not a level
+            let capitals = word.bytes().all(|byte| byte.is_ascii_uppercase());
+            let bracketed = begin > 0 && matches!(bytes[begin - 1], b'[' | b'<');
+            let labelled = ["level", "lvl", "severity"].iter().any(|label| previous.eq_ignore_ascii_case(label));
+            if capitals || bracketed || labelled {
+                return Some(severity);
+            }
+        }
+        previous = word;
+    }
+    None
+}
+
+fn iso_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(\d{4})[-/](\d{2})[-/](\d{2})[T ](\d{2}):(\d{2})(?::(\d{2}))?(?:[.,]\d+)?(Z|[+-]\d{2}:?\d{2})?").unwrap()
+    })
+}
+
+fn syslog_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"\b([A-Z][a-z]{2}) +(\d{1,2}) (\d{2}):(\d{2}):(\d{2})\b").unwrap())
+}
+
+fn common_log_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"(\d{2})/([A-Z][a-z]{2})/(\d{4}):(\d{2}):(\d{2}):(\d{2})(?: ([+-]\d{4}))?").unwrap())
+}
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+fn month_number(name: &str) -> Option<u32> {
+    MONTHS.iter().position(|month| *month == name).map(|index| index as u32 + 1)
+}
+
+/// Number in capture group `group`, 0 when it didn't take part
+fn number(captures: &Captures, group: usize) -> Option<u32> {
+    captures.get(group).map_or(Some(0), |found| found.as_str().parse().ok())
+}
+
+/// Unix timestamp of `date` written in a zone `offset` seconds ahead of
+/// UTC; None for impossible dates
+fn timestamp_at(date: DateTime, offset: i64) -> Option<i64> {
+    let valid = (1..=12).contains(&date.month) && (1..=31).contains(&date.day) && date.hour < 24 && date.minute < 60 && date.second < 61;
+    valid.then(|| date.to_utc() - offset)
+}
+
+/// The start of `text`, at most `max` bytes of it
+fn head(text: &str, max: usize) -> &str {
+    let mut end = max.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Timestamp near the start of `line`, reading times without an offset in
+/// a zone `offset` seconds ahead of UTC and syslog times in the year up to
+/// `now`
+fn parse_timestamp(line: &str, now: i64, offset: i64) -> Option<i64> {
+    let text = head(line, TIMESTAMP_SPAN);
+    if let Some(captures) = iso_regex().captures(text) {
+        let date = DateTime {
+            year: captures[1].parse().ok()?,
+            month: number(&captures, 2)?,
+            day: number(&captures, 3)?,
+            hour: number(&captures, 4)?,
+            minute: number(&captures, 5)?,
+            second: number(&captures, 6)?,
+        };
+        let offset = match captures.get(7).map(|zone| zone.as_str()) {
+            Some("Z") => 0,
+            Some(zone) => time_util::parse_utc_offset(zone)?,
+            None => offset,
+        };
+        return timestamp_at(date, offset);
+    }
+    if let Some(captures) = common_log_regex().captures(text) {
+        let date = DateTime {
+            year: captures[3].parse().ok()?,
+            month: month_number(&captures[2])?,
+            day: number(&captures, 1)?,
+            hour: number(&captures, 4)?,
+            minute: number(&captures, 5)?,
+            second: number(&captures, 6)?,
+        };
+        let offset = match captures.get(7) {
+            Some(zone) => time_util::parse_utc_offset(zone.as_str())?,
+            None => offset,
+        };
+        return timestamp_at(date, offset);
+    }
+    let captures = syslog_regex().captures(text)?;
+    let mut date = DateTime {
+        year: DateTime::from_utc(now + offset).year,
+        month: month_number(&captures[1])?,
+        day: number(&captures, 2)?,
+        hour: number(&captures, 3)?,
+        minute: number(&captures, 4)?,
+        second: number(&captures, 5)?,
+    };
+    // Lines from December read in January are from last year
+    let secs = timestamp_at(date, offset)?;
+    if secs > now + 86_400 {
+        date.year -= 1;
+        return timestamp_at(date, offset);
+    }
+    Some(secs)
+}
+
+/// A time typed to jump to: `14:30`, `14:30:15` on the day of `reference`,
+/// or a date with an optional time (`2024-05-01`, `2024-05-01 14:30`), in
+/// local time
+pub fn parse_jump_time(query: &str, reference: i64) -> Option<i64> {
+    parse_jump_time_with_offset(query, reference, time_util::local_offset_secs())
+}
+
+fn parse_jump_time_with_offset(query: &str, reference: i64, offset: i64) -> Option<i64> {
+    let query = query.trim();
+    let mut parts = query.split(':');
+    let clock: Option<Vec<u32>> = parts.by_ref().map(|part| part.parse().ok().filter(|_| part.len() <= 2)).collect();
+    if let Some(clock) = clock.filter(|clock| (2..=3).contains(&clock.len())) {
+        let day = DateTime::from_utc(reference + offset);
+        let date = DateTime { hour: clock[0], minute: clock[1], second: clock.get(2).copied().unwrap_or(0), ..day };
+        return timestamp_at(date, offset);
+    }
+    if iso_regex().is_match(query) {
+        return parse_timestamp(query, reference, offset);
+    }
+    if let [year, month, day] = query.split('-').collect::<Vec<_>>()[..] {
+        let date = DateTime {
+            year: year.parse().ok()?,
+            month: month.parse().ok()?,
+            day: day.parse().ok()?,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        };
+        return timestamp_at(date, offset);
+    }
+    None
+}
+
+/// A line of the log with what was parsed from it
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub text: String,
+    /// The line's level, or that of the entry it continues
+    pub severity: Option<Severity>,
+    /// The line's timestamp, or that of the entry it continues
+    pub time: Option<i64>,
+}
+
+/// Parses lines in order, carrying each entry's level and time over to the
+/// lines that continue it
+pub struct Parser {
+    now: i64,
+    offset: i64,
+    /// Level and time of the last timestamped entry
+    entry: Option<(Option<Severity>, i64)>,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Parser { now: time_util::now_secs(), offset: time_util::local_offset_secs(), entry: None }
+    }
+}
+
+impl Parser {
+    pub fn parse(&mut self, text: String) -> LogLine {
+        let severity = severity(&text);
+        let time = parse_timestamp(&text, self.now, self.offset);
+        match (time, severity, self.entry) {
+            (Some(time), _, _) => self.entry = Some((severity, time)),
+            (None, None, Some((severity, time))) => return LogLine { text, severity, time: Some(time) },
+            // A level without a time starts an untimed entry
+            (None, Some(_), _) => self.entry = None,
+            (None, None, None) => {}
+        }
+        LogLine { text, severity, time }
+    }
+}
+
+/// Where `tt log` reads from
+#[derive(Debug, Clone)]
+pub enum LogSource {
+    File(PathBuf),
+    Stdin,
+}
+
+impl LogSource {
+    /// Shown in titles: the file's path or `stdin`
+    pub fn name(&self) -> String {
+        match self {
+            LogSource::File(path) => path.display().to_string(),
+            LogSource::Stdin => "stdin".to_string(),
+        }
+    }
+}
+
+/// Events sent by a background reader
+#[derive(Debug)]
+pub enum ReadEvent {
+    /// New lines, in order
+    Lines(Vec<LogLine>),
+    /// The start of a large file was left out; how many bytes
+    Skipped(u64),
+    /// The file was truncated or replaced and is read again from the start
+    Restarted,
+    /// The end was reached and nothing more will come
+    Finished,
+    Failed(String),
+}
+
+/// Start reading `source` on a background thread; a followed file is
+/// watched for new lines until the receiver is dropped
+pub fn spawn_reader(source: LogSource, follow: bool) -> Receiver<ReadEvent> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = match &source {
+            LogSource::File(path) => read_file(path, follow, &tx),
+            LogSource::Stdin => read_available(&mut BufReader::new(io::stdin()), &mut Parser::default(), &mut Vec::new(), &tx).map(|_| ()),
+        };
+        let _ = tx.send(match result {
+            Ok(()) => ReadEvent::Finished,
+            Err(e) => ReadEvent::Failed(format!("Could not read {}: {}", source.name(), e)),
+        });
+    });
+    rx
+}
+
+/// Device and inode of a file, which change when the path is replaced
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+fn read_file(path: &Path, follow: bool, tx: &Sender<ReadEvent>) -> io::Result<()> {
+    let file = File::open(path)?;
+    let mut id = file_id(&file.metadata()?);
+    let mut reader = BufReader::new(file);
+    let mut parser = Parser::default();
+    let mut pending = Vec::new();
+    let mut position = 0;
+
+    let len = reader.get_ref().metadata()?.len();
+    if follow && len > INITIAL_BYTES {
+        // Start at a line boundary
+        reader.seek(SeekFrom::Start(len - INITIAL_BYTES))?;
+        position = len - INITIAL_BYTES + reader.read_until(b'\n', &mut pending)? as u64;
+        pending.clear();
+        let _ = tx.send(ReadEvent::Skipped(position));
+    }
+
+    loop {
+        match read_available(&mut reader, &mut parser, &mut pending, tx)? {
+            Some(read) => position += read,
+            None => return Ok(()),
+        }
+        if !follow {
+            return Ok(());
+        }
+        thread::sleep(POLL_INTERVAL);
+
+        // A missing file is waited for: rotation may not have created the
+        // new one yet
+        let Ok(metadata) = fs::metadata(path) else {
+            continue;
+        };
+        if file_id(&metadata) != id || metadata.len() < position {
+            let Ok(file) = File::open(path) else {
+                continue;
+            };
+            id = file_id(&file.metadata()?);
+            reader = BufReader::new(file);
+            parser = Parser::default();
+            pending.clear();
+            position = 0;
+            if tx.send(ReadEvent::Restarted).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// The text of a line read with its line break: invalid UTF-8 replaced,
+/// colors removed and tabs expanded so it lays out like the terminal would
+fn line_text(bytes: &[u8]) -> String {
+    static ESCAPES: OnceLock<Regex> = OnceLock::new();
+    let escapes = ESCAPES.get_or_init(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").unwrap());
+    let line = String::from_utf8_lossy(bytes);
+    let line = line.trim_end_matches(['\n', '\r']);
+    escapes.replace_all(line, "").replace('\t', "    ")
+}
+
+/// Read `reader` to its current end, sending complete lines whenever no
+/// more are waiting; a last line without a line break is kept in `pending`
+/// until it is finished. Returns the bytes read, or None once the
+/// receiver is gone.
+fn read_available<R: Read>(reader: &mut BufReader<R>, parser: &mut Parser, pending: &mut Vec<u8>, tx: &Sender<ReadEvent>) -> io::Result<Option<u64>> {
+    let mut total = 0;
+    let mut batch = Vec::new();
+    loop {
+        let read = reader.read_until(b'\n', pending)?;
+        total += read as u64;
+        if pending.ends_with(b"\n") {
+            batch.push(parser.parse(line_text(pending)));
+            pending.clear();
+        }
+        let waiting = read > 0 && !reader.buffer().is_empty() && batch.len() < BATCH_SIZE;
+        if !waiting && !batch.is_empty() && tx.send(ReadEvent::Lines(std::mem::take(&mut batch))).is_err() {
+            return Ok(None);
+        }
+        if read == 0 {
+            return Ok(Some(total));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity() {
+        assert_eq!(severity("2024-05-01 14:30:05 ERROR db: connection refused"), Some(Severity::Error));
+        assert_eq!(severity("[2024-05-01T14:30:05Z WARN  app] slow query"), Some(Severity::Warn));
+        assert_eq!(severity("2024/05/01 14:30:05 [error] 42#0: open() failed"), Some(Severity::Error));
+        assert_eq!(severity("time=14:30 level=info msg=\"started\""), Some(Severity::Info));
+        assert_eq!(severity(r#"{"level":"debug","msg":"tick"}"#), Some(Severity::Debug));
+        assert_eq!(severity("no error here, just information"), None);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let now = 1_714_573_805; // 2024-05-01 14:30:05 UTC
+        assert_eq!(parse_timestamp("2024-05-01T14:30:05Z INFO up", now, 3600), Some(now));
+        assert_eq!(parse_timestamp("2024-05-01 16:30:05,123 INFO up", now, 7200), Some(now));
+        assert_eq!(parse_timestamp("2024-05-01T16:30:05.5+02:00 up", now, 0), Some(now));
+        assert_eq!(parse_timestamp("May  1 14:30:05 host sshd[42]: accepted", now, 0), Some(now));
+        assert_eq!(parse_timestamp("Dec 31 23:00:00 host cron: run", now, 0), Some(1_704_063_600)); // 2023
+        assert_eq!(parse_timestamp(r#"10.0.0.1 - - [01/May/2024:14:30:05 +0000] "GET / HTTP/1.1""#, now, 3600), Some(now));
+        assert_eq!(parse_timestamp("    at main.rs:12", now, 0), None);
+    }
+
+    #[test]
+    fn test_parse_jump_time() {
+        let reference = 1_714_573_805; // 2024-05-01 14:30:05 UTC
+        assert_eq!(parse_jump_time_with_offset("14:30", reference, 0), Some(reference - 5));
+        assert_eq!(parse_jump_time_with_offset("16:30:05", reference, 7200), Some(reference));
+        assert_eq!(parse_jump_time_with_offset("2024-05-01", reference, 0), Some(reference - 52_205));
+        assert_eq!(parse_jump_time_with_offset("2024-05-01 14:30:05", reference, 0), Some(reference));
+        assert_eq!(parse_jump_time_with_offset("25:00", reference, 0), None);
+        assert_eq!(parse_jump_time_with_offset("soon", reference, 0), None);
+    }
+
+    #[test]
+    fn test_continuation_lines() {
+        let mut parser = Parser { now: 1_714_573_805, offset: 0, entry: None };
+        let error = parser.parse("2024-05-01 14:30:05 ERROR failed".to_string());
+        let trace = parser.parse("    at handler (app.js:12)".to_string());
+        assert_eq!((trace.severity, trace.time), (Some(Severity::Error), error.time));
+        let info = parser.parse("2024-05-01 14:30:06 INFO retry".to_string());
+        assert_eq!(info.severity, Some(Severity::Info));
+        assert_eq!(line_text(b"\x1b[31mred\x1b[0m\tdone\r\n"), "red    done");
+    }
+}
@@ -12,9 +12,21 @@
 //! - **🌳 git** - Git operations (log, diff, branch) with TUI interface
 //! - **📁 dir** - File/directory explorer with preview pane
 //! - **📚 hist** - Command history browser and executor
+//! - **⭐ snip** - Saved command snippets with placeholders
 //! - **🌍 env** - Environment variable viewer and manager
 //! - **📖 man** - Interactive manual page browser
+//! - **📜 log** - Log file viewer with live follow and filtering
+//! - **🌐 net** - Listening ports and connections browser
+//! - **⚙️ systemd** - Service browser with journal preview and controls
+//! - **🧾 json** - JSON and YAML explorer with jq-style paths
+//! - **🔢 hex** - Hex viewer for binary files
+//! - **🖥️ sys** - System resource dashboard
+//! - **📦 pkg** - Package search, details and install/remove
+//! - **📝 todo** - Project TODO/FIXME browser
+//! - **🆚 diff** - File and directory diff viewer
+//! - **🗂️ workspace** - Several tools as tabs of one screen
 //! - **📂 recent** - Recent files browser with MRU tracking
+//! - **🚀 z** - Frecency-ranked directory jumper backed by zoxide
 //!
 //! ## Key Features
 //!
@@ -22,6 +34,7 @@
 //! - **Native image preview** using ASCII art generation
 //! - **High performance** with optimized rendering and timeouts
 //! - **Robust error handling** with graceful degradation
+//! - **Sessions** that reopen each tool where it was left in a directory
 //! - **Zero external dependencies** for core functionality
 //!
 //! ## Quick Start
@@ -31,11 +44,13 @@
 //! cargo install terminal_tools
 //!
 //! # Essential commands
+//! tt                         # Pick a tool from the menu
 //! tt find                    # Find files with fuzzy search
 //! tt search "pattern"        # Search content in files
 //! tt kill                    # Manage processes interactively
 //! tt git log                 # Browse git commit history
 //! tt dir /path              # Explore directories
+//! tt find --json | jq .path  # Print data instead of the TUI (also --plain)
 //! ```
 //!
 //! ## Navigation
@@ -56,39 +71,195 @@ mod cli;
 mod tools;
 mod tui_common;
 mod image_preview;
+mod process_info;
+mod users;
+mod file_ops;
+mod time_util;
+mod state;
+mod fuzzy;
+mod bookmarks;
+mod shell_init;
+mod archive;
+mod disk_usage;
+mod preview;
+mod frecency;
+mod shell_history;
+mod command_log;
+mod env_vars;
+mod clipboard;
+mod snippets;
+mod man_page;
+mod man_index;
+mod help_text;
+mod shell_type;
+mod mru;
+mod editor_history;
+mod modified_files;
+mod config;
+mod theme;
+mod notify;
+mod tldr;
+mod text_width;
+mod platform;
+mod ansi;
+mod image_cache;
+mod media;
+mod plain;
+mod log_file;
+mod systemd;
+mod json_tree;
+mod hex_dump;
+mod system_stats;
+mod packages;
+mod todos;
+mod diff;
+mod session;
+mod palette;
+mod jobs;
+mod syntax;
+mod bounded_read;
 
 use cli::*;
+use config::Config;
+use plain::Format;
 
 fn main() -> io::Result<()> {
+    tui_common::install_panic_hook();
     let cli = Cli::parse();
+    let config = Config::load()?;
+    let tool_settings = config.tool(cli.command.as_ref().map_or("", Commands::name));
+    theme::configure(cli.theme.as_deref().or(tool_settings.theme.as_deref()), &config.colors)?;
+    image_preview::configure(cli.ascii_only, tool_settings.graphics.as_deref())?;
+    session::configure(cli.fresh);
 
     match cli.command {
+        Some(command) => {
+            let output = Format::choose(cli.plain, cli.json, command.plain_when_piped());
+            run_command(command, output, &config)
+        }
+        None => run_launcher(&config),
+    }
+}
+
+/// Show the menu of tools, running each one chosen until the user quits it
+fn run_launcher(config: &Config) -> io::Result<()> {
+    let mut launcher = tools::launcher::Launcher::new(config.tool(""));
+    while let Some(tool) = launcher.choose()? {
+        let args = std::iter::once("tt").chain(tool.args.iter().copied());
+        let command = Cli::try_parse_from(args)
+            .ok()
+            .and_then(|cli| cli.command)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("bad menu entry: tt {}", tool.name)))?;
+        if let Err(e) = run_command(command, None, config) {
+            launcher.report_error(tool, &e);
+        }
+    }
+    Ok(())
+}
+
+/// Run a tool, printing its data in `output` instead of starting the TUI if
+/// given
+fn run_command(command: Commands, output: Option<Format>, config: &Config) -> io::Result<()> {
+    match command {
         Commands::Find { path, extensions, search } => {
-            tools::find::run(path, extensions, search)
+            tools::find::run(path, extensions, search, output, config.tool("find"))
         }
-        Commands::Kill { filter } => {
-            tools::kill::run(filter)
+        Commands::Kill { filter, interval, user, state, ports, pattern, signal, yes } => {
+            let state = state.map(Into::into);
+            match pattern {
+                Some(pattern) => tools::kill::kill_matching(&pattern, &signal, yes, user, state, config.tool("kill")),
+                None => tools::kill::run(filter, interval, user, state, ports, output, config.tool("kill")),
+            }
         }
         Commands::Git { subcommand } => {
-            tools::git::run(subcommand)
+            tools::git::run(subcommand, output, config.tool("git"))
+        }
+        Commands::Hist { shell, record: Some(command), status, duration, cwd, .. } => {
+            tools::history::record(command, status.unwrap_or(0), duration.unwrap_or(0), cwd, HistShell::shells(shell))
+        }
+        Commands::Hist { limit, shell, query, print_selection, .. } => {
+            let settings = config.tool("hist");
+            let limit = limit.or(settings.limit).unwrap_or(100);
+            tools::history::run(limit, HistShell::shells(shell), query, print_selection, output, settings)
+        }
+        Commands::Snip => tools::snip::run(output, config.tool("snip")),
+        Commands::Dir { path, trash: true, .. } => tools::trash::run(path, output, config.tool("dir")),
+        Commands::Dir { path, choose_dir, choose_file, .. } => {
+            let choose = if choose_dir {
+                Some(tools::explore::Choose::Dir)
+            } else if choose_file {
+                Some(tools::explore::Choose::File)
+            } else {
+                None
+            };
+            tools::explore::run(path, choose, output, config.tool("dir"))
         }
-        Commands::Hist { limit } => {
-            tools::history::run(limit)
+        Commands::Du { path } => {
+            tools::du::run(path, output, config.tool("du"))
         }
-        Commands::Dir { path } => {
-            tools::explore::run(path)
+        Commands::Z { query, add } => {
+            tools::z::run(query, add, output, config.tool("z"))
         }
-        Commands::Env { filter: _ } => {
-            tools::env::run()
+        Commands::Init { shell } => plain::print_text(&shell_init::script(shell, frecency::Source::detect())),
+        Commands::Completions { shell } => plain::print_text(&shell.script()),
+        Commands::Env { snapshot: Some(name), .. } => {
+            tools::env::snapshot(&name)
+        }
+        Commands::Env { filter, file, diff, show_secrets, apply, .. } => {
+            tools::env::run(filter, file, diff, show_secrets, apply.map(env_vars::Syntax::for_shell), output, config.tool("env"))
         }
         Commands::Recent { limit } => {
-            tools::recent::run(limit)
+            let settings = config.tool("recent");
+            let limit = limit.or(settings.limit).unwrap_or(10);
+            tools::recent::run(limit, output, settings)
+        }
+        Commands::Man { search, section } => {
+            tools::man::run(search, section, output, config.tool("man"))
+        }
+        Commands::Log { file, include, exclude, level, no_follow } => {
+            tools::log::run(file, !no_follow, include, exclude, level.map(Into::into), output, config.tool("log"))
+        }
+        Commands::Net { filter, listening, connected, interval } => {
+            let view = match (listening, connected) {
+                (true, _) => tools::net::View::Listening,
+                (_, true) => tools::net::View::Connected,
+                _ => tools::net::View::All,
+            };
+            tools::net::run(filter, view, interval, output, config.tool("net"))
+        }
+        Commands::Systemd { filter, user, failed, all } => {
+            let scope = if user { systemd::Scope::User } else { systemd::Scope::System };
+            let view = match (failed, all) {
+                (true, _) => tools::systemd::View::Failed,
+                (_, true) => tools::systemd::View::All,
+                _ => tools::systemd::View::Services,
+            };
+            tools::systemd::run(scope, filter, view, output, config.tool("systemd"))
+        }
+        Commands::Json { file } => {
+            tools::json::run(file, output, config.tool("json"))
+        }
+        Commands::Hex { file, width, offset } => {
+            tools::hex::run(file, width, offset, output, config.tool("hex"))
+        }
+        Commands::Sys { interval } => {
+            tools::sys::run(interval, output, config.tool("sys"))
+        }
+        Commands::Pkg { query, installed, manager } => {
+            let view = if installed { tools::pkg::View::Installed } else { tools::pkg::View::All };
+            tools::pkg::run(manager.map(Into::into), query, view, output, config.tool("pkg"))
+        }
+        Commands::Todo { path, tag } => {
+            tools::todo::run(path, tag.into_iter().map(Into::into).collect(), output, config.tool("todo"))
+        }
+        Commands::Diff { old, new, side_by_side, context } => {
+            tools::diff::run(old, new, context, side_by_side, output, config.tool("diff"))
         }
-        Commands::Man { search } => {
-            tools::man::run(search)
+        Commands::Workspace { tools } => {
+            tools::workspace::run(tools.into_iter().map(Into::into).collect(), output, config)
         }
         Commands::Search { pattern, path, file_type, ignore_case } => {
-            tools::search::run(pattern, path, file_type, ignore_case)
+            tools::search::run(pattern, path, file_type, ignore_case, output, config.tool("search"))
         }
     }
 }
@@ -0,0 +1,247 @@
+//! The list of installed man pages for `tt man`.
+//!
+//! `apropos .` prints every page with its one-line summary, but on a large
+//! system it can take seconds. The browser therefore reads it on a background
+//! thread that sends the pages in batches as `apropos` prints them, so the
+//! list fills in while it runs.
+//!
+//! The parsed index is cached in `$XDG_CACHE_HOME/terminal-tools/man-index.json` together
+//! with the modification time of the man database (man-db's `index.db`,
+//! mandoc's `mandoc.db` or a BSD `whatis` file). The cache is used for as long
+//! as the database is unchanged, so running `mandb` after installing software
+//! refreshes the list. Without a database to compare against nothing is cached.
+//!
+//! Where `apropos` is missing or fails, a short list of common commands that
+//! `man -w` can find stands in for the index, and the browser is told so
+//! through a [`Notifier`].
+
+use crate::notify::Notifier;
+use crate::platform;
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::mpsc::Sender,
+    thread,
+    time::UNIX_EPOCH,
+};
+
+/// Pages sent to the browser at a time
+const BATCH_SIZE: usize = 500;
+
+/// man-db keeps its databases here, one per locale in sub-directories
+const MANDB_CACHE: &str = "/var/cache/man";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManPage {
+    pub name: String,
+    pub section: String,
+    pub description: String,
+}
+
+/// Progress of a background load
+#[derive(Debug)]
+pub enum Event {
+    /// More pages, unsorted
+    Pages(Vec<ManPage>),
+    Done,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cache {
+    database_mtime: u64,
+    pages: Vec<ManPage>,
+}
+
+fn cache_file() -> Option<PathBuf> {
+    platform::cache_dir().map(|dir| dir.join("man-index.json"))
+}
+
+/// Directories searched for man pages
+fn man_dirs() -> Vec<PathBuf> {
+    match env::var("MANPATH") {
+        Ok(manpath) if !manpath.is_empty() => env::split_paths(&manpath).filter(|dir| dir.is_absolute()).collect(),
+        _ => ["/usr/share/man", "/usr/local/share/man", "/opt/homebrew/share/man"].iter().map(PathBuf::from).collect(),
+    }
+}
+
+/// Newest modification time, in seconds, of the man databases on this system
+pub fn database_mtime() -> Option<u64> {
+    let mut files = vec![PathBuf::from(MANDB_CACHE).join("index.db")];
+    if let Ok(entries) = fs::read_dir(MANDB_CACHE) {
+        files.extend(entries.flatten().map(|entry| entry.path().join("index.db")));
+    }
+    for dir in man_dirs() {
+        files.push(dir.join("mandoc.db"));
+        files.push(dir.join("whatis"));
+    }
+    files
+        .iter()
+        .filter_map(|file| fs::metadata(file).and_then(|meta| meta.modified()).ok())
+        .filter_map(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|age| age.as_secs())
+        .max()
+}
+
+/// The cached index, if the man database has not changed since it was saved
+pub fn cached() -> Option<Vec<ManPage>> {
+    let stamp = database_mtime()?;
+    let content = fs::read_to_string(cache_file()?).ok()?;
+    let cache: Cache = serde_json::from_str(&content).ok()?;
+    (cache.database_mtime == stamp).then_some(cache.pages)
+}
+
+fn save(database_mtime: u64, pages: Vec<ManPage>) {
+    let Some(path) = cache_file() else {
+        return;
+    };
+    let cache = Cache { database_mtime, pages };
+    // A failed write only means reading the index again next time
+    if let Ok(json) = serde_json::to_string(&cache) {
+        let _ = path.parent().map(fs::create_dir_all);
+        let _ = fs::write(&path, json);
+    }
+}
+
+/// Sort by name then section, dropping duplicates; a name documented in
+/// several sections keeps one entry per section
+pub fn sort(pages: &mut Vec<ManPage>) {
+    pages.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.section.cmp(&b.section)));
+    pages.dedup_by(|a, b| a.name == b.name && a.section == b.section);
+}
+
+/// Read the index on a new thread, sending [`Event`]s to `tx` and problems
+/// to `notifier`
+pub fn spawn_load(tx: Sender<Event>, notifier: Notifier) {
+    thread::spawn(move || {
+        load(&tx, &notifier);
+        let _ = tx.send(Event::Done);
+    });
+}
+
+fn load(tx: &Sender<Event>, notifier: &Notifier) {
+    let stamp = database_mtime();
+    let mut pages = Vec::new();
+    let mut success = false;
+
+    if let Ok(mut child) = Command::new("apropos").arg(".").stdout(Stdio::piped()).stderr(Stdio::null()).spawn() {
+        if let Some(stdout) = child.stdout.take() {
+            let mut batch = Vec::new();
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                batch.extend(parse_apropos_line(&line));
+                if batch.len() >= BATCH_SIZE {
+                    pages.extend_from_slice(&batch);
+                    if tx.send(Event::Pages(std::mem::take(&mut batch))).is_err() {
+                        // The browser has closed
+                        let _ = child.kill();
+                        return;
+                    }
+                }
+            }
+            pages.extend_from_slice(&batch);
+            let _ = tx.send(Event::Pages(batch));
+        }
+        success = child.wait().is_ok_and(|status| status.success());
+    }
+
+    if success {
+        if let Some(stamp) = stamp {
+            sort(&mut pages);
+            save(stamp, pages);
+        }
+    } else if pages.is_empty() {
+        notifier.error("apropos failed; listing common commands only");
+        let _ = tx.send(Event::Pages(common_pages()));
+    }
+}
+
+/// Parse a line of `apropos` output: `name (section) - description` from
+/// man-db, `name(section) - description` from mandoc
+pub fn parse_apropos_line(line: &str) -> Option<ManPage> {
+    let (names, description) = line.split_once(" - ")?;
+    let open = names.find('(')?;
+    let close = open + names[open..].find(')')?;
+    let name = names[..open].trim();
+    let section = names[open + 1..close].trim();
+    if name.is_empty() || section.is_empty() {
+        return None;
+    }
+    Some(ManPage {
+        name: name.to_string(),
+        section: section.to_string(),
+        description: description.trim().to_string(),
+    })
+}
+
+/// Common commands that have a man page, for systems without `apropos`
+fn common_pages() -> Vec<ManPage> {
+    let common_commands = [
+        ("ls", "1", "list directory contents"),
+        ("cd", "1", "change directory"),
+        ("cp", "1", "copy files"),
+        ("mv", "1", "move files"),
+        ("rm", "1", "remove files"),
+        ("cat", "1", "concatenate files"),
+        ("grep", "1", "search text patterns"),
+        ("find", "1", "search for files"),
+        ("ps", "1", "show running processes"),
+        ("top", "1", "display running processes"),
+        ("kill", "1", "terminate processes"),
+        ("man", "1", "display manual pages"),
+        ("vim", "1", "text editor"),
+        ("nano", "1", "text editor"),
+        ("git", "1", "version control system"),
+        ("ssh", "1", "secure shell"),
+        ("wget", "1", "download files"),
+        ("curl", "1", "transfer data"),
+        ("tar", "1", "archive files"),
+        ("chmod", "1", "change file permissions"),
+        ("chown", "1", "change file ownership"),
+    ];
+
+    common_commands
+        .iter()
+        .filter(|(name, _, _)| {
+            // Check if man page actually exists
+            Command::new("man")
+                .args(["-w", name])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        })
+        .map(|(name, section, description)| ManPage {
+            name: name.to_string(),
+            section: section.to_string(),
+            description: description.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(name: &str, section: &str, description: &str) -> ManPage {
+        ManPage { name: name.to_string(), section: section.to_string(), description: description.to_string() }
+    }
+
+    #[test]
+    fn test_parse_apropos_line() {
+        assert_eq!(parse_apropos_line("ls (1)               - list directory contents"), Some(page("ls", "1", "list directory contents")));
+        assert_eq!(parse_apropos_line("printf(3p) - print formatted output"), Some(page("printf", "3p", "print formatted output")));
+        assert_eq!(parse_apropos_line("git-log (1) - Show commit logs - with options"), Some(page("git-log", "1", "Show commit logs - with options")));
+        assert_eq!(parse_apropos_line("nothing appropriate."), None);
+        assert_eq!(parse_apropos_line("(1) - no name"), None);
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut pages = vec![page("printf", "3", "b"), page("ls", "1", "a"), page("printf", "1", "c"), page("ls", "1", "a")];
+        sort(&mut pages);
+        let keys: Vec<(&str, &str)> = pages.iter().map(|p| (p.name.as_str(), p.section.as_str())).collect();
+        assert_eq!(keys, vec![("ls", "1"), ("printf", "1"), ("printf", "3")]);
+    }
+}
@@ -0,0 +1,328 @@
+//! Formatted man page text for `tt man`.
+//!
+//! `man` lays pages out for a terminal and marks emphasis with overstrike
+//! sequences: `X\bX` prints a bold `X` and `_\bX` an underlined one. This is
+//! what man-db (with `MAN_KEEP_FORMATTING` and `GROFF_NO_SGR`) and mandoc
+//! write when piped. Newer groff may use ANSI SGR escapes (`ESC[1m`) instead;
+//! both are turned into styled runs here so the TUI can draw them.
+//!
+//! Section headings, the unindented upper-case lines such as `SYNOPSIS` or
+//! `SEE ALSO`, are indexed so the reader can jump between them. Running
+//! headers and footers (`LS(1)  User Commands  LS(1)`) contain lower-case
+//! text and are not mistaken for headings.
+//!
+//! Searching a page matches its plain text case-insensitively and reports
+//! positions in characters, so matches can be highlighted across runs.
+//!
+//! Options are read from the sections whose heading mentions options or
+//! flags, or from `DESCRIPTION` on pages such as GNU `ls(1)` that list them
+//! there. An option is a line starting with `-` at the shallowest indent
+//! such lines have in the section; deeper ones are part of a description.
+
+/// Name of a manual section from its number; sub-sections such as `3p` or
+/// `1ssl` share the name of their main section
+pub fn section_name(section: &str) -> Option<&'static str> {
+    Some(match section.chars().next()? {
+        '1' => "User commands",
+        '2' => "System calls",
+        '3' => "Library functions",
+        '4' => "Devices",
+        '5' => "File formats",
+        '6' => "Games",
+        '7' => "Miscellaneous",
+        '8' => "Administration",
+        _ => return None,
+    })
+}
+
+/// Emphasis of a run of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Plain,
+    Bold,
+    Underline,
+}
+
+/// A stretch of text with one format
+pub type Run = (String, Format);
+
+/// A search hit: `len` characters from character `start` of line `line`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub line: usize,
+    pub start: usize,
+    pub len: usize,
+}
+
+/// An option described on a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flag {
+    /// The flags as written, such as `-a, --all`
+    pub names: String,
+    /// First line of the description
+    pub summary: String,
+    /// Line the option is on
+    pub line: usize,
+}
+
+/// A formatted page, one list of runs per line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Page {
+    pub lines: Vec<Vec<Run>>,
+    /// Section headings and the line each starts on
+    pub sections: Vec<(String, usize)>,
+}
+
+impl Page {
+    /// Parse `man` output (or any plain text)
+    pub fn parse(text: &str) -> Self {
+        let lines: Vec<Vec<Run>> = text.lines().map(parse_line).collect();
+        let sections = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, runs)| {
+                let text = plain_text(runs);
+                is_heading(&text).then(|| (text.trim_end().to_string(), index))
+            })
+            .collect();
+        Page { lines, sections }
+    }
+
+    /// Every occurrence of `query`, ignoring case, in reading order
+    pub fn find(&self, query: &str) -> Vec<Match> {
+        let query: Vec<char> = query.to_lowercase().chars().collect();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut matches = Vec::new();
+        for (line, runs) in self.lines.iter().enumerate() {
+            // Per-character lowering keeps indices aligned with the displayed text
+            let text: Vec<char> = plain_text(runs)
+                .chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c))
+                .collect();
+            let mut start = 0;
+            while start + query.len() <= text.len() {
+                if text[start..start + query.len()] == query[..] {
+                    matches.push(Match { line, start, len: query.len() });
+                    start += query.len();
+                } else {
+                    start += 1;
+                }
+            }
+        }
+        matches
+    }
+
+    /// Text of line `index` without formatting
+    pub fn line_text(&self, index: usize) -> String {
+        self.lines.get(index).map(|runs| plain_text(runs)).unwrap_or_default()
+    }
+
+    /// The options the page describes, in page order
+    pub fn flags(&self) -> Vec<Flag> {
+        // Lines of section `index`, after its heading
+        let range = |index: usize| {
+            let end = self.sections.get(index + 1).map_or(self.lines.len(), |(_, next)| *next);
+            (self.sections[index].1 + 1, end)
+        };
+        let mut ranges: Vec<(usize, usize)> = (0..self.sections.len())
+            .filter(|index| {
+                let name = self.sections[*index].0.to_uppercase();
+                name.contains("OPTION") || name.contains("FLAG")
+            })
+            .map(range)
+            .collect();
+        if ranges.is_empty() {
+            ranges.extend(self.sections.iter().position(|(name, _)| name == "DESCRIPTION").map(range));
+        }
+
+        let mut flags = Vec::new();
+        for (start, end) in ranges {
+            let texts: Vec<String> = (start..end).map(|index| self.line_text(index)).collect();
+            let candidates: Vec<(usize, usize)> = texts
+                .iter()
+                .enumerate()
+                .filter(|(_, text)| split_flags(text).is_some())
+                .map(|(offset, text)| (offset, text.len() - text.trim_start().len()))
+                .collect();
+            let Some(indent) = candidates.iter().map(|(_, indent)| *indent).min() else {
+                continue;
+            };
+            for (offset, _) in candidates.into_iter().filter(|(_, own)| *own == indent) {
+                let Some((_, names, rest)) = split_flags(&texts[offset]) else {
+                    continue;
+                };
+                // The summary follows on the same line or starts the next
+                let summary = match rest.trim() {
+                    "" => texts[offset + 1..]
+                        .iter()
+                        .find(|text| !text.trim().is_empty())
+                        .filter(|text| text.len() - text.trim_start().len() > indent)
+                        .map(|text| text.trim().to_string())
+                        .unwrap_or_default(),
+                    rest => rest.to_string(),
+                };
+                flags.push(Flag { names: names.to_string(), summary, line: start + offset });
+            }
+        }
+        flags
+    }
+
+    /// Heading of the section containing line `index`
+    pub fn section_at(&self, index: usize) -> Option<&str> {
+        self.sections
+            .iter()
+            .rev()
+            .find(|(_, start)| *start <= index)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// First line of the section after the one containing line `index`
+    pub fn next_section(&self, index: usize) -> Option<usize> {
+        self.sections.iter().map(|(_, start)| *start).find(|start| *start > index)
+    }
+
+    /// First line of the section before line `index`; from inside a section
+    /// this is the start of that section
+    pub fn previous_section(&self, index: usize) -> Option<usize> {
+        self.sections.iter().map(|(_, start)| *start).rev().find(|start| *start < index)
+    }
+}
+
+fn plain_text(runs: &[Run]) -> String {
+    runs.iter().map(|(text, _)| text.as_str()).collect()
+}
+
+/// Whether `text` is a man page section heading
+pub fn is_heading(text: &str) -> bool {
+    !text.starts_with(char::is_whitespace)
+        && text.chars().any(char::is_alphabetic)
+        && !text.chars().any(char::is_lowercase)
+}
+
+/// Split an indented option line into indent, flags and description
+pub fn split_flags(line: &str) -> Option<(&str, &str, &str)> {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    if indent.is_empty() || !trimmed.starts_with('-') || trimmed.starts_with("- ") {
+        return None;
+    }
+    // Flags end at a gap of two spaces or a tab before the description
+    let end = [trimmed.find("  "), trimmed.find('\t')].into_iter().flatten().min().unwrap_or(trimmed.len());
+    Some((indent, &trimmed[..end], &trimmed[end..]))
+}
+
+/// Split one line into runs, resolving overstrikes and SGR escapes
+fn parse_line(line: &str) -> Vec<Run> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut runs: Vec<Run> = Vec::new();
+    let mut sgr = Format::Plain;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\x1b' && chars.get(i + 1) == Some(&'[') {
+            let mut end = i + 2;
+            while end < chars.len() && !chars[end].is_ascii_alphabetic() {
+                end += 1;
+            }
+            if chars.get(end) == Some(&'m') {
+                let params: String = chars[i + 2..end].iter().collect();
+                for param in params.split(';') {
+                    match param {
+                        "1" => sgr = Format::Bold,
+                        "4" => sgr = Format::Underline,
+                        "" | "0" | "22" | "24" => sgr = Format::Plain,
+                        _ => {}
+                    }
+                }
+            }
+            i = end + 1;
+            continue;
+        }
+        if c.is_control() && c != '\t' {
+            i += 1;
+            continue;
+        }
+
+        // Each `\b` pair overstrikes the character shown so far
+        let mut shown = c;
+        let mut format = sgr;
+        while chars.get(i + 1) == Some(&'\x08') && i + 2 < chars.len() {
+            let next = chars[i + 2];
+            if shown == '_' && next != '_' {
+                format = Format::Underline;
+                shown = next;
+            } else if next == '_' && shown != '_' {
+                format = Format::Underline;
+            } else {
+                format = Format::Bold;
+                shown = next;
+            }
+            i += 2;
+        }
+
+        match runs.last_mut() {
+            Some((text, last)) if *last == format => text.push(shown),
+            _ => runs.push((shown.to_string(), format)),
+        }
+        i += 1;
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("N\x08NA\x08AM\x08ME\x08E  _\x08f_\x08i_\x08l_\x08e plain"),
+            vec![
+                ("NAME".to_string(), Format::Bold),
+                ("  ".to_string(), Format::Plain),
+                ("file".to_string(), Format::Underline),
+                (" plain".to_string(), Format::Plain),
+            ]
+        );
+        assert_eq!(
+            parse_line("\x1b[1mls\x1b[0m [\x1b[4mOPTION\x1b[24m]"),
+            vec![
+                ("ls".to_string(), Format::Bold),
+                (" [".to_string(), Format::Plain),
+                ("OPTION".to_string(), Format::Underline),
+                ("]".to_string(), Format::Plain),
+            ]
+        );
+        assert_eq!(parse_line("_\x08_"), vec![("_".to_string(), Format::Bold)]);
+    }
+
+    #[test]
+    fn test_sections() {
+        let page = Page::parse("LS(1)   User Commands   LS(1)\n\nNAME\n   ls - list\n\nSEE ALSO\n   dir(1)\n");
+        assert_eq!(page.sections, vec![("NAME".to_string(), 2), ("SEE ALSO".to_string(), 5)]);
+        assert_eq!(page.section_at(4), Some("NAME"));
+        assert_eq!(page.section_at(0), None);
+        assert_eq!(page.next_section(2), Some(5));
+        assert_eq!(page.previous_section(6), Some(5));
+        assert_eq!(page.previous_section(5), Some(2));
+        assert_eq!(page.find("LIST"), vec![Match { line: 3, start: 8, len: 4 }]);
+        assert_eq!(page.find("s").len(), 8);
+    }
+
+    #[test]
+    fn test_flags() {
+        let page = Page::parse("NAME\n   ls\nDESCRIPTION\n   List files.\n\n   -a, --all\n          do not ignore .*\n\n   -l     long listing\n          -- not a flag\n          -x  deeper\n\nAUTHOR\n   -me\n");
+        assert_eq!(
+            page.flags(),
+            vec![
+                Flag { names: "-a, --all".to_string(), summary: "do not ignore .*".to_string(), line: 5 },
+                Flag { names: "-l".to_string(), summary: "long listing".to_string(), line: 8 },
+            ]
+        );
+        let page = Page::parse("OPTIONS\n  --color=WHEN\n\nEXAMPLES\n  -n\n");
+        assert_eq!(page.flags().len(), 1);
+        assert_eq!(page.flags()[0].summary, "");
+    }
+}
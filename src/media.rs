@@ -0,0 +1,241 @@
+//! Details of video and audio files from `ffprobe`.
+//!
+//! Decoding media is out of scope for tt, so previews ask FFmpeg's tools:
+//! `ffprobe -print_format json` describes the container and its streams, and
+//! `ffmpeg` extracts the first video frame as a PNG, which is drawn as text
+//! art like any other image. Without FFmpeg installed the preview says so
+//! instead of treating the file as unreadable binary.
+//!
+//! Cover art embedded in audio files is a video stream to ffprobe; it is
+//! marked as an attached picture and not counted as video.
+
+use crate::jobs::Task;
+use serde_json::Value;
+use std::{io, path::Path, process::Command};
+
+// `.ts` is left out: far more of those are TypeScript than MPEG streams
+const VIDEO_EXTENSIONS: [&str; 11] = ["mp4", "m4v", "mkv", "webm", "mov", "avi", "wmv", "flv", "mpg", "mpeg", "3gp"];
+
+const AUDIO_EXTENSIONS: [&str; 10] = ["mp3", "flac", "wav", "ogg", "oga", "opus", "m4a", "aac", "wma", "aiff"];
+
+/// Whether `path` looks like a video or audio file by its extension
+pub fn is_media_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| VIDEO_EXTENSIONS.contains(&ext.as_str()) || AUDIO_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// One audio track
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioStream {
+    pub codec: String,
+    pub sample_rate: Option<u32>,
+    /// Channel layout like `stereo` or `5.1`, else the number of channels
+    pub channels: Option<String>,
+}
+
+/// What ffprobe reports about a media file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaInfo {
+    pub container: Option<String>,
+    pub duration_secs: Option<f64>,
+    /// Overall bits per second
+    pub bit_rate: Option<u64>,
+    pub video_codec: Option<String>,
+    pub resolution: Option<(u32, u32)>,
+    pub frame_rate: Option<f64>,
+    pub audio: Vec<AudioStream>,
+    /// Title, artist and album tags, in that order, where present
+    pub tags: Vec<(&'static str, String)>,
+}
+
+impl MediaInfo {
+    pub fn has_video(&self) -> bool {
+        self.video_codec.is_some()
+    }
+
+    /// Labelled values for the preview, in display order
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        if let Some(container) = &self.container {
+            fields.push(("Container", container.clone()));
+        }
+        if let Some(duration) = self.duration_secs {
+            fields.push(("Duration", format_duration(duration)));
+        }
+        if let Some((width, height)) = self.resolution {
+            let rate = self.frame_rate.map(|rate| format!(" @ {} fps", format_rate(rate))).unwrap_or_default();
+            fields.push(("Resolution", format!("{}x{}{}", width, height, rate)));
+        }
+        if let Some(codec) = &self.video_codec {
+            fields.push(("Video", codec.clone()));
+        }
+        for audio in &self.audio {
+            let mut parts = vec![audio.codec.clone()];
+            parts.extend(audio.sample_rate.map(|rate| format!("{} kHz", format_rate(rate as f64 / 1000.0))));
+            parts.extend(audio.channels.clone());
+            fields.push(("Audio", parts.join(", ")));
+        }
+        if let Some(bit_rate) = self.bit_rate {
+            fields.push(("Bitrate", format_bit_rate(bit_rate)));
+        }
+        fields.extend(self.tags.iter().cloned());
+        fields
+    }
+}
+
+/// Run ffprobe on `path` in a job; NotFound when FFmpeg isn't installed
+pub fn probe(task: &Task, path: &Path) -> io::Result<MediaInfo> {
+    let output = task.output(Command::new("ffprobe")
+        .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(path))?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    Ok(parse_probe(&json))
+}
+
+/// `MediaInfo` from ffprobe's JSON output
+fn parse_probe(json: &Value) -> MediaInfo {
+    // ffprobe writes most numbers as strings
+    let number = |value: &Value| value.as_str().and_then(|text| text.parse::<f64>().ok()).or_else(|| value.as_f64());
+    let text = |value: &Value| value.as_str().filter(|text| !text.is_empty()).map(str::to_string);
+
+    let format = &json["format"];
+    let mut info = MediaInfo {
+        container: text(&format["format_long_name"]).or_else(|| text(&format["format_name"])),
+        duration_secs: number(&format["duration"]),
+        bit_rate: number(&format["bit_rate"]).map(|rate| rate as u64),
+        ..MediaInfo::default()
+    };
+
+    for stream in json["streams"].as_array().into_iter().flatten() {
+        let codec = text(&stream["codec_name"]).unwrap_or_else(|| "unknown".to_string());
+        match stream["codec_type"].as_str() {
+            Some("video") if stream["disposition"]["attached_pic"].as_u64() != Some(1) && info.video_codec.is_none() => {
+                info.video_codec = Some(codec);
+                info.resolution = stream["width"].as_u64().zip(stream["height"].as_u64()).map(|(w, h)| (w as u32, h as u32));
+                info.frame_rate = stream["avg_frame_rate"].as_str().and_then(parse_ratio);
+            }
+            Some("audio") => info.audio.push(AudioStream {
+                codec,
+                sample_rate: number(&stream["sample_rate"]).map(|rate| rate as u32),
+                channels: text(&stream["channel_layout"])
+                    .or_else(|| stream["channels"].as_u64().map(|channels| format!("{} channels", channels))),
+            }),
+            _ => {}
+        }
+    }
+
+    // Tag names vary in case between containers
+    if let Some(tags) = format["tags"].as_object() {
+        for (label, key) in [("Title", "title"), ("Artist", "artist"), ("Album", "album")] {
+            if let Some(value) = tags.iter().find(|(name, _)| name.eq_ignore_ascii_case(key)).and_then(|(_, value)| text(value)) {
+                info.tags.push((label, value));
+            }
+        }
+    }
+    info
+}
+
+/// A rate like `30000/1001`; `0/0` means unknown
+fn parse_ratio(ratio: &str) -> Option<f64> {
+    let (numerator, denominator) = ratio.split_once('/')?;
+    let (numerator, denominator) = (numerator.parse::<f64>().ok()?, denominator.parse::<f64>().ok()?);
+    (numerator > 0.0 && denominator > 0.0).then(|| numerator / denominator)
+}
+
+/// `1:02:03` or `3:05`
+fn format_duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// Up to two decimals without trailing zeros: `29.97`, `25`, `44.1`
+fn format_rate(rate: f64) -> String {
+    let text = format!("{:.2}", rate);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn format_bit_rate(bits: u64) -> String {
+    if bits >= 1_000_000 {
+        format!("{:.1} Mb/s", bits as f64 / 1_000_000.0)
+    } else {
+        format!("{} kb/s", bits / 1000)
+    }
+}
+
+/// The first video frame of `path` as a PNG, via ffmpeg in a job
+pub fn first_frame(task: &Task, path: &Path) -> Option<Vec<u8>> {
+    let output = task
+        .output(Command::new("ffmpeg")
+            .args(["-v", "error", "-i"])
+            .arg(path)
+            .args(["-frames:v", "1", "-f", "image2pipe", "-c:v", "png", "-"]))
+        .ok()?;
+    (output.status.success() && !output.stdout.is_empty()).then_some(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_probe() {
+        let json: Value = serde_json::from_str(
+            r#"{
+                "streams": [
+                    {"codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080, "avg_frame_rate": "30000/1001"},
+                    {"codec_type": "audio", "codec_name": "aac", "sample_rate": "48000", "channel_layout": "stereo"},
+                    {"codec_type": "audio", "codec_name": "ac3", "sample_rate": "44100", "channels": 6}
+                ],
+                "format": {"format_long_name": "QuickTime / MOV", "duration": "3723.4", "bit_rate": "5012345", "tags": {"TITLE": "Holiday"}}
+            }"#,
+        )
+        .unwrap();
+        let info = parse_probe(&json);
+        assert!(info.has_video());
+        assert_eq!(
+            info.fields(),
+            vec![
+                ("Container", "QuickTime / MOV".to_string()),
+                ("Duration", "1:02:03".to_string()),
+                ("Resolution", "1920x1080 @ 29.97 fps".to_string()),
+                ("Video", "h264".to_string()),
+                ("Audio", "aac, 48 kHz, stereo".to_string()),
+                ("Audio", "ac3, 44.1 kHz, 6 channels".to_string()),
+                ("Bitrate", "5.0 Mb/s".to_string()),
+                ("Title", "Holiday".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cover_art_is_not_video() {
+        let json: Value = serde_json::from_str(
+            r#"{
+                "streams": [
+                    {"codec_type": "audio", "codec_name": "mp3", "sample_rate": "44100", "channel_layout": "stereo"},
+                    {"codec_type": "video", "codec_name": "mjpeg", "width": 500, "height": 500, "disposition": {"attached_pic": 1}}
+                ],
+                "format": {"format_name": "mp3", "duration": "185.0", "bit_rate": "320000", "tags": {"artist": "Band", "album": "Record"}}
+            }"#,
+        )
+        .unwrap();
+        let info = parse_probe(&json);
+        assert!(!info.has_video());
+        assert_eq!(info.resolution, None);
+        assert_eq!(format_duration(info.duration_secs.unwrap()), "3:05");
+        assert_eq!(format_bit_rate(info.bit_rate.unwrap()), "320 kb/s");
+        assert_eq!(info.tags, vec![("Artist", "Band".to_string()), ("Album", "Record".to_string())]);
+        assert!(is_media_file(Path::new("song.FLAC")));
+        assert!(!is_media_file(Path::new("notes.txt")));
+    }
+}
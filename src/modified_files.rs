@@ -0,0 +1,150 @@
+//! Recently modified files, for `tt recent` before any history exists.
+//!
+//! The current directory and the home directory are walked for files
+//! modified within [`MAX_AGE`], newest first. The walk reads metadata itself
+//! rather than running `find -printf`, which only GNU find understands, so it
+//! works the same on Linux, macOS and the BSDs. Hidden files and directories
+//! are skipped, as are dependency and build directories that change all the
+//! time without being edited.
+//!
+//! A home directory can hold millions of files, so the walk is bounded: it
+//! goes at most [`MAX_DEPTH`] directories down and gives up after looking at
+//! [`MAX_ENTRIES`] entries. It runs on a background thread and reports its
+//! progress over a channel like the `tt du` scan ([`crate::disk_usage`]).
+
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, SystemTime},
+};
+use walkdir::{DirEntry, WalkDir};
+
+/// How recently a file must have changed
+pub const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Directory levels walked below each starting directory
+pub const MAX_DEPTH: usize = 6;
+
+/// Entries looked at before the walk gives up
+pub const MAX_ENTRIES: u64 = 200_000;
+
+/// Entries between progress reports, to keep the channel quiet
+const PROGRESS_INTERVAL: u64 = 1000;
+
+/// Directories that are never worth walking
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "__pycache__", "venv"];
+
+/// Events sent by a background scan
+#[derive(Debug)]
+pub enum ScanEvent {
+    /// Entries looked at so far
+    Progress(u64),
+    /// Files found, newest first, and whether the walk stopped early
+    Finished { files: Vec<PathBuf>, truncated: bool },
+}
+
+/// Start looking for files modified within [`MAX_AGE`] below `dirs`,
+/// keeping the newest `limit`
+pub fn spawn_scan(dirs: Vec<PathBuf>, limit: usize) -> Receiver<ScanEvent> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let since = SystemTime::now() - MAX_AGE;
+        let mut scanned = 0;
+        let (files, truncated) = scan(&dirs, since, limit, MAX_DEPTH, MAX_ENTRIES, &mut |_| {
+            scanned += 1;
+            if scanned % PROGRESS_INTERVAL == 0 {
+                let _ = tx.send(ScanEvent::Progress(scanned));
+            }
+        });
+        let _ = tx.send(ScanEvent::Finished { files, truncated });
+    });
+    rx
+}
+
+/// Whether the walk should go into or list `entry`
+fn is_visible(entry: &DirEntry) -> bool {
+    // The starting directory is walked even when it is hidden
+    if entry.depth() == 0 {
+        return true;
+    }
+    let name = entry.file_name().to_string_lossy();
+    let skipped = entry.file_type().is_dir() && SKIPPED_DIRS.contains(&name.as_ref());
+    !name.starts_with('.') && !skipped
+}
+
+/// Files below `dirs` modified after `since`, newest first and at most
+/// `limit` of them, looking at no more than `max_entries` entries; calls
+/// `on_entry` for each entry looked at. Also returns whether the walk
+/// stopped early.
+pub fn scan(
+    dirs: &[PathBuf],
+    since: SystemTime,
+    limit: usize,
+    max_depth: usize,
+    max_entries: u64,
+    on_entry: &mut dyn FnMut(&DirEntry),
+) -> (Vec<PathBuf>, bool) {
+    let mut found: Vec<(SystemTime, PathBuf)> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut scanned = 0;
+    for dir in dirs {
+        let walk = WalkDir::new(dir).max_depth(max_depth).into_iter().filter_entry(is_visible);
+        for entry in walk.filter_map(Result::ok) {
+            if scanned == max_entries {
+                return (newest(found, limit), true);
+            }
+            scanned += 1;
+            on_entry(&entry);
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Some(modified) = entry.metadata().ok().and_then(|meta| meta.modified().ok()) else {
+                continue;
+            };
+            // The home directory usually contains the current one
+            if modified >= since && seen.insert(entry.path().to_path_buf()) {
+                found.push((modified, entry.into_path()));
+            }
+        }
+    }
+    (newest(found, limit), false)
+}
+
+fn newest(mut found: Vec<(SystemTime, PathBuf)>, limit: usize) -> Vec<PathBuf> {
+    found.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    found.into_iter().take(limit).map(|(_, path)| path).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, time::UNIX_EPOCH};
+
+    #[test]
+    fn test_scan() {
+        let dir = env::temp_dir().join(format!("tt-modified-files-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("a/b/c")).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        fs::create_dir_all(dir.join("node_modules")).unwrap();
+        for file in ["top", "a/one", "a/b/c/deep", ".hidden", ".git/HEAD", "node_modules/x"] {
+            fs::write(dir.join(file), file).unwrap();
+        }
+
+        let dirs = [dir.clone(), dir.join("a")];
+        let (mut files, truncated) = scan(&dirs, UNIX_EPOCH, 10, 2, u64::MAX, &mut |_| {});
+        files.sort();
+        assert_eq!(files, vec![dir.join("a/one"), dir.join("top")]);
+        assert!(!truncated);
+
+        let (files, truncated) = scan(&dirs, UNIX_EPOCH, 10, 5, 2, &mut |_| {});
+        assert!(truncated);
+        assert!(files.len() <= 1);
+
+        let (files, _) = scan(&dirs, SystemTime::now() + MAX_AGE, 10, 5, u64::MAX, &mut |_| {});
+        assert!(files.is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
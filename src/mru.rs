@@ -0,0 +1,287 @@
+//! Most-recently-used files for `tt recent`.
+//!
+//! The list lives in `~/.cache/fzf-mru.txt`, one absolute path per line with
+//! the newest last, the format the original shell scripts used, so both keep
+//! feeding the same history. Files opened from `tt find`, `tt search`,
+//! `tt explore`, `tt todo` and `tt recent` itself are recorded by
+//! [`open_in_editor`]: a path already listed moves to the end instead of
+//! appearing twice, and only the newest [`MAX_ENTRIES`] are kept.
+//!
+//! The file is rewritten with [`file_ops::replace_contents`], so a reader
+//! never sees half a list even with several tools writing.
+//!
+//! Pinned files, kept at the top of `tt recent`, are listed in
+//! `$XDG_STATE_HOME/terminal-tools/recent-pins`. Removing a file takes it off the MRU
+//! list and adds it to `recent-hidden`, which also keeps it out of the editor
+//! histories tt reads but cannot change; opening it again un-hides it.
+//!
+//! Files edited from the shell count too: the prompt hook of `tt init` logs
+//! every command through `tt hist --record`, which records the file
+//! arguments of editor commands (`vim notes.md`, `code -g main.rs`).
+//!
+//! How often and how lately each file was opened is kept in
+//! `recent-opens`, in the `path|rank|last_access` format of `tt z`'s store
+//! and aged the same way (see [`crate::frecency`]), so the most used files
+//! rank first.
+
+use crate::config::Settings;
+use crate::frecency::{self, StoreEntry};
+use crate::{file_ops, platform, time_util};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Paths kept in the list
+pub const MAX_ENTRIES: usize = 1000;
+
+/// Location of the MRU list
+pub fn mru_file() -> Option<PathBuf> {
+    platform::home_dir().map(|home| home.join(".cache").join("fzf-mru.txt"))
+}
+
+/// Recorded paths, newest first
+pub fn load() -> Vec<PathBuf> {
+    mru_file()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .map(|content| parse(&content))
+        .unwrap_or_default()
+}
+
+fn parse(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .rev()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+fn no_home() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "No home directory for the MRU list")
+}
+
+/// Record `path` as opened, then open it (at `line`) in the configured
+/// editor or a common one; returns whether an editor ran
+pub fn open_in_editor(settings: &Settings, path: &Path, line: Option<usize>) -> bool {
+    // Failing to update the list must not stop the file opening
+    let _ = record(path);
+    settings.editor_commands(path, line).into_iter().any(|mut command| command.status().is_ok())
+}
+
+/// Record that `path` was opened
+pub fn record(path: &Path) -> io::Result<()> {
+    let file = mru_file().ok_or_else(no_home)?;
+    let path = path.canonicalize()?;
+    let content = fs::read_to_string(&file).unwrap_or_default();
+    file_ops::replace_contents(&file, add(&content, &path, MAX_ENTRIES).as_bytes())?;
+    update_opens(|entries| frecency::record_visit(entries, &path, time_util::now_secs()))?;
+    if hidden().contains(&path) {
+        update_list("recent-hidden", |line| line != path.to_string_lossy())?;
+    }
+    Ok(())
+}
+
+/// Editors whose file arguments count as opened files, besides the
+/// programs in `$EDITOR` and `$VISUAL`
+const EDITORS: &[&str] = &[
+    "vi", "vim", "nvim", "gvim", "view", "nano", "pico", "emacs", "emacsclient", "hx", "helix", "micro", "kak",
+    "code", "codium", "subl", "gedit", "kate", "mousepad", "ed", "joe", "mcedit",
+];
+
+/// Existing files a shell command line run in `cwd` opens in an editor
+pub fn files_opened_by(command: &str, cwd: &Path) -> Vec<PathBuf> {
+    let editor_vars: Vec<String> = ["EDITOR", "VISUAL"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .filter_map(|editor| editor.split_whitespace().next().map(program_name))
+        .collect();
+    let words = shell_words(command);
+    let mut words = words.iter().map(String::as_str).skip_while(|word| word.contains('=') || *word == "sudo");
+    let Some(program) = words.next().map(program_name) else {
+        return Vec::new();
+    };
+    if !EDITORS.contains(&program.as_str()) && !editor_vars.contains(&program) {
+        return Vec::new();
+    }
+    words
+        // Options, and `+42` line jumps
+        .filter(|word| !word.starts_with('-') && !word.starts_with('+'))
+        .map(|word| match word.strip_prefix("~/") {
+            Some(rest) => platform::home_dir().map(|home| home.join(rest)).unwrap_or_else(|| PathBuf::from(word)),
+            None => cwd.join(word),
+        })
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// File name of `program` without directories
+fn program_name(program: &str) -> String {
+    Path::new(program).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Split a command line into words, honouring quotes and backslashes;
+/// anything fancier (variables, globs, pipes) is taken literally
+fn shell_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => word.get_or_insert_with(String::new).extend(chars.next()),
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            // The editor command ends where the next one starts
+            (None, ';' | '&' | '|') => break,
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+/// A list of paths tt keeps in its state directory
+fn state_list(name: &str) -> Option<PathBuf> {
+    platform::state_dir().map(|dir| dir.join(name))
+}
+
+fn read_list(name: &str) -> Vec<PathBuf> {
+    state_list(name)
+        .and_then(|file| fs::read_to_string(file).ok())
+        .map(|content| content.lines().filter(|line| !line.is_empty()).map(PathBuf::from).collect())
+        .unwrap_or_default()
+}
+
+/// Rewrite the state list `name`, keeping the lines `keep` accepts
+fn update_list(name: &str, keep: impl Fn(&str) -> bool) -> io::Result<()> {
+    let file = state_list(name).ok_or_else(no_home)?;
+    let content = fs::read_to_string(&file).unwrap_or_default();
+    file_ops::replace_contents(&file, retain(&content, keep).as_bytes())
+}
+
+fn append_list(name: &str, path: &Path) -> io::Result<()> {
+    let file = state_list(name).ok_or_else(no_home)?;
+    let content = fs::read_to_string(&file).unwrap_or_default();
+    file_ops::replace_contents(&file, add(&content, path, usize::MAX).as_bytes())
+}
+
+/// The lines of `content` that `keep` accepts
+fn retain(content: &str, keep: impl Fn(&str) -> bool) -> String {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && keep(line))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn read_opens() -> Vec<StoreEntry> {
+    state_list("recent-opens")
+        .and_then(|file| fs::read_to_string(file).ok())
+        .map(|content| frecency::parse_store(&content))
+        .unwrap_or_default()
+}
+
+fn update_opens(change: impl FnOnce(&mut Vec<StoreEntry>)) -> io::Result<()> {
+    let file = state_list("recent-opens").ok_or_else(no_home)?;
+    let mut entries = read_opens();
+    change(&mut entries);
+    file_ops::replace_contents(&file, frecency::format_store(&entries).as_bytes())
+}
+
+/// Rank and latest open time of every file opened through tt
+pub fn opens() -> HashMap<PathBuf, StoreEntry> {
+    read_opens().into_iter().map(|entry| (entry.path.clone(), entry)).collect()
+}
+
+/// Pinned files, in the order they were pinned
+pub fn pinned() -> Vec<PathBuf> {
+    read_list("recent-pins")
+}
+
+/// Pin or unpin `path`
+pub fn set_pinned(path: &Path, pin: bool) -> io::Result<()> {
+    if pin {
+        append_list("recent-pins", path)
+    } else {
+        update_list("recent-pins", |line| Path::new(line) != path)
+    }
+}
+
+/// Files removed from the recent list
+pub fn hidden() -> HashSet<PathBuf> {
+    read_list("recent-hidden").into_iter().collect()
+}
+
+/// Take `path` off the MRU list and the pins, and keep it out of the recent
+/// list until it is opened again
+pub fn remove(path: &Path) -> io::Result<()> {
+    let file = mru_file().ok_or_else(no_home)?;
+    let content = fs::read_to_string(&file).unwrap_or_default();
+    file_ops::replace_contents(&file, retain(&content, |line| Path::new(line) != path).as_bytes())?;
+    set_pinned(path, false)?;
+    update_opens(|entries| entries.retain(|entry| entry.path != path))?;
+    append_list("recent-hidden", path)
+}
+
+/// Drop files that no longer exist from the MRU list, the pins, the open
+/// counts and the hidden list; returns how many MRU entries and pins went
+pub fn purge_missing() -> io::Result<usize> {
+    let file = mru_file().ok_or_else(no_home)?;
+    let gone = load().iter().chain(pinned().iter()).filter(|path| !path.exists()).count();
+    let content = fs::read_to_string(&file).unwrap_or_default();
+    file_ops::replace_contents(&file, retain(&content, |line| Path::new(line).exists()).as_bytes())?;
+    update_list("recent-pins", |line| Path::new(line).exists())?;
+    update_list("recent-hidden", |line| Path::new(line).exists())?;
+    update_opens(|entries| entries.retain(|entry| entry.path.exists()))?;
+    Ok(gone)
+}
+
+/// `content` with `path` moved or added to the end, keeping the newest `cap`
+fn add(content: &str, path: &Path, cap: usize) -> String {
+    let path = path.to_string_lossy();
+    let mut lines: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && *line != path)
+        .collect();
+    lines.push(&path);
+    let skip = lines.len().saturating_sub(cap);
+    lines[skip..].iter().map(|line| format!("{}\n", line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        let content = "/a\n/b\n\n/c\n";
+        assert_eq!(add(content, Path::new("/b"), 10), "/a\n/c\n/b\n");
+        assert_eq!(add(content, Path::new("/d"), 3), "/b\n/c\n/d\n");
+        assert_eq!(add("", Path::new("/a"), 3), "/a\n");
+        assert_eq!(parse("/a\n/c\n/b\n"), vec![PathBuf::from("/b"), PathBuf::from("/c"), PathBuf::from("/a")]);
+        assert_eq!(retain("/a\n\n/b\n/c\n", |line| line != "/b"), "/a\n/c\n");
+    }
+
+    #[test]
+    fn test_files_opened_by() {
+        assert_eq!(shell_words(r#"vim "my notes.md" it\'s 'a b' && ls"#), vec!["vim", "my notes.md", "it's", "a b"]);
+
+        let dir = std::env::temp_dir().join(format!("tt-mru-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.txt"), "").unwrap();
+        assert_eq!(files_opened_by("sudo /usr/bin/nvim -O +3 a.txt missing.txt", &dir), vec![dir.join("a.txt")]);
+        assert!(files_opened_by("cat a.txt", &dir).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+//! Status line and notifications shared by every tool.
+//!
+//! Each tool keeps a [`Notifications`] and shows it in its status bar with
+//! [`crate::tui_common::render_status_bar`]. It holds two kinds of message:
+//!
+//! - the **status**, describing the current state ("Found 120 files",
+//!   "Scanning /home..."), which stays until the tool replaces it
+//! - **toasts**, reporting what just happened at an info, success or error
+//!   [`Level`]; a toast covers the status in its level's color for
+//!   [`TOAST_DURATION`] ([`ERROR_DURATION`] for errors) and then goes away
+//!
+//! The last [`HISTORY_LIMIT`] toasts are kept, and `Ctrl-L` lists them in a
+//! popup, so a message that went by too quickly can still be read.
+//!
+//! Background threads report through a [`Notifier`], a cloneable handle that
+//! sends toasts over a channel. The event loop collects them before every
+//! frame, so a failing background task shows an error without stopping the
+//! tool or making it wait.
+//!
+//! The tool's background [`Jobs`] are kept here too, so the status bar can
+//! show a spinner while they run.
+
+use crate::jobs::Jobs;
+use crate::time_util;
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant},
+};
+
+/// How long info and success toasts stay up
+pub const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// How long error toasts stay up
+pub const ERROR_DURATION: Duration = Duration::from_secs(8);
+
+/// Toasts kept for the history popup
+pub const HISTORY_LIMIT: usize = 100;
+
+/// Kind of a toast, which sets its color and how long it stays up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Success,
+    Error,
+}
+
+impl Level {
+    pub fn duration(self) -> Duration {
+        match self {
+            Level::Info | Level::Success => TOAST_DURATION,
+            Level::Error => ERROR_DURATION,
+        }
+    }
+
+    /// Marker shown before the message
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Level::Info => "•",
+            Level::Success => "✓",
+            Level::Error => "✗",
+        }
+    }
+}
+
+/// A toast, as shown and as kept in the history
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub text: String,
+    /// When it was raised, in seconds since the Unix epoch
+    pub time: i64,
+    /// When it was first shown, for the timeout
+    shown: Instant,
+}
+
+impl Notification {
+    fn new(level: Level, text: String) -> Self {
+        Notification { level, text, time: time_util::now_secs(), shown: Instant::now() }
+    }
+}
+
+/// Status line, current toast and toast history of one tool
+#[derive(Debug)]
+pub struct Notifications {
+    status: String,
+    /// Oldest first; the last one is the current toast until it times out
+    history: VecDeque<Notification>,
+    tx: Sender<Notification>,
+    rx: Receiver<Notification>,
+    jobs: Jobs,
+    /// Spinner frame at the last poll, to redraw when it turns
+    spinner: Option<char>,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Notifications {
+            status: String::new(),
+            history: VecDeque::new(),
+            tx,
+            rx,
+            jobs: Jobs::default(),
+            spinner: None,
+        }
+    }
+
+    /// Start with `status` on the status line
+    pub fn with_status(status: impl Into<String>) -> Self {
+        let mut notifications = Self::new();
+        notifications.set_status(status);
+        notifications
+    }
+
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(Level::Info, text);
+    }
+
+    pub fn success(&mut self, text: impl Into<String>) {
+        self.push(Level::Success, text);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(Level::Error, text);
+    }
+
+    /// Show a toast, replacing the current one
+    pub fn push(&mut self, level: Level, text: impl Into<String>) {
+        self.add(Notification::new(level, text.into()));
+    }
+
+    fn add(&mut self, notification: Notification) {
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(notification);
+    }
+
+    /// Handle for background threads to raise toasts with
+    pub fn notifier(&self) -> Notifier {
+        Notifier(self.tx.clone())
+    }
+
+    /// The tool's background jobs, to start them and list them
+    pub fn jobs(&self) -> &Jobs {
+        &self.jobs
+    }
+
+    /// Show the toasts sent by background threads since the last call;
+    /// returns whether there were any, or the job spinner turned
+    pub fn poll(&mut self) -> bool {
+        let spinner = self.jobs.spinner();
+        let mut changed = spinner != self.spinner;
+        self.spinner = spinner;
+        while let Ok(mut notification) = self.rx.try_recv() {
+            // The timeout starts when it appears, not when it was sent
+            notification.shown = Instant::now();
+            self.add(notification);
+            changed = true;
+        }
+        changed
+    }
+
+    /// The toast to show now, if one is up
+    pub fn current(&self) -> Option<&Notification> {
+        self.current_at(Instant::now())
+    }
+
+    fn current_at(&self, now: Instant) -> Option<&Notification> {
+        let last = self.history.back()?;
+        (now.saturating_duration_since(last.shown) < last.level.duration()).then_some(last)
+    }
+
+    /// Every kept toast, oldest first
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &Notification> {
+        self.history.iter()
+    }
+}
+
+/// Raises toasts from another thread; see [`Notifications::notifier`]
+#[derive(Debug, Clone)]
+pub struct Notifier(Sender<Notification>);
+
+impl Notifier {
+    pub fn error(&self, text: impl Into<String>) {
+        self.push(Level::Error, text);
+    }
+
+    /// Send a toast; it is dropped if the tool has already quit
+    pub fn push(&self, level: Level, text: impl Into<String>) {
+        let _ = self.0.send(Notification::new(level, text.into()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_toasts() {
+        let mut notifications = Notifications::with_status("Found 3 files");
+        assert!(notifications.current().is_none());
+
+        notifications.success("Removed a.txt");
+        let now = Instant::now();
+        assert_eq!(notifications.current_at(now).unwrap().text, "Removed a.txt");
+        assert!(notifications.current_at(now + TOAST_DURATION).is_none());
+
+        notifications.error("Could not remove b.txt");
+        assert!(notifications.current_at(now + TOAST_DURATION).is_some());
+        assert!(notifications.current_at(now + ERROR_DURATION + Duration::from_secs(1)).is_none());
+        assert_eq!(notifications.status(), "Found 3 files");
+        let levels: Vec<_> = notifications.history().map(|n| n.level).collect();
+        assert_eq!(levels, vec![Level::Success, Level::Error]);
+
+        for i in 0..HISTORY_LIMIT {
+            notifications.info(i.to_string());
+        }
+        assert_eq!(notifications.history().count(), HISTORY_LIMIT);
+        assert_eq!(notifications.history().next().unwrap().text, "0");
+    }
+
+    #[test]
+    fn test_notifier() {
+        let mut notifications = Notifications::new();
+        let notifier = notifications.notifier();
+        thread::spawn(move || notifier.error("Scan failed")).join().unwrap();
+        assert!(notifications.current().is_none());
+
+        assert!(notifications.poll());
+        assert!(!notifications.poll());
+        let current = notifications.current().unwrap();
+        assert_eq!(current.level, Level::Error);
+        assert_eq!(current.text, "Scan failed");
+    }
+}
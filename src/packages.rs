@@ -0,0 +1,367 @@
+//! Package managers behind `tt pkg`: apt, dnf, pacman and Homebrew.
+//!
+//! The first manager found on `PATH` is used, in that order, so a Linux
+//! system with Homebrew installed alongside still browses its own packages.
+//! Each manager is asked for every package it can install, with a one-line
+//! description where it has one, and separately for what is installed:
+//!
+//! - **apt**: `apt-cache search --names-only .`, installed from `dpkg-query`
+//! - **dnf**: `dnf repoquery`, installed from `rpm -qa`
+//! - **pacman**: `pacman -Ss`, which marks installed packages itself
+//! - **brew**: `brew formulae` (names only), installed from `brew list`
+//!
+//! The details of a package come from `apt-cache show`, `dnf info`,
+//! `pacman -Si` or `brew info`, with its dependencies picked out of them
+//! (dnf has a separate `dnf repoquery --requires`).
+//!
+//! Installing and removing go through sudo unless `tt` runs as root (Homebrew
+//! refuses sudo). They are run on the terminal so the manager can ask for a
+//! password and confirmation and show its progress.
+
+use crate::help_text;
+use crate::users;
+use std::{collections::HashMap, io, process::Command};
+
+/// A supported package manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Manager {
+    Apt,
+    Dnf,
+    Pacman,
+    Brew,
+}
+
+impl Manager {
+    /// In the order they are looked for
+    pub const ALL: [Manager; 4] = [Manager::Apt, Manager::Dnf, Manager::Pacman, Manager::Brew];
+
+    /// The first manager on `PATH`
+    pub fn detect() -> Option<Manager> {
+        Manager::ALL.into_iter().find(|manager| help_text::find_command(manager.program()).is_some())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Manager::Apt => "apt",
+            Manager::Dnf => "dnf",
+            Manager::Pacman => "pacman",
+            Manager::Brew => "brew",
+        }
+    }
+
+    /// The executable that tells the manager is installed
+    fn program(self) -> &'static str {
+        match self {
+            Manager::Apt => "apt-cache",
+            other => other.name(),
+        }
+    }
+}
+
+/// A package that can be installed, or is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    /// The installed version, or else the available one when the manager
+    /// lists it; may be empty
+    pub version: String,
+    pub description: String,
+    pub installed: bool,
+}
+
+impl Package {
+    fn new(name: &str, version: &str, description: &str) -> Package {
+        Package {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: description.trim().to_string(),
+            installed: false,
+        }
+    }
+}
+
+/// Every package `manager` knows, installed or not, sorted by name
+pub fn list(manager: Manager) -> io::Result<Vec<Package>> {
+    let (mut packages, installed) = match manager {
+        Manager::Apt => (
+            parse_apt_search(&output(Command::new("apt-cache").args(["search", "--names-only", "."]))?),
+            parse_dpkg_query(&output(Command::new("dpkg-query").args(["-W", "-f", "${Package}\t${Version}\t${db:Status-Abbrev}\n"]))?),
+        ),
+        Manager::Dnf => (
+            parse_tab_separated(&output(Command::new("dnf").args([
+                "repoquery",
+                "--quiet",
+                "--latest-limit",
+                "1",
+                "--queryformat",
+                "%{name}\\t%{evr}\\t%{summary}\\n",
+            ]))?),
+            parse_tab_separated(&output(Command::new("rpm").args(["-qa", "--queryformat", "%{NAME}\t%{VERSION}-%{RELEASE}\n"]))?)
+                .into_iter()
+                .map(|package| (package.name, package.version))
+                .collect(),
+        ),
+        Manager::Pacman => (parse_pacman_search(&output(Command::new("pacman").arg("-Ss"))?), HashMap::new()),
+        Manager::Brew => (
+            output(Command::new("brew").arg("formulae"))?.lines().map(|name| Package::new(name.trim(), "", "")).collect(),
+            parse_brew_list(&output(Command::new("brew").args(["list", "--formula", "--versions"]))?),
+        ),
+    };
+    mark_installed(&mut packages, installed);
+    Ok(packages)
+}
+
+/// Flag the packages in `installed` (name to version), adding any that
+/// no repository offers any more, then sort by name
+fn mark_installed(packages: &mut Vec<Package>, mut installed: HashMap<String, String>) {
+    for package in packages.iter_mut() {
+        if let Some(version) = installed.remove(&package.name) {
+            package.installed = true;
+            package.version = version;
+        }
+    }
+    packages.extend(installed.into_iter().map(|(name, version)| Package { installed: true, ..Package::new(&name, &version, "") }));
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    packages.dedup_by(|a, b| a.name == b.name);
+}
+
+/// Stdout of `command`, or its last line of stderr as the error
+fn output(command: &mut Command) -> io::Result<String> {
+    let output = command.output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("failed");
+        return Err(io::Error::other(message.trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse `apt-cache search`: `name - description`
+fn parse_apt_search(text: &str) -> Vec<Package> {
+    text.lines()
+        .filter_map(|line| line.split_once(" - "))
+        .map(|(name, description)| Package::new(name.trim(), "", description))
+        .collect()
+}
+
+/// Parse `dpkg-query -W` lines of name, version and status abbreviation,
+/// keeping the installed packages (`ii`)
+fn parse_dpkg_query(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let (name, version, status) = (fields.next()?, fields.next()?, fields.next()?);
+            status.starts_with("ii").then(|| (name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+/// Parse tab-separated name, version and optional description, as dnf and
+/// rpm are asked to print them
+fn parse_tab_separated(text: &str) -> Vec<Package> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let name = fields.next().filter(|name| !name.trim().is_empty())?;
+            Some(Package::new(name.trim(), fields.next().unwrap_or(""), fields.next().unwrap_or("")))
+        })
+        .collect()
+}
+
+/// Parse `pacman -Ss`: `repo/name version [installed]` followed by an
+/// indented description
+fn parse_pacman_search(text: &str) -> Vec<Package> {
+    let mut packages: Vec<Package> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with(char::is_whitespace) {
+            if let Some(package) = packages.last_mut() {
+                package.description = line.trim().to_string();
+            }
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let (Some(qualified), Some(version)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let name = qualified.split_once('/').map_or(qualified, |(_, name)| name);
+        let mut package = Package::new(name, version, "");
+        package.installed = line.contains("[installed");
+        packages.push(package);
+    }
+    packages
+}
+
+/// Parse `brew list --versions`: a name and its installed versions
+fn parse_brew_list(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?;
+            Some((name.to_string(), fields.last().unwrap_or("").to_string()))
+        })
+        .collect()
+}
+
+/// Details of one package
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Info {
+    /// The manager's description, as it prints it
+    pub lines: Vec<String>,
+    /// Names of the packages it depends on
+    pub dependencies: Vec<String>,
+}
+
+/// Details and dependencies of the package `name`
+pub fn info(manager: Manager, name: &str) -> io::Result<Info> {
+    let text = match manager {
+        Manager::Apt => output(Command::new("apt-cache").args(["show", "--no-all-versions", name]))?,
+        Manager::Dnf => output(Command::new("dnf").args(["info", "--quiet", name]))?,
+        // Packages no repository offers are only known locally
+        Manager::Pacman => output(Command::new("pacman").args(["-Si", name]))
+            .or_else(|_| output(Command::new("pacman").args(["-Qi", name])))?,
+        Manager::Brew => output(Command::new("brew").args(["info", name]))?,
+    };
+    let dependencies = match manager {
+        Manager::Apt => apt_dependencies(&text),
+        Manager::Dnf => output(Command::new("dnf").args(["repoquery", "--quiet", "--requires", "--resolve", "--queryformat", "%{name}\\n", name]))
+            .map(|text| split_dependencies(&text, '\n'))
+            .unwrap_or_default(),
+        Manager::Pacman => field(&text, "Depends On").map(|value| split_dependencies(&value, ' ')).unwrap_or_default(),
+        Manager::Brew => brew_dependencies(&text),
+    };
+    let mut lines: Vec<String> = text.lines().map(|line| line.trim_end().to_string()).collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    Ok(Info { lines, dependencies })
+}
+
+/// The value of `key` in `Key: value` output, with indented continuation
+/// lines joined on
+fn field(text: &str, key: &str) -> Option<String> {
+    let mut lines = text.lines();
+    let first = lines.by_ref().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        (!line.starts_with(char::is_whitespace) && name.trim() == key).then_some(value)
+    })?;
+    let mut value = first.trim().to_string();
+    for line in lines.take_while(|line| line.starts_with(char::is_whitespace)) {
+        value.push(' ');
+        value.push_str(line.trim());
+    }
+    Some(value)
+}
+
+/// Package names in a dependency list separated by `separator`, without
+/// version constraints, architecture qualifiers or duplicates; apt's
+/// alternatives (`a | b`) count as separate dependencies
+fn split_dependencies(list: &str, separator: char) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for spec in list.split(separator).flat_map(|spec| spec.split('|')) {
+        let name = spec.trim().split([' ', '(', '<', '>', '=', ':']).next().unwrap_or("");
+        if !name.is_empty() && name != "None" && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Dependencies from the `Pre-Depends` and `Depends` fields of
+/// `apt-cache show`
+fn apt_dependencies(text: &str) -> Vec<String> {
+    let fields: Vec<String> = ["Pre-Depends", "Depends"].iter().filter_map(|key| field(text, key)).collect();
+    split_dependencies(&fields.join(","), ',')
+}
+
+/// Dependencies from the `Required:` line under `brew info`'s
+/// `==> Dependencies` heading
+fn brew_dependencies(text: &str) -> Vec<String> {
+    text.lines()
+        .skip_while(|line| !line.starts_with("==> Dependencies"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("==>"))
+        .filter_map(|line| line.strip_prefix("Required:"))
+        .flat_map(|list| split_dependencies(list, ','))
+        .collect()
+}
+
+/// What can be done to a package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageAction {
+    Install,
+    Remove,
+}
+
+impl PackageAction {
+    pub fn verb(self) -> &'static str {
+        match self {
+            PackageAction::Install => "install",
+            PackageAction::Remove => "remove",
+        }
+    }
+
+    /// The command line for the shell, with sudo where it is needed.
+    /// Package names are made of letters, digits and `+-._@`, which the
+    /// shell leaves alone.
+    pub fn command(self, manager: Manager, name: &str) -> String {
+        let command = match (manager, self) {
+            (Manager::Apt, PackageAction::Install) => "apt install",
+            (Manager::Apt, PackageAction::Remove) => "apt remove",
+            (Manager::Dnf, PackageAction::Install) => "dnf install",
+            (Manager::Dnf, PackageAction::Remove) => "dnf remove",
+            (Manager::Pacman, PackageAction::Install) => "pacman -S",
+            (Manager::Pacman, PackageAction::Remove) => "pacman -R",
+            (Manager::Brew, PackageAction::Install) => "brew install",
+            (Manager::Brew, PackageAction::Remove) => "brew uninstall",
+        };
+        let sudo = if manager != Manager::Brew && !users::is_root() { "sudo " } else { "" };
+        format!("{}{} {}", sudo, command, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lists() {
+        let mut packages = parse_apt_search("ripgrep - Recursively searches directories for a regex pattern\nvim - Vi IMproved - enhanced vi editor\n");
+        let installed = parse_dpkg_query("vim\t2:9.0.1378-2\tii \nripgrep\t13.0.0-4\trc \nlocal-tool\t1.0\tii \n");
+        mark_installed(&mut packages, installed);
+        assert_eq!(packages.len(), 3);
+        assert_eq!(packages[0].name, "local-tool");
+        assert!(!packages[1].installed);
+        assert_eq!((packages[2].version.as_str(), packages[2].installed), ("2:9.0.1378-2", true));
+        assert_eq!(packages[2].description, "Vi IMproved - enhanced vi editor");
+
+        let pacman = "core/acl 2.3.2-1 [installed]\n    Access control list utilities\nextra/ripgrep 14.1.0-1\n    A search tool\n";
+        let packages = parse_pacman_search(pacman);
+        assert_eq!(packages.len(), 2);
+        assert!(packages[0].installed && !packages[1].installed);
+        assert_eq!((packages[1].name.as_str(), packages[1].description.as_str()), ("ripgrep", "A search tool"));
+
+        let packages = parse_tab_separated("bash\t5.2.26-3.fc40\tThe GNU Bourne Again shell\n\n");
+        assert_eq!(packages, vec![Package::new("bash", "5.2.26-3.fc40", "The GNU Bourne Again shell")]);
+        assert_eq!(parse_brew_list("git 2.44.0\npython@3.12 3.12.1 3.12.2\n")["python@3.12"], "3.12.2");
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let apt = "Package: curl\nPre-Depends: libc6 (>= 2.34)\nDepends: libc6 (>= 2.34), libcurl4 (= 7.88.1-10),\n zlib1g | zlib-ng, perl:any\nDescription: tool\n";
+        assert_eq!(apt_dependencies(apt), ["libc6", "libcurl4", "zlib1g", "zlib-ng", "perl"]);
+
+        let pacman = "Name            : curl\nDepends On      : ca-certificates  krb5  libssh2.so=1-64\nOptional Deps   : None\n";
+        assert_eq!(split_dependencies(&field(pacman, "Depends On").unwrap(), ' '), ["ca-certificates", "krb5", "libssh2.so"]);
+        assert_eq!(split_dependencies(&field(pacman, "Optional Deps").unwrap(), ' '), Vec::<String>::new());
+
+        let brew = "==> jq: stable 1.7.1\n==> Dependencies\nBuild: autoconf ✘, automake ✘\nRequired: oniguruma ✔\n==> Options\n";
+        assert_eq!(brew_dependencies(brew), ["oniguruma"]);
+    }
+
+    #[test]
+    fn test_action_command() {
+        assert_eq!(PackageAction::Remove.command(Manager::Brew, "jq"), "brew uninstall jq");
+        let sudo = if users::is_root() { "" } else { "sudo " };
+        assert_eq!(PackageAction::Install.command(Manager::Pacman, "jq"), format!("{}pacman -S jq", sudo));
+    }
+}
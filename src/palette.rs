@@ -0,0 +1,347 @@
+//! Command palette, opened with `Ctrl-P` in every tool.
+//!
+//! The palette lists two kinds of [`Command`]:
+//!
+//! - the tool's own actions, read from the keys its help popup shows,
+//!   which run by pressing their key in the tool
+//! - jumps to other tools on what the tool is on (its [`Context`]): searching
+//!   its directory, the git log of the selected file, killing the processes
+//!   of the selected name; the other tool runs in place until it quits
+//!
+//! Typing filters the commands fuzzily and `Enter` runs the selected one.
+//! The key or `tt` command line shown next to each one does the same
+//! directly next time.
+
+use crate::fuzzy;
+use crate::text_width;
+use crate::tui_common::{self, colors, SelectableList, TextInput};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, ListState},
+    Frame,
+};
+use std::{
+    cmp::Reverse,
+    env,
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::Command as Process,
+};
+
+/// What running a command does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Run {
+    /// Press these keys in the tool
+    Keys(Vec<KeyEvent>),
+    /// Run `tt` with these arguments
+    Tool(Vec<String>),
+}
+
+/// An entry of the palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    /// What it does, which the filter matches
+    pub label: String,
+    /// The key doing the same, or the `tt` command line
+    pub hint: String,
+    pub run: Run,
+}
+
+/// What a tool is on, for the jumps to other tools
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Context {
+    /// Directory the tool lists or searches
+    pub dir: Option<PathBuf>,
+    /// Selected file
+    pub file: Option<PathBuf>,
+    /// Name of the selected process
+    pub process: Option<String>,
+}
+
+/// The keys of one key name in a keymap, such as `Ctrl-F`, `G`, `gg`,
+/// `Shift-Tab` or `↑`; None for descriptions like `Type` or `1-8`
+fn parse_key(name: &str) -> Option<Vec<KeyEvent>> {
+    let (modifiers, name) = if let Some(rest) = name.strip_prefix("Ctrl-") {
+        (KeyModifiers::CONTROL, rest)
+    } else if let Some(rest) = name.strip_prefix("Alt-") {
+        (KeyModifiers::ALT, rest)
+    } else {
+        (KeyModifiers::NONE, name)
+    };
+    let code = match name {
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Tab" => KeyCode::Tab,
+        "Shift-Tab" => return Some(vec![KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT)]),
+        "Space" => KeyCode::Char(' '),
+        "Backspace" => KeyCode::Backspace,
+        "Delete" | "Del" => KeyCode::Delete,
+        "Insert" => KeyCode::Insert,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "PgUp" => KeyCode::PageUp,
+        "PgDn" => KeyCode::PageDown,
+        "↑" => KeyCode::Up,
+        "↓" => KeyCode::Down,
+        "←" => KeyCode::Left,
+        "→" => KeyCode::Right,
+        _ => {
+            if let Some(number) = name.strip_prefix('F').and_then(|number| number.parse().ok()) {
+                return (1..=12).contains(&number).then(|| vec![KeyEvent::new(KeyCode::F(number), modifiers)]);
+            }
+            let chars: Vec<char> = name.chars().collect();
+            return match chars[..] {
+                [c] if modifiers.is_empty() && c.is_uppercase() => Some(vec![KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT)]),
+                [c] => Some(vec![KeyEvent::new(KeyCode::Char(c.to_ascii_lowercase()), modifiers)]),
+                // Doubled commands such as `gg` and `dd`
+                [first, second] if modifiers.is_empty() && first == second => {
+                    Some(vec![KeyEvent::new(KeyCode::Char(first), KeyModifiers::NONE); 2])
+                }
+                _ => None,
+            };
+        }
+    };
+    Some(vec![KeyEvent::new(code, modifiers)])
+}
+
+/// Whether `keys` would be typed into a text field
+fn types_text(keys: &[KeyEvent]) -> bool {
+    keys.iter().any(|key| matches!(key.code, KeyCode::Char(_)) && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT))
+}
+
+/// The actions of `keymap`, one per key where an entry pairs keys such as
+/// `] / [`. Of the alternatives of an entry (`PgDn, Ctrl-F`) the first that
+/// can be pressed is used; while the tool takes text (`captures_text`),
+/// that leaves out letters, which would be typed.
+pub fn key_commands(keymap: &[(&str, &str)], captures_text: bool) -> Vec<Command> {
+    let mut commands = Vec::new();
+    for (keys, action) in keymap {
+        let chosen = keys
+            .split(", ")
+            .flat_map(|alternative| alternative.split(" or "))
+            .filter_map(|alternative| {
+                alternative
+                    .split(" / ")
+                    .map(|name| Some((name, parse_key(name)?)))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .find(|keys| !captures_text || keys.iter().all(|(_, events)| !types_text(events)));
+        for (name, events) in chosen.into_iter().flatten() {
+            commands.push(Command { label: action.to_string(), hint: name.to_string(), run: Run::Keys(events) });
+        }
+    }
+    commands
+}
+
+/// Jumps to other tools on what `context` says the tool is on; the
+/// directory ones use the working directory when the tool has none
+pub fn jumps(context: &Context) -> Vec<Command> {
+    let dir = context
+        .dir
+        .clone()
+        .or_else(|| context.file.as_deref().and_then(Path::parent).map(Path::to_path_buf))
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let dir = dir.to_string_lossy();
+
+    let mut commands = Vec::new();
+    let mut jump = |label: &str, args: &[&str]| {
+        commands.push(Command {
+            label: label.to_string(),
+            hint: format!("tt {}", args.join(" ")),
+            run: Run::Tool(args.iter().map(|arg| arg.to_string()).collect()),
+        });
+    };
+    jump("Search in this directory", &["search", "--path", &dir]);
+    jump("Find files in this directory", &["find", &dir]);
+    jump("Explore this directory", &["dir", &dir]);
+    jump("TODOs in this directory", &["todo", &dir]);
+    jump("Disk usage of this directory", &["du", &dir]);
+    if let Some(file) = &context.file {
+        let file = file.to_string_lossy();
+        jump("Git log for this file", &["git", "log", &file]);
+        jump("Hex view of this file", &["hex", &file]);
+    }
+    if let Some(process) = &context.process {
+        jump("Kill processes matching this name", &["kill", "--filter", process]);
+        jump("Connections of this process", &["net", "--filter", process]);
+    }
+    commands
+}
+
+/// Run `tt` with `args` on the plain terminal (inside
+/// [`tui_common::suspend`]) until it quits. When it fails, its message stays
+/// up until Enter is pressed.
+pub fn launch(args: &[String]) -> io::Result<()> {
+    let mut command = Process::new(env::current_exe()?);
+    command.args(args);
+    // With stdout captured, as in `$(tt dir)`, the other tool draws on the
+    // terminal like this one rather than printing into the capture
+    #[cfg(unix)]
+    if !io::stdout().is_terminal() {
+        use std::os::fd::AsFd;
+        command.stdout(io::stderr().as_fd().try_clone_to_owned()?);
+    }
+
+    if command.status()?.success() {
+        return Ok(());
+    }
+    eprint!("\n[tt {} failed] Enter to return: ", args.join(" "));
+    io::stderr().flush()?;
+    io::stdin().lock().read_line(&mut String::new())?;
+    Ok(())
+}
+
+/// What the palette wants after a key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Stay open
+    Stay,
+    /// Close without running anything
+    Close,
+    /// Close and run this
+    Run(Run),
+}
+
+pub struct Palette {
+    commands: Vec<Command>,
+    query: TextInput,
+    /// Matching commands as indices into `commands` with the matched char
+    /// positions of their labels, best first
+    matches: Vec<(usize, Vec<usize>)>,
+    list_state: ListState,
+}
+
+impl Palette {
+    pub fn new(commands: Vec<Command>) -> Self {
+        let mut palette = Palette {
+            commands,
+            query: TextInput::default(),
+            matches: Vec::new(),
+            list_state: ListState::default(),
+        };
+        palette.filter();
+        palette
+    }
+
+    fn filter(&mut self) {
+        let query = self.query.value();
+        let mut matches: Vec<(i64, usize, Vec<usize>)> = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(index, command)| {
+                let (score, positions) = fuzzy::match_positions(query, &command.label)?;
+                Some((score, index, positions))
+            })
+            .collect();
+        // Stable, so the tool's own order stays among equal matches
+        matches.sort_by_key(|(score, ..)| Reverse(*score));
+        self.matches = matches.into_iter().map(|(_, index, positions)| (index, positions)).collect();
+        self.list_state.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Step {
+        if tui_common::navigate_list(&mut self.list_state, key.code, key.modifiers, self.matches.len(), 10) {
+            return Step::Stay;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => Step::Close,
+            KeyCode::Char('p') if ctrl => Step::Close,
+            KeyCode::Enter => match self.list_state.selected().and_then(|index| self.matches.get(index)) {
+                Some((index, _)) => Step::Run(self.commands[*index].run.clone()),
+                None => Step::Stay,
+            },
+            _ if ctrl || key.modifiers.contains(KeyModifiers::ALT) => Step::Stay,
+            code => {
+                if self.query.handle_key(code) {
+                    self.filter();
+                }
+                Step::Stay
+            }
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame) {
+        let height = (self.commands.len() as u16).saturating_add(4).min(f.area().height.saturating_mul(2) / 3).max(6);
+        let area = tui_common::centered_rect(f.area().width.saturating_mul(7) / 10, height, f.area());
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Commands ")
+            .title_bottom(Line::from(" Enter Run • Esc Close ").right_aligned())
+            .border_style(Style::default().fg(colors::primary()));
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let mut query = self.query.line();
+        query.spans.insert(0, Span::styled("> ", Style::default().fg(colors::secondary())));
+        f.render_widget(query, Rect { height: 1, ..inner });
+
+        // Less the selection marker and a scrollbar
+        let width = (inner.width as usize).saturating_sub(3);
+        let highlight = Style::default().fg(colors::warning()).add_modifier(Modifier::BOLD);
+        let items: Vec<Line> = self
+            .matches
+            .iter()
+            .map(|(index, positions)| {
+                let command = &self.commands[*index];
+                let hint = text_width::truncate(&command.hint, width / 2);
+                let room = width.saturating_sub(text_width::width(&hint) + 2);
+                let label = text_width::truncate(&command.label, room);
+                let gap = width.saturating_sub(text_width::width(&label) + text_width::width(&hint) + 1);
+                let mut spans = vec![Span::raw(" ")];
+                spans.extend(tui_common::highlight_spans(&label, positions, highlight));
+                spans.push(Span::raw(" ".repeat(gap)));
+                spans.push(Span::styled(hint, Style::default().fg(colors::muted())));
+                Line::from(spans)
+            })
+            .collect();
+        let list_area = Rect { y: inner.y + 1, height: inner.height.saturating_sub(1), ..inner };
+        f.render_stateful_widget(SelectableList::new(items), list_area, &mut self.list_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui_common::Keymap;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("Ctrl-F"), Some(vec![KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL)]));
+        assert_eq!(parse_key("G"), Some(vec![KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT)]));
+        assert_eq!(parse_key("gg").map(|keys| keys.len()), Some(2));
+        assert_eq!(parse_key("PgDn"), Some(vec![KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE)]));
+        assert_eq!(parse_key("F5"), Some(vec![KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE)]));
+        assert_eq!(parse_key("Type"), None);
+        assert_eq!(parse_key("1-8"), None);
+    }
+
+    #[test]
+    fn test_key_commands() {
+        let keymap: Keymap = &[
+            ("] / [", "Next / previous hunk"),
+            ("PgDn, Ctrl-F", "Page down"),
+            ("Type", "Filter"),
+            ("y", "Copy the path"),
+        ];
+        let hints = |commands: Vec<Command>| commands.into_iter().map(|command| command.hint).collect::<Vec<_>>();
+        assert_eq!(hints(key_commands(keymap, false)), vec!["]", "[", "PgDn", "y"]);
+        // Letters would be typed into the filter
+        assert_eq!(hints(key_commands(keymap, true)), vec!["PgDn"]);
+    }
+
+    #[test]
+    fn test_jumps() {
+        let context = Context { file: Some(PathBuf::from("src/main.rs")), ..Context::default() };
+        let commands = jumps(&context);
+        assert_eq!(commands[0].run, Run::Tool(vec!["search".to_string(), "--path".to_string(), "src".to_string()]));
+        assert!(commands.iter().any(|command| command.hint == "tt git log src/main.rs"));
+        assert!(!commands.iter().any(|command| command.hint.starts_with("tt kill")));
+    }
+}
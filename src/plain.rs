@@ -0,0 +1,125 @@
+//! Plain output for scripts and pipelines.
+//!
+//! With `--plain` (or `--no-tui`) or `--json`, tools print their data
+//! instead of starting the TUI: the files `tt find` would list, the matches
+//! of `tt search`, the processes of `tt kill`, the commits of `tt git log`,
+//! and so on. Plain text is also the default when stdout is not a terminal,
+//! except for pickers like `tt dir` and `tt hist` that already print the
+//! chosen item there.
+//!
+//! Each item is a [`Record`] of named fields, printed either as one line of
+//! tab-separated values in field order (so `cut -f` and `awk -F'\t'` work)
+//! or as one JSON object per line (JSON Lines, for `jq`). Tabs and line
+//! breaks inside text values become spaces in the plain format; JSON keeps
+//! them.
+//!
+//! A closed pipe (`tt find | head`) ends the output quietly.
+
+use crate::notify::{Level, Notifications};
+use serde_json::Value;
+use std::io::{self, BufWriter, IsTerminal, Write};
+
+/// How tools print their data instead of running the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Tab-separated values, one record per line
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl Format {
+    /// The format asked for with `--plain` or `--json`; otherwise plain text
+    /// when stdout is redirected and `when_piped` is set, which pickers like
+    /// `tt dir` leave unset since they print their choice to stdout
+    pub fn choose(plain: bool, json: bool, when_piped: bool) -> Option<Format> {
+        if json {
+            Some(Format::Json)
+        } else if plain || (when_piped && !io::stdout().is_terminal()) {
+            Some(Format::Text)
+        } else {
+            None
+        }
+    }
+}
+
+/// One item: field names and values, in output order
+pub type Record = Vec<(&'static str, Value)>;
+
+/// A measurement like CPU percent rounded to one decimal, as the TUI shows it
+pub fn tenths(value: f64) -> Value {
+    Value::from((value * 10.0).round() / 10.0)
+}
+
+/// Fail with the first error a tool raised while loading its data; the TUI
+/// would have shown it as a toast over an empty list
+pub fn check(notifications: &Notifications) -> io::Result<()> {
+    match notifications.history().find(|notification| notification.level == Level::Error) {
+        Some(notification) => Err(io::Error::other(notification.text.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Print `records` to stdout in `format`
+pub fn print(format: Format, records: impl IntoIterator<Item = Record>) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    let result = records
+        .into_iter()
+        .try_for_each(|record| writeln!(out, "{}", format_record(format, &record)))
+        .and_then(|_| out.flush());
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+/// Print `text` to stdout as it is, such as a script for the shell to source
+pub fn print_text(text: &str) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match out.write_all(text.as_bytes()).and_then(|_| out.flush()) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn format_record(format: Format, record: &Record) -> String {
+    match format {
+        Format::Text => record.iter().map(|(_, value)| text_value(value)).collect::<Vec<_>>().join("\t"),
+        Format::Json => {
+            // Written by hand to keep the fields in order
+            let fields: Vec<String> = record
+                .iter()
+                .map(|(name, value)| format!("{}:{}", Value::from(*name), value))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+    }
+}
+
+/// A value as a plain-text field: strings unquoted on one line, null empty
+fn text_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.replace(['\t', '\n', '\r'], " "),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_record() {
+        let record: Record = vec![
+            ("pid", Value::from(42)),
+            ("name", Value::from("a\tb")),
+            ("cpu", tenths(1.2345)),
+            ("user", Value::Null),
+        ];
+        assert_eq!(format_record(Format::Text, &record), "42\ta b\t1.2\t");
+        assert_eq!(format_record(Format::Json, &record), r#"{"pid":42,"name":"a\tb","cpu":1.2,"user":null}"#);
+        assert_eq!(Format::choose(true, true, true), Some(Format::Json));
+        assert_eq!(Format::choose(true, false, false), Some(Format::Text));
+    }
+}
@@ -0,0 +1,223 @@
+//! What differs between Unix and Windows outside of process handling (see
+//! [`crate::process_info`] for that).
+//!
+//! - the home directory: `$HOME`, or `%USERPROFILE%` on Windows, where
+//!   `HOME` is usually unset; the XDG base directories default to places
+//!   below it on both, and tt keeps its files in a `terminal-tools`
+//!   directory in each ([`config_dir`], [`state_dir`], [`cache_dir`])
+//! - the user's shell: `$SHELL`, or PowerShell on Windows
+//! - running a command line through a shell: `sh -c` and friends, `cmd /C`
+//!   or PowerShell's `-Command`
+//! - opening a file with its default application: `start` on Windows, where
+//!   it is the last resort when no editor starts, `open` on macOS and
+//!   `xdg-open` elsewhere
+//! - asking the terminal about itself (background color, graphics support)
+//!   with escape sequences, which needs termios and so only works on Unix
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The user's home directory
+pub fn home_dir() -> Option<PathBuf> {
+    let home = env::var_os("HOME").filter(|home| !home.is_empty());
+    #[cfg(windows)]
+    let home = home.or_else(|| env::var_os("USERPROFILE").filter(|home| !home.is_empty()));
+    home.map(PathBuf::from)
+}
+
+/// Name of tt's directory in each XDG base directory
+const APP_DIR: &str = "terminal-tools";
+
+/// An XDG base directory: `$var` when it holds an absolute path, else
+/// `default` below the home directory
+fn xdg_dir(var: &str, default: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home_dir().map(|home| home.join(default)))
+}
+
+/// `$XDG_CONFIG_HOME`, by default `~/.config`
+pub fn config_home() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_DATA_HOME`, by default `~/.local/share`
+pub fn data_home() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// Directory holding tt's configuration files, `~/.config/terminal-tools`
+pub fn config_dir() -> Option<PathBuf> {
+    config_home().map(|dir| dir.join(APP_DIR))
+}
+
+/// Directory holding tt's persisted state, `~/.local/state/terminal-tools`
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state").map(|dir| dir.join(APP_DIR))
+}
+
+/// Directory holding tt's caches, which may be deleted at any time,
+/// `~/.cache/terminal-tools`
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache").map(|dir| dir.join(APP_DIR))
+}
+
+/// The shell the user works in: `$SHELL`, else PowerShell on Windows and
+/// `sh` elsewhere
+pub fn user_shell() -> String {
+    env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "powershell".to_string() } else { "sh".to_string() })
+}
+
+/// Whether `shell` (a name or a path) is PowerShell
+pub fn is_powershell(shell: &str) -> bool {
+    matches!(program_name(shell).as_str(), "pwsh" | "powershell")
+}
+
+/// Whether `shell` (a name or a path) is the Windows command prompt
+fn is_cmd(shell: &str) -> bool {
+    program_name(shell) == "cmd"
+}
+
+/// File name of `program` without directories or `.exe`, lowercased;
+/// either slash separates directories, so Windows paths work everywhere
+fn program_name(program: &str) -> String {
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program).to_lowercase();
+    name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+}
+
+/// `command` run by `shell`; PowerShell is `pwsh` outside Windows
+pub fn shell_command(shell: &str, command: &str) -> Command {
+    if is_powershell(shell) {
+        let program = if cfg!(windows) && program_name(shell) == "powershell" { shell } else { "pwsh" };
+        let mut process = Command::new(program);
+        process.args(["-NoLogo", "-Command", command]);
+        process
+    } else if is_cmd(shell) {
+        let mut process = Command::new(shell);
+        process.args(["/C", command]);
+        process
+    } else {
+        let mut process = Command::new(shell);
+        process.args(["-c", command]);
+        process
+    }
+}
+
+/// `command` run by the shell for scripts: `sh` on Unix, `cmd` on Windows
+pub fn script_command(command: &str) -> Command {
+    shell_command(if cfg!(windows) { "cmd" } else { "sh" }, command)
+}
+
+/// Opens `path` with the application the system associates with it
+pub fn open_command(path: &Path) -> Command {
+    if cfg!(windows) {
+        // `start` is built into cmd; the empty argument is the window title
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", "/WAIT", ""]).arg(path);
+        command
+    } else {
+        let mut command = Command::new(if cfg!(target_os = "macos") { "open" } else { "xdg-open" });
+        command.arg(path);
+        command
+    }
+}
+
+/// How long to wait for the terminal to answer
+#[cfg(unix)]
+const QUERY_TIMEOUT_MS: i32 = 200;
+
+/// Send `query` to the terminal on `/dev/tty`, followed by a device
+/// attributes query that every terminal answers, and collect the replies up
+/// to the end of that answer. Terminals that ignore `query` thus cost no
+/// timeout. Call it before the TUI starts reading keys.
+#[cfg(unix)]
+pub fn query_terminal(query: &[u8]) -> Option<String> {
+    use std::{
+        fs::OpenOptions,
+        io::{Read, Write},
+        os::unix::io::AsRawFd,
+    };
+
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+    let fd = tty.as_raw_fd();
+    // SAFETY: termios is plain data filled in by tcgetattr, and fd is open
+    let mut original: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+        return None;
+    }
+    let mut raw = original;
+    unsafe {
+        libc::cfmakeraw(&mut raw);
+        libc::tcsetattr(fd, libc::TCSANOW, &raw);
+    }
+
+    let mut response = Vec::new();
+    if tty.write_all(query).and_then(|_| tty.write_all(b"\x1b[c")).and_then(|_| tty.flush()).is_ok() {
+        let mut buf = [0u8; 256];
+        loop {
+            let mut poll_fd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+            if unsafe { libc::poll(&mut poll_fd, 1, QUERY_TIMEOUT_MS) } <= 0 {
+                break;
+            }
+            match tty.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => response.extend_from_slice(&buf[..n]),
+            }
+            // The device attributes reply, `ESC [ ? ... c`, comes last
+            let text = String::from_utf8_lossy(&response);
+            if text.rfind("\x1b[?").is_some_and(|start| text[start..].contains('c')) {
+                break;
+            }
+        }
+    }
+    unsafe {
+        libc::tcsetattr(fd, libc::TCSANOW, &original);
+    }
+    Some(String::from_utf8_lossy(&response).to_string())
+}
+
+#[cfg(not(unix))]
+pub fn query_terminal(_query: &[u8]) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &Command) -> Vec<String> {
+        command.get_args().map(|arg| arg.to_string_lossy().to_string()).collect()
+    }
+
+    #[test]
+    fn test_shell_command() {
+        let command = shell_command("/bin/zsh", "ls");
+        assert_eq!(command.get_program(), "/bin/zsh");
+        assert_eq!(args(&command), vec!["-c", "ls"]);
+        assert_eq!(args(&shell_command("C:\\Windows\\System32\\cmd.exe", "dir")), vec!["/C", "dir"]);
+        assert_eq!(args(&shell_command("pwsh", "Get-Date")), vec!["-NoLogo", "-Command", "Get-Date"]);
+        assert!(is_powershell("PowerShell.exe"));
+        assert!(!is_powershell("/usr/bin/fish"));
+    }
+
+    #[test]
+    fn test_xdg_dir() {
+        // A variable of its own, so other tests reading the real ones are
+        // not disturbed
+        let var = "TT_TEST_XDG_DIR";
+        let dir = env::temp_dir().join("xdg");
+        env::set_var(var, &dir);
+        assert_eq!(xdg_dir(var, ".x"), Some(dir));
+        // Relative paths are ignored, as the spec says
+        env::set_var(var, "relative");
+        assert_eq!(xdg_dir(var, ".x"), home_dir().map(|home| home.join(".x")));
+        env::remove_var(var);
+    }
+}
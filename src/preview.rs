@@ -0,0 +1,1089 @@
+//! File previews shared by find, search, explore and recent.
+//!
+//! A [`PreviewEngine`] holds a list of [`Previewer`]s, each pairing a file
+//! matcher with a renderer that produces styled [`Text`]. The first previewer
+//! whose matcher accepts a path and renders it wins, so every tool shows a
+//! file the same way. Images are the exception: decoding one takes too long
+//! for the UI thread, so they render in the background through
+//! [`ImagePreviews`] and the tool calls [`PreviewEngine::poll`] on each tick.
+//! Previewers that run a command (archives, PDFs, video and audio) are
+//! marked `background` and render in a [`Job`] the same way, with a
+//! placeholder until they finish; selecting another file cancels the job.
+//!
+//! ## Built-in Previewers
+//!
+//! - **Directory**: the first entries, hidden ones only when the tool shows
+//!   them
+//! - **Archive** (zip, tar, 7z, ...): the first entries
+//! - **Markdown** (`.md`, `.markdown`): headings, emphasis, inline code,
+//!   links, lists, quotes and fenced code blocks are styled
+//! - **JSON**: re-indented (minified files become readable) and colored
+//! - **YAML / TOML**: colored line by line, keeping the original layout
+//! - **CSV / TSV**: the first rows as an aligned table
+//! - **PDF**: text of the first pages via `pdftotext` (poppler-utils)
+//! - **Video / audio**: duration, resolution, codecs and bitrate via
+//!   `ffprobe`, and the first video frame as text art via `ffmpeg`
+//! - **Code**: keywords, strings, comments and numbers colored by
+//!   [`crate::syntax`]
+//! - **Text**: any other UTF-8 file
+//! - **Binary**: the size and a hex dump sized to the pane
+//!
+//! ## Limits and Caching
+//!
+//! Only the first [`READ_LIMIT`] bytes of a file are read, however large it
+//! is, and no more lines are kept than the tool's `preview_lines`, so
+//! previews stay fast; [`Preview::label`] says when a preview shows only
+//! the start of a file.
+//! Finished previews are cached by path, modification time, size and pane
+//! width; the last [`CACHE_LIMIT`] are kept, so going back and forth over a
+//! list reads nothing again while an edited file is read afresh.
+//!
+//! [`PreviewEngine::context`] shows the lines around a match instead, for
+//! search results and notes.
+
+use crate::config::Settings;
+use crate::image_cache::ImagePreviews;
+use crate::jobs::{Job, Jobs, Task};
+use crate::syntax::{self, Highlighter};
+use crate::bounded_read::{self, LineIndex};
+use crate::{ansi, archive, hex_dump, image_preview, media, text_width};
+use crate::tui_common::{colors, format_size};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Paragraph, Wrap},
+};
+use std::{
+    cell::Cell,
+    collections::{HashMap, VecDeque},
+    fs,
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+    time::UNIX_EPOCH,
+};
+
+/// Bytes read from a file for previewing
+const READ_LIMIT: u64 = 256 * 1024;
+
+/// Lines of rendered output kept
+const MAX_LINES: usize = 200;
+
+/// Previews kept by the cache
+pub const CACHE_LIMIT: usize = 32;
+
+/// Files whose line indexes are kept for [`PreviewEngine::context`]
+const INDEX_LIMIT: usize = 16;
+
+/// Rows shown in a CSV table, including the header
+const CSV_ROWS: usize = 50;
+
+/// Widest a CSV column is drawn before values are truncated
+const CSV_COLUMN_WIDTH: usize = 30;
+
+/// Pages of a PDF extracted for the preview
+const PDF_PAGES: &str = "3";
+
+/// Cells the first frame of a video is drawn in
+const THUMBNAIL_SIZE: (u32, u32) = (48, 14);
+
+/// A file to preview, with what renderers need to know about the pane
+pub struct Target<'a> {
+    pub path: &'a Path,
+    pub is_dir: bool,
+    /// Cells inside the preview pane
+    pub columns: u16,
+    pub max_lines: usize,
+    /// Whether directory listings include dotfiles
+    pub show_hidden: bool,
+    pub settings: &'a Settings,
+    /// The job a background previewer renders in, for commands to run
+    /// through so they stop when the job is cancelled
+    pub task: Option<&'a Task>,
+    /// Set when the renderer read only the start of a longer file
+    truncated: Cell<bool>,
+}
+
+impl Target<'_> {
+    /// The first [`READ_LIMIT`] bytes of the file as text, or None when it
+    /// is binary
+    pub fn read_head(&self) -> Option<String> {
+        let head = bounded_read::head(self.path, READ_LIMIT).ok()??;
+        self.truncated.set(head.truncated);
+        Some(head.text)
+    }
+}
+
+/// A preview renderer for one kind of file
+#[derive(Clone, Copy)]
+pub struct Previewer {
+    pub name: &'static str,
+    pub matches: fn(&Target) -> bool,
+    /// Rendered preview, or None to leave the file to the next previewer
+    pub render: fn(&Target) -> Option<Text<'static>>,
+    /// Whether long lines should be wrapped rather than cut off
+    pub wrap: bool,
+    /// Whether rendering runs a command, so it is done off the UI thread
+    pub background: bool,
+}
+
+impl Previewer {
+    /// The preview of `target`, or None to leave it to the next previewer
+    fn preview(&self, target: &Target, size: u64) -> Option<Preview> {
+        let mut text = (self.render)(target)?;
+        text.lines.truncate(target.max_lines);
+        let truncated = target.truncated.get().then_some(size);
+        Some(Preview { kind: self.name, text, wrap: self.wrap, truncated })
+    }
+}
+
+/// Rendered preview together with how it should be laid out
+#[derive(Debug, Clone)]
+pub struct Preview {
+    /// Name of the previewer that made it, such as "Rust" or "JSON"
+    pub kind: &'static str,
+    pub text: Text<'static>,
+    pub wrap: bool,
+    /// Size of the file when only its start was read
+    pub truncated: Option<u64>,
+}
+
+impl Preview {
+    fn notice(kind: &'static str, message: String) -> Preview {
+        Preview { kind, text: notice(message), wrap: true, truncated: None }
+    }
+
+    /// The kind of preview for the title, saying when it shows only the
+    /// start of the file
+    pub fn label(&self) -> String {
+        match self.truncated {
+            Some(size) => format!("{}, first {} of {}", self.kind, format_size(READ_LIMIT), format_size(size)),
+            None => self.kind.to_string(),
+        }
+    }
+
+    /// The preview drawn in `block`, wrapped if it should be
+    pub fn paragraph<'a>(&self, block: Block<'a>) -> Paragraph<'a> {
+        let paragraph = Paragraph::new(self.text.clone()).block(block);
+        if self.wrap {
+            paragraph.wrap(Wrap { trim: false })
+        } else {
+            paragraph
+        }
+    }
+}
+
+/// A cached preview: the file as it is now and the pane width it fills
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    path: PathBuf,
+    /// Modification time in nanoseconds since the Unix epoch
+    modified: u128,
+    size: u64,
+    columns: u16,
+}
+
+impl CacheKey {
+    fn of(path: &Path, metadata: &fs::Metadata, columns: u16) -> Option<CacheKey> {
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+        Some(CacheKey { path: path.to_path_buf(), modified, size: metadata.len(), columns })
+    }
+}
+
+/// The previewers of one tool, with its image renderer and cache
+pub struct PreviewEngine {
+    previewers: Vec<Previewer>,
+    max_lines: usize,
+    show_hidden: bool,
+    /// Whether the tool draws images itself with a graphics protocol,
+    /// leaving their details to the text preview
+    graphics: bool,
+    images: ImagePreviews,
+    jobs: Jobs,
+    /// The file a background previewer is rendering
+    pending: Option<(CacheKey, Job<Preview>)>,
+    cache: HashMap<CacheKey, Preview>,
+    /// Keys in `cache`, oldest first
+    order: VecDeque<CacheKey>,
+    /// Line indexes of the files context was shown from, the latest last
+    line_indexes: VecDeque<(PathBuf, LineIndex)>,
+    settings: Settings,
+}
+
+impl PreviewEngine {
+    /// The built-in previewers, keeping as many lines as `settings` asks
+    pub fn new(settings: &Settings) -> Self {
+        let mut engine = PreviewEngine {
+            previewers: Vec::new(),
+            max_lines: settings.preview_lines.min(MAX_LINES),
+            show_hidden: false,
+            graphics: false,
+            images: ImagePreviews::new(),
+            jobs: Jobs::default(),
+            pending: None,
+            cache: HashMap::new(),
+            order: VecDeque::new(),
+            line_indexes: VecDeque::new(),
+            settings: settings.clone(),
+        };
+        engine.register(Previewer { name: "Directory", matches: |target| target.is_dir, render: render_directory, wrap: false, background: false });
+        engine.register(Previewer { name: "Archive", matches: |target| archive::archive_kind(target.path).is_some(), render: render_archive, wrap: false, background: true });
+        engine.register(Previewer { name: "Markdown", matches: |target| is_markdown(target.path), render: render_markdown, wrap: true, background: false });
+        engine.register(Previewer { name: "JSON", matches: |target| is_json(target.path), render: render_json, wrap: false, background: false });
+        engine.register(Previewer { name: "YAML", matches: |target| is_yaml(target.path), render: render_yaml, wrap: false, background: false });
+        engine.register(Previewer { name: "TOML", matches: |target| is_toml(target.path), render: render_toml, wrap: false, background: false });
+        engine.register(Previewer { name: "CSV", matches: |target| is_csv(target.path), render: render_csv, wrap: false, background: false });
+        engine.register(Previewer { name: "PDF", matches: |target| is_pdf(target.path), render: render_pdf, wrap: true, background: true });
+        engine.register(Previewer { name: "Media", matches: |target| media::is_media_file(target.path), render: render_media, wrap: false, background: true });
+        engine.register(Previewer { name: "Code", matches: |target| syntax::language(target.path).is_some(), render: render_code, wrap: true, background: false });
+        engine.register(Previewer { name: "Text", matches: |_| true, render: render_text, wrap: true, background: false });
+        engine.register(Previewer { name: "Binary", matches: |_| true, render: render_binary, wrap: false, background: false });
+        engine
+    }
+
+    /// Add a previewer; earlier registrations take precedence
+    pub fn register(&mut self, previewer: Previewer) {
+        self.previewers.push(previewer);
+    }
+
+    /// Whether directory listings include dotfiles
+    pub fn set_show_hidden(&mut self, show_hidden: bool) {
+        self.show_hidden = show_hidden;
+    }
+
+    /// Leave images to the tool's graphics protocol, previewing only their
+    /// details
+    pub fn set_graphics(&mut self, graphics: bool) {
+        self.graphics = graphics;
+    }
+
+    /// Whether an image or background preview finished rendering since the
+    /// last call, so the tool should preview the selection again
+    pub fn poll(&mut self) -> bool {
+        let images = self.images.poll();
+        let Some(result) = self.pending.as_mut().and_then(|(_, job)| job.poll()) else {
+            return images;
+        };
+        if let Some((key, _)) = self.pending.take() {
+            let preview = result.unwrap_or_else(|e| Preview::notice("Error", format!("Preview failed: {}", e)));
+            self.remember(key, preview);
+        }
+        true
+    }
+
+    /// The preview of `path` in a pane of `columns` x `rows` cells
+    pub fn preview(&mut self, path: &Path, columns: u16, rows: u16) -> Preview {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) => return Preview::notice("Error", format!("Cannot read {}: {}", path.display(), e)),
+        };
+        if !metadata.is_dir() && image_preview::is_image_file(path) {
+            // Capable terminals get a real thumbnail drawn below the details
+            let text = match self.graphics {
+                true => image_preview::image_details(path),
+                false => self.images.preview(path, columns, rows),
+            };
+            return Preview { kind: "Image", text: Text::from(ansi::to_lines(&text)), wrap: true, truncated: None };
+        }
+
+        // Directory listings depend on more than the directory's own times
+        let key = if metadata.is_dir() { None } else { CacheKey::of(path, &metadata, columns) };
+        if let Some(preview) = key.as_ref().and_then(|key| self.cache.get(key)) {
+            return preview.clone();
+        }
+        // The file being rendered in the background is no longer wanted
+        if self.pending.as_ref().is_some_and(|(pending, _)| Some(pending) != key.as_ref()) {
+            self.pending = None;
+        }
+        let target = Target {
+            path,
+            is_dir: metadata.is_dir(),
+            columns,
+            max_lines: self.max_lines,
+            show_hidden: self.show_hidden,
+            settings: &self.settings,
+            task: None,
+            truncated: Cell::new(false),
+        };
+        let found = self.previewers.iter().filter(|previewer| (previewer.matches)(&target)).find_map(|previewer| {
+            match previewer.background {
+                true => Some(Err(*previewer)),
+                false => previewer.preview(&target, metadata.len()).map(Ok),
+            }
+        });
+        let preview = match found {
+            Some(Ok(preview)) => preview,
+            // Only a job for this very file is still pending
+            Some(Err(previewer)) if self.pending.is_some() => return rendering(previewer),
+            Some(Err(previewer)) => {
+                let job = self.spawn_preview(previewer, path, columns, metadata.len());
+                let Some(key) = key else {
+                    return job.wait().unwrap_or_else(|e| Preview::notice("Error", format!("Preview failed: {}", e)));
+                };
+                self.pending = Some((key, job));
+                return rendering(previewer);
+            }
+            None => Preview::notice("Unknown", "[No preview]".to_string()),
+        };
+        if let Some(key) = key {
+            self.remember(key, preview.clone());
+        }
+        preview
+    }
+
+    /// Render `path` with a background previewer in a job
+    fn spawn_preview(&self, previewer: Previewer, path: &Path, columns: u16, size: u64) -> Job<Preview> {
+        let label = format!("Preview {}", path.display());
+        let (path, settings) = (path.to_path_buf(), self.settings.clone());
+        let (max_lines, show_hidden) = (self.max_lines, self.show_hidden);
+        self.jobs.spawn(label, move |task| {
+            let target = Target {
+                path: &path,
+                is_dir: false,
+                columns,
+                max_lines,
+                show_hidden,
+                settings: &settings,
+                task: Some(task),
+                truncated: Cell::new(false),
+            };
+            Ok(previewer
+                .preview(&target, size)
+                .unwrap_or_else(|| Preview::notice("Unknown", "[No preview]".to_string())))
+        })
+    }
+
+    /// Keep `preview` in the cache, dropping the oldest when it is full
+    fn remember(&mut self, key: CacheKey, preview: Preview) {
+        if self.order.len() == CACHE_LIMIT {
+            if let Some(oldest) = self.order.pop_front() {
+                self.cache.remove(&oldest);
+            }
+        }
+        self.order.push_back(key.clone());
+        self.cache.insert(key, preview);
+    }
+
+    /// The lines of `path` around `line` (counted from 1), `context` on
+    /// each side, numbered and with `line` marked in `mark`; code is
+    /// highlighted
+    pub fn context(&mut self, path: &Path, line: usize, context: usize, mark: Style) -> Preview {
+        let index = line.saturating_sub(1);
+        let start = index.saturating_sub(context);
+        let window = match self.line_index(path).and_then(|lines| lines.lines(path, start, index + context + 1 - start)) {
+            Ok(window) => window,
+            Err(_) => return Preview::notice("Error", format!("Could not read file: {}", path.display())),
+        };
+        let mut highlighter = syntax::language(path).map(Highlighter::new);
+
+        let lines = window
+            .iter()
+            .zip(start..)
+            .map(|(text, number)| {
+                let text = text_width::expand_tabs(text);
+                let mut code = match highlighter.as_mut() {
+                    Some(highlighter) => highlighter.line(&text),
+                    None => Line::styled(text, Style::default().fg(colors::text())),
+                };
+                let gutter = if number == index {
+                    code = code.patch_style(Modifier::BOLD);
+                    Span::styled(format!("▶{:>5} ", number + 1), mark)
+                } else {
+                    Span::styled(format!(" {:>5} ", number + 1), Style::default().fg(colors::muted()))
+                };
+                code.spans.insert(0, gutter);
+                code
+            })
+            .collect::<Vec<_>>();
+        let kind = if highlighter.is_some() { "Code" } else { "Text" };
+        Preview { kind, text: Text::from(lines), wrap: true, truncated: None }
+    }
+
+    /// The line index of `path`, kept for the next match in the same file
+    /// unless the file changed
+    fn line_index(&mut self, path: &Path) -> io::Result<&mut LineIndex> {
+        let metadata = fs::metadata(path)?;
+        let kept = self.line_indexes.iter().position(|(indexed, _)| indexed == path);
+        let lines = match kept.and_then(|at| self.line_indexes.remove(at)) {
+            Some((_, lines)) if lines.is_current(&metadata) => lines,
+            _ => LineIndex::new(&metadata),
+        };
+        if self.line_indexes.len() == INDEX_LIMIT {
+            self.line_indexes.pop_front();
+        }
+        self.line_indexes.push_back((path.to_path_buf(), lines));
+        let latest = self.line_indexes.len() - 1;
+        Ok(&mut self.line_indexes[latest].1)
+    }
+}
+
+/// Placeholder while a background previewer renders
+fn rendering(previewer: Previewer) -> Preview {
+    Preview::notice(previewer.name, format!("⏳ Rendering {} preview…", previewer.name))
+}
+
+fn render_directory(target: &Target) -> Option<Text<'static>> {
+    let Ok(entries) = fs::read_dir(target.path) else {
+        return Some(notice("[Permission denied]".to_string()));
+    };
+    let lines: Vec<Line<'static>> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            (target.show_hidden || !name.starts_with('.')) && !target.settings.is_ignored(&name)
+        })
+        .take(target.max_lines)
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let icon = if entry.path().is_dir() { "📁" } else { "📄" };
+            Line::from(format!("{} {}", icon, name))
+        })
+        .collect();
+    if lines.is_empty() {
+        return Some(notice("[Empty directory]".to_string()));
+    }
+    Some(Text::from(lines))
+}
+
+fn render_archive(target: &Target) -> Option<Text<'static>> {
+    let entries = match archive::list(target.task?, target.path) {
+        Ok(entries) => entries,
+        Err(e) => return Some(notice(format!("📦 Archive\n\n[Cannot list contents: {}]", e))),
+    };
+    let shown = target.max_lines.saturating_sub(3);
+    let mut lines = vec![
+        Line::styled(format!("📦 Archive: {} entries", entries.len()), Style::default().add_modifier(Modifier::BOLD)),
+        Line::default(),
+    ];
+    lines.extend(entries.iter().take(shown).map(|entry| Line::from(entry.clone())));
+    if entries.len() > shown {
+        lines.push(Line::styled(format!("... and {} more", entries.len() - shown), Style::default().fg(colors::muted())));
+    }
+    Some(Text::from(lines))
+}
+
+fn render_code(target: &Target) -> Option<Text<'static>> {
+    let mut highlighter = Highlighter::new(syntax::language(target.path)?);
+    let text = target.read_head()?;
+    Some(Text::from(text.lines().take(target.max_lines).map(|line| highlighter.line(&text_width::expand_tabs(line))).collect::<Vec<_>>()))
+}
+
+fn render_text(target: &Target) -> Option<Text<'static>> {
+    let text = target.read_head()?;
+    if text.is_empty() {
+        return Some(notice("[Empty file]".to_string()));
+    }
+    Some(Text::from(text.lines().take(target.max_lines).map(|line| Line::from(text_width::expand_tabs(line))).collect::<Vec<_>>()))
+}
+
+/// A hex dump sized to the pane
+fn render_binary(target: &Target) -> Option<Text<'static>> {
+    let width = hex_dump::fit_width(target.columns as usize);
+    Some(match hex_dump::preview(target.path, width, target.max_lines) {
+        Ok(dump) => Text::from(dump.lines().map(|line| Line::from(line.to_string())).collect::<Vec<_>>()),
+        Err(e) => notice(format!("File: {}\n\n[Cannot read file: {}]", target.path.display(), e)),
+    })
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| extensions.contains(&ext.as_str()))
+}
+
+fn is_markdown(path: &Path) -> bool {
+    has_extension(path, &["md", "markdown"])
+}
+
+fn is_json(path: &Path) -> bool {
+    has_extension(path, &["json", "geojson"])
+}
+
+fn is_yaml(path: &Path) -> bool {
+    has_extension(path, &["yaml", "yml"])
+}
+
+fn is_toml(path: &Path) -> bool {
+    has_extension(path, &["toml"]) || path.file_name().is_some_and(|name| name == "Cargo.lock")
+}
+
+fn is_csv(path: &Path) -> bool {
+    has_extension(path, &["csv", "tsv"])
+}
+
+fn is_pdf(path: &Path) -> bool {
+    has_extension(path, &["pdf"])
+}
+
+/// Read up to [`READ_LIMIT`] bytes of a text file
+fn render_markdown(target: &Target) -> Option<Text<'static>> {
+    Some(markdown_text(&target.read_head()?))
+}
+
+fn markdown_text(source: &str) -> Text<'static> {
+    let code_style = Style::default().fg(colors::success());
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    // Highlights the open code block, when its language is known
+    let mut highlighter = None;
+
+    for raw in source.lines() {
+        let trimmed = raw.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            let language = trimmed.trim_start_matches(['`', '~']).trim();
+            highlighter = syntax::language_named(language).filter(|_| in_code).map(Highlighter::new);
+            if in_code && !language.is_empty() {
+                lines.push(Line::from(Span::styled(format!("  [{}]", language), Style::default().fg(colors::muted()))));
+            }
+            continue;
+        }
+        if in_code {
+            let mut line = match highlighter.as_mut() {
+                Some(highlighter) => highlighter.line(&text_width::expand_tabs(raw)),
+                None => Line::from(Span::styled(raw.to_string(), code_style)),
+            };
+            line.spans.insert(0, Span::raw("  "));
+            lines.push(line);
+            continue;
+        }
+
+        let heading = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&heading) && trimmed[heading..].starts_with(' ') {
+            let mut style = Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD);
+            if heading == 1 {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            lines.push(Line::from(inline_spans(trimmed[heading..].trim(), style)));
+        } else if matches!(trimmed, "---" | "***" | "___") {
+            lines.push(Line::from(Span::styled("─".repeat(40), Style::default().fg(colors::muted()))));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            let style = Style::default().fg(colors::muted()).add_modifier(Modifier::ITALIC);
+            let mut spans = vec![Span::styled("│ ", Style::default().fg(colors::muted()))];
+            spans.extend(inline_spans(quote.trim_start(), style));
+            lines.push(Line::from(spans));
+        } else if let Some((marker, item)) = list_item(trimmed) {
+            let indent = " ".repeat(raw.len() - trimmed.len());
+            let mut spans = vec![Span::styled(format!("{}{} ", indent, marker), Style::default().fg(colors::secondary()))];
+            spans.extend(inline_spans(item, Style::default()));
+            lines.push(Line::from(spans));
+        } else {
+            lines.push(Line::from(inline_spans(raw, Style::default())));
+        }
+    }
+    Text::from(lines)
+}
+
+/// Split a list item into its display marker and text
+fn list_item(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return Some(match item.strip_prefix("[ ] ").or_else(|| item.strip_prefix("[x] ")) {
+                Some(task) if item.starts_with("[x]") => ("☑".to_string(), task),
+                Some(task) => ("☐".to_string(), task),
+                None => ("•".to_string(), item),
+            });
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = &line[digits..];
+    if digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        return Some((line[..digits + 1].to_string(), &rest[2..]));
+    }
+    None
+}
+
+/// Style inline `**bold**`, `*emphasis*`, `` `code` `` and `[links](url)`
+fn inline_spans(text: &str, base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        // `_` only emphasizes at a word start, so snake_case stays plain
+        let word_start = !plain.ends_with(|p: char| p.is_alphanumeric());
+        let styled = match c {
+            '_' if !word_start => None,
+            '`' => rest[1..].find('`').map(|end| {
+                (Span::styled(rest[1..end + 1].to_string(), Style::default().fg(colors::success())), end + 2)
+            }),
+            '*' | '_' if rest[1..].starts_with(c) => {
+                let marker = &rest[..2];
+                rest[2..].find(marker).filter(|&end| end > 0).map(|end| {
+                    (Span::styled(rest[2..end + 2].to_string(), base.add_modifier(Modifier::BOLD)), end + 4)
+                })
+            }
+            '*' | '_' => rest[1..]
+                .find(c)
+                .filter(|&end| end > 0 && !rest[1..].starts_with(' '))
+                .map(|end| (Span::styled(rest[1..end + 1].to_string(), base.add_modifier(Modifier::ITALIC)), end + 2)),
+            '[' => rest.find("](").and_then(|close| {
+                let end = close + 2 + rest[close + 2..].find(')')?;
+                let label = Span::styled(
+                    rest[1..close].to_string(),
+                    base.fg(colors::primary()).add_modifier(Modifier::UNDERLINED),
+                );
+                Some((label, end + 1))
+            }),
+            _ => None,
+        };
+
+        match styled {
+            Some((span, consumed)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut plain), base));
+                }
+                spans.push(span);
+                rest = &rest[consumed..];
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(Span::styled(plain, base));
+    }
+    spans
+}
+
+fn render_json(target: &Target) -> Option<Text<'static>> {
+    Some(json_text(&target.read_head()?))
+}
+
+/// Re-indent JSON token by token and color it.
+///
+/// This is a tokenizer rather than a parser, so truncated or slightly
+/// invalid input still produces a readable preview.
+fn json_text(source: &str) -> Text<'static> {
+    let punctuation = Style::default().fg(colors::muted());
+    let mut lines: Vec<Line<'static>> = Vec::new();
+    let mut current: Vec<Span<'static>> = Vec::new();
+    let mut depth = 0usize;
+    let mut chars = source.chars().peekable();
+
+    fn new_line(lines: &mut Vec<Line<'static>>, current: &mut Vec<Span<'static>>, depth: usize) {
+        lines.push(Line::from(std::mem::take(current)));
+        current.push(Span::raw("  ".repeat(depth)));
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '[' => {
+                // Keep empty containers on one line
+                let close = if c == '{' { '}' } else { ']' };
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                if chars.peek() == Some(&close) {
+                    chars.next();
+                    current.push(Span::styled(format!("{}{}", c, close), punctuation));
+                } else {
+                    depth += 1;
+                    current.push(Span::styled(c.to_string(), punctuation));
+                    new_line(&mut lines, &mut current, depth);
+                }
+            }
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                new_line(&mut lines, &mut current, depth);
+                current.push(Span::styled(c.to_string(), punctuation));
+            }
+            ',' => {
+                current.push(Span::styled(",", punctuation));
+                new_line(&mut lines, &mut current, depth);
+            }
+            ':' => current.push(Span::styled(": ", punctuation)),
+            '"' => {
+                let mut string = String::from('"');
+                while let Some(c) = chars.next() {
+                    string.push(c);
+                    match c {
+                        '\\' => string.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+                // A string followed by `:` is an object key
+                let mut lookahead = chars.clone();
+                let is_key = lookahead.find(|c| !c.is_whitespace()) == Some(':');
+                let style = if is_key {
+                    Style::default().fg(colors::primary())
+                } else {
+                    Style::default().fg(colors::success())
+                };
+                current.push(Span::styled(string, style));
+            }
+            c if c.is_whitespace() => {}
+            _ => {
+                let mut literal = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, ',' | ':' | '{' | '}' | '[' | ']' | '"') {
+                        break;
+                    }
+                    literal.push(next);
+                    chars.next();
+                }
+                current.push(Span::styled(literal.clone(), scalar_style(&literal)));
+            }
+        }
+        if lines.len() > MAX_LINES {
+            break;
+        }
+    }
+    if !current.is_empty() {
+        lines.push(Line::from(current));
+    }
+    Text::from(lines)
+}
+
+/// Color for an unquoted scalar: numbers, booleans and null
+fn scalar_style(value: &str) -> Style {
+    match value {
+        "true" | "false" | "null" | "~" | "yes" | "no" => Style::default().fg(colors::secondary()),
+        _ if value.parse::<f64>().is_ok() => Style::default().fg(colors::warning()),
+        _ => Style::default(),
+    }
+}
+
+fn render_yaml(target: &Target) -> Option<Text<'static>> {
+    Some(Text::from(target.read_head()?.lines().take(MAX_LINES).map(yaml_line).collect::<Vec<_>>()))
+}
+
+fn render_toml(target: &Target) -> Option<Text<'static>> {
+    Some(Text::from(target.read_head()?.lines().take(MAX_LINES).map(toml_line).collect::<Vec<_>>()))
+}
+
+fn comment_style() -> Style {
+    Style::default().fg(colors::muted()).add_modifier(Modifier::ITALIC)
+}
+
+/// Style a value, with any trailing `# comment`
+fn value_spans(value: &str) -> Vec<Span<'static>> {
+    let (value, comment) = split_comment(value);
+    let trimmed = value.trim();
+    let style = if trimmed.starts_with(['"', '\'']) {
+        Style::default().fg(colors::success())
+    } else {
+        scalar_style(trimmed)
+    };
+    let mut spans = vec![Span::styled(value.to_string(), style)];
+    if let Some(comment) = comment {
+        spans.push(Span::styled(comment.to_string(), comment_style()));
+    }
+    spans
+}
+
+/// Split off a `#` comment that is not inside a quoted string
+fn split_comment(text: &str) -> (&str, Option<&str>) {
+    let mut quote = None;
+    for (index, c) in text.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            ('#', None) if index == 0 || text[..index].ends_with(char::is_whitespace) => {
+                return (&text[..index], Some(&text[index..]));
+            }
+            _ => {}
+        }
+    }
+    (text, None)
+}
+
+fn yaml_line(line: &str) -> Line<'static> {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    if trimmed.starts_with('#') {
+        return Line::from(Span::styled(line.to_string(), comment_style()));
+    }
+    if trimmed == "---" || trimmed == "..." {
+        return Line::from(Span::styled(line.to_string(), Style::default().fg(colors::muted())));
+    }
+
+    let mut spans = vec![Span::raw(indent.to_string())];
+    let mut rest = trimmed;
+    if let Some(item) = rest.strip_prefix("- ").or_else(|| (rest == "-").then_some("")) {
+        spans.push(Span::styled("- ", Style::default().fg(colors::secondary())));
+        rest = item;
+    }
+    match yaml_key(rest) {
+        Some(colon) => {
+            spans.push(Span::styled(rest[..colon].to_string(), Style::default().fg(colors::primary())));
+            spans.push(Span::styled(":", Style::default().fg(colors::muted())));
+            spans.extend(value_spans(&rest[colon + 1..]));
+        }
+        None => spans.extend(value_spans(rest)),
+    }
+    Line::from(spans)
+}
+
+/// Position of the `:` ending a YAML mapping key, if the line has one
+fn yaml_key(text: &str) -> Option<usize> {
+    if text.starts_with(['"', '\'', '#']) {
+        return None;
+    }
+    text.char_indices()
+        .find(|&(index, c)| c == ':' && text[index + 1..].chars().next().is_none_or(char::is_whitespace))
+        .map(|(index, _)| index)
+}
+
+fn toml_line(line: &str) -> Line<'static> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') {
+        return Line::from(Span::styled(line.to_string(), comment_style()));
+    }
+    if trimmed.starts_with('[') {
+        let (header, comment) = split_comment(line);
+        let mut spans = vec![Span::styled(
+            header.to_string(),
+            Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD),
+        )];
+        if let Some(comment) = comment {
+            spans.push(Span::styled(comment.to_string(), comment_style()));
+        }
+        return Line::from(spans);
+    }
+    match line.split_once('=') {
+        Some((key, value)) if !key.trim_start().starts_with(['"', '\'']) || key.trim_end().ends_with(['"', '\'']) => {
+            let mut spans = vec![
+                Span::styled(key.to_string(), Style::default().fg(colors::primary())),
+                Span::styled("=", Style::default().fg(colors::muted())),
+            ];
+            spans.extend(value_spans(value));
+            Line::from(spans)
+        }
+        _ => Line::from(value_spans(line)),
+    }
+}
+
+fn render_csv(target: &Target) -> Option<Text<'static>> {
+    let delimiter = if has_extension(target.path, &["tsv"]) { '\t' } else { ',' };
+    let rows = parse_csv(&target.read_head()?, delimiter, CSV_ROWS);
+    Some(csv_table(&rows))
+}
+
+/// Parse delimited rows, honouring double-quoted fields with `""` escapes
+fn parse_csv(source: &str, delimiter: char, limit: usize) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            c if c == delimiter && !quoted => row.push(std::mem::take(&mut field)),
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+                if rows.len() == limit {
+                    return rows;
+                }
+            }
+            '\r' if !quoted => {}
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Lay rows out as a table with a highlighted header row
+fn csv_table(rows: &[Vec<String>]) -> Text<'static> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| text_width::width(cell))
+                .max()
+                .unwrap_or(0)
+                .min(CSV_COLUMN_WIDTH)
+        })
+        .collect();
+
+    let separator = Style::default().fg(colors::muted());
+    let mut lines = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        let style = if index == 0 {
+            Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let mut spans = Vec::new();
+        for (column, width) in widths.iter().enumerate() {
+            if column > 0 {
+                spans.push(Span::styled(" │ ", separator));
+            }
+            let cell = row.get(column).map(String::as_str).unwrap_or("");
+            spans.push(Span::styled(fit(cell, *width), style));
+        }
+        lines.push(Line::from(spans));
+        if index == 0 {
+            let rule: Vec<String> = widths.iter().map(|width| "─".repeat(*width)).collect();
+            lines.push(Line::from(Span::styled(rule.join("─┼─"), separator)));
+        }
+    }
+    Text::from(lines)
+}
+
+/// Pad or truncate `text` to exactly `width` columns
+fn fit(text: &str, width: usize) -> String {
+    text_width::pad(&text.replace(['\n', '\t'], " "), width)
+}
+
+fn render_pdf(target: &Target) -> Option<Text<'static>> {
+    let output = target.task?.output(Command::new("pdftotext")
+        .args(["-l", PDF_PAGES, "-layout"])
+        .arg(target.path)
+        .arg("-"));
+    let text = match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Ok(output) => {
+            let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Some(notice(format!("pdftotext failed: {}", message)));
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Some(notice("Install `pdftotext` (poppler-utils) to preview PDFs".to_string()));
+        }
+        Err(e) => return Some(notice(format!("pdftotext failed: {}", e))),
+    };
+
+    let lines: Vec<Line<'static>> = text
+        .lines()
+        // Form feeds separate pages
+        .map(|line| match line.strip_prefix('\u{c}') {
+            Some(rest) => Line::from(vec![
+                Span::styled("── next page ──", Style::default().fg(colors::muted())),
+                Span::raw(format!(" {}", rest)),
+            ]),
+            None => Line::from(line.to_string()),
+        })
+        .collect();
+    Some(Text::from(lines))
+}
+
+fn render_media(target: &Target) -> Option<Text<'static>> {
+    let (task, path) = (target.task?, target.path);
+    let info = match media::probe(task, path) {
+        Ok(info) => info,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Some(notice("Install `ffprobe` (FFmpeg) to preview video and audio files".to_string()));
+        }
+        Err(e) => return Some(notice(format!("ffprobe failed: {}", e))),
+    };
+
+    let label = Style::default().fg(colors::muted());
+    let icon = if info.has_video() { "🎬" } else { "🎵" };
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let mut lines = vec![Line::from(Span::styled(format!("{} {}", icon, name), Style::default().add_modifier(Modifier::BOLD)))];
+    for (name, value) in info.fields() {
+        lines.push(Line::from(vec![Span::styled(format!("{:<11}", name), label), Span::raw(value)]));
+    }
+
+    // A thumbnail is a bonus: without ffmpeg or a decodable frame, skip it
+    let thumbnail = info
+        .has_video()
+        .then(|| media::first_frame(task, path))
+        .flatten()
+        .and_then(|png| image::load_from_memory(&png).ok())
+        .and_then(|frame| image_preview::render_image_to_terminal(&frame, THUMBNAIL_SIZE.0, THUMBNAIL_SIZE.1).ok());
+    if let Some(thumbnail) = thumbnail {
+        lines.push(Line::default());
+        lines.extend(ansi::to_lines(&thumbnail));
+    }
+    Some(Text::from(lines))
+}
+
+fn notice(message: String) -> Text<'static> {
+    Text::from(Line::from(Span::styled(message, Style::default().fg(colors::muted()))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: &Text) -> Vec<String> {
+        text.lines
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_markdown_inline_and_blocks() {
+        let text = markdown_text("# Title\n- item with **bold** and `code`\n1. [docs](http://x)\n```rust\nlet x;\n```");
+        assert_eq!(
+            plain(&text),
+            vec!["Title", "• item with bold and code", "1. docs", "  [rust]", "  let x;"]
+        );
+        let spans = inline_spans("a **b** *c*", Style::default());
+        assert!(spans[1].style.add_modifier.contains(Modifier::BOLD));
+        assert!(spans[3].style.add_modifier.contains(Modifier::ITALIC));
+    }
+
+    #[test]
+    fn test_json_reindents() {
+        let text = json_text(r#"{"a":[1,true],"b":{},"c":"x,y"}"#);
+        assert_eq!(
+            plain(&text),
+            vec!["{", "  \"a\": [", "    1,", "    true", "  ],", "  \"b\": {},", "  \"c\": \"x,y\"", "}"]
+        );
+    }
+
+    #[test]
+    fn test_csv_table() {
+        let rows = parse_csv("name,note\nann,\"says \"\"hi\"\", ok\"\nbo\n", ',', 10);
+        assert_eq!(rows[1], vec!["ann", "says \"hi\", ok"]);
+        assert_eq!(rows[2], vec!["bo"]);
+        let text = csv_table(&rows);
+        assert_eq!(plain(&text)[0], "name │ note         ");
+        assert_eq!(plain(&text)[3], "bo   │              ");
+    }
+
+    #[test]
+    fn test_yaml_and_toml_lines() {
+        let yaml = yaml_line("  - name: tt # comment");
+        let yaml_parts: Vec<&str> = yaml.spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(yaml_parts, vec!["  ", "- ", "name", ":", " tt ", "# comment"]);
+
+        let toml = toml_line("url = \"http://x#y\"");
+        let toml_parts: Vec<&str> = toml.spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(toml_parts, vec!["url ", "=", " \"http://x#y\""]);
+    }
+
+    #[test]
+    fn test_engine_picks_previewer_and_caches() {
+        let dir = std::env::temp_dir().join(format!("tt-preview-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("main.rs"), "fn main() {}\n\tok();\n").unwrap();
+        fs::write(dir.join("notes.txt"), "hello\n").unwrap();
+        fs::write(dir.join("data.bin"), [0u8, 159, 146, 150]).unwrap();
+
+        let mut engine = PreviewEngine::new(&Settings::default());
+        let kinds: Vec<&str> = ["main.rs", "notes.txt", "data.bin"]
+            .iter()
+            .map(|name| engine.preview(&dir.join(name), 80, 20).kind)
+            .collect();
+        assert_eq!(kinds, vec!["Code", "Text", "Binary"]);
+        assert_eq!(engine.preview(&dir, 80, 20).kind, "Directory");
+        assert_eq!(engine.cache.len(), 3);
+
+        fs::write(dir.join("big.log"), "line\n".repeat(READ_LIMIT as usize / 5 * 2)).unwrap();
+        assert_eq!(engine.preview(&dir.join("big.log"), 80, 20).label(), "Text, first 256.0KB of 512.0KB");
+
+        let context = engine.context(&dir.join("main.rs"), 2, 1, Style::default());
+        assert_eq!(plain(&context.text), vec!["     1 fn main() {}", "▶    2     ok();"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1433 @@
+//! Cross-platform process information provider.
+//!
+//! This module replaces ad-hoc parsing of `ps aux` output with a structured
+//! process snapshot. On Linux the data is read directly from `/proc`, which is
+//! faster than spawning `ps` and exposes fields `ps aux` drops. On other Unix
+//! systems (macOS, BSD) it falls back to `ps -o` with an explicit, portable
+//! column list so the parser does not depend on GNU-specific flags.
+//!
+//! Windows has neither, so processes come from `tasklist /v /fo csv` and
+//! listening sockets from `netstat -ano`. Those leave out the parent PID,
+//! arguments, threads and start time, and the CPU figures stay at zero.
+//! Signals don't exist there either: [`send_signal`] maps `TERM` and `KILL`
+//! to `taskkill` and refuses the rest, and [`renice`] sets the nearest
+//! priority class instead of a nice value.
+//!
+//! ## Provided Fields
+//!
+//! - PID, parent PID, owning user and scheduler state
+//! - Thread count and start time (seconds since the Unix epoch)
+//! - Resident set size in bytes and percentage of total memory
+//! - CPU usage (lifetime average, refined to per-interval by [`CpuSampler`])
+//! - Full command line with arguments
+//! - System memory, swap, load average and CPU usage, in total and per core
+//!   ([`system_summary`], [`CpuSampler`])
+//! - TCP/UDP sockets and their owning processes ([`connections`], [`listening_ports`])
+//! - Names of processes that should not be killed casually ([`protected_names`])
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use crate::process_info::{list_processes, CpuSampler};
+//!
+//! let mut sampler = CpuSampler::default();
+//! let mut processes = list_processes()?;
+//! sampler.apply(&mut processes);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use std::process::Command;
+#[cfg(target_os = "linux")]
+use std::{fs, sync::OnceLock};
+#[cfg(not(any(target_os = "linux", windows)))]
+use std::process::Stdio;
+#[cfg(windows)]
+use std::sync::OnceLock;
+
+/// Scheduler state of a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessState {
+    Running,
+    Sleeping,
+    DiskSleep,
+    Stopped,
+    Zombie,
+    Idle,
+    Unknown,
+}
+
+impl ProcessState {
+    /// Map a `ps`/`/proc` state code to a state
+    pub fn from_code(code: char) -> Self {
+        match code {
+            'R' => ProcessState::Running,
+            'S' => ProcessState::Sleeping,
+            'D' | 'U' => ProcessState::DiskSleep,
+            'T' | 't' => ProcessState::Stopped,
+            'Z' | 'X' => ProcessState::Zombie,
+            'I' => ProcessState::Idle,
+            _ => ProcessState::Unknown,
+        }
+    }
+
+    /// Single-letter code as shown by `ps`
+    pub fn code(&self) -> char {
+        match self {
+            ProcessState::Running => 'R',
+            ProcessState::Sleeping => 'S',
+            ProcessState::DiskSleep => 'D',
+            ProcessState::Stopped => 'T',
+            ProcessState::Zombie => 'Z',
+            ProcessState::Idle => 'I',
+            ProcessState::Unknown => '?',
+        }
+    }
+
+    /// Human readable label
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProcessState::Running => "running",
+            ProcessState::Sleeping => "sleeping",
+            ProcessState::DiskSleep => "disk sleep",
+            ProcessState::Stopped => "stopped",
+            ProcessState::Zombie => "zombie",
+            ProcessState::Idle => "idle",
+            ProcessState::Unknown => "unknown",
+        }
+    }
+}
+
+/// Snapshot of a single running process
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub user: String,
+    pub state: ProcessState,
+    /// Scheduling niceness (-20 highest priority, 19 lowest)
+    pub nice: i32,
+    pub threads: Option<u32>,
+    /// Start time in seconds since the Unix epoch
+    pub start_time: Option<u64>,
+    pub rss_bytes: u64,
+    /// CPU usage in percent of one core
+    pub cpu: f32,
+    /// Resident memory as a percentage of total RAM
+    pub memory: f32,
+    pub name: String,
+    pub command: String,
+}
+
+impl ProcessInfo {
+    /// Seconds elapsed since the process started
+    pub fn elapsed_secs(&self) -> Option<u64> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        self.start_time.map(|start| now.saturating_sub(start))
+    }
+}
+
+impl fmt::Display for ProcessInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>8} {:>6.1}% {:>6.1}% {}",
+            self.pid, self.cpu, self.memory, self.name
+        )
+    }
+}
+
+/// Format an elapsed duration compactly (e.g. `45s`, `12m`, `3h`, `5d`)
+pub fn format_elapsed(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86_399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+/// Extract the executable name from a command line, without its path
+fn name_from_command(command: &str) -> String {
+    let first = command.split_whitespace().next().unwrap_or(command);
+    first.rsplit(std::path::is_separator).next().unwrap_or(first).to_string()
+}
+
+/// List all user-space processes (kernel threads are skipped)
+pub fn list_processes() -> io::Result<Vec<ProcessInfo>> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::list_processes()
+    }
+    #[cfg(windows)]
+    {
+        windows::list_processes()
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        list_processes_ps()
+    }
+}
+
+/// Send `signal` (as returned by [`normalize_signal`]) to `pid`; the error
+/// carries what `kill` or `taskkill` reported
+pub fn send_signal(pid: u32, signal: &str) -> io::Result<()> {
+    let command = if cfg!(windows) {
+        // Without signals a process can only be asked to close, or be ended
+        let force = match signal {
+            "TERM" | "15" => false,
+            "KILL" | "9" => true,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("SIG{} does not exist on Windows; only TERM and KILL do", signal),
+                ))
+            }
+        };
+        let mut command = Command::new("taskkill");
+        if force {
+            command.arg("/F");
+        }
+        command.args(["/PID", &pid.to_string()]);
+        command
+    } else {
+        let mut command = Command::new("kill");
+        command.arg(format!("-{}", signal)).arg(pid.to_string());
+        command
+    };
+    run_quietly(command)
+}
+
+/// Change the scheduling priority of `pid` to the niceness `nice`; Windows
+/// gets the priority class closest to it
+pub fn renice(pid: u32, nice: i32) -> io::Result<()> {
+    let command = if cfg!(windows) {
+        let script = format!("(Get-Process -Id {}).PriorityClass = '{}'", pid, priority_class(nice));
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-Command", &script]);
+        command
+    } else {
+        let mut command = Command::new("renice");
+        command.args(["-n", &nice.to_string(), "-p", &pid.to_string()]);
+        command
+    };
+    run_quietly(command)
+}
+
+/// Windows priority class for a niceness
+fn priority_class(nice: i32) -> &'static str {
+    match nice {
+        i32::MIN..=-10 => "High",
+        -9..=-1 => "AboveNormal",
+        0 => "Normal",
+        1..=9 => "BelowNormal",
+        _ => "Idle",
+    }
+}
+
+/// Run `command`, turning a failure into an error with its stderr
+fn run_quietly(mut command: Command) -> io::Result<()> {
+    let output = command.output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(io::Error::other(error))
+    }
+}
+
+/// PIDs of `pid` and its ancestors up to (but excluding) init, following PPIDs
+pub fn ancestors(processes: &[ProcessInfo], pid: u32) -> Vec<u32> {
+    let mut chain = vec![pid];
+    while let Some(parent) = chain.last()
+        .and_then(|pid| processes.iter().find(|p| p.pid == *pid))
+        .map(|p| p.ppid)
+        .filter(|ppid| *ppid > 1 && !chain.contains(ppid))
+    {
+        chain.push(parent);
+    }
+    chain
+}
+
+/// Per-interval CPU usage sampler.
+///
+/// `ps` and `/proc` only expose cumulative CPU time, so the usage they imply
+/// is a lifetime average that hides processes which have just started
+/// spinning. The sampler remembers each process's cumulative CPU time between
+/// calls and converts the delta into a percentage of one core over the
+/// interval, matching what `top` shows. On platforms without `/proc` the
+/// lifetime figures are left untouched.
+#[derive(Default)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct CpuSampler {
+    /// Cumulative CPU jiffies per PID from the previous sample
+    previous: HashMap<u32, u64>,
+    /// Total system jiffies (all CPUs) from the previous sample
+    previous_total: u64,
+    /// Idle system jiffies (idle + iowait) from the previous sample
+    previous_idle: u64,
+    /// Whole-system CPU usage over the last interval, in percent
+    system_usage: Option<f32>,
+    /// Total and idle jiffies of each core from the previous sample
+    previous_cores: Vec<(u64, u64)>,
+    /// Usage of each core over the last interval, in percent
+    core_usage: Vec<f32>,
+}
+
+impl CpuSampler {
+    /// Replace each process's CPU figure with its usage since the last call.
+    ///
+    /// The first call only records a baseline and leaves the lifetime
+    /// averages in place.
+    pub fn apply(&mut self, processes: &mut [ProcessInfo]) {
+        #[cfg(target_os = "linux")]
+        {
+            let Some((total, idle, cpu_count)) = linux::read_total_jiffies() else {
+                return;
+            };
+
+            let mut current = HashMap::with_capacity(processes.len());
+            for process in processes.iter() {
+                if let Some(jiffies) = linux::read_process_jiffies(process.pid) {
+                    current.insert(process.pid, jiffies);
+                }
+            }
+
+            let total_delta = total.saturating_sub(self.previous_total);
+            if self.previous_total > 0 && total_delta > 0 {
+                for process in processes.iter_mut() {
+                    let Some(&jiffies) = current.get(&process.pid) else {
+                        continue;
+                    };
+                    let before = self.previous.get(&process.pid).copied().unwrap_or(jiffies);
+                    let delta = jiffies.saturating_sub(before) as f32;
+                    process.cpu = delta / total_delta as f32 * 100.0 * cpu_count as f32;
+                }
+            }
+
+            if self.previous_total > 0 && total_delta > 0 {
+                let idle_delta = idle.saturating_sub(self.previous_idle) as f32;
+                self.system_usage = Some((1.0 - idle_delta / total_delta as f32).clamp(0.0, 1.0) * 100.0);
+            }
+
+            let cores = linux::read_core_jiffies();
+            if self.previous_cores.len() == cores.len() {
+                self.core_usage = cores
+                    .iter()
+                    .zip(&self.previous_cores)
+                    .map(|(&(total, idle), &(previous_total, previous_idle))| busy_percent(total.saturating_sub(previous_total), idle.saturating_sub(previous_idle)))
+                    .collect();
+            }
+            
+            self.previous = current;
+            self.previous_total = total;
+            self.previous_idle = idle;
+            self.previous_cores = cores;
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = processes;
+    }
+
+    /// Whole-system CPU usage since the previous sample, once two samples exist
+    pub fn system_usage(&self) -> Option<f32> {
+        self.system_usage
+    }
+
+    /// Usage of each core since the previous sample, once two samples exist;
+    /// empty where `/proc` is missing
+    pub fn core_usage(&self) -> &[f32] {
+        &self.core_usage
+    }
+}
+
+/// Percentage of `total` jiffies that were not `idle`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn busy_percent(total: u64, idle: u64) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+    (1.0 - idle as f32 / total as f32).clamp(0.0, 1.0) * 100.0
+}
+
+/// Total and idle (idle + iowait) jiffies of each `cpuN` line of `/proc/stat`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_core_jiffies(stat: &str) -> Vec<(u64, u64)> {
+    stat.lines()
+        .filter(|line| line.starts_with("cpu") && line.as_bytes().get(3).is_some_and(u8::is_ascii_digit))
+        .map(|line| {
+            let values: Vec<u64> = line.split_whitespace().skip(1).filter_map(|v| v.parse().ok()).collect();
+            let idle = values.get(3).copied().unwrap_or(0) + values.get(4).copied().unwrap_or(0);
+            (values.iter().sum(), idle)
+        })
+        .collect()
+}
+
+/// System-wide resource summary shown above the process list
+#[derive(Debug, Clone, Default)]
+pub struct SystemSummary {
+    pub memory_total: u64,
+    /// Memory available to new processes, if the platform reports it
+    pub memory_available: Option<u64>,
+    /// 1, 5 and 15 minute load averages
+    pub load: Option<[f32; 3]>,
+    /// Total and free swap, if the platform reports them
+    pub swap: Option<(u64, u64)>,
+}
+
+/// Read total/available memory and load averages
+pub fn system_summary() -> SystemSummary {
+    #[cfg(target_os = "linux")]
+    {
+        let meminfo = fs::read_to_string("/proc/meminfo").unwrap_or_default();
+        let field = |key: &str| {
+            meminfo.lines().find_map(|line| {
+                let kb: u64 = line.strip_prefix(key)?.split_whitespace().next()?.parse().ok()?;
+                Some(kb * 1024)
+            })
+        };
+        SystemSummary {
+            memory_total: field("MemTotal:").unwrap_or(0),
+            memory_available: field("MemAvailable:"),
+            load: fs::read_to_string("/proc/loadavg").ok().and_then(|l| parse_load_average(&l)),
+            swap: field("SwapTotal:").zip(field("SwapFree:")),
+        }
+    }
+    #[cfg(windows)]
+    {
+        SystemSummary { memory_total: windows::total_memory(), ..SystemSummary::default() }
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        let sysctl = |name: &str| {
+            Command::new("sysctl")
+                .args(["-n", name])
+                .output()
+                .ok()
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        };
+        SystemSummary {
+            memory_total: sysctl("hw.memsize").and_then(|v| v.parse().ok()).unwrap_or(0),
+            memory_available: None,
+            load: sysctl("vm.loadavg").and_then(|l| parse_load_average(&l)),
+            swap: sysctl("vm.swapusage").and_then(|usage| parse_swap_usage(&usage)),
+        }
+    }
+}
+
+/// Parse load averages from `/proc/loadavg` or `sysctl vm.loadavg` (`{ 1.2 1.0 0.9 }`)
+#[cfg_attr(windows, allow(dead_code))]
+fn parse_load_average(content: &str) -> Option<[f32; 3]> {
+    let mut values = content
+        .split_whitespace()
+        .filter(|v| *v != "{" && *v != "}")
+        .map(|v| v.parse::<f32>().ok());
+    Some([values.next()??, values.next()??, values.next()??])
+}
+
+/// Parse `sysctl vm.swapusage` (`total = 2048.00M  used = 1024.00M  free =
+/// 1024.00M  (encrypted)`) into total and free bytes
+#[cfg_attr(any(target_os = "linux", windows), allow(dead_code))]
+fn parse_swap_usage(usage: &str) -> Option<(u64, u64)> {
+    let value = |name: &str| {
+        let text = usage.split(name).nth(1)?.trim_start().strip_prefix('=')?.split_whitespace().next()?;
+        let (number, unit) = text.split_at(text.len() - 1);
+        let multiplier = match unit {
+            "K" => 1 << 10,
+            "M" => 1 << 20,
+            "G" => 1 << 30,
+            _ => return None,
+        };
+        Some((number.parse::<f64>().ok()? * multiplier as f64) as u64)
+    };
+    Some((value("total")?, value("free")?))
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+
+    /// Query a numeric system constant via `getconf`
+    fn getconf(name: &str) -> Option<u64> {
+        let output = Command::new("getconf").arg(name).output().ok()?;
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    }
+
+    /// Clock ticks per second used by `/proc` time fields
+    fn clock_ticks() -> u64 {
+        static TICKS: OnceLock<u64> = OnceLock::new();
+        *TICKS.get_or_init(|| getconf("CLK_TCK").filter(|&t| t > 0).unwrap_or(100))
+    }
+
+    /// Memory page size in bytes
+    fn page_size() -> u64 {
+        static PAGE_SIZE: OnceLock<u64> = OnceLock::new();
+        *PAGE_SIZE.get_or_init(|| getconf("PAGESIZE").filter(|&p| p > 0).unwrap_or(4096))
+    }
+
+    /// Read total and idle (idle + iowait) jiffies and the CPU count from `/proc/stat`
+    pub(super) fn read_total_jiffies() -> Option<(u64, u64, usize)> {
+        let stat = fs::read_to_string("/proc/stat").ok()?;
+        let mut lines = stat.lines();
+        let values: Vec<u64> = lines
+            .next()?
+            .strip_prefix("cpu ")?
+            .split_whitespace()
+            .filter_map(|v| v.parse().ok())
+            .collect();
+        let total = values.iter().sum();
+        let idle = values.get(3).copied().unwrap_or(0) + values.get(4).copied().unwrap_or(0);
+        let cpu_count = lines
+            .take_while(|line| line.starts_with("cpu"))
+            .count()
+            .max(1);
+        Some((total, idle, cpu_count))
+    }
+
+    /// Total and idle jiffies of each core
+    pub(super) fn read_core_jiffies() -> Vec<(u64, u64)> {
+        fs::read_to_string("/proc/stat").map(|stat| parse_core_jiffies(&stat)).unwrap_or_default()
+    }
+
+    /// Split `/proc/<pid>/stat` into the command name and the fields after it.
+    ///
+    /// The command name may contain spaces and parentheses, so everything up
+    /// to the last `)` belongs to it.
+    pub(super) fn split_stat(stat: &str) -> Option<(&str, Vec<&str>)> {
+        let open = stat.find('(')?;
+        let close = stat.rfind(')')?;
+        let comm = &stat[open + 1..close];
+        let fields = stat.get(close + 2..)?.split_whitespace().collect();
+        Some((comm, fields))
+    }
+
+    /// Whether a process is a kernel thread, from the `/proc/<pid>/stat`
+    /// fields after its command name: flagged `PF_KTHREAD`, or `kthreadd`
+    /// (pid 2) and its children.
+    ///
+    /// Zombies have an empty command line like kernel threads, so that
+    /// cannot tell them apart.
+    pub(super) fn is_kernel_thread(pid: u32, fields: &[&str]) -> bool {
+        const PF_KTHREAD: u64 = 0x0020_0000;
+        let flags: u64 = fields.get(6).and_then(|flags| flags.parse().ok()).unwrap_or(0);
+        pid == 2 || fields.get(1) == Some(&"2") || flags & PF_KTHREAD != 0
+    }
+
+    /// Read utime+stime for a single process
+    pub(super) fn read_process_jiffies(pid: u32) -> Option<u64> {
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        let (_, fields) = split_stat(&stat)?;
+        let utime: u64 = fields.get(11)?.parse().ok()?;
+        let stime: u64 = fields.get(12)?.parse().ok()?;
+        Some(utime + stime)
+    }
+
+    /// Boot time in seconds since the epoch (`btime` in `/proc/stat`)
+    fn boot_time() -> Option<u64> {
+        fs::read_to_string("/proc/stat")
+            .ok()?
+            .lines()
+            .find_map(|line| line.strip_prefix("btime ")?.trim().parse().ok())
+    }
+
+    /// Total physical memory in bytes
+    fn total_memory() -> Option<u64> {
+        fs::read_to_string("/proc/meminfo")
+            .ok()?
+            .lines()
+            .find_map(|line| {
+                let kb: u64 = line
+                    .strip_prefix("MemTotal:")?
+                    .split_whitespace()
+                    .next()?
+                    .parse()
+                    .ok()?;
+                Some(kb * 1024)
+            })
+    }
+
+    fn uptime_secs() -> Option<f64> {
+        fs::read_to_string("/proc/uptime")
+            .ok()?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    }
+
+    /// Real UID of a process from `/proc/<pid>/status`
+    fn read_uid(pid: u32) -> Option<u32> {
+        fs::read_to_string(format!("/proc/{}/status", pid))
+            .ok()?
+            .lines()
+            .find_map(|line| line.strip_prefix("Uid:")?.split_whitespace().next()?.parse().ok())
+    }
+
+    pub(super) fn socket_table() -> Vec<SocketEntry> {
+        [
+            ("/proc/net/tcp", Protocol::Tcp),
+            ("/proc/net/tcp6", Protocol::Tcp),
+            ("/proc/net/udp", Protocol::Udp),
+            ("/proc/net/udp6", Protocol::Udp),
+        ]
+        .iter()
+        .filter_map(|(path, protocol)| {
+            fs::read_to_string(path).ok().map(|content| parse_socket_table(&content, *protocol))
+        })
+        .flatten()
+        .collect()
+    }
+
+    /// Socket inodes referenced by a process's file descriptors
+    pub(super) fn socket_inodes(pid: u32) -> Option<Vec<u64>> {
+        let inodes = fs::read_dir(format!("/proc/{}/fd", pid))
+            .ok()?
+            .flatten()
+            .filter_map(|fd| {
+                let target = fs::read_link(fd.path()).ok()?;
+                let target = target.to_str()?;
+                target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+            })
+            .collect();
+        Some(inodes)
+    }
+
+    pub(super) fn connections() -> Vec<Connection> {
+        let sockets = socket_table();
+        if sockets.is_empty() {
+            return Vec::new();
+        }
+
+        // Processes holding each socket inode; forked servers share theirs
+        let mut owners: HashMap<u64, Vec<u32>> = HashMap::new();
+        let pids = fs::read_dir("/proc")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok());
+        for pid in pids {
+            for inode in socket_inodes(pid).unwrap_or_default() {
+                owners.entry(inode).or_default().push(pid);
+            }
+        }
+
+        sockets
+            .into_iter()
+            .flat_map(|socket| {
+                // Sockets in TIME_WAIT have no inode and no owner
+                let pids = owners.get(&socket.inode).filter(|_| socket.inode != 0).cloned().unwrap_or_default();
+                if pids.is_empty() {
+                    vec![Connection { pid: None, socket }]
+                } else {
+                    pids.into_iter().map(|pid| Connection { pid: Some(pid), socket: socket.clone() }).collect()
+                }
+            })
+            .collect()
+    }
+
+    pub(super) fn process_details(pid: u32) -> ProcessDetails {
+        let base = format!("/proc/{}", pid);
+
+        let environment = fs::read(format!("{}/environ", base)).ok().map(|raw| {
+            String::from_utf8_lossy(&raw)
+                .split('\0')
+                .filter(|var| !var.is_empty())
+                .map(str::to_string)
+                .collect()
+        });
+
+        let sockets = match socket_inodes(pid) {
+            Some(inodes) if !inodes.is_empty() => socket_table()
+                .into_iter()
+                .filter(|socket| inodes.contains(&socket.inode))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        const MEMORY_FIELDS: [(&str, &str); 6] = [
+            ("VmRSS:", "Resident"),
+            ("RssAnon:", "Anonymous"),
+            ("RssFile:", "File-backed"),
+            ("RssShmem:", "Shared"),
+            ("VmSwap:", "Swapped"),
+            ("VmSize:", "Virtual"),
+        ];
+        let status = fs::read_to_string(format!("{}/status", base)).unwrap_or_default();
+        let memory = MEMORY_FIELDS
+            .iter()
+            .filter_map(|(key, label)| {
+                let kb: u64 = status
+                    .lines()
+                    .find_map(|line| line.strip_prefix(key))?
+                    .split_whitespace()
+                    .next()?
+                    .parse()
+                    .ok()?;
+                Some((*label, kb * 1024))
+            })
+            .collect();
+
+        ProcessDetails {
+            cwd: fs::read_link(format!("{}/cwd", base))
+                .ok()
+                .map(|p| p.display().to_string()),
+            environment,
+            open_files: fs::read_dir(format!("{}/fd", base)).ok().map(|dir| dir.count()),
+            sockets,
+            memory,
+        }
+    }
+
+    pub(super) fn list_processes() -> io::Result<Vec<ProcessInfo>> {
+        let ticks = clock_ticks() as f64;
+        let boot = boot_time();
+        let uptime = uptime_secs();
+        let mem_total = total_memory().unwrap_or(0);
+        let page = page_size();
+
+        let mut processes = Vec::new();
+        for entry in fs::read_dir("/proc")?.flatten() {
+            let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+                continue;
+            };
+
+            // Processes can exit while we scan, so every read is best-effort
+            let Ok(stat) = fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+                continue;
+            };
+            let Some((comm, fields)) = split_stat(&stat) else {
+                continue;
+            };
+            if fields.len() < 22 || is_kernel_thread(pid, &fields) {
+                continue;
+            }
+
+            let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).unwrap_or_default();
+            let command = String::from_utf8_lossy(&cmdline)
+                .split('\0')
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            // Zombies keep only their name
+            let command = if command.is_empty() { comm.to_string() } else { command };
+
+            let field = |i: usize| fields.get(i).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+            let state = ProcessState::from_code(fields[0].chars().next().unwrap_or('?'));
+            let ppid = field(1) as u32;
+            let cpu_ticks = field(11) + field(12);
+            let nice = fields[16].parse().unwrap_or(0);
+            let threads = field(17) as u32;
+            let start_ticks = field(19);
+            let rss_bytes = field(21) * page;
+
+            let started_after_boot = start_ticks as f64 / ticks;
+            let cpu = match uptime {
+                Some(up) if up > started_after_boot => {
+                    (cpu_ticks as f64 / ticks / (up - started_after_boot) * 100.0) as f32
+                }
+                _ => 0.0,
+            };
+            let memory = if mem_total > 0 {
+                (rss_bytes as f64 / mem_total as f64 * 100.0) as f32
+            } else {
+                0.0
+            };
+
+            let name = if comm.is_empty() { name_from_command(&command) } else { comm.to_string() };
+
+            processes.push(ProcessInfo {
+                pid,
+                ppid,
+                user: read_uid(pid).map(crate::users::user_name).unwrap_or_default(),
+                state,
+                nice,
+                threads: Some(threads),
+                start_time: boot.map(|b| b + started_after_boot as u64),
+                rss_bytes,
+                cpu,
+                memory,
+                name,
+                command,
+            });
+        }
+
+        Ok(processes)
+    }
+}
+
+/// Transport protocol of a socket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Protocol::Tcp => write!(f, "TCP"),
+            Protocol::Udp => write!(f, "UDP"),
+        }
+    }
+}
+
+/// A socket from the kernel socket tables
+#[derive(Debug, Clone)]
+pub struct SocketEntry {
+    pub protocol: Protocol,
+    pub local_addr: String,
+    pub local_port: u16,
+    pub remote_addr: String,
+    pub remote_port: u16,
+    /// TCP state name (e.g. `LISTEN`, `ESTABLISHED`); UDP sockets report `UNCONN`
+    pub state: String,
+    pub inode: u64,
+}
+
+impl SocketEntry {
+    /// Whether the socket accepts incoming traffic (TCP LISTEN or unconnected UDP)
+    pub fn is_listening(&self) -> bool {
+        self.state == "LISTEN" || (self.protocol == Protocol::Udp && self.remote_port == 0)
+    }
+}
+
+/// Detailed information about a single process, read from `/proc/<pid>/`
+#[derive(Debug, Clone, Default)]
+pub struct ProcessDetails {
+    pub cwd: Option<String>,
+    /// Environment variables, or `None` when the process is not readable
+    pub environment: Option<Vec<String>>,
+    pub open_files: Option<usize>,
+    pub sockets: Vec<SocketEntry>,
+    /// Memory breakdown as (label, bytes) pairs, e.g. `("RSS anon", 1024)`
+    pub memory: Vec<(&'static str, u64)>,
+}
+
+/// Load detailed information for a process.
+///
+/// Every field is best-effort: processes owned by other users typically hide
+/// their environment and file descriptors, which leaves those fields empty.
+pub fn process_details(pid: u32) -> ProcessDetails {
+    #[cfg(target_os = "linux")]
+    {
+        linux::process_details(pid)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        ProcessDetails::default()
+    }
+}
+
+/// A listening socket together with the process holding it
+#[derive(Debug, Clone)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub socket: SocketEntry,
+}
+
+/// A socket together with the process holding it, when that is known
+#[derive(Debug, Clone)]
+pub struct Connection {
+    pub pid: Option<u32>,
+    pub socket: SocketEntry,
+}
+
+/// Find every TCP/UDP socket, listening or connected, and the processes
+/// holding it (one entry per process when several share a socket).
+///
+/// Reads `/proc/net` on Linux, runs `netstat` on Windows and falls back to
+/// `lsof` elsewhere. The owners of other users' sockets are only known when
+/// running as root; `lsof` leaves those sockets out altogether.
+pub fn connections() -> Vec<Connection> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::connections()
+    }
+    #[cfg(windows)]
+    {
+        Command::new("netstat")
+            .arg("-ano")
+            .output()
+            .map(|output| parse_netstat(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default()
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        Command::new("lsof")
+            .args(["-nP", "-i", "-FpPnT"])
+            .output()
+            .map(|output| parse_lsof(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default()
+    }
+}
+
+/// Find every listening TCP/UDP socket with a known owner
+pub fn listening_ports() -> Vec<PortOwner> {
+    connections()
+        .into_iter()
+        .filter(|connection| connection.socket.is_listening())
+        .filter_map(|connection| Some(PortOwner { pid: connection.pid?, socket: connection.socket }))
+        .collect()
+}
+
+/// Split an `address:port` endpoint, unbracketing IPv6 addresses; a `*`
+/// port (as in `*:*`) is 0
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+fn split_endpoint(endpoint: &str) -> Option<(String, u16)> {
+    let (addr, port) = endpoint.rsplit_once(':')?;
+    let port = if port == "*" { 0 } else { port.parse().ok()? };
+    Some((addr.trim_matches(|c| c == '[' || c == ']').to_string(), port))
+}
+
+/// Parse `lsof -F pPnT` field output into sockets
+#[cfg_attr(any(target_os = "linux", windows), allow(dead_code))]
+fn parse_lsof(content: &str) -> Vec<Connection> {
+    let mut connections: Vec<Connection> = Vec::new();
+    let mut pid = 0;
+    let mut protocol = Protocol::Tcp;
+    // Whether the last name was parsed, so its state can follow
+    let mut parsed = false;
+
+    for line in content.lines() {
+        let mut chars = line.chars();
+        let tag = chars.next();
+        let value = chars.as_str();
+        match tag {
+            Some('p') => pid = value.parse().unwrap_or(0),
+            Some('P') => protocol = if value == "UDP" { Protocol::Udp } else { Protocol::Tcp },
+            Some('n') => {
+                // Connected sockets are reported as `local->remote`
+                let (local, remote) = match value.split_once("->") {
+                    Some((local, remote)) => (local, Some(remote)),
+                    None => (value, None),
+                };
+                let Some((local_addr, local_port)) = split_endpoint(local) else {
+                    parsed = false;
+                    continue;
+                };
+                let (remote_addr, remote_port) = remote.and_then(split_endpoint).unwrap_or_default();
+                let state = match (protocol, remote) {
+                    (Protocol::Tcp, None) => "LISTEN",
+                    (Protocol::Udp, None) => "UNCONN",
+                    (_, Some(_)) => "ESTABLISHED",
+                };
+                connections.push(Connection {
+                    pid: Some(pid),
+                    socket: SocketEntry {
+                        protocol,
+                        local_addr,
+                        local_port,
+                        remote_addr,
+                        remote_port,
+                        state: state.to_string(),
+                        inode: 0,
+                    },
+                });
+                parsed = true;
+            }
+            // The TCP state follows the name as `TST=CLOSE_WAIT`
+            Some('T') => {
+                if let (Some(state), Some(connection), true) = (value.strip_prefix("ST="), connections.last_mut(), parsed) {
+                    connection.socket.state = state.to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    connections
+}
+
+/// Parse `netstat -ano` (Windows) into sockets. The state column is
+/// translated on localized systems, so a TCP socket counts as listening
+/// when it has no remote port, and every UDP socket does.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_netstat(content: &str) -> Vec<Connection> {
+    let mut connections = Vec::new();
+    for line in content.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let (protocol, local, remote, state, pid) = match parts.as_slice() {
+            ["TCP", local, remote, state, pid] => (Protocol::Tcp, local, remote, Some(*state), pid),
+            ["UDP", local, remote, pid] => (Protocol::Udp, local, remote, None, pid),
+            _ => continue,
+        };
+        let (Some((local_addr, local_port)), Some((remote_addr, remote_port)), Ok(pid)) =
+            (split_endpoint(local), split_endpoint(remote), pid.parse::<u32>())
+        else {
+            continue;
+        };
+        let state = match (protocol, state) {
+            (Protocol::Tcp, _) if remote_port == 0 => "LISTEN",
+            (Protocol::Tcp, Some(state)) => state,
+            _ => "UNCONN",
+        };
+        connections.push(Connection {
+            // Closed connections belong to the idle process, PID 0
+            pid: (pid != 0).then_some(pid),
+            socket: SocketEntry {
+                protocol,
+                local_addr,
+                local_port,
+                remote_addr,
+                remote_port,
+                state: state.to_string(),
+                inode: 0,
+            },
+        });
+    }
+    connections
+}
+
+/// Parse `tasklist /v /fo csv /nh` output (image name, PID, session name,
+/// session number, memory, status, user, CPU time, window title); memory
+/// percentages need `memory_total` in bytes
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_tasklist(content: &str, memory_total: u64) -> Vec<ProcessInfo> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields = csv_fields(line);
+            let [name, pid, _, _, memory, status, user, ..] = fields.as_slice() else {
+                return None;
+            };
+            let pid = pid.parse().ok().filter(|pid| *pid != 0)?;
+            // `12,345 K`, with the thousands separator of the locale
+            let kb: u64 = memory.chars().filter(char::is_ascii_digit).collect::<String>().parse().unwrap_or(0);
+            let rss_bytes = kb * 1024;
+            Some(ProcessInfo {
+                pid,
+                ppid: 0,
+                // `DOMAIN\user`; `N/A` for processes that can't be inspected
+                user: if user == "N/A" { String::new() } else { user.rsplit('\\').next().unwrap_or(user).to_string() },
+                state: match status.as_str() {
+                    "Running" => ProcessState::Running,
+                    "Suspended" => ProcessState::Stopped,
+                    "Not Responding" => ProcessState::DiskSleep,
+                    _ => ProcessState::Unknown,
+                },
+                nice: 0,
+                threads: None,
+                start_time: None,
+                rss_bytes,
+                cpu: 0.0,
+                memory: if memory_total > 0 { (rss_bytes as f64 / memory_total as f64 * 100.0) as f32 } else { 0.0 },
+                name: name.clone(),
+                command: name.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Split a CSV line with every field quoted, as `tasklist` writes them
+#[cfg_attr(not(windows), allow(dead_code))]
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim_end().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::*;
+
+    pub fn list_processes() -> io::Result<Vec<ProcessInfo>> {
+        let output = Command::new("tasklist").args(["/v", "/fo", "csv", "/nh"]).output()?;
+        if !output.status.success() {
+            return Err(io::Error::new(io::ErrorKind::Other, "Failed to execute tasklist"));
+        }
+        Ok(parse_tasklist(&String::from_utf8_lossy(&output.stdout), total_memory()))
+    }
+
+    /// Installed memory in bytes, asked once since it does not change
+    pub fn total_memory() -> u64 {
+        static TOTAL: OnceLock<u64> = OnceLock::new();
+        *TOTAL.get_or_init(|| {
+            Command::new("powershell")
+                .args(["-NoProfile", "-Command", "(Get-CimInstance Win32_ComputerSystem).TotalPhysicalMemory"])
+                .output()
+                .ok()
+                .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+                .unwrap_or(0)
+        })
+    }
+}
+
+/// Process names that get a double confirmation before being killed
+const DEFAULT_PROTECTED: &[&str] = &[
+    "init", "systemd", "launchd", "sshd", "login", "dbus-daemon",
+    "Xorg", "Xwayland", "gdm", "sddm", "lightdm", "gnome-shell",
+    "kwin_x11", "kwin_wayland", "plasmashell", "sway", "i3", "Hyprland",
+    "WindowServer", "loginwindow", "Dock", "Finder",
+];
+
+/// Protected process names: the defaults, the `protected` names from
+/// config.toml and any listed in `$TT_PROTECTED` (comma separated)
+pub fn protected_names(configured: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = DEFAULT_PROTECTED.iter().map(|name| name.to_string()).collect();
+    names.extend(configured.iter().cloned());
+    if let Ok(extra) = std::env::var("TT_PROTECTED") {
+        names.extend(extra.split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_string));
+    }
+    names
+}
+
+/// Signal names accepted by `kill -<NAME>` on every supported platform
+const SIGNALS: [&str; 14] = [
+    "HUP", "INT", "QUIT", "KILL", "USR1", "USR2", "PIPE",
+    "ALRM", "TERM", "CHLD", "CONT", "STOP", "TSTP", "WINCH",
+];
+
+/// Normalize a signal given as `TERM`, `sigterm`, `SIGKILL` or `9`.
+///
+/// Returns the canonical name (or the number) suitable for `kill -<signal>`,
+/// or `None` if the signal is not recognised.
+pub fn normalize_signal(signal: &str) -> Option<String> {
+    let upper = signal.trim().to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    if let Ok(number) = name.parse::<u8>() {
+        return (1..=31).contains(&number).then(|| number.to_string());
+    }
+    SIGNALS.contains(&name).then(|| name.to_string())
+}
+
+/// Map a hex TCP state code from `/proc/net/tcp` to its name
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn tcp_state_name(code: &str) -> &'static str {
+    match code {
+        "01" => "ESTABLISHED",
+        "02" => "SYN_SENT",
+        "03" => "SYN_RECV",
+        "04" => "FIN_WAIT1",
+        "05" => "FIN_WAIT2",
+        "06" => "TIME_WAIT",
+        "07" => "CLOSE",
+        "08" => "CLOSE_WAIT",
+        "09" => "LAST_ACK",
+        "0A" => "LISTEN",
+        "0B" => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Decode a `/proc/net` hex address (`0100007F:1F90`) into address and port.
+///
+/// IPv4 addresses are stored as a little-endian u32; IPv6 addresses as four
+/// little-endian u32 words.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_hex_address(value: &str) -> Option<(String, u16)> {
+    let (addr, port) = value.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let addr = match addr.len() {
+        8 => {
+            let raw = u32::from_str_radix(addr, 16).ok()?;
+            std::net::Ipv4Addr::from(raw.to_le_bytes()).to_string()
+        }
+        32 => {
+            let mut bytes = [0u8; 16];
+            for (i, chunk) in bytes.chunks_mut(4).enumerate() {
+                let word = u32::from_str_radix(&addr[i * 8..i * 8 + 8], 16).ok()?;
+                chunk.copy_from_slice(&word.to_le_bytes());
+            }
+            std::net::Ipv6Addr::from(bytes).to_string()
+        }
+        _ => return None,
+    };
+    Some((addr, port))
+}
+
+/// Parse the content of a `/proc/net/{tcp,udp}[6]` table
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_socket_table(content: &str, protocol: Protocol) -> Vec<SocketEntry> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (local_addr, local_port) = parse_hex_address(fields.get(1)?)?;
+            let (remote_addr, remote_port) = parse_hex_address(fields.get(2)?)?;
+            let state = match protocol {
+                Protocol::Tcp => tcp_state_name(fields.get(3)?).to_string(),
+                Protocol::Udp if remote_port == 0 => "UNCONN".to_string(),
+                Protocol::Udp => "ESTABLISHED".to_string(),
+            };
+            Some(SocketEntry {
+                protocol,
+                local_addr,
+                local_port,
+                remote_addr,
+                remote_port,
+                state,
+                inode: fields.get(9)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Parse an `etime` value (`[[dd-]hh:]mm:ss`) into seconds
+#[cfg_attr(any(target_os = "linux", windows), allow(dead_code))]
+fn parse_etime(etime: &str) -> Option<u64> {
+    let (days, rest) = match etime.split_once('-') {
+        Some((d, rest)) => (d.parse::<u64>().ok()?, rest),
+        None => (0, etime),
+    };
+    let parts: Vec<u64> = rest.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let secs = match parts.as_slice() {
+        [h, m, s] => h * 3600 + m * 60 + s,
+        [m, s] => m * 60 + s,
+        [s] => *s,
+        _ => return None,
+    };
+    Some(days * 86_400 + secs)
+}
+
+/// Fallback provider using portable `ps -o` columns
+#[cfg(not(any(target_os = "linux", windows)))]
+fn list_processes_ps() -> io::Result<Vec<ProcessInfo>> {
+    let output = Command::new("ps")
+        .args(["-axo", "pid=,ppid=,user=,state=,nice=,rss=,%cpu=,%mem=,etime=,command="])
+        .stdout(Stdio::piped())
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::new(io::ErrorKind::Other, "Failed to execute ps command"));
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let processes = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pid = parts.next()?.parse().ok()?;
+            let ppid = parts.next()?.parse().ok()?;
+            let user = parts.next()?.to_string();
+            let state = ProcessState::from_code(parts.next()?.chars().next()?);
+            let nice = parts.next()?.parse().unwrap_or(0);
+            let rss_kb: u64 = parts.next()?.parse().ok()?;
+            let cpu = parts.next()?.parse().ok()?;
+            let memory = parts.next()?.parse().ok()?;
+            let elapsed = parse_etime(parts.next()?);
+            let command = parts.collect::<Vec<_>>().join(" ");
+            if command.is_empty() {
+                return None;
+            }
+
+            Some(ProcessInfo {
+                pid,
+                ppid,
+                user,
+                state,
+                nice,
+                threads: None,
+                start_time: elapsed.map(|e| now.saturating_sub(e)),
+                rss_bytes: rss_kb * 1024,
+                cpu,
+                memory,
+                name: name_from_command(&command),
+                command,
+            })
+        })
+        .collect();
+
+    Ok(processes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_from_command() {
+        assert_eq!(name_from_command("/usr/bin/python3 -m http.server"), "python3");
+        assert_eq!(name_from_command("node"), "node");
+    }
+
+    #[test]
+    fn test_cpu_and_swap_parsers() {
+        let stat = "cpu  30 0 10 50 10 0 0 0 0 0\ncpu0 20 0 5 20 5 0 0 0 0 0\ncpu1 10 0 5 30 5 0 0 0 0 0\nintr 1 2\n";
+        assert_eq!(parse_core_jiffies(stat), vec![(50, 25), (50, 35)]);
+        assert_eq!(busy_percent(50, 25), 50.0);
+        assert_eq!(busy_percent(0, 0), 0.0);
+        let usage = "total = 2048.00M  used = 512.00M  free = 1536.00M  (encrypted)";
+        assert_eq!(parse_swap_usage(usage), Some((2048 << 20, 1536 << 20)));
+    }
+
+    #[test]
+    fn test_parse_etime() {
+        assert_eq!(parse_etime("05"), Some(5));
+        assert_eq!(parse_etime("01:05"), Some(65));
+        assert_eq!(parse_etime("02:01:05"), Some(7265));
+        assert_eq!(parse_etime("3-00:00:10"), Some(3 * 86_400 + 10));
+        assert_eq!(parse_etime("bogus"), None);
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(42), "42s");
+        assert_eq!(format_elapsed(600), "10m");
+        assert_eq!(format_elapsed(7200), "2h");
+        assert_eq!(format_elapsed(3 * 86_400), "3d");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_split_stat_handles_spaces_in_name() {
+        let stat = "1234 (tmux: server) S 1 1234 1234 0 -1 4194560 0 0 0 0 7 3 0 0 20 0 1 0 100 0 50";
+        let (comm, fields) = linux::split_stat(stat).unwrap();
+        assert_eq!(comm, "tmux: server");
+        assert_eq!(fields[0], "S");
+        assert_eq!(fields[11], "7");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_zombie_is_not_kernel_thread() {
+        let zombie = "4321 (worker) Z 4320 4320 1234 0 -1 4227084 0 0 0 0 0 0 0 0 20 0 1 0 900 0 0";
+        let (comm, fields) = linux::split_stat(zombie).unwrap();
+        assert_eq!(comm, "worker");
+        assert_eq!(fields[0], "Z");
+        assert!(!linux::is_kernel_thread(4321, &fields));
+
+        let kworker = "57 (kworker/0:1) I 2 0 0 0 -1 69238880 0 0 0 0 0 0 0 0 20 0 1 0 12 0 0";
+        let (_, fields) = linux::split_stat(kworker).unwrap();
+        assert!(linux::is_kernel_thread(57, &fields));
+        let kthreadd = "2 (kthreadd) S 0 0 0 0 -1 2129984 0 0 0 0 0 0 0 0 20 0 1 0 1 0 0";
+        let (_, fields) = linux::split_stat(kthreadd).unwrap();
+        assert!(linux::is_kernel_thread(2, &fields));
+    }
+
+    #[test]
+    fn test_list_processes_includes_self() {
+        let own_pid = std::process::id();
+        let processes = list_processes().unwrap();
+        let own = processes.iter().find(|p| p.pid == own_pid).expect("current process listed");
+        assert!(own.rss_bytes > 0);
+        assert!(!own.command.is_empty());
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let process = |pid, ppid| ProcessInfo {
+            pid,
+            ppid,
+            user: String::new(),
+            state: ProcessState::Sleeping,
+            nice: 0,
+            threads: None,
+            start_time: None,
+            rss_bytes: 0,
+            cpu: 0.0,
+            memory: 0.0,
+            name: String::new(),
+            command: String::new(),
+        };
+        let processes = vec![process(10, 1), process(20, 10), process(30, 20), process(40, 10)];
+        assert_eq!(ancestors(&processes, 30), vec![30, 20, 10]);
+        assert_eq!(ancestors(&processes, 99), vec![99]);
+    }
+
+    #[test]
+    fn test_parse_load_average() {
+        assert_eq!(parse_load_average("0.52 0.58 0.59 1/234 5678\n"), Some([0.52, 0.58, 0.59]));
+        assert_eq!(parse_load_average("{ 1.20 1.00 0.90 }"), Some([1.2, 1.0, 0.9]));
+        assert_eq!(parse_load_average("garbage"), None);
+    }
+
+    #[test]
+    fn test_normalize_signal() {
+        assert_eq!(normalize_signal("term").as_deref(), Some("TERM"));
+        assert_eq!(normalize_signal("SIGKILL").as_deref(), Some("KILL"));
+        assert_eq!(normalize_signal("9").as_deref(), Some("9"));
+        assert_eq!(normalize_signal("BOGUS"), None);
+        assert_eq!(normalize_signal("64"), None);
+    }
+
+    #[test]
+    fn test_parse_lsof() {
+        let content = "p4242\nf12\nPTCP\nn*:3000\nTST=LISTEN\nf13\nPTCP\nn[::1]:8080\nf14\nPTCP\nn127.0.0.1:3000->127.0.0.1:51234\nTST=CLOSE_WAIT\np77\nf5\nPUDP\nn127.0.0.1:5353\nf6\nPUDP\nn10.0.0.2:5000->10.0.0.3:53\n";
+        let connections = parse_lsof(content);
+        assert_eq!(connections.len(), 5);
+        assert_eq!((connections[2].socket.state.as_str(), connections[2].socket.remote_port), ("CLOSE_WAIT", 51234));
+        let ports: Vec<&Connection> = connections.iter().filter(|connection| connection.socket.is_listening()).collect();
+        assert_eq!(ports.len(), 3);
+        assert_eq!((ports[0].pid, ports[0].socket.local_port), (Some(4242), 3000));
+        assert_eq!(ports[1].socket.local_addr, "::1");
+        assert_eq!(ports[2].socket.protocol, Protocol::Udp);
+        assert!(ports[2].socket.is_listening());
+    }
+
+    #[test]
+    fn test_parse_netstat() {
+        let content = "\nActive Connections\n\n  Proto  Local Address          Foreign Address        State           PID\n  TCP    0.0.0.0:135            0.0.0.0:0              LISTENING       1012\n  TCP    10.0.0.2:51234         140.82.112.3:443       ESTABLISHED     8812\n  TCP    [::]:445               [::]:0                 LISTENING       4\n  UDP    0.0.0.0:5353           *:*                                    2260\n";
+        let connections = parse_netstat(content);
+        assert_eq!(connections.len(), 4);
+        assert_eq!((connections[1].socket.remote_addr.as_str(), connections[1].socket.state.as_str()), ("140.82.112.3", "ESTABLISHED"));
+        let ports: Vec<&Connection> = connections.iter().filter(|connection| connection.socket.is_listening()).collect();
+        assert_eq!(ports.len(), 3);
+        assert_eq!((ports[0].pid, ports[0].socket.local_port), (Some(1012), 135));
+        assert_eq!(ports[1].socket.local_addr, "::");
+        assert_eq!(ports[2].socket.protocol, Protocol::Udp);
+        assert!(ports.iter().all(|port| port.socket.is_listening()));
+    }
+
+    #[test]
+    fn test_parse_tasklist() {
+        let content = "\"System Idle Process\",\"0\",\"Services\",\"0\",\"8 K\",\"Unknown\",\"NT AUTHORITY\\SYSTEM\",\"9:15:02\",\"N/A\"\r\n\"code.exe\",\"4242\",\"Console\",\"1\",\"152,340 K\",\"Running\",\"DESKTOP\\ada\",\"0:01:20\",\"main.rs - \"\"tt\"\"\"\r\n";
+        let processes = parse_tasklist(content, 1024 * 1024 * 1024);
+        assert_eq!(processes.len(), 1);
+        let process = &processes[0];
+        assert_eq!((process.pid, process.name.as_str(), process.user.as_str()), (4242, "code.exe", "ada"));
+        assert_eq!(process.rss_bytes, 152_340 * 1024);
+        assert_eq!(process.state, ProcessState::Running);
+        assert!((process.memory - 14.5).abs() < 0.1);
+        assert_eq!(csv_fields("\"a,b\",\"say \"\"hi\"\"\"")[1], "say \"hi\"");
+    }
+
+    #[test]
+    fn test_parse_socket_table() {
+        let content = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 54321 1 0000000000000000 100 0 0 10 0\n";
+        let sockets = parse_socket_table(content, Protocol::Tcp);
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].local_addr, "127.0.0.1");
+        assert_eq!(sockets[0].local_port, 3000);
+        assert_eq!(sockets[0].state, "LISTEN");
+        assert_eq!(sockets[0].inode, 54321);
+        assert!(sockets[0].is_listening());
+    }
+
+    #[test]
+    fn test_parse_hex_address_ipv6() {
+        let (addr, port) = parse_hex_address("00000000000000000000000001000000:0050").unwrap();
+        assert_eq!(addr, "::1");
+        assert_eq!(port, 80);
+    }
+
+    #[test]
+    fn test_state_codes_round_trip() {
+        for code in ['R', 'S', 'D', 'T', 'Z', 'I'] {
+            assert_eq!(ProcessState::from_code(code).code(), code);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Where each tool was left, per working directory, to pick up from on the
+//! next launch.
+//!
+//! Tools that implement [`Resume`] run through [`resume`], which restores
+//! the [`Session`] saved for the tool in the current directory and saves
+//! the new one when the tool exits. Sessions are kept as JSON in
+//! `sessions.json` under [`platform::state_dir`], keyed by tool and directory:
+//!
+//! ```json
+//! {"find": {"/home/me/project": {"query": "main", "selected": "src/main.rs", "saved": 1718000000}}}
+//! ```
+//!
+//! `--fresh` starts a tool without its session; what it is left in is still
+//! saved.
+
+use crate::{file_ops, platform};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env,
+    fs,
+    io,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Directories remembered per tool; the ones left longest ago go first
+const MAX_DIRS: usize = 100;
+
+/// Whether tools start without their sessions (`--fresh`)
+static FRESH: AtomicBool = AtomicBool::new(false);
+
+/// What a tool restores; each tool fills in the parts it has
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Filter or search text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// What identifies the selected row, such as its path or name, so it is
+    /// found again when the list has changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected: Option<String>,
+    /// Directory a browser was in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    /// Rows scrolled down in a pane that scrolls on its own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scroll: Option<usize>,
+    /// Seconds since the Unix epoch when it was saved
+    pub saved: u64,
+}
+
+/// A tool that picks up where it was left
+pub trait Resume {
+    /// Where the tool is now
+    fn session(&self) -> Session;
+
+    /// Go back to where `session` left off; arguments given on the command
+    /// line win over it
+    fn restore(&mut self, session: Session);
+}
+
+/// Make tools start without their sessions, for `--fresh`
+pub fn configure(fresh: bool) {
+    FRESH.store(fresh, Ordering::Relaxed);
+}
+
+/// Sessions by tool, then by directory
+type Store = BTreeMap<String, BTreeMap<String, Session>>;
+
+fn sessions_file() -> Option<PathBuf> {
+    platform::state_dir().map(|dir| dir.join("sessions.json"))
+}
+
+fn read_store() -> Store {
+    sessions_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn working_dir() -> Option<String> {
+    env::current_dir().ok().map(|dir| dir.to_string_lossy().into_owned())
+}
+
+/// The session `tool` was left in in the current directory, unless tools
+/// start fresh
+pub fn load(tool: &str) -> Option<Session> {
+    if FRESH.load(Ordering::Relaxed) {
+        return None;
+    }
+    read_store().remove(tool)?.remove(&working_dir()?)
+}
+
+/// Remember `session` for `tool` in the current directory
+pub fn save(tool: &str, session: Session) -> io::Result<()> {
+    let path = sessions_file()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory for sessions"))?;
+    let dir = working_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No working directory"))?;
+
+    let saved = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    let mut store = read_store();
+    let sessions = store.entry(tool.to_string()).or_default();
+    sessions.insert(dir, Session { saved, ..session });
+    prune(sessions, MAX_DIRS);
+    // Written in one rename, so tools quitting together can't leave half a
+    // file behind
+    file_ops::replace_contents(&path, serde_json::to_string(&store)?.as_bytes())
+}
+
+/// Drop the sessions saved longest ago until `max` are left
+fn prune(sessions: &mut BTreeMap<String, Session>, max: usize) {
+    while sessions.len() > max {
+        let Some(oldest) = sessions.iter().min_by_key(|(_, session)| session.saved).map(|(dir, _)| dir.clone()) else {
+            break;
+        };
+        sessions.remove(&oldest);
+    }
+}
+
+/// Run `app` with `run` (such as [`crate::tui_common::run_app`]), restoring
+/// its session for `tool` first and saving the new one afterwards
+pub fn resume<A: Resume>(app: &mut A, tool: &str, run: impl FnOnce(&mut A) -> io::Result<()>) -> io::Result<()> {
+    if let Some(session) = load(tool) {
+        app.restore(session);
+    }
+    run(app)?;
+    // Losing the session is no reason to report the tool failed
+    let _ = save(tool, app.session());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune() {
+        let mut sessions: BTreeMap<String, Session> = [("/a", 30), ("/b", 10), ("/c", 20)]
+            .into_iter()
+            .map(|(dir, saved)| (dir.to_string(), Session { saved, ..Session::default() }))
+            .collect();
+        prune(&mut sessions, 2);
+        assert_eq!(sessions.keys().collect::<Vec<_>>(), vec!["/a", "/c"]);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let session = Session { query: Some("main".to_string()), saved: 5, ..Session::default() };
+        let json = serde_json::to_string(&session).unwrap();
+        assert_eq!(json, r#"{"query":"main","saved":5}"#);
+        // Fields a newer tt added are ignored, missing ones are empty
+        let read: Session = serde_json::from_str(r#"{"selected":"x","later":1}"#).unwrap();
+        assert_eq!(read, Session { selected: Some("x".to_string()), ..Session::default() });
+    }
+}
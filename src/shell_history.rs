@@ -0,0 +1,376 @@
+//! Shell history files for `tt hist`.
+//!
+//! Each supported shell stores history differently:
+//!
+//! - **bash**: one command per line in `$HISTFILE` (default `~/.bash_history`),
+//!   preceded by a `#<unix time>` comment when `HISTTIMEFORMAT` is set
+//! - **zsh**: `$HISTFILE` (default `$ZDOTDIR/.zsh_history`), either plain lines
+//!   or the `EXTENDED_HISTORY` format `: <start>:<duration>;<command>`;
+//!   multi-line commands continue with a trailing `\` and non-ASCII bytes are
+//!   "metafied" (`0x83` followed by the byte xor `0x20`)
+//! - **fish**: a YAML-like `$XDG_DATA_HOME/fish/fish_history` with `- cmd:` and
+//!   `when:` keys and `\n`/`\\` escapes in commands
+//! - **PowerShell**: PSReadLine's `ConsoleHost_history.txt` (under
+//!   `%APPDATA%\Microsoft\Windows\PowerShell\PSReadLine` on Windows,
+//!   `$XDG_DATA_HOME/powershell/PSReadLine` elsewhere), one command per line
+//!   without timestamps; multi-line commands end every line but the last
+//!   with a backtick
+//!
+//! `$HISTFILE` is only honoured for the shell the user actually runs (from
+//! `$SHELL`, or PowerShell on Windows), since it is exported for that shell
+//! alone. Entries are returned oldest first. When merging several shells,
+//! entries without a timestamp take the timestamp of the entry before them so
+//! they stay in place.
+//!
+//! [`remove`] deletes entries by rewriting the file from its raw bytes, one
+//! chunk per entry (a bash command with its timestamp line, a zsh command with
+//! its continuation lines, a fish `- cmd:` record with its keys), so entries
+//! that are kept are written back exactly as the shell wrote them.
+
+use crate::{file_ops, platform};
+use std::{
+    env,
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Shells whose history can be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl Shell {
+    pub const ALL: [Shell; 4] = [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::Powershell];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::Powershell => "powershell",
+        }
+    }
+
+    /// The user's login shell from `$SHELL` (PowerShell on Windows), if it
+    /// is a supported one
+    pub fn detect() -> Option<Shell> {
+        let shell = platform::user_shell();
+        if platform::is_powershell(&shell) {
+            return Some(Shell::Powershell);
+        }
+        let name = Path::new(&shell).file_name()?.to_string_lossy().to_string();
+        Shell::ALL.into_iter().find(|candidate| candidate.name() == name)
+    }
+
+    /// Location of this shell's history file
+    pub fn history_file(self) -> Option<PathBuf> {
+        if Shell::detect() == Some(self) && matches!(self, Shell::Bash | Shell::Zsh) {
+            if let Some(file) = env::var_os("HISTFILE") {
+                return Some(PathBuf::from(file));
+            }
+        }
+        let home = platform::home_dir()?;
+        Some(match self {
+            Shell::Bash => home.join(".bash_history"),
+            Shell::Zsh => env::var_os("ZDOTDIR").map(PathBuf::from).unwrap_or(home).join(".zsh_history"),
+            Shell::Fish => platform::data_home()?.join("fish/fish_history"),
+            Shell::Powershell if cfg!(windows) => env::var_os("APPDATA")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join("AppData/Roaming"))
+                .join("Microsoft/Windows/PowerShell/PSReadLine/ConsoleHost_history.txt"),
+            Shell::Powershell => platform::data_home()?.join("powershell/PSReadLine/ConsoleHost_history.txt"),
+        })
+    }
+
+    /// Parse the contents of this shell's history file
+    pub fn parse(self, content: &[u8]) -> Vec<HistoryItem> {
+        match self {
+            Shell::Bash => parse_bash(&String::from_utf8_lossy(content), self),
+            Shell::Zsh => parse_zsh(&String::from_utf8_lossy(&unmetafy(content)), self),
+            Shell::Fish => parse_fish(&String::from_utf8_lossy(content), self),
+            Shell::Powershell => parse_powershell(&String::from_utf8_lossy(content), self),
+        }
+    }
+}
+
+/// A command read from a history file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryItem {
+    pub command: String,
+    /// When the command ran, in seconds since the Unix epoch
+    pub timestamp: Option<i64>,
+    pub shell: Shell,
+}
+
+/// Read the history of every shell in `shells`, merged oldest first
+pub fn load(shells: &[Shell]) -> Vec<HistoryItem> {
+    let mut items = Vec::new();
+    for &shell in shells {
+        let Some(content) = shell.history_file().and_then(|file| fs::read(file).ok()) else {
+            continue;
+        };
+        let mut last = 0;
+        items.extend(shell.parse(&content).into_iter().map(|item| {
+            last = item.timestamp.unwrap_or(last);
+            (last, item)
+        }));
+    }
+    // Stable, so each file keeps its own order for equal keys
+    items.sort_by_key(|(key, _)| *key);
+    items.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Remove the entries of `shell`'s history whose command satisfies `matches`,
+/// or only the most recent such entry when `last_only`. Returns how many
+/// entries were removed.
+pub fn remove(shell: Shell, matches: impl Fn(&str) -> bool, last_only: bool) -> io::Result<usize> {
+    let path = shell
+        .history_file()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory for the history file"))?;
+    let content = match fs::read(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let (kept, removed) = without_entries(shell, &content, matches, last_only);
+    if removed > 0 {
+        file_ops::replace_contents(&path, &kept)?;
+    }
+    Ok(removed)
+}
+
+/// `content` without the matching entries, and how many were dropped
+fn without_entries(shell: Shell, content: &[u8], matches: impl Fn(&str) -> bool, last_only: bool) -> (Vec<u8>, usize) {
+    let chunks = split_entries(shell, content);
+    let mut doomed: Vec<usize> = chunks
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| shell.parse(chunk).first().map(|item| matches(&item.command)).unwrap_or(false))
+        .map(|(index, _)| index)
+        .collect();
+    if last_only {
+        doomed = doomed.split_off(doomed.len().saturating_sub(1));
+    }
+    let kept = chunks
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !doomed.contains(index))
+        .flat_map(|(_, chunk)| chunk.iter().copied())
+        .collect();
+    (kept, doomed.len())
+}
+
+/// Split raw history contents into one chunk of lines per entry
+fn split_entries(shell: Shell, content: &[u8]) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    let mut previous: Option<&[u8]> = None;
+    for line in content.split_inclusive(|&byte| byte == b'\n') {
+        let text = line.strip_suffix(b"\n").unwrap_or(line);
+        let starts_entry = match shell {
+            // A timestamp line belongs to the command after it
+            Shell::Bash => !previous.map(is_bash_timestamp).unwrap_or(false),
+            Shell::Zsh => !previous.map(|line| line.ends_with(b"\\")).unwrap_or(false),
+            Shell::Fish => text.starts_with(b"- cmd: "),
+            Shell::Powershell => !previous.map(|line| line.ends_with(b"`")).unwrap_or(false),
+        };
+        if starts_entry && offset > start {
+            chunks.push(&content[start..offset]);
+            start = offset;
+        }
+        previous = Some(text);
+        offset += line.len();
+    }
+    if offset > start {
+        chunks.push(&content[start..offset]);
+    }
+    chunks
+}
+
+fn is_bash_timestamp(line: &[u8]) -> bool {
+    line.strip_prefix(b"#")
+        .map(|rest| !rest.is_empty() && rest.iter().all(u8::is_ascii_digit))
+        .unwrap_or(false)
+}
+
+fn parse_bash(content: &str, shell: Shell) -> Vec<HistoryItem> {
+    let mut items = Vec::new();
+    let mut timestamp = None;
+    for line in content.lines() {
+        if let Some(secs) = line.strip_prefix('#').and_then(|rest| rest.trim().parse().ok()) {
+            timestamp = Some(secs);
+        } else if !line.trim().is_empty() {
+            items.push(HistoryItem { command: line.to_string(), timestamp: timestamp.take(), shell });
+        }
+    }
+    items
+}
+
+/// Undo zsh's metafication of bytes that are special to it
+fn unmetafy(content: &[u8]) -> Vec<u8> {
+    const META: u8 = 0x83;
+    let mut bytes = Vec::with_capacity(content.len());
+    let mut iter = content.iter();
+    while let Some(&byte) = iter.next() {
+        if byte == META {
+            if let Some(&next) = iter.next() {
+                bytes.push(next ^ 0x20);
+            }
+        } else {
+            bytes.push(byte);
+        }
+    }
+    bytes
+}
+
+fn parse_zsh(content: &str, shell: Shell) -> Vec<HistoryItem> {
+    let mut items: Vec<HistoryItem> = Vec::new();
+    let mut continued = false;
+    for line in content.lines() {
+        if continued {
+            if let Some(item) = items.last_mut() {
+                item.command.push('\n');
+                item.command.push_str(line.strip_suffix('\\').unwrap_or(line));
+            }
+        } else {
+            let (timestamp, command) = match parse_zsh_extended(line) {
+                Some((secs, command)) => (Some(secs), command),
+                None => (None, line),
+            };
+            if !command.trim().is_empty() {
+                let command = command.strip_suffix('\\').unwrap_or(command).to_string();
+                items.push(HistoryItem { command, timestamp, shell });
+            }
+        }
+        continued = line.ends_with('\\');
+    }
+    items
+}
+
+/// Split `: <start>:<duration>;<command>` into its start time and command
+fn parse_zsh_extended(line: &str) -> Option<(i64, &str)> {
+    let rest = line.strip_prefix(": ")?;
+    let (meta, command) = rest.split_once(';')?;
+    let (start, _duration) = meta.split_once(':')?;
+    Some((start.trim().parse().ok()?, command))
+}
+
+fn parse_fish(content: &str, shell: Shell) -> Vec<HistoryItem> {
+    let mut items: Vec<HistoryItem> = Vec::new();
+    for line in content.lines() {
+        if let Some(command) = line.strip_prefix("- cmd: ") {
+            items.push(HistoryItem { command: unescape_fish(command), timestamp: None, shell });
+        } else if let Some(when) = line.trim_start().strip_prefix("when: ") {
+            if let Some(item) = items.last_mut() {
+                item.timestamp = when.trim().parse().ok();
+            }
+        }
+    }
+    items
+}
+
+fn parse_powershell(content: &str, shell: Shell) -> Vec<HistoryItem> {
+    let mut items: Vec<HistoryItem> = Vec::new();
+    let mut continued = false;
+    for line in content.lines() {
+        let text = line.strip_suffix('`').unwrap_or(line);
+        if continued {
+            if let Some(item) = items.last_mut() {
+                item.command.push('\n');
+                item.command.push_str(text);
+            }
+        } else if !line.trim().is_empty() {
+            items.push(HistoryItem { command: text.to_string(), timestamp: None, shell });
+        }
+        continued = line.ends_with('`');
+    }
+    items
+}
+
+/// Decode fish's `\n` and `\\` escapes
+fn unescape_fish(command: &str) -> String {
+    let mut result = String::with_capacity(command.len());
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bash() {
+        let items = parse_bash("ls\n#1700000000\ngit status\n\n", Shell::Bash);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].timestamp, None);
+        assert_eq!((items[1].command.as_str(), items[1].timestamp), ("git status", Some(1_700_000_000)));
+    }
+
+    #[test]
+    fn test_parse_zsh() {
+        let content = b": 1700000000:0;echo one\n: 1700000005:2;for f in *; do\\\n  echo $f\\\ndone\nplain\n";
+        let items = Shell::Zsh.parse(content);
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].timestamp, Some(1_700_000_000));
+        assert_eq!(items[1].command, "for f in *; do\n  echo $f\ndone");
+        assert_eq!((items[2].command.as_str(), items[2].timestamp), ("plain", None));
+
+        // "é" is 0xC3 0xA9; zsh stores 0xA9 as 0x83 0x89
+        let items = Shell::Zsh.parse(b": 1:0;echo caf\xC3\x83\x89\n");
+        assert_eq!(items[0].command, "echo café");
+    }
+
+    #[test]
+    fn test_without_entries() {
+        let bash = b"#1\nls\n#2\nexport TOKEN=abc\nls\n";
+        let (kept, removed) = without_entries(Shell::Bash, bash, |command| command.contains("TOKEN"), false);
+        assert_eq!((kept.as_slice(), removed), (&b"#1\nls\nls\n"[..], 1));
+        let (kept, removed) = without_entries(Shell::Bash, bash, |command| command == "ls", true);
+        assert_eq!((kept.as_slice(), removed), (&b"#1\nls\n#2\nexport TOKEN=abc\n"[..], 1));
+
+        let zsh = b": 1:0;for f in *; do\\\n  rm $f\\\ndone\n: 2:0;ls\n";
+        let (kept, _) = without_entries(Shell::Zsh, zsh, |command| command.starts_with("for"), false);
+        assert_eq!(kept, b": 2:0;ls\n");
+
+        let fish = b"- cmd: sl\n  when: 1\n- cmd: ls\n  when: 2\n  paths:\n    - a\n";
+        let (kept, removed) = without_entries(Shell::Fish, fish, |command| command == "ls", false);
+        assert_eq!((kept.as_slice(), removed), (&b"- cmd: sl\n  when: 1\n"[..], 1));
+    }
+
+    #[test]
+    fn test_parse_fish() {
+        let content = "- cmd: echo a\\\\b\\nc\n  when: 1700000000\n  paths:\n    - b\n- cmd: ls\n  when: 1700000009\n";
+        let items = parse_fish(content, Shell::Fish);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].command, "echo a\\b\nc");
+        assert_eq!(items[1].timestamp, Some(1_700_000_009));
+    }
+
+    #[test]
+    fn test_parse_powershell() {
+        let content = b"Get-ChildItem\nforeach ($f in ls) {`\n  $f.Name`\n}\ncd ~\n";
+        let items = Shell::Powershell.parse(content);
+        let commands: Vec<&str> = items.iter().map(|item| item.command.as_str()).collect();
+        assert_eq!(commands, vec!["Get-ChildItem", "foreach ($f in ls) {\n  $f.Name\n}", "cd ~"]);
+
+        let (kept, removed) = without_entries(Shell::Powershell, content, |command| command.starts_with("foreach"), false);
+        assert_eq!((kept.as_slice(), removed), (&b"Get-ChildItem\ncd ~\n"[..], 1));
+    }
+}
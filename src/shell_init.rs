@@ -0,0 +1,290 @@
+//! Shell integration snippets printed by `tt init`.
+//!
+//! A child process cannot change its parent shell's working directory, so
+//! cd-on-exit needs a small shell function: it runs `tt dir --choose-dir`
+//! (which draws on stderr and prints the final directory on stdout) and `cd`s
+//! into the result. `ttz` does the same with `tt z`. Install them with:
+//!
+//! ```bash
+//! eval "$(tt init bash)"    # ~/.bashrc
+//! eval "$(tt init zsh)"     # ~/.zshrc
+//! tt init fish | source     # ~/.config/fish/config.fish
+//! ```
+//!
+//! Then run `ttd [PATH]`, browse, and quit with `q` to land in that directory,
+//! or `ttz [WORDS]` and press `Enter` to jump to a frequently used one.
+//! `tte` opens `tt env` and applies the variables you set or unset there to
+//! the shell, since only the shell itself can change its environment.
+//!
+//! The snippets also bind `Ctrl-R` to `tt hist --print-selection`, seeded with
+//! the current command line: the chosen command replaces the line without
+//! being run, like fzf's history widget. Each binding first flushes the
+//! session's history to the history file so recent commands are included.
+//!
+//! Finally a prompt hook logs every finished command with its directory, exit
+//! status and duration through `tt hist --record` (see `command_log`). bash
+//! and zsh time commands with `$EPOCHREALTIME` (bash 5+, zsh's `zsh/datetime`;
+//! older bash falls back to whole seconds), fish with `$CMD_DURATION`. The
+//! record runs in the background so the prompt never waits for it, and is
+//! skipped in the shell itself while `$TT_NO_RECORD` is set. Files opened by
+//! editor commands are added to `tt recent`'s list along the way (see
+//! `mru`).
+//!
+//! Without zoxide, `tt z` keeps its own store (see `frecency`), so a
+//! directory hook records each new working directory with `tt z --add`:
+//! bash checks `$PWD` before each prompt, zsh uses a `chpwd` hook and fish
+//! `--on-variable PWD`. With zoxide its own hook does this instead.
+//!
+//! bash has a single DEBUG trap, which the hook needs to see commands start.
+//! With bash-preexec loaded first, the hook joins its `preexec_functions`
+//! and `precmd_functions` instead; otherwise a DEBUG trap set earlier keeps
+//! running after the hook's.
+//!
+//! The script ends with tab completions for tt (see
+//! [`CompletionShell::script`]), so the one `eval` line is the whole
+//! installation. zsh's need `compinit`, so they are left out when it has not
+//! been run.
+
+use crate::cli::{CompletionShell, InitShell};
+use crate::frecency::Source;
+
+const POSIX_FUNCTION: &str = r#"# tt shell integration: `ttd` cds to the directory you quit tt dir in
+ttd() {
+    local dir
+    dir="$(command tt dir --choose-dir --path "${1:-.}")" || return
+    [ -n "$dir" ] && [ "$dir" != "$PWD" ] && cd -- "$dir"
+}
+
+# `ttz` cds to a frecency-ranked directory picked with tt z
+ttz() {
+    local dir
+    dir="$(command tt z "$@")" || return
+    [ -n "$dir" ] && cd -- "$dir"
+}
+
+# `tte` applies the variables you set or unset in tt env to this shell
+tte() {
+    local statements
+    statements="$(command tt env --apply bash "$@")" || return
+    eval "$statements"
+}
+"#;
+
+const BASH_BINDING: &str = r#"
+# Ctrl-R: pick a command with tt hist and put it on the command line
+__tt_hist() {
+    history -a
+    local selected
+    selected="$(command tt hist --shell bash --print-selection --query "$READLINE_LINE")" || return
+    if [ -n "$selected" ]; then
+        READLINE_LINE="$selected"
+        READLINE_POINT=${#selected}
+    fi
+}
+bind -m emacs-standard -x '"\C-r": __tt_hist'
+bind -m vi-insert -x '"\C-r": __tt_hist'
+
+# Log each command's directory, exit status and duration for tt hist
+__tt_now() {
+    if [ -n "$EPOCHREALTIME" ]; then
+        __tt_ms=$(( 10#${EPOCHREALTIME//[.,]/} / 1000 ))
+    else
+        __tt_ms=$(( SECONDS * 1000 ))
+    fi
+}
+__tt_start_clock() {
+    __tt_dir=$PWD
+    __tt_now
+    __tt_start=$__tt_ms
+}
+__tt_preexec() {
+    # Only the first command after a prompt starts the clock
+    if [ -n "$__tt_at_prompt" ]; then
+        case "$BASH_COMMAND" in
+            __tt_prompt_start|__tt_hist) ;;
+            *)
+                __tt_at_prompt=
+                __tt_start_clock
+                ;;
+        esac
+    fi
+    return 0
+}
+__tt_prompt_start() {
+    __tt_status=$?
+    __tt_at_prompt=
+}
+__tt_precmd() {
+    if [ -n "$__tt_start" ] && [ -z "${TT_NO_RECORD:-}" ]; then
+        local command
+        command="$(HISTTIMEFORMAT= builtin history 1)"
+        command="${command#*[0-9]  }"
+        __tt_now
+        (command tt hist --shell bash --record "$command" --status "$__tt_status" --duration $(( __tt_ms - __tt_start )) --cwd "$__tt_dir" >/dev/null 2>&1 &)
+    fi
+    __tt_start=
+    __tt_at_prompt=1
+}
+if [ -n "${bash_preexec_imported:-}${__bp_imported:-}" ]; then
+    # bash-preexec owns the DEBUG trap and PROMPT_COMMAND; join its hooks
+    __tt_bp_precmd() {
+        __tt_status=$?
+        __tt_precmd
+    }
+    preexec_functions+=(__tt_start_clock)
+    precmd_functions+=(__tt_bp_precmd)
+else
+    # Keep a DEBUG trap set before this one running after it, with $? as
+    # it found it
+    __tt_old_debug=
+    __tt_trap_command() { __tt_old_debug=$3; }
+    eval "__tt_trap_command $(trap -p DEBUG)"
+    case "$__tt_old_debug" in
+        *__tt_preexec*) __tt_old_debug= ;;
+    esac
+    if [ -n "$__tt_old_debug" ]; then
+        __tt_set_status() { return "$1"; }
+        trap '__tt_debug_status=$?; __tt_preexec; __tt_set_status "$__tt_debug_status"; eval "$__tt_old_debug"' DEBUG
+    else
+        trap '__tt_preexec' DEBUG
+    fi
+    PROMPT_COMMAND="__tt_prompt_start${PROMPT_COMMAND:+;$PROMPT_COMMAND};__tt_precmd"
+fi
+"#;
+
+const ZSH_BINDING: &str = r#"
+# Ctrl-R: pick a command with tt hist and put it on the command line
+__tt_hist() {
+    fc -AI 2>/dev/null
+    local selected
+    selected="$(command tt hist --shell zsh --print-selection --query "$BUFFER" </dev/tty)"
+    if [ -n "$selected" ]; then
+        BUFFER="$selected"
+        CURSOR=${#BUFFER}
+    fi
+    zle reset-prompt
+}
+zle -N __tt_hist
+bindkey '^R' __tt_hist
+
+# Log each command's directory, exit status and duration for tt hist
+zmodload zsh/datetime 2>/dev/null
+autoload -Uz add-zsh-hook
+__tt_preexec() {
+    __tt_command=$1
+    __tt_dir=$PWD
+    __tt_start=${EPOCHREALTIME/./}
+}
+__tt_precmd() {
+    local exit_code=$?
+    if [ -n "$__tt_command" ] && [ -z "${TT_NO_RECORD:-}" ]; then
+        local duration=$(( (${EPOCHREALTIME/./} - __tt_start) / 1000 ))
+        (command tt hist --shell zsh --record "$__tt_command" --status $exit_code --duration $duration --cwd "$__tt_dir" >/dev/null 2>&1 &)
+    fi
+    __tt_command=
+}
+add-zsh-hook preexec __tt_preexec
+add-zsh-hook precmd __tt_precmd
+"#;
+
+const BASH_Z_HOOK: &str = r#"
+# Record the directories cd'd into for tt z
+__tt_z_dir=$PWD
+__tt_z_precmd() {
+    if [ "$PWD" != "$__tt_z_dir" ]; then
+        __tt_z_dir=$PWD
+        (command tt z --add "$PWD" >/dev/null 2>&1 &)
+    fi
+}
+if [ -n "${bash_preexec_imported:-}${__bp_imported:-}" ]; then
+    precmd_functions+=(__tt_z_precmd)
+else
+    PROMPT_COMMAND="${PROMPT_COMMAND:+$PROMPT_COMMAND;}__tt_z_precmd"
+fi
+"#;
+
+const ZSH_Z_HOOK: &str = r#"
+# Record the directories cd'd into for tt z
+__tt_z_chpwd() {
+    (command tt z --add "$PWD" >/dev/null 2>&1 &)
+}
+add-zsh-hook chpwd __tt_z_chpwd
+"#;
+
+const FISH_FUNCTION: &str = r#"# tt shell integration: `ttd` cds to the directory you quit tt dir in
+function ttd
+    set -l start .
+    if set -q argv[1]
+        set start $argv[1]
+    end
+    set -l dir (command tt dir --choose-dir --path $start); or return
+    if test -n "$dir"; and test "$dir" != "$PWD"
+        cd -- $dir
+    end
+end
+
+# `ttz` cds to a frecency-ranked directory picked with tt z
+function ttz
+    set -l dir (command tt z $argv); or return
+    if test -n "$dir"
+        cd -- $dir
+    end
+end
+
+# `tte` applies the variables you set or unset in tt env to this shell
+function tte
+    command tt env --apply fish $argv | source
+end
+
+# Ctrl-R: pick a command with tt hist and put it on the command line
+function __tt_hist
+    history save
+    set -l query (commandline)
+    set -l selected (command tt hist --shell fish --print-selection --query "$query" | string collect)
+    if test -n "$selected"
+        commandline --replace -- $selected
+    end
+    commandline -f repaint
+end
+bind \cr __tt_hist
+if bind -M insert >/dev/null 2>&1
+    bind -M insert \cr __tt_hist
+end
+
+# Log each command's directory, exit status and duration for tt hist
+function __tt_preexec --on-event fish_preexec
+    set -g __tt_dir $PWD
+end
+function __tt_record --on-event fish_postexec
+    set -l exit_code $status
+    test -n "$argv[1]"; and test -z "$TT_NO_RECORD"; or return
+    command tt hist --shell fish --record $argv[1] --status $exit_code --duration $CMD_DURATION --cwd $__tt_dir >/dev/null 2>&1 &
+    disown 2>/dev/null
+end
+"#;
+
+const FISH_Z_HOOK: &str = r#"
+# Record the directories cd'd into for tt z
+function __tt_z_add --on-variable PWD
+    command tt z --add "$PWD" >/dev/null 2>&1 &
+    disown 2>/dev/null
+end
+"#;
+
+/// Shell integration source for `shell`, recording directory changes when
+/// `tt z` reads from `source`'s own store
+pub fn script(shell: InitShell, source: Source) -> String {
+    let (mut integration, z_hook, completion) = match shell {
+        InitShell::Bash => (format!("{}{}", POSIX_FUNCTION, BASH_BINDING), BASH_Z_HOOK, CompletionShell::Bash),
+        InitShell::Zsh => (format!("{}{}", POSIX_FUNCTION, ZSH_BINDING), ZSH_Z_HOOK, CompletionShell::Zsh),
+        InitShell::Fish => (FISH_FUNCTION.to_string(), FISH_Z_HOOK, CompletionShell::Fish),
+    };
+    if source == Source::Builtin {
+        integration.push_str(z_hook);
+    }
+    let completion = match completion {
+        CompletionShell::Zsh => format!("if (( $+functions[compdef] )); then\n{}fi\n", completion.script()),
+        _ => completion.script(),
+    };
+    format!("{}\n# Tab completion for tt\n{}", integration, completion)
+}
@@ -0,0 +1,128 @@
+//! What a command name means to the user's shell.
+//!
+//! A name typed at the prompt may be an alias, a shell function, a builtin
+//! or a keyword rather than a program, and then its man page (if any) does
+//! not describe what runs: `printf(1)` documents coreutils, not the bash
+//! builtin, and `cd` has no page at all. The shell's own `type` builtin
+//! knows, so `$SHELL -i -c "type -- NAME"` is run with the user's startup
+//! files loaded (that is where aliases and functions come from). Startup
+//! files that set up language version managers can take seconds, so the
+//! shell gets [`SHELL_TIMEOUT`] to answer.
+//!
+//! `type` wording differs between bash, zsh and fish; [`parse`] knows all
+//! three. Help for builtins and keywords comes from bash's `help -m`, which
+//! prints them laid out like a man page.
+
+use crate::help_text;
+use std::{env, path::Path, process::Command, time::Duration};
+
+/// How long an interactive shell may take to start and answer
+pub const SHELL_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// What kind of command a name is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Alias,
+    Function,
+    Builtin,
+    Keyword,
+    /// A program on disk
+    File,
+}
+
+/// A name as the shell sees it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellType {
+    pub kind: Kind,
+    /// What `type` said, such as ``ll is aliased to `ls -l'``
+    pub description: String,
+}
+
+impl ShellType {
+    /// Whether the shell runs something other than the program of that name
+    pub fn shadows_program(&self) -> bool {
+        self.kind != Kind::File
+    }
+}
+
+/// Whether `name` can be passed to a shell without quoting trouble
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name.chars().all(|c| c.is_alphanumeric() || "._+-:@[".contains(c))
+}
+
+/// Ask the user's shell what `name` is; `None` if it is not a command
+pub fn lookup(name: &str) -> Option<ShellType> {
+    if !is_valid_name(name) {
+        return None;
+    }
+    let shell = env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    let mut command = Command::new(&shell);
+    command.args(["-i", "-c", &format!("type -- '{}'", name)]);
+    let output = help_text::output_with_timeout(command, &shell, SHELL_TIMEOUT).ok()?;
+    parse(name, &output)
+}
+
+/// Read the output of `type NAME` from bash, zsh, sh or fish
+pub fn parse(name: &str, output: &str) -> Option<ShellType> {
+    output.lines().find_map(|line| {
+        let rest = line.strip_prefix(name)?.strip_prefix(" is ")?;
+        let kind = if rest.starts_with("aliased to") || rest.starts_with("an alias for") {
+            Kind::Alias
+        } else if rest.contains("function") {
+            Kind::Function
+        } else if rest.ends_with("builtin") {
+            Kind::Builtin
+        } else if rest == "a shell keyword" || rest == "a reserved word" {
+            Kind::Keyword
+        } else if rest.starts_with('/') || rest.starts_with("hashed (") {
+            Kind::File
+        } else {
+            return None;
+        };
+        Some(ShellType { kind, description: line.trim_end().to_string() })
+    })
+}
+
+/// Help for the bash builtin or keyword `name`, laid out like a man page
+pub fn builtin_help(name: &str) -> Result<String, String> {
+    if !is_valid_name(name) {
+        return Err(format!("No help for {}", name));
+    }
+    let bash = ["/bin/bash", "/usr/bin/bash", "/usr/local/bin/bash", "/opt/homebrew/bin/bash"]
+        .into_iter()
+        .find(|path| Path::new(path).exists())
+        .unwrap_or("bash");
+    let mut command = Command::new(bash);
+    command.args(["-c", &format!("help -m '{}'", name)]);
+    let output = help_text::output_with_timeout(command, "bash help", help_text::TIMEOUT)?;
+    if output.contains("no help topics match") {
+        return Err(format!("bash has no help for {}; it may be specific to your shell", name));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let kind = |name: &str, output: &str| parse(name, output).map(|shell_type| shell_type.kind);
+        assert_eq!(kind("ll", "bash: no job control in this shell\nll is aliased to `ls -l'\n"), Some(Kind::Alias));
+        assert_eq!(kind("ll", "ll is an alias for ls -l"), Some(Kind::Alias));
+        assert_eq!(kind("f", "f is a function\nf () \n{\n    :\n}"), Some(Kind::Function));
+        assert_eq!(kind("f", "f is a shell function from /home/me/.zshrc"), Some(Kind::Function));
+        assert_eq!(kind("cd", "cd is a shell builtin"), Some(Kind::Builtin));
+        assert_eq!(kind("echo", "echo is a builtin"), Some(Kind::Builtin));
+        assert_eq!(kind("if", "if is a reserved word"), Some(Kind::Keyword));
+        assert_eq!(kind("ls", "ls is hashed (/usr/bin/ls)"), Some(Kind::File));
+        assert_eq!(kind("ls", "ls is /usr/bin/ls"), Some(Kind::File));
+        assert_eq!(kind("nope", "bash: type: nope: not found"), None);
+        assert_eq!(
+            parse("ll", "ll is aliased to `ls -l'").map(|shell_type| shell_type.description),
+            Some("ll is aliased to `ls -l'".to_string())
+        );
+    }
+}
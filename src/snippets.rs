@@ -0,0 +1,172 @@
+//! Saved command snippets for `tt hist` and `tt snip`.
+//!
+//! Snippets live in `$XDG_CONFIG_HOME/terminal-tools/snippets.toml` (default
+//! `~/.config/terminal-tools/snippets.toml`) as an array of tables, so the file can be
+//! edited and shared by hand:
+//!
+//! ```toml
+//! [[snippet]]
+//! name = "Tail a service log"
+//! description = "Follows the journal of one unit"
+//! command = "journalctl -fu {unit:nginx}"
+//! ```
+//!
+//! Only `command` is required. Commands may contain `{placeholders}`, which
+//! `tt snip` prompts for before running or printing the command; `{name:value}`
+//! gives a default. A bare `{}` is left alone so `find -exec ... {} \;` style
+//! commands work as-is.
+
+use crate::platform;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+
+/// A saved command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub command: String,
+}
+
+impl Snippet {
+    pub fn new(command: impl Into<String>) -> Self {
+        Snippet { name: None, description: None, command: command.into() }
+    }
+
+    /// Name if set, otherwise the command itself
+    pub fn title(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.command)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SnippetFile {
+    #[serde(default, rename = "snippet")]
+    snippets: Vec<Snippet>,
+}
+
+fn snippets_file() -> Option<PathBuf> {
+    platform::config_dir().map(|dir| dir.join("snippets.toml"))
+}
+
+/// Load saved snippets; a missing file yields an empty list
+pub fn load() -> io::Result<Vec<Snippet>> {
+    let Some(content) = snippets_file().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Ok(Vec::new());
+    };
+    toml::from_str::<SnippetFile>(&content)
+        .map(|file| file.snippets)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("snippets.toml: {}", e)))
+}
+
+/// Replace the saved snippets with `snippets`
+pub fn save(snippets: &[Snippet]) -> io::Result<()> {
+    let path = snippets_file()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory for snippets"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = SnippetFile { snippets: snippets.to_vec() };
+    let content = toml::to_string(&file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    fs::write(path, content)
+}
+
+/// A `{name}` or `{name:default}` placeholder in a command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    pub name: String,
+    pub default: String,
+}
+
+/// Placeholders in `command`, each name once, in order of appearance
+pub fn placeholders(command: &str) -> Vec<Placeholder> {
+    let mut found: Vec<Placeholder> = Vec::new();
+    for (_, inner) in placeholder_spans(command) {
+        let (name, default) = inner.split_once(':').unwrap_or((inner, ""));
+        if !found.iter().any(|placeholder| placeholder.name == name) {
+            found.push(Placeholder { name: name.to_string(), default: default.to_string() });
+        }
+    }
+    found
+}
+
+/// Replace every placeholder in `command` with its value from `values`
+pub fn fill(command: &str, values: &[(String, String)]) -> String {
+    let mut result = String::with_capacity(command.len());
+    let mut last = 0;
+    for (start, inner) in placeholder_spans(command) {
+        let name = inner.split_once(':').map(|(name, _)| name).unwrap_or(inner);
+        let Some((_, value)) = values.iter().find(|(key, _)| key == name) else {
+            continue;
+        };
+        result.push_str(&command[last..start]);
+        result.push_str(value);
+        last = start + inner.len() + 2;
+    }
+    result.push_str(&command[last..]);
+    result
+}
+
+/// Byte offset and inner text of each `{...}` whose name is a word
+fn placeholder_spans(command: &str) -> Vec<(usize, &str)> {
+    let mut spans = Vec::new();
+    let mut rest = command;
+    let mut offset = 0;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        let inner = &rest[open + 1..open + close];
+        let name = inner.split_once(':').map(|(name, _)| name).unwrap_or(inner);
+        let is_word = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+        if is_word && !inner.contains('{') {
+            spans.push((offset + open, inner));
+            rest = &rest[open + close + 1..];
+            offset += open + close + 1;
+        } else {
+            rest = &rest[open + 1..];
+            offset += open + 1;
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_and_fill() {
+        let command = "ssh {host} 'tail -n {lines:50} {file}' && find . -exec rm {} \\; # {host}";
+        let found = placeholders(command);
+        let names: Vec<(&str, &str)> = found.iter().map(|p| (p.name.as_str(), p.default.as_str())).collect();
+        assert_eq!(names, vec![("host", ""), ("lines", "50"), ("file", "")]);
+
+        let values = vec![
+            ("host".to_string(), "web1".to_string()),
+            ("lines".to_string(), "10".to_string()),
+            ("file".to_string(), "app.log".to_string()),
+        ];
+        assert_eq!(
+            fill(command, &values),
+            "ssh web1 'tail -n 10 app.log' && find . -exec rm {} \\; # web1"
+        );
+        assert!(placeholders("awk '{print $1}'").is_empty());
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let file = SnippetFile {
+            snippets: vec![
+                Snippet { name: Some("Logs".to_string()), description: None, command: "journalctl -fu \"{unit}\"".to_string() },
+                Snippet::new("ls -la"),
+            ],
+        };
+        let content = toml::to_string(&file).unwrap();
+        assert!(content.contains("[[snippet]]"));
+        let parsed: SnippetFile = toml::from_str(&content).unwrap();
+        assert_eq!(parsed.snippets, file.snippets);
+    }
+}
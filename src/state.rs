@@ -0,0 +1,78 @@
+//! Small persisted UI preferences shared across sessions.
+//!
+//! Preferences such as "show hidden files" are stored as `key=value` lines in
+//! `$XDG_STATE_HOME/terminal-tools/state` (default
+//! `~/.local/state/terminal-tools/state`, see [`platform::state_dir`]). Keys are
+//! namespaced by tool, e.g. `explore.hidden`. Reads never fail: a missing or
+//! unreadable file simply yields no value, and write errors are reported to
+//! the caller so tools can surface them in their status bar.
+//!
+//! Data that can be rebuilt, such as downloaded tldr pages, lives under
+//! [`platform::cache_dir`] (`~/.cache/terminal-tools`) instead.
+
+use crate::platform;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io,
+    path::PathBuf,
+};
+
+fn state_file() -> Option<PathBuf> {
+    platform::state_dir().map(|dir| dir.join("state"))
+}
+
+fn parse(content: &str) -> BTreeMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+fn load() -> BTreeMap<String, String> {
+    state_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|content| parse(&content))
+        .unwrap_or_default()
+}
+
+/// Read a persisted value
+pub fn get(key: &str) -> Option<String> {
+    load().remove(key)
+}
+
+/// Read a persisted boolean, falling back to `default`
+pub fn get_bool(key: &str, default: bool) -> bool {
+    get(key).map(|value| value == "true").unwrap_or(default)
+}
+
+/// Persist a value, replacing any previous one
+pub fn set(key: &str, value: &str) -> io::Result<()> {
+    let path = state_file()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home directory for state file"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut values = load();
+    values.insert(key.to_string(), value.to_string());
+    let content: String = values
+        .iter()
+        .map(|(key, value)| format!("{}={}\n", key, value))
+        .collect();
+    fs::write(path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let values = parse("explore.hidden=true\nbroken line\nexplore.sort = size\n");
+        assert_eq!(values.get("explore.hidden").map(String::as_str), Some("true"));
+        assert_eq!(values.get("explore.sort").map(String::as_str), Some("size"));
+        assert_eq!(values.len(), 2);
+    }
+}
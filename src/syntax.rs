@@ -0,0 +1,421 @@
+//! Keyword highlighting for code previews.
+//!
+//! This is no parser: each [`Language`] is a list of keywords with its
+//! comment and string delimiters, and [`Highlighter`] colors a line at a
+//! time with them. That is enough to make code readable in a preview pane
+//! while costing nothing to build, and a [`Highlighter`] carries block
+//! comments over from one line to the next.
+
+use crate::tui_common::colors;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+use std::path::Path;
+
+/// How to highlight one language
+#[derive(Debug)]
+pub struct Language {
+    pub name: &'static str,
+    /// File extensions, lowercase
+    extensions: &'static [&'static str],
+    /// File names without an extension, such as `Makefile`
+    file_names: &'static [&'static str],
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Characters opening a string, each closed by the same character
+    quotes: &'static [char],
+    /// Whether `'` only quotes a single character, as in `'a'`, so that
+    /// Rust lifetimes and the like stay plain
+    char_literals: bool,
+}
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else", "enum", "extern",
+    "float", "for", "goto", "if", "inline", "int", "long", "register", "return", "short", "signed", "sizeof",
+    "static", "struct", "switch", "typedef", "union", "unsigned", "void", "volatile", "while", "NULL", "true",
+    "false", "bool",
+];
+
+const CPP_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "catch", "char", "class", "const", "constexpr", "continue", "default",
+    "delete", "do", "double", "else", "enum", "explicit", "extern", "false", "float", "for", "friend", "if",
+    "inline", "int", "long", "namespace", "new", "noexcept", "nullptr", "operator", "override", "private",
+    "protected", "public", "return", "short", "signed", "sizeof", "static", "struct", "switch", "template",
+    "this", "throw", "true", "try", "typedef", "typename", "union", "unsigned", "using", "virtual", "void",
+    "while",
+];
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        name: "Rust",
+        extensions: &["rs"],
+        file_names: &[],
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false",
+            "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+            "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        char_literals: true,
+    },
+    Language {
+        name: "C",
+        extensions: &["c", "h"],
+        file_names: &[],
+        keywords: C_KEYWORDS,
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        char_literals: true,
+    },
+    Language {
+        name: "C++",
+        extensions: &["cpp", "cc", "cxx", "hpp", "hh", "hxx"],
+        file_names: &[],
+        keywords: CPP_KEYWORDS,
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        char_literals: true,
+    },
+    Language {
+        name: "C#",
+        extensions: &["cs"],
+        file_names: &[],
+        keywords: &[
+            "abstract", "as", "async", "await", "base", "bool", "break", "case", "catch", "class", "const",
+            "continue", "default", "do", "else", "enum", "false", "finally", "for", "foreach", "if", "in", "int",
+            "interface", "internal", "is", "namespace", "new", "null", "override", "private", "protected", "public",
+            "readonly", "return", "sealed", "static", "string", "struct", "switch", "this", "throw", "true", "try",
+            "using", "var", "virtual", "void", "while",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        char_literals: true,
+    },
+    Language {
+        name: "Go",
+        extensions: &["go"],
+        file_names: &[],
+        keywords: &[
+            "break", "case", "chan", "const", "continue", "default", "defer", "else", "fallthrough", "false", "for",
+            "func", "go", "goto", "if", "import", "interface", "map", "nil", "package", "range", "return", "select",
+            "struct", "switch", "true", "type", "var",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+        char_literals: true,
+    },
+    Language {
+        name: "Java",
+        extensions: &["java", "kt", "kts", "scala"],
+        file_names: &[],
+        keywords: &[
+            "abstract", "boolean", "break", "case", "catch", "class", "continue", "default", "do", "else", "enum",
+            "extends", "false", "final", "finally", "for", "fun", "if", "implements", "import", "instanceof",
+            "interface", "new", "null", "object", "override", "package", "private", "protected", "public", "return",
+            "static", "super", "switch", "this", "throw", "throws", "true", "try", "val", "var", "void", "when",
+            "while",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        char_literals: true,
+    },
+    Language {
+        name: "JavaScript",
+        extensions: &["js", "mjs", "cjs", "jsx", "ts", "tsx"],
+        file_names: &[],
+        keywords: &[
+            "async", "await", "break", "case", "catch", "class", "const", "continue", "default", "delete", "do",
+            "else", "enum", "export", "extends", "false", "finally", "for", "from", "function", "if", "import", "in",
+            "instanceof", "interface", "let", "new", "null", "of", "return", "static", "super", "switch", "this",
+            "throw", "true", "try", "type", "typeof", "undefined", "var", "void", "while", "yield",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+        char_literals: false,
+    },
+    Language {
+        name: "Python",
+        extensions: &["py", "pyi"],
+        file_names: &[],
+        keywords: &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else",
+            "except", "False", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "None",
+            "nonlocal", "not", "or", "pass", "raise", "return", "self", "True", "try", "while", "with", "yield",
+        ],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        char_literals: false,
+    },
+    Language {
+        name: "Ruby",
+        extensions: &["rb", "rake", "gemspec"],
+        file_names: &["Gemfile", "Rakefile"],
+        keywords: &[
+            "alias", "and", "begin", "break", "case", "class", "def", "do", "else", "elsif", "end", "ensure",
+            "false", "for", "if", "in", "module", "next", "nil", "not", "or", "redo", "rescue", "retry", "return",
+            "self", "super", "then", "true", "unless", "until", "when", "while", "yield",
+        ],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        char_literals: false,
+    },
+    Language {
+        name: "Shell",
+        extensions: &["sh", "bash", "zsh", "fish"],
+        file_names: &[".bashrc", ".zshrc", ".profile", ".bash_profile"],
+        keywords: &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in", "local",
+            "return", "then", "until", "while", "end", "set",
+        ],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        char_literals: false,
+    },
+    Language {
+        name: "Lua",
+        extensions: &["lua"],
+        file_names: &[],
+        keywords: &[
+            "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in", "local",
+            "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+        ],
+        line_comments: &["--"],
+        block_comment: Some(("--[[", "]]")),
+        quotes: &['"', '\''],
+        char_literals: false,
+    },
+    Language {
+        name: "PHP",
+        extensions: &["php"],
+        file_names: &[],
+        keywords: &[
+            "abstract", "array", "as", "break", "case", "catch", "class", "const", "continue", "default", "do",
+            "echo", "else", "elseif", "extends", "false", "final", "finally", "for", "foreach", "function", "if",
+            "implements", "namespace", "new", "null", "private", "protected", "public", "return", "static",
+            "switch", "throw", "true", "try", "use", "while",
+        ],
+        line_comments: &["//", "#"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\''],
+        char_literals: false,
+    },
+    Language {
+        name: "Swift",
+        extensions: &["swift"],
+        file_names: &[],
+        keywords: &[
+            "as", "break", "case", "class", "continue", "default", "defer", "do", "else", "enum", "extension",
+            "false", "for", "func", "guard", "if", "import", "in", "init", "let", "nil", "private", "protocol",
+            "public", "return", "self", "static", "struct", "switch", "throw", "true", "try", "var", "where",
+            "while",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"'],
+        char_literals: false,
+    },
+    Language {
+        name: "SQL",
+        extensions: &["sql"],
+        file_names: &[],
+        keywords: &[
+            "and", "as", "by", "create", "delete", "from", "group", "having", "in", "insert", "into", "join", "key",
+            "left", "limit", "not", "null", "on", "or", "order", "primary", "select", "set", "table", "update",
+            "values", "where", "AND", "AS", "BY", "CREATE", "DELETE", "FROM", "GROUP", "HAVING", "IN", "INSERT",
+            "INTO", "JOIN", "KEY", "LEFT", "LIMIT", "NOT", "NULL", "ON", "OR", "ORDER", "PRIMARY", "SELECT", "SET",
+            "TABLE", "UPDATE", "VALUES", "WHERE",
+        ],
+        line_comments: &["--"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['\''],
+        char_literals: false,
+    },
+    Language {
+        name: "Makefile",
+        extensions: &["mk"],
+        file_names: &["Makefile", "makefile", "GNUmakefile"],
+        keywords: &["ifeq", "ifneq", "ifdef", "ifndef", "else", "endif", "include", "define", "endef", "export"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &[],
+        char_literals: false,
+    },
+];
+
+/// The language of `path`, by its extension or file name
+pub fn language(path: &Path) -> Option<&'static Language> {
+    let name = path.file_name()?.to_string_lossy();
+    let extension = path.extension().map(|ext| ext.to_string_lossy().to_lowercase());
+    LANGUAGES.iter().find(|language| {
+        language.file_names.contains(&name.as_ref())
+            || extension.as_deref().is_some_and(|ext| language.extensions.contains(&ext))
+    })
+}
+
+/// The language a Markdown code fence names, such as `rust` or `py`
+pub fn language_named(name: &str) -> Option<&'static Language> {
+    let name = name.to_lowercase();
+    LANGUAGES.iter().find(|language| {
+        language.name.to_lowercase() == name || language.extensions.contains(&name.as_str())
+    })
+}
+
+fn comment_style() -> Style {
+    Style::default().fg(colors::muted()).add_modifier(Modifier::ITALIC)
+}
+
+/// Colors lines of one language in order, remembering open block comments
+pub struct Highlighter {
+    language: &'static Language,
+    in_comment: bool,
+}
+
+impl Highlighter {
+    pub fn new(language: &'static Language) -> Self {
+        Highlighter { language, in_comment: false }
+    }
+
+    pub fn line(&mut self, line: &str) -> Line<'static> {
+        let language = self.language;
+        let mut spans = Vec::new();
+        let mut plain = String::new();
+        let mut rest = line;
+
+        while !rest.is_empty() {
+            let (token, style) = if self.in_comment {
+                let (_, close) = language.block_comment.unwrap_or(("", ""));
+                match rest.find(close) {
+                    Some(end) => {
+                        self.in_comment = false;
+                        (&rest[..end + close.len()], comment_style())
+                    }
+                    None => (rest, comment_style()),
+                }
+            } else if let Some((open, _)) = language.block_comment.filter(|(open, _)| rest.starts_with(open)) {
+                self.in_comment = true;
+                (&rest[..open.len()], comment_style())
+            } else if language.line_comments.iter().any(|marker| rest.starts_with(marker)) {
+                (rest, comment_style())
+            } else {
+                let c = rest.chars().next().unwrap_or_default();
+                let word_start = !plain.ends_with(|p: char| p.is_alphanumeric() || p == '_');
+                match self.string_end(rest, c) {
+                    Some(end) => (&rest[..end], Style::default().fg(colors::success())),
+                    None if c.is_ascii_digit() && word_start => {
+                        (&rest[..word_len(rest)], Style::default().fg(colors::warning()))
+                    }
+                    None if (c.is_alphabetic() || c == '_') && word_start => {
+                        let word = &rest[..word_len(rest)];
+                        if language.keywords.contains(&word) {
+                            (word, Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD))
+                        } else if word.starts_with(char::is_uppercase) {
+                            (word, Style::default().fg(colors::secondary()))
+                        } else {
+                            plain.push_str(word);
+                            rest = &rest[word.len()..];
+                            continue;
+                        }
+                    }
+                    None => {
+                        plain.push(c);
+                        rest = &rest[c.len_utf8()..];
+                        continue;
+                    }
+                }
+            };
+
+            if !plain.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut plain), Style::default().fg(colors::text())));
+            }
+            spans.push(Span::styled(token.to_string(), style));
+            rest = &rest[token.len()..];
+        }
+        if !plain.is_empty() {
+            spans.push(Span::styled(plain, Style::default().fg(colors::text())));
+        }
+        Line::from(spans)
+    }
+
+    /// Where the string opening `text` with `quote` ends, just past its
+    /// closing quote or at the end of the line; None when `quote` opens none
+    fn string_end(&self, text: &str, quote: char) -> Option<usize> {
+        if !self.language.quotes.contains(&quote) {
+            return None;
+        }
+        if quote == '\'' && self.language.char_literals {
+            // One character, or a short escape like '\n' or '\u{1F600}';
+            // anything else is a lifetime or a label
+            let mut chars = text.char_indices().skip(1);
+            let (_, first) = chars.next()?;
+            if first == '\\' {
+                return text[1..].find('\'').filter(|&end| end > 1 && end <= 11).map(|end| end + 2);
+            }
+            let (index, c) = chars.next()?;
+            return (c == '\'').then_some(index + 1);
+        }
+        let mut escaped = false;
+        for (index, c) in text.char_indices().skip(1) {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                c if c == quote => return Some(index + 1),
+                _ => {}
+            }
+        }
+        Some(text.len())
+    }
+}
+
+/// Length of the identifier or number starting `text`
+fn word_len(text: &str) -> usize {
+    text.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.')).unwrap_or(text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(line: &Line) -> Vec<String> {
+        line.spans.iter().map(|span| span.content.to_string()).collect()
+    }
+
+    #[test]
+    fn test_language_lookup() {
+        assert_eq!(language(Path::new("src/main.rs")).map(|language| language.name), Some("Rust"));
+        assert_eq!(language(Path::new("Makefile")).map(|language| language.name), Some("Makefile"));
+        assert!(language(Path::new("notes.txt")).is_none());
+        assert_eq!(language_named("py").map(|language| language.name), Some("Python"));
+    }
+
+    #[test]
+    fn test_highlight_rust_line() {
+        let mut highlighter = Highlighter::new(language(Path::new("a.rs")).unwrap());
+        let line = highlighter.line("fn f<'a>(x: &'a str) -> Foo { \"hi\" } // done");
+        assert_eq!(
+            tokens(&line),
+            vec!["fn", " f<'a>(x: &'a str) -> ", "Foo", " { ", "\"hi\"", " } ", "// done"]
+        );
+        assert_eq!(tokens(&highlighter.line("let c = 'x';")), vec!["let", " c = ", "'x'", ";"]);
+    }
+
+    #[test]
+    fn test_block_comment_spans_lines() {
+        let mut highlighter = Highlighter::new(language(Path::new("a.c")).unwrap());
+        highlighter.line("int x; /* start");
+        let line = highlighter.line("still comment */ return 1;");
+        assert_eq!(tokens(&line), vec!["still comment */", " ", "return", " ", "1", ";"]);
+    }
+}
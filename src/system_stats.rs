@@ -0,0 +1,244 @@
+//! Disk and network figures for the `tt sys` dashboard; CPU, memory and
+//! processes come from [`crate::process_info`].
+//!
+//! Mounted filesystems and their usage come from `df -kP`, whose POSIX
+//! output is the same on Linux and macOS. Virtual filesystems (tmpfs,
+//! overlay, proc...) and loop devices are left out, keeping the disks a
+//! person would check for space.
+//!
+//! Network counters are the bytes each interface has received and sent
+//! since boot: from `/proc/net/dev` on Linux and `netstat -ibn` elsewhere on
+//! Unix. [`NetSampler`] turns them into rates between samples. Windows has
+//! neither, so the dashboard shows no disks or network there.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    process::Command,
+    time::Instant,
+};
+
+/// Rates kept for the throughput sparklines
+pub const HISTORY_LEN: usize = 120;
+
+/// A mounted filesystem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disk {
+    pub filesystem: String,
+    pub mount: String,
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+}
+
+impl Disk {
+    /// Share of the space in use, as `df` computes it
+    pub fn percent(&self) -> f64 {
+        let usable = self.used + self.available;
+        if usable == 0 { 0.0 } else { self.used as f64 / usable as f64 * 100.0 }
+    }
+}
+
+/// Real filesystems mounted now
+pub fn disks() -> io::Result<Vec<Disk>> {
+    let output = Command::new("df").arg("-kP").output()?;
+    // df fails when one mount is unreadable but still lists the rest
+    let disks = parse_df(&String::from_utf8_lossy(&output.stdout));
+    if disks.is_empty() && !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(stderr.lines().next().unwrap_or("df failed").to_string()));
+    }
+    Ok(disks)
+}
+
+/// Parse `df -kP`, keeping devices (`/dev/...`) and network shares
+/// (`host:/path`, `//host/share`), one entry per device
+fn parse_df(text: &str) -> Vec<Disk> {
+    let mut disks: Vec<Disk> = Vec::new();
+    for line in text.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 {
+            continue;
+        }
+        let filesystem = fields[0];
+        let is_real = (filesystem.starts_with('/') && !filesystem.starts_with("/dev/loop")) || filesystem.contains(":/");
+        let numbers: Option<Vec<u64>> = fields[1..4].iter().map(|field| field.parse().ok()).collect();
+        let Some(numbers) = numbers.filter(|numbers| is_real && numbers[0] > 0) else {
+            continue;
+        };
+        // Bind mounts and btrfs subvolumes repeat a device; keep its shortest mount
+        let mount = fields[5..].join(" ");
+        if let Some(disk) = disks.iter_mut().find(|disk| disk.filesystem == filesystem) {
+            if mount.len() < disk.mount.len() {
+                disk.mount = mount;
+            }
+            continue;
+        }
+        disks.push(Disk {
+            filesystem: filesystem.to_string(),
+            mount,
+            total: numbers[0] * 1024,
+            used: numbers[1] * 1024,
+            available: numbers[2] * 1024,
+        });
+    }
+    disks
+}
+
+/// Bytes received and sent by one interface since boot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetCounters {
+    pub interface: String,
+    pub received: u64,
+    pub sent: u64,
+}
+
+/// Counters of every interface but loopback
+pub fn net_counters() -> Vec<NetCounters> {
+    #[cfg(target_os = "linux")]
+    let counters = std::fs::read_to_string("/proc/net/dev").map(|text| parse_proc_net_dev(&text)).unwrap_or_default();
+    #[cfg(windows)]
+    let counters = Vec::new();
+    #[cfg(not(any(target_os = "linux", windows)))]
+    let counters = Command::new("netstat")
+        .arg("-ibn")
+        .output()
+        .map(|output| parse_netstat_ibn(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default();
+    counters.into_iter().filter(|counters| !counters.interface.starts_with("lo")).collect()
+}
+
+/// Parse `/proc/net/dev`: two header lines, then `iface: rx_bytes ... tx_bytes ...`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_net_dev(text: &str) -> Vec<NetCounters> {
+    text.lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (interface, counters) = line.split_once(':')?;
+            let counters: Vec<u64> = counters.split_whitespace().filter_map(|value| value.parse().ok()).collect();
+            Some(NetCounters { interface: interface.trim().to_string(), received: *counters.first()?, sent: *counters.get(8)? })
+        })
+        .collect()
+}
+
+/// Parse `netstat -ibn` on macOS and BSD, using the `<Link#N>` row of each
+/// interface, which counts all its traffic
+#[cfg_attr(any(target_os = "linux", windows), allow(dead_code))]
+fn parse_netstat_ibn(text: &str) -> Vec<NetCounters> {
+    let mut lines = text.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let columns: Vec<&str> = header.split_whitespace().collect();
+    let column = |name: &str| columns.iter().position(|column| *column == name);
+    let (Some(received), Some(sent)) = (column("Ibytes"), column("Obytes")) else {
+        return Vec::new();
+    };
+    lines
+        .filter(|line| line.contains("<Link#"))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // Interfaces without a MAC address leave the Address column empty
+            let shift = columns.len().saturating_sub(fields.len());
+            Some(NetCounters {
+                interface: fields.first()?.to_string(),
+                received: fields.get(received.checked_sub(shift)?)?.parse().ok()?,
+                sent: fields.get(sent.checked_sub(shift)?)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Receive and send rates, in bytes per second
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rates {
+    pub received: u64,
+    pub sent: u64,
+}
+
+/// Network throughput from counters sampled over time, in total and per
+/// interface, with a history of the totals
+#[derive(Default)]
+pub struct NetSampler {
+    previous: HashMap<String, (u64, u64)>,
+    sampled_at: Option<Instant>,
+    /// Rates of each interface over the last interval, by name
+    pub interfaces: Vec<(String, Rates)>,
+    /// Total rates, oldest first, at most [`HISTORY_LEN`]
+    pub history: VecDeque<Rates>,
+}
+
+impl NetSampler {
+    /// Read the counters and update the rates; the first call only records
+    /// a baseline
+    pub fn sample(&mut self) {
+        let now = Instant::now();
+        let counters = net_counters();
+        if let Some(sampled_at) = self.sampled_at {
+            let seconds = now.duration_since(sampled_at).as_secs_f64().max(0.001);
+            let rate = |now: u64, before: u64| (now.saturating_sub(before) as f64 / seconds) as u64;
+            self.interfaces = counters
+                .iter()
+                .map(|counters| {
+                    let (received, sent) = self.previous.get(&counters.interface).copied().unwrap_or((counters.received, counters.sent));
+                    let rates = Rates { received: rate(counters.received, received), sent: rate(counters.sent, sent) };
+                    (counters.interface.clone(), rates)
+                })
+                .collect();
+            let total = self.interfaces.iter().fold(Rates::default(), |total, (_, rates)| Rates {
+                received: total.received + rates.received,
+                sent: total.sent + rates.sent,
+            });
+            if self.history.len() == HISTORY_LEN {
+                self.history.pop_front();
+            }
+            self.history.push_back(total);
+        }
+        self.previous = counters.into_iter().map(|counters| (counters.interface, (counters.received, counters.sent))).collect();
+        self.sampled_at = Some(now);
+    }
+
+    /// The latest total rates
+    pub fn current(&self) -> Rates {
+        self.history.back().copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_df() {
+        let df = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                  /dev/nvme0n1p2   479596204 201234560 253928316      45% /\n\
+                  tmpfs              8112340      2048   8110292       1% /dev/shm\n\
+                  /dev/nvme0n1p2   479596204 201234560 253928316      45% /home\n\
+                  /dev/loop3           56832     56832         0     100% /snap/core/1\n\
+                  nas:/export/media 976762584 500000000 476762584      52% /mnt/My Media\n";
+        let disks = parse_df(df);
+        assert_eq!(disks.len(), 2);
+        assert_eq!((disks[0].mount.as_str(), disks[0].total), ("/", 479596204 * 1024));
+        assert_eq!(disks[1].mount, "/mnt/My Media");
+        assert!((disks[0].percent() - 44.2).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_parse_net_counters() {
+        let dev = "Inter-|   Receive                                                |  Transmit\n \
+                   face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed\n    \
+                   lo: 1000 10 0 0 0 0 0 0 1000 10 0 0 0 0 0 0\n  \
+                   eth0: 5000 50 0 0 0 0 0 0 7000 60 0 0 0 0 0 0\n";
+        let counters = parse_proc_net_dev(dev);
+        assert_eq!(counters[1], NetCounters { interface: "eth0".to_string(), received: 5000, sent: 7000 });
+
+        let netstat = "Name  Mtu   Network       Address            Ipkts Ierrs     Ibytes    Opkts Oerrs     Obytes  Coll\n\
+                       en0   1500  <Link#6>      a4:83:e7:00:00:01  1000     0    2000000      900     0     300000     0\n\
+                       en0   1500  192.168.1     192.168.1.20       1000     -    2000000      900     -     300000     -\n\
+                       utun0 1380  <Link#12>                          10     0       4000       12     0       5000     0\n";
+        let counters = parse_netstat_ibn(netstat);
+        assert_eq!(counters.len(), 2);
+        assert_eq!((counters[0].received, counters[0].sent), (2000000, 300000));
+        assert_eq!((counters[1].interface.as_str(), counters[1].sent), ("utun0", 5000));
+    }
+}
@@ -0,0 +1,238 @@
+//! systemd units, their journals and the actions `tt systemd` runs on them.
+//!
+//! Units come from `systemctl list-units --all --output=json` (systemd 246
+//! or later), with the enablement of each unit file from `systemctl
+//! list-unit-files --output=json`. The journal of a unit is read with
+//! `journalctl -u`.
+//!
+//! Starting, stopping, enabling or disabling a system unit needs root. When
+//! `tt` is not running as root the action goes through `sudo -n`, which
+//! succeeds when sudo needs no password; otherwise it fails with
+//! [`io::ErrorKind::PermissionDenied`] and the caller runs
+//! [`UnitAction::command`] on the terminal, where sudo can ask for one.
+
+use crate::jobs::Task;
+use crate::users;
+use serde_json::Value;
+use std::{collections::HashMap, io, process::Command};
+
+/// Whether units of the system manager or of the user's own manager
+/// (`systemctl --user`) are meant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    System,
+    User,
+}
+
+impl Scope {
+    /// `systemctl` or `journalctl` with the flag selecting this manager
+    fn command(self, program: &str) -> Command {
+        let mut command = Command::new(program);
+        if self == Scope::User {
+            command.arg("--user");
+        }
+        command
+    }
+
+    /// Whether actions on units need sudo
+    fn needs_sudo(self) -> bool {
+        self == Scope::System && !users::is_root()
+    }
+}
+
+/// A loaded unit as `systemctl list-units` reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unit {
+    pub name: String,
+    /// `loaded`, `not-found`, `masked`...
+    pub load: String,
+    /// `active`, `inactive`, `failed`, `activating`...
+    pub active: String,
+    /// Finer state: `running`, `exited`, `dead`, `listening`...
+    pub sub: String,
+    pub description: String,
+    /// Unit file state: `enabled`, `disabled`, `static`, `masked`...; None
+    /// for units without a file (devices, scopes)
+    pub enabled: Option<String>,
+}
+
+impl Unit {
+    /// The unit type: `service`, `timer`, `socket`...
+    pub fn kind(&self) -> &str {
+        self.name.rsplit_once('.').map_or("", |(_, kind)| kind)
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.active == "failed"
+    }
+}
+
+/// Actions on a unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitAction {
+    Start,
+    Stop,
+    Restart,
+    Enable,
+    Disable,
+}
+
+impl UnitAction {
+    /// The `systemctl` verb
+    pub fn verb(self) -> &'static str {
+        match self {
+            UnitAction::Start => "start",
+            UnitAction::Stop => "stop",
+            UnitAction::Restart => "restart",
+            UnitAction::Enable => "enable",
+            UnitAction::Disable => "disable",
+        }
+    }
+
+    /// Past tense, for messages
+    pub fn done(self) -> &'static str {
+        match self {
+            UnitAction::Start => "started",
+            UnitAction::Stop => "stopped",
+            UnitAction::Restart => "restarted",
+            UnitAction::Enable => "enabled",
+            UnitAction::Disable => "disabled",
+        }
+    }
+
+    /// The command line for the shell, with sudo where it is needed
+    pub fn command(self, scope: Scope, unit: &str) -> String {
+        let unit = quote(unit);
+        match scope {
+            Scope::User => format!("systemctl --user {} {}", self.verb(), unit),
+            Scope::System if scope.needs_sudo() => format!("sudo systemctl {} {}", self.verb(), unit),
+            Scope::System => format!("systemctl {} {}", self.verb(), unit),
+        }
+    }
+
+    /// Run the action without a terminal. Fails with PermissionDenied when
+    /// sudo wants a password.
+    pub fn run(self, scope: Scope, unit: &str) -> io::Result<()> {
+        let mut command = if scope.needs_sudo() {
+            let mut command = Command::new("sudo");
+            command.args(["-n", "systemctl"]);
+            command
+        } else {
+            // Keep polkit from asking for a password over the TUI
+            let mut command = scope.command("systemctl");
+            command.arg("--no-ask-password");
+            command
+        };
+        let output = command.args([self.verb(), unit]).output()?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let kind = if scope.needs_sudo() && stderr.contains("password is required") {
+            io::ErrorKind::PermissionDenied
+        } else {
+            io::ErrorKind::Other
+        };
+        Err(io::Error::new(kind, stderr.lines().last().unwrap_or("systemctl failed").to_string()))
+    }
+}
+
+/// Quote a unit name for `sh -c` using single quotes; escaped names like
+/// `dev-disk-by\x2duuid.device` hold backslashes
+fn quote(unit: &str) -> String {
+    format!("'{}'", unit.replace('\'', "'\\''"))
+}
+
+/// `systemctl` output, or its error message as an error
+fn systemctl(task: &Task, scope: Scope, args: &[&str]) -> io::Result<String> {
+    let output = task.output(scope.command("systemctl").args(args))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(stderr.lines().next().unwrap_or("systemctl failed").to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Every unit the manager has loaded, including inactive ones, sorted by name
+pub fn list_units(task: &Task, scope: Scope) -> io::Result<Vec<Unit>> {
+    let units = systemctl(task, scope, &["list-units", "--all", "--output=json", "--no-pager"])?;
+    let mut units = parse_units(&units).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "could not read systemctl's JSON output (systemd 246 or later is needed)")
+    })?;
+    // Enablement is a nicety; an old or restricted systemctl goes without
+    let files = systemctl(task, scope, &["list-unit-files", "--output=json", "--no-pager"])
+        .ok()
+        .and_then(|files| parse_unit_files(&files))
+        .unwrap_or_default();
+    for unit in &mut units {
+        unit.enabled = files.get(&unit.name).cloned();
+    }
+    units.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(units)
+}
+
+/// Parse `systemctl list-units --output=json`
+fn parse_units(json: &str) -> Option<Vec<Unit>> {
+    let value: Value = serde_json::from_str(json).ok()?;
+    let field = |entry: &Value, name: &str| entry[name].as_str().unwrap_or_default().to_string();
+    Some(value
+        .as_array()?
+        .iter()
+        .filter(|entry| entry["unit"].is_string())
+        .map(|entry| Unit {
+            name: field(entry, "unit"),
+            load: field(entry, "load"),
+            active: field(entry, "active"),
+            sub: field(entry, "sub"),
+            description: field(entry, "description"),
+            enabled: None,
+        })
+        .collect())
+}
+
+/// Parse `systemctl list-unit-files --output=json` into unit → state
+fn parse_unit_files(json: &str) -> Option<HashMap<String, String>> {
+    let value: Value = serde_json::from_str(json).ok()?;
+    Some(value
+        .as_array()?
+        .iter()
+        .filter_map(|entry| Some((entry["unit_file"].as_str()?.to_string(), entry["state"].as_str()?.to_string())))
+        .collect())
+}
+
+/// The last `lines` journal entries of `unit`, oldest first. Without access
+/// to the journal (not root nor in the `systemd-journal` group) this is
+/// journalctl's hint about it.
+pub fn journal(task: &Task, scope: Scope, unit: &str, lines: usize) -> io::Result<Vec<String>> {
+    let output = task.output(scope
+        .command("journalctl")
+        .args(["-u", unit, "-n", &lines.to_string(), "--no-pager", "-o", "short-iso", "-q"]))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let text = if stdout.trim().is_empty() { stderr } else { stdout };
+    Ok(text.lines().map(str::to_string).collect())
+}
+
+/// The command showing the whole journal of `unit` in a pager, at its end
+pub fn journal_command(scope: Scope, unit: &str) -> String {
+    let user = if scope == Scope::User { "--user " } else { "" };
+    format!("journalctl {}-u {} -e", user, quote(unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_units() {
+        let units = r#"[{"unit":"cron.service","load":"loaded","active":"active","sub":"running","description":"Regular background program processing daemon"},{"unit":"nginx.service","load":"loaded","active":"failed","sub":"failed","description":"A high performance web server"}]"#;
+        let units = parse_units(units).unwrap();
+        assert_eq!(units.len(), 2);
+        assert_eq!((units[0].name.as_str(), units[0].sub.as_str(), units[0].kind()), ("cron.service", "running", "service"));
+        assert!(units[1].is_failed());
+        assert_eq!(parse_units("UNIT LOAD ACTIVE SUB"), None);
+
+        let files = parse_unit_files(r#"[{"unit_file":"cron.service","state":"enabled","preset":"enabled"}]"#).unwrap();
+        assert_eq!(files.get("cron.service").map(String::as_str), Some("enabled"));
+    }
+}
@@ -0,0 +1,164 @@
+//! Measuring and cutting text by terminal columns.
+//!
+//! A char is not a column: CJK characters and most emoji take two, combining
+//! accents and zero-width joiners take none, and a flag or a family emoji is
+//! several chars drawn as one glyph. Everything here works on grapheme
+//! clusters (what the user sees as one character) and their display width,
+//! so columns line up and a glyph is never split in half.
+//!
+//! Widths come from `unicode-width`, the same tables ratatui uses to lay
+//! text out, so what these functions measure is what ends up on screen.
+
+use std::borrow::Cow;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Marks the place where text was cut
+pub const ELLIPSIS: &str = "…";
+
+/// Columns a tab is drawn as
+const TAB_WIDTH: usize = 4;
+
+/// Columns `text` takes on screen
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// The grapheme clusters of `text` with their widths
+pub fn graphemes(text: &str) -> impl Iterator<Item = (&str, usize)> {
+    text.graphemes(true).map(|grapheme| (grapheme, grapheme.width()))
+}
+
+/// Split `text` after the longest start that fits in `columns`, without
+/// splitting a grapheme; returns the two parts and the width of the first
+pub fn split_at_width(text: &str, columns: usize) -> (&str, &str, usize) {
+    let mut used = 0;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        let grapheme_width = grapheme.width();
+        if used + grapheme_width > columns {
+            return (&text[..index], &text[index..], used);
+        }
+        used += grapheme_width;
+    }
+    (text, "", used)
+}
+
+/// `text` cut to at most `columns`, ending in [`ELLIPSIS`] when anything was
+/// cut
+pub fn truncate(text: &str, columns: usize) -> Cow<'_, str> {
+    if width(text) <= columns {
+        return Cow::Borrowed(text);
+    }
+    if columns == 0 {
+        return Cow::Borrowed("");
+    }
+    let (head, _, _) = split_at_width(text, columns - 1);
+    Cow::Owned(format!("{}{}", head, ELLIPSIS))
+}
+
+/// `text` cut to at most `columns` by dropping its start, for paths whose
+/// end matters most
+pub fn truncate_start(text: &str, columns: usize) -> Cow<'_, str> {
+    if width(text) <= columns {
+        return Cow::Borrowed(text);
+    }
+    if columns == 0 {
+        return Cow::Borrowed("");
+    }
+    let mut used = 0;
+    let mut start = text.len();
+    for (index, grapheme) in text.grapheme_indices(true).rev() {
+        used += grapheme.width();
+        if used > columns - 1 {
+            break;
+        }
+        start = index;
+    }
+    Cow::Owned(format!("{}{}", ELLIPSIS, &text[start..]))
+}
+
+/// `text` truncated or padded with spaces to exactly `columns`
+pub fn pad(text: &str, columns: usize) -> String {
+    let text = truncate(text, columns);
+    let padding = columns - width(&text);
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// `text` broken into rows of at most `columns`, between graphemes; a
+/// grapheme wider than `columns` gets a row of its own
+pub fn wrap(text: &str, columns: usize) -> Vec<&str> {
+    let mut rows = Vec::new();
+    let mut rest = text;
+    while width(rest) > columns {
+        let (row, next, _) = split_at_width(rest, columns);
+        let (row, next) = if row.is_empty() {
+            // Too wide to fit at all
+            let end = rest.graphemes(true).next().map_or(rest.len(), str::len);
+            rest.split_at(end)
+        } else {
+            (row, next)
+        };
+        rows.push(row);
+        rest = next;
+    }
+    if !rest.is_empty() || rows.is_empty() {
+        rows.push(rest);
+    }
+    rows
+}
+
+/// Tabs as spaces, which the terminal would otherwise draw as nothing
+pub fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+/// Rows `text` takes when wrapped to `columns`; an empty line takes one
+pub fn rows(text: &str, columns: usize) -> usize {
+    if columns == 0 {
+        return 1;
+    }
+    wrap(text, columns).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_width() {
+        assert_eq!(width("abc"), 3);
+        assert_eq!(width("日本語"), 6);
+        // e + combining acute accent
+        assert_eq!(width("e\u{301}"), 1);
+        assert_eq!(graphemes("e\u{301}x").collect::<Vec<_>>(), vec![("e\u{301}", 1), ("x", 1)]);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("abcdef", 4), "abc…");
+        assert_eq!(truncate("abc", 4), "abc");
+        assert_eq!(truncate("abc", 0), "");
+        // A wide char that would straddle the limit is left out whole
+        assert_eq!(truncate("日本語", 4), "日…");
+        assert_eq!(truncate("cafe\u{301} noir", 5), "cafe\u{301}…");
+        assert_eq!(truncate_start("/home/user/src/main.rs", 10), "…c/main.rs");
+        assert_eq!(truncate_start("日本語", 4), "…語");
+    }
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad("日本", 6), "日本  ");
+        assert_eq!(pad("日本語", 5), "日本…");
+        assert_eq!(width(&pad("日本語", 4)), 4);
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("abcdef", 4), vec!["abcd", "ef"]);
+        assert_eq!(wrap("日本語", 3), vec!["日", "本", "語"]);
+        assert_eq!(wrap("日", 1), vec!["日"]);
+        assert_eq!(wrap("", 4), vec![""]);
+        assert_eq!(rows("日本語です", 4), 3);
+        assert_eq!(split_at_width("ab日", 3), ("ab", "日", 2));
+    }
+}
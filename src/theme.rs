@@ -0,0 +1,248 @@
+//! Color themes for every tool.
+//!
+//! A [`Theme`] assigns colors to the roles the widgets use ([`colors`] reads
+//! them). The theme is picked once per run, from `--theme`, then the tool's
+//! `theme` in `config.toml`, then the global one:
+//!
+//! - `auto` (the default): `dark` or `light` to match the terminal background
+//! - `dark`, `light`: the terminal's own ANSI colors
+//! - `solarized-dark`, `solarized-light`: fixed RGB palettes
+//!
+//! A `[colors]` table in `config.toml` replaces single roles of the chosen
+//! theme with color names (`"blue"`, `"darkgray"`), `"#rrggbb"` values or
+//! 256-color indexes.
+//!
+//! ## Background Detection
+//!
+//! `auto` first looks at `COLORFGBG`, which rxvt, Konsole and some others
+//! export. Otherwise it asks the terminal for its background color with an
+//! OSC 11 query on `/dev/tty`, followed by a device attributes query that
+//! every terminal answers, so terminals that ignore OSC 11 cost no timeout.
+//! This happens right before the first tool draws, not for commands without
+//! a TUI. When nothing answers, the theme is `dark`.
+//!
+//! [`colors`]: crate::tui_common::colors
+
+use crate::platform;
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::{io, sync::OnceLock};
+
+/// Theme names accepted by `--theme` and `config.toml`
+pub const THEME_NAMES: &[&str] = &["auto", "dark", "light", "solarized-dark", "solarized-light"];
+
+/// Colors of each role in the interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Borders, titles and the selection highlight
+    pub primary: Color,
+    /// Emphasized values, filters and prompts
+    pub secondary: Color,
+    pub success: Color,
+    pub danger: Color,
+    pub warning: Color,
+    /// Secondary text such as hints, paths and ages
+    pub muted: Color,
+    /// Text drawn on a colored highlight
+    pub background: Color,
+    pub text: Color,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        primary: Color::Cyan,
+        secondary: Color::Yellow,
+        success: Color::Green,
+        danger: Color::Red,
+        warning: Color::Magenta,
+        muted: Color::DarkGray,
+        background: Color::Black,
+        text: Color::White,
+    };
+
+    pub const LIGHT: Theme = Theme {
+        primary: Color::Blue,
+        secondary: Color::Magenta,
+        success: Color::Green,
+        danger: Color::Red,
+        warning: Color::Rgb(175, 95, 0),
+        muted: Color::DarkGray,
+        background: Color::White,
+        text: Color::Black,
+    };
+
+    pub const SOLARIZED_DARK: Theme = Theme {
+        primary: Color::Rgb(0x26, 0x8b, 0xd2),
+        secondary: Color::Rgb(0xb5, 0x89, 0x00),
+        success: Color::Rgb(0x85, 0x99, 0x00),
+        danger: Color::Rgb(0xdc, 0x32, 0x2f),
+        warning: Color::Rgb(0xd3, 0x36, 0x82),
+        muted: Color::Rgb(0x58, 0x6e, 0x75),
+        background: Color::Rgb(0x00, 0x2b, 0x36),
+        text: Color::Rgb(0x93, 0xa1, 0xa1),
+    };
+
+    pub const SOLARIZED_LIGHT: Theme = Theme {
+        primary: Color::Rgb(0x26, 0x8b, 0xd2),
+        secondary: Color::Rgb(0xcb, 0x4b, 0x16),
+        success: Color::Rgb(0x85, 0x99, 0x00),
+        danger: Color::Rgb(0xdc, 0x32, 0x2f),
+        warning: Color::Rgb(0xd3, 0x36, 0x82),
+        muted: Color::Rgb(0x93, 0xa1, 0xa1),
+        background: Color::Rgb(0xfd, 0xf6, 0xe3),
+        text: Color::Rgb(0x07, 0x36, 0x42),
+    };
+
+    /// The named theme, or None for `auto` (and unknown names)
+    pub fn named(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::DARK),
+            "light" => Some(Theme::LIGHT),
+            "solarized-dark" => Some(Theme::SOLARIZED_DARK),
+            "solarized-light" => Some(Theme::SOLARIZED_LIGHT),
+            _ => None,
+        }
+    }
+}
+
+/// Colors from the `[colors]` table, each replacing one role
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Palette {
+    pub primary: Option<String>,
+    pub secondary: Option<String>,
+    pub success: Option<String>,
+    pub danger: Option<String>,
+    pub warning: Option<String>,
+    pub muted: Option<String>,
+    pub background: Option<String>,
+    pub text: Option<String>,
+}
+
+impl Palette {
+    /// `theme` with this palette's colors put in
+    fn apply(&self, mut theme: Theme) -> io::Result<Theme> {
+        let roles = [
+            (&self.primary, &mut theme.primary),
+            (&self.secondary, &mut theme.secondary),
+            (&self.success, &mut theme.success),
+            (&self.danger, &mut theme.danger),
+            (&self.warning, &mut theme.warning),
+            (&self.muted, &mut theme.muted),
+            (&self.background, &mut theme.background),
+            (&self.text, &mut theme.text),
+        ];
+        for (value, color) in roles {
+            if let Some(value) = value {
+                *color = value.parse().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("config.toml: unknown color '{}'", value))
+                })?;
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// Theme picked by [`configure`], before `auto` is resolved
+struct Choice {
+    name: String,
+    palette: Palette,
+}
+
+static CHOICE: OnceLock<Choice> = OnceLock::new();
+static CURRENT: OnceLock<Theme> = OnceLock::new();
+
+/// Pick the theme `name` (default `auto`) with `palette` on top; checked
+/// here so mistakes are reported before any tool starts
+pub fn configure(name: Option<&str>, palette: &Palette) -> io::Result<()> {
+    let name = name.unwrap_or("auto");
+    if !THEME_NAMES.contains(&name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown theme '{}' (expected one of: {})", name, THEME_NAMES.join(", ")),
+        ));
+    }
+    palette.apply(Theme::DARK)?;
+    let _ = CHOICE.set(Choice { name: name.to_string(), palette: palette.clone() });
+    Ok(())
+}
+
+/// The theme in use, resolved on first use; `auto` queries the terminal,
+/// so the first call must happen outside raw mode
+pub fn current() -> &'static Theme {
+    CURRENT.get_or_init(|| {
+        let Some(choice) = CHOICE.get() else {
+            return Theme::DARK;
+        };
+        let base = Theme::named(&choice.name).unwrap_or_else(|| match detect_light_background() {
+            Some(true) => Theme::LIGHT,
+            _ => Theme::DARK,
+        });
+        choice.palette.apply(base).unwrap_or(base)
+    })
+}
+
+/// Whether the terminal background is light, if it can be told
+fn detect_light_background() -> Option<bool> {
+    if let Some(light) = std::env::var("COLORFGBG").ok().as_deref().and_then(colorfgbg_is_light) {
+        return Some(light);
+    }
+    let response = platform::query_terminal(b"\x1b]11;?\x1b\\")?;
+    let (r, g, b) = parse_osc11(&response)?;
+    Some(0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5)
+}
+
+/// Light background according to `COLORFGBG` (`fg;bg` or `fg;default;bg`);
+/// 7 and 9-15 are the light ANSI colors
+fn colorfgbg_is_light(value: &str) -> Option<bool> {
+    let bg: u8 = value.rsplit(';').next()?.parse().ok()?;
+    Some(bg == 7 || (9..=15).contains(&bg))
+}
+
+/// Background color from an OSC 11 reply (`ESC ] 11 ; rgb:RRRR/GGGG/BBBB`),
+/// each channel scaled to 0.0-1.0
+fn parse_osc11(response: &str) -> Option<(f64, f64, f64)> {
+    let start = response.find("rgb:")? + 4;
+    let body = &response[start..];
+    let end = body.find(|c: char| !c.is_ascii_hexdigit() && c != '/').unwrap_or(body.len());
+    let mut channels = body[..end].split('/').map(|hex| {
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1u32 << (4 * hex.len().clamp(1, 4))) - 1;
+        Some(value as f64 / max as f64)
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    Some((r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette() {
+        let palette = Palette { primary: Some("#ff8000".to_string()), muted: Some("244".to_string()), ..Palette::default() };
+        let theme = palette.apply(Theme::LIGHT).unwrap();
+        assert_eq!(theme.primary, Color::Rgb(255, 128, 0));
+        assert_eq!(theme.muted, Color::Indexed(244));
+        assert_eq!(theme.text, Theme::LIGHT.text);
+
+        let bad = Palette { text: Some("blurple".to_string()), ..Palette::default() };
+        assert!(bad.apply(Theme::DARK).is_err());
+        assert!(THEME_NAMES.iter().filter(|name| **name != "auto").all(|name| Theme::named(name).is_some()));
+    }
+
+    #[test]
+    fn test_background_detection() {
+        assert_eq!(colorfgbg_is_light("15;0"), Some(false));
+        assert_eq!(colorfgbg_is_light("0;15"), Some(true));
+        assert_eq!(colorfgbg_is_light("0;default;7"), Some(true));
+        assert_eq!(colorfgbg_is_light("default"), None);
+
+        let (r, g, b) = parse_osc11("\x1b]11;rgb:ffff/ffff/ffff\x1b\\\x1b[?62;22c").unwrap();
+        assert_eq!((r, g, b), (1.0, 1.0, 1.0));
+        let (r, _, b) = parse_osc11("\x1b]11;rgb:00/80/ff\x07").unwrap();
+        assert_eq!(r, 0.0);
+        assert_eq!(b, 1.0);
+        assert!(parse_osc11("\x1b[?62;22c").is_none());
+    }
+}
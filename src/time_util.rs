@@ -0,0 +1,255 @@
+//! Timestamp formatting without a date/time dependency.
+//!
+//! Tools display modification times, history timestamps and trash deletion
+//! dates. This module converts Unix timestamps to calendar dates in the local
+//! time zone using Howard Hinnant's `civil_from_days` algorithm, with the
+//! local UTC offset queried once from `date +%z` (falling back to UTC when
+//! unavailable). DST transitions between "now" and the formatted timestamp
+//! are not accounted for, which is acceptable for display purposes.
+
+use std::{
+    process::Command,
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Broken-down calendar date and time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+/// Local offset from UTC in seconds, cached for the process lifetime
+pub fn local_offset_secs() -> i64 {
+    static OFFSET: OnceLock<i64> = OnceLock::new();
+    *OFFSET.get_or_init(|| {
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("powershell");
+            command.args(["-NoProfile", "-Command", "(Get-Date).ToString('zzz')"]);
+            command
+        } else {
+            let mut command = Command::new("date");
+            command.arg("+%z");
+            command
+        };
+        command
+            .output()
+            .ok()
+            .and_then(|output| parse_utc_offset(String::from_utf8_lossy(&output.stdout).trim()))
+            .unwrap_or(0)
+    })
+}
+
+/// Parse a `+HHMM`/`-HHMM` (or PowerShell's `+HH:MM`) offset into seconds
+pub fn parse_utc_offset(offset: &str) -> Option<i64> {
+    let offset = offset.replacen(':', "", 1);
+    let (sign, digits) = match offset.as_bytes().first()? {
+        b'+' => (1, &offset[1..]),
+        b'-' => (-1, &offset[1..]),
+        _ => return None,
+    };
+    if digits.len() != 4 {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Convert days since the Unix epoch into a (year, month, day) triple
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Convert a (year, month, day) triple into days since the Unix epoch; the
+/// inverse of `civil_from_days`
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+impl DateTime {
+    /// Break a Unix timestamp down in UTC
+    pub fn from_utc(secs: i64) -> Self {
+        let days = secs.div_euclid(86_400);
+        let rem = secs.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        DateTime {
+            year,
+            month,
+            day,
+            hour: (rem / 3600) as u32,
+            minute: (rem % 3600 / 60) as u32,
+            second: (rem % 60) as u32,
+        }
+    }
+
+    /// Break a Unix timestamp down in the local time zone
+    pub fn from_local(secs: i64) -> Self {
+        Self::from_utc(secs + local_offset_secs())
+    }
+
+    /// The Unix timestamp of this date and time read as UTC
+    pub fn to_utc(self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * 86_400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+}
+
+/// Seconds since the Unix epoch for a `SystemTime` (negative before 1970)
+pub fn unix_secs(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
+
+/// Current time in seconds since the Unix epoch
+pub fn now_secs() -> i64 {
+    unix_secs(SystemTime::now())
+}
+
+/// Format a timestamp as local `YYYY-MM-DD HH:MM`
+pub fn format_timestamp(secs: i64) -> String {
+    let t = DateTime::from_local(secs);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", t.year, t.month, t.day, t.hour, t.minute)
+}
+
+/// Format a timestamp as local ISO 8601 without offset (`YYYY-MM-DDTHH:MM:SS`)
+pub fn format_iso(secs: i64) -> String {
+    let t = DateTime::from_local(secs);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        t.year, t.month, t.day, t.hour, t.minute, t.second
+    )
+}
+
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+/// Heading for the day `secs` falls on, relative to `now`: `Today`,
+/// `Yesterday`, `This week`, `Last week`, then the month (`March 2024`)
+pub fn day_group(secs: i64, now: i64) -> String {
+    day_group_with_offset(secs, now, local_offset_secs())
+}
+
+fn day_group_with_offset(secs: i64, now: i64, offset: i64) -> String {
+    let day = (secs + offset).div_euclid(86_400);
+    let today = (now + offset).div_euclid(86_400);
+    match today - day {
+        i64::MIN..=0 => "Today".to_string(),
+        1 => "Yesterday".to_string(),
+        2..=6 => "This week".to_string(),
+        7..=13 => "Last week".to_string(),
+        _ => {
+            let t = DateTime::from_utc(secs + offset);
+            format!("{} {}", MONTHS[t.month as usize - 1], t.year)
+        }
+    }
+}
+
+/// Compact age of `secs` as seen at `now`: `now`, `5m`, `3h`, `2d`, `6w`, `4mo`, `2y`
+pub fn format_age(secs: i64, now: i64) -> String {
+    let age = (now - secs).max(0);
+    match age {
+        0..=59 => "now".to_string(),
+        60..=3599 => format!("{}m", age / 60),
+        3600..=86_399 => format!("{}h", age / 3600),
+        86_400..=1_209_599 => format!("{}d", age / 86_400),
+        1_209_600..=5_183_999 => format!("{}w", age / 604_800),
+        5_184_000..=31_535_999 => format!("{}mo", age / 2_592_000),
+        _ => format!("{}y", age / 31_536_000),
+    }
+}
+
+/// Compact duration of `ms` milliseconds: `850ms`, `2.3s`, `4m 12s`, `1h 5m`
+pub fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    match ms {
+        0..=999 => format!("{}ms", ms),
+        1000..=59_999 => format!("{:.1}s", ms as f64 / 1000.0),
+        60_000..=3_599_999 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_utc() {
+        let t = DateTime::from_utc(1_700_000_000);
+        assert_eq!((t.year, t.month, t.day), (2023, 11, 14));
+        assert_eq!((t.hour, t.minute, t.second), (22, 13, 20));
+        assert_eq!(DateTime::from_utc(0).year, 1970);
+        assert_eq!(DateTime::from_utc(951_782_400).day, 29); // 2000-02-29
+    }
+
+    #[test]
+    fn test_to_utc() {
+        for secs in [0, 951_782_400, 1_700_000_000, -86_401] {
+            assert_eq!(DateTime::from_utc(secs).to_utc(), secs);
+        }
+    }
+
+    #[test]
+    fn test_parse_utc_offset() {
+        assert_eq!(parse_utc_offset("+0100"), Some(3600));
+        assert_eq!(parse_utc_offset("-0530"), Some(-19_800));
+        assert_eq!(parse_utc_offset("+05:45"), Some(20_700));
+        assert_eq!(parse_utc_offset("UTC"), None);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(100, 130), "now");
+        assert_eq!(format_age(0, 7200), "2h");
+        assert_eq!(format_age(0, 3 * 86_400), "3d");
+        assert_eq!(format_age(0, 100 * 86_400), "3mo");
+        assert_eq!(format_age(500, 0), "now");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(850), "850ms");
+        assert_eq!(format_duration(2340), "2.3s");
+        assert_eq!(format_duration(252_000), "4m 12s");
+        assert_eq!(format_duration(3_900_000), "1h 5m");
+    }
+
+    #[test]
+    fn test_day_group() {
+        let now = 1_700_000_000; // 2023-11-14 22:13 UTC
+        assert_eq!(day_group_with_offset(now - 3600, now, 0), "Today");
+        assert_eq!(day_group_with_offset(now - 86_400, now, 0), "Yesterday");
+        assert_eq!(day_group_with_offset(now - 9 * 86_400, now, 0), "Last week");
+        assert_eq!(day_group_with_offset(now - 60 * 86_400, now, 0), "September 2023");
+        // 01:00 local on the 15th is still "Today" two hours ahead of UTC
+        assert_eq!(day_group_with_offset(now + 3600, now + 3600, 7200), "Today");
+        assert_eq!(day_group_with_offset(now - 23 * 3600, now, 7200), "Yesterday");
+    }
+}
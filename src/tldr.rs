@@ -0,0 +1,222 @@
+//! tldr pages: short, example-first help for command-line tools.
+//!
+//! Pages come from the tldr-pages project
+//! (<https://github.com/tldr-pages/tldr>) and are downloaded with `curl` (or
+//! `wget`) on first use, then cached as Markdown in
+//! `$XDG_CACHE_HOME/terminal-tools/tldr/<platform>/<name>.md`. A cached page older than
+//! 30 days is refreshed when the network allows; offline, the cached copy is
+//! used however old it is.
+//!
+//! A page looks like
+//!
+//! ```markdown
+//! # tar
+//!
+//! > Archiving utility.
+//!
+//! - Create an archive from files:
+//!
+//! `tar cf {{path/to/target.tar}} {{path/to/file1 path/to/file2 ...}}`
+//! ```
+//!
+//! and is parsed into [`Line`]s, with `{{placeholders}}` marked so the TUI can
+//! highlight what to replace.
+
+use crate::platform;
+use std::{
+    fs, io,
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::{Duration, SystemTime},
+};
+
+const BASE_URL: &str = "https://raw.githubusercontent.com/tldr-pages/tldr/main/pages";
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Where a page was loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Network,
+    Cache,
+    /// An expired cached copy, used because the download failed
+    StaleCache,
+}
+
+/// One line of a tldr page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    Title(String),
+    Description(String),
+    /// What the following command does
+    Example(String),
+    /// A command as `(text, is_placeholder)` segments
+    Command(Vec<(String, bool)>),
+    Blank,
+}
+
+/// Platform directories to look in, most specific last
+fn platforms() -> &'static [&'static str] {
+    if cfg!(target_os = "macos") {
+        &["common", "osx"]
+    } else if cfg!(windows) {
+        &["common", "windows"]
+    } else {
+        &["common", "linux"]
+    }
+}
+
+fn cache_file(platform: &str, name: &str) -> Option<PathBuf> {
+    platform::cache_dir().map(|dir| dir.join("tldr").join(platform).join(format!("{}.md", name)))
+}
+
+/// The cached page for `name` and whether it has expired
+fn cached(name: &str) -> Option<(String, bool)> {
+    platforms().iter().rev().find_map(|platform| {
+        let path = cache_file(platform, name)?;
+        let content = fs::read_to_string(&path).ok()?;
+        let age = fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .unwrap_or_default();
+        Some((content, age > MAX_AGE))
+    })
+}
+
+/// The Markdown page for `name`, from the cache or downloaded
+pub fn lookup(name: &str) -> Result<(String, Source), String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("No tldr page for '{}'", name));
+    }
+    let cache = cached(name);
+    if let Some((content, false)) = &cache {
+        return Ok((content.clone(), Source::Cache));
+    }
+    match download(name) {
+        Ok(content) => Ok((content, Source::Network)),
+        Err(e) => match cache {
+            Some((content, _)) => Ok((content, Source::StaleCache)),
+            None => Err(e),
+        },
+    }
+}
+
+/// Download the page for `name` and cache it
+fn download(name: &str) -> Result<String, String> {
+    let mut missing = false;
+    for platform in platforms().iter().rev() {
+        let url = format!("{}/{}/{}.md", BASE_URL, platform, name);
+        match fetch(&url) {
+            Ok(Some(content)) => {
+                if let Some(path) = cache_file(platform, name) {
+                    // A failed cache write only costs a download next time
+                    let _ = path.parent().map(fs::create_dir_all);
+                    let _ = fs::write(&path, &content);
+                }
+                return Ok(content);
+            }
+            Ok(None) => missing = true,
+            Err(e) if !missing => return Err(format!("Could not download the tldr page: {}", e)),
+            Err(_) => {}
+        }
+    }
+    Err(format!("No tldr page for {}", name))
+}
+
+/// Body of `url`, or `None` when the server has no such page
+fn fetch(url: &str) -> io::Result<Option<String>> {
+    let output = match Command::new("curl")
+        .args(["-fsSL", "--max-time", "5", url])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+    {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Command::new("wget")
+            .args(["-q", "-T", "5", "-O", "-", url])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "neither curl nor wget is installed"))?,
+        result => result?,
+    };
+    if output.status.success() {
+        return Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()));
+    }
+    // curl exits with 22 and wget with 8 on an HTTP error such as 404
+    match output.status.code() {
+        Some(22) | Some(8) => Ok(None),
+        _ => Err(io::Error::other("network unavailable")),
+    }
+}
+
+/// Parse a tldr Markdown page
+pub fn parse(markdown: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        let parsed = if let Some(title) = line.strip_prefix("# ") {
+            Line::Title(title.to_string())
+        } else if let Some(description) = line.strip_prefix('>') {
+            Line::Description(description.trim().to_string())
+        } else if let Some(example) = line.strip_prefix("- ") {
+            Line::Example(example.to_string())
+        } else if line.starts_with('`') && line.ends_with('`') && line.len() > 1 {
+            Line::Command(placeholders(&line[1..line.len() - 1]))
+        } else if line.is_empty() {
+            // Runs of blank lines collapse into one
+            if matches!(lines.last(), Some(Line::Blank) | None) {
+                continue;
+            }
+            Line::Blank
+        } else {
+            Line::Description(line.to_string())
+        };
+        lines.push(parsed);
+    }
+    lines
+}
+
+/// Split a command into literal text and `{{placeholder}}` segments
+fn placeholders(command: &str) -> Vec<(String, bool)> {
+    let mut segments = Vec::new();
+    let mut rest = command;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        if start > 0 {
+            segments.push((rest[..start].to_string(), false));
+        }
+        segments.push((rest[start + 2..start + end].to_string(), true));
+        rest = &rest[start + end + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push((rest.to_string(), false));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let page = "# tar\n\n> Archiving utility.\n> More information: <https://www.gnu.org/software/tar>.\n\n\n- Extract an archive:\n\n`tar xf {{source.tar}} -C {{directory}}`\n";
+        assert_eq!(parse(page), vec![
+            Line::Title("tar".to_string()),
+            Line::Blank,
+            Line::Description("Archiving utility.".to_string()),
+            Line::Description("More information: <https://www.gnu.org/software/tar>.".to_string()),
+            Line::Blank,
+            Line::Example("Extract an archive:".to_string()),
+            Line::Blank,
+            Line::Command(vec![
+                ("tar xf ".to_string(), false),
+                ("source.tar".to_string(), true),
+                (" -C ".to_string(), false),
+                ("directory".to_string(), true),
+            ]),
+        ]);
+    }
+}
@@ -0,0 +1,92 @@
+//! TODO, FIXME, HACK and XXX comments for `tt todo`.
+//!
+//! The tree is searched with the same ripgrep (or grep) command as `tt
+//! search` for [`PATTERN`], the tags as whole, upper-case words. Each
+//! matching line is then read for its tag, an optional author in
+//! parentheses and the note that follows:
+//!
+//! ```text
+//! // TODO(alice): handle symlinks   ->  Todo, Some("alice"), "handle symlinks"
+//! # FIXME - breaks on empty input   ->  Fixme, None, "breaks on empty input"
+//! ```
+
+/// Regex for the tags, understood by ripgrep and `grep -E`
+pub const PATTERN: &str = r"\b(TODO|FIXME|HACK|XXX)\b";
+
+/// Kinds of notes, most pressing first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tag {
+    Fixme,
+    Xxx,
+    Hack,
+    Todo,
+}
+
+impl Tag {
+    pub const ALL: [Tag; 4] = [Tag::Fixme, Tag::Xxx, Tag::Hack, Tag::Todo];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Tag::Fixme => "FIXME",
+            Tag::Xxx => "XXX",
+            Tag::Hack => "HACK",
+            Tag::Todo => "TODO",
+        }
+    }
+}
+
+/// What a comment says
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    pub tag: Tag,
+    pub author: Option<String>,
+    pub text: String,
+}
+
+/// The first tag in `line` that stands as a word of its own, with the
+/// author and text after it
+pub fn parse(line: &str) -> Option<Note> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let (start, tag) = Tag::ALL
+        .iter()
+        .flat_map(|&tag| line.match_indices(tag.label()).map(move |(start, _)| (start, tag)))
+        .filter(|&(start, tag)| {
+            let before = line[..start].chars().next_back();
+            let after = line[start + tag.label().len()..].chars().next();
+            !before.is_some_and(is_word) && !after.is_some_and(is_word)
+        })
+        .min_by_key(|&(start, _)| start)?;
+
+    let mut rest = &line[start + tag.label().len()..];
+    let mut author = None;
+    if let Some(inner) = rest.strip_prefix('(') {
+        if let Some((name, after)) = inner.split_once(')') {
+            author = Some(name.trim().to_string()).filter(|name| !name.is_empty());
+            rest = after;
+        }
+    }
+    // Closers of block comments on the same line
+    let text = ["*/", "-->", "#}", "%}"]
+        .iter()
+        .fold(rest.trim_end(), |text, closer| text.strip_suffix(closer).unwrap_or(text).trim_end());
+    let text = text.trim_start_matches(|c: char| c == ':' || c == '-' || c.is_whitespace());
+    Some(Note { tag, author, text: text.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let note = parse("    // TODO(alice): handle symlinks").unwrap();
+        assert_eq!((note.tag, note.author.as_deref(), note.text.as_str()), (Tag::Todo, Some("alice"), "handle symlinks"));
+        let note = parse("# FIXME - breaks on empty input").unwrap();
+        assert_eq!((note.tag, note.author, note.text.as_str()), (Tag::Fixme, None, "breaks on empty input"));
+        assert_eq!(parse("/* HACK: until v2 ships */").unwrap().text, "until v2 ships");
+        assert_eq!(parse("<!-- XXX -->").unwrap(), Note { tag: Tag::Xxx, author: None, text: String::new() });
+        // The first tag counts; tags inside words do not
+        assert_eq!(parse("let TODOS = 1; // FIXME: TODO later").unwrap().tag, Tag::Fixme);
+        assert_eq!(parse("MY_TODO_LIST"), None);
+    }
+}
@@ -0,0 +1,311 @@
+//! Diff viewer for two files, or two directories file by file, with no git
+//! needed.
+
+use crate::clipboard;
+use crate::config::Settings;
+use crate::diff::{self, Change, DiffView, FileDiff, Status};
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, SelectableList, VimNav};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState},
+    Frame,
+};
+use serde_json::Value;
+use std::{fs, io, path::PathBuf};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move through the files, or scroll the diff"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("Home / End, g / G", "Top / bottom"),
+    ("] / [", "Next / previous hunk"),
+    ("s", "Side by side or unified"),
+    ("Enter", "Open the diff of the selected file (directories)"),
+    ("Tab / Shift-Tab", "Next / previous file, with a diff open"),
+    ("y", "Copy the path"),
+    ("q / Esc", "Back to the files, or quit"),
+];
+
+pub struct DiffBrowser {
+    old: PathBuf,
+    new: PathBuf,
+    /// Files that differ, when comparing directories
+    changes: Option<Vec<Change>>,
+    /// Whether the diff of the selected file fills the screen
+    opened: bool,
+    /// Lines of context around changes
+    context: usize,
+    view: DiffView,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    settings: Settings,
+}
+
+fn status_color(status: Status) -> Color {
+    match status {
+        Status::Modified => colors::warning(),
+        Status::Added => colors::success(),
+        Status::Removed => colors::danger(),
+    }
+}
+
+impl DiffBrowser {
+    /// Compare `old` with `new`; a file and a directory compare the file
+    /// with the one of the same name in the directory, as diff(1) does
+    pub fn new(old: PathBuf, new: PathBuf, context: usize, side_by_side: bool, settings: Settings) -> io::Result<Self> {
+        let (old, new) = match (old.is_dir(), new.is_dir()) {
+            (false, true) => {
+                let new = new.join(old.file_name().unwrap_or_default());
+                (old, new)
+            }
+            (true, false) => (old.join(new.file_name().unwrap_or_default()), new),
+            _ => (old, new),
+        };
+        for path in [&old, &new] {
+            if let Err(e) = fs::metadata(path) {
+                return Err(io::Error::new(e.kind(), format!("{}: {}", path.display(), e)));
+            }
+        }
+        let changes = if old.is_dir() {
+            Some(diff::compare_dirs(&old, &new, |name| settings.is_ignored(name))?)
+        } else {
+            None
+        };
+        
+        let mut browser = DiffBrowser {
+            old,
+            new,
+            changes,
+            opened: false,
+            context,
+            view: DiffView::new(side_by_side),
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::new(),
+            settings,
+        };
+        browser.load()?;
+        Ok(browser)
+    }
+    
+    /// Show the first changed file, or the diff of the two files
+    fn load(&mut self) -> io::Result<()> {
+        let Some(changes) = &self.changes else {
+            let file = diff::compare_files(Some(&self.old), Some(&self.new), &self.new.to_string_lossy(), self.context)?;
+            if file.is_empty() {
+                self.notifications.set_status("Files are identical");
+                return Ok(());
+            }
+            let (added, removed) = file.stats();
+            self.notifications.set_status(format!("+{} -{}", added, removed));
+            self.view.set_files(vec![file]);
+            return Ok(());
+        };
+        
+        let count = |status: Status| changes.iter().filter(|change| change.status == status).count();
+        if changes.is_empty() {
+            self.notifications.set_status("No differences");
+        } else {
+            let status = format!("{} modified, {} added, {} removed", count(Status::Modified), count(Status::Added), count(Status::Removed));
+            self.notifications.set_status(status);
+            self.list_state.select(Some(0));
+        }
+        self.update_view();
+        Ok(())
+    }
+    
+    fn selected_change(&self) -> Option<&Change> {
+        self.changes.as_ref()?.get(self.list_state.selected()?)
+    }
+    
+    /// The diff of a file in the two directories
+    fn file_diff(&self, change: &Change) -> io::Result<FileDiff> {
+        let old = (change.status != Status::Added).then(|| self.old.join(&change.path));
+        let new = (change.status != Status::Removed).then(|| self.new.join(&change.path));
+        diff::compare_files(old.as_deref(), new.as_deref(), &change.path.to_string_lossy(), self.context)
+    }
+    
+    /// Show the diff of the selected file
+    fn update_view(&mut self) {
+        let Some(change) = self.selected_change() else {
+            self.view.set_files(Vec::new());
+            return;
+        };
+        match self.file_diff(change) {
+            Ok(file) => self.view.set_files(vec![file]),
+            Err(e) => {
+                let message = format!("Could not read {}: {}", change.path.display(), e);
+                self.view.set_files(Vec::new());
+                self.notifications.error(message);
+            }
+        }
+    }
+    
+    fn copy_path(&mut self) {
+        let path = match self.selected_change() {
+            Some(change) => change.path.to_string_lossy().into_owned(),
+            None => self.new.to_string_lossy().into_owned(),
+        };
+        clipboard::copy_and_notify(&mut self.notifications, "path", &path);
+    }
+    
+    fn handle_key(&mut self, key: KeyEvent) {
+        let len = self.changes.as_ref().map_or(0, Vec::len);
+        if self.changes.is_some() && !self.opened {
+            if self.vim.navigate(&mut self.list_state, key.code, key.modifiers, len, self.settings.page_size) {
+                self.update_view();
+                return;
+            }
+            match key.code {
+                KeyCode::Enter if self.list_state.selected().is_some() => self.opened = true,
+                KeyCode::Char('s' | ']' | '[') => {
+                    self.view.handle_key(key.code, key.modifiers);
+                }
+                KeyCode::Char('y') => self.copy_path(),
+                KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+                _ => {}
+            }
+            return;
+        }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc if self.opened => self.opened = false,
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Tab | KeyCode::BackTab if self.opened => {
+                let code = if key.code == KeyCode::Tab { KeyCode::Down } else { KeyCode::Up };
+                tui_common::navigate_list(&mut self.list_state, code, key.modifiers, len, self.settings.page_size);
+                self.update_view();
+            }
+            KeyCode::Char('y') => self.copy_path(),
+            code => {
+                self.view.handle_key(code, key.modifiers);
+            }
+        }
+    }
+    
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(area);
+        
+        if self.changes.is_some() && !self.opened {
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .split(chunks[0]);
+            self.render_list(f, panes[0]);
+            self.render_diff(f, panes[1]);
+        } else {
+            self.render_diff(f, chunks[0]);
+        }
+        self.render_status_bar(f, chunks[1]);
+    }
+    
+    fn render_list(&mut self, f: &mut Frame, area: Rect) {
+        let changes = self.changes.as_deref().unwrap_or_default();
+        let items: Vec<Line> = changes
+            .iter()
+            .map(|change| Line::from(vec![
+                Span::styled(format!("{} ", change.status.letter()), Style::default().fg(status_color(change.status))),
+                Span::styled(change.path.display().to_string(), Style::default().fg(colors::text())),
+            ]))
+            .collect();
+        
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Changes ({})", changes.len()))
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    fn render_diff(&mut self, f: &mut Frame, area: Rect) {
+        let (title, border) = match self.selected_change() {
+            Some(change) if self.opened => (change.path.display().to_string(), colors::primary()),
+            Some(change) => (change.path.display().to_string(), colors::secondary()),
+            None => (format!("{} → {}", self.old.display(), self.new.display()), colors::primary()),
+        };
+        let block = Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(border));
+        self.view.render(f, area, block);
+    }
+    
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let help_text = match &self.changes {
+            Some(_) if self.opened => "j/k Scroll • ]/[ Hunk • Tab Next file • s Side by side • ? Help • Esc Back",
+            Some(_) => "Enter Open • ]/[ Hunk • s Side by side • y Copy path • ? Help • q Quit",
+            None => "j/k Scroll • ]/[ Hunk • s Side by side • y Copy path • ? Help • q Quit",
+        };
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the diff viewer
+    pub fn run(&mut self) -> io::Result<()> {
+        tui_common::run_app(self)
+    }
+    
+    /// Print the diff instead of running the TUI: for two files each line
+    /// of the unified diff, for two directories the files that differ with
+    /// the lines added and removed (none for binary files)
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        plain::check(&self.notifications)?;
+        let Some(changes) = &self.changes else {
+            let (old, new) = (self.old.to_string_lossy(), self.new.to_string_lossy());
+            return plain::print(format, self.view.files().iter().flat_map(|file| file.records(&old, &new)));
+        };
+        let rows: Vec<(Status, String, io::Result<FileDiff>)> = changes
+            .iter()
+            .map(|change| (change.status, change.path.to_string_lossy().into_owned(), self.file_diff(change)))
+            .collect();
+        plain::print(format, rows.into_iter().map(|(status, path, file)| {
+            let stats = file.ok().filter(|file| !file.binary).map(|file| file.stats());
+            vec![
+                ("status", Value::from(status.label())),
+                ("path", Value::from(path)),
+                ("added", Value::from(stats.map(|(added, _)| added))),
+                ("removed", Value::from(stats.map(|(_, removed)| removed))),
+            ]
+        }))
+    }
+}
+
+impl App for DiffBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        if let AppEvent::Key(key) = event {
+            self.handle_key(key);
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+/// Run the diff viewer on `old` and `new`, or print the files that differ
+/// in `output`
+pub fn run(old: PathBuf, new: PathBuf, context: usize, side_by_side: bool, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut browser = DiffBrowser::new(old, new, context, side_by_side, settings)?;
+    match output {
+        Some(format) => browser.print(format),
+        None => browser.run(),
+    }
+}
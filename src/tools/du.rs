@@ -0,0 +1,425 @@
+//! Disk usage analyzer with drill-down and delete.
+
+use crate::config::Settings;
+use crate::disk_usage::{self, DuNode, ScanEvent};
+use crate::file_ops;
+use crate::notify::Notifications;
+use crate::palette::Context;
+use crate::plain::{self, Format};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, SelectableList, VimNav};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, TryRecvError},
+};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("Home / End, gg / G", "First / last item"),
+    ("Enter / →", "Open the directory"),
+    ("← / Backspace", "Go up a level"),
+    ("d / Delete", "Delete (then t to trash, D permanently)"),
+    ("r", "Rescan"),
+    ("Esc / q", "Quit"),
+];
+
+/// Width of the usage bar drawn before each entry
+const BAR_WIDTH: usize = 20;
+
+enum Mode {
+    Normal,
+    ConfirmDelete,
+    Error(String),
+}
+
+pub struct DiskUsage {
+    root_path: PathBuf,
+    /// Scan result; None while the scan is running
+    root: Option<DuNode>,
+    scan: Option<Receiver<ScanEvent>>,
+    /// Files and bytes counted so far by the running scan
+    progress: (u64, u64),
+    /// Child indices from the root to the directory being shown
+    stack: Vec<usize>,
+    /// Selection to restore when leaving each directory on the stack
+    selections: Vec<usize>,
+    list_state: ListState,
+    vim: VimNav,
+    mode: Mode,
+    should_quit: bool,
+    notifications: Notifications,
+    settings: Settings,
+}
+
+impl DiskUsage {
+    /// Create an analyzer and start scanning `path`
+    pub fn new(path: PathBuf, settings: Settings) -> io::Result<Self> {
+        let root_path = path.canonicalize()?;
+        let mut analyzer = DiskUsage {
+            root_path,
+            root: None,
+            scan: None,
+            progress: (0, 0),
+            stack: Vec::new(),
+            selections: Vec::new(),
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            mode: Mode::Normal,
+            should_quit: false,
+            notifications: Notifications::new(),
+            settings,
+        };
+        
+        analyzer.start_scan();
+        Ok(analyzer)
+    }
+    
+    /// (Re)scan the root directory in the background
+    fn start_scan(&mut self) {
+        self.scan = Some(disk_usage::spawn_scan(self.root_path.clone()));
+        self.progress = (0, 0);
+        self.notifications.set_status(format!("Scanning {}...", self.root_path.display()));
+    }
+    
+    /// Drain events from a running scan
+    fn poll_scan(&mut self) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+        
+        loop {
+            match scan.try_recv() {
+                Ok(ScanEvent::Progress { files, bytes }) => self.progress = (files, bytes),
+                Ok(ScanEvent::Finished(root)) => {
+                    self.notifications.set_status(format!(
+                        "{} in {} files",
+                        tui_common::format_size(root.size),
+                        root.files
+                    ));
+                    if root.error {
+                        self.notifications.error("Some directories could not be read; their sizes are too low");
+                    }
+                    self.root = Some(root);
+                    self.scan = None;
+                    self.restore_position();
+                    return;
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.scan = None;
+                    return;
+                }
+            }
+        }
+    }
+    
+    /// After a rescan, keep as much of the previous location as still exists
+    fn restore_position(&mut self) {
+        let Some(root) = &self.root else {
+            return;
+        };
+        while !self.stack.is_empty() && root.descendant(&self.stack).is_none_or(|node| !node.is_dir) {
+            self.stack.pop();
+            self.selections.pop();
+        }
+        let len = self.current().map_or(0, |node| node.children.len());
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state.select((len > 0).then(|| selected.min(len - 1)));
+    }
+    
+    /// Directory being shown
+    fn current(&self) -> Option<&DuNode> {
+        self.root.as_ref().and_then(|root| root.descendant(&self.stack))
+    }
+    
+    fn selected(&self) -> Option<&DuNode> {
+        let index = self.list_state.selected()?;
+        self.current()?.children.get(index)
+    }
+    
+    /// Drill down into the selected directory
+    fn enter(&mut self) {
+        let Some(index) = self.list_state.selected() else {
+            return;
+        };
+        match self.selected() {
+            Some(node) if node.is_dir => {
+                let has_children = !node.children.is_empty();
+                self.stack.push(index);
+                self.selections.push(index);
+                self.list_state.select(has_children.then_some(0));
+            }
+            _ => {}
+        }
+    }
+    
+    /// Go back to the parent directory, reselecting the one we came from
+    fn leave(&mut self) {
+        if self.stack.pop().is_some() {
+            self.list_state.select(self.selections.pop());
+        }
+    }
+    
+    /// Delete the selected entry, via the trash or permanently
+    fn delete_selected(&mut self, to_trash: bool) {
+        self.mode = Mode::Normal;
+        let Some(index) = self.list_state.selected() else {
+            return;
+        };
+        let Some(path) = self.selected().map(|node| node.path.clone()) else {
+            return;
+        };
+        
+        let result = if to_trash {
+            file_ops::trash(&path).map(|_| ())
+        } else {
+            file_ops::remove(&path)
+        };
+        if let Err(e) = result {
+            self.mode = Mode::Error(format!("Could not delete {}: {}", path.display(), e));
+            return;
+        }
+        
+        let mut indices = self.stack.clone();
+        indices.push(index);
+        let freed = self.root.as_mut().and_then(|root| root.remove(&indices)).map_or(0, |node| node.size);
+        let len = self.current().map_or(0, |node| node.children.len());
+        self.list_state.select((len > 0).then(|| index.min(len - 1)));
+        self.notifications.success(format!(
+            "{} {} (freed {})",
+            if to_trash { "Moved to trash:" } else { "Deleted" },
+            path.display(),
+            tui_common::format_size(freed)
+        ));
+    }
+    
+    /// Handle keyboard input
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        match self.mode {
+            Mode::ConfirmDelete => {
+                match key.code {
+                    KeyCode::Char('t') | KeyCode::Char('T') | KeyCode::Enter => self.delete_selected(true),
+                    KeyCode::Char('D') => self.delete_selected(false),
+                    KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                        self.mode = Mode::Normal;
+                        self.notifications.info("Delete cancelled");
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            Mode::Error(_) => {
+                self.mode = Mode::Normal;
+                return Ok(());
+            }
+            Mode::Normal => {}
+        }
+        
+        let len = self.current().map_or(0, |node| node.children.len());
+        if self.vim.navigate(&mut self.list_state, key.code, key.modifiers, len, self.settings.page_size) {
+            return Ok(());
+        }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_quit = true;
+            }
+            KeyCode::Enter | KeyCode::Right => self.enter(),
+            KeyCode::Left | KeyCode::Backspace => self.leave(),
+            KeyCode::Char('r') if self.scan.is_none() => self.start_scan(),
+            KeyCode::Char('d') | KeyCode::Delete if self.selected().is_some() => {
+                self.mode = Mode::ConfirmDelete;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// Render the analyzer
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(area);
+        
+        self.render_usage_list(f, chunks[0]);
+        self.render_status_bar(f, area);
+        self.render_popup(f);
+    }
+    
+    /// Bar chart of the current directory's children, largest first
+    fn render_usage_list(&mut self, f: &mut Frame, area: Rect) {
+        let Some(current) = self.current() else {
+            let (files, bytes) = self.progress;
+            let paragraph = Paragraph::new(format!(
+                "Scanning... {} files, {}",
+                files,
+                tui_common::format_size(bytes)
+            ))
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Disk Usage: {}", self.root_path.display()))
+                .border_style(Style::default().fg(colors::primary())));
+            f.render_widget(paragraph, area);
+            return;
+        };
+        
+        let total = current.size.max(1);
+        let items: Vec<Line> = current.children
+            .iter()
+            .map(|node| {
+                let fraction = node.size as f64 / total as f64;
+                let filled = (fraction * BAR_WIDTH as f64).round() as usize;
+                let name_style = if node.is_dir {
+                    Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(colors::text())
+                };
+                
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{:>10} ", tui_common::format_size(node.size)),
+                        Style::default().fg(colors::secondary()),
+                    ),
+                    Span::styled(format!("{:>5.1}% ", fraction * 100.0), Style::default().fg(colors::muted())),
+                    Span::styled("█".repeat(filled), Style::default().fg(colors::primary())),
+                    Span::styled(
+                        format!("{} ", "░".repeat(BAR_WIDTH - filled)),
+                        Style::default().fg(colors::muted()),
+                    ),
+                    Span::styled(
+                        format!("{}{}", node.name, if node.is_dir { "/" } else { "" }),
+                        name_style,
+                    ),
+                ];
+                if node.error {
+                    spans.push(Span::styled(" (unreadable)", Style::default().fg(colors::danger())));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        
+        let title = format!(
+            "Disk Usage: {} ({}, {} files)",
+            current.path.display(),
+            tui_common::format_size(current.size),
+            current.files
+        );
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    /// Render the delete confirmation or error popup, if any
+    fn render_popup(&self, f: &mut Frame) {
+        match &self.mode {
+            Mode::Normal => {}
+            Mode::ConfirmDelete => {
+                let Some(node) = self.selected() else {
+                    return;
+                };
+                let lines = vec![
+                    Line::from(Span::styled(
+                        format!("Delete '{}' ({})?", node.name, tui_common::format_size(node.size)),
+                        Style::default().fg(colors::danger()).add_modifier(Modifier::BOLD)
+                    )),
+                    Line::from(""),
+                    Line::from("T/Enter Move to trash • D Delete permanently • N/Esc Cancel"),
+                ];
+                tui_common::render_message_popup(f, "Confirm Delete", lines, colors::danger());
+            }
+            Mode::Error(message) => {
+                let lines = vec![
+                    Line::from(Span::styled(message.as_str(), Style::default().fg(colors::danger()))),
+                    Line::from(""),
+                    Line::from(Span::styled("Press any key to continue", Style::default().fg(colors::muted()))),
+                ];
+                tui_common::render_message_popup(f, "Error", lines, colors::danger());
+            }
+        }
+    }
+    
+    /// Render status bar
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
+        
+        let help_text = "Enter/→ Open • ← Up • d Delete • r Rescan • ? Help • Esc Quit";
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the disk usage analyzer
+    pub fn run(&mut self) -> io::Result<()> {
+        tui_common::run_app(self)
+    }
+}
+
+impl App for DiskUsage {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            // The progress changes for as long as the scan runs
+            AppEvent::Tick if self.scan.is_some() => {
+                self.poll_scan();
+                return Ok(Action::Redraw);
+            }
+            AppEvent::Tick => {}
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn context(&self) -> Context {
+        Context {
+            dir: self.current().map(|node| node.path.clone()),
+            file: self.selected().filter(|node| !node.is_dir).map(|node| node.path.clone()),
+            ..Context::default()
+        }
+    }
+}
+
+/// Run the disk usage analyzer on `path`, or print its entries in `output`
+pub fn run(path: PathBuf, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    if let Some(format) = output {
+        return print(&path, format);
+    }
+    let mut analyzer = DiskUsage::new(path, settings)?;
+    analyzer.run()
+}
+
+/// Print the entries of `path` with their total sizes, largest first
+fn print(path: &Path, format: Format) -> io::Result<()> {
+    let root = disk_usage::scan(&path.canonicalize()?, &mut |_| {});
+    plain::print(format, root.children.iter().map(|node| vec![
+        ("path", Value::from(node.path.to_string_lossy())),
+        ("size", Value::from(node.size)),
+        ("files", Value::from(node.files)),
+        ("dir", Value::from(node.is_dir)),
+    ]))
+}
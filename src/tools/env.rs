@@ -1,39 +1,147 @@
 //! Environment variable browser.
 
-use crate::tui_common::{self, colors};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crate::clipboard;
+use crate::config::Settings;
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::platform;
+use crate::session::{self, Resume, Session};
+use crate::state;
+use crate::env_vars::{self, Assignment, PathEntry, Syntax};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, NavKey, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
-    text::Line,
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
-    Frame, Terminal,
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph, Wrap},
+    Frame,
 };
+use serde_json::Value;
 use std::{
+    collections::{BTreeMap, BTreeSet},
     env,
+    fs,
     io,
-    time::Duration,
+    path::{Path, PathBuf},
 };
 
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Filter variables"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Home / End", "First / last item"),
+    ("Enter", "Fold or unfold the group"),
+    ("Ctrl-G", "Group by prefix"),
+    ("Ctrl-E", "Edit the value"),
+    ("Ctrl-N", "New variable"),
+    ("Ctrl-D, Delete in normal mode", "Unset"),
+    ("y / Y", "Copy the value / an export statement (normal mode)"),
+    ("Tab / Shift-Tab", "Next / previous PATH entry"),
+    ("Ctrl-Y", "Copy the PATH entry"),
+    ("Ctrl-R", "Reveal secret values"),
+    ("Ctrl-W", "Show where it is set"),
+    ("Ctrl-A", "Adopt compared values (--file, --diff)"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
+enum Mode {
+    Normal,
+    /// Editing the value of a variable (new or existing)
+    Edit { key: String, input: TextInput },
+    /// Naming a new variable
+    NewName(TextInput),
+    ConfirmUnset,
+}
+
+/// Variables to compare the environment against: a `--file` dotenv file
+/// or a `--diff` snapshot
+struct Baseline {
+    /// Shown in titles: the file name or `snapshot NAME`
+    label: String,
+    vars: BTreeMap<String, String>,
+    /// A snapshot holds the whole environment, so variables it lacks were
+    /// added since; a dotenv file only covers some
+    complete: bool,
+}
+
+/// How a variable compares with the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    /// Set now but not in the snapshot
+    Added,
+    /// In the baseline but not in the environment
+    Missing,
+    /// Set to a different value than in the baseline
+    Differs,
+    Same,
+}
+
+/// A line of the variable list
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Row {
+    /// Group heading with the number of variables in the group
+    Header(String, usize),
+    /// Index into `filtered_vars`
+    Var(usize),
+}
+
 pub struct EnvBrowser {
     env_vars: Vec<(String, String)>,
     filtered_vars: Vec<(String, String)>,
+    /// What the list shows: every filtered variable, or groups of them
+    rows: Vec<Row>,
+    grouped: bool,
+    /// Headings of the groups whose variables are hidden
+    collapsed: BTreeSet<String>,
     list_state: ListState,
+    vim: VimNav,
     search_query: String,
     should_quit: bool,
-    status_message: String,
+    notifications: Notifications,
+    mode: Mode,
+    /// Changes to hand back to the shell on exit: a new value, or `None` to unset
+    changes: BTreeMap<String, Option<String>>,
+    /// Print the changes as statements in this syntax on exit
+    apply: Option<Syntax>,
+    /// Highlighted entry of a `PATH`-style value, for the variable it belongs to
+    path_cursor: Option<(String, usize)>,
+    baseline: Option<Baseline>,
+    /// Show credential-like values instead of masking them (`--show-secrets`)
+    show_secrets: bool,
+    /// Secret variables revealed one by one
+    revealed: BTreeSet<String>,
+    /// Startup file lines setting a variable, and how many files were searched
+    provenance: Option<(String, Vec<Assignment>, usize)>,
+    settings: Settings,
 }
 
 impl EnvBrowser {
     /// Create a new environment browser instance
-    pub fn new() -> io::Result<Self> {
+    pub fn new(settings: Settings) -> io::Result<Self> {
         let mut browser = EnvBrowser {
             env_vars: Vec::new(),
             filtered_vars: Vec::new(),
+            rows: Vec::new(),
+            grouped: state::get_bool("env.grouped", false),
+            collapsed: BTreeSet::new(),
             list_state: ListState::default(),
+            vim: VimNav::default(),
             search_query: String::new(),
             should_quit: false,
-            status_message: "Loading environment variables...".to_string(),
+            notifications: Notifications::with_status("Loading environment variables..."),
+            mode: Mode::Normal,
+            changes: BTreeMap::new(),
+            apply: None,
+            path_cursor: None,
+            baseline: None,
+            show_secrets: false,
+            revealed: BTreeSet::new(),
+            provenance: None,
+            settings,
         };
         
         browser.load_env_vars();
@@ -46,97 +154,477 @@ impl EnvBrowser {
     fn load_env_vars(&mut self) {
         self.env_vars = env::vars().collect();
         self.env_vars.sort_by(|a, b| a.0.cmp(&b.0));
-        self.status_message = format!("Found {} environment variables", self.env_vars.len());
+        self.notifications.set_status(format!("Found {} environment variables", self.env_vars.len()));
+    }
+    
+    /// Load `path` as a dotenv file to compare the environment against
+    fn load_dotenv(&mut self, path: PathBuf) -> io::Result<()> {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        let vars: BTreeMap<String, String> = env_vars::parse_dotenv(&content).into_iter().collect();
+        self.notifications.set_status(format!("Loaded {} variable(s) from {}", vars.len(), path.display()));
+        let label = path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        self.baseline = Some(Baseline { label, vars, complete: false });
+        self.update_filter();
+        Ok(())
+    }
+    
+    /// Load the snapshot `name` to show what changed since it was taken
+    fn load_snapshot(&mut self, name: &str) -> io::Result<()> {
+        let vars = env_vars::load_snapshot(name)?;
+        self.baseline = Some(Baseline { label: format!("snapshot {}", name), vars, complete: true });
+        self.update_filter();
+        let (added, changed, removed) = self.differences();
+        self.notifications.set_status(format!("Since {}: {} added, {} changed, {} removed", name, added, changed, removed));
+        Ok(())
+    }
+    
+    fn is_set(&self, key: &str) -> bool {
+        self.env_vars.iter().any(|(name, _)| name == key)
+    }
+    
+    /// How `key` compares with the baseline, if it has something to say
+    fn comparison(&self, key: &str) -> Option<Comparison> {
+        let baseline = self.baseline.as_ref()?;
+        // `$_` is the shell's last argument and changes with every command
+        if key == "_" && baseline.complete {
+            return None;
+        }
+        let current = self.env_vars.iter().find(|(name, _)| name == key).map(|(_, value)| value);
+        Some(match (baseline.vars.get(key), current) {
+            (Some(_), None) => Comparison::Missing,
+            (Some(old), Some(value)) if old != value => Comparison::Differs,
+            (Some(_), Some(_)) => Comparison::Same,
+            (None, Some(_)) if baseline.complete => Comparison::Added,
+            (None, _) => return None,
+        })
+    }
+    
+    /// Whether `key` differs from the baseline in any way
+    fn differs(&self, key: &str) -> bool {
+        matches!(self.comparison(key), Some(Comparison::Added | Comparison::Missing | Comparison::Differs))
+    }
+    
+    /// Number of variables added, changed and missing relative to the baseline
+    fn differences(&self) -> (usize, usize, usize) {
+        self.all_vars().iter().fold((0, 0, 0), |(added, changed, missing), (key, _)| match self.comparison(key) {
+            Some(Comparison::Added) => (added + 1, changed, missing),
+            Some(Comparison::Differs) => (added, changed + 1, missing),
+            Some(Comparison::Missing) => (added, changed, missing + 1),
+            _ => (added, changed, missing),
+        })
+    }
+    
+    /// The environment plus the baseline variables it lacks, by name
+    fn all_vars(&self) -> Vec<(String, String)> {
+        let mut vars = self.env_vars.clone();
+        if let Some(baseline) = &self.baseline {
+            vars.extend(
+                baseline.vars.iter()
+                    .filter(|(key, _)| !self.is_set(key))
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+            vars.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        vars
+    }
+    
+    /// Queue the changes that make the environment match the baseline
+    fn adopt_baseline(&mut self) {
+        let Some(baseline) = &self.baseline else {
+            self.notifications.error("Nothing to compare with (use --file or --diff)");
+            return;
+        };
+        let label = baseline.label.clone();
+        let differences: Vec<(String, Option<String>)> = self
+            .all_vars()
+            .into_iter()
+            .filter(|(key, _)| self.differs(key))
+            .map(|(key, _)| {
+                let value = baseline.vars.get(&key).cloned();
+                (key, value)
+            })
+            .collect();
+        if differences.is_empty() {
+            self.notifications.info(format!("Environment already matches {}", label));
+            return;
+        }
+        let count = differences.len();
+        for (key, value) in differences {
+            match value {
+                Some(value) => self.set_var(key, value),
+                None => self.unset_var(&key),
+            }
+        }
+        self.notifications.success(format!(
+            "Queued {} change(s) from {} ({} pending change(s))",
+            count, label, self.changes.len()
+        ));
     }
     
     /// Update filtered variables based on search query
     fn update_filter(&mut self) {
+        let mut vars = self.all_vars();
+        // A snapshot diff lists only what changed
+        if self.baseline.as_ref().is_some_and(|baseline| baseline.complete) {
+            vars.retain(|(key, _)| self.differs(key));
+        }
         if self.search_query.is_empty() {
-            self.filtered_vars = self.env_vars.clone();
+            self.filtered_vars = vars;
         } else {
             let query = self.search_query.to_lowercase();
-            self.filtered_vars = self.env_vars
-                .iter()
+            self.filtered_vars = vars
+                .into_iter()
                 .filter(|(key, value)| {
+                    // Masked values are not searched, or the filter would leak them
                     key.to_lowercase().contains(&query) ||
-                    value.to_lowercase().contains(&query)
+                    (!self.is_masked(key) && value.to_lowercase().contains(&query))
                 })
-                .cloned()
                 .collect();
         }
+        self.build_rows();
         
         // Reset selection
-        if !self.filtered_vars.is_empty() {
+        if !self.rows.is_empty() {
             self.list_state.select(Some(0));
         } else {
             self.list_state.select(None);
         }
     }
     
+    /// Lay out the filtered variables as list rows, grouped or not
+    fn build_rows(&mut self) {
+        if !self.grouped {
+            self.rows = (0..self.filtered_vars.len()).map(Row::Var).collect();
+            return;
+        }
+        let keys: Vec<&str> = self.filtered_vars.iter().map(|(key, _)| key.as_str()).collect();
+        let mut rows = Vec::new();
+        for (name, indices) in env_vars::group(&keys) {
+            let collapsed = self.collapsed.contains(&name);
+            rows.push(Row::Header(name, indices.len()));
+            if !collapsed {
+                rows.extend(indices.into_iter().map(Row::Var));
+            }
+        }
+        self.rows = rows;
+    }
+    
+    fn selected_row(&self) -> Option<&Row> {
+        self.list_state.selected().and_then(|index| self.rows.get(index))
+    }
+    
+    fn selected_var(&self) -> Option<&(String, String)> {
+        match self.selected_row()? {
+            Row::Var(index) => self.filtered_vars.get(*index),
+            Row::Header(..) => None,
+        }
+    }
+    
+    /// Select `key` in the list if it is shown
+    fn select_key(&mut self, key: &str) {
+        let position = self.rows.iter().position(|row| match row {
+            Row::Var(index) => self.filtered_vars[*index].0 == key,
+            Row::Header(..) => false,
+        });
+        if let Some(index) = position {
+            self.list_state.select(Some(index));
+        }
+    }
+    
+    /// Switch between the flat and the grouped list, keeping the selection
+    fn toggle_grouped(&mut self) {
+        let selected = self.selected_var().map(|(key, _)| key.clone());
+        self.grouped = !self.grouped;
+        self.update_filter();
+        if let Some(key) = selected {
+            self.select_key(&key);
+        }
+        let label = if self.grouped { "Grouped by prefix" } else { "Not grouped" };
+        match state::set("env.grouped", &self.grouped.to_string()) {
+            Ok(()) => self.notifications.info(label),
+            Err(e) => self.notifications.error(format!("{} (not saved: {})", label, e)),
+        }
+    }
+    
+    /// Collapse or expand the group whose heading is selected
+    fn toggle_collapsed(&mut self) {
+        let Some(Row::Header(name, _)) = self.selected_row().cloned() else {
+            return;
+        };
+        if !self.collapsed.remove(&name) {
+            self.collapsed.insert(name);
+        }
+        // Rows above the heading are unchanged, so the selection stays on it
+        self.build_rows();
+    }
+    
+    /// Record `key` = `value` (or its removal) as a pending change; changes
+    /// that restore the original environment are dropped
+    fn record_change(&mut self, key: &str, value: Option<String>) {
+        if env::var(key).ok() == value {
+            self.changes.remove(key);
+        } else {
+            self.changes.insert(key.to_string(), value);
+        }
+    }
+    
+    /// Set `key` to `value` in the browser and queue the export
+    fn set_var(&mut self, key: String, value: String) {
+        match self.env_vars.iter_mut().find(|(name, _)| *name == key) {
+            Some(var) => var.1 = value.clone(),
+            None => {
+                self.env_vars.push((key.clone(), value.clone()));
+                self.env_vars.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+        }
+        self.record_change(&key, Some(value));
+        self.update_filter();
+        self.select_key(&key);
+        self.notifications.success(format!("Set {} ({} pending change(s))", key, self.changes.len()));
+    }
+    
+    /// Remove `key` from the browser and queue the unset
+    fn unset_var(&mut self, key: &str) {
+        self.env_vars.retain(|(name, _)| name != key);
+        self.record_change(key, None);
+        self.update_filter();
+    }
+    
+    /// Remove the selected variable and queue the unset
+    fn unset_selected(&mut self) {
+        let Some(key) = self.selected_var().map(|(key, _)| key.clone()) else {
+            return;
+        };
+        let selected = self.list_state.selected();
+        self.unset_var(&key);
+        if let Some(index) = selected {
+            if !self.rows.is_empty() {
+                self.list_state.select(Some(index.min(self.rows.len() - 1)));
+            }
+        }
+        self.notifications.success(format!("Unset {} ({} pending change(s))", key, self.changes.len()));
+    }
+    
+    /// Whether the value of `key` is hidden behind a mask
+    fn is_masked(&self, key: &str) -> bool {
+        !self.show_secrets && env_vars::is_secret(key) && !self.revealed.contains(key)
+    }
+    
+    /// `value` of `key` as it may be shown on screen
+    fn shown_value(&self, key: &str, value: &str) -> String {
+        if self.is_masked(key) {
+            env_vars::mask(value)
+        } else {
+            value.to_string()
+        }
+    }
+    
+    /// Reveal or mask the selected secret again
+    fn toggle_reveal(&mut self) {
+        let Some(key) = self.selected_var().map(|(key, _)| key.clone()) else {
+            return;
+        };
+        if !env_vars::is_secret(&key) || self.show_secrets {
+            self.notifications.info(format!("{} is not masked", key));
+        } else if self.revealed.remove(&key) {
+            self.notifications.info(format!("Masked {}", key));
+        } else {
+            self.notifications.info(format!("Revealed {}", key));
+            self.revealed.insert(key);
+        }
+    }
+    
+    /// Search the startup files for the selected variable, or hide the
+    /// results when they are already shown
+    fn toggle_provenance(&mut self) {
+        let Some(key) = self.selected_var().map(|(key, _)| key.clone()) else {
+            return;
+        };
+        if self.provenance_for(&key).is_some() {
+            self.provenance = None;
+            return;
+        }
+        let (assignments, searched) = env_vars::where_set(&key);
+        self.notifications.info(format!("{} line(s) set {} in {} startup file(s)", assignments.len(), key, searched));
+        self.provenance = Some((key, assignments, searched));
+    }
+    
+    /// Startup file search results for `key`, if they are shown
+    fn provenance_for(&self, key: &str) -> Option<(&[Assignment], usize)> {
+        match &self.provenance {
+            Some((shown, assignments, searched)) if shown == key => Some((assignments, *searched)),
+            _ => None,
+        }
+    }
+    
+    /// Entries of the selected variable when its value is a `PATH`-style list
+    fn path_entries(&self) -> Option<Vec<PathEntry>> {
+        let (key, value) = self.selected_var()?;
+        if self.is_masked(key) || !env_vars::is_path_list(key, value) {
+            return None;
+        }
+        Some(env_vars::path_entries(value, |path| expand_home(path).exists()))
+    }
+    
+    /// Index of the highlighted path entry of the selected variable
+    fn path_cursor(&self) -> usize {
+        match (&self.path_cursor, self.selected_var()) {
+            (Some((key, index)), Some((selected, _))) if key == selected => *index,
+            _ => 0,
+        }
+    }
+    
+    /// Move the highlighted path entry, wrapping around
+    fn move_path_cursor(&mut self, forward: bool) {
+        let (Some(entries), Some((key, _))) = (self.path_entries(), self.selected_var()) else {
+            return;
+        };
+        let len = entries.len();
+        let index = self.path_cursor();
+        let next = if forward { (index + 1) % len } else { (index + len - 1) % len };
+        self.path_cursor = Some((key.clone(), next));
+    }
+    
+    /// Copy the highlighted path entry to the clipboard
+    fn copy_path_entry(&mut self) {
+        let Some(entries) = self.path_entries() else {
+            self.notifications.error("Not a path list");
+            return;
+        };
+        let path = &entries[self.path_cursor().min(entries.len() - 1)].path;
+        clipboard::copy_and_notify(&mut self.notifications, path, path);
+    }
+    
+    /// Copy the selected value, or the statement exporting it for `$SHELL`,
+    /// to the clipboard
+    fn copy_selected(&mut self, statement: bool) {
+        let Some((key, value)) = self.selected_var() else {
+            return;
+        };
+        let (what, text) = if statement {
+            ("export of", Syntax::for_shell(None).set(key, value))
+        } else {
+            ("value of", value.clone())
+        };
+        let what = format!("{} {}", what, key);
+        clipboard::copy_and_notify(&mut self.notifications, &what, &text);
+    }
+    
+    /// Shell statements for the pending changes
+    fn statements(&self, syntax: Syntax) -> Vec<String> {
+        self.changes
+            .iter()
+            .map(|(key, value)| match value {
+                Some(value) => syntax.set(key, value),
+                None => syntax.unset(key),
+            })
+            .collect()
+    }
+    
     /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page down
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.filtered_vars.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                        }
-                    }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page up
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.filtered_vars.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                        }
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal_input(key.code, key.modifiers),
+            Mode::Edit { key: name, mut input } => match key.code {
+                KeyCode::Esc => self.notifications.info("Cancelled"),
+                KeyCode::Enter => self.set_var(name, input.value().to_string()),
+                code => {
+                    if !ctrl {
+                        input.handle_key(code);
                     }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected > 0 {
-                                self.list_state.select(Some(selected - 1));
-                            }
-                        }
-                    }
-                    KeyCode::Down => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected + 1 < self.filtered_vars.len() {
-                                self.list_state.select(Some(selected + 1));
-                            }
-                        } else if !self.filtered_vars.is_empty() {
-                            self.list_state.select(Some(0));
-                        }
-                    }
-                    KeyCode::Char(c) => {
-                        self.search_query.push(c);
-                        self.update_filter();
+                    self.mode = Mode::Edit { key: name, input };
+                }
+            },
+            Mode::NewName(mut input) => match key.code {
+                KeyCode::Esc => self.notifications.info("Cancelled"),
+                KeyCode::Enter => {
+                    let name = input.value().trim().to_string();
+                    if !env_vars::is_valid_name(&name) {
+                        self.notifications.error(format!("'{}' is not a valid variable name", name));
+                        self.mode = Mode::NewName(input);
+                    } else {
+                        // An existing name edits that variable instead
+                        let value = self.env_vars
+                            .iter()
+                            .find(|(key, _)| *key == name)
+                            .map(|(_, value)| value.clone())
+                            .or_else(|| self.baseline.as_ref().and_then(|baseline| baseline.vars.get(&name).cloned()))
+                            .unwrap_or_default();
+                        self.mode = Mode::Edit { key: name, input: TextInput::new(value) };
                     }
-                    KeyCode::Backspace => {
-                        self.search_query.pop();
-                        self.update_filter();
+                }
+                code => {
+                    if !ctrl {
+                        input.handle_key(code);
                     }
-                    _ => {}
+                    self.mode = Mode::NewName(input);
                 }
-            }
+            },
+            Mode::ConfirmUnset => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => self.unset_selected(),
+                _ => self.notifications.info("Cancelled"),
+            },
         }
         Ok(())
     }
     
+    /// Handle keys in the variable list
+    fn handle_normal_input(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        if self.vim.filter_key(&mut self.list_state, code, modifiers, self.rows.len(), self.settings.page_size) != NavKey::Unused {
+            return;
+        }
+        
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('e') if ctrl => {
+                if let Some((name, value)) = self.selected_var().cloned() {
+                    self.mode = Mode::Edit { key: name, input: TextInput::new(value) };
+                }
+            }
+            KeyCode::Char('n') if ctrl => {
+                self.mode = Mode::NewName(TextInput::default());
+            }
+            KeyCode::Char('d') if ctrl && self.selected_var().is_some_and(|(key, _)| self.is_set(key)) => {
+                self.mode = Mode::ConfirmUnset;
+            }
+            KeyCode::Delete if !self.vim.inserting() && self.selected_var().is_some_and(|(key, _)| self.is_set(key)) => {
+                self.mode = Mode::ConfirmUnset;
+            }
+            KeyCode::Char('g') if ctrl => self.toggle_grouped(),
+            KeyCode::Char('a') if ctrl => self.adopt_baseline(),
+            KeyCode::Char('r') if ctrl => self.toggle_reveal(),
+            KeyCode::Char('w') if ctrl => self.toggle_provenance(),
+            KeyCode::Enter => self.toggle_collapsed(),
+            KeyCode::Tab => self.move_path_cursor(true),
+            KeyCode::BackTab => self.move_path_cursor(false),
+            KeyCode::Char('y') if ctrl => self.copy_path_entry(),
+            KeyCode::Char('y') if !self.vim.inserting() => self.copy_selected(false),
+            KeyCode::Char('Y') if !self.vim.inserting() => self.copy_selected(true),
+            KeyCode::Char(c) if !ctrl && self.vim.inserting() => {
+                self.search_query.push(c);
+                self.update_filter();
+            }
+            KeyCode::Backspace if self.vim.inserting() => {
+                self.search_query.pop();
+                self.update_filter();
+            }
+            _ => {}
+        }
+    }
+    
     /// Render the environment browser interface
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(f.area());
+            .split(area);
         
         // Left panel - variable list
         self.render_var_list(f, chunks[0]);
@@ -145,111 +633,406 @@ impl EnvBrowser {
         self.render_value_preview(f, chunks[1]);
         
         // Status bar
-        self.render_status_bar(f);
+        self.render_status_bar(f, area);
+        
+        match &self.mode {
+            Mode::Normal => {}
+            Mode::Edit { key, input } => {
+                let title = if env::var_os(key).is_some() { "Edit Variable" } else { "New Variable" };
+                tui_common::render_input_popup(f, title, &format!("Value of {}:", key), input);
+            }
+            Mode::NewName(input) => {
+                tui_common::render_input_popup(f, "New Variable", "Name:", input);
+            }
+            Mode::ConfirmUnset => {
+                let key = self.selected_var().map(|(key, _)| key.as_str()).unwrap_or_default();
+                let lines = vec![
+                    Line::from(format!("Unset {}?", key)),
+                    Line::from(""),
+                    Line::from(Span::styled("y Unset • any other key Cancel", Style::default().fg(colors::muted()))),
+                ];
+                tui_common::render_message_popup(f, "Unset Variable", lines, colors::danger());
+            }
+        }
     }
     
     /// Render the variable list panel
     fn render_var_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.filtered_vars
+        let items: Vec<Line> = self.rows
             .iter()
-            .map(|(key, _)| {
-                ListItem::new(Line::from(key.clone()))
+            .map(|row| match row {
+                Row::Header(name, count) => {
+                    let arrow = if self.collapsed.contains(name) { "▶" } else { "▼" };
+                    Line::from(Span::styled(
+                        format!("{} {} ({})", arrow, name, count),
+                        Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD),
+                    ))
+                }
+                Row::Var(index) => {
+                    let key = &self.filtered_vars[*index].0;
+                    // Pending changes: new variables in green, edited ones in yellow
+                    let style = match self.changes.contains_key(key) {
+                        true if env::var_os(key).is_none() => Style::default().fg(colors::success()),
+                        true => Style::default().fg(colors::warning()),
+                        false => Style::default(),
+                    };
+                    let indent = if self.grouped { "  " } else { "" };
+                    let mut spans = vec![Span::styled(format!("{}{}", indent, key), style)];
+                    let snapshot = self.baseline.as_ref().is_some_and(|baseline| baseline.complete);
+                    let marker = match self.comparison(key) {
+                        Some(Comparison::Added) => Some((" (added)", colors::success())),
+                        Some(Comparison::Missing) if snapshot => Some((" (removed)", colors::danger())),
+                        Some(Comparison::Missing) => Some((" (not set)", colors::danger())),
+                        Some(Comparison::Differs) if snapshot => Some((" (changed)", colors::secondary())),
+                        Some(Comparison::Differs) => Some((" (differs)", colors::secondary())),
+                        Some(Comparison::Same) | None => None,
+                    };
+                    if let Some((text, color)) = marker {
+                        spans.push(Span::styled(text, Style::default().fg(color)));
+                    }
+                    Line::from(spans)
+                }
             })
             .collect();
         
-        let title = if self.search_query.is_empty() {
+        let mut title = if self.search_query.is_empty() {
             format!("Environment Variables ({})", self.filtered_vars.len())
         } else {
             format!("Environment Variables ({}) - Filter: '{}'", self.filtered_vars.len(), self.search_query)
         };
+        if let Some(baseline) = &self.baseline {
+            let (added, changed, missing) = self.differences();
+            if baseline.complete {
+                title.push_str(&format!(
+                    " - since {}: {} added, {} changed, {} removed",
+                    baseline.label, added, changed, missing
+                ));
+            } else {
+                title.push_str(&format!(" - {}: {} differ, {} not set", baseline.label, changed, missing));
+            }
+        }
+        if !self.changes.is_empty() {
+            title.push_str(&format!(" - {} pending change(s)", self.changes.len()));
+        }
         
-        let list = List::new(items)
+        let list = SelectableList::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
+                .border_style(Style::default().fg(colors::primary())));
         
         f.render_stateful_widget(list, area, &mut self.list_state);
     }
     
     /// Render the value preview panel
     fn render_value_preview(&self, f: &mut Frame, area: Rect) {
-        let (title, content) = if let Some(selected) = self.list_state.selected() {
-            if let Some((key, value)) = self.filtered_vars.get(selected) {
-                (format!("Value: {}", key), value.clone())
-            } else {
-                ("Value".to_string(), String::new())
+        if let Some((key, _)) = self.selected_var() {
+            if let Some((assignments, searched)) = self.provenance_for(key) {
+                self.render_provenance(f, area, key, assignments, searched);
+                return;
             }
-        } else {
-            ("Value".to_string(), String::new())
+            if self.differs(key) {
+                self.render_comparison(f, area, key);
+                return;
+            }
+        }
+        
+        if let Some(entries) = self.path_entries() {
+            self.render_path_entries(f, area, &entries);
+            return;
+        }
+        
+        let (title, content) = match self.selected_row() {
+            Some(Row::Var(index)) => {
+                let (key, value) = &self.filtered_vars[*index];
+                if self.is_masked(key) {
+                    (format!("Value: {} - masked, ^R reveals", key), env_vars::mask(value))
+                } else {
+                    (format!("Value: {}", key), value.clone())
+                }
+            }
+            Some(Row::Header(name, count)) => {
+                let action = if self.collapsed.contains(name) { "expand" } else { "collapse" };
+                (format!("Group: {}", name), format!("{} variable(s) - Enter to {}", count, action))
+            }
+            None => ("Value".to_string(), String::new()),
         };
         
         let paragraph = Paragraph::new(content)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::SECONDARY)))
+                .border_style(Style::default().fg(colors::secondary())))
             .wrap(Wrap { trim: true });
         
         f.render_widget(paragraph, area);
     }
     
-    /// Render status bar
-    fn render_status_bar(&self, f: &mut Frame) {
-        let area = Rect {
-            x: 0,
-            y: f.area().height - 1,
-            width: f.area().width,
-            height: 1,
-        };
+    /// Render the startup file lines that set `key`
+    fn render_provenance(&self, f: &mut Frame, area: Rect, key: &str, assignments: &[Assignment], searched: usize) {
+        let home = platform::home_dir();
+        let mut lines: Vec<Line> = Vec::new();
+        for assignment in assignments {
+            let path = match home.as_deref().and_then(|home| assignment.path.strip_prefix(home).ok()) {
+                Some(rest) => format!("~/{}", rest.display()),
+                None => assignment.path.display().to_string(),
+            };
+            lines.push(Line::from(Span::styled(
+                format!("{}:{}", path, assignment.line),
+                Style::default().fg(colors::primary()),
+            )));
+            // The snippet may spell out a secret value
+            let text = if self.is_masked(key) { env_vars::mask(&assignment.text) } else { assignment.text.clone() };
+            lines.push(Line::from(format!("  {}", text)));
+        }
+        if assignments.is_empty() {
+            lines.push(Line::from(format!("Not set in any of the {} startup files searched.", searched)));
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "It may come from the program that started your shell (terminal, login manager, sshd, tmux) or from a script sourced by hand.",
+                Style::default().fg(colors::muted()),
+            )));
+        }
         
-        let help_text = "Type to filter • ↑↓ Navigate • Esc Quit";
-        let status_text = if !self.status_message.is_empty() {
-            format!("{} | {}", self.status_message, help_text)
-        } else {
-            help_text.to_string()
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Where {} is set - {} file(s) searched, ^W hides", key, searched))
+                .border_style(Style::default().fg(colors::secondary())))
+            .wrap(Wrap { trim: false });
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    /// Render the environment and baseline values of `key` one above the other
+    fn render_comparison(&self, f: &mut Frame, area: Rect, key: &str) {
+        let Some(baseline) = &self.baseline else {
+            return;
         };
+        let heading = Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD);
+        let not_set = || Line::from(Span::styled("(not set)", Style::default().fg(colors::danger())));
+        let mut lines = vec![Line::from(Span::styled("Environment:", heading))];
+        match self.env_vars.iter().find(|(name, _)| name == key) {
+            Some((_, value)) => {
+                let value = self.shown_value(key, value);
+                lines.extend(value.lines().map(|line| Line::from(line.to_string())));
+            }
+            None => lines.push(not_set()),
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(format!("{}:", baseline.label), heading)));
+        match baseline.vars.get(key) {
+            Some(value) => {
+                let value = self.shown_value(key, value);
+                lines.extend(value.lines().map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(colors::success())))));
+            }
+            None => lines.push(not_set()),
+        }
         
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(if baseline.complete {
+                    format!("Value: {} - ^A restores the snapshot", key)
+                } else {
+                    format!("Value: {} - ^A adopts the file's values", key)
+                })
+                .border_style(Style::default().fg(colors::secondary())))
+            .wrap(Wrap { trim: false });
         
         f.render_widget(paragraph, area);
     }
     
-    /// Run the environment browser application
-    pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
+    /// Render a `PATH`-style value one entry per line, flagging missing
+    /// directories and repeated entries
+    fn render_path_entries(&self, f: &mut Frame, area: Rect, entries: &[PathEntry]) {
+        let cursor = self.path_cursor();
+        let lines: Vec<Line> = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let marker = if index == cursor { "► " } else { "  " };
+                let mut spans = vec![Span::styled(format!("{}{:>2} ", marker, index + 1), Style::default().fg(colors::muted()))];
+                if entry.path.is_empty() {
+                    spans.push(Span::styled("(empty: current directory)", Style::default().fg(colors::warning())));
+                } else if !entry.exists {
+                    spans.push(Span::styled(format!("{} (missing)", entry.path), Style::default().fg(colors::danger())));
+                } else {
+                    spans.push(Span::raw(entry.path.clone()));
+                }
+                if let Some(first) = entry.duplicate_of {
+                    spans.push(Span::styled(format!(" (duplicate of {})", first + 1), Style::default().fg(colors::warning())));
+                }
+                let line = Line::from(spans);
+                if index == cursor { line.style(Style::default().add_modifier(Modifier::BOLD)) } else { line }
+            })
+            .collect();
         
-        let result = self.run_app(&mut terminal);
+        let missing = entries.iter().filter(|entry| !entry.exists).count();
+        let duplicates = entries.iter().filter(|entry| entry.duplicate_of.is_some()).count();
+        let key = self.selected_var().map(|(key, _)| key.as_str()).unwrap_or_default();
+        let title = format!("Value: {} - {} entries, {} missing, {} duplicate", key, entries.len(), missing, duplicates);
         
-        tui_common::restore_terminal(&mut terminal)?;
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(colors::secondary())))
+            .scroll((cursor.saturating_sub(area.height.saturating_sub(3) as usize) as u16, 0));
         
-        result
+        f.render_widget(paragraph, area);
     }
     
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            
-            self.handle_input()?;
-            
-            if self.should_quit {
-                break;
+    /// Render status bar
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
+        
+        let help_text = match (self.vim.inserting(), self.baseline.is_some()) {
+            (true, true) => "Type to filter • ^A Adopt • ^E Edit • ^N New • Esc Normal mode • F1 Help",
+            (true, false) => "Type to filter • ^E Edit • ^N New • Esc Normal mode • F1 Help",
+            (false, true) => "j/k Move • / Filter • ^A Adopt • ^E Edit • y Copy • ? Help • q Quit",
+            (false, false) => "j/k Move • / Filter • ^E Edit • ^N New • y Copy • ? Help • q Quit",
+        };
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Print the filtered variables instead of running the TUI, secrets
+    /// masked unless `--show-secrets`; with `--file` or `--diff`, how each
+    /// one compares
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        let snapshot = self.baseline.as_ref().is_some_and(|baseline| baseline.complete);
+        plain::print(format, self.filtered_vars.iter().map(|(key, value)| {
+            let status = match self.comparison(key) {
+                Some(Comparison::Added) => Some("added"),
+                Some(Comparison::Missing) if snapshot => Some("removed"),
+                Some(Comparison::Missing) => Some("not set"),
+                Some(Comparison::Differs) if snapshot => Some("changed"),
+                Some(Comparison::Differs) => Some("differs"),
+                Some(Comparison::Same) => Some("same"),
+                None => None,
+            };
+            vec![
+                ("name", Value::from(key.as_str())),
+                ("value", Value::from(self.shown_value(key, value))),
+                ("status", Value::from(status)),
+            ]
+        }))
+    }
+    
+    /// Run the environment browser application, from where it was left in
+    /// this directory, then print the pending changes as shell statements
+    pub fn run(&mut self) -> io::Result<()> {
+        // With --apply stdout belongs to the shell evaluating the statements
+        let result = match self.apply {
+            Some(_) => session::resume(self, "env", |browser| tui_common::run_app_on(browser, io::stderr())),
+            None => session::resume(self, "env", |browser| tui_common::run_app_on(browser, io::stdout())),
+        };
+        
+        if !self.changes.is_empty() {
+            let syntax = match self.apply {
+                Some(syntax) => syntax,
+                None => {
+                    println!("# Not applied: run eval \"$(tt env --apply)\" (or tte from tt init) to change your shell");
+                    Syntax::for_shell(None)
+                }
+            };
+            for statement in self.statements(syntax) {
+                println!("{}", statement);
             }
         }
-        
-        Ok(())
+        result
     }
 }
 
-/// Run the environment browser tool
-pub fn run() -> io::Result<()> {
-    let mut browser = EnvBrowser::new()?;
+impl App for EnvBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        if let AppEvent::Key(key) = event {
+            self.handle_key(key)?;
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        self.vim.inserting() || matches!(self.mode, Mode::Edit { .. } | Mode::NewName(_))
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+/// Expand a leading `~` the way shells do when searching `PATH`
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), platform::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl Resume for EnvBrowser {
+    fn session(&self) -> Session {
+        Session {
+            query: Some(self.search_query.clone()),
+            selected: self.selected_var().map(|(key, _)| key.clone()),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query.filter(|_| self.search_query.is_empty()) {
+            self.search_query = query;
+            self.update_filter();
+        }
+        if let Some(key) = session.selected {
+            self.select_key(&key);
+        }
+    }
+}
+
+/// Run the environment browser tool, starting with `filter` as the search
+/// query and comparing against the dotenv `file` or the snapshot `diff` if
+/// given; with `apply`, print the changes as statements in that syntax for
+/// the shell to evaluate. With `output`, print the variables instead.
+pub fn run(
+    filter: Option<String>,
+    file: Option<PathBuf>,
+    diff: Option<String>,
+    show_secrets: bool,
+    apply: Option<Syntax>,
+    output: Option<Format>,
+    settings: Settings,
+) -> io::Result<()> {
+    let mut browser = EnvBrowser::new(settings)?;
+    browser.show_secrets = show_secrets;
+    if let Some(file) = file {
+        browser.load_dotenv(file)?;
+    }
+    if let Some(name) = diff {
+        browser.load_snapshot(&name)?;
+    }
+    if let Some(filter) = filter {
+        browser.search_query = filter;
+        browser.update_filter();
+    }
+    if let Some(format) = output {
+        return browser.print(format);
+    }
+    browser.apply = apply;
     browser.run()
-}
\ No newline at end of file
+}
+/// Save the current environment as the snapshot `name`
+pub fn snapshot(name: &str) -> io::Result<()> {
+    let vars: BTreeMap<String, String> = env::vars().collect();
+    let path = env_vars::save_snapshot(name, &vars)?;
+    println!("Saved {} variables to {}", vars.len(), path.display());
+    println!("Compare later with: tt env --diff {}", name);
+    Ok(())
+}
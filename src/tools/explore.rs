@@ -1,23 +1,95 @@
 //! Interactive file/directory explorer with navigation.
 
-use crate::tui_common::{self, colors};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crate::archive;
+use crate::bookmarks;
+use crate::clipboard;
+use crate::config::Settings;
+use crate::file_ops::{self, Transfer, TransferProgress, TrashItem};
+use crate::fuzzy;
+use crate::image_preview::{self, GraphicsProtocol};
+use crate::jobs::{self, Job};
+use crate::mru;
+use crate::notify::Notifications;
+use crate::palette::Context;
+use crate::plain::{self, Format};
+use crate::platform;
+use crate::preview::{Preview, PreviewEngine};
+use crate::session::{self, Resume, Session};
+use crate::state;
+use crate::text_width;
+use crate::time_util;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, SelectableList, TextInput, VimNav};
+use crate::users;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
-    text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Gauge, ListState, Paragraph},
     Frame, Terminal,
 };
+use serde_json::Value;
 use std::{
+    collections::{BTreeSet, HashMap, HashSet},
     env,
     fs,
-    io,
+    io::{self, IsTerminal, Write},
     path::{Path, PathBuf},
-    process::Command,
-    time::Duration,
+    process::{Command, Output},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
+/// Keys of the file list, listed by the help popup
+const KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("gg / G, End", "First / last item"),
+    ("Enter / →", "Open"),
+    ("←", "Go up a directory"),
+    ("`", "Go back"),
+    ("~ / Home", "Go home"),
+    ("/", "Filter the list"),
+    ("b", "Bookmark the directory"),
+    ("'", "Jump to a bookmark"),
+    ("Space", "Mark the entry"),
+    ("p", "Print the selection and quit"),
+    ("y", "Copy the path of the selection"),
+    ("c / m / r", "Copy / move / rename"),
+    ("d / Delete", "Delete"),
+    ("n / N", "New file / directory"),
+    ("L", "Create a symlink"),
+    ("u", "Undo"),
+    ("e / E", "Extract here / to"),
+    ("i", "Properties and permissions"),
+    ("h", "Show hidden files"),
+    ("z / Z", "Size of the directory / all directories"),
+    ("S", "Cycle sorting"),
+    ("F", "Directories first"),
+    ("T", "Tree view"),
+    ("F2", "Dual pane"),
+    ("Tab", "Switch pane"),
+    ("!", "Open a shell here"),
+    ("x", "Run a command"),
+    ("R", "Refresh"),
+    ("Esc", "Clear marks, then quit"),
+    ("q", "Quit"),
+];
+
+/// Keys inside an archive, listed by the help popup
+const ARCHIVE_KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move the selection"),
+    ("gg / G", "First / last item"),
+    ("Enter / →", "Open the directory"),
+    ("← / Backspace", "Go up a directory"),
+    ("e", "Extract here"),
+    ("E", "Extract to"),
+    ("Esc / q", "Leave the archive"),
+];
+
 #[derive(Debug, Clone)]
 pub struct FileEntry {
     pub name: String,
@@ -25,300 +97,2190 @@ pub struct FileEntry {
     pub is_directory: bool,
     pub size: Option<u64>,
     pub is_parent: bool,
+    pub is_hidden: bool,
+    pub modified: Option<SystemTime>,
+    /// Where a symlink points, as stored in the link
+    pub link_target: Option<PathBuf>,
+    /// Symlink whose target does not exist
+    pub is_broken_link: bool,
+}
+
+/// Action waiting for text input from the prompt popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputAction {
+    Copy,
+    Move,
+    Rename,
+    NewFile,
+    NewDirectory,
+    Extract,
+    Command,
+    Symlink,
+}
+
+impl InputAction {
+    fn title(&self) -> &'static str {
+        match self {
+            InputAction::Copy => "Copy",
+            InputAction::Move => "Move",
+            InputAction::Rename => "Rename",
+            InputAction::NewFile => "New File",
+            InputAction::NewDirectory => "New Directory",
+            InputAction::Extract => "Extract Archive",
+            InputAction::Command => "Run Command",
+            InputAction::Symlink => "Create Symlink",
+        }
+    }
+    
+    fn label(&self) -> &'static str {
+        match self {
+            InputAction::Copy => "Copy to:",
+            InputAction::Move => "Move to:",
+            InputAction::Rename => "New name:",
+            InputAction::NewFile => "File name:",
+            InputAction::NewDirectory => "Directory name:",
+            InputAction::Extract => "Extract to:",
+            InputAction::Command => "Command ({} is replaced by the selected path):",
+            InputAction::Symlink => "Create a link to the selection at:",
+        }
+    }
+}
+
+/// Ordering of the entry list, persisted as `explore.sort`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortMode {
+    /// Alphabetical, ignoring case
+    Name,
+    /// Largest first; directories count once their size is computed
+    Size,
+    /// Most recently modified first
+    Modified,
+    /// Grouped by extension, then alphabetical
+    Extension,
+}
+
+impl SortMode {
+    const ALL: [SortMode; 4] = [SortMode::Name, SortMode::Size, SortMode::Modified, SortMode::Extension];
+    
+    fn label(&self) -> &'static str {
+        match self {
+            SortMode::Name => "name",
+            SortMode::Size => "size",
+            SortMode::Modified => "modified",
+            SortMode::Extension => "extension",
+        }
+    }
+    
+    fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.label() == label)
+    }
+    
+    fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Entries of `dir` in no particular order, without hidden ones unless
+/// `show_hidden` and without ignored ones; directories have no size yet
+fn list_entries(dir: &Path, show_hidden: bool, settings: &Settings) -> Vec<FileEntry> {
+    let mut entries = Vec::new();
+    if let Ok(dir_entries) = fs::read_dir(dir) {
+        for entry in dir_entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            
+            // Skip hidden files (starting with .) unless toggled on
+            let is_hidden = name.starts_with('.');
+            if is_hidden && !show_hidden || settings.is_ignored(&name) {
+                continue;
+            }
+            
+            let metadata = fs::metadata(&path).ok();
+            let link_target = entry
+                .file_type()
+                .is_ok_and(|file_type| file_type.is_symlink())
+                .then(|| fs::read_link(&path).unwrap_or_default());
+            let is_directory = metadata.as_ref().is_some_and(|m| m.is_dir());
+            let size = if is_directory { None } else { metadata.as_ref().map(|m| m.len()) };
+            
+            entries.push(FileEntry {
+                name,
+                path,
+                is_directory,
+                size,
+                is_parent: false,
+                is_hidden,
+                is_broken_link: link_target.is_some() && metadata.is_none(),
+                modified: metadata.and_then(|m| m.modified().ok()),
+                link_target,
+            });
+        }
+    }
+    entries
+}
+
+/// Sort entries in place according to `mode`, optionally keeping directories on top
+fn sort_entries(entries: &mut [FileEntry], mode: SortMode, dirs_first: bool) {
+    let extension = |entry: &FileEntry| entry.path.extension().map(|e| e.to_string_lossy().to_lowercase());
+    entries.sort_by(|a, b| {
+        let group = if dirs_first {
+            b.is_directory.cmp(&a.is_directory)
+        } else {
+            std::cmp::Ordering::Equal
+        };
+        group
+            .then_with(|| match mode {
+                SortMode::Name => std::cmp::Ordering::Equal,
+                SortMode::Size => b.size.unwrap_or(0).cmp(&a.size.unwrap_or(0)),
+                SortMode::Modified => b.modified.cmp(&a.modified),
+                SortMode::Extension => extension(a).cmp(&extension(b)),
+            })
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+}
+
+/// Popup currently shown on top of the explorer
+enum Mode {
+    Normal,
+    Input(InputAction, TextInput),
+    ConfirmDelete,
+    Error(String),
+    /// Live fuzzy filter typed after `/`
+    Filter(TextInput),
+    /// Bookmark and quick-jump picker opened with `'`
+    Jump(ListState),
+    /// Properties and permissions editor opened with `i`
+    Properties(Box<Properties>),
+    /// Scrollable output of a command run with `x`
+    Output {
+        title: String,
+        lines: Vec<String>,
+        scroll: usize,
+    },
+}
+
+/// Quote a path for `sh -c` using single quotes
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+/// Text field being edited inside the properties popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PropertyInput {
+    Octal,
+    Owner,
+}
+
+/// Details and pending permission changes for one entry
+struct Properties {
+    path: PathBuf,
+    is_dir: bool,
+    /// Read-only `label: value` rows (type, size, owner, timestamps)
+    details: Vec<(&'static str, String)>,
+    original_mode: u32,
+    mode: u32,
+    /// Selected cell in the rwx grid: (user/group/other, r/w/x)
+    cursor: (usize, usize),
+    input: Option<(PropertyInput, TextInput)>,
+    error: Option<String>,
+}
+
+impl Properties {
+    fn load(path: &Path) -> io::Result<Self> {
+        let metadata = fs::symlink_metadata(path)?;
+        let kind = if metadata.file_type().is_symlink() {
+            "Symbolic link"
+        } else if metadata.is_dir() {
+            "Directory"
+        } else {
+            "File"
+        };
+        let timestamp = |time: io::Result<std::time::SystemTime>| {
+            time.map(|t| time_util::format_timestamp(time_util::unix_secs(t)))
+                .unwrap_or_else(|_| "unavailable".to_string())
+        };
+        
+        let mut details = vec![
+            ("Path", path.display().to_string()),
+            ("Type", kind.to_string()),
+            ("Size", format!("{} ({} bytes)", tui_common::format_size(metadata.len()), metadata.len())),
+        ];
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            details.push(("Owner", format!("{} ({})", users::user_name(metadata.uid()), metadata.uid())));
+            details.push(("Group", format!("{} ({})", users::group_name(metadata.gid()), metadata.gid())));
+        }
+        details.extend([
+            ("Modified", timestamp(metadata.modified())),
+            ("Accessed", timestamp(metadata.accessed())),
+            ("Created", timestamp(metadata.created())),
+        ]);
+        
+        let mode = file_ops::mode_bits(&metadata);
+        Ok(Properties {
+            path: path.to_path_buf(),
+            is_dir: metadata.is_dir(),
+            details,
+            original_mode: mode,
+            mode,
+            cursor: (0, 0),
+            input: None,
+            error: None,
+        })
+    }
+    
+    /// Permission bit under the grid cursor
+    fn cursor_bit(&self) -> u32 {
+        0o400 >> (self.cursor.0 * 3 + self.cursor.1)
+    }
+}
+
+/// Archive being browsed read-only in place of a directory
+struct ArchiveView {
+    path: PathBuf,
+    /// Every inner path, as listed by [`archive::list`]
+    entries: Vec<String>,
+    /// Inner directory being shown: empty or ending in `/`
+    prefix: String,
+}
+
+/// Archive being listed or extracted as a job
+enum ArchiveJob {
+    /// Listing an archive to browse it
+    Open { path: PathBuf, job: Job<Vec<String>> },
+    Extract { path: PathBuf, destination: PathBuf, job: Job<()> },
+}
+
+/// Path `tt dir` prints on exit for shell integration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choose {
+    /// The directory the explorer was quit in
+    Dir,
+    /// The file opened with Enter
+    File,
+}
+
+/// Quiet period after a filesystem event before the listing is reloaded,
+/// so bursts (e.g. an extracting archive) cause a single refresh
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Most directories remembered for the backtick history stack
+const HISTORY_LIMIT: usize = 50;
+
+/// Destination offered by the quick-jump picker
+struct JumpTarget {
+    /// Shortcut key inside the picker, if any
+    key: Option<char>,
+    label: String,
+    path: PathBuf,
+    /// Index into the saved bookmarks, for deletion
+    bookmark: Option<usize>,
+}
+
+/// Nearest ancestor of `dir` (inclusive) containing a `.git` entry
+fn git_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Directory listing of the inactive side in dual-pane mode.
+///
+/// The active pane lives directly on [`FileExplorer`]; switching sides swaps
+/// the two so every operation keeps working on `current_dir`/`entries`.
+struct Pane {
+    current_dir: PathBuf,
+    logical_dir: Option<PathBuf>,
+    entries: Vec<FileEntry>,
+    list_state: ListState,
+}
+
+/// Last file operation of the session, reversible with `u`
+enum UndoOp {
+    Rename { from: PathBuf, to: PathBuf },
+    /// A newly created file or directory; undone by trashing it
+    Create(PathBuf),
+    Trash(Vec<TrashItem>),
+    /// `(source, target)` pairs of a finished copy or move
+    Transfer { kind: Transfer, pairs: Vec<(PathBuf, PathBuf)> },
+}
+
+/// Copy or move running as a job
+struct ActiveTransfer {
+    kind: Transfer,
+    job: Job<Vec<(PathBuf, PathBuf)>>,
+    events: Receiver<TransferProgress>,
+    done: u64,
+    total: u64,
+    current: PathBuf,
+    /// Every path touched by the transfer, for size cache invalidation
+    affected: Vec<PathBuf>,
 }
 
 pub struct FileExplorer {
     current_dir: PathBuf,
+    /// Path as navigated when `current_dir` was reached through a symlink
+    logical_dir: Option<PathBuf>,
     entries: Vec<FileEntry>,
     list_state: ListState,
+    vim: VimNav,
     should_quit: bool,
-    status_message: String,
-    preview_content: String,
+    notifications: Notifications,
+    /// Preview of the selected entry
+    preview: Option<Preview>,
+    previews: PreviewEngine,
+    /// Columns and rows inside the preview border at the last draw
+    preview_size: (u16, u16),
+    mode: Mode,
+    transfer: Option<ActiveTransfer>,
+    /// Shell command from the `x` prompt running as a job, as typed
+    command: Option<(String, Job<Output>)>,
+    archive_job: Option<ArchiveJob>,
+    /// Entries marked with Space, kept across directory changes
+    marked: BTreeSet<PathBuf>,
+    /// Paths printed to stdout after the TUI exits
+    output: Vec<PathBuf>,
+    show_hidden: bool,
+    sort_mode: SortMode,
+    dirs_first: bool,
+    /// Recursive directory sizes computed this session
+    dir_sizes: HashMap<PathBuf, u64>,
+    /// Directories whose size is still being computed
+    pending_sizes: HashSet<PathBuf>,
+    size_tx: Sender<(PathBuf, u64)>,
+    size_rx: Receiver<(PathBuf, u64)>,
+    dual_pane: bool,
+    /// The inactive pane (kept while dual-pane mode is off)
+    other_pane: Option<Pane>,
+    /// Which side the active pane is shown on (0 = left, 1 = right)
+    active_side: usize,
+    /// Full listing while a fuzzy filter narrows `entries`
+    unfiltered: Vec<FileEntry>,
+    bookmarks: Vec<PathBuf>,
+    /// Directories visited this session, most recent last
+    history: Vec<PathBuf>,
+    choose: Option<Choose>,
+    archive: Option<ArchiveView>,
+    /// Filesystem watcher for the displayed directories (None if unsupported)
+    watcher: Option<RecommendedWatcher>,
+    watch_rx: Receiver<notify::Result<notify::Event>>,
+    watched: Vec<PathBuf>,
+    /// When to reload after external changes were seen
+    refresh_due: Option<Instant>,
+    /// Show the directory hierarchy as a collapsible tree
+    tree_mode: bool,
+    /// Directories expanded in tree mode
+    expanded: HashSet<PathBuf>,
+    /// Indent guide drawn before each entry in tree mode
+    tree_guides: HashMap<PathBuf, String>,
+    /// Set by `!`; the shell runs from the main loop, which owns the terminal
+    shell_requested: bool,
+    last_op: Option<UndoOp>,
+    /// Terminal graphics protocol for image thumbnails (None: ASCII art)
+    graphics: Option<GraphicsProtocol>,
+    /// Where the explorer was last drawn, set by `render`
+    area: Rect,
+    /// Where the previewed image should be drawn, set by `render`
+    image_slot: Option<(PathBuf, Rect)>,
+    /// Image currently drawn on screen with the graphics protocol
+    shown_image: Option<(PathBuf, Rect)>,
+    settings: Settings,
 }
 
 impl FileExplorer {
     /// Create a new file explorer instance
-    pub fn new(start_path: PathBuf) -> io::Result<Self> {
+    pub fn new(start_path: PathBuf, settings: Settings) -> io::Result<Self> {
+        let (size_tx, size_rx) = mpsc::channel();
+        let (watch_tx, watch_rx) = mpsc::channel();
         let mut explorer = FileExplorer {
             current_dir: start_path.canonicalize().unwrap_or(start_path),
+            logical_dir: None,
             entries: Vec::new(),
             list_state: ListState::default(),
+            vim: VimNav::default(),
             should_quit: false,
-            status_message: String::new(),
-            preview_content: String::new(),
+            notifications: Notifications::new(),
+            preview: None,
+            previews: PreviewEngine::new(&settings),
+            preview_size: (0, 0),
+            mode: Mode::Normal,
+            transfer: None,
+            command: None,
+            archive_job: None,
+            marked: BTreeSet::new(),
+            output: Vec::new(),
+            show_hidden: state::get_bool("explore.hidden", false),
+            sort_mode: state::get("explore.sort")
+                .and_then(|label| SortMode::from_label(&label))
+                .unwrap_or(SortMode::Name),
+            dirs_first: state::get_bool("explore.dirs_first", true),
+            dir_sizes: HashMap::new(),
+            pending_sizes: HashSet::new(),
+            size_tx,
+            size_rx,
+            dual_pane: false,
+            other_pane: None,
+            active_side: 0,
+            unfiltered: Vec::new(),
+            bookmarks: bookmarks::load(),
+            history: Vec::new(),
+            choose: None,
+            archive: None,
+            watcher: notify::recommended_watcher(watch_tx).ok(),
+            watch_rx,
+            watched: Vec::new(),
+            refresh_due: None,
+            tree_mode: false,
+            expanded: HashSet::new(),
+            tree_guides: HashMap::new(),
+            shell_requested: false,
+            last_op: None,
+            graphics: image_preview::detect_protocol(),
+            area: Rect::default(),
+            image_slot: None,
+            shown_image: None,
+            settings,
+        };
+        explorer.previews.set_graphics(explorer.graphics.is_some());
+        explorer.previews.set_show_hidden(explorer.show_hidden);
+        
+        explorer.load_directory()?;
+        
+        Ok(explorer)
+    }
+    
+    /// Load current directory contents
+    fn load_directory(&mut self) -> io::Result<()> {
+        // A reload (navigation, refresh, finished transfer) ends any filter
+        if let Mode::Filter(_) = self.mode {
+            self.mode = Mode::Normal;
+            self.unfiltered.clear();
+        }
+        self.entries.clear();
+        
+        // Add parent directory entry if not at root
+        if let Some((parent, _)) = self.parent_dir() {
+            self.entries.push(FileEntry {
+                name: "..".to_string(),
+                path: parent,
+                is_directory: true,
+                size: None,
+                is_parent: true,
+                is_hidden: false,
+                modified: None,
+                link_target: None,
+                is_broken_link: false,
+            });
+        }
+        
+        let entries = self.read_entries(&self.current_dir);
+        if self.tree_mode {
+            self.extend_tree(entries, "");
+        } else {
+            self.entries.extend(entries);
+        }
+        
+        // Reset selection
+        if !self.entries.is_empty() {
+            self.list_state.select(Some(0));
+            self.update_preview();
+        } else {
+            self.list_state.select(None);
+            self.preview = None;
+        }
+        
+        self.notifications.set_status(format!("Directory: {} ({} items)", 
+            self.location(), 
+            self.entries.len()
+        ));
+        
+        Ok(())
+    }
+    
+    /// Read, filter and sort the entries of one directory
+    fn read_entries(&self, dir: &Path) -> Vec<FileEntry> {
+        let mut entries = list_entries(dir, self.show_hidden, &self.settings);
+        for entry in entries.iter_mut().filter(|entry| entry.is_directory) {
+            entry.size = self.dir_sizes.get(&entry.path).copied();
+        }
+        sort_entries(&mut entries, self.sort_mode, self.dirs_first);
+        entries
+    }
+    
+    /// Append `entries` and, recursively, the children of expanded directories
+    fn extend_tree(&mut self, entries: Vec<FileEntry>, indent: &str) {
+        let count = entries.len();
+        for (index, entry) in entries.into_iter().enumerate() {
+            let last = index + 1 == count;
+            let connector = if last { "└─ " } else { "├─ " };
+            self.tree_guides.insert(entry.path.clone(), format!("{}{}", indent, connector));
+            
+            let expanded = entry.is_directory && self.expanded.contains(&entry.path);
+            let path = entry.path.clone();
+            self.entries.push(entry);
+            if expanded {
+                // Children are only read once their directory is expanded
+                let children = self.read_entries(&path);
+                let child_indent = format!("{}{}", indent, if last { "   " } else { "│  " });
+                self.extend_tree(children, &child_indent);
+            }
+        }
+    }
+    
+    /// Reload the tree after expanding or collapsing, keeping the selection
+    fn rebuild_tree(&mut self) -> io::Result<()> {
+        let selected = self.selected_entry().map(|entry| entry.path.clone());
+        self.load_directory()?;
+        if let Some(path) = selected {
+            self.select_path(&path);
+        }
+        Ok(())
+    }
+    
+    /// Toggle between the flat listing and the tree view
+    fn toggle_tree_mode(&mut self) -> io::Result<()> {
+        self.tree_mode = !self.tree_mode;
+        self.tree_guides.clear();
+        self.rebuild_tree()?;
+        self.reload_other_pane()?;
+        self.notifications.info(if self.tree_mode {
+            "Tree view: Enter/→ expand, ← collapse"
+        } else {
+            "List view"
+        });
+        Ok(())
+    }
+    
+    /// Expand or collapse a directory in tree mode
+    fn toggle_expanded(&mut self, path: PathBuf) -> io::Result<()> {
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+        self.rebuild_tree()
+    }
+    
+    /// `←` in tree mode: collapse, then move to the parent row, then go up
+    fn tree_left(&mut self) -> io::Result<()> {
+        if let Some(entry) = self.selected_entry().cloned() {
+            if entry.is_directory && self.expanded.contains(&entry.path) {
+                return self.toggle_expanded(entry.path);
+            }
+            if let Some(parent) = entry.path.parent().filter(|parent| *parent != self.current_dir) {
+                self.select_path(parent);
+                return Ok(());
+            }
+        }
+        self.go_up()
+    }
+    
+    /// Swap the active pane with the inactive one
+    fn swap_panes(&mut self) {
+        if let Some(other) = self.other_pane.as_mut() {
+            std::mem::swap(&mut self.current_dir, &mut other.current_dir);
+            std::mem::swap(&mut self.logical_dir, &mut other.logical_dir);
+            std::mem::swap(&mut self.entries, &mut other.entries);
+            std::mem::swap(&mut self.list_state, &mut other.list_state);
+        }
+    }
+    
+    /// Toggle commander-style dual-pane mode
+    fn toggle_dual_pane(&mut self) {
+        self.dual_pane = !self.dual_pane;
+        if self.dual_pane && self.other_pane.is_none() {
+            self.other_pane = Some(Pane {
+                current_dir: self.current_dir.clone(),
+                logical_dir: self.logical_dir.clone(),
+                entries: self.entries.clone(),
+                list_state: self.list_state.clone(),
+            });
+        }
+        self.notifications.info(if self.dual_pane {
+            "Dual-pane mode: Tab switches panes, copy/move target the other pane"
+        } else {
+            "Single-pane mode"
+        });
+    }
+    
+    /// Switch focus to the other pane in dual-pane mode
+    fn switch_pane(&mut self) {
+        if !self.dual_pane {
+            return;
+        }
+        self.swap_panes();
+        self.active_side = 1 - self.active_side;
+        self.update_preview();
+        self.notifications.set_status(format!("Directory: {}", self.location()));
+    }
+    
+    /// Reload the inactive pane, keeping its selection by name
+    fn reload_other_pane(&mut self) -> io::Result<()> {
+        if self.other_pane.is_none() {
+            return Ok(());
+        }
+        let status = self.notifications.status().to_string();
+        self.swap_panes();
+        let selected = self.selected_entry().map(|entry| entry.name.clone());
+        let result = self.load_directory();
+        if let Some(name) = selected {
+            self.select_name(&name);
+        }
+        self.swap_panes();
+        self.update_preview();
+        self.notifications.set_status(status);
+        result
+    }
+    
+    /// Re-sort the current entries, keeping the selection on the same entry
+    fn resort(&mut self) {
+        if self.tree_mode {
+            // Sorting happens per directory while the tree is built
+            let _ = self.rebuild_tree();
+            return;
+        }
+        let selected = self.list_state.selected()
+            .and_then(|i| self.entries.get(i))
+            .map(|entry| entry.path.clone());
+        
+        let start = usize::from(self.entries.first().is_some_and(|entry| entry.is_parent));
+        sort_entries(&mut self.entries[start..], self.sort_mode, self.dirs_first);
+        
+        if let Some(path) = selected {
+            if let Some(index) = self.entries.iter().position(|entry| entry.path == path) {
+                self.list_state.select(Some(index));
+            }
+        }
+    }
+    
+    /// Compute recursive sizes of directories on background threads
+    fn compute_sizes(&mut self, paths: Vec<PathBuf>) {
+        for path in paths {
+            if self.dir_sizes.contains_key(&path) || !self.pending_sizes.insert(path.clone()) {
+                continue;
+            }
+            let tx = self.size_tx.clone();
+            thread::spawn(move || {
+                let size = file_ops::tree_size(&path);
+                let _ = tx.send((path, size));
+            });
+        }
+    }
+    
+    /// Forget cached sizes of directories containing or inside `path`
+    fn invalidate_size(&mut self, path: &Path) {
+        self.dir_sizes.retain(|dir, _| !path.starts_with(dir) && !dir.starts_with(path));
+    }
+    
+    /// Collect finished directory size computations
+    fn poll_sizes(&mut self) {
+        let mut updated = false;
+        while let Ok((path, size)) = self.size_rx.try_recv() {
+            self.pending_sizes.remove(&path);
+            if let Some(entry) = self.entries.iter_mut().find(|entry| entry.path == path && !entry.is_parent) {
+                entry.size = Some(size);
+                updated = true;
+            }
+            self.dir_sizes.insert(path, size);
+        }
+        
+        if updated && self.sort_mode == SortMode::Size {
+            self.resort();
+        }
+    }
+    
+    /// Watch exactly the directories on screen (both panes in dual-pane mode)
+    fn sync_watches(&mut self) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+        let mut wanted = Vec::new();
+        if self.archive.is_none() {
+            wanted.push(self.current_dir.clone());
+        }
+        if let Some(other) = self.other_pane.as_ref().filter(|_| self.dual_pane) {
+            if !wanted.contains(&other.current_dir) {
+                wanted.push(other.current_dir.clone());
+            }
+        }
+        if wanted == self.watched {
+            return;
+        }
+        
+        for dir in self.watched.iter().filter(|dir| !wanted.contains(dir)) {
+            let _ = watcher.unwatch(dir);
+        }
+        for dir in wanted.iter().filter(|dir| !self.watched.contains(dir)) {
+            let _ = watcher.watch(dir, RecursiveMode::NonRecursive);
+        }
+        self.watched = wanted;
+    }
+    
+    /// Reload after external changes, once events have settled
+    fn poll_watcher(&mut self) -> io::Result<()> {
+        self.sync_watches();
+        
+        while let Ok(event) = self.watch_rx.try_recv() {
+            let relevant = event.is_ok_and(|event| !matches!(event.kind, EventKind::Access(_)));
+            if relevant {
+                self.refresh_due = Some(Instant::now() + WATCH_DEBOUNCE);
+            }
+        }
+        
+        // Don't pull the listing out from under a filter, popup or archive view
+        let idle = matches!(self.mode, Mode::Normal) && self.archive.is_none();
+        if idle && self.refresh_due.is_some_and(|due| Instant::now() >= due) {
+            self.refresh_due = None;
+            self.refresh()?;
+        }
+        Ok(())
+    }
+    
+    /// Reload both panes, keeping the selection on the same entry by name
+    fn refresh(&mut self) -> io::Result<()> {
+        let selected = self.selected_entry().map(|entry| entry.name.clone());
+        let index = self.list_state.selected().unwrap_or(0);
+        self.load_directory()?;
+        match selected {
+            Some(name) if self.entries.iter().any(|entry| entry.name == name) => self.select_name(&name),
+            // The selected entry went away: stay at the same position
+            _ if !self.entries.is_empty() => {
+                self.list_state.select(Some(index.min(self.entries.len() - 1)));
+                self.update_preview();
+            }
+            _ => {}
+        }
+        self.reload_other_pane()
+    }
+    
+    /// Update preview content for selected file
+    fn update_preview(&mut self) {
+        let Some(entry) = self.list_state.selected().and_then(|selected| self.entries.get(selected)) else {
+            return;
+        };
+        if let Some(view) = &self.archive {
+            self.preview = Some(Self::archive_entry_preview(view, entry));
+            return;
+        }
+        let mut preview = self.previews.preview(&entry.path, self.preview_size.0, self.preview_size.1);
+        if preview.kind == "Archive" {
+            let hint = Line::styled("Enter Browse • e Extract Here • E Extract To", Style::default().fg(colors::muted()));
+            preview.text.lines.insert(1.min(preview.text.lines.len()), hint);
+        }
+        self.preview = Some(preview);
+    }
+    
+    /// Preview for an entry inside the archive being browsed
+    fn archive_entry_preview(view: &ArchiveView, entry: &FileEntry) -> Preview {
+        let text = if entry.is_parent {
+            format!("📦 {}\n\n{} entries", view.path.display(), view.entries.len())
+        } else {
+            let inner = format!("{}{}", view.prefix, entry.name);
+            let children = archive::children(&view.entries, &format!("{}/", inner));
+            if !entry.is_directory {
+                format!("Archive member: {}\n\n[Read-only — e extracts the archive here, E elsewhere]", inner)
+            } else if children.is_empty() {
+                "[Empty directory]".to_string()
+            } else {
+                children
+                    .iter()
+                    .take(20)
+                    .map(|child| format!("{} {}", if child.is_dir { "📁" } else { "📄" }, child.name))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        };
+        Preview { kind: "Archive", text: Text::from(text), wrap: true, truncated: None }
+    }
+    
+    /// Handle keyboard input
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        match self.mode {
+            Mode::Normal if self.archive.is_some() => self.handle_archive_input(key.code, key.modifiers)?,
+            Mode::Normal => self.handle_normal_input(key.code, key.modifiers)?,
+            Mode::Filter(_) => self.handle_filter_input(key.code, key.modifiers)?,
+            Mode::Jump(_) => self.handle_jump_input(key.code)?,
+            Mode::Properties(_) => self.handle_properties_input(key.code),
+            _ => self.handle_popup_input(key.code)?,
+        }
+        Ok(())
+    }
+    
+    /// Handle input while browsing
+    fn handle_normal_input(&mut self, key_code: KeyCode, modifiers: KeyModifiers) -> io::Result<()> {
+        // Home goes to the home directory
+        if key_code != KeyCode::Home && self.vim.navigate(&mut self.list_state, key_code, modifiers, self.entries.len(), self.settings.page_size) {
+            self.update_preview();
+            return Ok(());
+        }
+        
+        match key_code {
+            KeyCode::Esc if !self.marked.is_empty() => {
+                self.marked.clear();
+                self.notifications.info("Marks cleared");
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if self.choose == Some(Choose::Dir) {
+                    // The logical path lets the shell keep its symlinked view
+                    self.output = vec![self.logical_dir.clone().unwrap_or_else(|| self.current_dir.clone())];
+                }
+                self.should_quit = true;
+            }
+            KeyCode::Char(' ') => {
+                if let Some(entry) = self.selected_entry() {
+                    let path = entry.path.clone();
+                    if !self.marked.remove(&path) {
+                        self.marked.insert(path);
+                    }
+                    // Advance so several entries can be marked in a row
+                    if let Some(selected) = self.list_state.selected() {
+                        if selected + 1 < self.entries.len() {
+                            self.list_state.select(Some(selected + 1));
+                            self.update_preview();
+                        }
+                    }
+                }
+            }
+            KeyCode::Char('p') => {
+                self.output = self.targets();
+                if !self.output.is_empty() {
+                    self.should_quit = true;
+                }
+            }
+            KeyCode::Enter | KeyCode::Right if self.tree_mode && self.selected_entry().is_some_and(|e| e.is_directory) => {
+                let path = self.selected_entry().map(|entry| entry.path.clone()).unwrap_or_default();
+                if key_code == KeyCode::Enter || !self.expanded.contains(&path) {
+                    self.toggle_expanded(path)?;
+                }
+            }
+            KeyCode::Left if self.tree_mode => self.tree_left()?,
+            KeyCode::Char('T') => self.toggle_tree_mode()?,
+            KeyCode::Char('!') => self.shell_requested = true,
+            KeyCode::Char('x') => self.start_input(InputAction::Command),
+            KeyCode::Char('u') => self.undo()?,
+            KeyCode::Char('L') => self.start_input(InputAction::Symlink),
+            KeyCode::Enter | KeyCode::Right => {
+                if let Some(selected) = self.list_state.selected() {
+                    if let Some(entry) = self.entries.get(selected) {
+                        if entry.is_directory {
+                            let entry = entry.clone();
+                            self.enter_dir(&entry)?;
+                        } else {
+                            if self.choose.is_none() && archive::archive_kind(&entry.path).is_some() {
+                                let path = entry.path.clone();
+                                self.open_archive(path);
+                                return Ok(());
+                            }
+                            match self.choose {
+                                Some(Choose::File) => self.output = vec![entry.path.clone()],
+                                Some(Choose::Dir) => self.output = vec![self.current_dir.clone()],
+                                // Without an editor the path is printed instead
+                                None if !mru::open_in_editor(&self.settings, &entry.path, None) => println!("{}", entry.path.display()),
+                                None => {}
+                            }
+                            self.should_quit = true;
+                        }
+                    }
+                }
+            }
+            KeyCode::Left => self.go_up()?,
+            KeyCode::Char('h') => {
+                self.show_hidden = !self.show_hidden;
+                self.previews.set_show_hidden(self.show_hidden);
+                let selected = self.selected_entry().map(|entry| entry.name.clone());
+                self.load_directory()?;
+                if let Some(name) = selected {
+                    self.select_name(&name);
+                }
+                match state::set("explore.hidden", &self.show_hidden.to_string()) {
+                    Ok(()) if self.show_hidden => self.notifications.info("Showing hidden files"),
+                    Ok(()) => self.notifications.info("Hiding hidden files"),
+                    Err(e) => self.notifications.error(format!("Could not save preference: {}", e)),
+                }
+            }
+            KeyCode::Char('z') => {
+                if let Some(entry) = self.selected_entry().filter(|entry| entry.is_directory).cloned() {
+                    self.dir_sizes.remove(&entry.path);
+                    self.notifications.info(format!("Calculating size of {}...", entry.name));
+                    self.compute_sizes(vec![entry.path]);
+                }
+            }
+            KeyCode::Char('Z') => {
+                let directories: Vec<PathBuf> = self.entries
+                    .iter()
+                    .filter(|entry| entry.is_directory && !entry.is_parent)
+                    .map(|entry| entry.path.clone())
+                    .collect();
+                self.notifications.info(format!("Calculating sizes of {} directories...", directories.len()));
+                self.compute_sizes(directories);
+            }
+            KeyCode::Char('S') => {
+                self.sort_mode = self.sort_mode.next();
+                self.resort();
+                self.update_preview();
+                let saved = state::set("explore.sort", self.sort_mode.label());
+                match saved {
+                    Ok(()) => self.notifications.info(format!("Sorted by {}", self.sort_mode.label())),
+                    Err(e) => self.notifications.error(format!("Sorted by {} (not saved: {})", self.sort_mode.label(), e)),
+                }
+            }
+            KeyCode::Char('F') => {
+                self.dirs_first = !self.dirs_first;
+                self.resort();
+                self.update_preview();
+                let label = if self.dirs_first { "Directories first" } else { "Directories mixed with files" };
+                let saved = state::set("explore.dirs_first", &self.dirs_first.to_string());
+                match saved {
+                    Ok(()) => self.notifications.info(label),
+                    Err(e) => self.notifications.error(format!("{} (not saved: {})", label, e)),
+                }
+            }
+            KeyCode::Char('/') => self.start_filter(),
+            KeyCode::F(2) => self.toggle_dual_pane(),
+            KeyCode::Tab => self.switch_pane(),
+            KeyCode::Char('R') => {
+                // Refresh directory
+                self.refresh()?;
+                self.notifications.info("Directory refreshed");
+            }
+            KeyCode::Char('c') => self.start_input(InputAction::Copy),
+            KeyCode::Char('m') => self.start_input(InputAction::Move),
+            KeyCode::Char('r') => self.start_input(InputAction::Rename),
+            KeyCode::Char('n') => self.start_input(InputAction::NewFile),
+            KeyCode::Char('N') => self.start_input(InputAction::NewDirectory),
+            KeyCode::Char('d') | KeyCode::Delete if !self.targets().is_empty() => {
+                self.mode = Mode::ConfirmDelete;
+            }
+            KeyCode::Home | KeyCode::Char('~') => {
+                // Go to home directory
+                if let Some(home) = platform::home_dir() {
+                    self.change_dir(home)?;
+                }
+            }
+            KeyCode::Char('e') => {
+                if let Some(path) = self.archive_target() {
+                    self.extract_archive(&path, self.current_dir.clone());
+                }
+            }
+            KeyCode::Char('E') => self.start_input(InputAction::Extract),
+            KeyCode::Char('i') => {
+                if let Some(entry) = self.selected_entry() {
+                    self.mode = match Properties::load(&entry.path) {
+                        Ok(properties) => Mode::Properties(Box::new(properties)),
+                        Err(e) => Mode::Error(format!("Cannot read {}:\n{}", entry.path.display(), e)),
+                    };
+                }
+            }
+            KeyCode::Char('b') => self.toggle_bookmark(),
+            KeyCode::Char('y') => self.copy_targets(),
+            KeyCode::Char('\'') => self.mode = Mode::Jump(ListState::default().with_selected(Some(0))),
+            KeyCode::Char('`') => self.go_back()?,
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// Selected entry, excluding the `..` parent link
+    fn selected_entry(&self) -> Option<&FileEntry> {
+        self.list_state.selected()
+            .and_then(|i| self.entries.get(i))
+            .filter(|entry| !entry.is_parent)
+    }
+    
+    /// Copy the paths of the marked entries, or of the selection, one per line
+    fn copy_targets(&mut self) {
+        let targets = self.targets();
+        let what = match targets.as_slice() {
+            [] => return,
+            [path] => path.display().to_string(),
+            paths => format!("{} paths", paths.len()),
+        };
+        let text = targets.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join("\n");
+        clipboard::copy_and_notify(&mut self.notifications, &what, &text);
+    }
+    
+    /// Paths an operation applies to: the marked entries, or else the selection
+    fn targets(&self) -> Vec<PathBuf> {
+        if self.marked.is_empty() {
+            self.selected_entry().map(|entry| vec![entry.path.clone()]).unwrap_or_default()
+        } else {
+            self.marked.iter().cloned().collect()
+        }
+    }
+    
+    /// Select the entry at `path`, if present
+    fn select_path(&mut self, path: &Path) {
+        if let Some(index) = self.entries.iter().position(|entry| entry.path == path && !entry.is_parent) {
+            self.list_state.select(Some(index));
+            self.update_preview();
+        }
+    }
+    
+    /// Path of the selected entry, for another tool to act on
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        let entry = self.list_state.selected().and_then(|index| self.entries.get(index))?;
+        (!entry.is_parent && self.archive.is_none()).then(|| entry.path.clone())
+    }
+    
+    /// Go to the directory holding `path` and select it there
+    pub fn reveal(&mut self, path: &Path) -> io::Result<()> {
+        let path = path.canonicalize()?;
+        let parent = path.parent().unwrap_or(&path).to_path_buf();
+        // Out of an archive, and listed afresh since the file may be new
+        self.archive = None;
+        if parent == self.current_dir && self.logical_dir.is_none() {
+            self.load_directory()?;
+        } else {
+            self.change_dir(parent)?;
+        }
+        self.select_path(&path);
+        Ok(())
+    }
+    
+    /// Select the entry with the given name, if present
+    fn select_name(&mut self, name: &str) {
+        if let Some(index) = self.entries.iter().position(|entry| entry.name == name) {
+            self.list_state.select(Some(index));
+            self.update_preview();
+        }
+    }
+    
+    /// Open the prompt popup for a file operation
+    fn start_input(&mut self, action: InputAction) {
+        if self.transfer.is_some() && matches!(action, InputAction::Copy | InputAction::Move) {
+            self.notifications.error("Another copy/move is still running");
+            return;
+        }
+        
+        let initial = match action {
+            InputAction::Copy | InputAction::Move => {
+                if self.targets().is_empty() {
+                    return;
+                }
+                // Like Midnight Commander, default to the other pane's directory
+                let destination = match &self.other_pane {
+                    Some(other) if self.dual_pane => &other.current_dir,
+                    _ => &self.current_dir,
+                };
+                format!("{}/", destination.display())
+            }
+            InputAction::Rename => match self.selected_entry() {
+                Some(entry) => entry.name.clone(),
+                None => return,
+            },
+            InputAction::NewFile | InputAction::NewDirectory | InputAction::Command => String::new(),
+            InputAction::Symlink => {
+                let Some(entry) = self.selected_entry().filter(|entry| !entry.is_parent) else {
+                    return;
+                };
+                match &self.other_pane {
+                    Some(other) if self.dual_pane => format!("{}/", other.current_dir.display()),
+                    _ => format!("{}/{} link", self.current_dir.display(), entry.name),
+                }
+            }
+            InputAction::Extract => {
+                if self.archive_target().is_none() {
+                    self.notifications.error("Select an archive to extract");
+                    return;
+                }
+                let destination = match &self.other_pane {
+                    Some(other) if self.dual_pane => &other.current_dir,
+                    _ => &self.current_dir,
+                };
+                format!("{}/", destination.display())
+            }
+        };
+        self.mode = Mode::Input(action, TextInput::new(initial));
+    }
+    
+    /// Archive to extract: the one being browsed, else the selected file
+    fn archive_target(&self) -> Option<PathBuf> {
+        match &self.archive {
+            Some(view) => Some(view.path.clone()),
+            None => self
+                .selected_entry()
+                .filter(|entry| !entry.is_directory && archive::archive_kind(&entry.path).is_some())
+                .map(|entry| entry.path.clone()),
+        }
+    }
+    
+    /// Start extracting an archive without overwriting, as a job
+    fn extract_archive(&mut self, path: &Path, destination: PathBuf) {
+        let label = format!("Extracting {}", path.file_name().unwrap_or_default().to_string_lossy());
+        let job = self.notifications.jobs().spawn(label, {
+            let (path, destination) = (path.to_path_buf(), destination.clone());
+            move |task| archive::extract(task, &path, &destination)
+        });
+        self.archive_job = Some(ArchiveJob::Extract { path: path.to_path_buf(), destination, job });
+    }
+    
+    /// Start listing an archive, to browse its contents read-only once
+    /// listed
+    fn open_archive(&mut self, path: PathBuf) {
+        let label = format!("Listing {}", path.file_name().unwrap_or_default().to_string_lossy());
+        let job = self.notifications.jobs().spawn(label, {
+            let path = path.clone();
+            move |task| archive::list(task, &path)
+        });
+        self.archive_job = Some(ArchiveJob::Open { path, job });
+    }
+    
+    /// Browse the listed archive or report the extraction once the archive
+    /// job is done; returns whether it was
+    fn poll_archive(&mut self) -> bool {
+        match self.archive_job.take() {
+            Some(ArchiveJob::Open { path, mut job }) => match job.poll() {
+                None => {
+                    self.archive_job = Some(ArchiveJob::Open { path, job });
+                    return false;
+                }
+                Some(Ok(entries)) => {
+                    self.archive = Some(ArchiveView { path, entries, prefix: String::new() });
+                    self.load_archive_listing();
+                }
+                Some(Err(e)) if jobs::is_cancelled(&e) => self.notifications.info(format!("Cancelled listing {}", path.display())),
+                Some(Err(e)) => self.mode = Mode::Error(format!("Cannot open {}:\n{}", path.display(), e)),
+            },
+            Some(ArchiveJob::Extract { path, destination, mut job }) => match job.poll() {
+                None => {
+                    self.archive_job = Some(ArchiveJob::Extract { path, destination, job });
+                    return false;
+                }
+                Some(Ok(())) => {
+                    self.invalidate_size(&destination);
+                    if self.archive.is_none() {
+                        let selected = self.selected_entry().map(|entry| entry.name.clone());
+                        let _ = self.load_directory();
+                        if let Some(name) = selected {
+                            self.select_name(&name);
+                        }
+                    }
+                    let _ = self.reload_other_pane();
+                    self.notifications.success(format!("Extracted to {}", destination.display()));
+                }
+                Some(Err(e)) if jobs::is_cancelled(&e) => self.notifications.info(format!("Cancelled extracting {}", path.display())),
+                Some(Err(e)) => self.mode = Mode::Error(format!("Failed to extract {}:\n{}", path.display(), e)),
+            },
+            None => return false,
+        }
+        true
+    }
+    
+    /// Fill the entry list from the archive directory being browsed
+    fn load_archive_listing(&mut self) {
+        let Some(view) = &self.archive else {
+            return;
+        };
+        let base = view.path.join(&view.prefix);
+        self.entries = vec![FileEntry {
+            name: "..".to_string(),
+            path: base.parent().unwrap_or(&view.path).to_path_buf(),
+            is_directory: true,
+            size: None,
+            is_parent: true,
+            is_hidden: false,
+            modified: None,
+            link_target: None,
+            is_broken_link: false,
+        }];
+        self.entries.extend(archive::children(&view.entries, &view.prefix).into_iter().map(|child| FileEntry {
+            path: base.join(&child.name),
+            is_directory: child.is_dir,
+            size: None,
+            is_parent: false,
+            is_hidden: child.name.starts_with('.'),
+            modified: None,
+            link_target: None,
+            is_broken_link: false,
+            name: child.name,
+        }));
+        self.notifications.set_status(format!(
+            "{}:/{} ({} items, read-only)",
+            view.path.display(),
+            view.prefix,
+            self.entries.len() - 1
+        ));
+        self.list_state.select(Some(0));
+        self.update_preview();
+    }
+    
+    /// Go up one directory inside the archive, leaving it from the top level
+    fn archive_up(&mut self) -> io::Result<()> {
+        let Some(view) = self.archive.as_mut() else {
+            return Ok(());
+        };
+        if view.prefix.is_empty() {
+            let path = self.archive.take().map(|view| view.path).unwrap_or_default();
+            self.load_directory()?;
+            if let Some(name) = path.file_name() {
+                self.select_name(&name.to_string_lossy());
+            }
+            return Ok(());
+        }
+        
+        let trimmed = view.prefix.trim_end_matches('/');
+        let left = trimmed.rsplit_once('/').map(|(parent, name)| (format!("{}/", parent), name.to_string()));
+        let (parent, child) = left.unwrap_or_else(|| (String::new(), trimmed.to_string()));
+        view.prefix = parent;
+        self.load_archive_listing();
+        self.select_name(&child);
+        Ok(())
+    }
+    
+    /// Handle input while browsing inside an archive
+    fn handle_archive_input(&mut self, key_code: KeyCode, modifiers: KeyModifiers) -> io::Result<()> {
+        match key_code {
+            KeyCode::Up | KeyCode::Down | KeyCode::Char('j' | 'k' | 'g' | 'G') => self.handle_normal_input(key_code, modifiers)?,
+            KeyCode::Enter | KeyCode::Right => {
+                let Some(entry) = self.list_state.selected().and_then(|i| self.entries.get(i)).cloned() else {
+                    return Ok(());
+                };
+                if entry.is_parent {
+                    self.archive_up()?;
+                } else if entry.is_directory {
+                    if let Some(view) = self.archive.as_mut() {
+                        view.prefix = format!("{}{}/", view.prefix, entry.name);
+                    }
+                    self.load_archive_listing();
+                } else {
+                    self.notifications.error("Archive is read-only: e extracts here, E extracts elsewhere");
+                }
+            }
+            KeyCode::Left | KeyCode::Backspace => self.archive_up()?,
+            KeyCode::Esc | KeyCode::Char('q') => {
+                if let Some(view) = self.archive.as_mut() {
+                    view.prefix.clear();
+                }
+                self.archive_up()?;
+            }
+            KeyCode::Char('e') => {
+                if let Some(path) = self.archive_target() {
+                    self.extract_archive(&path, self.current_dir.clone());
+                }
+            }
+            KeyCode::Char('E') => self.start_input(InputAction::Extract),
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// Navigate to `path`, remembering the current directory for `` ` ``
+    fn change_dir(&mut self, path: PathBuf) -> io::Result<()> {
+        self.change_dir_via(path, None)
+    }
+    
+    /// Change directory, remembering the symlinked path it was reached by
+    fn change_dir_via(&mut self, path: PathBuf, logical: Option<PathBuf>) -> io::Result<()> {
+        if path == self.current_dir && logical == self.logical_dir {
+            return Ok(());
+        }
+        self.logical_dir = logical;
+        let previous = std::mem::replace(&mut self.current_dir, path);
+        self.history.retain(|dir| *dir != previous);
+        self.history.push(previous);
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+        self.load_directory()
+    }
+    
+    /// Current directory for display: the logical path with its resolved
+    /// location when it was reached through a symlink
+    fn location(&self) -> String {
+        match &self.logical_dir {
+            Some(logical) => format!("{} → {}", logical.display(), self.current_dir.display()),
+            None => self.current_dir.display().to_string(),
+        }
+    }
+    
+    /// Directory `..` leads to, with its logical path if that still goes
+    /// through a symlink
+    fn parent_dir(&self) -> Option<(PathBuf, Option<PathBuf>)> {
+        match &self.logical_dir {
+            Some(logical) => {
+                let parent = logical.parent()?;
+                let resolved = parent.canonicalize().ok()?;
+                let logical = (resolved != parent).then(|| parent.to_path_buf());
+                Some((resolved, logical))
+            }
+            None => self.current_dir.parent().map(|parent| (parent.to_path_buf(), None)),
+        }
+    }
+    
+    /// Go up one directory, backing out of symlinks the way they were
+    /// entered, and select the directory we came from
+    fn go_up(&mut self) -> io::Result<()> {
+        let Some((parent, logical)) = self.parent_dir() else {
+            return Ok(());
+        };
+        let child = self.logical_dir.clone().unwrap_or_else(|| self.current_dir.clone());
+        self.change_dir_via(parent, logical)?;
+        if let Some(name) = child.file_name() {
+            self.select_name(&name.to_string_lossy());
+        }
+        Ok(())
+    }
+    
+    /// Open a directory entry. Symlinked directories are entered at their
+    /// resolved location, keeping the path through the link as a breadcrumb.
+    fn enter_dir(&mut self, entry: &FileEntry) -> io::Result<()> {
+        if entry.is_parent {
+            return self.go_up();
+        }
+        if entry.link_target.is_none() && self.logical_dir.is_none() {
+            return self.change_dir(entry.path.clone());
+        }
+        let logical = self.logical_dir.as_ref().unwrap_or(&self.current_dir).join(&entry.name);
+        match entry.path.canonicalize() {
+            Ok(resolved) => self.change_dir_via(resolved, Some(logical)),
+            Err(e) => {
+                self.mode = Mode::Error(format!("Cannot open {}: {}", logical.display(), e));
+                Ok(())
+            }
+        }
+    }
+    
+    /// Return to the previously visited directory
+    fn go_back(&mut self) -> io::Result<()> {
+        let Some(previous) = self.history.pop() else {
+            self.notifications.error("No previous directory");
+            return Ok(());
+        };
+        let child = std::mem::replace(&mut self.current_dir, previous);
+        self.logical_dir = None;
+        self.load_directory()?;
+        // Re-select the directory we came back out of, if it is listed here
+        if let Some(name) = child.file_name() {
+            self.select_name(&name.to_string_lossy());
+        }
+        Ok(())
+    }
+    
+    /// Bookmark the current directory, or remove its bookmark
+    fn toggle_bookmark(&mut self) {
+        let message = if let Some(index) = self.bookmarks.iter().position(|b| *b == self.current_dir) {
+            self.bookmarks.remove(index);
+            format!("Removed bookmark {}", self.current_dir.display())
+        } else {
+            self.bookmarks.push(self.current_dir.clone());
+            format!("Bookmarked {}", self.current_dir.display())
+        };
+        match bookmarks::save(&self.bookmarks) {
+            Ok(()) => self.notifications.success(message),
+            Err(e) => self.notifications.error(format!("Failed to save bookmarks: {}", e)),
+        }
+    }
+    
+    /// Entries for the quick-jump picker: built-ins, bookmarks, then history
+    fn jump_targets(&self) -> Vec<JumpTarget> {
+        let mut targets = Vec::new();
+        if let Some(home) = platform::home_dir() {
+            targets.push(JumpTarget { key: Some('~'), label: "Home".to_string(), path: home, bookmark: None });
+        }
+        targets.push(JumpTarget { key: Some('/'), label: "Root".to_string(), path: PathBuf::from("/"), bookmark: None });
+        if let Some(root) = git_root(&self.current_dir) {
+            targets.push(JumpTarget { key: Some('g'), label: "Git root".to_string(), path: root, bookmark: None });
+        }
+        for (index, path) in self.bookmarks.iter().enumerate() {
+            targets.push(JumpTarget {
+                key: char::from_digit(index as u32 + 1, 10).filter(|_| index < 9),
+                label: "Bookmark".to_string(),
+                path: path.clone(),
+                bookmark: Some(index),
+            });
+        }
+        for (index, path) in self.history.iter().rev().enumerate() {
+            targets.push(JumpTarget {
+                key: (index == 0).then_some('`'),
+                label: "Visited".to_string(),
+                path: path.clone(),
+                bookmark: None,
+            });
+        }
+        targets
+    }
+    
+    /// Handle input while the quick-jump picker is open
+    fn handle_jump_input(&mut self, key_code: KeyCode) -> io::Result<()> {
+        let Mode::Jump(list_state) = &self.mode else {
+            return Ok(());
+        };
+        let selected = list_state.selected().unwrap_or(0);
+        let targets = self.jump_targets();
+        let last = targets.len().saturating_sub(1);
+        
+        let chosen = match key_code {
+            KeyCode::Esc | KeyCode::Char('\'') | KeyCode::Char('q') => {
+                self.mode = Mode::Normal;
+                return Ok(());
+            }
+            KeyCode::Up => {
+                self.mode = Mode::Jump(ListState::default().with_selected(Some(selected.saturating_sub(1))));
+                return Ok(());
+            }
+            KeyCode::Down => {
+                self.mode = Mode::Jump(ListState::default().with_selected(Some((selected + 1).min(last))));
+                return Ok(());
+            }
+            KeyCode::Char('d') => {
+                if let Some(index) = targets.get(selected).and_then(|target| target.bookmark) {
+                    let removed = self.bookmarks.remove(index);
+                    match bookmarks::save(&self.bookmarks) {
+                        Ok(()) => self.notifications.success(format!("Removed bookmark {}", removed.display())),
+                        Err(e) => self.notifications.error(format!("Failed to save bookmarks: {}", e)),
+                    }
+                    self.mode = Mode::Jump(ListState::default().with_selected(Some(selected.min(last - 1))));
+                }
+                return Ok(());
+            }
+            KeyCode::Enter => targets.get(selected),
+            KeyCode::Char(c) => targets.iter().find(|target| target.key == Some(c)),
+            _ => None,
+        };
+        
+        if let Some(target) = chosen {
+            let path = target.path.clone();
+            self.mode = Mode::Normal;
+            if path.is_dir() {
+                self.change_dir(path)?;
+            } else {
+                self.notifications.error(format!("{} is not a directory", path.display()));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Start fuzzy-filtering the current directory
+    fn start_filter(&mut self) {
+        self.unfiltered = self.entries.clone();
+        self.mode = Mode::Filter(TextInput::new(""));
+        self.notifications.set_status("");
+    }
+    
+    /// Narrow the listing to entries fuzzy-matching `pattern`, best first
+    fn apply_filter(&mut self, pattern: &str) {
+        self.entries = if pattern.is_empty() {
+            self.unfiltered.clone()
+        } else {
+            let candidates = self.unfiltered.iter().filter(|entry| !entry.is_parent).cloned();
+            fuzzy::filter(pattern, candidates, |entry| &entry.name)
+        };
+        self.list_state.select(if self.entries.is_empty() { None } else { Some(0) });
+        self.update_preview();
+    }
+    
+    /// Leave filter mode and restore the full listing, keeping the selection
+    fn clear_filter(&mut self) {
+        let selected = self.selected_entry().map(|entry| entry.name.clone());
+        self.entries = std::mem::take(&mut self.unfiltered);
+        self.mode = Mode::Normal;
+        self.list_state.select(if self.entries.is_empty() { None } else { Some(0) });
+        if let Some(name) = selected {
+            self.select_name(&name);
+        }
+        self.update_preview();
+    }
+    
+    /// Handle input while the fuzzy filter is active
+    fn handle_filter_input(&mut self, key_code: KeyCode, modifiers: KeyModifiers) -> io::Result<()> {
+        match key_code {
+            KeyCode::Esc => self.clear_filter(),
+            KeyCode::Enter => {
+                // Open the selected match from the unfiltered listing
+                let has_selection = self.list_state.selected().is_some();
+                self.clear_filter();
+                if has_selection {
+                    self.handle_normal_input(KeyCode::Enter, modifiers)?;
+                }
+            }
+            KeyCode::Up | KeyCode::Down => self.handle_normal_input(key_code, modifiers)?,
+            _ => {
+                let Mode::Filter(input) = &mut self.mode else {
+                    return Ok(());
+                };
+                if input.handle_key(key_code) {
+                    let pattern = input.value().to_string();
+                    self.apply_filter(&pattern);
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// Handle input in the properties popup: rwx grid, octal and chown prompts
+    fn handle_properties_input(&mut self, key_code: KeyCode) {
+        let Mode::Properties(properties) = &mut self.mode else {
+            return;
+        };
+        
+        if let Some((field, input)) = properties.input.as_mut() {
+            match key_code {
+                KeyCode::Esc => properties.input = None,
+                KeyCode::Enter => {
+                    let field = *field;
+                    let value = input.value().trim().to_string();
+                    properties.input = None;
+                    match field {
+                        PropertyInput::Octal => match file_ops::parse_octal_mode(&value) {
+                            Some(mode) => {
+                                properties.mode = mode;
+                                properties.error = None;
+                            }
+                            None => properties.error = Some(format!("'{}' is not an octal mode", value)),
+                        },
+                        PropertyInput::Owner if value.is_empty() => {}
+                        PropertyInput::Owner => match file_ops::chown(&properties.path, &value) {
+                            Ok(()) => {
+                                // Reload owner/group but keep any pending mode edits
+                                let mode = properties.mode;
+                                if let Ok(reloaded) = Properties::load(&properties.path) {
+                                    **properties = reloaded;
+                                    properties.mode = mode;
+                                }
+                                self.notifications.success(format!("Changed owner of {} to {}", properties.path.display(), value));
+                            }
+                            Err(e) => properties.error = Some(format!("chown failed: {}", e)),
+                        },
+                    }
+                }
+                _ => {
+                    input.handle_key(key_code);
+                }
+            }
+            return;
+        }
+        
+        let (row, column) = properties.cursor;
+        match key_code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::Normal,
+            KeyCode::Up => properties.cursor.0 = row.saturating_sub(1),
+            KeyCode::Down => properties.cursor.0 = (row + 1).min(2),
+            KeyCode::Left => properties.cursor.1 = column.saturating_sub(1),
+            KeyCode::Right => properties.cursor.1 = (column + 1).min(2),
+            KeyCode::Char(' ') => properties.mode ^= properties.cursor_bit(),
+            KeyCode::Char('o') => {
+                let current = format!("{:04o}", properties.mode);
+                properties.input = Some((PropertyInput::Octal, TextInput::new(current)));
+            }
+            KeyCode::Char('c') => properties.input = Some((PropertyInput::Owner, TextInput::new(""))),
+            KeyCode::Enter => {
+                if properties.mode == properties.original_mode {
+                    self.mode = Mode::Normal;
+                    return;
+                }
+                match file_ops::set_mode(&properties.path, properties.mode) {
+                    Ok(()) => {
+                        self.notifications.success(format!(
+                            "Changed mode of {} to {:04o} ({})",
+                            properties.path.display(),
+                            properties.mode,
+                            file_ops::format_mode(properties.mode, properties.is_dir)
+                        ));
+                        self.mode = Mode::Normal;
+                    }
+                    Err(e) => properties.error = Some(format!("chmod failed: {}", e)),
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// Handle input while a popup is open
+    fn handle_popup_input(&mut self, key_code: KeyCode) -> io::Result<()> {
+        match &mut self.mode {
+            Mode::Normal | Mode::Filter(_) | Mode::Jump(_) | Mode::Properties(_) => {}
+            Mode::Error(_) => self.mode = Mode::Normal,
+            Mode::Output { lines, scroll, .. } => match key_code {
+                KeyCode::Up => *scroll = scroll.saturating_sub(1),
+                KeyCode::Down => *scroll = (*scroll + 1).min(lines.len().saturating_sub(1)),
+                KeyCode::PageUp => *scroll = scroll.saturating_sub(10),
+                KeyCode::PageDown => *scroll = (*scroll + 10).min(lines.len().saturating_sub(1)),
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => self.mode = Mode::Normal,
+                _ => {}
+            },
+            Mode::ConfirmDelete => match key_code {
+                KeyCode::Char('t') | KeyCode::Char('T') | KeyCode::Enter => self.delete_targets(true),
+                KeyCode::Char('D') => self.delete_targets(false),
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    self.mode = Mode::Normal;
+                    self.notifications.info("Delete cancelled");
+                }
+                _ => {}
+            },
+            Mode::Input(action, input) => match key_code {
+                KeyCode::Esc => self.mode = Mode::Normal,
+                KeyCode::Enter => {
+                    let action = *action;
+                    let value = input.value().trim().to_string();
+                    self.mode = Mode::Normal;
+                    if !value.is_empty() {
+                        self.apply_input(action, &value)?;
+                    }
+                }
+                _ => {
+                    input.handle_key(key_code);
+                }
+            },
+        }
+        Ok(())
+    }
+    
+    /// Run the file operation for a confirmed prompt
+    fn apply_input(&mut self, action: InputAction, value: &str) -> io::Result<()> {
+        match action {
+            InputAction::Copy | InputAction::Move => {
+                let sources = self.targets();
+                let Some(first) = sources.first().cloned() else {
+                    return Ok(());
+                };
+                let kind = if action == InputAction::Copy { Transfer::Copy } else { Transfer::Move };
+                let destination = self.current_dir.join(value);
+                self.marked.clear();
+                let mut affected = sources.clone();
+                affected.push(destination.clone());
+                let (job, events) = file_ops::spawn_transfer(self.notifications.jobs(), kind, sources, destination);
+                self.transfer = Some(ActiveTransfer {
+                    kind,
+                    job,
+                    events,
+                    done: 0,
+                    total: 0,
+                    current: first,
+                    affected,
+                });
+            }
+            InputAction::Rename => {
+                let Some(entry) = self.selected_entry().cloned() else {
+                    return Ok(());
+                };
+                self.rename_entry(&entry, value)?;
+            }
+            InputAction::NewFile | InputAction::NewDirectory => self.create_entry(action, value)?,
+            InputAction::Extract => {
+                if let Some(path) = self.archive_target() {
+                    self.extract_archive(&path, self.current_dir.join(value));
+                }
+            }
+            InputAction::Command => self.run_command(value),
+            InputAction::Symlink => {
+                let Some(entry) = self.selected_entry().cloned() else {
+                    return Ok(());
+                };
+                match file_ops::symlink(&entry.path, &self.current_dir.join(value)) {
+                    Ok(link) => {
+                        self.last_op = Some(UndoOp::Create(link.clone()));
+                        self.refresh()?;
+                        self.notifications.success(format!("Linked {} -> {}", link.display(), entry.path.display()));
+                    }
+                    Err(e) => self.mode = Mode::Error(format!("Could not create symlink: {}", e)),
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// Run a shell command in the current directory and show its output.
+    ///
+    /// `{}` is replaced by the quoted target paths (marked entries, else the
+    /// selection); commands without `{}` run as typed.
+    fn run_command(&mut self, template: &str) {
+        let paths: Vec<String> = self.targets().iter().map(|path| shell_quote(path)).collect();
+        let command = template.replace("{}", &paths.join(" "));
+        
+        // A command that waits for input or never ends must not freeze the
+        // explorer; Ctrl-J cancels it
+        let mut script = platform::script_command(&command);
+        script.current_dir(&self.current_dir);
+        let job = self.notifications.jobs().spawn(format!("$ {}", command), move |task| task.output(&mut script));
+        self.command = Some((command, job));
+    }
+    
+    /// Show the output of the command from the `x` prompt once it is done;
+    /// returns whether it was
+    fn poll_command(&mut self) -> io::Result<bool> {
+        let Some(result) = self.command.as_mut().and_then(|(_, job)| job.poll()) else {
+            return Ok(false);
+        };
+        let command = self.command.take().map(|(command, _)| command).unwrap_or_default();
+        let output = match result {
+            Ok(output) => output,
+            Err(e) if jobs::is_cancelled(&e) => {
+                self.notifications.info(format!("Cancelled {}", command));
+                return Ok(true);
+            }
+            Err(e) => {
+                self.mode = Mode::Error(format!("Failed to run {}:\n{}", command, e));
+                return Ok(true);
+            }
         };
         
-        explorer.load_directory()?;
+        let mut lines: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(String::from).collect();
+        lines.extend(String::from_utf8_lossy(&output.stderr).lines().map(String::from));
+        if lines.is_empty() {
+            lines.push("[No output]".to_string());
+        }
+        let status = match output.status.code() {
+            Some(0) => "ok".to_string(),
+            Some(code) => format!("exit {}", code),
+            None => "killed".to_string(),
+        };
+        self.mode = Mode::Output {
+            title: format!("$ {} ({})", command, status),
+            lines,
+            scroll: 0,
+        };
         
-        Ok(explorer)
+        // The command may have changed files
+        self.refresh()?;
+        Ok(true)
     }
     
-    /// Load current directory contents
-    fn load_directory(&mut self) -> io::Result<()> {
-        self.entries.clear();
+    /// Run `$SHELL` in the current directory until the user exits it
+    fn spawn_shell(&self) -> io::Result<()> {
+        let shell = platform::user_shell();
+        eprintln!("Starting {} in {} — type `exit` to return to tt", shell, self.current_dir.display());
         
-        // Add parent directory entry if not at root
-        if self.current_dir.parent().is_some() {
-            self.entries.push(FileEntry {
-                name: "..".to_string(),
-                path: self.current_dir.parent().unwrap().to_path_buf(),
-                is_directory: true,
-                size: None,
-                is_parent: true,
-            });
+        let mut command = Command::new(&shell);
+        command.current_dir(&self.current_dir);
+        // When stdout is captured (e.g. `--choose-dir`), keep the shell on the terminal
+        #[cfg(unix)]
+        if !io::stdout().is_terminal() {
+            use std::os::fd::AsFd;
+            command.stdout(io::stderr().as_fd().try_clone_to_owned()?);
         }
-        
-        // Read directory entries
-        let mut entries = Vec::new();
-        if let Ok(dir_entries) = fs::read_dir(&self.current_dir) {
-            for entry in dir_entries.flatten() {
-                let path = entry.path();
-                let name = entry.file_name().to_string_lossy().to_string();
-                
-                // Skip hidden files (starting with .)
-                if name.starts_with('.') && name != ".." {
-                    continue;
-                }
-                
-                let is_directory = path.is_dir();
-                let size = if is_directory {
-                    None
-                } else {
-                    fs::metadata(&path).ok().map(|m| m.len())
-                };
-                
-                entries.push(FileEntry {
-                    name,
-                    path,
-                    is_directory,
-                    size,
-                    is_parent: false,
-                });
+        command.status().map(|_| ())
+    }
+    
+    /// Rename an entry within the current directory
+    fn rename_entry(&mut self, entry: &FileEntry, value: &str) -> io::Result<()> {
+        match file_ops::rename(&entry.path, value) {
+            Ok(new_path) => {
+                self.last_op = Some(UndoOp::Rename { from: entry.path.clone(), to: new_path });
+                self.invalidate_size(&entry.path);
+                self.load_directory()?;
+                self.select_name(value);
+                self.notifications.success(format!("Renamed {} to {}", entry.name, value));
             }
+            Err(e) => self.mode = Mode::Error(format!("Rename failed: {}", e)),
         }
+        Ok(())
+    }
+    
+    /// Create a new empty file or directory in the current directory
+    fn create_entry(&mut self, action: InputAction, name: &str) -> io::Result<()> {
+        let path = self.current_dir.join(name);
+        let result = if action == InputAction::NewDirectory {
+            fs::create_dir_all(&path)
+        } else {
+            file_ops::create_file(&path)
+        };
         
-        // Sort: directories first, then files, both alphabetically
-        entries.sort_by(|a, b| {
-            match (a.is_directory, b.is_directory) {
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        match result {
+            Ok(()) => {
+                self.invalidate_size(&path);
+                self.last_op = Some(UndoOp::Create(path.clone()));
+                self.load_directory()?;
+                self.select_name(name);
+                self.notifications.success(format!("Created {}", path.display()));
             }
-        });
+            Err(e) => self.mode = Mode::Error(format!("Could not create {}: {}", path.display(), e)),
+        }
+        Ok(())
+    }
+    
+    /// Delete the marked (or selected) entries, via the trash or permanently
+    fn delete_targets(&mut self, to_trash: bool) {
+        self.mode = Mode::Normal;
+        let targets = self.targets();
+        if targets.is_empty() {
+            return;
+        }
+        let index = self.list_state.selected().unwrap_or(0);
         
-        self.entries.extend(entries);
+        let mut errors = Vec::new();
+        let mut trashed = Vec::new();
+        for path in &targets {
+            let result = if to_trash {
+                file_ops::trash(path).map(|item| trashed.push(item))
+            } else {
+                file_ops::remove(path)
+            };
+            match result {
+                Ok(()) => {
+                    self.marked.remove(path);
+                    self.invalidate_size(path);
+                }
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
         
-        // Reset selection
+        let _ = self.reload_other_pane();
+        let _ = self.load_directory();
         if !self.entries.is_empty() {
-            self.list_state.select(Some(0));
+            self.list_state.select(Some(index.min(self.entries.len() - 1)));
             self.update_preview();
-        } else {
-            self.list_state.select(None);
-            self.preview_content.clear();
         }
         
-        self.status_message = format!("Directory: {} ({} items)", 
-            self.current_dir.display(), 
-            self.entries.len()
-        );
-        
-        Ok(())
-    }
-    
-    /// Update preview content for selected file
-    fn update_preview(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(entry) = self.entries.get(selected) {
-                self.preview_content = self.load_file_preview(&entry.path, entry.is_directory);
-            }
+        if !trashed.is_empty() {
+            self.last_op = Some(UndoOp::Trash(trashed));
+        }
+        let deleted = targets.len() - errors.len();
+        self.notifications.success(if to_trash {
+            format!("Moved {} item(s) to trash", deleted)
+        } else {
+            format!("Deleted {} item(s)", deleted)
+        });
+        if !errors.is_empty() {
+            self.mode = Mode::Error(format!("Could not delete:\n{}", errors.join("\n")));
         }
     }
     
-    /// Load file preview content
-    fn load_file_preview(&self, path: &Path, is_directory: bool) -> String {
-        if is_directory {
-            // For directories, show contents
-            if let Ok(dir_entries) = fs::read_dir(path) {
-                let mut contents = Vec::new();
-                for entry in dir_entries.flatten().take(20) {
-                    let name = entry.file_name().to_string_lossy().to_string();
-                    let icon = if entry.path().is_dir() { "📁" } else { "📄" };
-                    contents.push(format!("{} {}", icon, name));
-                }
-                if contents.is_empty() {
-                    "[Empty directory]".to_string()
-                } else {
-                    contents.join("\n")
+    /// Reverse the last rename, create, trash, copy or move
+    fn undo(&mut self) -> io::Result<()> {
+        let Some(op) = self.last_op.take() else {
+            self.notifications.info("Nothing to undo");
+            return Ok(());
+        };
+        
+        let mut errors = Vec::new();
+        let mut touched = Vec::new();
+        let summary = match op {
+            UndoOp::Rename { from, to } => {
+                if from.symlink_metadata().is_ok() {
+                    errors.push(format!("{} already exists", from.display()));
+                } else if let Err(e) = fs::rename(&to, &from) {
+                    errors.push(format!("{}: {}", to.display(), e));
                 }
-            } else {
-                "[Permission denied]".to_string()
+                touched.extend([from, to]);
+                "Undid rename"
             }
-        } else {
-            // Check if it's an image file first
-            if crate::image_preview::is_image_file(path) {
-                return crate::image_preview::generate_image_preview(path);
+            UndoOp::Create(path) => {
+                if let Err(e) = file_ops::trash(&path) {
+                    errors.push(format!("{}: {}", path.display(), e));
+                }
+                touched.push(path);
+                "Undid create (moved to trash)"
             }
-            
-            // For files, show content preview
-            match fs::read_to_string(path) {
-                Ok(content) => {
-                    let lines: Vec<&str> = content.lines().take(50).collect();
-                    lines.join("\n")
-                }
-                Err(_) => {
-                    // For binary files or read errors, show file info
-                    if let Ok(metadata) = fs::metadata(path) {
-                        format!(
-                            "File: {}\nSize: {} bytes\nModified: {:?}\n\n[Binary file or read error]",
-                            path.display(),
-                            metadata.len(),
-                            metadata.modified().ok()
-                        )
-                    } else {
-                        "[Could not read file]".to_string()
+            UndoOp::Trash(items) => {
+                for item in items {
+                    if let Err(e) = file_ops::restore(&item) {
+                        errors.push(format!("{}: {}", item.original.display(), e));
                     }
+                    touched.push(item.original);
                 }
+                "Restored from trash"
             }
-        }
-    }
-    
-    /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page down
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.entries.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page up
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.entries.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected > 0 {
-                                self.list_state.select(Some(selected - 1));
-                                self.update_preview();
-                            }
-                        }
-                    }
-                    KeyCode::Down => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected + 1 < self.entries.len() {
-                                self.list_state.select(Some(selected + 1));
-                                self.update_preview();
-                            }
-                        } else if !self.entries.is_empty() {
-                            self.list_state.select(Some(0));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Enter | KeyCode::Right => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if let Some(entry) = self.entries.get(selected) {
-                                if entry.is_directory {
-                                    // Navigate to directory
-                                    self.current_dir = entry.path.clone();
-                                    self.load_directory()?;
-                                } else {
-                                    // Open file
-                                    self.open_file(&entry.path)?;
-                                    self.should_quit = true;
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Left => {
-                        // Go up one directory
-                        if let Some(parent) = self.current_dir.parent() {
-                            self.current_dir = parent.to_path_buf();
-                            self.load_directory()?;
-                        }
-                    }
-                    KeyCode::Char('h') => {
-                        // Toggle hidden files (currently not implemented)
-                        self.status_message = "Hidden files toggle not implemented yet".to_string();
+            UndoOp::Transfer { kind, pairs } => {
+                for (source, target) in pairs {
+                    let result = match kind {
+                        Transfer::Copy => file_ops::trash(&target).map(|_| ()),
+                        Transfer::Move if source.symlink_metadata().is_ok() => Err(io::Error::new(
+                            io::ErrorKind::AlreadyExists,
+                            format!("{} already exists", source.display()),
+                        )),
+                        Transfer::Move => file_ops::move_path(&target, &source),
+                    };
+                    if let Err(e) = result {
+                        errors.push(format!("{}: {}", target.display(), e));
                     }
-                    KeyCode::Char('r') => {
-                        // Refresh directory
-                        self.load_directory()?;
-                        self.status_message = "Directory refreshed".to_string();
-                    }
-                    KeyCode::Home => {
-                        // Go to home directory
-                        if let Ok(home) = env::var("HOME") {
-                            self.current_dir = PathBuf::from(home);
-                            self.load_directory()?;
-                        }
-                    }
-                    _ => {}
+                    touched.extend([source, target]);
+                }
+                match kind {
+                    Transfer::Copy => "Undid copy (copies moved to trash)",
+                    Transfer::Move => "Undid move",
                 }
             }
+        };
+        
+        for path in &touched {
+            self.invalidate_size(path);
+        }
+        self.refresh()?;
+        self.notifications.success(summary);
+        if !errors.is_empty() {
+            self.mode = Mode::Error(format!("Undo incomplete:\n{}", errors.join("\n")));
         }
         Ok(())
     }
     
-    /// Open selected file in default editor
-    fn open_file(&self, path: &Path) -> io::Result<()> {
-        // Try different editors in order of preference
-        let editors = ["nvim", "vim", "nano", "code"];
+    /// Drain progress events from a running copy/move
+    fn poll_transfer(&mut self) -> io::Result<()> {
+        let Some(transfer) = self.transfer.as_mut() else {
+            return Ok(());
+        };
         
-        for editor in editors.iter() {
-            let result = Command::new(editor)
-                .arg(path)
-                .status();
-                
-            if result.is_ok() {
-                return Ok(());
-            }
+        for TransferProgress { done, total, current } in transfer.events.try_iter() {
+            transfer.done = done;
+            transfer.total = total;
+            transfer.current = current;
         }
+        let Some(result) = transfer.job.poll() else {
+            return Ok(());
+        };
         
-        // If no editor found, just print the path
-        println!("{}", path.display());
+        let kind = transfer.kind;
+        for path in std::mem::take(&mut transfer.affected) {
+            self.invalidate_size(&path);
+        }
+        self.transfer = None;
+        self.reload_other_pane()?;
+        let selected = self.selected_entry().map(|entry| entry.name.clone());
+        self.load_directory()?;
+        if let Some(name) = selected {
+            self.select_name(&name);
+        }
+        match result {
+            Ok(pairs) => {
+                self.notifications.success(format!("{} {} item(s)", kind.past(), pairs.len()));
+                self.last_op = Some(UndoOp::Transfer { kind, pairs });
+            }
+            Err(e) if jobs::is_cancelled(&e) => self.notifications.info(format!("{} cancelled", kind.verb())),
+            Err(e) => self.notifications.error(format!("{} failed: {}", kind.verb(), e)),
+        }
         Ok(())
     }
     
     /// Render the file explorer interface
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        self.area = area;
+        self.image_slot = None;
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(f.area());
+            .split(area);
         
-        // Left panel - file list
-        self.render_file_list(f, chunks[0]);
-        
-        // Right panel - preview
-        self.render_preview(f, chunks[1]);
+        if self.dual_pane {
+            // Two file lists; the preview is replaced by the inactive pane
+            let (active_area, other_area) = if self.active_side == 0 {
+                (chunks[0], chunks[1])
+            } else {
+                (chunks[1], chunks[0])
+            };
+            self.render_file_list(f, active_area);
+            self.render_other_pane(f, other_area);
+        } else {
+            // Left panel - file list
+            self.render_file_list(f, chunks[0]);
+            
+            // Right panel - preview
+            self.render_preview(f, chunks[1]);
+            self.image_slot = self.image_slot(chunks[1]);
+        }
         
         // Status bar
-        self.render_status_bar(f);
+        self.render_status_bar(f, area);
+        
+        self.render_popup(f);
+    }
+    
+    /// Render the active popup, if any
+    fn render_popup(&self, f: &mut Frame) {
+        match &self.mode {
+            Mode::Normal | Mode::Filter(_) => {}
+            Mode::Jump(list_state) => self.render_jump_picker(f, list_state),
+            Mode::Properties(properties) => Self::render_properties(f, properties),
+            Mode::Input(action, input) => {
+                tui_common::render_input_popup(f, action.title(), action.label(), input);
+            }
+            Mode::ConfirmDelete => {
+                let question = if self.marked.is_empty() {
+                    let name = self.selected_entry().map(|entry| entry.name.as_str()).unwrap_or_default();
+                    format!("Delete '{}'?", name)
+                } else {
+                    format!("Delete {} marked item(s)?", self.marked.len())
+                };
+                let lines = vec![
+                    Line::from(Span::styled(
+                        question,
+                        Style::default().fg(colors::danger()).add_modifier(Modifier::BOLD)
+                    )),
+                    Line::from(""),
+                    Line::from("T/Enter Move to trash • D Delete permanently • N/Esc Cancel"),
+                ];
+                tui_common::render_message_popup(f, "Confirm Delete", lines, colors::danger());
+            }
+            Mode::Error(message) => {
+                let mut lines: Vec<Line> = message
+                    .lines()
+                    .map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(colors::danger()))))
+                    .collect();
+                lines.extend([
+                    Line::from(""),
+                    Line::from(Span::styled("Press any key to continue", Style::default().fg(colors::muted()))),
+                ]);
+                tui_common::render_message_popup(f, "Error", lines, colors::danger());
+            }
+            Mode::Output { title, lines, scroll } => {
+                let area = tui_common::centered_rect(
+                    f.area().width.saturating_mul(4) / 5,
+                    f.area().height.saturating_mul(7) / 10,
+                    f.area(),
+                );
+                let text: Vec<Line> = lines.iter().map(|line| Line::from(line.as_str())).collect();
+                let paragraph = Paragraph::new(text)
+                    .block(Block::default()
+                        .borders(Borders::ALL)
+                        .title(title.as_str())
+                        .title_bottom(Line::from(" ↑↓/PgUp/PgDn Scroll • Esc Close ").style(Style::default().fg(colors::muted())))
+                        .border_style(Style::default().fg(colors::primary())))
+                    .scroll((*scroll as u16, 0));
+                f.render_widget(Clear, area);
+                f.render_widget(paragraph, area);
+            }
+        }
+    }
+    
+    /// Render the properties popup with its rwx permission grid
+    fn render_properties(f: &mut Frame, properties: &Properties) {
+        let label_style = Style::default().fg(colors::secondary());
+        let mut lines: Vec<Line> = properties
+            .details
+            .iter()
+            .map(|(label, value)| Line::from(vec![
+                Span::styled(format!("{:<10}", label), label_style),
+                Span::raw(value.clone()),
+            ]))
+            .collect();
+        
+        let mut mode_line = vec![
+            Span::styled(format!("{:<10}", "Mode"), label_style),
+            Span::styled(
+                format!("{} ({:04o})", file_ops::format_mode(properties.mode, properties.is_dir), properties.mode),
+                Style::default().fg(colors::text()).add_modifier(Modifier::BOLD),
+            ),
+        ];
+        if properties.mode != properties.original_mode {
+            mode_line.push(Span::styled(
+                format!("  was {:04o}", properties.original_mode),
+                Style::default().fg(colors::muted()),
+            ));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(mode_line));
+        lines.push(Line::from(""));
+        
+        lines.push(Line::from(Span::styled("           r   w   x", label_style)));
+        for (row, name) in ["User", "Group", "Other"].iter().enumerate() {
+            let mut spans = vec![Span::styled(format!("  {:<7}", name), label_style)];
+            for column in 0..3 {
+                let bit = 0o400 >> (row * 3 + column);
+                let mark = if properties.mode & bit != 0 { "[x]" } else { "[ ]" };
+                let style = if properties.cursor == (row, column) {
+                    Style::default().fg(colors::primary()).add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default().fg(colors::text())
+                };
+                spans.push(Span::styled(mark, style));
+                spans.push(Span::raw(" "));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::from(""));
+        
+        match &properties.input {
+            Some((field, input)) => {
+                let label = match field {
+                    PropertyInput::Octal => "Octal mode: ",
+                    PropertyInput::Owner => "Owner[:group]: ",
+                };
+                let mut spans = vec![Span::styled(label, label_style)];
+                spans.extend(input.line().spans);
+                lines.push(Line::from(spans));
+                lines.push(Line::from(Span::styled("Enter Confirm • Esc Cancel", Style::default().fg(colors::muted()))));
+            }
+            None => lines.extend([
+                Line::from(Span::styled("←→↑↓ Move • Space Toggle • o Octal • c Chown", Style::default().fg(colors::muted()))),
+                Line::from(Span::styled("Enter Apply • Esc Cancel", Style::default().fg(colors::muted()))),
+            ]),
+        }
+        if let Some(error) = &properties.error {
+            lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(colors::danger()))));
+        }
+        
+        tui_common::render_message_popup(f, "Properties", lines, colors::primary());
+    }
+    
+    /// Render the bookmark and quick-jump picker
+    fn render_jump_picker(&self, f: &mut Frame, list_state: &ListState) {
+        let targets = self.jump_targets();
+        let height = (targets.len() as u16).saturating_add(2).min(f.area().height.saturating_sub(2));
+        let area = tui_common::centered_rect(f.area().width.saturating_mul(3) / 5, height, f.area());
+        
+        let items: Vec<Line> = targets
+            .iter()
+            .map(|target| {
+                let key = target.key.map(|c| c.to_string()).unwrap_or_default();
+                Line::from(vec![
+                    Span::styled(format!("{:>2}  ", key), Style::default().fg(colors::success()).add_modifier(Modifier::BOLD)),
+                    Span::styled(format!("{:<9}", target.label), Style::default().fg(colors::secondary())),
+                    Span::styled(target.path.display().to_string(), Style::default().fg(colors::text())),
+                ])
+            })
+            .collect();
+        
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Jump To • Enter/key Go • d Delete Bookmark • Esc Close")
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(list, area, &mut list_state.clone());
     }
     
     /// Render the file list panel
     fn render_file_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.entries
+        let mut title = if let Some(view) = &self.archive {
+            let name = view.path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            format!("📦 {}:/{} ({})", name, view.prefix, self.entries.len() - 1)
+        } else if self.dual_pane {
+            // Long paths give up their start, leaving room for the flags after
+            let location = self.location();
+            format!("{} ({})", text_width::truncate_start(&location, area.width as usize / 2), self.entries.len())
+        } else {
+            format!("Files & Directories ({})", self.entries.len())
+        };
+        if self.archive.is_none() {
+            if self.tree_mode {
+                title.push_str(" • tree");
+            }
+            title.push_str(&format!(" • by {}", self.sort_mode.label()));
+            if self.dirs_first {
+                title.push_str(", dirs first");
+            }
+        }
+        if !self.marked.is_empty() {
+            title.push_str(&format!(" • {} marked", self.marked.len()));
+        }
+        
+        let list = self.file_list(&self.entries, title, true);
+        let mut list_state = self.list_state.clone();
+        f.render_stateful_widget(list, area, &mut list_state);
+        self.list_state = list_state;
+    }
+    
+    /// Render the inactive pane in dual-pane mode
+    fn render_other_pane(&self, f: &mut Frame, area: Rect) {
+        let Some(other) = &self.other_pane else {
+            return;
+        };
+        let count = format!(" ({})", other.entries.len());
+        let path = other.current_dir.display().to_string();
+        let path = text_width::truncate_start(&path, (area.width as usize).saturating_sub(count.len() + 2));
+        let title = format!("{}{}", path, count);
+        let list = self.file_list(&other.entries, title, false);
+        f.render_stateful_widget(list, area, &mut other.list_state.clone());
+    }
+    
+    /// Build the list widget for a pane's entries
+    fn file_list<'a>(&'a self, entries: &'a [FileEntry], title: String, focused: bool) -> SelectableList<'a> {
+        let items: Vec<Line> = entries
             .iter()
             .map(|entry| {
                 let icon = if entry.is_parent {
                     "⬆️ "
+                } else if self.tree_mode && self.expanded.contains(&entry.path) {
+                    "📂 "
                 } else if entry.is_directory {
                     "📁 "
                 } else {
@@ -326,138 +2288,376 @@ impl FileExplorer {
                 };
                 
                 let size_info = if let Some(size) = entry.size {
-                    format!(" ({})", format_size(size))
+                    format!(" ({})", tui_common::format_size(size))
+                } else if self.pending_sizes.contains(&entry.path) && !entry.is_parent {
+                    " (…)".to_string()
                 } else {
                     String::new()
                 };
                 
+                let marker = if self.marked.contains(&entry.path) {
+                    Span::styled("✓ ", Style::default().fg(colors::success()).add_modifier(Modifier::BOLD))
+                } else {
+                    Span::raw("  ")
+                };
+                
+                let guide = match self.tree_guides.get(&entry.path) {
+                    Some(guide) if self.tree_mode && !entry.is_parent => guide.as_str(),
+                    _ => "",
+                };
+                
+                let link = match &entry.link_target {
+                    Some(target) => Span::styled(
+                        format!(" -> {}", target.display()),
+                        Style::default().fg(if entry.is_broken_link { colors::danger() } else { colors::muted() }),
+                    ),
+                    None => Span::raw(""),
+                };
+                
                 let line = Line::from(vec![
+                    marker,
+                    Span::styled(guide, Style::default().fg(colors::muted())),
                     Span::raw(icon),
                     Span::styled(
                         &entry.name,
-                        if entry.is_directory {
-                            Style::default().fg(colors::PRIMARY).add_modifier(Modifier::BOLD)
+                        if entry.is_broken_link {
+                            Style::default().fg(colors::danger())
+                        } else if entry.is_hidden {
+                            Style::default().fg(colors::muted())
+                        } else if entry.is_directory {
+                            Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)
                         } else {
-                            Style::default().fg(colors::TEXT)
+                            Style::default().fg(colors::text())
                         }
                     ),
+                    link,
                     Span::styled(
                         size_info,
-                        Style::default().fg(colors::SECONDARY)
+                        Style::default().fg(colors::secondary())
                     ),
                 ]);
                 
-                ListItem::new(line)
+                line
             })
             .collect();
         
-        let title = format!("Files & Directories ({})", self.entries.len());
+        let (border, highlight) = if focused {
+            (colors::primary(), Style::default().bg(colors::primary()).fg(colors::background()).add_modifier(Modifier::BOLD))
+        } else {
+            (colors::muted(), Style::default().add_modifier(Modifier::REVERSED))
+        };
         
-        let list = List::new(items)
+        SelectableList::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
-        
-        f.render_stateful_widget(list, area, &mut self.list_state);
+                .border_style(Style::default().fg(border)))
+            .highlight_style(highlight)
     }
     
     /// Render the preview panel
-    fn render_preview(&self, f: &mut Frame, area: Rect) {
+    fn render_preview(&mut self, f: &mut Frame, area: Rect) {
+        // Image previews fill the pane, so follow its size
+        let size = (area.width.saturating_sub(2), area.height.saturating_sub(2));
+        if size != self.preview_size {
+            self.preview_size = size;
+            self.update_preview();
+        }
+        
         let title = if let Some(selected) = self.list_state.selected() {
-            if let Some(entry) = self.entries.get(selected) {
-                format!("Preview: {}", entry.name)
-            } else {
-                "Preview".to_string()
+            match (self.entries.get(selected), &self.preview) {
+                (Some(entry), Some(preview)) => format!("Preview: {} ({})", entry.name, preview.label()),
+                (Some(entry), None) => format!("Preview: {}", entry.name),
+                (None, _) => "Preview".to_string(),
             }
         } else {
             "Preview".to_string()
         };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(colors::secondary()));
         
-        let paragraph = Paragraph::new(self.preview_content.as_str())
-            .block(Block::default()
-                .borders(Borders::ALL)
-                .title(title)
-                .border_style(Style::default().fg(colors::SECONDARY)))
-            .wrap(Wrap { trim: true });
+        let paragraph = match &self.preview {
+            Some(preview) => preview.paragraph(block),
+            None => Paragraph::new("").block(block),
+        };
         
         f.render_widget(paragraph, area);
     }
     
-    /// Render status bar
-    fn render_status_bar(&self, f: &mut Frame) {
-        let area = Rect {
-            x: 0,
-            y: f.area().height - 1,
-            width: f.area().width,
-            height: 1,
-        };
+    /// Area inside the preview panel left free for a graphics-protocol
+    /// thumbnail of the selected image, below its text details
+    fn image_slot(&self, preview_area: Rect) -> Option<(PathBuf, Rect)> {
+        self.graphics?;
+        if self.archive.is_some() || !matches!(self.mode, Mode::Normal | Mode::Filter(_)) {
+            return None;
+        }
+        let entry = self.selected_entry().filter(|entry| !entry.is_directory && image_preview::is_image_file(&entry.path))?;
         
-        let help_text = "↑↓ Navigate • Enter/→ Open • ← Back • Home Home • R Refresh • Esc Quit";
-        let status_text = if !self.status_message.is_empty() {
-            format!("{} | {}", self.status_message, help_text)
-        } else {
-            help_text.to_string()
+        let inner = Rect {
+            x: preview_area.x + 1,
+            y: preview_area.y + 1,
+            width: preview_area.width.saturating_sub(2),
+            height: preview_area.height.saturating_sub(2),
+        };
+        let width = inner.width.max(1) as usize;
+        let text_rows: usize = self.preview
+            .iter()
+            .flat_map(|preview| &preview.text.lines)
+            .map(|line| text_width::rows(&line.to_string(), width))
+            .sum();
+        let offset = (text_rows as u16 + 1).min(inner.height);
+        let slot = Rect {
+            y: inner.y + offset,
+            height: inner.height - offset,
+            ..inner
+        };
+        (slot.width >= 4 && slot.height >= 2).then(|| (entry.path.clone(), slot))
+    }
+    
+    /// Draw, replace or remove the graphics-protocol thumbnail after a frame.
+    ///
+    /// Images bypass ratatui and are written straight to the terminal, so
+    /// they are only re-sent when the image or its slot changes.
+    fn sync_image<B: ratatui::backend::Backend + Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        let Some(protocol) = self.graphics else {
+            return Ok(());
         };
+        if self.image_slot == self.shown_image {
+            return Ok(());
+        }
         
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
+        if self.shown_image.take().is_some() {
+            match protocol {
+                GraphicsProtocol::Kitty => write!(terminal.backend_mut(), "{}", image_preview::KITTY_CLEAR)?,
+                // Inline images replace cell contents; repaint every cell to wipe them
+                GraphicsProtocol::Iterm2 | GraphicsProtocol::Sixel => {
+                    terminal.clear()?;
+                    let area = self.area;
+                    terminal.draw(|f| self.render(f, area))?;
+                }
+            }
+        }
         
-        f.render_widget(paragraph, area);
+        if let Some((path, slot)) = self.image_slot.clone() {
+            if let Ok(data) = image_preview::encode_graphics(&path, protocol, slot.width, slot.height) {
+                let backend = terminal.backend_mut();
+                crossterm::queue!(backend, crossterm::cursor::MoveTo(slot.x, slot.y))?;
+                backend.write_all(data.as_bytes())?;
+            }
+            self.shown_image = Some((path, slot));
+        }
+        Write::flush(terminal.backend_mut())
     }
     
-    /// Run the file explorer application
-    pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
+    /// Render status bar
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
         
-        let result = self.run_app(&mut terminal);
+        if let Some(transfer) = &self.transfer {
+            let ratio = if transfer.total > 0 {
+                (transfer.done as f64 / transfer.total as f64).min(1.0)
+            } else {
+                0.0
+            };
+            let name = transfer.current.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let gauge = Gauge::default()
+                .gauge_style(Style::default().fg(colors::success()).bg(colors::background()))
+                .ratio(ratio)
+                .label(format!(
+                    "{} {} — {} / {}",
+                    transfer.kind.verb(),
+                    name,
+                    tui_common::format_size(transfer.done),
+                    tui_common::format_size(transfer.total)
+                ));
+            f.render_widget(gauge, area);
+            return;
+        }
         
-        tui_common::restore_terminal(&mut terminal)?;
+        if let Mode::Filter(input) = &self.mode {
+            let mut spans = vec![Span::raw(" / ")];
+            spans.extend(input.line().spans);
+            spans.push(Span::raw(format!(
+                " — {} of {} | Type to filter • ↑↓ Navigate • Enter Open • Esc Clear",
+                self.entries.len(),
+                self.unfiltered.len()
+            )));
+            let paragraph = Paragraph::new(Line::from(spans))
+                .style(Style::default().bg(colors::primary()).fg(colors::background()));
+            f.render_widget(paragraph, area);
+            return;
+        }
         
-        result
+        let help_text = if self.archive.is_some() {
+            "Enter/→ Open • ← Up • e/E Extract • ? Help • Esc Leave Archive"
+        } else {
+            "/ Filter • Enter/→ Open • ← Back • Space Mark • c/m/r Copy/Move/Rename • d Delete • ? Help • Esc Quit"
+        };
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
     }
     
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            
-            self.handle_input()?;
-            
-            if self.should_quit {
-                break;
-            }
+    /// Run the file explorer application, from where it was left in this
+    /// directory
+    pub fn run(&mut self) -> io::Result<()> {
+        // Keep stdout clean for the chosen paths when it is captured or piped
+        let result = if self.choose.is_some() || !io::stdout().is_terminal() {
+            session::resume(self, "dir", |explorer| tui_common::run_app_on(explorer, io::stderr()))
+        } else {
+            session::resume(self, "dir", |explorer| tui_common::run_app_on(explorer, io::stdout()))
+        };
+        
+        // Print chosen paths for shell consumption, e.g. `tt dir | xargs ...`
+        for path in &self.output {
+            println!("{}", path.display());
         }
         
+        result
+    }
+    
+    /// Remove a Kitty thumbnail before leaving the screen; inline images go
+    /// away with the cells they were drawn in
+    fn clear_image<B: ratatui::backend::Backend + Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        if self.shown_image.take().is_some() && self.graphics == Some(GraphicsProtocol::Kitty) {
+            write!(terminal.backend_mut(), "{}", image_preview::KITTY_CLEAR)?;
+            Write::flush(terminal.backend_mut())?;
+        }
         Ok(())
     }
 }
 
-/// Format file size in human readable format
-fn format_size(size: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size = size as f64;
-    let mut unit_index = 0;
+impl App for FileExplorer {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick => {
+                // Redraw while anything is on its way: progress, sizes or a reload
+                let busy = self.transfer.is_some() || !self.pending_sizes.is_empty() || self.refresh_due.is_some();
+                self.poll_transfer()?;
+                self.poll_sizes();
+                self.poll_watcher()?;
+                if self.poll_command()? | self.poll_archive() {
+                    return Ok(Action::Redraw);
+                }
+                if self.previews.poll() {
+                    self.update_preview();
+                    return Ok(Action::Redraw);
+                }
+                if busy {
+                    return Ok(Action::Redraw);
+                }
+            }
+        }
+        Ok(if self.should_quit {
+            Action::Quit
+        } else if std::mem::take(&mut self.shell_requested) {
+            Action::Suspend
+        } else {
+            Action::Continue
+        })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        if self.archive.is_some() {
+            ARCHIVE_KEYMAP
+        } else {
+            KEYMAP
+        }
+    }
+    
+    fn captures_text(&self) -> bool {
+        match &self.mode {
+            Mode::Input(..) | Mode::Filter(_) => true,
+            Mode::Properties(properties) => properties.input.is_some(),
+            _ => false,
+        }
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn context(&self) -> Context {
+        Context {
+            dir: Some(self.current_dir.clone()),
+            file: self.selected_path().filter(|path| path.is_file()),
+            ..Context::default()
+        }
+    }
     
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_index += 1;
+    fn suspended(&mut self) -> io::Result<Action> {
+        if let Err(e) = self.spawn_shell() {
+            self.mode = Mode::Error(format!("Failed to start shell:\n{}", e));
+        }
+        self.refresh()?;
+        Ok(Action::Continue)
+    }
+    
+    fn after_draw<B: ratatui::backend::Backend + Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        self.sync_image(terminal)
+    }
+    
+    fn clear_overlay<B: ratatui::backend::Backend + Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        self.clear_image(terminal)
+    }
+}
+
+impl Resume for FileExplorer {
+    fn session(&self) -> Session {
+        let selected = self.list_state.selected().and_then(|index| self.entries.get(index));
+        Session {
+            selected: selected.filter(|entry| !entry.is_parent && self.archive.is_none()).map(|entry| entry.name.clone()),
+            dir: Some(self.current_dir.clone()),
+            ..Session::default()
+        }
     }
     
-    if unit_index == 0 {
-        format!("{:.0}{}", size, UNITS[unit_index])
-    } else {
-        format!("{:.1}{}", size, UNITS[unit_index])
+    /// Go back to the directory left, unless another one was asked for
+    fn restore(&mut self, session: Session) {
+        let here = env::current_dir().and_then(|dir| dir.canonicalize()).ok();
+        if here.as_ref() != Some(&self.current_dir) {
+            return;
+        }
+        if let Some(dir) = session.dir.filter(|dir| *dir != self.current_dir && dir.is_dir()) {
+            if let Err(e) = self.change_dir(dir) {
+                self.notifications.error(format!("Could not reopen the last directory: {}", e));
+                return;
+            }
+        }
+        if let Some(name) = session.selected {
+            self.select_name(&name);
+        }
     }
 }
 
-/// Run the file explorer tool
-pub fn run(path: PathBuf) -> io::Result<()> {
-    let mut explorer = FileExplorer::new(path)?;
+/// Run the file explorer tool, or print the entries of `path` in `output`
+pub fn run(path: PathBuf, choose: Option<Choose>, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    if let Some(format) = output {
+        return print(&path, format, &settings);
+    }
+    let mut explorer = FileExplorer::new(path, settings)?;
+    explorer.choose = choose;
     explorer.run()
+}
+
+/// Print the entries of `path` as the explorer would list them, with its
+/// saved hidden-file and sort settings
+fn print(path: &Path, format: Format, settings: &Settings) -> io::Result<()> {
+    let dir = path.canonicalize()?;
+    let mut entries = list_entries(&dir, state::get_bool("explore.hidden", false), settings);
+    let sort_mode = state::get("explore.sort")
+        .and_then(|label| SortMode::from_label(&label))
+        .unwrap_or(SortMode::Name);
+    sort_entries(&mut entries, sort_mode, state::get_bool("explore.dirs_first", true));
+    plain::print(format, entries.iter().map(|entry| vec![
+        ("path", Value::from(entry.path.to_string_lossy())),
+        ("dir", Value::from(entry.is_directory)),
+        ("size", Value::from(entry.size)),
+        ("modified", Value::from(entry.modified.and_then(|time| time.duration_since(UNIX_EPOCH).ok()).map(|age| age.as_secs()))),
+    ]))
 }
\ No newline at end of file
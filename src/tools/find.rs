@@ -1,93 +1,119 @@
 //! File finder tool with fuzzy search and preview.
 
-use crate::tui_common::{self, colors};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crate::clipboard;
+use crate::config::Settings;
+use crate::jobs::{self, Job, Task};
+use crate::mru;
+use crate::notify::Notifications;
+use crate::palette::Context;
+use crate::plain::{self, Format};
+use crate::preview::{Preview, PreviewEngine};
+use crate::session::{self, Resume, Session};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Debounce, Keymap, NavKey, SelectableList, VimNav};
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
+    style::Style,
     text::Line,
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
-    Frame, Terminal,
+    widgets::{Block, Borders, ListState, Paragraph},
+    Frame,
 };
+use serde_json::Value;
 use std::{
-    fs,
     io,
     path::{Path, PathBuf},
-    process::Command,
-    time::Duration,
 };
 use walkdir::WalkDir;
 
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Filter files by name"),
+    ("Backspace", "Delete a filter character"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Home / End", "First / last item"),
+    ("Enter", "Open in the editor"),
+    ("y", "Copy the path (normal mode)"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
 pub struct FileFinder {
     files: Vec<PathBuf>,
     filtered_files: Vec<PathBuf>,
     list_state: ListState,
+    vim: VimNav,
     search_query: String,
-    preview_content: String,
+    /// Filtering waits for typing to pause
+    filter_debounce: Debounce,
+    /// Preview of the selected file
+    preview: Option<Preview>,
+    previews: PreviewEngine,
+    /// Columns and rows inside the preview border at the last draw
+    preview_size: (u16, u16),
     should_quit: bool,
-    status_message: String,
+    notifications: Notifications,
+    /// The walk for files running in the background
+    walk: Option<Job<Vec<PathBuf>>>,
+    /// File to select once the walk restored from the session is done
+    resume_selected: Option<String>,
+    settings: Settings,
 }
 
 impl FileFinder {
     /// Create a new file finder instance
-    pub fn new(start_path: PathBuf, extensions: Option<String>, initial_search: Option<String>) -> io::Result<Self> {
+    pub fn new(start_path: PathBuf, extensions: Option<String>, initial_search: Option<String>, settings: Settings) -> io::Result<Self> {
         let mut finder = FileFinder {
             files: Vec::new(),
             filtered_files: Vec::new(),
             list_state: ListState::default(),
+            vim: VimNav::default(),
             search_query: initial_search.unwrap_or_default(),
-            preview_content: String::new(),
+            filter_debounce: Debounce::default(),
+            preview: None,
+            previews: PreviewEngine::new(&settings),
+            preview_size: (0, 0),
             should_quit: false,
-            status_message: "Loading files...".to_string(),
+            notifications: Notifications::with_status("Loading files..."),
+            walk: None,
+            resume_selected: None,
+            settings,
         };
         
-        finder.load_files(start_path, extensions)?;
-        finder.update_filter();
+        let settings = finder.settings.clone();
+        let label = format!("Walk {}", start_path.display());
+        finder.walk = Some(finder.notifications.jobs().spawn(label, move |task| load_files(task, &start_path, extensions, &settings)));
         
         Ok(finder)
     }
     
-    /// Load all files from the starting path
-    fn load_files(&mut self, start_path: PathBuf, extensions: Option<String>) -> io::Result<()> {
-        let ext_filter: Option<Vec<String>> = extensions.map(|exts| {
-            exts.split(',').map(|s| s.trim().to_lowercase()).collect()
-        });
-        
-        for entry in WalkDir::new(start_path)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            if entry.file_type().is_file() {
-                let path = entry.path().to_path_buf();
-                
-                // Filter by extension if specified
-                if let Some(ref filters) = ext_filter {
-                    if let Some(ext) = path.extension() {
-                        let ext_str = ext.to_string_lossy().to_lowercase();
-                        if !filters.contains(&ext_str) {
-                            continue;
-                        }
-                    } else {
-                        continue; // Skip files without extensions when filtering
-                    }
-                }
-                
-                // Skip hidden files and common build directories
-                let path_str = path.to_string_lossy();
-                if path_str.contains("/.git/") || 
-                   path_str.contains("/node_modules/") || 
-                   path_str.contains("/target/") ||
-                   path_str.contains("/.vscode/") {
-                    continue;
-                }
-                
-                self.files.push(path);
-            }
+    /// Show the files once the walk is done; returns whether it was
+    fn poll_walk(&mut self) -> bool {
+        let Some(result) = self.walk.as_mut().and_then(Job::poll) else {
+            return false;
+        };
+        self.walk = None;
+        match result {
+            Ok(files) => self.set_files(files),
+            Err(e) if jobs::is_cancelled(&e) => self.notifications.set_status("Walk cancelled"),
+            Err(e) => self.notifications.error(format!("Could not list files: {}", e)),
         }
+        true
+    }
+    
+    fn set_files(&mut self, files: Vec<PathBuf>) {
+        self.files = files;
+        self.update_filter();
+        self.notifications.set_status(format!("Found {} files", self.files.len()));
         
-        self.status_message = format!("Found {} files", self.files.len());
-        Ok(())
+        let position = self.resume_selected.take().and_then(|selected| {
+            self.filtered_files.iter().position(|path| path.to_string_lossy() == selected)
+        });
+        if let Some(position) = position {
+            self.list_state.select(Some(position));
+            self.update_preview();
+        }
     }
     
     /// Update filtered files based on search query
@@ -111,146 +137,81 @@ impl FileFinder {
             self.update_preview();
         } else {
             self.list_state.select(None);
-            self.preview_content.clear();
+            self.preview = None;
         }
     }
     
     /// Update preview content for selected file
     fn update_preview(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(path) = self.filtered_files.get(selected) {
-                self.preview_content = self.load_file_preview(path);
-            }
+        if let Some(path) = self.selected_path() {
+            self.preview = Some(self.previews.preview(&path, self.preview_size.0, self.preview_size.1));
         }
     }
     
-    /// Load file preview content
-    fn load_file_preview(&self, path: &Path) -> String {
-        // Check if it's an image file first
-        if crate::image_preview::is_image_file(path) {
-            return crate::image_preview::generate_image_preview(path);
+    /// The selected file, for another tool to act on
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        self.list_state.selected().and_then(|index| self.filtered_files.get(index)).cloned()
+    }
+    
+    /// Handle keyboard input
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if !self.vim.typing(&key) && self.filter_debounce.take() {
+            self.update_filter();
         }
         
-        // Try to read file content
-        match fs::read_to_string(path) {
-            Ok(content) => {
-                // Limit preview to first 50 lines
-                let lines: Vec<&str> = content.lines().take(50).collect();
-                lines.join("\n")
-            }
-            Err(_) => {
-                // For binary files or read errors, show file info
-                if let Ok(metadata) = fs::metadata(path) {
-                    format!(
-                        "File: {}\nSize: {} bytes\nModified: {:?}\n\n[Binary file or read error]",
-                        path.display(),
-                        metadata.len(),
-                        metadata.modified().ok()
-                    )
-                } else {
-                    "[Could not read file]".to_string()
-                }
+        let len = self.filtered_files.len();
+        match self.vim.filter_key(&mut self.list_state, key.code, key.modifiers, len, self.settings.page_size) {
+            NavKey::Moved => {
+                self.update_preview();
+                return Ok(());
             }
+            NavKey::Used => return Ok(()),
+            NavKey::Unused => {}
         }
-    }
-    
-    /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page down
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.filtered_files.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page up
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.filtered_files.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected > 0 {
-                                self.list_state.select(Some(selected - 1));
-                                self.update_preview();
-                            }
-                        }
-                    }
-                    KeyCode::Down => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected + 1 < self.filtered_files.len() {
-                                self.list_state.select(Some(selected + 1));
-                                self.update_preview();
-                            }
-                        } else if !self.filtered_files.is_empty() {
-                            self.list_state.select(Some(0));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Enter => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if let Some(path) = self.filtered_files.get(selected) {
-                                self.open_file(path)?;
-                                self.should_quit = true;
-                            }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Enter => {
+                if let Some(selected) = self.list_state.selected() {
+                    if let Some(path) = self.filtered_files.get(selected) {
+                        // Without an editor the path is printed instead
+                        if !mru::open_in_editor(&self.settings, path, None) {
+                            println!("{}", path.display());
                         }
+                        self.should_quit = true;
                     }
-                    KeyCode::Char(c) => {
-                        self.search_query.push(c);
-                        self.update_filter();
-                    }
-                    KeyCode::Backspace => {
-                        self.search_query.pop();
-                        self.update_filter();
-                    }
-                    _ => {}
                 }
             }
+            KeyCode::Char('y') if !self.vim.inserting() => self.copy_selected(),
+            KeyCode::Char(c) if self.vim.inserting() => {
+                self.search_query.push(c);
+                self.filter_debounce.schedule();
+            }
+            KeyCode::Backspace if self.vim.inserting() => {
+                self.search_query.pop();
+                self.filter_debounce.schedule();
+            }
+            _ => {}
         }
         Ok(())
     }
     
-    /// Open selected file in default editor
-    fn open_file(&self, path: &Path) -> io::Result<()> {
-        // Try different editors in order of preference
-        let editors = ["nvim", "vim", "nano", "code"];
-        
-        for editor in editors.iter() {
-            let result = Command::new(editor)
-                .arg(path)
-                .status();
-                
-            if result.is_ok() {
-                return Ok(());
-            }
+    /// Copy the selected path to the clipboard
+    fn copy_selected(&mut self) {
+        if let Some(path) = self.list_state.selected().and_then(|i| self.filtered_files.get(i)) {
+            let path = path.display().to_string();
+            clipboard::copy_and_notify(&mut self.notifications, &path, &path);
         }
-        
-        // If no editor found, just print the path
-        println!("{}", path.display());
-        Ok(())
     }
     
     /// Render the file finder interface
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(f.area());
+            .split(area);
         
         // Left panel - file list
         self.render_file_list(f, chunks[0]);
@@ -259,12 +220,12 @@ impl FileFinder {
         self.render_preview(f, chunks[1]);
         
         // Status bar
-        self.render_status_bar(f);
+        self.render_status_bar(f, area);
     }
     
     /// Render the file list panel
     fn render_file_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.filtered_files
+        let items: Vec<Line> = self.filtered_files
             .iter()
             .map(|path| {
                 let display_path = if let Ok(current_dir) = std::env::current_dir() {
@@ -276,7 +237,7 @@ impl FileFinder {
                     path.display().to_string()
                 };
                 
-                ListItem::new(Line::from(display_path))
+                Line::from(display_path)
             })
             .collect();
         
@@ -286,93 +247,191 @@ impl FileFinder {
             format!("Files ({}) - Filter: '{}'", self.filtered_files.len(), self.search_query)
         };
         
-        let list = List::new(items)
+        let list = SelectableList::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
+                .border_style(Style::default().fg(colors::primary())));
         
         f.render_stateful_widget(list, area, &mut self.list_state);
     }
     
     /// Render the preview panel
-    fn render_preview(&self, f: &mut Frame, area: Rect) {
-        let title = if let Some(selected) = self.list_state.selected() {
-            if let Some(path) = self.filtered_files.get(selected) {
-                format!("Preview: {}", path.file_name().unwrap_or_default().to_string_lossy())
-            } else {
-                "Preview".to_string()
-            }
-        } else {
-            "Preview".to_string()
+    fn render_preview(&mut self, f: &mut Frame, area: Rect) {
+        // Image previews fill the pane, so follow its size
+        let size = (area.width.saturating_sub(2), area.height.saturating_sub(2));
+        if size != self.preview_size {
+            self.preview_size = size;
+            self.update_preview();
+        }
+        
+        let title = match (self.selected_path(), &self.preview) {
+            (Some(path), Some(preview)) => format!("Preview: {} ({})", path.file_name().unwrap_or_default().to_string_lossy(), preview.label()),
+            (Some(path), None) => format!("Preview: {}", path.file_name().unwrap_or_default().to_string_lossy()),
+            (None, _) => "Preview".to_string(),
         };
         
-        let paragraph = Paragraph::new(self.preview_content.as_str())
-            .block(Block::default()
-                .borders(Borders::ALL)
-                .title(title)
-                .border_style(Style::default().fg(colors::SECONDARY)))
-            .wrap(Wrap { trim: true });
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(colors::secondary()));
+        let paragraph = match &self.preview {
+            Some(preview) => preview.paragraph(block),
+            None => Paragraph::new("").block(block),
+        };
         
         f.render_widget(paragraph, area);
     }
     
     /// Render status bar
-    fn render_status_bar(&self, f: &mut Frame) {
-        let area = Rect {
-            x: 0,
-            y: f.area().height - 1,
-            width: f.area().width,
-            height: 1,
-        };
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
         
-        let help_text = "Type to filter • ↑↓ Navigate • Ctrl-F/B Page • Enter Open • Esc Quit";
-        let status_text = if !self.status_message.is_empty() {
-            format!("{} | {}", self.status_message, help_text)
+        let help_text = if self.vim.inserting() {
+            "Type to filter • Enter Open • Esc Normal mode • F1 Help"
         } else {
-            help_text.to_string()
+            "j/k Move • / Filter • Enter Open • y Copy • ? Help • q Quit"
         };
-        
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
-        
-        f.render_widget(paragraph, area);
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
     }
     
-    /// Run the file finder application
+    /// Run the file finder application, from where it was left in this
+    /// directory
     pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
-        
-        let result = self.run_app(&mut terminal);
-        
-        tui_common::restore_terminal(&mut terminal)?;
-        
-        result
+        session::resume(self, "find", tui_common::run_app)
+    }
+    
+    /// Print the matching files instead of running the TUI
+    pub fn print(&mut self, format: Format) -> io::Result<()> {
+        if let Some(walk) = self.walk.take() {
+            let files = walk.wait()?;
+            self.files = files;
+            self.update_filter();
+        }
+        plain::print(format, self.filtered_files.iter().map(|path| vec![("path", Value::from(path.to_string_lossy()))]))
+    }
+}
+
+impl App for FileFinder {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick if self.filter_debounce.ready() => {
+                self.update_filter();
+                return Ok(Action::Redraw);
+            }
+            AppEvent::Tick if self.previews.poll() => {
+                self.update_preview();
+                return Ok(Action::Redraw);
+            }
+            AppEvent::Tick if self.poll_walk() => return Ok(Action::Redraw),
+            AppEvent::Tick => {}
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
     }
     
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
+    fn captures_text(&self) -> bool {
+        self.vim.inserting()
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn context(&self) -> Context {
+        Context { file: self.selected_path(), ..Context::default() }
+    }
+}
+
+impl Resume for FileFinder {
+    fn session(&self) -> Session {
+        let selected = self.list_state.selected().and_then(|index| self.filtered_files.get(index));
+        Session {
+            query: Some(self.search_query.clone()),
+            selected: selected.map(|path| path.to_string_lossy().into_owned()),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query.filter(|_| self.search_query.is_empty()) {
+            self.search_query = query;
+            self.update_filter();
+        }
+        if self.walk.is_some() {
+            // The files come in the background
+            self.resume_selected = session.selected;
+            return;
+        }
+        let position = session.selected.and_then(|selected| {
+            self.filtered_files.iter().position(|path| path.to_string_lossy() == selected)
+        });
+        if let Some(position) = position {
+            self.list_state.select(Some(position));
+            self.update_preview();
+        }
+    }
+}
+
+/// All files under `start_path`, of the comma-separated `extensions` if
+/// given, counted as the job's progress while the walk goes on
+fn load_files(task: &Task, start_path: &Path, extensions: Option<String>, settings: &Settings) -> io::Result<Vec<PathBuf>> {
+    let ext_filter: Option<Vec<String>> = extensions.map(|exts| {
+        exts.split(',').map(|s| s.trim().to_lowercase()).collect()
+    });
+    
+    let mut files = Vec::new();
+    for entry in WalkDir::new(start_path)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !settings.is_ignored(&e.file_name().to_string_lossy()))
+        .filter_map(|e| e.ok())
+    {
+        task.check()?;
+        if entry.file_type().is_file() {
+            let path = entry.path().to_path_buf();
             
-            self.handle_input()?;
+            // Filter by extension if specified
+            if let Some(ref filters) = ext_filter {
+                if let Some(ext) = path.extension() {
+                    let ext_str = ext.to_string_lossy().to_lowercase();
+                    if !filters.contains(&ext_str) {
+                        continue;
+                    }
+                } else {
+                    continue; // Skip files without extensions when filtering
+                }
+            }
             
-            if self.should_quit {
-                break;
+            // Skip hidden files and common build directories
+            let path_str = path.to_string_lossy();
+            if path_str.contains("/.git/") || 
+               path_str.contains("/node_modules/") || 
+               path_str.contains("/target/") ||
+               path_str.contains("/.vscode/") {
+                continue;
             }
+            
+            files.push(path);
+            task.progress(files.len() as u64, None);
         }
-        
-        Ok(())
     }
+    Ok(files)
 }
 
-/// Run the file finder tool
-pub fn run(path: PathBuf, extensions: Option<String>, search: Option<String>) -> io::Result<()> {
-    let mut finder = FileFinder::new(path, extensions, search)?;
-    finder.run()
+/// Run the file finder tool, or print the files it finds in `output`
+pub fn run(path: PathBuf, extensions: Option<String>, search: Option<String>, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut finder = FileFinder::new(path, extensions, search, settings)?;
+    match output {
+        Some(format) => finder.print(format),
+        None => finder.run(),
+    }
 }
\ No newline at end of file
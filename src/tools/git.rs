@@ -1,46 +1,82 @@
 //! Git operations and history browser.
 
+use crate::ansi;
 use crate::cli::GitCommands;
-use crate::tui_common::{self, colors};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crate::clipboard;
+use crate::config::Settings;
+use crate::diff::{self, DiffView, FileDiff};
+use crate::jobs::{self, Job, Jobs, Task};
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::session::{self, Resume, Session};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, SelectableList, VimNav};
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
-    Frame, Terminal,
+    widgets::{Block, Borders, ListState, Paragraph, Wrap},
+    Frame,
 };
+use serde_json::Value;
 use std::{
     io,
-    process::{Command, Stdio},
-    time::Duration,
+    path::{Path, PathBuf},
+    process::Command,
 };
 
-/// Run a git command with timeout to prevent hanging
-fn run_git_command_with_timeout(args: &[&str], timeout_secs: u64) -> io::Result<String> {
-    use std::time::Instant;
-    
-    let start = Instant::now();
-    let mut cmd = Command::new("git");
-    cmd.args(args).stdout(Stdio::piped()).stderr(Stdio::piped());
-    
-    let output = cmd.output()?;
-    
-    // Simple timeout check (not perfect but better than hanging)
-    if start.elapsed().as_secs() > timeout_secs {
-        return Err(io::Error::new(io::ErrorKind::TimedOut, "Git command timed out"));
-    }
+/// Keys of the log, listed by the help popup
+const LOG_KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("Home / End, gg / G", "First / last item"),
+    ("y", "Copy the commit hash"),
+    ("Esc / q", "Quit"),
+];
+
+/// Keys of the branch switcher, listed by the help popup
+const BRANCH_KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("Home / End, gg / G", "First / last item"),
+    ("Enter", "Switch to the branch"),
+    ("Esc / q", "Quit"),
+];
+
+/// Keys of the diff view, listed by the help popup
+const DIFF_KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Scroll"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("g / Home", "Go to the top"),
+    ("G / End", "Go to the bottom"),
+    ("] / [", "Next / previous hunk"),
+    ("s", "Side by side or unified"),
+    ("Esc / q", "Quit"),
+];
+
+/// Run git with `args` in a job, which stops it when cancelled
+fn run_git(task: &Task, args: &[&str]) -> io::Result<String> {
+    let output = task.output(Command::new("git").args(args))?;
     
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
+        Err(io::Error::other(
             format!("Git command failed: {}", String::from_utf8_lossy(&output.stderr))
         ))
     }
 }
 
+/// Run git with `args` and wait for it, for plain output where there is no
+/// screen to keep responsive
+fn run_git_now(args: &[&'static str]) -> io::Result<String> {
+    let args = args.to_vec();
+    Jobs::default().spawn(format!("git {}", args.join(" ")), move |task| run_git(task, &args)).wait()
+}
+
 /// Git commit information
 #[derive(Debug, Clone)]
 pub struct GitCommit {
@@ -63,217 +99,178 @@ pub struct GitBranch {
 pub struct GitLogBrowser {
     commits: Vec<GitCommit>,
     list_state: ListState,
+    vim: VimNav,
     should_quit: bool,
-    status_message: String,
-    preview_content: String,
+    notifications: Notifications,
+    /// Commit header and stats, then the start of the patch
+    preview_content: Vec<Line<'static>>,
+    /// `git log` running in the background
+    loading: Option<Job<Vec<GitCommit>>>,
+    /// `git show` of the selected commit running in the background
+    preview: Option<Job<Vec<Line<'static>>>>,
+    /// Commit to select once the log restored from the session is loaded
+    resume_selected: Option<String>,
+    /// Only the commits touching this file or directory, with their patch
+    /// limited to it
+    path: Option<PathBuf>,
+    settings: Settings,
 }
 
 impl GitLogBrowser {
-    /// Create a new git log browser
-    pub fn new() -> io::Result<Self> {
+    /// Create a new git log browser, of the commits touching `path` if given
+    pub fn new(path: Option<PathBuf>, settings: Settings) -> io::Result<Self> {
         let mut browser = GitLogBrowser {
             commits: Vec::new(),
             list_state: ListState::default(),
+            vim: VimNav::default(),
             should_quit: false,
-            status_message: "Loading git log...".to_string(),
-            preview_content: String::new(),
+            notifications: Notifications::with_status("Loading git log..."),
+            preview_content: Vec::new(),
+            loading: None,
+            preview: None,
+            resume_selected: None,
+            path,
+            settings,
         };
         
-        browser.load_commits()?;
+        browser.load_commits();
         
         Ok(browser)
     }
     
-    /// Load git commits
-    fn load_commits(&mut self) -> io::Result<()> {
-        let log_output = match run_git_command_with_timeout(
-            &["log", "--pretty=format:%H|%h|%s|%an|%ar", "-50"], 
-            5  // 5 second timeout
-        ) {
-            Ok(output) => output,
-            Err(_) => {
-                self.status_message = "Error: Not a git repository, git not found, or command timed out".to_string();
-                return Ok(());
+    /// Start loading the git commits in the background
+    fn load_commits(&mut self) {
+        let path = self.path.clone();
+        self.loading = Some(self.notifications.jobs().spawn("git log", move |task| {
+            let mut args = vec!["log", "--pretty=format:%H|%h|%s|%an|%ar", "-50"];
+            let path = path.as_ref().map(|path| path.to_string_lossy());
+            if let Some(path) = &path {
+                args.extend(["--", path]);
             }
+            let log_output = run_git(task, &args)?;
+            Ok(log_output.lines().filter_map(parse_commit).collect())
+        }));
+    }
+    
+    /// Show the commits once they are loaded; returns whether they were
+    fn poll_commits(&mut self) -> bool {
+        let Some(result) = self.loading.as_mut().and_then(Job::poll) else {
+            return false;
         };
-        
-        for line in log_output.lines() {
-            let parts: Vec<&str> = line.split('|').collect();
-            if parts.len() >= 5 {
-                self.commits.push(GitCommit {
-                    hash: parts[0].to_string(),
-                    short_hash: parts[1].to_string(),
-                    message: parts[2].to_string(),
-                    author: parts[3].to_string(),
-                    date: parts[4].to_string(),
-                });
+        self.loading = None;
+        match result {
+            Ok(commits) => self.set_commits(commits),
+            Err(e) if jobs::is_cancelled(&e) => self.notifications.set_status("Loading cancelled"),
+            Err(_) => {
+                self.notifications.set_status("");
+                self.notifications.error("Not a git repository or git not found");
             }
         }
-        
+        true
+    }
+    
+    fn set_commits(&mut self, commits: Vec<GitCommit>) {
+        self.commits = commits;
+        let resumed = self.resume_selected.take().and_then(|hash| self.commits.iter().position(|commit| commit.hash == hash));
         if !self.commits.is_empty() {
-            self.list_state.select(Some(0));
+            self.list_state.select(Some(resumed.unwrap_or(0)));
             self.update_preview();
         }
         
-        self.status_message = format!("Loaded {} commits", self.commits.len());
-        Ok(())
+        self.notifications.set_status(format!("Loaded {} commits", self.commits.len()));
     }
     
-    /// Update preview for selected commit
+    /// Start loading the preview of the selected commit, cancelling the
+    /// one loading before
     fn update_preview(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(commit) = self.commits.get(selected) {
-                self.preview_content = self.load_commit_diff(&commit.hash);
-            }
-        }
+        let Some(commit) = self.list_state.selected().and_then(|selected| self.commits.get(selected)) else {
+            return;
+        };
+        let (hash, path) = (commit.hash.clone(), self.path.clone());
+        let label = format!("git show {}", commit.short_hash);
+        self.preview_content = vec![Line::styled(format!("Loading {}...", commit.short_hash), Style::default().fg(colors::muted()))];
+        self.preview = Some(self.notifications.jobs().spawn(label, move |task| Ok(load_commit_diff(task, &hash, path.as_deref()))));
     }
     
-    /// Load commit diff with optimization for large commits
-    fn load_commit_diff(&self, hash: &str) -> String {
-        // First, get just the commit info and stats (fast)
-        let mut result = match run_git_command_with_timeout(
-            &["show", "--color=never", "--stat", "--no-patch", hash],
-            3  // 3 second timeout for stats
-        ) {
-            Ok(output) => output,
-            Err(_) => format!("Commit: {}\n", hash),
+    /// Show the preview once it is loaded; returns whether it was
+    fn poll_preview(&mut self) -> bool {
+        let Some(result) = self.preview.as_mut().and_then(Job::poll) else {
+            return false;
         };
-        
-        // Add a separator
-        result.push_str("\n--- Diff Preview (limited) ---\n");
-        
-        // Get a limited diff with timeout
-        match run_git_command_with_timeout(
-            &[
-                "show", 
-                "--color=never", 
-                "--patch", 
-                "--unified=3",  // Limited context
-                hash
-            ],
-            5  // 5 second timeout for diff
-        ) {
-            Ok(diff_text) => {
-                let lines: Vec<&str> = diff_text.lines().collect();
-                
-                // Take only first 100 lines to prevent UI freezing
-                let limited_lines: Vec<&str> = lines.iter().take(100).cloned().collect();
-                result.push_str(&limited_lines.join("\n"));
-                
-                if lines.len() > 100 {
-                    result.push_str(&format!("\n\n... (showing first 100 of {} lines total)\nUse 'git show {}' for full diff", lines.len(), hash));
-                }
-            }
-            Err(_) => {
-                result.push_str("Failed to load commit diff (timeout or error)");
-            }
+        self.preview = None;
+        if let Ok(lines) = result {
+            self.preview_content = lines;
         }
-        
-        result
+        true
     }
     
     /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page down
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.commits.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page up
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.commits.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected > 0 {
-                                self.list_state.select(Some(selected - 1));
-                                self.update_preview();
-                            }
-                        }
-                    }
-                    KeyCode::Down => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected + 1 < self.commits.len() {
-                                self.list_state.select(Some(selected + 1));
-                                self.update_preview();
-                            }
-                        } else if !self.commits.is_empty() {
-                            self.list_state.select(Some(0));
-                            self.update_preview();
-                        }
-                    }
-                    _ => {}
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if self.vim.navigate(&mut self.list_state, key.code, key.modifiers, self.commits.len(), self.settings.page_size) {
+            self.update_preview();
+            return Ok(());
+        }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('y') => {
+                if let Some(commit) = self.list_state.selected().and_then(|i| self.commits.get(i)) {
+                    let what = format!("commit {}", commit.short_hash);
+                    clipboard::copy_and_notify(&mut self.notifications, &what, &commit.hash);
                 }
             }
+            _ => {}
         }
         Ok(())
     }
     
     /// Render the git log browser
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(f.area());
+            .split(area);
         
         self.render_commit_list(f, chunks[0]);
         self.render_commit_diff(f, chunks[1]);
-        self.render_status_bar(f);
+        self.render_status_bar(f, area);
     }
     
     /// Render commit list
     fn render_commit_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.commits
+        let items: Vec<Line> = self.commits
             .iter()
             .map(|commit| {
                 let line = Line::from(vec![
                     Span::styled(
                         &commit.short_hash,
-                        Style::default().fg(colors::SECONDARY)
+                        Style::default().fg(colors::secondary())
                     ),
                     Span::raw(" "),
                     Span::styled(
                         &commit.message,
-                        Style::default().fg(colors::TEXT)
+                        Style::default().fg(colors::text())
                     ),
                     Span::raw(" "),
                     Span::styled(
                         format!("({}) {}", commit.date, commit.author),
-                        Style::default().fg(colors::PRIMARY)
+                        Style::default().fg(colors::primary())
                     ),
                 ]);
-                ListItem::new(line)
+                line
             })
             .collect();
         
-        let list = List::new(items)
+        let list = SelectableList::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
-                .title(format!("Git Log ({})", self.commits.len()))
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
+                .title(match &self.path {
+                    Some(path) => format!("Git Log: {} ({})", path.display(), self.commits.len()),
+                    None => format!("Git Log ({})", self.commits.len()),
+                })
+                .border_style(Style::default().fg(colors::primary())));
         
         f.render_stateful_widget(list, area, &mut self.list_state);
     }
@@ -290,209 +287,286 @@ impl GitLogBrowser {
             "Diff".to_string()
         };
         
-        let paragraph = Paragraph::new(self.preview_content.as_str())
+        let paragraph = Paragraph::new(self.preview_content.clone())
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::SECONDARY)))
-            .wrap(Wrap { trim: true });
+                .border_style(Style::default().fg(colors::secondary())))
+            .wrap(Wrap { trim: false });
         
         f.render_widget(paragraph, area);
     }
     
     /// Render status bar
-    fn render_status_bar(&self, f: &mut Frame) {
-        let area = Rect {
-            x: 0,
-            y: f.area().height - 1,
-            width: f.area().width,
-            height: 1,
-        };
-        
-        let help_text = "↑↓ Navigate • Esc Quit";
-        let status_text = format!("{} | {}", self.status_message, help_text);
-        
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
         
-        f.render_widget(paragraph, area);
+        let help_text = "j/k Navigate • y Copy hash • ? Help • Esc Quit";
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
     }
     
-    /// Run the git log browser
+    /// Run the git log browser, from where it was left in this directory
     pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
-        let result = self.run_app(&mut terminal);
-        tui_common::restore_terminal(&mut terminal)?;
-        result
-    }
-    
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            self.handle_input()?;
-            if self.should_quit {
-                break;
+        session::resume(self, "git log", tui_common::run_app)
+    }
+    
+    /// Print the commits instead of running the TUI
+    pub fn print(&mut self, format: Format) -> io::Result<()> {
+        if let Some(loading) = self.loading.take() {
+            let commits = loading.wait().map_err(|e| io::Error::new(e.kind(), "Not a git repository or git not found"))?;
+            self.commits = commits;
+        }
+        plain::print(format, self.commits.iter().map(|commit| vec![
+            ("hash", Value::from(commit.hash.as_str())),
+            ("author", Value::from(commit.author.as_str())),
+            ("date", Value::from(commit.date.as_str())),
+            ("subject", Value::from(commit.message.as_str())),
+        ]))
+    }
+}
+
+impl App for GitLogBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick => {
+                // Both may be done on the same tick
+                if self.poll_commits() | self.poll_preview() {
+                    return Ok(Action::Redraw);
+                }
             }
         }
-        Ok(())
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        LOG_KEYMAP
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+impl Resume for GitLogBrowser {
+    fn session(&self) -> Session {
+        let selected = self.list_state.selected().and_then(|index| self.commits.get(index));
+        Session {
+            selected: selected.map(|commit| commit.hash.clone()),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if self.loading.is_some() {
+            // The commits come in the background
+            self.resume_selected = session.selected;
+            return;
+        }
+        let position = session.selected.and_then(|hash| self.commits.iter().position(|commit| commit.hash == hash));
+        if position.is_some() {
+            self.list_state.select(position);
+            self.update_preview();
+        }
+    }
+}
+
+/// A commit from its `%H|%h|%s|%an|%ar` line of `git log`
+fn parse_commit(line: &str) -> Option<GitCommit> {
+    let parts: Vec<&str> = line.split('|').collect();
+    if parts.len() < 5 {
+        return None;
+    }
+    Some(GitCommit {
+        hash: parts[0].to_string(),
+        short_hash: parts[1].to_string(),
+        message: parts[2].to_string(),
+        author: parts[3].to_string(),
+        date: parts[4].to_string(),
+    })
+}
+
+/// Load commit diff with optimization for large commits
+fn load_commit_diff(task: &Task, hash: &str, path: Option<&Path>) -> Vec<Line<'static>> {
+    // First, get just the commit info and stats (fast), in git's own
+    // colors like `git show` in a terminal
+    let header = match run_git(task, &["show", "--color=always", "--stat", "--no-patch", hash]) {
+        Ok(output) => output,
+        Err(_) => format!("Commit: {}\n", hash),
+    };
+    let mut result = ansi::to_lines(&header);
+    
+    // Add a separator
+    result.push(Line::default());
+    result.push(Line::styled("--- Diff Preview (limited) ---", Style::default().fg(colors::muted())));
+    
+    // Get a limited diff, drawn like `tt git diff`
+    let mut args = vec![
+        "show", 
+        "--format=", 
+        "--no-color", 
+        "--no-ext-diff", 
+        "--patch", 
+        "--unified=3",  // Limited context
+        hash
+    ];
+    let path = path.map(|path| path.to_string_lossy());
+    if let Some(path) = &path {
+        args.extend(["--", path]);
+    }
+    match run_git(task, &args) {
+        Ok(diff_text) => {
+            let lines: Vec<&str> = diff_text.lines().collect();
+            
+            // Take only first 100 lines to prevent UI freezing
+            let limited_lines: Vec<&str> = lines.iter().take(100).cloned().collect();
+            result.extend(diff::unified_lines(&diff::parse_unified(&limited_lines.join("\n"))));
+            
+            if lines.len() > 100 {
+                result.push(Line::default());
+                result.push(Line::raw(format!("... (showing first 100 of {} lines total)", lines.len())));
+                result.push(Line::raw(format!("Use 'git show {}' for full diff", hash)));
+            }
+        }
+        Err(_) => {
+            result.push(Line::raw("Failed to load commit diff"));
+        }
     }
+    
+    result
 }
 
 /// Git branch switcher
 pub struct GitBranchSwitcher {
     branches: Vec<GitBranch>,
     list_state: ListState,
+    vim: VimNav,
     should_quit: bool,
-    status_message: String,
+    notifications: Notifications,
+    /// `git branch` running in the background
+    loading: Option<Job<Vec<GitBranch>>>,
+    /// `git checkout` running in the background, with the branch
+    checkout: Option<(String, Job<()>)>,
+    settings: Settings,
 }
 
 impl GitBranchSwitcher {
     /// Create a new git branch switcher
-    pub fn new() -> io::Result<Self> {
+    pub fn new(settings: Settings) -> io::Result<Self> {
         let mut switcher = GitBranchSwitcher {
             branches: Vec::new(),
             list_state: ListState::default(),
+            vim: VimNav::default(),
             should_quit: false,
-            status_message: "Loading git branches...".to_string(),
+            notifications: Notifications::with_status("Loading git branches..."),
+            loading: None,
+            checkout: None,
+            settings,
         };
         
-        switcher.load_branches()?;
+        switcher.loading = Some(switcher.notifications.jobs().spawn("git branch", |task| {
+            Ok(parse_branches(&run_git(task, &["branch", "-a"])?))
+        }));
         
         Ok(switcher)
     }
     
-    /// Load git branches
-    fn load_branches(&mut self) -> io::Result<()> {
-        let output = Command::new("git")
-            .args(&["branch", "-a"])
-            .stdout(Stdio::piped())
-            .output()?;
-        
-        if !output.status.success() {
-            self.status_message = "Error: Not a git repository or git not found".to_string();
-            return Ok(());
-        }
-        
-        let branches_output = String::from_utf8_lossy(&output.stdout);
-        
-        for line in branches_output.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.contains("HEAD ->") {
-                continue;
-            }
-            
-            let is_current = line.starts_with('*');
-            let is_remote = line.contains("remotes/");
-            
-            let name = line
-                .trim_start_matches('*')
-                .trim()
-                .trim_start_matches("remotes/origin/")
-                .to_string();
-            
-            // Skip if we already have this branch (local version takes precedence)
-            if !self.branches.iter().any(|b| b.name == name) {
-                self.branches.push(GitBranch {
-                    name,
-                    is_current,
-                    is_remote,
-                });
+    /// Show the branches once they are loaded, and quit once the checkout
+    /// is done; returns whether either was
+    fn poll_jobs(&mut self) -> bool {
+        if let Some(result) = self.loading.as_mut().and_then(Job::poll) {
+            self.loading = None;
+            match result {
+                Ok(branches) => {
+                    self.branches = branches;
+                    if !self.branches.is_empty() {
+                        self.list_state.select(Some(0));
+                    }
+                    self.notifications.set_status(format!("Loaded {} branches", self.branches.len()));
+                }
+                Err(e) if jobs::is_cancelled(&e) => self.notifications.set_status("Loading cancelled"),
+                Err(_) => {
+                    self.notifications.set_status("");
+                    self.notifications.error("Not a git repository or git not found");
+                }
             }
+            return true;
         }
         
-        if !self.branches.is_empty() {
-            self.list_state.select(Some(0));
+        let Some(result) = self.checkout.as_mut().and_then(|(_, job)| job.poll()) else {
+            return false;
+        };
+        let Some((name, _)) = self.checkout.take() else {
+            return false;
+        };
+        match result {
+            Ok(()) => {
+                self.notifications.success(format!("Switched to branch '{}'", name));
+                self.should_quit = true;
+            }
+            Err(e) if jobs::is_cancelled(&e) => self.notifications.info("Switch cancelled"),
+            Err(e) => self.notifications.error(format!("Failed to switch: {}", e)),
         }
-        
-        self.status_message = format!("Loaded {} branches", self.branches.len());
-        Ok(())
+        true
     }
     
     /// Switch to selected branch
-    fn switch_branch(&mut self) -> io::Result<()> {
+    fn switch_branch(&mut self) {
+        if self.checkout.is_some() {
+            return;
+        }
         if let Some(selected) = self.list_state.selected() {
             if let Some(branch) = self.branches.get(selected) {
                 if branch.is_current {
-                    self.status_message = "Already on this branch".to_string();
-                    return Ok(());
+                    self.notifications.info("Already on this branch");
+                    return;
                 }
                 
-                let output = Command::new("git")
-                    .args(&["checkout", &branch.name])
-                    .output()?;
-                
-                if output.status.success() {
-                    self.status_message = format!("Switched to branch '{}'", branch.name);
-                    self.should_quit = true;
-                } else {
-                    let error = String::from_utf8_lossy(&output.stderr);
-                    self.status_message = format!("Failed to switch: {}", error.trim());
-                }
+                let name = branch.name.clone();
+                let job = self.notifications.jobs().spawn(format!("git checkout {}", name), {
+                    let name = name.clone();
+                    move |task| {
+                        let output = task.output(Command::new("git").args(["checkout", &name]))?;
+                        if output.status.success() {
+                            Ok(())
+                        } else {
+                            Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+                        }
+                    }
+                });
+                self.checkout = Some((name, job));
             }
         }
-        Ok(())
     }
     
     /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page down
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.branches.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                        }
-                    }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page up
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.branches.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                        }
-                    }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected > 0 {
-                                self.list_state.select(Some(selected - 1));
-                            }
-                        }
-                    }
-                    KeyCode::Down => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected + 1 < self.branches.len() {
-                                self.list_state.select(Some(selected + 1));
-                            }
-                        } else if !self.branches.is_empty() {
-                            self.list_state.select(Some(0));
-                        }
-                    }
-                    KeyCode::Enter => {
-                        self.switch_branch()?;
-                    }
-                    _ => {}
-                }
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if self.vim.navigate(&mut self.list_state, key.code, key.modifiers, self.branches.len(), self.settings.page_size) {
+            return Ok(());
+        }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_quit = true;
+            }
+            KeyCode::Enter => {
+                self.switch_branch();
             }
+            _ => {}
         }
         Ok(())
     }
     
     /// Render the branch switcher
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Min(3), Constraint::Length(1)])
-            .split(f.area());
+            .split(area);
         
         self.render_branch_list(f, chunks[0]);
         self.render_status_bar(f, chunks[1]);
@@ -500,16 +574,16 @@ impl GitBranchSwitcher {
     
     /// Render branch list
     fn render_branch_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.branches
+        let items: Vec<Line> = self.branches
             .iter()
             .map(|branch| {
                 let prefix = if branch.is_current { "* " } else { "  " };
                 let style = if branch.is_current {
-                    Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+                    Style::default().fg(colors::success()).add_modifier(Modifier::BOLD)
                 } else if branch.is_remote {
-                    Style::default().fg(colors::SECONDARY)
+                    Style::default().fg(colors::secondary())
                 } else {
-                    Style::default().fg(colors::TEXT)
+                    Style::default().fg(colors::text())
                 };
                 
                 let line = Line::from(vec![
@@ -517,268 +591,312 @@ impl GitBranchSwitcher {
                     Span::styled(&branch.name, style),
                 ]);
                 
-                ListItem::new(line)
+                line
             })
             .collect();
         
-        let list = List::new(items)
+        let list = SelectableList::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(format!("Git Branches ({})", self.branches.len()))
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
+                .border_style(Style::default().fg(colors::primary())));
         
         f.render_stateful_widget(list, area, &mut self.list_state);
     }
     
     /// Render status bar
     fn render_status_bar(&self, f: &mut Frame, area: Rect) {
-        let help_text = "↑↓ Navigate • Enter Switch • Esc Quit";
-        let status_text = format!("{} | {}", self.status_message, help_text);
-        
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
-        
-        f.render_widget(paragraph, area);
+        let help_text = "Enter Switch • ? Help • Esc Quit";
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
     }
     
     /// Run the branch switcher
     pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
-        let result = self.run_app(&mut terminal);
-        tui_common::restore_terminal(&mut terminal)?;
-        result
-    }
-    
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            self.handle_input()?;
-            if self.should_quit {
-                break;
-            }
+        tui_common::run_app(self)
+    }
+    
+    /// Print the branches instead of running the TUI
+    pub fn print(&mut self, format: Format) -> io::Result<()> {
+        if let Some(loading) = self.loading.take() {
+            let branches = loading.wait().map_err(|e| io::Error::new(e.kind(), "Not a git repository or git not found"))?;
+            self.branches = branches;
+        }
+        plain::print(format, self.branches.iter().map(|branch| vec![
+            ("name", Value::from(branch.name.as_str())),
+            ("current", Value::from(branch.is_current)),
+            ("remote", Value::from(branch.is_remote)),
+        ]))
+    }
+}
+
+impl App for GitBranchSwitcher {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick if self.poll_jobs() => return Ok(Action::Redraw),
+            AppEvent::Tick => {}
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        BRANCH_KEYMAP
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+/// Branches from the lines of `git branch -a`, a local branch standing for
+/// the remote one of the same name
+fn parse_branches(output: &str) -> Vec<GitBranch> {
+    let mut branches: Vec<GitBranch> = Vec::new();
+    for line in output.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.contains("HEAD ->") {
+            continue;
+        }
+        
+        let is_current = line.starts_with('*');
+        let is_remote = line.contains("remotes/");
+        
+        let name = line
+            .trim_start_matches('*')
+            .trim()
+            .trim_start_matches("remotes/origin/")
+            .to_string();
+        
+        // Skip if we already have this branch (local version takes precedence)
+        if !branches.iter().any(|b| b.name == name) {
+            branches.push(GitBranch {
+                name,
+                is_current,
+                is_remote,
+            });
         }
-        Ok(())
     }
+    branches
 }
 
 /// Git diff browser
 pub struct GitDiffBrowser {
-    diff_content: String,
-    scroll_offset: usize,
+    view: DiffView,
     should_quit: bool,
-    status_message: String,
+    notifications: Notifications,
+    /// `git diff` running in the background
+    loading: Option<Job<Vec<FileDiff>>>,
+    /// Scroll to go back to once the diff restored from the session is loaded
+    resume_scroll: Option<usize>,
 }
 
 impl GitDiffBrowser {
     /// Create a new git diff browser
     pub fn new() -> io::Result<Self> {
         let mut browser = GitDiffBrowser {
-            diff_content: String::new(),
-            scroll_offset: 0,
+            view: DiffView::new(false),
             should_quit: false,
-            status_message: "Loading git diff...".to_string(),
+            notifications: Notifications::with_status("Loading git diff..."),
+            loading: None,
+            resume_scroll: None,
         };
         
-        browser.load_diff()?;
+        // Drawn by tt rather than in git's colors, so it can be shown side by side
+        browser.loading = Some(browser.notifications.jobs().spawn("git diff", |task| {
+            Ok(diff::parse_unified(&run_git(task, &["diff", "--no-color", "--no-ext-diff"])?))
+        }));
         
         Ok(browser)
     }
     
-    /// Load git diff content
-    fn load_diff(&mut self) -> io::Result<()> {
-        let output = Command::new("git")
-            .args(&["diff", "--color=never"])
-            .stdout(Stdio::piped())
-            .output()?;
+    /// Show the diff once it is loaded; returns whether it was
+    fn poll_diff(&mut self) -> bool {
+        let Some(result) = self.loading.as_mut().and_then(Job::poll) else {
+            return false;
+        };
+        self.loading = None;
+        let files = match result {
+            Ok(files) => files,
+            Err(e) if jobs::is_cancelled(&e) => {
+                self.notifications.set_status("Loading cancelled");
+                return true;
+            }
+            Err(_) => {
+                self.notifications.set_status("");
+                self.notifications.error("Not a git repository or git not found");
+                return true;
+            }
+        };
         
-        if !output.status.success() {
-            self.status_message = "Error: Not a git repository or git not found".to_string();
-            return Ok(());
+        self.view.set_files(files);
+        if let Some(scroll) = self.resume_scroll.take() {
+            self.view.set_scroll(scroll);
         }
         
-        self.diff_content = String::from_utf8_lossy(&output.stdout).to_string();
-        
-        if self.diff_content.trim().is_empty() {
-            self.diff_content = "No changes to show".to_string();
-            self.status_message = "Working tree clean".to_string();
+        if self.view.files().is_empty() {
+            self.notifications.set_status("Working tree clean");
         } else {
-            let line_count = self.diff_content.lines().count();
-            self.status_message = format!("Git diff ({} lines)", line_count);
+            let (added, removed) = self.view.stats();
+            self.notifications.set_status(format!("Git diff ({} files, +{} -{})", self.view.files().len(), added, removed));
         }
-        
-        Ok(())
+        true
     }
     
     /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page down
-                        self.page_down();
-                    }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page up
-                        self.page_up();
-                    }
-                    KeyCode::Up | KeyCode::Char('k') => {
-                        if self.scroll_offset > 0 {
-                            self.scroll_offset -= 1;
-                        }
-                    }
-                    KeyCode::Down | KeyCode::Char('j') => {
-                        let max_scroll = self.diff_content.lines().count().saturating_sub(1);
-                        if self.scroll_offset < max_scroll {
-                            self.scroll_offset += 1;
-                        }
-                    }
-                    KeyCode::Home | KeyCode::Char('g') => {
-                        self.scroll_offset = 0;
-                    }
-                    KeyCode::End | KeyCode::Char('G') => {
-                        self.scroll_offset = self.diff_content.lines().count().saturating_sub(20);
-                    }
-                    _ => {}
-                }
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_quit = true;
+            }
+            code => {
+                self.view.handle_key(code, key.modifiers);
             }
         }
         Ok(())
     }
     
-    /// Page down
-    fn page_down(&mut self) {
-        let max_scroll = self.diff_content.lines().count().saturating_sub(1);
-        self.scroll_offset = std::cmp::min(self.scroll_offset + 20, max_scroll);
-    }
-    
-    /// Page up
-    fn page_up(&mut self) {
-        self.scroll_offset = self.scroll_offset.saturating_sub(20);
-    }
-    
     /// Render the diff browser
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Min(3), Constraint::Length(1)])
-            .split(f.area());
+            .split(area);
         
         self.render_diff_content(f, chunks[0]);
         self.render_status_bar(f, chunks[1]);
     }
     
     /// Render diff content
-    fn render_diff_content(&self, f: &mut Frame, area: Rect) {
-        let lines: Vec<&str> = self.diff_content.lines().collect();
-        let visible_lines: Vec<Line> = lines
-            .iter()
-            .skip(self.scroll_offset)
-            .take(area.height as usize - 2)
-            .map(|line| {
-                // Color diff lines
-                if line.starts_with('+') && !line.starts_with("+++") {
-                    Line::from(Span::styled(*line, Style::default().fg(Color::Green)))
-                } else if line.starts_with('-') && !line.starts_with("---") {
-                    Line::from(Span::styled(*line, Style::default().fg(Color::Red)))
-                } else if line.starts_with("@@") {
-                    Line::from(Span::styled(*line, Style::default().fg(colors::PRIMARY).add_modifier(Modifier::BOLD)))
-                } else if line.starts_with("diff --git") {
-                    Line::from(Span::styled(*line, Style::default().fg(colors::SECONDARY).add_modifier(Modifier::BOLD)))
-                } else {
-                    Line::from(*line)
-                }
-            })
-            .collect();
+    fn render_diff_content(&mut self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Git Diff")
+            .border_style(Style::default().fg(colors::primary()));
         
-        let paragraph = Paragraph::new(visible_lines)
-            .block(Block::default()
-                .borders(Borders::ALL)
-                .title("Git Diff")
-                .border_style(Style::default().fg(colors::PRIMARY)));
-        
-        f.render_widget(paragraph, area);
+        self.view.render(f, area, block);
     }
     
     /// Render status bar
     fn render_status_bar(&self, f: &mut Frame, area: Rect) {
-        let help_text = "↑↓/jk Scroll • Ctrl-F/B Page • g/G Top/Bottom • Esc Quit";
-        let status_text = format!("{} | {}", self.status_message, help_text);
-        
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
-        
-        f.render_widget(paragraph, area);
+        let help_text = "↑↓/jk Scroll • ]/[ Hunk • s Side by side • ? Help • Esc Quit";
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
     }
     
-    /// Run the diff browser
+    /// Run the diff browser, from where it was left in this directory
     pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
-        let result = self.run_app(&mut terminal);
-        tui_common::restore_terminal(&mut terminal)?;
-        result
-    }
-    
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            self.handle_input()?;
-            if self.should_quit {
-                break;
-            }
+        session::resume(self, "git diff", tui_common::run_app)
+    }
+}
+
+impl App for GitDiffBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick if self.poll_diff() => return Ok(Action::Redraw),
+            AppEvent::Tick => {}
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        DIFF_KEYMAP
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+impl Resume for GitDiffBrowser {
+    fn session(&self) -> Session {
+        Session { scroll: Some(self.view.scroll()), ..Session::default() }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if self.loading.is_some() {
+            // The diff comes in the background
+            self.resume_scroll = session.scroll;
+        } else {
+            self.view.set_scroll(session.scroll.unwrap_or(0));
         }
-        Ok(())
     }
 }
 
-/// Run git tools
-pub fn run(subcommand: GitCommands) -> io::Result<()> {
+/// Run git tools, or print their data in `output`
+pub fn run(subcommand: GitCommands, output: Option<Format>, settings: Settings) -> io::Result<()> {
     match subcommand {
-        GitCommands::Log => {
-            let mut browser = GitLogBrowser::new()?;
-            browser.run()
+        GitCommands::Log { path } => {
+            let mut browser = GitLogBrowser::new(path, settings)?;
+            match output {
+                Some(format) => browser.print(format),
+                None => browser.run(),
+            }
         }
         GitCommands::Branch => {
-            let mut switcher = GitBranchSwitcher::new()?;
-            switcher.run()
+            let mut switcher = GitBranchSwitcher::new(settings)?;
+            match output {
+                Some(format) => switcher.print(format),
+                None => switcher.run(),
+            }
         }
-        GitCommands::Status => {
-            // For now, just run git status
-            let output = Command::new("git")
-                .args(&["status", "--porcelain"])
-                .output()?;
-            
-            if output.status.success() {
-                let status_output = String::from_utf8_lossy(&output.stdout);
-                if status_output.trim().is_empty() {
-                    println!("Working tree clean");
-                } else {
-                    println!("Git Status:");
-                    for line in status_output.lines() {
-                        println!("{}", line);
+        GitCommands::Status => match output {
+            Some(format) => print_status(format),
+            None => {
+                // For now, just run git status
+                if let Ok(status_output) = run_git_now(&["status", "--porcelain"]) {
+                    if status_output.trim().is_empty() {
+                        println!("Working tree clean");
+                    } else {
+                        println!("Git Status:");
+                        for line in status_output.lines() {
+                            println!("{}", line);
+                        }
                     }
+                } else {
+                    println!("Error: Not a git repository or git not found");
                 }
-            } else {
-                println!("Error: Not a git repository or git not found");
+                Ok(())
             }
-            Ok(())
-        }
-        GitCommands::Diff => {
-            let mut diff_browser = GitDiffBrowser::new()?;
-            diff_browser.run()
-        }
+        },
+        GitCommands::Diff => match output {
+            Some(format) => print_diff(format),
+            None => {
+                let mut diff_browser = GitDiffBrowser::new()?;
+                diff_browser.run()
+            }
+        },
     }
+}
+
+/// Print the changed files with their two-letter status code
+fn print_status(format: Format) -> io::Result<()> {
+    let status = run_git_now(&["status", "--porcelain"])?;
+    // `XY path`
+    plain::print(format, status.lines().filter(|line| line.len() > 3).map(|line| vec![
+        ("status", Value::from(line[..2].trim())),
+        ("path", Value::from(&line[3..])),
+    ]))
+}
+
+/// Print the lines added and removed per changed file
+fn print_diff(format: Format) -> io::Result<()> {
+    let numstat = run_git_now(&["diff", "--numstat"])?;
+    plain::print(format, numstat.lines().filter_map(|line| {
+        // `added<TAB>removed<TAB>path`, with `-` counts for binary files
+        let mut fields = line.splitn(3, '\t');
+        let (added, removed, path) = (fields.next()?, fields.next()?, fields.next()?);
+        Some(vec![
+            ("added", Value::from(added.parse::<u64>().ok())),
+            ("removed", Value::from(removed.parse::<u64>().ok())),
+            ("path", Value::from(path)),
+        ])
+    }))
 }
\ No newline at end of file
@@ -0,0 +1,432 @@
+//! Hex viewer for binary files with goto-offset, byte and text search.
+
+use crate::clipboard;
+use crate::config::Settings;
+use crate::hex_dump::{self, ByteClass};
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Previous / next row"),
+    ("← / →, h / l", "Previous / next byte"),
+    ("0 / $", "Start / end of the row"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("Home / End, gg / G", "Start / end of the file"),
+    (":", "Go to an offset (1024, 0x400, +16, -16)"),
+    ("/", "Search text, or hex bytes after Tab"),
+    ("n / N", "Next / previous match"),
+    ("w", "Bytes per row: 8, 16, 24 or 32, as wide as fits"),
+    ("y", "Copy the offset"),
+    ("Y", "Copy the bytes of the row in hex"),
+    ("q / Esc", "Quit"),
+];
+
+enum Mode {
+    Normal,
+    /// Typing an offset to go to
+    Goto(TextInput),
+    /// Typing a search; `hex` reads it as hex bytes rather than text
+    Search { input: TextInput, hex: bool },
+}
+
+/// The last search, for `n` and `N`
+struct Search {
+    pattern: Vec<u8>,
+    /// As typed, for messages
+    label: String,
+    /// Offset of the match shown, highlighted while the cursor is on it
+    found: Option<u64>,
+}
+
+pub struct HexViewer {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    /// Bytes per row
+    width: usize,
+    /// Widest row that fit the pane at the last draw, for `w`
+    max_width: usize,
+    /// Offset of the byte under the cursor
+    cursor: u64,
+    /// First row shown
+    top: u64,
+    /// The selected row, for vim-style movement
+    list_state: ListState,
+    search: Option<Search>,
+    mode: Mode,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    settings: Settings,
+}
+
+/// Color of a byte in both columns
+fn byte_color(byte: u8) -> Color {
+    match ByteClass::of(byte) {
+        ByteClass::Null => colors::muted(),
+        ByteClass::Printable => colors::text(),
+        ByteClass::Whitespace => colors::success(),
+        ByteClass::Control => colors::warning(),
+        ByteClass::High => colors::secondary(),
+    }
+}
+
+impl HexViewer {
+    /// Open `path`, with `width` bytes per row and the cursor at `offset`
+    pub fn new(path: PathBuf, width: usize, offset: Option<&str>, settings: Settings) -> io::Result<Self> {
+        let file = File::open(&path)?;
+        let metadata = file.metadata()?;
+        if metadata.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is a directory", path.display())));
+        }
+        let len = metadata.len();
+        let cursor = match offset {
+            Some(offset) => hex_dump::parse_offset(offset, 0, len).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+            None => 0,
+        };
+        
+        let mut viewer = HexViewer {
+            path,
+            file,
+            len,
+            width,
+            max_width: width,
+            cursor,
+            top: 0,
+            list_state: ListState::default(),
+            search: None,
+            mode: Mode::Normal,
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::new(),
+            settings,
+        };
+        viewer.set_cursor(cursor);
+        viewer.notifications.set_status(format!("{} bytes", len));
+        Ok(viewer)
+    }
+    
+    fn row_count(&self) -> u64 {
+        self.len.div_ceil(self.width as u64)
+    }
+    
+    /// Move the cursor to `offset`, within the file
+    fn set_cursor(&mut self, offset: u64) {
+        self.cursor = offset.min(self.len.saturating_sub(1));
+        self.list_state.select(Some((self.cursor / self.width as u64) as usize));
+    }
+    
+    /// Up to `len` bytes from `offset`; an unreadable file shows as empty
+    fn read(&self, offset: u64, len: usize) -> Vec<u8> {
+        hex_dump::read_at(&mut &self.file, offset, len).unwrap_or_default()
+    }
+    
+    fn handle_key(&mut self, key: KeyEvent) {
+        match &mut self.mode {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Goto(input) => match key.code {
+                KeyCode::Esc => self.mode = Mode::Normal,
+                KeyCode::Enter => {
+                    match hex_dump::parse_offset(input.value(), self.cursor, self.len) {
+                        Ok(offset) => self.set_cursor(offset),
+                        Err(e) => self.notifications.error(e),
+                    }
+                    self.mode = Mode::Normal;
+                }
+                code => {
+                    input.handle_key(code);
+                }
+            },
+            Mode::Search { input, hex } => match key.code {
+                KeyCode::Esc => self.mode = Mode::Normal,
+                KeyCode::Tab => *hex = !*hex,
+                KeyCode::Enter => {
+                    let label = input.value().to_string();
+                    let pattern = if *hex { hex_dump::parse_hex(&label) } else { Ok(label.clone().into_bytes()) };
+                    self.mode = Mode::Normal;
+                    match pattern {
+                        Ok(pattern) if !pattern.is_empty() => {
+                            self.search = Some(Search { pattern, label, found: None });
+                            // The byte under the cursor may start a match
+                            self.find_next(self.cursor.checked_sub(1));
+                        }
+                        Ok(_) => {}
+                        Err(e) => self.notifications.error(e),
+                    }
+                }
+                code => {
+                    input.handle_key(code);
+                }
+            },
+        }
+    }
+    
+    fn handle_normal_key(&mut self, key: KeyEvent) {
+        let row_start = self.cursor - self.cursor % self.width as u64;
+        let rows = self.row_count() as usize;
+        if self.vim.navigate(&mut self.list_state, key.code, key.modifiers, rows, self.settings.page_size) {
+            let row = self.list_state.selected().unwrap_or(0) as u64;
+            self.set_cursor(row * self.width as u64 + self.cursor % self.width as u64);
+            return;
+        }
+        
+        let plain = !key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Left | KeyCode::Char('h') if plain => self.set_cursor(self.cursor.saturating_sub(1)),
+            KeyCode::Right | KeyCode::Char('l') if plain => self.set_cursor(self.cursor + 1),
+            KeyCode::Char('0') => self.set_cursor(row_start),
+            KeyCode::Char('$') => self.set_cursor(row_start + self.width as u64 - 1),
+            KeyCode::Char(':') => self.mode = Mode::Goto(TextInput::new("")),
+            KeyCode::Char('/') => self.mode = Mode::Search { input: TextInput::new(""), hex: false },
+            KeyCode::Char('n') => self.find_next(Some(self.cursor)),
+            KeyCode::Char('N') => self.find_previous(),
+            KeyCode::Char('w') => {
+                let width = hex_dump::WIDTHS
+                    .iter()
+                    .copied()
+                    .find(|&width| width > self.width && width <= self.max_width)
+                    .unwrap_or(hex_dump::WIDTHS[0]);
+                self.width = width;
+                self.set_cursor(self.cursor);
+                self.notifications.info(format!("{} bytes per row", width));
+            }
+            KeyCode::Char('y') => {
+                clipboard::copy_and_notify(&mut self.notifications, "the offset", &format!("0x{:x}", self.cursor));
+            }
+            KeyCode::Char('Y') => {
+                let bytes = self.read(row_start, self.width);
+                clipboard::copy_and_notify(&mut self.notifications, "the row", &hex_dump::hex_text(&bytes, bytes.len()));
+            }
+            _ => {}
+        }
+    }
+    
+    /// Go to the next match after `after`, or from the start of the file
+    fn find_next(&mut self, after: Option<u64>) {
+        let Some(search) = &self.search else {
+            self.notifications.info("Nothing to find: search with /");
+            return;
+        };
+        // Searching after the end wraps around to the start
+        let result = hex_dump::find(&mut &self.file, self.len, &search.pattern, after.unwrap_or(self.len), true);
+        self.show_match(result);
+    }
+    
+    fn find_previous(&mut self) {
+        let Some(search) = &self.search else {
+            self.notifications.info("Nothing to find: search with /");
+            return;
+        };
+        let result = hex_dump::find(&mut &self.file, self.len, &search.pattern, self.cursor, false);
+        self.show_match(result);
+    }
+    
+    fn show_match(&mut self, result: io::Result<Option<u64>>) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        match result {
+            Ok(Some(offset)) => {
+                search.found = Some(offset);
+                self.set_cursor(offset);
+            }
+            Ok(None) => {
+                search.found = None;
+                let message = format!("Not found: {}", search.label);
+                self.notifications.info(message);
+            }
+            Err(e) => self.notifications.error(format!("Cannot read {}: {}", self.path.display(), e)),
+        }
+    }
+    
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1), Constraint::Length(1)])
+            .split(area);
+        
+        self.render_dump(f, chunks[0]);
+        self.render_info(f, chunks[1]);
+        self.render_status_bar(f, chunks[2]);
+        
+        match &self.mode {
+            Mode::Normal => {}
+            Mode::Goto(input) => tui_common::render_input_popup(f, "Go to", "Offset: 1024, 0x400, +16 or -16", input),
+            Mode::Search { input, hex: false } => tui_common::render_input_popup(f, "Search", "Text (Tab for hex bytes)", input),
+            Mode::Search { input, hex: true } => tui_common::render_input_popup(f, "Search", "Hex bytes like de ad be ef (Tab for text)", input),
+        }
+    }
+    
+    /// The offset, hex and ASCII columns of the rows in view
+    fn render_dump(&mut self, f: &mut Frame, area: Rect) {
+        // Borders and the column header take three rows
+        let rows_shown = area.height.saturating_sub(3) as usize;
+        self.max_width = hex_dump::fit_width(area.width.saturating_sub(2) as usize);
+        let rows = rows_shown.max(1) as u64;
+        let cursor_row = self.cursor / self.width as u64;
+        if cursor_row < self.top {
+            self.top = cursor_row;
+        } else if cursor_row >= self.top + rows {
+            self.top = cursor_row + 1 - rows;
+        }
+        
+        let muted = Style::default().fg(colors::muted());
+        let columns: Vec<u8> = (0..self.width).map(|index| index as u8).collect();
+        let header = hex_dump::hex_text(&columns, self.width);
+        let mut lines = vec![Line::from(Span::styled(format!("{:<8}  {}", "Offset", header), muted))];
+        
+        let start = self.top * self.width as u64;
+        let bytes = self.read(start, rows_shown * self.width);
+        let found = self.search.as_ref().and_then(|search| Some((search.found?, search.pattern.len() as u64)));
+        let style_at = |offset: u64, byte: u8| {
+            let mut style = Style::default().fg(byte_color(byte));
+            if found.is_some_and(|(found, len)| (found..found + len).contains(&offset)) {
+                style = style.fg(colors::background()).bg(colors::warning());
+            }
+            if offset == self.cursor {
+                style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            style
+        };
+        for (row, chunk) in bytes.chunks(self.width).enumerate() {
+            let offset = start + (row * self.width) as u64;
+            let mut spans = vec![Span::styled(hex_dump::offset_text(offset), muted), Span::raw("  ")];
+            for (index, &byte) in chunk.iter().enumerate() {
+                if index > 0 {
+                    spans.push(Span::raw(if index % 8 == 0 { "  " } else { " " }));
+                }
+                spans.push(Span::styled(format!("{:02x}", byte), style_at(offset + index as u64, byte)));
+            }
+            // Pad a short last row so its ASCII column lines up
+            let padding = hex_dump::hex_text(chunk, self.width).len() - hex_dump::hex_text(chunk, chunk.len()).len();
+            spans.push(Span::raw(" ".repeat(padding + 2)));
+            for (index, &byte) in chunk.iter().enumerate() {
+                spans.push(Span::styled(hex_dump::ascii_char(byte).to_string(), style_at(offset + index as u64, byte)));
+            }
+            lines.push(Line::from(spans));
+        }
+        if self.len == 0 {
+            lines.push(Line::from(Span::styled("(empty file)", muted)));
+        }
+        
+        let title = format!("{} ({})", self.path.display(), tui_common::format_size(self.len));
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    /// The cursor's offset and byte
+    fn render_info(&self, f: &mut Frame, area: Rect) {
+        let mut spans = vec![Span::styled(
+            format!(" Offset 0x{:x} ({})", self.cursor, self.cursor),
+            Style::default().fg(colors::secondary()),
+        )];
+        if let Some(&byte) = self.read(self.cursor, 1).first() {
+            spans.push(Span::styled(
+                format!(" • 0x{:02x} {} 0b{:08b} '{}'", byte, byte, byte, hex_dump::ascii_char(byte)),
+                Style::default().fg(byte_color(byte)),
+            ));
+        }
+        if let Some(percent) = ((self.cursor + 1) * 100).checked_div(self.len) {
+            spans.push(Span::styled(format!(" • {}%", percent), Style::default().fg(colors::muted())));
+        }
+        if let Some(search) = &self.search {
+            spans.push(Span::styled(format!(" • /{}", search.label), Style::default().fg(colors::muted())));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+    
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let help_text = match self.mode {
+            Mode::Normal => "hjkl Move • : Go to • / Search • n/N Next/Prev • w Width • y Copy offset • ? Help • q Quit",
+            Mode::Goto(_) => "Enter Go • Esc Cancel",
+            Mode::Search { .. } => "Enter Search • Tab Text/Hex • Esc Cancel",
+        };
+        
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the viewer
+    pub fn run(&mut self) -> io::Result<()> {
+        tui_common::run_app(self)
+    }
+    
+    /// Print the dump from the cursor to the end instead of running the
+    /// TUI, one row per record
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        let mut reader = BufReader::new(&self.file);
+        reader.seek(SeekFrom::Start(self.cursor))?;
+        let mut offset = self.cursor;
+        let rows = std::iter::from_fn(|| {
+            let mut row = Vec::with_capacity(self.width);
+            reader.by_ref().take(self.width as u64).read_to_end(&mut row).ok()?;
+            if row.is_empty() {
+                return None;
+            }
+            let record = vec![
+                ("offset", Value::from(hex_dump::offset_text(offset))),
+                ("hex", Value::from(hex_dump::hex_text(&row, row.len()))),
+                ("ascii", Value::from(hex_dump::ascii_text(&row))),
+            ];
+            offset += row.len() as u64;
+            Some(record)
+        });
+        plain::print(format, rows)
+    }
+}
+
+impl App for HexViewer {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        if let AppEvent::Key(key) = event {
+            self.handle_key(key);
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        !matches!(self.mode, Mode::Normal)
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+/// Run the hex viewer on `file`, or print its dump in `output`
+pub fn run(file: PathBuf, width: u8, offset: Option<String>, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut viewer = HexViewer::new(file, width as usize, offset.as_deref(), settings)?;
+    match output {
+        Some(format) => viewer.print(format),
+        None => viewer.run(),
+    }
+}
@@ -1,344 +1,1026 @@
 //! Command history browser and executor.
 
-use crate::tui_common::{self, colors};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crate::command_log::{self, CommandRecord, CommandStats};
+use crate::config::Settings;
+use crate::fuzzy;
+use crate::help_text;
+use crate::jobs::{self, Job, Task};
+use crate::mru;
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::platform;
+use crate::session::{self, Resume, Session};
+use crate::shell_history::{self, Shell};
+use crate::snippets::{self, Snippet};
+use crate::state;
+use crate::time_util;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Debounce, Keymap, NavKey, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use regex::Regex;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
-    text::Line,
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
-    Frame, Terminal,
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph, Wrap},
+    Frame,
 };
+use serde_json::Value;
 use std::{
+    collections::HashMap,
     env,
-    fs,
-    io,
+    io::{self, IsTerminal},
     path::PathBuf,
     process::Command,
-    time::Duration,
 };
 
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Filter commands"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Tab / Shift-Tab", "Next / previous day"),
+    ("Enter", "Run the command (insert it with --print)"),
+    ("Ctrl-O", "Print the command"),
+    ("e", "Edit, then run or print (normal mode)"),
+    ("d", "Delete from the history files (normal mode)"),
+    ("Ctrl-K", "Prune entries matching a regex"),
+    ("Ctrl-T", "Star as a snippet"),
+    ("Ctrl-S", "Switch ranking"),
+    ("Ctrl-D", "Only commands run here (while typing)"),
+    ("Ctrl-X", "Cycle failed / OK filter"),
+    ("Esc / q", "Clear the filter, then quit (normal mode)"),
+];
+
 #[derive(Debug, Clone)]
 pub struct HistoryEntry {
     pub command: String,
-    #[allow(dead_code)]
-    pub timestamp: Option<String>,
+    /// When the command ran, in seconds since the Unix epoch
+    pub timestamp: Option<i64>,
+    /// Shell whose history the command came from
+    pub shell: Option<Shell>,
+    /// How many times the command appears in the history
+    pub count: usize,
+    /// Runs recorded by the `tt init` hook, with exit status and directory
+    pub log: Option<CommandStats>,
+}
+
+impl HistoryEntry {
+    /// Whether the last logged run exited non-zero
+    fn failed(&self) -> bool {
+        self.log.as_ref().map(|log| log.last.failed()).unwrap_or(false)
+    }
+}
+
+/// Order of the history list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ranking {
+    /// Most recently used first
+    Recent,
+    /// Highest run count first, ties broken by recency
+    MostUsed,
+    /// Alphabetical by command
+    Alphabetical,
+}
+
+enum Mode {
+    Normal,
+    /// Editing a command before it is run or printed
+    Edit(TextInput),
+    /// Confirming removal of the selected command from the history files
+    ConfirmDelete,
+    /// Typing a regex of entries to prune
+    Prune(TextInput),
+    /// Confirming removal of every entry matching the regex
+    ConfirmPrune(Regex),
+}
+
+/// Which commands to show by the exit status of their last logged run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatusFilter {
+    All,
+    Failed,
+    Succeeded,
+}
+
+impl StatusFilter {
+    fn next(&self) -> Self {
+        match self {
+            StatusFilter::All => StatusFilter::Failed,
+            StatusFilter::Failed => StatusFilter::Succeeded,
+            StatusFilter::Succeeded => StatusFilter::All,
+        }
+    }
+}
+
+impl Ranking {
+    const ALL: [Ranking; 3] = [Ranking::Recent, Ranking::MostUsed, Ranking::Alphabetical];
+    
+    fn label(&self) -> &'static str {
+        match self {
+            Ranking::Recent => "recent",
+            Ranking::MostUsed => "most used",
+            Ranking::Alphabetical => "alphabetical",
+        }
+    }
+    
+    fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|ranking| ranking.label() == label)
+    }
+    
+    fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|ranking| ranking == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
 }
 
 pub struct HistoryBrowser {
+    /// Unique commands, most recently used first
     entries: Vec<HistoryEntry>,
+    /// Indices into `entries` in the current ranking
+    order: Vec<usize>,
+    ranking: Ranking,
+    /// Indices into `entries` matching the filter, best match first, with
+    /// the matched char positions for highlighting
+    filtered: Vec<(usize, Vec<usize>)>,
+    filter: TextInput,
+    /// Filtering waits for typing to pause
+    filter_debounce: Debounce,
+    /// Selection within `filtered`
     list_state: ListState,
+    vim: VimNav,
+    /// Selection and scroll offset of the rendered list, which also contains
+    /// date headers
+    view_state: ListState,
     should_quit: bool,
-    status_message: String,
+    notifications: Notifications,
     preview_content: String,
+    /// Help already looked up, by command name
+    help: HashMap<String, String>,
+    /// Help being looked up in the background
+    help_job: Option<(String, Job<String>)>,
     limit: usize,
+    /// Shells whose history files are merged
+    shells: Vec<Shell>,
+    mode: Mode,
+    /// Command to run in a subshell once the terminal is released
+    run_requested: Option<String>,
+    /// Command printed to stdout on exit
+    output: Option<String>,
+    /// Enter prints the command instead of running it
+    print_selection: bool,
+    /// Saved snippets; history commands among them are shown starred
+    snippets: Vec<Snippet>,
+    /// Directory tt was started in, for the "run here" filter
+    cwd: Option<PathBuf>,
+    /// Only show commands logged in `cwd`
+    here_only: bool,
+    status_filter: StatusFilter,
+    settings: Settings,
 }
 
 impl HistoryBrowser {
-    /// Create a new history browser
-    pub fn new(limit: usize) -> io::Result<Self> {
+    /// Create a new history browser over the history of `shells`
+    pub fn new(limit: usize, shells: Vec<Shell>, settings: Settings) -> io::Result<Self> {
         let mut browser = HistoryBrowser {
             entries: Vec::new(),
+            order: Vec::new(),
+            ranking: state::get("hist.ranking")
+                .and_then(|label| Ranking::from_label(&label))
+                .unwrap_or(Ranking::Recent),
+            filtered: Vec::new(),
+            filter: TextInput::default(),
+            filter_debounce: Debounce::default(),
             list_state: ListState::default(),
+            vim: VimNav::default(),
+            view_state: ListState::default(),
             should_quit: false,
-            status_message: "Loading command history...".to_string(),
+            notifications: Notifications::with_status("Loading command history..."),
             preview_content: String::new(),
+            help: HashMap::new(),
+            help_job: None,
             limit,
+            shells,
+            mode: Mode::Normal,
+            run_requested: None,
+            output: None,
+            print_selection: false,
+            snippets: Vec::new(),
+            cwd: env::current_dir().ok(),
+            here_only: false,
+            status_filter: StatusFilter::All,
+            settings,
         };
         
         browser.load_history()?;
+        match snippets::load() {
+            Ok(snippets) => browser.snippets = snippets,
+            Err(e) => browser.notifications.error(format!("Could not load snippets: {}", e)),
+        }
         
         Ok(browser)
     }
     
     /// Load command history
     fn load_history(&mut self) -> io::Result<()> {
-        // Try to load from bash history file
-        if let Ok(home) = env::var("HOME") {
-            let history_file = PathBuf::from(home).join(".bash_history");
-            if let Ok(content) = fs::read_to_string(history_file) {
-                let lines: Vec<&str> = content.lines().collect();
-                let start = if lines.len() > self.limit {
-                    lines.len() - self.limit
-                } else {
-                    0
-                };
-                
-                for line in lines[start..].iter().rev() {
-                    if !line.trim().is_empty() {
-                        self.entries.push(HistoryEntry {
-                            command: line.to_string(),
-                            timestamp: None,
-                        });
-                    }
+        // Newest first from the shells' history files
+        let items = shell_history::load(&self.shells);
+        for item in items.into_iter().rev() {
+            self.entries.push(HistoryEntry {
+                command: item.command,
+                timestamp: item.timestamp,
+                shell: Some(item.shell),
+                count: 1,
+                log: None,
+            });
+        }
+        
+        // Merge duplicates into their most recent use, keeping the run count
+        let mut first_seen: HashMap<String, usize> = HashMap::new();
+        let mut unique: Vec<HistoryEntry> = Vec::new();
+        for entry in std::mem::take(&mut self.entries) {
+            match first_seen.get(&entry.command) {
+                Some(&index) => unique[index].count += 1,
+                None => {
+                    first_seen.insert(entry.command.clone(), unique.len());
+                    unique.push(entry);
                 }
-            } else {
-                // Fallback to history command
-                self.load_from_history_command()?;
             }
-        } else {
-            self.load_from_history_command()?;
         }
-        
-        // Remove duplicates while preserving order
-        let mut seen = std::collections::HashSet::new();
-        self.entries.retain(|entry| seen.insert(entry.command.clone()));
-        
-        if !self.entries.is_empty() {
-            self.list_state.select(Some(0));
-            self.update_preview();
+        unique.truncate(self.limit);
+        let mut logged = command_log::stats(command_log::load());
+        for entry in &mut unique {
+            entry.log = logged.remove(&entry.command);
         }
+        self.entries = unique;
+        self.apply_ranking();
         
-        self.status_message = format!("Loaded {} commands", self.entries.len());
+        let shells: Vec<&str> = self.shells.iter().map(|shell| shell.name()).collect();
+        self.notifications.set_status(format!("Loaded {} commands ({})", self.entries.len(), shells.join(", ")));
         Ok(())
     }
     
-    /// Load from history command as fallback
-    fn load_from_history_command(&mut self) -> io::Result<()> {
-        let output = Command::new("history")
-            .arg(format!("{}", self.limit))
-            .output();
+    /// Reorder the list for the current ranking
+    fn apply_ranking(&mut self) {
+        self.order = (0..self.entries.len()).collect();
+        let entries = &self.entries;
+        match self.ranking {
+            // Entries are already most recent first
+            Ranking::Recent => {}
+            Ranking::MostUsed => self.order.sort_by_key(|&index| std::cmp::Reverse(entries[index].count)),
+            Ranking::Alphabetical => self.order.sort_by(|&a, &b| entries[a].command.cmp(&entries[b].command)),
+        }
+        self.apply_filter();
+    }
+    
+    /// Whether `entry` passes the directory and exit status filters;
+    /// commands the hook never logged only pass when both are off
+    fn in_scope(&self, entry: &HistoryEntry) -> bool {
+        if self.here_only {
+            let ran_here = match (&entry.log, &self.cwd) {
+                (Some(log), Some(cwd)) => log.dirs.contains(cwd),
+                _ => false,
+            };
+            if !ran_here {
+                return false;
+            }
+        }
+        match self.status_filter {
+            StatusFilter::All => true,
+            StatusFilter::Failed => entry.failed(),
+            StatusFilter::Succeeded => entry.log.is_some() && !entry.failed(),
+        }
+    }
+    
+    /// Title suffix describing the active directory and status filters
+    fn scope_label(&self) -> String {
+        let mut label = String::new();
+        if self.here_only {
+            label.push_str(" - run here");
+        }
+        match self.status_filter {
+            StatusFilter::All => {}
+            StatusFilter::Failed => label.push_str(" - failed"),
+            StatusFilter::Succeeded => label.push_str(" - succeeded"),
+        }
+        label
+    }
+    
+    /// Status message after changing the directory or status filter
+    fn scope_message(&self) -> String {
+        if !self.entries.iter().any(|entry| entry.log.is_some()) {
+            return "No commands logged yet: enable the hook with tt init".to_string();
+        }
+        format!("{} of {} commands{}", self.filtered.len(), self.entries.len(), self.scope_label())
+    }
+    
+    /// Recompute the fuzzy matches for the filter and select the best one;
+    /// equally good matches keep the ranking order
+    fn apply_filter(&mut self) {
+        let pattern = self.filter.value();
+        let order = self.order.iter().copied().filter(|&index| self.in_scope(&self.entries[index]));
+        self.filtered = if pattern.is_empty() {
+            order.map(|index| (index, Vec::new())).collect()
+        } else {
+            let mut scored: Vec<(i64, usize, Vec<usize>)> = order
+                .filter_map(|index| {
+                    fuzzy::match_positions(pattern, &self.entries[index].command)
+                        .map(|(score, positions)| (score, index, positions))
+                })
+                .collect();
+            scored.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+            scored.into_iter().map(|(_, index, positions)| (index, positions)).collect()
+        };
         
-        if let Ok(output) = output {
-            if output.status.success() {
-                let history_output = String::from_utf8_lossy(&output.stdout);
-                for line in history_output.lines().rev() {
-                    if let Some(cmd_start) = line.find(' ') {
-                        let command = line[cmd_start..].trim().to_string();
-                        if !command.is_empty() {
-                            self.entries.push(HistoryEntry {
-                                command,
-                                timestamp: None,
-                            });
-                        }
-                    }
-                }
+        self.list_state.select(if self.filtered.is_empty() { None } else { Some(0) });
+        self.update_preview();
+    }
+    
+    /// Rows of `filtered` that start a new day group, with the group's
+    /// heading. Only the unfiltered most-recent-first view is grouped, and
+    /// only when the history has timestamps.
+    fn group_starts(&self) -> Vec<(usize, String)> {
+        if self.ranking != Ranking::Recent || !self.filter.value().is_empty() {
+            return Vec::new();
+        }
+        let now = time_util::now_secs();
+        let mut starts: Vec<(usize, String)> = Vec::new();
+        for (row, (index, _)) in self.filtered.iter().enumerate() {
+            // Untimed commands stay in the group of the command before them
+            let Some(timestamp) = self.entries[*index].timestamp else {
+                continue;
+            };
+            let label = time_util::day_group(timestamp, now);
+            if starts.last().map(|(_, last)| last != &label).unwrap_or(true) {
+                starts.push((row, label));
             }
         }
-        Ok(())
+        starts
+    }
+    
+    /// Select the first command of the next (or previous) day group
+    fn jump_group(&mut self, forward: bool) {
+        let Some(selected) = self.list_state.selected() else {
+            return;
+        };
+        let starts = self.group_starts();
+        let target = if forward {
+            starts.iter().map(|(row, _)| *row).find(|&row| row > selected)
+        } else {
+            starts.iter().map(|(row, _)| *row).rev().find(|&row| row < selected)
+        };
+        if let Some(row) = target {
+            self.list_state.select(Some(row));
+            self.update_preview();
+        }
+    }
+    
+    fn selected_entry(&self) -> Option<&HistoryEntry> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.filtered.get(index))
+            .map(|(index, _)| &self.entries[*index])
     }
     
     /// Update preview content
     fn update_preview(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(entry) = self.entries.get(selected) {
-                // Show command details and man page if available
-                let parts: Vec<&str> = entry.command.split_whitespace().collect();
-                if let Some(command) = parts.first() {
-                    self.preview_content = self.get_command_help(command);
-                } else {
-                    self.preview_content = "No command selected".to_string();
+        self.preview_content = match self.selected_entry() {
+            // Show command details and man page if available
+            Some(entry) => match entry.command.split_whitespace().next() {
+                Some(command) => {
+                    let mut details = Vec::new();
+                    if let Some(shell) = entry.shell {
+                        details.push(format!("Shell: {}", shell.name()));
+                    }
+                    if let Some(timestamp) = entry.timestamp {
+                        details.push(format!("Last run: {}", time_util::format_timestamp(timestamp)));
+                    }
+                    details.push(format!("Runs: {}", entry.count));
+                    if let Some(log) = &entry.log {
+                        let last = &log.last;
+                        let outcome = if last.failed() { "failed" } else { "ok" };
+                        details.push(format!("Exit status: {} ({})", last.status, outcome));
+                        details.push(format!("Took: {}", time_util::format_duration(last.duration_ms)));
+                        details.push(format!("Directory: {}", last.cwd.display()));
+                        details.push(format!("Logged runs: {} ({} failed)", log.runs, log.failures));
+                    }
+                    let help = match self.help.get(command) {
+                        Some(help) => help.clone(),
+                        None => format!("Looking up help for {}...", command),
+                    };
+                    // The full command, line breaks and indentation intact
+                    format!("{}\n\n{}\n\n{}", entry.command, details.join("\n"), help)
                 }
-            }
+                None => "No command selected".to_string(),
+            },
+            None => String::new(),
+        };
+        let command = self.selected_entry().and_then(|entry| entry.command.split_whitespace().next());
+        if let Some(command) = command.filter(|command| !self.help.contains_key(*command)).map(str::to_string) {
+            self.request_help(command);
         }
     }
     
-    /// Get help for a command
-    fn get_command_help(&self, command: &str) -> String {
-        // Try to get brief help from man or --help
-        if let Ok(output) = Command::new("man")
-            .args(&["-f", command])
-            .output() {
-            if output.status.success() {
-                let help = String::from_utf8_lossy(&output.stdout);
-                if !help.trim().is_empty() {
-                    return format!("Manual page for '{}':\n\n{}", command, help);
-                }
-            }
+    /// Look up help for `command` in the background, replacing (and so
+    /// cancelling) a lookup for another command
+    fn request_help(&mut self, command: String) {
+        if self.help_job.as_ref().is_some_and(|(pending, _)| *pending == command) {
+            return;
+        }
+        let name = command.clone();
+        let job = self.notifications.jobs().spawn(format!("Help for {}", command), move |task| command_help(task, &name));
+        self.help_job = Some((command, job));
+    }
+    
+    /// Keep help looked up in the background once done, showing it if its
+    /// command is still selected; returns whether it came
+    fn poll_help(&mut self) -> bool {
+        let Some(result) = self.help_job.as_mut().and_then(|(_, job)| job.poll()) else {
+            return false;
+        };
+        let Some((command, _)) = self.help_job.take() else {
+            return false;
+        };
+        let help = match result {
+            Ok(help) => help,
+            Err(e) if jobs::is_cancelled(&e) => return true,
+            Err(e) => format!("No help available for command: {} ({})", command, e),
+        };
+        self.help.insert(command, help);
+        self.update_preview();
+        true
+    }
+    
+    /// Print `command` on exit so the shell (or a pipe) can use it
+    fn print_command(&mut self, command: String) {
+        self.output = Some(command);
+        self.should_quit = true;
+    }
+    
+    /// Act on the chosen command: print it in `--print-selection` mode,
+    /// otherwise run it
+    fn choose(&mut self, command: String) {
+        if self.print_selection {
+            self.print_command(command);
+        } else {
+            self.run_requested = Some(command);
         }
+    }
+    
+    fn is_starred(&self, command: &str) -> bool {
+        self.snippets.iter().any(|snippet| snippet.command == command)
+    }
+    
+    /// Save the selected command as a snippet, or remove it if already saved
+    fn toggle_star(&mut self) {
+        let Some(command) = self.selected_entry().map(|entry| entry.command.clone()) else {
+            return;
+        };
+        let starred = self.is_starred(&command);
+        if starred {
+            self.snippets.retain(|snippet| snippet.command != command);
+        } else {
+            self.snippets.push(Snippet::new(command));
+        }
+        match snippets::save(&self.snippets) {
+            Ok(()) if starred => self.notifications.success("Removed from snippets"),
+            Ok(()) => self.notifications.success("Saved to snippets (browse with tt snip)"),
+            Err(e) => self.notifications.error(format!("Could not save snippets: {}", e)),
+        }
+    }
+    
+    /// Shell to run `command` with: the one it came from, else `$SHELL`
+    fn shell_for(&self, command: &str) -> String {
+        let shell = self.entries
+            .iter()
+            .find(|entry| entry.command == command)
+            .and_then(|entry| entry.shell);
+        match shell {
+            Some(shell) => shell.name().to_string(),
+            None => platform::user_shell(),
+        }
+    }
+    
+    /// Remove entries whose command satisfies `matches` from the history
+    /// files of `shells` and from the command log, then reload the list
+    fn remove_entries(&mut self, shells: &[Shell], matches: impl Fn(&str) -> bool, last_only: bool) -> io::Result<usize> {
+        let mut removed = 0;
+        for &shell in shells {
+            removed += shell_history::remove(shell, &matches, last_only)?;
+        }
+        command_log::remove(&matches, last_only)?;
         
-        // Try --help as fallback
-        if let Ok(output) = Command::new(command)
-            .arg("--help")
-            .output() {
-            if output.status.success() {
-                let help = String::from_utf8_lossy(&output.stdout);
-                let lines: Vec<&str> = help.lines().take(20).collect();
-                return format!("Help for '{}':\n\n{}", command, lines.join("\n"));
+        let selected = self.list_state.selected();
+        self.entries.clear();
+        self.load_history()?;
+        if let Some(row) = selected {
+            if !self.filtered.is_empty() {
+                self.list_state.select(Some(row.min(self.filtered.len() - 1)));
+                self.update_preview();
             }
         }
-        
-        format!("No help available for command: {}", command)
+        Ok(removed)
     }
     
-    /// Execute selected command
-    fn execute_command(&mut self) -> io::Result<()> {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(entry) = self.entries.get(selected) {
-                // Print the command and exit - let the shell handle execution
-                println!("{}", entry.command);
-                self.should_quit = true;
-            }
+    /// Delete the selected command's latest use, or every use with `all`
+    fn delete_selected(&mut self, all: bool) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let command = entry.command.clone();
+        // The latest use is in the file of the shell the entry came from
+        let shells: Vec<Shell> = if all { self.shells.clone() } else { entry.shell.into_iter().collect() };
+        if shells.is_empty() {
+            self.notifications.error("Not from a history file; nothing to delete");
+            return;
+        }
+        match self.remove_entries(&shells, |candidate| candidate == command, !all) {
+            Ok(removed) => self.notifications.success(format!(
+                "Deleted {} entr{} from history",
+                removed,
+                if removed == 1 { "y" } else { "ies" }
+            )),
+            Err(e) => self.notifications.error(format!("Could not delete from history: {}", e)),
         }
-        Ok(())
+    }
+    
+    /// Delete every history entry matching `pattern`
+    fn prune(&mut self, pattern: &Regex) {
+        let shells = self.shells.clone();
+        match self.remove_entries(&shells, |command| pattern.is_match(command), false) {
+            Ok(removed) => self.notifications.success(format!(
+                "Pruned {} entr{} matching /{}/",
+                removed,
+                if removed == 1 { "y" } else { "ies" },
+                pattern
+            )),
+            Err(e) => self.notifications.error(format!("Could not prune history: {}", e)),
+        }
+    }
+    
+    /// Commands shown in the list that match `pattern`, and their total uses
+    fn prune_matches(&self, pattern: &Regex) -> (usize, usize) {
+        self.entries
+            .iter()
+            .filter(|entry| pattern.is_match(&entry.command))
+            .fold((0, 0), |(commands, uses), entry| (commands + 1, uses + entry.count))
     }
     
     /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page down
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.entries.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page up
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.entries.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected > 0 {
-                                self.list_state.select(Some(selected - 1));
-                                self.update_preview();
-                            }
-                        }
-                    }
-                    KeyCode::Down => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected + 1 < self.entries.len() {
-                                self.list_state.select(Some(selected + 1));
-                                self.update_preview();
-                            }
-                        } else if !self.entries.is_empty() {
-                            self.list_state.select(Some(0));
-                            self.update_preview();
-                        }
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if !self.vim.typing(&key) && self.filter_debounce.take() {
+            self.apply_filter();
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal_input(key.code, key.modifiers),
+            Mode::Edit(mut input) => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => self.choose(input.value().to_string()),
+                KeyCode::Char('o') if ctrl => self.print_command(input.value().to_string()),
+                code => {
+                    if !ctrl {
+                        input.handle_key(code);
                     }
-                    KeyCode::Enter => {
-                        self.execute_command()?;
+                    self.mode = Mode::Edit(input);
+                }
+            },
+            Mode::ConfirmDelete => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => self.delete_selected(false),
+                KeyCode::Char('a') | KeyCode::Char('A') => self.delete_selected(true),
+                _ => self.notifications.info("Cancelled"),
+            },
+            Mode::Prune(mut input) => match key.code {
+                KeyCode::Esc => self.notifications.info("Cancelled"),
+                KeyCode::Enter => match Regex::new(input.value()) {
+                    Ok(pattern) if !input.value().is_empty() => self.mode = Mode::ConfirmPrune(pattern),
+                    _ => self.mode = Mode::Prune(input),
+                },
+                code => {
+                    if !ctrl {
+                        input.handle_key(code);
                     }
-                    _ => {}
+                    self.mode = Mode::Prune(input);
                 }
-            }
+            },
+            Mode::ConfirmPrune(pattern) => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => self.prune(&pattern),
+                _ => self.notifications.info("Cancelled"),
+            },
         }
         Ok(())
     }
     
+    /// Handle keys in the history list
+    fn handle_normal_input(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        // Home and End move the cursor in the filter
+        let is_cursor_key = self.vim.inserting() && matches!(code, KeyCode::Home | KeyCode::End);
+        if !is_cursor_key {
+            match self.vim.filter_key(&mut self.list_state, code, modifiers, self.filtered.len(), self.settings.page_size) {
+                NavKey::Moved => {
+                    self.update_preview();
+                    return;
+                }
+                NavKey::Used => return,
+                NavKey::Unused => {}
+            }
+        }
+        
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') if !self.vim.inserting() && !self.filter.value().is_empty() => {
+                self.filter = TextInput::default();
+                self.apply_filter();
+            }
+            KeyCode::Esc | KeyCode::Char('q') if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Enter => {
+                if let Some(command) = self.selected_entry().map(|entry| entry.command.clone()) {
+                    self.choose(command);
+                }
+            }
+            KeyCode::Char('t') if ctrl => self.toggle_star(),
+            KeyCode::Tab => self.jump_group(true),
+            KeyCode::BackTab => self.jump_group(false),
+            KeyCode::Char('o') if ctrl => {
+                if let Some(command) = self.selected_entry().map(|entry| entry.command.clone()) {
+                    self.print_command(command);
+                }
+            }
+            KeyCode::Char('e') if !ctrl && !self.vim.inserting() => {
+                if let Some(entry) = self.selected_entry() {
+                    self.mode = Mode::Edit(TextInput::new(entry.command.as_str()));
+                }
+            }
+            KeyCode::Char('d') if !ctrl && !self.vim.inserting() && self.selected_entry().is_some() => {
+                self.mode = Mode::ConfirmDelete;
+            }
+            KeyCode::Char('k') if ctrl => {
+                self.mode = Mode::Prune(TextInput::default());
+            }
+            KeyCode::Char('d') if ctrl => {
+                self.here_only = !self.here_only;
+                self.apply_filter();
+                self.notifications.info(self.scope_message());
+            }
+            KeyCode::Char('x') if ctrl => {
+                self.status_filter = self.status_filter.next();
+                self.apply_filter();
+                self.notifications.info(self.scope_message());
+            }
+            KeyCode::Char('s') if ctrl => {
+                self.ranking = self.ranking.next();
+                self.apply_ranking();
+                let saved = state::set("hist.ranking", self.ranking.label());
+                match saved {
+                    Ok(()) => self.notifications.info(format!("Ranked by {}", self.ranking.label())),
+                    Err(e) => self.notifications.error(format!("Ranked by {} (not saved: {})", self.ranking.label(), e)),
+                }
+            }
+            code if !ctrl && self.vim.inserting() && self.filter.handle_key(code) => {
+                self.filter_debounce.schedule();
+            }
+            _ => {}
+        }
+    }
+    
     /// Render the history browser
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
-            .split(f.area());
+            .split(area);
         
-        self.render_history_list(f, chunks[0]);
+        let left = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(chunks[0]);
+        
+        self.render_filter(f, left[0]);
+        self.render_history_list(f, left[1]);
         self.render_command_help(f, chunks[1]);
-        self.render_status_bar(f);
+        self.render_status_bar(f, area);
+        
+        match &self.mode {
+            Mode::Normal => {}
+            Mode::Edit(input) => {
+                let label = if self.print_selection { "Enter Insert" } else { "Enter Run • Ctrl-O Print instead" };
+                tui_common::render_input_popup(f, "Edit Command", label, input);
+            }
+            Mode::ConfirmDelete => {
+                let (command, count) = self.selected_entry()
+                    .map(|entry| (tui_common::single_line(&entry.command), entry.count))
+                    .unwrap_or_default();
+                let keys = if count > 1 {
+                    format!("y Delete latest use • a Delete all {} uses • any other key Cancel", count)
+                } else {
+                    "y Delete • any other key Cancel".to_string()
+                };
+                let lines = vec![
+                    Line::from("Delete from the history file?"),
+                    Line::from(Span::styled(command, Style::default().fg(colors::warning()))),
+                    Line::from(""),
+                    Line::from(Span::styled(keys, Style::default().fg(colors::muted()))),
+                ];
+                tui_common::render_message_popup(f, "Delete Command", lines, colors::danger());
+            }
+            Mode::Prune(input) => {
+                let label = match Regex::new(input.value()) {
+                    _ if input.value().is_empty() => "Regex of commands to remove (tokens, secrets, typos):".to_string(),
+                    Ok(pattern) => {
+                        let (commands, uses) = self.prune_matches(&pattern);
+                        format!("Matches {} listed command(s), {} use(s) • Enter Review", commands, uses)
+                    }
+                    Err(_) => "Invalid regex".to_string(),
+                };
+                tui_common::render_input_popup(f, "Prune History", &label, input);
+            }
+            Mode::ConfirmPrune(pattern) => {
+                let (commands, uses) = self.prune_matches(pattern);
+                let shells: Vec<&str> = self.shells.iter().map(|shell| shell.name()).collect();
+                let lines = vec![
+                    Line::from(format!("Remove every entry matching /{}/ from the {} history?", pattern, shells.join(", "))),
+                    Line::from(format!("Matches {} listed command(s), {} use(s)", commands, uses)),
+                    Line::from(""),
+                    Line::from(Span::styled("y Remove • any other key Cancel", Style::default().fg(colors::muted()))),
+                ];
+                tui_common::render_message_popup(f, "Prune History", lines, colors::danger());
+            }
+        }
+    }
+    
+    /// Render the filter input
+    fn render_filter(&self, f: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.filter.line())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Filter")
+                .border_style(Style::default().fg(colors::secondary())));
+        
+        f.render_widget(paragraph, area);
     }
     
-    /// Render history list
+    /// Render history list, highlighting the characters the filter matched
     fn render_history_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.entries
-            .iter()
-            .enumerate()
-            .map(|(i, entry)| {
-                let line = Line::from(format!("{:3}: {}", 
-                    self.entries.len() - i, 
-                    entry.command
-                ));
-                ListItem::new(line)
-            })
-            .collect();
+        let highlight = Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD);
+        let now = time_util::now_secs();
+        let mut groups = self.group_starts().into_iter().peekable();
+        let mut items: Vec<Line> = Vec::new();
+        let mut selected_item = None;
+        for (row, (index, positions)) in self.filtered.iter().enumerate() {
+            if let Some((_, label)) = groups.next_if(|(start, _)| *start == row) {
+                items.push(Line::from(Span::styled(
+                    format!("── {} ──", label),
+                    Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD),
+                )));
+            }
+            if self.list_state.selected() == Some(row) {
+                selected_item = Some(items.len());
+            }
+            
+            let entry = &self.entries[*index];
+            let age = entry.timestamp.map(|secs| time_util::format_age(secs, now)).unwrap_or_default();
+            let star = if self.is_starred(&entry.command) { "★ " } else { "  " };
+            let mut spans = vec![
+                Span::styled(format!("{:>4}× {:>4} ", entry.count, age), Style::default().fg(colors::muted())),
+                Span::styled(star, Style::default().fg(colors::secondary())),
+            ];
+            spans.extend(tui_common::highlight_spans(&tui_common::single_line(&entry.command), positions, highlight));
+            let mut line = Line::from(spans);
+            if entry.failed() {
+                line = line.style(Style::default().fg(colors::danger()));
+            }
+            items.push(line);
+        }
+        self.view_state.select(selected_item);
         
-        let list = List::new(items)
+        let title = if self.filter.value().is_empty() && self.filtered.len() == self.entries.len() {
+            format!("Command History ({}) - {}", self.entries.len(), self.ranking.label())
+        } else {
+            format!("Command History ({} of {}) - {}", self.filtered.len(), self.entries.len(), self.ranking.label())
+        };
+        let title = format!("{}{}", title, self.scope_label());
+        let list = SelectableList::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
-                .title(format!("Command History ({})", self.entries.len()))
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
+                .title(title)
+                .border_style(Style::default().fg(colors::primary())));
         
-        f.render_stateful_widget(list, area, &mut self.list_state);
+        f.render_stateful_widget(list, area, &mut self.view_state);
     }
     
     /// Render command help
     fn render_command_help(&self, f: &mut Frame, area: Rect) {
-        let title = if let Some(selected) = self.list_state.selected() {
-            if let Some(entry) = self.entries.get(selected) {
-                let parts: Vec<&str> = entry.command.split_whitespace().collect();
-                if let Some(command) = parts.first() {
-                    format!("Help: {}", command)
-                } else {
-                    "Help".to_string()
-                }
-            } else {
-                "Help".to_string()
-            }
-        } else {
-            "Help".to_string()
+        let title = match self.selected_entry().and_then(|entry| entry.command.split_whitespace().next()) {
+            Some(command) => format!("Help: {}", command),
+            None => "Help".to_string(),
         };
         
         let paragraph = Paragraph::new(self.preview_content.as_str())
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::SECONDARY)))
-            .wrap(Wrap { trim: true });
+                .border_style(Style::default().fg(colors::secondary())))
+            .wrap(Wrap { trim: false });
         
         f.render_widget(paragraph, area);
     }
     
     /// Render status bar
-    fn render_status_bar(&self, f: &mut Frame) {
-        let area = Rect {
-            x: 0,
-            y: f.area().height - 1,
-            width: f.area().width,
-            height: 1,
-        };
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
         
-        let help_text = "↑↓ Navigate • Enter Execute • Esc Quit";
-        let status_text = format!("{} | {}", self.status_message, help_text);
-        
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
-        
-        f.render_widget(paragraph, area);
+        let help_text = match (self.vim.inserting(), self.print_selection) {
+            (true, true) => "Type to filter • Enter Insert • F1 Help • Esc Normal mode",
+            (true, false) => "Type to filter • Enter Run • ^P Print • F1 Help • Esc Normal mode",
+            (false, true) => "j/k Move • / Filter • Enter Insert • e Edit • ? Help • q Clear/Quit",
+            (false, false) => "j/k Move • / Filter • Enter Run • e Edit • ^P Print • ? Help • q Clear/Quit",
+        };
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
     }
     
-    /// Run the history browser
+    /// Run the history browser, from where it was left in this directory
     pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
-        let result = self.run_app(&mut terminal);
-        tui_common::restore_terminal(&mut terminal)?;
+        // Keep stdout clean for the chosen command when it is captured
+        let result = if self.print_selection || !io::stdout().is_terminal() {
+            session::resume(self, "hist", |browser| tui_common::run_app_on(browser, io::stderr()))
+        } else {
+            session::resume(self, "hist", |browser| tui_common::run_app_on(browser, io::stdout()))
+        };
+        
+        if let Some(command) = &self.output {
+            println!("{}", command);
+        }
         result
     }
     
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            self.handle_input()?;
-            if self.should_quit {
-                break;
+    /// Print the filtered commands instead of running the TUI, with the
+    /// exit status of the last logged run where there is one
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        plain::print(format, self.filtered.iter().map(|(index, _)| {
+            let entry = &self.entries[*index];
+            vec![
+                ("command", Value::from(entry.command.as_str())),
+                ("count", Value::from(entry.count)),
+                ("timestamp", Value::from(entry.timestamp)),
+                ("shell", Value::from(entry.shell.map(Shell::name))),
+                ("status", Value::from(entry.log.as_ref().map(|log| log.last.status))),
+            ]
+        }))
+    }
+}
+
+impl App for HistoryBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick if self.filter_debounce.ready() => {
+                self.apply_filter();
+                return Ok(Action::Redraw);
+            }
+            AppEvent::Tick => {
+                if self.poll_help() {
+                    return Ok(Action::Redraw);
+                }
             }
         }
-        Ok(())
+        Ok(if self.should_quit {
+            Action::Quit
+        } else if self.run_requested.is_some() {
+            Action::Suspend
+        } else {
+            Action::Continue
+        })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        match self.mode {
+            Mode::Normal => self.vim.inserting(),
+            _ => matches!(self.mode, Mode::Edit(_) | Mode::Prune(_)),
+        }
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn suspended(&mut self) -> io::Result<Action> {
+        if let Some(command) = self.run_requested.take() {
+            let shell = self.shell_for(&command);
+            match tui_common::run_and_wait(&command, &shell, "tt hist") {
+                Ok(quit) => self.should_quit = quit,
+                Err(e) => self.notifications.error(format!("Could not run command: {}", e)),
+            }
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+}
+
+/// Brief help for `command`: its `man -f` summary, or the start of its
+/// `--help` output
+fn command_help(task: &Task, command: &str) -> io::Result<String> {
+    match task.output(Command::new("man").args(["-f", command])) {
+        Ok(output) if output.status.success() => {
+            let help = String::from_utf8_lossy(&output.stdout);
+            if !help.trim().is_empty() {
+                return Ok(format!("Manual page for '{}':\n\n{}", command, help));
+            }
+        }
+        Err(e) if jobs::is_cancelled(&e) => return Err(e),
+        _ => {}
+    }
+    task.check()?;
+    
+    // Try --help as fallback, found on PATH and given up on if it hangs
+    Ok(match help_text::run(command, 80) {
+        Ok(help) => {
+            let lines: Vec<&str> = help.lines().take(20).collect();
+            format!("Help for '{}':\n\n{}", command, lines.join("\n"))
+        }
+        Err(_) => format!("No help available for command: {}", command),
+    })
+}
+
+impl Resume for HistoryBrowser {
+    fn session(&self) -> Session {
+        Session {
+            query: Some(self.filter.value().to_string()),
+            selected: self.selected_entry().map(|entry| entry.command.clone()),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query.filter(|_| self.filter.value().is_empty()) {
+            self.filter = TextInput::new(query);
+            self.apply_filter();
+        }
+        let position = session.selected.and_then(|selected| {
+            self.filtered.iter().position(|(index, _)| self.entries[*index].command == selected)
+        });
+        if let Some(position) = position {
+            self.list_state.select(Some(position));
+            self.update_preview();
+        }
+    }
+}
+
+/// Run the command history browser over the history of `shells`, or print
+/// the commands in `output`
+pub fn run(
+    limit: usize,
+    shells: Vec<Shell>,
+    query: Option<String>,
+    print_selection: bool,
+    output: Option<Format>,
+    settings: Settings,
+) -> io::Result<()> {
+    let mut browser = HistoryBrowser::new(limit, shells, settings)?;
+    browser.print_selection = print_selection;
+    if let Some(query) = query {
+        browser.filter = TextInput::new(query);
+        browser.apply_filter();
+    }
+    match output {
+        Some(format) => browser.print(format),
+        None => browser.run(),
     }
 }
 
-/// Run the command history browser
-pub fn run(limit: usize) -> io::Result<()> {
-    let mut browser = HistoryBrowser::new(limit)?;
-    browser.run()
-}
\ No newline at end of file
+/// Log a finished command for the `tt init` hook, unless `$TT_NO_RECORD`
+/// is set; `shells` names the shell that ran it when it holds exactly one
+pub fn record(command: String, status: i32, duration_ms: u64, cwd: Option<PathBuf>, shells: Vec<Shell>) -> io::Result<()> {
+    if command.trim().is_empty() || env::var_os("TT_NO_RECORD").is_some_and(|value| !value.is_empty()) {
+        return Ok(());
+    }
+    let shell = match shells.as_slice() {
+        [shell] => Some(shell.name().to_string()),
+        _ => None,
+    };
+    let cwd = match cwd {
+        Some(cwd) => cwd,
+        None => env::current_dir()?,
+    };
+    // Best effort: the command is logged even if the MRU list can't be written
+    for file in mru::files_opened_by(&command, &cwd) {
+        let _ = mru::record(&file);
+    }
+    command_log::append(&CommandRecord {
+        command,
+        cwd,
+        status,
+        duration_ms,
+        time: time_util::now_secs(),
+        shell,
+    })
+}
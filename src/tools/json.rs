@@ -0,0 +1,508 @@
+//! JSON and YAML explorer: a collapsible tree with value preview, jq-style
+//! paths and fuzzy key search.
+
+use crate::clipboard;
+use crate::config::Settings;
+use crate::fuzzy;
+use crate::json_tree::{self, DataFormat, Path, Row, Segment};
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::text_width;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, ListState, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, IsTerminal, Read},
+    path::PathBuf,
+};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Home / End, gg / G", "First / last row"),
+    ("Enter / Space", "Expand or collapse"),
+    ("→ / l", "Expand, or go to the first child"),
+    ("← / h", "Collapse, or go to the parent"),
+    ("e", "Expand everything below the selection"),
+    ("c", "Collapse everything"),
+    ("/", "Fuzzy search the keys"),
+    ("y", "Copy the jq path"),
+    ("Y", "Copy the value"),
+    ("q / Esc", "Quit"),
+];
+
+/// Levels expanded when a document is opened
+const INITIAL_DEPTH: usize = 2;
+
+/// Search results listed at most
+const MAX_MATCHES: usize = 200;
+
+enum Mode {
+    Normal,
+    /// Fuzzy search over the paths of every node
+    Search {
+        query: TextInput,
+        /// Indices into `JsonExplorer::paths` with the matched char positions
+        matches: Vec<(usize, Vec<usize>)>,
+        list_state: ListState,
+    },
+}
+
+pub struct JsonExplorer {
+    /// File name, or `stdin`
+    name: String,
+    format: DataFormat,
+    value: Value,
+    expanded: HashSet<Path>,
+    rows: Vec<Row>,
+    /// Every node's path and jq path, for the search; filled on first use
+    paths: Vec<(Path, String)>,
+    /// The selected node's value as shown in the preview
+    preview: Option<(Path, Vec<String>)>,
+    mode: Mode,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    settings: Settings,
+}
+
+/// Color of a value by its type
+fn value_color(value: &Value) -> Color {
+    match value {
+        Value::String(_) => colors::success(),
+        Value::Number(_) => colors::warning(),
+        Value::Bool(_) | Value::Null => colors::secondary(),
+        Value::Array(_) | Value::Object(_) => colors::muted(),
+    }
+}
+
+impl JsonExplorer {
+    /// Read `file`, or standard input without one
+    pub fn new(file: Option<PathBuf>, settings: Settings) -> io::Result<Self> {
+        let (name, text, format) = match file {
+            Some(path) => {
+                let format = path.extension().and_then(|extension| DataFormat::from_extension(&extension.to_string_lossy()));
+                (path.display().to_string(), fs::read_to_string(&path)?, format)
+            }
+            None if io::stdin().is_terminal() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "no document to show: give a file or pipe one in (`curl -s URL | tt json`)",
+                ));
+            }
+            None => {
+                let mut text = String::new();
+                io::stdin().read_to_string(&mut text)?;
+                ("stdin".to_string(), text, None)
+            }
+        };
+        let (value, format) = json_tree::parse(&text, format)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", name, e)))?;
+        
+        let mut expanded: HashSet<Path> = json_tree::containers(&value, INITIAL_DEPTH).into_iter().collect();
+        expanded.insert(Vec::new());
+        let mut explorer = JsonExplorer {
+            name,
+            format,
+            value,
+            expanded,
+            rows: Vec::new(),
+            paths: Vec::new(),
+            preview: None,
+            mode: Mode::Normal,
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::new(),
+            settings,
+        };
+        explorer.update_rows();
+        explorer.list_state.select(Some(0));
+        explorer.update_preview();
+        explorer.notifications.set_status(format!("{} • {}", explorer.format.label(), json_tree::summary(&explorer.value)));
+        Ok(explorer)
+    }
+    
+    fn update_rows(&mut self) {
+        self.rows = json_tree::rows(&self.value, &self.expanded);
+    }
+    
+    fn selected_path(&self) -> Option<&Path> {
+        self.list_state.selected().and_then(|index| self.rows.get(index)).map(|row| &row.path)
+    }
+    
+    fn selected_value(&self) -> Option<&Value> {
+        self.selected_path().and_then(|path| json_tree::get(&self.value, path))
+    }
+    
+    /// Select the row of `path`
+    fn select_path(&mut self, path: &[Segment]) {
+        if let Some(index) = self.rows.iter().position(|row| row.path == path) {
+            self.list_state.select(Some(index));
+        }
+        self.update_preview();
+    }
+    
+    /// Render the selected value in the document's format for the preview,
+    /// once per selection
+    fn update_preview(&mut self) {
+        let Some(path) = self.selected_path().cloned() else {
+            self.preview = None;
+            return;
+        };
+        if self.preview.as_ref().is_some_and(|(shown, _)| *shown == path) {
+            return;
+        }
+        let value = json_tree::get(&self.value, &path).unwrap_or(&Value::Null);
+        let text = match self.format {
+            #[cfg(feature = "yaml")]
+            DataFormat::Yaml if json_tree::has_children(value) => serde_yaml::to_string(value).unwrap_or_default(),
+            _ => serde_json::to_string_pretty(value).unwrap_or_default(),
+        };
+        let lines = text.lines().take(self.settings.preview_lines).map(str::to_string).collect();
+        self.preview = Some((path, lines));
+    }
+    
+    /// Open or close the selected container
+    fn set_expanded(&mut self, open: bool) {
+        let Some(path) = self.selected_path().cloned() else {
+            return;
+        };
+        if !self.selected_value().is_some_and(json_tree::has_children) {
+            return;
+        }
+        if open {
+            self.expanded.insert(path);
+        } else {
+            self.expanded.remove(&path);
+        }
+        self.update_rows();
+    }
+    
+    fn handle_key(&mut self, key: KeyEvent) {
+        if matches!(self.mode, Mode::Search { .. }) {
+            self.handle_search_key(key);
+            return;
+        }
+        
+        if self.vim.navigate(&mut self.list_state, key.code, key.modifiers, self.rows.len(), self.settings.page_size) {
+            self.update_preview();
+            return;
+        }
+        
+        let Some(path) = self.selected_path().cloned() else {
+            if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                self.should_quit = true;
+            }
+            return;
+        };
+        let expanded = self.expanded.contains(&path);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Enter | KeyCode::Char(' ') => self.set_expanded(!expanded),
+            KeyCode::Right | KeyCode::Char('l') if expanded => {
+                let next = self.list_state.selected().map(|index| index + 1);
+                if next.is_some_and(|next| self.rows.get(next).is_some_and(|row| row.depth > path.len())) {
+                    self.list_state.select(next);
+                    self.update_preview();
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') => self.set_expanded(true),
+            KeyCode::Left | KeyCode::Char('h') if expanded && !path.is_empty() => self.set_expanded(false),
+            KeyCode::Left | KeyCode::Char('h') => {
+                if let Some(parent) = path.split_last().map(|(_, parent)| parent.to_vec()) {
+                    self.select_path(&parent);
+                }
+            }
+            KeyCode::Char('e') => {
+                let below = json_tree::get(&self.value, &path).map(|value| json_tree::containers(value, usize::MAX)).unwrap_or_default();
+                self.expanded.extend(below.into_iter().map(|inner| [path.clone(), inner].concat()));
+                self.update_rows();
+            }
+            KeyCode::Char('c') => {
+                self.expanded = HashSet::from([Vec::new()]);
+                self.update_rows();
+                // The top-level node the selection was under
+                self.select_path(&path[..path.len().min(1)]);
+            }
+            KeyCode::Char('/') => {
+                if self.paths.is_empty() {
+                    self.paths = json_tree::all_paths(&self.value)
+                        .into_iter()
+                        .map(|path| {
+                            let text = json_tree::jq_path(&path);
+                            (path, text)
+                        })
+                        .collect();
+                }
+                self.mode = Mode::Search { query: TextInput::new(""), matches: Vec::new(), list_state: ListState::default() };
+                self.update_matches();
+            }
+            KeyCode::Char('y') => {
+                clipboard::copy_and_notify(&mut self.notifications, "the path", &json_tree::jq_path(&path));
+            }
+            KeyCode::Char('Y') => {
+                if let Some(text) = self.selected_value().map(json_tree::raw_text) {
+                    clipboard::copy_and_notify(&mut self.notifications, "the value", &text);
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    fn handle_search_key(&mut self, key: KeyEvent) {
+        let Mode::Search { query, matches, list_state } = &mut self.mode else {
+            return;
+        };
+        if tui_common::navigate_list(list_state, key.code, key.modifiers, matches.len(), self.settings.page_size) {
+            return;
+        }
+        match key.code {
+            KeyCode::Esc => self.mode = Mode::Normal,
+            KeyCode::Enter => {
+                let target = list_state.selected().and_then(|index| matches.get(index)).map(|(index, _)| self.paths[*index].0.clone());
+                self.mode = Mode::Normal;
+                if let Some(target) = target {
+                    // Open every container on the way to the match
+                    for depth in 0..target.len() {
+                        self.expanded.insert(target[..depth].to_vec());
+                    }
+                    self.update_rows();
+                    self.select_path(&target);
+                }
+            }
+            code if !key.modifiers.contains(KeyModifiers::CONTROL) && query.handle_key(code) => self.update_matches(),
+            _ => {}
+        }
+    }
+    
+    /// Match the search query against every path, best first
+    fn update_matches(&mut self) {
+        let Mode::Search { query, matches, list_state } = &mut self.mode else {
+            return;
+        };
+        let query = query.value();
+        let mut scored: Vec<(i64, usize, Vec<usize>)> = self.paths
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter_map(|(index, (_, text))| fuzzy::match_positions(query, text).map(|(score, positions)| (score, index, positions)))
+            .collect();
+        // Stable, so an empty query lists the paths in document order
+        scored.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+        scored.truncate(MAX_MATCHES);
+        *matches = scored.into_iter().map(|(_, index, positions)| (index, positions)).collect();
+        list_state.select(if matches.is_empty() { None } else { Some(0) });
+    }
+    
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(chunks[1]);
+        
+        self.render_path(f, chunks[0]);
+        self.render_tree(f, panes[0]);
+        self.render_preview(f, panes[1]);
+        self.render_status_bar(f, chunks[2]);
+        self.render_search(f);
+    }
+    
+    /// The jq path of the selected node
+    fn render_path(&self, f: &mut Frame, area: Rect) {
+        let path = self.selected_path().map(|path| json_tree::jq_path(path)).unwrap_or_default();
+        let kind = self.selected_value().map(json_tree::type_name).unwrap_or_default();
+        let paragraph = Paragraph::new(Line::from(vec![
+            Span::styled(path, Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("  {}", kind), Style::default().fg(colors::muted())),
+        ]))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title("Path")
+            .border_style(Style::default().fg(colors::secondary())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    fn render_tree(&mut self, f: &mut Frame, area: Rect) {
+        // Only rows near the selection are drawn; build those alone
+        let selected = self.list_state.selected().unwrap_or(0);
+        let window = area.height as usize + self.settings.page_size;
+        let (start, end) = (selected.saturating_sub(window), (selected + window).min(self.rows.len()));
+        let items: Vec<Line> = self.rows
+            .iter()
+            .enumerate()
+            .map(|(index, row)| {
+                if index < start || index >= end {
+                    return Line::default();
+                }
+                let value = json_tree::get(&self.value, &row.path).unwrap_or(&Value::Null);
+                let marker = match (json_tree::has_children(value), self.expanded.contains(&row.path)) {
+                    (true, true) => "▾ ",
+                    (true, false) => "▸ ",
+                    (false, _) => "  ",
+                };
+                let label = match row.path.last() {
+                    None => Span::styled(self.name.clone(), Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)),
+                    Some(Segment::Key(key)) => Span::styled(key.clone(), Style::default().fg(colors::primary())),
+                    Some(Segment::Index(index)) => Span::styled(format!("[{}]", index), Style::default().fg(colors::muted())),
+                };
+                Line::from(vec![
+                    Span::raw("  ".repeat(row.depth)),
+                    Span::styled(marker, Style::default().fg(colors::secondary())),
+                    label,
+                    Span::styled(": ", Style::default().fg(colors::muted())),
+                    Span::styled(json_tree::summary(value), Style::default().fg(value_color(value))),
+                ])
+            })
+            .collect();
+        
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} ({})", self.name, self.format.label()))
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    fn render_preview(&self, f: &mut Frame, area: Rect) {
+        let lines: Vec<Line> = self.preview
+            .iter()
+            .flat_map(|(_, lines)| lines)
+            .map(|line| Line::from(Span::styled(line.clone(), Style::default().fg(colors::text()))))
+            .collect();
+        
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Value")
+                .border_style(Style::default().fg(colors::muted())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    /// The search popup over the tree
+    fn render_search(&mut self, f: &mut Frame) {
+        let Mode::Search { query, matches, list_state } = &mut self.mode else {
+            return;
+        };
+        let area = tui_common::centered_rect(f.area().width.saturating_mul(3) / 4, f.area().height.saturating_mul(3) / 4, f.area());
+        f.render_widget(Clear, area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(area);
+        
+        let input = Paragraph::new(query.line())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Search Keys")
+                .border_style(Style::default().fg(colors::secondary())));
+        f.render_widget(input, chunks[0]);
+        
+        let highlight = Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD);
+        let width = chunks[1].width.saturating_sub(4) as usize;
+        let items: Vec<Line> = matches
+            .iter()
+            .map(|(index, positions)| {
+                let (path, text) = &self.paths[*index];
+                let mut spans = tui_common::highlight_spans(text, positions, highlight);
+                let value = json_tree::get(&self.value, path).unwrap_or(&Value::Null);
+                let room = width.saturating_sub(text_width::width(text) + 2);
+                if room > 0 {
+                    spans.push(Span::styled(
+                        format!("  {}", text_width::truncate(&json_tree::summary(value), room)),
+                        Style::default().fg(colors::muted()),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Matches ({})", matches.len()))
+                .border_style(Style::default().fg(colors::primary())));
+        f.render_stateful_widget(list, chunks[1], list_state);
+    }
+    
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let help_text = if matches!(self.mode, Mode::Search { .. }) {
+            "Type to search • ↑/↓ Move • Enter Go to • Esc Cancel"
+        } else {
+            "j/k Move • Enter Toggle • h/l Collapse/Expand • / Search • y Copy path • Y Copy value • ? Help • q Quit"
+        };
+        
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the explorer
+    pub fn run(&mut self) -> io::Result<()> {
+        tui_common::run_app(self)
+    }
+    
+    /// Print every leaf with its path instead of running the TUI, like
+    /// `gron`; empty arrays and objects count as leaves
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        plain::print(format, json_tree::all_paths(&self.value).into_iter().filter_map(|path| {
+            let value = json_tree::get(&self.value, &path)?;
+            (!json_tree::has_children(value)).then(|| vec![
+                ("path", Value::from(json_tree::jq_path(&path))),
+                ("type", Value::from(json_tree::type_name(value))),
+                ("value", value.clone()),
+            ])
+        }))
+    }
+}
+
+impl App for JsonExplorer {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        if let AppEvent::Key(key) = event {
+            self.handle_key(key);
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        matches!(self.mode, Mode::Search { .. })
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+/// Run the explorer on `file` or standard input, or print its leaves in
+/// `output`
+pub fn run(file: Option<PathBuf>, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut explorer = JsonExplorer::new(file, settings)?;
+    match output {
+        Some(format) => explorer.print(format),
+        None => explorer.run(),
+    }
+}
@@ -1,63 +1,177 @@
 //! Process killer tool with interactive selection.
 
-use crate::tui_common::{self, colors};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crate::clipboard;
+use crate::config::Settings;
+use crate::notify::Notifications;
+use crate::palette::Context;
+use crate::plain::{self, Format};
+use crate::process_info::{self, CpuSampler, PortOwner, ProcessDetails, ProcessInfo, ProcessState, SystemSummary};
+use crate::session::{self, Resume, Session};
+use crate::text_width;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, NavKey, SelectableList, VimNav};
+use crate::users;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
-    Frame, Terminal,
+    widgets::{Block, BorderType, Borders, Gauge, ListState, Paragraph, Sparkline, Wrap},
+    Frame,
 };
+use regex::Regex;
+use serde_json::Value;
 use std::{
-    fmt,
-    io,
-    process::{Command, Stdio},
-    time::Duration,
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    time::{Duration, Instant},
 };
 
-#[derive(Debug, Clone)]
-pub struct Process {
-    pub pid: u32,
-    pub name: String,
-    pub cpu: f32,
-    pub memory: f32,
-    pub command: String,
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Filter processes"),
+    ("Backspace", "Delete a filter character"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Home / End", "First / last item"),
+    ("Enter", "Kill the process (Y to confirm)"),
+    ("Tab", "Show or hide details"),
+    ("Ctrl-N", "Renice"),
+    ("Ctrl-S / Ctrl-R", "Stop / resume"),
+    ("u", "Only my processes (normal mode)"),
+    ("z", "Only zombies (normal mode)"),
+    ("P", "Only processes with open ports (normal mode)"),
+    ("r", "Refresh (normal mode)"),
+    ("p", "Pause refreshing (normal mode)"),
+    ("y", "Copy the PID (normal mode)"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
+/// Number of samples kept per process for the detail pane sparklines
+const HISTORY_LEN: usize = 60;
+
+/// Recent resource samples for one process
+#[derive(Default)]
+struct UsageHistory {
+    /// CPU usage in whole percent
+    cpu: Vec<u64>,
+    /// Resident memory in KiB
+    memory: Vec<u64>,
 }
 
-impl fmt::Display for Process {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{:>8} {:>6.1}% {:>6.1}% {}",
-            self.pid, self.cpu, self.memory, self.name
-        )
+impl UsageHistory {
+    fn push(&mut self, process: &ProcessInfo) {
+        if self.cpu.len() == HISTORY_LEN {
+            self.cpu.remove(0);
+            self.memory.remove(0);
+        }
+        self.cpu.push(process.cpu.round() as u64);
+        self.memory.push(process.rss_bytes / 1024);
+    }
+}
+
+/// Owner filter cycled with `u`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum UserFilter {
+    All,
+    Mine,
+    Root,
+    Named(String),
+}
+
+impl UserFilter {
+    /// Next filter in the `u` cycle: my user → root → all
+    fn next(&self) -> Self {
+        match self {
+            UserFilter::All | UserFilter::Named(_) => UserFilter::Mine,
+            UserFilter::Mine => UserFilter::Root,
+            UserFilter::Root => UserFilter::All,
+        }
+    }
+    
+    /// User name this filter matches, if any
+    fn user_name(&self) -> Option<String> {
+        match self {
+            UserFilter::All => None,
+            UserFilter::Mine => Some(users::current_user_name()),
+            UserFilter::Root => Some("root".to_string()),
+            UserFilter::Named(name) => Some(name.clone()),
+        }
     }
 }
 
 pub struct ProcessKiller {
-    processes: Vec<Process>,
-    filtered_processes: Vec<Process>,
+    processes: Vec<ProcessInfo>,
+    filtered_processes: Vec<ProcessInfo>,
     list_state: ListState,
+    vim: VimNav,
     search_query: String,
     should_quit: bool,
-    status_message: String,
+    notifications: Notifications,
     confirmation_mode: bool,
-    selected_process: Option<Process>,
+    /// Confirmations still required before the kill is sent (2 for protected processes)
+    confirmations_left: u8,
+    selected_process: Option<ProcessInfo>,
+    cpu_sampler: CpuSampler,
+    refresh_interval: Duration,
+    last_refresh: Instant,
+    paused: bool,
+    show_details: bool,
+    details: Option<(u32, ProcessDetails)>,
+    /// Pending nice value while the renice prompt is open
+    nice_input: Option<String>,
+    user_filter: UserFilter,
+    state_filter: Option<ProcessState>,
+    ports_mode: bool,
+    ports: Vec<PortOwner>,
+    filtered_ports: Vec<(PortOwner, ProcessInfo)>,
+    history: HashMap<u32, UsageHistory>,
+    system: SystemSummary,
+    protected_names: Vec<String>,
+    /// PIDs of the shells and terminals `tt` is running under
+    session_pids: Vec<u32>,
+    settings: Settings,
 }
 
 impl ProcessKiller {
     /// Create a new process killer instance
-    pub fn new(filter: Option<String>) -> io::Result<Self> {
+    pub fn new(
+        filter: Option<String>,
+        interval: u64,
+        user: Option<String>,
+        state: Option<ProcessState>,
+        ports: bool,
+        settings: Settings,
+    ) -> io::Result<Self> {
         let mut killer = ProcessKiller {
             processes: Vec::new(),
             filtered_processes: Vec::new(),
             list_state: ListState::default(),
+            vim: VimNav::default(),
             search_query: filter.unwrap_or_default(),
             should_quit: false,
-            status_message: "Loading processes...".to_string(),
+            notifications: Notifications::with_status("Loading processes..."),
             confirmation_mode: false,
+            confirmations_left: 0,
             selected_process: None,
+            cpu_sampler: CpuSampler::default(),
+            refresh_interval: Duration::from_secs(interval.max(1)),
+            last_refresh: Instant::now(),
+            paused: interval == 0,
+            show_details: false,
+            details: None,
+            nice_input: None,
+            user_filter: user.map(UserFilter::Named).unwrap_or(UserFilter::All),
+            state_filter: state,
+            ports_mode: ports,
+            ports: Vec::new(),
+            filtered_ports: Vec::new(),
+            history: HashMap::new(),
+            system: SystemSummary::default(),
+            protected_names: process_info::protected_names(&settings.protected),
+            session_pids: Vec::new(),
+            settings,
         };
         
         killer.load_processes()?;
@@ -68,172 +182,261 @@ impl ProcessKiller {
     
     /// Load all running processes
     fn load_processes(&mut self) -> io::Result<()> {
-        self.processes.clear();
-        
-        // Use ps command to get process information
-        let output = Command::new("ps")
-            .args(&["aux", "--no-headers"])
-            .stdout(Stdio::piped())
-            .output()?;
+        let own_pid = std::process::id();
+        let processes = process_info::list_processes()?;
+        self.session_pids = process_info::ancestors(&processes, own_pid);
+        self.processes = processes
+            .into_iter()
+            .filter(|process| process.pid > 1 && process.pid != own_pid)
+            .collect();
         
-        if !output.status.success() {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Failed to execute ps command"
-            ));
+        if self.ports_mode {
+            self.ports = process_info::listening_ports();
         }
         
-        let ps_output = String::from_utf8_lossy(&output.stdout);
+        // Replace lifetime-average CPU with usage over the last interval
+        self.cpu_sampler.apply(&mut self.processes);
+        self.last_refresh = Instant::now();
+        self.system = process_info::system_summary();
         
-        for line in ps_output.lines() {
-            if let Some(process) = self.parse_ps_line(line) {
-                // Skip kernel threads and very short-lived processes
-                if !process.name.starts_with('[') && process.pid > 1 {
-                    self.processes.push(process);
-                }
-            }
+        // Record samples for the sparklines, dropping processes that exited
+        let live: HashMap<u32, &ProcessInfo> = self.processes.iter().map(|p| (p.pid, p)).collect();
+        self.history.retain(|pid, _| live.contains_key(pid));
+        for (pid, process) in live {
+            self.history.entry(pid).or_default().push(process);
         }
         
         // Sort by CPU usage (descending)
         self.processes.sort_by(|a, b| b.cpu.partial_cmp(&a.cpu).unwrap_or(std::cmp::Ordering::Equal));
         
-        self.status_message = format!("Found {} processes", self.processes.len());
+        self.notifications.set_status(format!("Found {} processes", self.processes.len()));
         Ok(())
     }
     
-    /// Parse a line from ps aux output
-    fn parse_ps_line(&self, line: &str) -> Option<Process> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        
-        if parts.len() < 11 {
-            return None;
-        }
-        
-        let pid: u32 = parts[1].parse().ok()?;
-        let cpu: f32 = parts[2].parse().ok()?;
-        let memory: f32 = parts[3].parse().ok()?;
+    /// Reload the process list while keeping the same PID selected
+    fn refresh(&mut self) -> io::Result<()> {
+        let selected_pid = self.current_process().map(|p| p.pid);
         
-        // Command is everything from column 11 onwards
-        let command = parts[10..].join(" ");
+        self.load_processes()?;
+        self.update_filter();
         
-        // Extract process name (first part of command, without path)
-        let name = command
-            .split_whitespace()
-            .next()
-            .unwrap_or(&command)
-            .split('/')
-            .last()
-            .unwrap_or(&command)
-            .to_string();
+        if let Some(pid) = selected_pid {
+            let index = if self.ports_mode {
+                self.filtered_ports.iter().position(|(port, _)| port.pid == pid)
+            } else {
+                self.filtered_processes.iter().position(|p| p.pid == pid)
+            };
+            if let Some(index) = index {
+                self.list_state.select(Some(index));
+                self.update_details();
+            }
+        }
+        Ok(())
+    }
+    
+    /// Refresh after a key or an action; a failure is reported rather than
+    /// ending the tool. Returns whether it succeeded
+    fn refresh_or_report(&mut self) -> bool {
+        match self.refresh() {
+            Ok(()) => true,
+            Err(e) => {
+                self.notifications.error(format!("Refresh failed: {}", e));
+                false
+            }
+        }
+    }
+    
+    /// Refresh once the interval has elapsed; returns whether it did
+    fn auto_refresh(&mut self) -> bool {
+        if self.paused || self.confirmation_mode || self.nice_input.is_some() || self.last_refresh.elapsed() < self.refresh_interval {
+            return false;
+        }
         
-        Some(Process {
-            pid,
-            name,
-            cpu,
-            memory,
-            command,
-        })
+        if !self.refresh_or_report() {
+            self.last_refresh = Instant::now();
+        }
+        true
     }
     
     /// Update filtered processes based on search query
     fn update_filter(&mut self) {
-        if self.search_query.is_empty() {
-            self.filtered_processes = self.processes.clone();
-        } else {
-            let query = self.search_query.to_lowercase();
-            self.filtered_processes = self.processes
+        let query = self.search_query.to_lowercase();
+        let user = self.user_filter.user_name();
+        
+        let visible = |process: &ProcessInfo| {
+            user.as_ref().is_none_or(|user| &process.user == user) &&
+            self.state_filter.is_none_or(|state| process.state == state)
+        };
+        let matches_query = |process: &ProcessInfo| {
+            query.is_empty() ||
+            process.name.to_lowercase().contains(&query) ||
+            process.command.to_lowercase().contains(&query) ||
+            process.user.to_lowercase().contains(&query) ||
+            process.pid.to_string().contains(&query)
+        };
+        
+        self.filtered_processes = self.processes
+            .iter()
+            .filter(|process| visible(process) && matches_query(process))
+            .cloned()
+            .collect();
+        
+        if self.ports_mode {
+            let mut ports: Vec<(PortOwner, ProcessInfo)> = self.ports
                 .iter()
-                .filter(|process| {
-                    process.name.to_lowercase().contains(&query) ||
-                    process.command.to_lowercase().contains(&query) ||
-                    process.pid.to_string().contains(&query)
+                .filter_map(|port| {
+                    let process = self.processes.iter().find(|p| p.pid == port.pid)?;
+                    let port_matches = port.socket.local_port.to_string().contains(&query);
+                    (visible(process) && (port_matches || matches_query(process)))
+                        .then(|| (port.clone(), process.clone()))
                 })
-                .cloned()
                 .collect();
+            ports.sort_by_key(|(port, _)| (port.socket.local_port, port.pid));
+            self.filtered_ports = ports;
         }
         
         // Reset selection
-        if !self.filtered_processes.is_empty() {
+        if self.visible_len() > 0 {
             self.list_state.select(Some(0));
         } else {
             self.list_state.select(None);
         }
+        self.update_details();
+    }
+    
+    /// Reload the detail pane for the selected process
+    fn update_details(&mut self) {
+        if !self.show_details {
+            return;
+        }
+        
+        self.details = self.current_process()
+            .map(|process| (process.pid, process_info::process_details(process.pid)));
+    }
+    
+    /// Number of rows in the active list (processes or ports)
+    fn visible_len(&self) -> usize {
+        if self.ports_mode {
+            self.filtered_ports.len()
+        } else {
+            self.filtered_processes.len()
+        }
     }
     
     /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                if self.confirmation_mode {
-                    self.handle_confirmation_input(key.code)?;
-                } else {
-                    self.handle_normal_input(key.code, key.modifiers)?;
-                }
-            }
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if self.confirmation_mode {
+            self.handle_confirmation_input(key.code)?;
+        } else if self.nice_input.is_some() {
+            self.handle_nice_input(key.code)?;
+        } else {
+            self.handle_normal_input(key.code, key.modifiers)?;
         }
         Ok(())
     }
     
     /// Handle input in normal mode
     fn handle_normal_input(&mut self, key_code: KeyCode, modifiers: KeyModifiers) -> io::Result<()> {
+        let len = self.visible_len();
+        match self.vim.filter_key(&mut self.list_state, key_code, modifiers, len, self.settings.page_size) {
+            NavKey::Moved => {
+                self.update_details();
+                return Ok(());
+            }
+            NavKey::Used => return Ok(()),
+            NavKey::Unused => {}
+        }
+        let letters = !self.vim.inserting() && !modifiers.contains(KeyModifiers::CONTROL);
+        
         match key_code {
-            KeyCode::Char('q') | KeyCode::Esc => {
+            KeyCode::Char('q') | KeyCode::Esc if !self.vim.inserting() => {
                 self.should_quit = true;
             }
-            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
-                self.should_quit = true;
+            KeyCode::Char('n') if modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(process) = self.current_process() {
+                    self.nice_input = Some(process.nice.to_string());
+                }
             }
-            KeyCode::Char('f') if modifiers.contains(KeyModifiers::CONTROL) => {
-                // Page down
-                if let Some(new_selection) = tui_common::handle_page_navigation(
-                    key_code, modifiers, self.list_state.selected(), self.filtered_processes.len(), 10
-                ) {
-                    self.list_state.select(Some(new_selection));
+            KeyCode::Char('s') if modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(process) = self.current_process().cloned() {
+                    // Stopping the session's own shell would freeze the terminal
+                    match self.protection_reason(&process) {
+                        Some(reason) => {
+                            self.notifications.error(format!("Refusing to stop {}: {}", process.pid, reason));
+                        }
+                        None => self.signal_process(process.pid, "STOP", "stopped"),
+                    }
                 }
             }
-            KeyCode::Char('b') if modifiers.contains(KeyModifiers::CONTROL) => {
-                // Page up
-                if let Some(new_selection) = tui_common::handle_page_navigation(
-                    key_code, modifiers, self.list_state.selected(), self.filtered_processes.len(), 10
-                ) {
-                    self.list_state.select(Some(new_selection));
+            KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(pid) = self.current_process().map(|p| p.pid) {
+                    self.signal_process(pid, "CONT", "resumed");
                 }
             }
-            KeyCode::Char('r') => {
-                self.load_processes()?;
+            KeyCode::Char('r') if letters => match self.refresh() {
+                Ok(()) => self.notifications.info("Processes refreshed"),
+                Err(e) => self.notifications.error(format!("Refresh failed: {}", e)),
+            },
+            KeyCode::Char('p') if letters => {
+                self.paused = !self.paused;
+                self.last_refresh = Instant::now();
+                self.notifications.info(if self.paused {
+                    "Auto-refresh paused".to_string()
+                } else {
+                    format!("Auto-refresh every {}s", self.refresh_interval.as_secs())
+                });
+            }
+            KeyCode::Char('u') if letters => {
+                self.user_filter = self.user_filter.next();
                 self.update_filter();
-                self.status_message = "Processes refreshed".to_string();
+                self.notifications.info(match self.user_filter.user_name() {
+                    Some(user) => format!("Showing processes owned by {}", user),
+                    None => "Showing processes of all users".to_string(),
+                });
             }
-            KeyCode::Up => {
-                if let Some(selected) = self.list_state.selected() {
-                    if selected > 0 {
-                        self.list_state.select(Some(selected - 1));
-                    }
+            KeyCode::Char('y') if letters => {
+                if let Some(pid) = self.current_process().map(|p| p.pid.to_string()) {
+                    clipboard::copy_and_notify(&mut self.notifications, &format!("PID {}", pid), &pid);
                 }
             }
-            KeyCode::Down => {
-                if let Some(selected) = self.list_state.selected() {
-                    if selected + 1 < self.filtered_processes.len() {
-                        self.list_state.select(Some(selected + 1));
-                    }
-                } else if !self.filtered_processes.is_empty() {
-                    self.list_state.select(Some(0));
-                }
+            KeyCode::Char('z') if letters => {
+                self.state_filter = if self.state_filter == Some(ProcessState::Zombie) {
+                    None
+                } else {
+                    Some(ProcessState::Zombie)
+                };
+                self.update_filter();
+                self.notifications.info(if self.state_filter.is_some() {
+                    "Showing zombie processes only"
+                } else {
+                    "Showing processes in any state"
+                });
+            }
+            KeyCode::Char('P') if letters => {
+                self.ports_mode = !self.ports_mode;
+                self.refresh_or_report();
+                self.notifications.info(if self.ports_mode {
+                    format!("{} listening sockets", self.filtered_ports.len())
+                } else {
+                    format!("Found {} processes", self.processes.len())
+                });
+            }
+            KeyCode::Tab => {
+                self.show_details = !self.show_details;
+                self.update_details();
             }
             KeyCode::Enter => {
-                if let Some(selected) = self.list_state.selected() {
-                    if let Some(process) = self.filtered_processes.get(selected) {
-                        self.selected_process = Some(process.clone());
-                        self.confirmation_mode = true;
-                        self.status_message = format!("Kill process {} ({})?", process.name, process.pid);
-                    }
+                if let Some(process) = self.current_process().cloned() {
+                    self.confirmations_left = if self.protection_reason(&process).is_some() { 2 } else { 1 };
+                    self.selected_process = Some(process);
+                    self.confirmation_mode = true;
                 }
             }
-            KeyCode::Char(c) => {
+            KeyCode::Char(c) if self.vim.inserting() => {
                 self.search_query.push(c);
                 self.update_filter();
             }
-            KeyCode::Backspace => {
+            KeyCode::Backspace if self.vim.inserting() => {
                 self.search_query.pop();
                 self.update_filter();
             }
@@ -242,12 +445,74 @@ impl ProcessKiller {
         Ok(())
     }
     
+    /// Handle input while the renice prompt is open
+    fn handle_nice_input(&mut self, key_code: KeyCode) -> io::Result<()> {
+        let Some(input) = self.nice_input.as_mut() else {
+            return Ok(());
+        };
+        
+        match key_code {
+            KeyCode::Enter => {
+                let input = self.nice_input.take().unwrap_or_default();
+                match input.trim().parse::<i32>() {
+                    Ok(nice) if (-20..=19).contains(&nice) => {
+                        if let Some(pid) = self.current_process().map(|p| p.pid) {
+                            self.renice_process(pid, nice);
+                        }
+                    }
+                    _ => {
+                        self.notifications.error(format!("Invalid nice value '{}' (expected -20 to 19)", input.trim()));
+                    }
+                }
+            }
+            KeyCode::Esc => {
+                self.nice_input = None;
+                self.notifications.info("Renice cancelled");
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() || (c == '-' && input.is_empty()) => {
+                input.push(c);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// Currently highlighted process
+    fn current_process(&self) -> Option<&ProcessInfo> {
+        let selected = self.list_state.selected()?;
+        if self.ports_mode {
+            self.filtered_ports.get(selected).map(|(_, process)| process)
+        } else {
+            self.filtered_processes.get(selected)
+        }
+    }
+    
+    /// Why a process should not be killed casually, if it is protected
+    fn protection_reason(&self, process: &ProcessInfo) -> Option<String> {
+        if self.session_pids.contains(&process.pid) {
+            Some("it is an ancestor of this terminal session".to_string())
+        } else if self.protected_names.contains(&process.name) {
+            Some(format!("'{}' is on the protected process list", process.name))
+        } else {
+            None
+        }
+    }
+    
     /// Handle input in confirmation mode
     fn handle_confirmation_input(&mut self, key_code: KeyCode) -> io::Result<()> {
         match key_code {
+            // Protected processes must be confirmed explicitly with Y, twice
+            KeyCode::Enter if self.confirmations_left > 1 => {}
             KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                self.confirmations_left = self.confirmations_left.saturating_sub(1);
+                if self.confirmations_left > 0 {
+                    return Ok(());
+                }
                 if let Some(process) = &self.selected_process {
-                    self.kill_process(process.pid)?;
+                    self.kill_process(process.pid);
                 }
                 self.confirmation_mode = false;
                 self.selected_process = None;
@@ -255,7 +520,7 @@ impl ProcessKiller {
             KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
                 self.confirmation_mode = false;
                 self.selected_process = None;
-                self.status_message = "Kill cancelled".to_string();
+                self.notifications.info("Kill cancelled");
             }
             _ => {}
         }
@@ -263,96 +528,202 @@ impl ProcessKiller {
     }
     
     /// Kill a process by PID
-    fn kill_process(&mut self, pid: u32) -> io::Result<()> {
-        let result = Command::new("kill")
-            .arg(pid.to_string())
-            .output();
-        
-        match result {
-            Ok(output) => {
-                if output.status.success() {
-                    self.status_message = format!("Process {} killed successfully", pid);
-                    // Refresh process list
-                    self.load_processes()?;
-                    self.update_filter();
-                } else {
-                    let error = String::from_utf8_lossy(&output.stderr);
-                    self.status_message = format!("Failed to kill process {}: {}", pid, error.trim());
-                }
+    fn kill_process(&mut self, pid: u32) {
+        match process_info::send_signal(pid, "TERM") {
+            Ok(()) => {
+                self.notifications.success(format!("Process {} killed successfully", pid));
+                self.refresh_or_report();
             }
             Err(e) => {
-                self.status_message = format!("Error killing process {}: {}", pid, e);
+                self.notifications.error(format!("Failed to kill process {}: {}", pid, e));
+            }
+        }
+    }
+    
+    /// Send a signal (e.g. `STOP`, `CONT`) to a process by PID
+    fn signal_process(&mut self, pid: u32, signal: &str, action: &str) {
+        match process_info::send_signal(pid, signal) {
+            Ok(()) => {
+                self.notifications.success(format!("Process {} {}", pid, action));
+                self.refresh_or_report();
+            }
+            Err(e) => {
+                self.notifications.error(format!("Failed to send SIG{} to {}: {}", signal, pid, e));
+            }
+        }
+    }
+    
+    /// Change a process's nice value
+    fn renice_process(&mut self, pid: u32, nice: i32) {
+        match process_info::renice(pid, nice) {
+            Ok(()) => {
+                self.notifications.success(format!("Process {} reniced to {}", pid, nice));
+                self.refresh_or_report();
+            }
+            Err(e) => {
+                self.notifications.error(format!("Failed to renice {}: {}", pid, e));
             }
         }
-        
-        Ok(())
     }
     
     /// Render the process killer interface
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
         if self.confirmation_mode {
-            self.render_confirmation(f);
+            self.render_confirmation(f, area);
         } else {
-            self.render_normal(f);
+            self.render_normal(f, area);
         }
     }
     
     /// Render normal mode
-    fn render_normal(&mut self, f: &mut Frame) {
+    fn render_normal(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(3), Constraint::Length(1)])
-            .split(f.area());
+            .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(1)])
+            .split(area);
         
-        // Process list
-        self.render_process_list(f, chunks[0]);
+        // System summary header
+        self.render_summary(f, chunks[0]);
+        
+        if self.show_details {
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .split(chunks[1]);
+            
+            self.render_process_list(f, panes[0]);
+            self.render_details(f, panes[1]);
+        } else {
+            self.render_process_list(f, chunks[1]);
+        }
         
         // Status bar
-        self.render_status_bar(f, chunks[1]);
+        self.render_status_bar(f, chunks[2]);
     }
     
-    /// Render confirmation dialog
-    fn render_confirmation(&self, f: &mut Frame) {
-        let area = f.area();
-        
-        // Create a centered popup
-        let popup_area = Rect {
-            x: area.width / 4,
-            y: area.height / 3,
-            width: area.width / 2,
-            height: 7,
+    /// Render the system CPU, memory and load average header
+    fn render_summary(&self, f: &mut Frame, area: Rect) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(40), Constraint::Percentage(20)])
+            .split(area);
+        
+        let gauge_color = |percent: f64| {
+            if percent > 80.0 {
+                colors::danger()
+            } else if percent > 50.0 {
+                colors::secondary()
+            } else {
+                colors::success()
+            }
+        };
+        
+        let cpu = self.cpu_sampler.system_usage().map(f64::from);
+        let cpu_gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title("CPU").border_style(Style::default().fg(colors::muted())))
+            .gauge_style(Style::default().fg(gauge_color(cpu.unwrap_or(0.0))))
+            .ratio(cpu.unwrap_or(0.0).clamp(0.0, 100.0) / 100.0)
+            .label(cpu.map(|c| format!("{:.1}%", c)).unwrap_or_else(|| "sampling...".to_string()));
+        f.render_widget(cpu_gauge, columns[0]);
+        
+        let total = self.system.memory_total;
+        let used = self.system.memory_available.map(|available| total.saturating_sub(available));
+        let memory_ratio = match used {
+            Some(used) if total > 0 => used as f64 / total as f64,
+            _ => 0.0,
         };
+        let memory_label = match used {
+            Some(used) => format!("{} / {}", tui_common::format_size(used), tui_common::format_size(total)),
+            None => format!("total {}", tui_common::format_size(total)),
+        };
+        let memory_gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title("Memory").border_style(Style::default().fg(colors::muted())))
+            .gauge_style(Style::default().fg(gauge_color(memory_ratio * 100.0)))
+            .ratio(memory_ratio.clamp(0.0, 1.0))
+            .label(memory_label);
+        f.render_widget(memory_gauge, columns[1]);
+        
+        let load = self.system.load
+            .map(|[one, five, fifteen]| format!("{:.2} {:.2} {:.2}", one, five, fifteen))
+            .unwrap_or_else(|| "n/a".to_string());
+        let load_paragraph = Paragraph::new(load)
+            .style(Style::default().fg(colors::text()))
+            .block(Block::default().borders(Borders::ALL).title("Load").border_style(Style::default().fg(colors::muted())));
+        f.render_widget(load_paragraph, columns[2]);
+    }
+    
+    /// Render confirmation dialog
+    fn render_confirmation(&self, f: &mut Frame, area: Rect) {
+        let protection = self.selected_process.as_ref().and_then(|p| self.protection_reason(p));
+        
+        // Centered, and cut to fit small terminals
+        let height = if protection.is_some() { 12 } else { 9 };
+        let popup_area = tui_common::centered_rect(area.width / 2, height, area);
         
         if let Some(process) = &self.selected_process {
-            let text = vec![
+            let mut text = vec![
                 Line::from(""),
                 Line::from(Span::styled(
                     format!("Kill process {} (PID {})?", process.name, process.pid),
-                    Style::default().fg(colors::PRIMARY).add_modifier(Modifier::BOLD)
+                    Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)
                 )),
                 Line::from(""),
                 Line::from(Span::styled(
                     format!("Command: {}", process.command),
-                    Style::default().fg(colors::SECONDARY)
+                    Style::default().fg(colors::secondary())
                 )),
-                Line::from(""),
                 Line::from(Span::styled(
-                    "[Y]es / [N]o",
-                    Style::default().fg(colors::TEXT).add_modifier(Modifier::BOLD)
+                    format!(
+                        "User: {} • State: {} • PPID: {} • RSS: {}",
+                        process.user,
+                        process.state.label(),
+                        process.ppid,
+                        tui_common::format_size(process.rss_bytes)
+                    ),
+                    Style::default().fg(colors::text())
                 )),
+                Line::from(""),
             ];
             
+            let mut block = Block::default()
+                .borders(Borders::ALL)
+                .title("Confirm Kill")
+                .border_style(Style::default().fg(colors::danger()));
+            
+            if let Some(reason) = &protection {
+                let warning = Style::default().fg(colors::text()).bg(colors::danger()).add_modifier(Modifier::BOLD);
+                text.insert(0, Line::from(Span::styled(format!(" ⚠ PROTECTED: {} ", reason), warning)));
+                text.push(Line::from(Span::styled(
+                    "Killing it may end your session or destabilise the system.",
+                    Style::default().fg(colors::danger()).add_modifier(Modifier::BOLD)
+                )));
+                text.push(Line::from(""));
+                text.push(Line::from(Span::styled(
+                    if self.confirmations_left > 1 {
+                        "Press Y twice to kill • [N]o"
+                    } else {
+                        "Press Y again to kill • [N]o"
+                    },
+                    Style::default().fg(colors::text()).add_modifier(Modifier::BOLD)
+                )));
+                block = block
+                    .title("⚠ Confirm Kill of Protected Process")
+                    .border_type(BorderType::Thick);
+            } else {
+                text.push(Line::from(Span::styled(
+                    "[Y]es / [N]o",
+                    Style::default().fg(colors::text()).add_modifier(Modifier::BOLD)
+                )));
+            }
+            
             let paragraph = Paragraph::new(text)
-                .block(Block::default()
-                    .borders(Borders::ALL)
-                    .title("Confirm Kill")
-                    .border_style(Style::default().fg(Color::Red)))
+                .block(block)
                 .wrap(Wrap { trim: true });
             
             // Clear background
             f.render_widget(
                 Block::default()
-                    .style(Style::default().bg(Color::Black)),
+                    .style(Style::default().bg(colors::background())),
                 area
             );
             
@@ -362,132 +733,553 @@ impl ProcessKiller {
     
     /// Render the process list
     fn render_process_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.filtered_processes
+        if self.ports_mode {
+            self.render_port_list(f, area);
+            return;
+        }
+        
+        let items: Vec<Line> = self.filtered_processes
             .iter()
             .map(|process| {
+                let pid_style = if self.protection_reason(process).is_some() {
+                    Style::default().fg(colors::danger()).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(colors::secondary())
+                };
                 let line = Line::from(vec![
                     Span::styled(
                         format!("{:>8}", process.pid),
-                        Style::default().fg(colors::SECONDARY)
+                        pid_style
                     ),
-                    Span::raw("  "),
+                    Span::raw(" "),
+                    Span::styled(
+                        text_width::pad(&process.user, 10),
+                        Style::default().fg(colors::text())
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        process.state.code().to_string(),
+                        match process.state {
+                            ProcessState::Running => Style::default().fg(colors::success()),
+                            ProcessState::Zombie => Style::default().fg(colors::danger()),
+                            ProcessState::Stopped => Style::default().fg(colors::secondary()),
+                            _ => Style::default().fg(colors::text()),
+                        }
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:>3}", process.nice),
+                        match process.nice {
+                            n if n < 0 => Style::default().fg(colors::danger()),
+                            n if n > 0 => Style::default().fg(colors::muted()),
+                            _ => Style::default().fg(colors::text()),
+                        }
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:>4}", process.threads.map(|t| t.to_string()).unwrap_or_default()),
+                        Style::default().fg(colors::text())
+                    ),
+                    Span::raw(" "),
                     Span::styled(
                         format!("{:>6.1}%", process.cpu),
                         if process.cpu > 50.0 {
-                            Style::default().fg(Color::Red)
+                            Style::default().fg(colors::danger())
                         } else if process.cpu > 10.0 {
-                            Style::default().fg(Color::Yellow)
+                            Style::default().fg(colors::secondary())
                         } else {
-                            Style::default().fg(colors::TEXT)
+                            Style::default().fg(colors::text())
                         }
                     ),
-                    Span::raw("  "),
+                    Span::raw(" "),
                     Span::styled(
                         format!("{:>6.1}%", process.memory),
                         if process.memory > 50.0 {
-                            Style::default().fg(Color::Red)
+                            Style::default().fg(colors::danger())
                         } else if process.memory > 10.0 {
-                            Style::default().fg(Color::Yellow)
+                            Style::default().fg(colors::secondary())
                         } else {
-                            Style::default().fg(colors::TEXT)
+                            Style::default().fg(colors::text())
                         }
                     ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:>8}", tui_common::format_size(process.rss_bytes)),
+                        Style::default().fg(colors::text())
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:>5}", process.elapsed_secs().map(process_info::format_elapsed).unwrap_or_default()),
+                        Style::default().fg(colors::text())
+                    ),
                     Span::raw("  "),
                     Span::styled(
                         process.name.clone(),
-                        Style::default().fg(colors::PRIMARY).add_modifier(Modifier::BOLD)
+                        Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        process.command.clone(),
+                        Style::default().fg(colors::muted())
                     ),
                 ]);
                 
-                ListItem::new(line)
+                line
             })
             .collect();
         
-        let title = if self.search_query.is_empty() {
-            format!("Processes ({}) - Sorted by CPU", self.filtered_processes.len())
+        let refresh_state = if self.paused {
+            "⏸ paused".to_string()
         } else {
-            format!("Processes ({}) - Filter: '{}'", self.filtered_processes.len(), self.search_query)
+            format!("↻ {}s", self.refresh_interval.as_secs())
         };
         
-        let header = ListItem::new(Line::from(vec![
-            Span::styled("     PID", Style::default().fg(colors::SECONDARY).add_modifier(Modifier::BOLD)),
-            Span::styled("    CPU", Style::default().fg(colors::SECONDARY).add_modifier(Modifier::BOLD)),
-            Span::styled("    MEM", Style::default().fg(colors::SECONDARY).add_modifier(Modifier::BOLD)),
-            Span::styled("  NAME", Style::default().fg(colors::SECONDARY).add_modifier(Modifier::BOLD)),
-        ]));
+        let mut filters = Vec::new();
+        if let Some(user) = self.user_filter.user_name() {
+            filters.push(format!("user: {}", user));
+        }
+        if let Some(state) = self.state_filter {
+            filters.push(format!("state: {}", state.label()));
+        }
+        if !self.search_query.is_empty() {
+            filters.push(format!("Filter: '{}'", self.search_query));
+        }
         
-        let mut all_items = vec![header];
-        all_items.extend(items);
+        let title = if filters.is_empty() {
+            format!("Processes ({}) - Sorted by CPU - {}", self.filtered_processes.len(), refresh_state)
+        } else {
+            format!("Processes ({}) - {} - {}", self.filtered_processes.len(), filters.join(", "), refresh_state)
+        };
         
-        let list = List::new(all_items)
+        let header_style = Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD);
+        let header = Line::from(Span::styled(
+            format!(
+                "{:>8} {:<10} {} {:>3} {:>4} {:>7} {:>7} {:>8} {:>5}  {}",
+                "PID", "USER", "S", "NI", "THR", "CPU", "MEM", "RSS", "TIME", "COMMAND"
+            ),
+            header_style,
+        ));
+        
+        let list = SelectableList::new(items)
+            .header(header)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
+                .border_style(Style::default().fg(colors::primary())));
         
-        // Adjust list state to account for header
-        let mut adjusted_state = self.list_state.clone();
-        if let Some(selected) = adjusted_state.selected() {
-            adjusted_state.select(Some(selected + 1));
-        }
-        
-        f.render_stateful_widget(list, area, &mut adjusted_state);
+        f.render_stateful_widget(list, area, &mut self.list_state);
     }
     
-    /// Render status bar
-    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
-        let help_text = if self.confirmation_mode {
-            "Y/Enter Confirm • N/Esc Cancel"
+    /// Render the listening ports view
+    fn render_port_list(&mut self, f: &mut Frame, area: Rect) {
+        let items: Vec<Line> = self.filtered_ports
+            .iter()
+            .map(|(port, process)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<5}", port.socket.protocol.to_string()),
+                        Style::default().fg(colors::text())
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:>6}", port.socket.local_port),
+                        Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD)
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:<24.24}", port.socket.local_addr),
+                        Style::default().fg(colors::text())
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:>8}", process.pid),
+                        Style::default().fg(colors::secondary())
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        text_width::pad(&process.user, 10),
+                        Style::default().fg(colors::text())
+                    ),
+                    Span::raw("  "),
+                    Span::styled(
+                        process.name.clone(),
+                        Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        process.command.clone(),
+                        Style::default().fg(colors::muted())
+                    ),
+                ])
+            })
+            .collect();
+        
+        let title = if self.search_query.is_empty() {
+            format!("Listening Ports ({})", self.filtered_ports.len())
         } else {
-            "Type to filter • ↑↓ Navigate • Enter Kill • R Refresh • Esc Quit"
+            format!("Listening Ports ({}) - Filter: '{}'", self.filtered_ports.len(), self.search_query)
         };
         
-        let status_text = if !self.status_message.is_empty() {
-            format!("{} | {}", self.status_message, help_text)
-        } else {
-            help_text.to_string()
+        let header = Line::from(Span::styled(
+            format!(
+                "{:<5} {:>6} {:<24} {:>8} {:<10}  {}",
+                "PROTO", "PORT", "ADDRESS", "PID", "USER", "COMMAND"
+            ),
+            Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD),
+        ));
+        
+        let list = SelectableList::new(items)
+            .header(header)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    /// Render the process detail pane
+    fn render_details(&self, f: &mut Frame, area: Rect) {
+        let selected = self.current_process();
+        let (Some(process), Some((_, details))) = (selected, &self.details) else {
+            let placeholder = Paragraph::new("No process selected")
+                .block(Block::default()
+                    .borders(Borders::ALL)
+                    .title("Details")
+                    .border_style(Style::default().fg(colors::secondary())));
+            f.render_widget(placeholder, area);
+            return;
         };
         
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Details: {} ({})", process.name, process.pid))
+            .border_style(Style::default().fg(colors::secondary()));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        
+        let sections = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(4), Constraint::Min(0)])
+            .split(inner);
+        self.render_sparklines(f, sections[0], process);
+        
+        let heading = |text: &str| Line::from(Span::styled(
+            text.to_string(),
+            Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)
+        ));
+        let unavailable = || Line::from(Span::styled("  [permission denied]", Style::default().fg(colors::muted())));
+        
+        let mut lines = vec![
+            heading("Command line"),
+            Line::from(format!("  {}", process.command)),
+            Line::from(""),
+            heading("Process"),
+            Line::from(format!(
+                "  User: {}   PPID: {}   State: {}   Nice: {}",
+                process.user, process.ppid, process.state.label(), process.nice
+            )),
+            Line::from(format!(
+                "  Working dir: {}",
+                details.cwd.as_deref().unwrap_or("[permission denied]")
+            )),
+            Line::from(format!(
+                "  Open files: {}",
+                details.open_files.map(|n| n.to_string()).unwrap_or_else(|| "[permission denied]".to_string())
+            )),
+            Line::from(""),
+            heading("Memory"),
+        ];
         
-        f.render_widget(paragraph, area);
+        if details.memory.is_empty() {
+            lines.push(unavailable());
+        }
+        for (label, bytes) in &details.memory {
+            lines.push(Line::from(format!("  {:<12} {:>10}", label, tui_common::format_size(*bytes))));
+        }
+        
+        lines.push(Line::from(""));
+        lines.push(heading("Sockets"));
+        if details.sockets.is_empty() {
+            lines.push(Line::from(Span::styled("  none", Style::default().fg(colors::muted()))));
+        }
+        for socket in &details.sockets {
+            let remote = if socket.is_listening() {
+                String::new()
+            } else {
+                format!(" → {}:{}", socket.remote_addr, socket.remote_port)
+            };
+            lines.push(Line::from(format!(
+                "  {} {}:{} {}{}",
+                socket.protocol, socket.local_addr, socket.local_port, socket.state, remote
+            )));
+        }
+        
+        lines.push(Line::from(""));
+        lines.push(heading("Environment"));
+        match &details.environment {
+            Some(vars) if vars.is_empty() => {
+                lines.push(Line::from(Span::styled("  (empty)", Style::default().fg(colors::muted()))));
+            }
+            Some(vars) => {
+                lines.extend(vars.iter().map(|var| Line::from(format!("  {}", var))));
+            }
+            None => lines.push(unavailable()),
+        }
+        
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: false });
+        
+        f.render_widget(paragraph, sections[1]);
     }
     
-    /// Run the process killer application
-    pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
+    /// Render CPU and memory history sparklines for a process
+    fn render_sparklines(&self, f: &mut Frame, area: Rect, process: &ProcessInfo) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
         
-        let result = self.run_app(&mut terminal);
+        let empty = UsageHistory::default();
+        let history = self.history.get(&process.pid).unwrap_or(&empty);
+        
+        // Show the most recent samples that fit in the available width
+        let tail = |samples: &[u64], width: u16| -> Vec<u64> {
+            let width = width.saturating_sub(2) as usize;
+            samples[samples.len().saturating_sub(width)..].to_vec()
+        };
         
-        tui_common::restore_terminal(&mut terminal)?;
+        let cpu = Sparkline::default()
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("CPU {:.1}%", process.cpu))
+                .border_style(Style::default().fg(colors::muted())))
+            .data(tail(&history.cpu, columns[0].width))
+            .max(100.max(history.cpu.iter().copied().max().unwrap_or(0)))
+            .style(Style::default().fg(colors::success()));
+        f.render_widget(cpu, columns[0]);
         
-        result
+        let memory = Sparkline::default()
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("RSS {}", tui_common::format_size(process.rss_bytes)))
+                .border_style(Style::default().fg(colors::muted())))
+            .data(tail(&history.memory, columns[1].width))
+            .style(Style::default().fg(colors::primary()));
+        f.render_widget(memory, columns[1]);
     }
     
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            
-            self.handle_input()?;
-            
-            if self.should_quit {
-                break;
-            }
+    /// Render status bar
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        if let Some(input) = &self.nice_input {
+            let name = self.current_process().map(|p| format!("{} ({})", p.name, p.pid)).unwrap_or_default();
+            let prompt = Paragraph::new(format!(
+                "Renice {} | New nice value (-20 to 19): {}█ | Enter Apply • Esc Cancel",
+                name, input
+            ))
+            .style(Style::default().bg(colors::secondary()).fg(colors::background()));
+            f.render_widget(prompt, area);
+            return;
         }
         
-        Ok(())
+        let help_text = if self.confirmation_mode {
+            "Y/Enter Confirm • N/Esc Cancel"
+        } else if self.vim.inserting() {
+            "Type to filter • Enter Kill • Tab Details • Esc Normal mode • F1 Help"
+        } else {
+            "j/k Move • / Filter • Enter Kill • y Copy PID • r Refresh • ? Help • q Quit"
+        };
+        
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the process killer application, from where it was left in this
+    /// directory
+    pub fn run(&mut self) -> io::Result<()> {
+        session::resume(self, "kill", tui_common::run_app)
+    }
+    
+    /// Print the filtered processes, or their listening ports in ports mode,
+    /// instead of running the TUI
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        if self.ports_mode {
+            return plain::print(format, self.filtered_ports.iter().map(|(port, process)| vec![
+                ("protocol", Value::from(port.socket.protocol.to_string())),
+                ("address", Value::from(port.socket.local_addr.as_str())),
+                ("port", Value::from(port.socket.local_port)),
+                ("pid", Value::from(port.pid)),
+                ("name", Value::from(process.name.as_str())),
+            ]));
+        }
+        plain::print(format, self.filtered_processes.iter().map(|process| vec![
+            ("pid", Value::from(process.pid)),
+            ("ppid", Value::from(process.ppid)),
+            ("user", Value::from(process.user.as_str())),
+            ("state", Value::from(process.state.label())),
+            ("cpu", plain::tenths(f64::from(process.cpu))),
+            ("memory", plain::tenths(f64::from(process.memory))),
+            ("rss_bytes", Value::from(process.rss_bytes)),
+            ("name", Value::from(process.name.as_str())),
+            ("command", Value::from(process.command.as_str())),
+        ]))
     }
 }
 
-/// Run the process killer tool
-pub fn run(filter: Option<String>) -> io::Result<()> {
-    let mut killer = ProcessKiller::new(filter)?;
-    killer.run()
-}
\ No newline at end of file
+impl App for ProcessKiller {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick if self.auto_refresh() => return Ok(Action::Redraw),
+            AppEvent::Tick => {}
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        self.nice_input.is_some() || (self.vim.inserting() && !self.confirmation_mode)
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn context(&self) -> Context {
+        Context {
+            process: self.current_process().map(|process| process.name.clone()),
+            ..Context::default()
+        }
+    }
+}
+
+impl Resume for ProcessKiller {
+    /// The filter, and the selected process by name since its PID will not
+    /// come back
+    fn session(&self) -> Session {
+        Session {
+            query: Some(self.search_query.clone()),
+            selected: self.current_process().map(|process| process.name.clone()),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query.filter(|_| self.search_query.is_empty()) {
+            self.search_query = query;
+            self.update_filter();
+        }
+        let Some(name) = session.selected else {
+            return;
+        };
+        let position = if self.ports_mode {
+            self.filtered_ports.iter().position(|(_, process)| process.name == name)
+        } else {
+            self.filtered_processes.iter().position(|process| process.name == name)
+        };
+        if let Some(position) = position {
+            self.list_state.select(Some(position));
+            self.update_details();
+        }
+    }
+}
+
+/// Run the process killer tool, or print the processes in `output`
+pub fn run(
+    filter: Option<String>,
+    interval: u64,
+    user: Option<String>,
+    state: Option<ProcessState>,
+    ports: bool,
+    output: Option<Format>,
+    settings: Settings,
+) -> io::Result<()> {
+    let mut killer = ProcessKiller::new(filter, interval, user, state, ports, settings)?;
+    match output {
+        Some(format) => killer.print(format),
+        None => killer.run(),
+    }
+}
+
+/// Signal every process whose command line matches `pattern`, without the TUI.
+///
+/// Matching processes are printed first; unless `yes` is set a single
+/// `[y/N]` confirmation is read from stdin before any signal is sent.
+pub fn kill_matching(
+    pattern: &str,
+    signal: &str,
+    yes: bool,
+    user: Option<String>,
+    state: Option<ProcessState>,
+    settings: Settings,
+) -> io::Result<()> {
+    let regex = Regex::new(pattern)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid pattern: {}", e)))?;
+    let signal = process_info::normalize_signal(signal).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown signal '{}'", signal))
+    })?;
+    
+    let processes = process_info::list_processes()?;
+    
+    // Never signal tt itself or the shells that launched it, whose command
+    // lines usually contain the pattern too
+    let ancestors = process_info::ancestors(&processes, std::process::id());
+    let protected_names = process_info::protected_names(&settings.protected);
+    
+    let (protected, matches): (Vec<ProcessInfo>, Vec<ProcessInfo>) = processes
+        .into_iter()
+        .filter(|process| process.pid > 1 && !ancestors.contains(&process.pid))
+        .filter(|process| user.as_ref().is_none_or(|user| &process.user == user))
+        .filter(|process| state.is_none_or(|state| process.state == state))
+        .filter(|process| regex.is_match(&process.command))
+        .partition(|process| protected_names.contains(&process.name));
+    
+    for process in &protected {
+        eprintln!("Skipping protected process {} ({})", process.name, process.pid);
+    }
+    
+    if matches.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No processes match '{}'", pattern),
+        ));
+    }
+    
+    for process in &matches {
+        println!("{:>8} {:<10} {}", process.pid, process.user, process.command);
+    }
+    
+    if !yes {
+        eprint!("Send SIG{} to {} process(es)? [y/N] ", signal, matches.len());
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            eprintln!("Aborted");
+            return Ok(());
+        }
+    }
+    
+    let mut failed = 0;
+    for process in &matches {
+        if let Err(e) = process_info::send_signal(process.pid, &signal) {
+            failed += 1;
+            eprintln!("Failed to signal {}: {}", process.pid, e);
+        }
+    }
+    
+    eprintln!("Sent SIG{} to {} process(es)", signal, matches.len() - failed);
+    if failed > 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} process(es) could not be signalled", failed),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,234 @@
+//! Main menu shown by a bare `tt`: every tool with its icon and description,
+//! filtered as you type. The chosen tool runs in place and the menu comes
+//! back when it exits.
+
+use crate::config::Settings;
+use crate::fuzzy;
+use crate::notify::Notifications;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, NavKey, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph},
+    Frame,
+};
+use std::io;
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Filter tools"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("Enter", "Start the tool; quitting it comes back here"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
+/// A menu entry: the command line that starts the tool
+pub struct Tool {
+    pub icon: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Arguments after `tt`
+    pub args: &'static [&'static str],
+}
+
+/// The interactive tools, in menu order; `z`, `init` and `completions`
+/// print for the shell and are left out, as are `log`, `json`, `hex` and
+/// `diff`, which need files
+pub const TOOLS: &[Tool] = &[
+    Tool { icon: "🔍", name: "find", description: "Fuzzy file finder with live preview", args: &["find"] },
+    Tool { icon: "⚡", name: "search", description: "Search file contents with ripgrep", args: &["search"] },
+    Tool { icon: "📝", name: "todo", description: "TODO and FIXME comments in this tree", args: &["todo"] },
+    Tool { icon: "📁", name: "dir", description: "File explorer with previews", args: &["dir"] },
+    Tool { icon: "🗑️", name: "trash", description: "Restore or purge deleted files", args: &["dir", "--trash"] },
+    Tool { icon: "💾", name: "du", description: "Disk usage by directory", args: &["du"] },
+    Tool { icon: "📂", name: "recent", description: "Recently used files", args: &["recent"] },
+    Tool { icon: "📊", name: "kill", description: "Process manager and killer", args: &["kill"] },
+    Tool { icon: "🖥️", name: "sys", description: "CPU, memory, disk and network at a glance", args: &["sys"] },
+    Tool { icon: "🌐", name: "net", description: "Listening ports and connections", args: &["net"] },
+    Tool { icon: "⚙️", name: "systemd", description: "Start, stop and inspect services", args: &["systemd"] },
+    Tool { icon: "📦", name: "pkg", description: "Search, install and remove packages", args: &["pkg"] },
+    Tool { icon: "🌳", name: "git log", description: "Commit history with diffs", args: &["git", "log"] },
+    Tool { icon: "🌿", name: "git branch", description: "Switch branches", args: &["git", "branch"] },
+    Tool { icon: "🔀", name: "git diff", description: "Browse the working tree diff", args: &["git", "diff"] },
+    Tool { icon: "🗂️", name: "workspace", description: "Explorer, search and git log as tabs", args: &["workspace"] },
+    Tool { icon: "📚", name: "hist", description: "Command history browser and runner", args: &["hist"] },
+    Tool { icon: "⭐", name: "snip", description: "Saved command snippets", args: &["snip"] },
+    Tool { icon: "🌍", name: "env", description: "Environment variables", args: &["env"] },
+    Tool { icon: "📖", name: "man", description: "Manual pages", args: &["man"] },
+];
+
+/// Columns the tool names are padded to
+const NAME_WIDTH: usize = 12;
+
+pub struct Launcher {
+    query: TextInput,
+    /// Matching tools as indices into [`TOOLS`] with the matched char
+    /// positions in [`Launcher::label`]
+    matches: Vec<(usize, Vec<usize>)>,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    chosen: Option<&'static Tool>,
+    settings: Settings,
+}
+
+impl Launcher {
+    pub fn new(settings: Settings) -> Self {
+        let mut launcher = Launcher {
+            query: TextInput::new(""),
+            matches: Vec::new(),
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::new(),
+            chosen: None,
+            settings,
+        };
+        launcher.apply_query();
+        launcher
+    }
+    
+    /// Name and description as one line, which the query is matched against
+    fn label(tool: &Tool) -> String {
+        format!("{:<width$}{}", tool.name, tool.description, width = NAME_WIDTH)
+    }
+    
+    /// Recompute the matching tools, best first
+    fn apply_query(&mut self) {
+        let query = self.query.value();
+        let mut matches: Vec<(i64, usize, Vec<usize>)> = TOOLS
+            .iter()
+            .enumerate()
+            .filter_map(|(index, tool)| {
+                fuzzy::match_positions(query, &Self::label(tool)).map(|(score, positions)| (score, index, positions))
+            })
+            .collect();
+        // Stable, so an empty query keeps the menu order
+        matches.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+        self.matches = matches.into_iter().map(|(_, index, positions)| (index, positions)).collect();
+        self.list_state.select(if self.matches.is_empty() { None } else { Some(0) });
+    }
+    
+    /// Show the menu until a tool is chosen or the user quits; returns the
+    /// chosen tool
+    pub fn choose(&mut self) -> io::Result<Option<&'static Tool>> {
+        self.chosen = None;
+        self.should_quit = false;
+        tui_common::run_app(self)?;
+        Ok(self.chosen.take())
+    }
+    
+    /// Report that the last tool failed, on the next showing of the menu
+    pub fn report_error(&mut self, tool: &Tool, error: &io::Error) {
+        self.notifications.error(format!("tt {}: {}", tool.name, error));
+    }
+    
+    fn handle_key(&mut self, key: KeyEvent) {
+        match self.vim.filter_key(&mut self.list_state, key.code, key.modifiers, self.matches.len(), self.settings.page_size) {
+            NavKey::Moved | NavKey::Used => return,
+            NavKey::Unused => {}
+        }
+        
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Enter => {
+                if let Some((index, _)) = self.list_state.selected().and_then(|selected| self.matches.get(selected)) {
+                    self.chosen = Some(&TOOLS[*index]);
+                    self.should_quit = true;
+                }
+            }
+            code if !key.modifiers.contains(KeyModifiers::CONTROL) && self.vim.inserting() && self.query.handle_key(code) => {
+                self.apply_query();
+            }
+            _ => {}
+        }
+    }
+    
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        
+        self.render_query(f, chunks[0]);
+        self.render_tool_list(f, chunks[1]);
+        self.render_status_bar(f, area);
+    }
+    
+    fn render_query(&self, f: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.query.line())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Terminal Tools")
+                .border_style(Style::default().fg(colors::secondary())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    /// Render the tools, highlighting the characters the query matched
+    fn render_tool_list(&mut self, f: &mut Frame, area: Rect) {
+        let highlight = Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD);
+        let items: Vec<Line> = self.matches
+            .iter()
+            .map(|(index, positions)| {
+                let tool = &TOOLS[*index];
+                let mut spans = vec![Span::raw(format!("{}  ", tool.icon))];
+                spans.extend(tui_common::highlight_spans(&Self::label(tool), positions, highlight));
+                spans.push(Span::styled(format!("  tt {}", tool.args.join(" ")), Style::default().fg(colors::muted())));
+                Line::from(spans)
+            })
+            .collect();
+        
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Tools ({})", self.matches.len()))
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
+        
+        let help_text = if self.vim.inserting() {
+            "Type to filter • Enter Start • F1 Help • Esc Normal mode"
+        } else {
+            "j/k Move • / Filter • Enter Start • ? Help • q Quit"
+        };
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+}
+
+impl App for Launcher {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        if let AppEvent::Key(key) = event {
+            self.handle_key(key);
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        self.vim.inserting()
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
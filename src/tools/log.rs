@@ -0,0 +1,595 @@
+//! Log viewer with live follow, level colors, regex filters and jump-to-time.
+
+use crate::clipboard;
+use crate::config::Settings;
+use crate::log_file::{self, LogLine, LogSource, ReadEvent, Severity};
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, SelectableList, TextInput, VimNav};
+use crate::{text_width, time_util};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState},
+    Frame,
+};
+use regex::Regex;
+use serde_json::Value;
+use std::{
+    io::{self, IsTerminal},
+    path::PathBuf,
+    sync::mpsc::Receiver,
+};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("Home / End, gg / G", "First / last line; on the last line new lines are followed"),
+    ("f", "Follow new lines, or stop"),
+    ("Space", "Pause, or resume with the lines read meanwhile"),
+    ("/", "Only show lines matching a regex"),
+    ("x", "Hide lines matching a regex"),
+    ("l", "Minimum level: all, debug, info, warn, error"),
+    ("c", "Clear the filters"),
+    ("e / E", "Next / previous warning or error"),
+    ("t", "Jump to a time"),
+    ("Enter", "Show the whole line"),
+    ("y", "Copy the line"),
+    ("Esc / q", "Quit"),
+];
+
+/// Lines kept in memory; the oldest go beyond this
+const MAX_LINES: usize = 200_000;
+
+enum Mode {
+    Normal,
+    /// Typing the regex lines must match
+    Include(TextInput),
+    /// Typing the regex of lines to hide
+    Exclude(TextInput),
+    /// Typing a time to jump to
+    Jump(TextInput),
+    /// The selected line in full
+    Line,
+}
+
+pub struct LogViewer {
+    source: LogSource,
+    lines: Vec<LogLine>,
+    /// Indices into `lines` that pass the filters
+    visible: Vec<usize>,
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+    min_level: Option<Severity>,
+    /// Lines still to come; None once the input ended
+    reader: Option<Receiver<ReadEvent>>,
+    /// Keep the last line selected as new ones arrive
+    follow: bool,
+    /// Lines read while paused
+    held: Option<Vec<LogLine>>,
+    mode: Mode,
+    /// Selection within `visible`
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    settings: Settings,
+}
+
+/// A regex given on the command line, or an InvalidInput error naming the flag
+fn compile(pattern: Option<String>, flag: &str) -> io::Result<Option<Regex>> {
+    pattern
+        .map(|pattern| Regex::new(&pattern))
+        .transpose()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid --{} regex: {}", flag, e)))
+}
+
+impl LogViewer {
+    /// Start reading `source`; with `follow` a file is watched for new lines
+    /// and the view starts at the end
+    pub fn new(
+        source: LogSource,
+        follow: bool,
+        include: Option<String>,
+        exclude: Option<String>,
+        min_level: Option<Severity>,
+        settings: Settings,
+    ) -> io::Result<Self> {
+        if matches!(source, LogSource::Stdin) && io::stdin().is_terminal() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no log to show: give a file or pipe one in (`journalctl -f | tt log`)",
+            ));
+        }
+        Ok(LogViewer {
+            include: compile(include, "include")?,
+            exclude: compile(exclude, "exclude")?,
+            min_level,
+            reader: Some(log_file::spawn_reader(source.clone(), follow)),
+            source,
+            lines: Vec::new(),
+            visible: Vec::new(),
+            follow,
+            held: None,
+            mode: Mode::Normal,
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::with_status("Reading..."),
+            settings,
+        })
+    }
+    
+    /// Whether `line` passes the level, include and exclude filters
+    fn shows(&self, line: &LogLine) -> bool {
+        self.min_level.is_none_or(|min| line.severity.is_some_and(|severity| severity >= min))
+            && self.include.as_ref().is_none_or(|include| include.is_match(&line.text))
+            && !self.exclude.as_ref().is_some_and(|exclude| exclude.is_match(&line.text))
+    }
+    
+    fn selected_line(&self) -> Option<&LogLine> {
+        self.list_state.selected().and_then(|row| self.visible.get(row)).map(|index| &self.lines[*index])
+    }
+    
+    fn select_last(&mut self) {
+        self.list_state.select(self.visible.len().checked_sub(1));
+    }
+    
+    /// Add lines read from the log, dropping the oldest beyond [`MAX_LINES`]
+    fn append(&mut self, lines: Vec<LogLine>) {
+        let start = self.lines.len();
+        self.lines.extend(lines);
+        let added: Vec<usize> = (start..self.lines.len()).filter(|index| self.shows(&self.lines[*index])).collect();
+        self.visible.extend(added);
+        
+        if self.lines.len() > MAX_LINES {
+            // A tenth more than needed, so this doesn't happen on every read
+            let dropped = self.lines.len() - MAX_LINES + MAX_LINES / 10;
+            self.lines.drain(..dropped);
+            let hidden = self.visible.partition_point(|index| *index < dropped);
+            self.visible.drain(..hidden);
+            self.visible.iter_mut().for_each(|index| *index -= dropped);
+            let row = self.list_state.selected().map(|row| row.saturating_sub(hidden));
+            self.list_state.select(row.filter(|_| !self.visible.is_empty()));
+        }
+        
+        if self.follow {
+            self.select_last();
+        } else if self.list_state.selected().is_none() && !self.visible.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+    
+    /// Apply changed filters, keeping the selected line or the one after it
+    fn refilter(&mut self) {
+        let selected = self.list_state.selected().and_then(|row| self.visible.get(row).copied());
+        self.visible = (0..self.lines.len()).filter(|index| self.shows(&self.lines[*index])).collect();
+        if self.follow || selected.is_none() {
+            self.select_last();
+        } else if let Some(selected) = selected {
+            let row = self.visible.partition_point(|index| *index < selected);
+            self.list_state.select(Some(row.min(self.visible.len().saturating_sub(1))).filter(|_| !self.visible.is_empty()));
+        }
+        self.update_status();
+    }
+    
+    /// Take what the reader sent; returns whether anything changed
+    fn poll_reader(&mut self) -> bool {
+        let Some(reader) = &self.reader else {
+            return false;
+        };
+        let events: Vec<ReadEvent> = reader.try_iter().collect();
+        if events.is_empty() {
+            return false;
+        }
+        for event in events {
+            match event {
+                ReadEvent::Lines(lines) => match &mut self.held {
+                    Some(held) => held.extend(lines),
+                    None => self.append(lines),
+                },
+                ReadEvent::Skipped(bytes) => {
+                    self.notifications.info(format!("Skipped the first {} of the file", tui_common::format_size(bytes)));
+                }
+                ReadEvent::Restarted => {
+                    self.notifications.info("The file was truncated or replaced; reading it from the start");
+                }
+                ReadEvent::Finished => self.reader = None,
+                ReadEvent::Failed(message) => {
+                    self.notifications.error(message);
+                    self.reader = None;
+                }
+            }
+        }
+        self.update_status();
+        true
+    }
+    
+    /// Describe following, pausing and the filters on the status line
+    fn update_status(&mut self) {
+        let mut parts = vec![match (&self.held, &self.reader) {
+            (Some(held), _) => format!("Paused, {} new lines waiting", held.len()),
+            (None, None) => format!("End of {}", self.source.name()),
+            (None, Some(_)) if self.follow => "Following".to_string(),
+            (None, Some(_)) => "Not following (f or G to follow)".to_string(),
+        }];
+        if let Some(level) = self.min_level {
+            parts.push(format!("{} and above", level.label()));
+        }
+        if let Some(include) = &self.include {
+            parts.push(format!("matching /{}/", include));
+        }
+        if let Some(exclude) = &self.exclude {
+            parts.push(format!("hiding /{}/", exclude));
+        }
+        self.notifications.set_status(parts.join(" • "));
+    }
+    
+    fn toggle_pause(&mut self) {
+        match self.held.take() {
+            Some(held) => {
+                let count = held.len();
+                self.append(held);
+                self.notifications.info(format!("Resumed with {} new lines", count));
+            }
+            None => self.held = Some(Vec::new()),
+        }
+        self.update_status();
+    }
+    
+    fn toggle_follow(&mut self) {
+        self.follow = !self.follow;
+        if self.follow {
+            self.select_last();
+        }
+        self.update_status();
+    }
+    
+    /// Show only lines at the next level up, wrapping round to all levels
+    fn cycle_level(&mut self) {
+        self.min_level = match self.min_level {
+            None => Some(Severity::Debug),
+            Some(Severity::Trace) | Some(Severity::Debug) => Some(Severity::Info),
+            Some(Severity::Info) => Some(Severity::Warn),
+            Some(Severity::Warn) => Some(Severity::Error),
+            Some(Severity::Error) => None,
+        };
+        self.refilter();
+    }
+    
+    fn clear_filters(&mut self) {
+        self.include = None;
+        self.exclude = None;
+        self.min_level = None;
+        self.refilter();
+        self.notifications.info("Filters cleared");
+    }
+    
+    /// Move to the next (or previous) warning or error
+    fn jump_to_problem(&mut self, forward: bool) {
+        let current = self.list_state.selected().unwrap_or(0);
+        let is_problem = |row: &usize| self.lines[self.visible[*row]].severity >= Some(Severity::Warn);
+        let found = if forward {
+            (current + 1..self.visible.len()).find(is_problem)
+        } else {
+            (0..current).rev().find(is_problem)
+        };
+        match found {
+            Some(row) => {
+                self.list_state.select(Some(row));
+                self.follow = false;
+                self.update_status();
+            }
+            None => self.notifications.info(format!("No warnings or errors {}", if forward { "below" } else { "above" })),
+        }
+    }
+    
+    /// Select the first line logged at or after the time typed in `query`;
+    /// returns false if it isn't a time
+    fn jump_to_time(&mut self, query: &str) -> bool {
+        // Times of day are on the day of the selected line
+        let reference = self.selected_line()
+            .and_then(|line| line.time)
+            .or_else(|| self.lines.iter().rev().find_map(|line| line.time))
+            .unwrap_or_else(time_util::now_secs);
+        let Some(target) = log_file::parse_jump_time(query, reference) else {
+            self.notifications.error(format!("Not a time: {} (try 14:30, 14:30:15 or 2024-05-01 14:30)", query));
+            return false;
+        };
+        match self.visible.iter().position(|index| self.lines[*index].time.is_some_and(|time| time >= target)) {
+            Some(row) => {
+                self.list_state.select(Some(row));
+                self.follow = false;
+                self.update_status();
+            }
+            None => self.notifications.info(format!("No lines at or after {}", time_util::format_timestamp(target))),
+        }
+        true
+    }
+    
+    /// A regex typed for `/` or `x`: None when empty, or an error toast
+    fn parse_filter(&mut self, input: &TextInput) -> Result<Option<Regex>, ()> {
+        if input.value().is_empty() {
+            return Ok(None);
+        }
+        Regex::new(input.value()).map(Some).map_err(|e| self.notifications.error(format!("Invalid regex: {}", e)))
+    }
+    
+    fn handle_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Line => {}
+            Mode::Include(mut input) => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => match self.parse_filter(&input) {
+                    Ok(include) => {
+                        self.include = include;
+                        self.refilter();
+                    }
+                    Err(()) => self.mode = Mode::Include(input),
+                },
+                code => {
+                    if !ctrl {
+                        input.handle_key(code);
+                    }
+                    self.mode = Mode::Include(input);
+                }
+            },
+            Mode::Exclude(mut input) => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => match self.parse_filter(&input) {
+                    Ok(exclude) => {
+                        self.exclude = exclude;
+                        self.refilter();
+                    }
+                    Err(()) => self.mode = Mode::Exclude(input),
+                },
+                code => {
+                    if !ctrl {
+                        input.handle_key(code);
+                    }
+                    self.mode = Mode::Exclude(input);
+                }
+            },
+            Mode::Jump(mut input) => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => {
+                    let query = input.value().to_string();
+                    if !self.jump_to_time(&query) {
+                        self.mode = Mode::Jump(input);
+                    }
+                }
+                code => {
+                    if !ctrl {
+                        input.handle_key(code);
+                    }
+                    self.mode = Mode::Jump(input);
+                }
+            },
+        }
+    }
+    
+    fn handle_normal_key(&mut self, key: KeyEvent) {
+        if self.vim.navigate(&mut self.list_state, key.code, key.modifiers, self.visible.len(), self.settings.page_size) {
+            // Back on the last line, new lines are followed again
+            let follow = self.list_state.selected().is_some_and(|row| row + 1 == self.visible.len());
+            if follow != self.follow {
+                self.follow = follow;
+                self.update_status();
+            }
+            return;
+        }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Char('f') => self.toggle_follow(),
+            KeyCode::Char(' ') => self.toggle_pause(),
+            KeyCode::Char('/') => {
+                let pattern = self.include.as_ref().map(Regex::to_string).unwrap_or_default();
+                self.mode = Mode::Include(TextInput::new(pattern));
+            }
+            KeyCode::Char('x') => {
+                let pattern = self.exclude.as_ref().map(Regex::to_string).unwrap_or_default();
+                self.mode = Mode::Exclude(TextInput::new(pattern));
+            }
+            KeyCode::Char('l') => self.cycle_level(),
+            KeyCode::Char('c') => self.clear_filters(),
+            KeyCode::Char('e') => self.jump_to_problem(true),
+            KeyCode::Char('E') => self.jump_to_problem(false),
+            KeyCode::Char('t') => self.mode = Mode::Jump(TextInput::default()),
+            KeyCode::Enter if self.selected_line().is_some() => self.mode = Mode::Line,
+            KeyCode::Char('y') => {
+                if let Some(text) = self.selected_line().map(|line| line.text.clone()) {
+                    clipboard::copy_and_notify(&mut self.notifications, "the line", &text);
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// Color of a line logged at `severity`
+    fn severity_style(severity: Option<Severity>) -> Style {
+        match severity {
+            Some(Severity::Error) => Style::default().fg(colors::danger()),
+            Some(Severity::Warn) => Style::default().fg(colors::warning()),
+            Some(Severity::Info) => Style::default().fg(colors::text()),
+            Some(Severity::Debug) | Some(Severity::Trace) => Style::default().fg(colors::muted()),
+            None => Style::default(),
+        }
+    }
+    
+    /// `line` colored by its level, with the include regex's matches marked
+    fn styled_line(&self, line: &LogLine) -> Line<'static> {
+        let style = Self::severity_style(line.severity);
+        let Some(include) = &self.include else {
+            return Line::from(Span::styled(line.text.clone(), style));
+        };
+        let mut spans = Vec::new();
+        let mut end = 0;
+        for found in include.find_iter(&line.text).filter(|found| !found.is_empty()) {
+            spans.push(Span::styled(line.text[end..found.start()].to_string(), style));
+            spans.push(Span::styled(found.as_str().to_string(), style.add_modifier(Modifier::REVERSED)));
+            end = found.end();
+        }
+        spans.push(Span::styled(line.text[end..].to_string(), style));
+        Line::from(spans)
+    }
+    
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(area);
+        
+        self.render_log(f, chunks[0]);
+        self.render_status_bar(f, area);
+        self.render_popup(f);
+    }
+    
+    fn render_log(&mut self, f: &mut Frame, area: Rect) {
+        // Only the rows around the selection can be on screen: the list
+        // keeps the selection in the middle
+        let selected = self.list_state.selected().unwrap_or(0);
+        let reach = area.height as usize;
+        let items: Vec<Line> = self.visible
+            .iter()
+            .enumerate()
+            .map(|(row, index)| {
+                if row.abs_diff(selected) <= reach {
+                    self.styled_line(&self.lines[*index])
+                } else {
+                    Line::default()
+                }
+            })
+            .collect();
+        
+        let title = format!("📜 {} ({} of {} lines)", self.source.name(), self.visible.len(), self.lines.len());
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    fn render_popup(&self, f: &mut Frame) {
+        match &self.mode {
+            Mode::Normal => {}
+            Mode::Include(input) => {
+                tui_common::render_input_popup(f, "Show Matching Lines", "Regex lines must match (empty shows all):", input);
+            }
+            Mode::Exclude(input) => {
+                tui_common::render_input_popup(f, "Hide Matching Lines", "Regex of lines to hide (empty hides none):", input);
+            }
+            Mode::Jump(input) => {
+                tui_common::render_input_popup(f, "Jump to Time", "14:30, 14:30:15 or 2024-05-01 14:30:", input);
+            }
+            Mode::Line => {
+                let Some(line) = self.selected_line() else {
+                    return;
+                };
+                // The popup's inner width, as laid out by render_message_popup
+                let width = (f.area().width.saturating_mul(3) / 5).saturating_sub(2).max(1) as usize;
+                let max_rows = (f.area().height as usize).saturating_sub(6).max(1);
+                let style = Self::severity_style(line.severity);
+                let mut lines: Vec<Line> = text_width::wrap(&line.text, width)
+                    .into_iter()
+                    .take(max_rows)
+                    .map(|row| Line::from(Span::styled(row.to_string(), style)))
+                    .collect();
+                let mut details = Vec::new();
+                details.extend(line.severity.map(Severity::label).map(str::to_string));
+                details.extend(line.time.map(time_util::format_iso));
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(details.join(" • "), Style::default().fg(colors::muted()))));
+                tui_common::render_message_popup(f, "Line", lines, colors::primary());
+            }
+        }
+    }
+    
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
+        
+        let help_text = "Space Pause • f Follow • / Match • x Hide • l Level • t Time • ? Help • q Quit";
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the log viewer
+    pub fn run(&mut self) -> io::Result<()> {
+        tui_common::run_app(self)
+    }
+    
+    /// Print the lines that pass the filters as they are read, until the
+    /// end of the file or input
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        for event in self.reader.iter().flat_map(Receiver::iter) {
+            match event {
+                ReadEvent::Lines(lines) => plain::print(format, lines.iter().filter(|line| self.shows(line)).map(|line| {
+                    vec![
+                        ("time", Value::from(line.time)),
+                        ("level", Value::from(line.severity.map(Severity::label))),
+                        ("text", Value::from(line.text.as_str())),
+                    ]
+                }))?,
+                ReadEvent::Failed(message) => return Err(io::Error::other(message)),
+                ReadEvent::Skipped(_) | ReadEvent::Restarted | ReadEvent::Finished => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl App for LogViewer {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key),
+            AppEvent::Tick => {
+                if self.poll_reader() {
+                    return Ok(Action::Redraw);
+                }
+            }
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        matches!(self.mode, Mode::Include(_) | Mode::Exclude(_) | Mode::Jump(_))
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+/// Run the log viewer on `file`, or standard input without one; print the
+/// matching lines in `output` instead, without following
+pub fn run(
+    file: Option<PathBuf>,
+    follow: bool,
+    include: Option<String>,
+    exclude: Option<String>,
+    min_level: Option<Severity>,
+    output: Option<Format>,
+    settings: Settings,
+) -> io::Result<()> {
+    let source = file.map_or(LogSource::Stdin, LogSource::File);
+    let mut viewer = LogViewer::new(source, follow && output.is_none(), include, exclude, min_level, settings)?;
+    match output {
+        Some(format) => viewer.print(format),
+        None => viewer.run(),
+    }
+}
@@ -1,176 +1,443 @@
-//! Man page browser with search and preview.
+//! Man page browser with search, preview and a built-in reader.
 
-use crate::tui_common::{self, colors};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crate::config::Settings;
+use crate::help_text;
+use crate::jobs::{self, Job, Task};
+use crate::man_index::{self, ManPage};
+use crate::man_page::{self, Flag, Format, Match, Page};
+use crate::notify::Notifications;
+use crate::plain;
+use crate::session::{self, Resume, Session};
+use crate::shell_type::{self, Kind, ShellType};
+use crate::state;
+use crate::tldr;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Debounce, Keymap, NavKey, Overflow, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
-    text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, ListState, Paragraph, Wrap},
     Frame, Terminal,
 };
+use serde_json::Value;
 use std::{
+    collections::{HashMap, HashSet},
     io,
-    process::{Command, Stdio},
-    time::Duration,
+    process::Command,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+    time::{Duration, Instant},
 };
 
-#[derive(Debug, Clone)]
-pub struct ManPage {
-    pub name: String,
-    pub section: String,
-    pub description: String,
+/// Keys of the page list, listed by the help popup
+const LIST_KEYMAP: Keymap = &[
+    ("Type", "Filter pages"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("i", "Back to typing (normal mode)"),
+    ("1-8 / 0", "Only one section / all sections"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Home / End", "First / last item"),
+    ("Enter", "Read the page"),
+    ("/", "Search the preview"),
+    ("n / N", "Next / previous match"),
+    ("Ctrl-T", "Preview man, tldr or both"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
+/// Keys of the page reader, listed by the help popup
+const READER_KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Scroll"),
+    ("PgUp / b, PgDn / Space", "Page up / down"),
+    ("g / G", "Go to the top / bottom"),
+    ("Tab / ]", "Next section"),
+    ("Shift-Tab / [", "Previous section"),
+    ("o", "List the options"),
+    ("/", "Search the page"),
+    ("n / N", "Next / previous match"),
+    ("Esc / q", "Back to the list"),
+];
+
+/// Keys of the options list, listed by the help popup
+const SIDEBAR_KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Choose an option"),
+    ("g / G", "First / last option"),
+    ("Enter", "Go to the option"),
+    ("Esc / o", "Close the list"),
+    ("q", "Back to the page list"),
+];
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Width of the reader's option list
+const SIDEBAR_WIDTH: u16 = 40;
+
+/// Section of the entry for a command without a man page, shown from its
+/// `--help` output
+const HELP_SECTION: &str = "help";
+
+/// Section of the entry for a shell builtin or keyword, shown from bash's
+/// `help`
+const BUILTIN_SECTION: &str = "builtin";
+
+/// Laid out pages kept before starting over
+const PAGE_CACHE: usize = 64;
+
+/// How long the query must stay unchanged before the shell is asked about it
+const SHELL_TYPE_DELAY: Duration = Duration::from_millis(300);
+
+/// How an entry is shown: `ls(1)`, `rg --help` for a help entry or
+/// `cd (builtin)`
+fn label(page: &ManPage) -> String {
+    match page.section.as_str() {
+        HELP_SECTION => format!("{} --help", page.name),
+        BUILTIN_SECTION => format!("{} (builtin)", page.name),
+        section => format!("{}({})", page.name, section),
+    }
+}
+
+/// A search within a page and the hit being shown
+struct Search {
+    query: String,
+    matches: Vec<Match>,
+    current: usize,
+}
+
+impl Search {
+    /// Search `page` for `query`, starting at the first hit from line `from`
+    fn new(page: &Page, query: String, from: usize) -> Self {
+        let matches = page.find(&query);
+        let current = matches.iter().position(|hit| hit.line >= from).unwrap_or(0);
+        Search { query, matches, current }
+    }
+    
+    fn current(&self) -> Option<Match> {
+        self.matches.get(self.current).copied()
+    }
+    
+    /// Move to the next or previous hit, wrapping around
+    fn step(&mut self, forward: bool) {
+        let len = self.matches.len().max(1);
+        self.current = if forward { (self.current + 1) % len } else { (self.current + len - 1) % len };
+    }
+    
+    /// Hits on line `line`
+    fn on_line(&self, line: usize) -> &[Match] {
+        let start = self.matches.partition_point(|hit| hit.line < line);
+        let end = self.matches.partition_point(|hit| hit.line <= line);
+        &self.matches[start..end]
+    }
+    
+    fn status(&self) -> String {
+        if self.matches.is_empty() {
+            format!("Pattern not found: {}", self.query)
+        } else {
+            format!("Match {}/{} for '{}' • n/N Next/previous", self.current + 1, self.matches.len(), self.query)
+        }
+    }
+}
+
+/// First line to show so that `hit` sits a little below the top
+fn scroll_to(hit: Match, last: usize) -> usize {
+    hit.line.saturating_sub(2).min(last)
+}
+
+/// A page open in the full-screen reader
+struct Reader {
+    page: ManPage,
+    content: Page,
+    /// First line shown
+    scroll: usize,
+    search: Option<Search>,
+    /// Search being typed after `/`
+    input: Option<TextInput>,
+    flags: Vec<Flag>,
+    /// The option list, when shown
+    sidebar: Option<ListState>,
+    /// Shown in the status bar until the next key
+    message: Option<String>,
+}
+
+enum Mode {
+    Normal,
+    Reader(Box<Reader>),
+    /// Typing a search in the preview
+    PreviewSearch(TextInput),
+}
+
+/// What the preview pane shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreviewMode {
+    Man,
+    Tldr,
+    /// tldr examples above the man page
+    Both,
+}
+
+impl PreviewMode {
+    const ALL: [PreviewMode; 3] = [PreviewMode::Man, PreviewMode::Tldr, PreviewMode::Both];
+    
+    fn label(&self) -> &'static str {
+        match self {
+            PreviewMode::Man => "man",
+            PreviewMode::Tldr => "tldr",
+            PreviewMode::Both => "both",
+        }
+    }
+    
+    fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.label() == label)
+    }
+    
+    fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
 }
 
+/// A parsed tldr page and where it came from, or why there is none
+type TldrResult = Result<(Vec<tldr::Line>, tldr::Source), String>;
+
 pub struct ManPageBrowser {
     man_pages: Vec<ManPage>,
     filtered_pages: Vec<ManPage>,
     list_state: ListState,
+    vim: VimNav,
     search_query: String,
+    /// Filtering waits for typing to pause
+    filter_debounce: Debounce,
+    /// Only list pages of this main section (`'1'` to `'8'`)
+    section_filter: Option<char>,
     should_quit: bool,
-    status_message: String,
-    preview_content: String,
+    notifications: Notifications,
+    preview_content: Page,
+    mode: Mode,
+    /// Terminal width and reader height at the last draw, to lay pages out
+    /// and page through them
+    screen_width: u16,
+    page_height: usize,
+    /// Preview width at the last draw and the width its page was laid out for
+    preview_width: u16,
+    preview_loaded_width: u16,
+    preview_scroll: usize,
+    preview_search: Option<Search>,
+    preview_mode: PreviewMode,
+    /// tldr pages by command name, once looked up
+    tldr_pages: HashMap<String, TldrResult>,
+    pending_tldr: HashSet<String>,
+    tldr_tx: Sender<(String, TldrResult)>,
+    tldr_rx: Receiver<(String, TldrResult)>,
+    /// Index still being read in the background, and since when
+    index_rx: Option<Receiver<man_index::Event>>,
+    loading_since: Instant,
+    /// Pages already laid out, by entry and width
+    pages: HashMap<(String, u16), Page>,
+    /// The page being laid out in the background
+    page_job: Option<((String, u16), Job<Page>)>,
+    /// What the shell makes of names searched for, once asked
+    shell_types: HashMap<String, Option<ShellType>>,
+    /// When the query last changed, until the shell has been asked about it
+    query_changed: Option<Instant>,
+    shell_type_tx: Sender<(String, Option<ShellType>)>,
+    shell_type_rx: Receiver<(String, Option<ShellType>)>,
+    settings: Settings,
 }
 
 impl ManPageBrowser {
     /// Create a new man page browser
-    pub fn new(search: Option<String>) -> io::Result<Self> {
+    pub fn new(search: Option<String>, section: Option<char>, settings: Settings) -> io::Result<Self> {
+        let (tldr_tx, tldr_rx) = mpsc::channel();
+        let (shell_type_tx, shell_type_rx) = mpsc::channel();
         let mut browser = ManPageBrowser {
             man_pages: Vec::new(),
             filtered_pages: Vec::new(),
             list_state: ListState::default(),
+            vim: VimNav::default(),
             search_query: search.unwrap_or_default(),
+            filter_debounce: Debounce::default(),
+            section_filter: section,
             should_quit: false,
-            status_message: "Loading man pages...".to_string(),
-            preview_content: String::new(),
+            notifications: Notifications::with_status("Loading man pages..."),
+            preview_content: Page::default(),
+            mode: Mode::Normal,
+            screen_width: 80,
+            page_height: 20,
+            preview_width: 80,
+            preview_loaded_width: 80,
+            preview_scroll: 0,
+            preview_search: None,
+            preview_mode: state::get("man.preview")
+                .and_then(|label| PreviewMode::from_label(&label))
+                .unwrap_or(PreviewMode::Man),
+            tldr_pages: HashMap::new(),
+            pending_tldr: HashSet::new(),
+            tldr_tx,
+            tldr_rx,
+            index_rx: None,
+            loading_since: Instant::now(),
+            pages: HashMap::new(),
+            page_job: None,
+            shell_types: HashMap::new(),
+            query_changed: Some(Instant::now()),
+            shell_type_tx,
+            shell_type_rx,
+            settings,
         };
         
-        browser.load_man_pages()?;
-        browser.update_filter();
+        match man_index::cached() {
+            Some(pages) => {
+                browser.add_pages(pages);
+                browser.notifications.set_status(format!("Loaded {} man pages", browser.man_pages.len()));
+            }
+            None => {
+                let (tx, rx) = mpsc::channel();
+                man_index::spawn_load(tx, browser.notifications.notifier());
+                browser.index_rx = Some(rx);
+            }
+        }
         
         Ok(browser)
     }
     
-    /// Load available man pages
-    fn load_man_pages(&mut self) -> io::Result<()> {
-        // Try to use apropos to get all man pages
-        let output = Command::new("apropos")
-            .arg(".")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .output()?;
-        
-        if output.status.success() {
-            let apropos_output = String::from_utf8_lossy(&output.stdout);
-            
-            for line in apropos_output.lines() {
-                if let Some(man_page) = self.parse_apropos_line(line) {
-                    self.man_pages.push(man_page);
+    /// Add pages read by the background load; returns whether any came
+    fn poll_index(&mut self) -> bool {
+        let Some(rx) = &self.index_rx else {
+            return false;
+        };
+        let mut pages = Vec::new();
+        let mut done = false;
+        loop {
+            match rx.try_recv() {
+                Ok(man_index::Event::Pages(batch)) => pages.extend(batch),
+                Ok(man_index::Event::Done) | Err(TryRecvError::Disconnected) => {
+                    done = true;
+                    break;
                 }
+                Err(TryRecvError::Empty) => break,
             }
-        } else {
-            // Fallback: try to load from common man page directories
-            self.load_from_man_directories()?;
         }
+        let pages_empty = pages.is_empty();
+        if !pages_empty {
+            self.add_pages(pages);
+        }
+        if done {
+            self.index_rx = None;
+            // A help entry may be offered now that the index is complete
+            self.add_pages(Vec::new());
+            self.notifications.set_status(format!("Loaded {} man pages", self.man_pages.len()));
+        }
+        done || !pages_empty
+    }
+    
+    /// Merge `pages` into the list, keeping the selected page selected
+    fn add_pages(&mut self, pages: Vec<ManPage>) {
+        let selected = self.list_state.selected().and_then(|index| self.filtered_pages.get(index)).cloned();
+        self.man_pages.extend(pages);
+        man_index::sort(&mut self.man_pages);
+        self.filtered_pages = self.filter_pages();
         
-        // Sort by name
-        self.man_pages.sort_by(|a, b| a.name.cmp(&b.name));
-        
-        if !self.man_pages.is_empty() {
+        let index = match &selected {
+            Some(selected) => self.filtered_pages.iter().position(|page| page == selected),
+            None => None,
+        };
+        if index.is_some() {
+            self.list_state.select(index);
+        } else if !self.filtered_pages.is_empty() {
             self.list_state.select(Some(0));
             self.update_preview();
         }
-        
-        self.status_message = format!("Loaded {} man pages", self.man_pages.len());
-        Ok(())
     }
     
-    /// Parse apropos output line
-    fn parse_apropos_line(&self, line: &str) -> Option<ManPage> {
-        // Format: "command (section) - description"
-        if let Some(desc_start) = line.find(" - ") {
-            let command_section = &line[..desc_start];
-            let description = line[desc_start + 3..].to_string();
-            
-            // Extract command and section
-            if let Some(paren_start) = command_section.find(" (") {
-                let command = command_section[..paren_start].trim().to_string();
-                if let Some(paren_end) = command_section.find(')') {
-                    let section = command_section[paren_start + 2..paren_end].to_string();
-                    
-                    return Some(ManPage {
-                        name: command,
-                        section,
-                        description,
-                    });
-                }
-            }
+    /// Whether `page` matches the search query, ignoring the section filter
+    fn matches_query(&self, page: &ManPage) -> bool {
+        let query = self.search_query.to_lowercase();
+        query.is_empty() ||
+            page.name.to_lowercase().contains(&query) ||
+            page.description.to_lowercase().contains(&query)
+    }
+    
+    /// Man pages matching the search query and section
+    fn filter_pages(&self) -> Vec<ManPage> {
+        let mut pages: Vec<ManPage> = self.man_pages
+            .iter()
+            .filter(|page| self.section_filter.is_none_or(|section| page.section.starts_with(section)))
+            .filter(|page| self.matches_query(page))
+            .cloned()
+            .collect();
+        
+        // A builtin or keyword is offered first, since what the shell runs is
+        // not what a man page of that name describes
+        let query = self.search_query.trim();
+        if let Some(shell_type) = self.query_shell_type().filter(|t| matches!(t.kind, Kind::Builtin | Kind::Keyword)) {
+            let description = match shell_type.kind {
+                Kind::Keyword => "shell keyword",
+                _ => "shell builtin",
+            };
+            pages.insert(0, ManPage {
+                name: query.to_string(),
+                section: BUILTIN_SECTION.to_string(),
+                description: description.to_string(),
+            });
+            return pages;
         }
-        None
+        
+        // A command on PATH without a man page of its own is offered first,
+        // shown from its --help output
+        if self.index_rx.is_none()
+            && matches!(self.section_filter, None | Some('1'))
+            && !pages.iter().any(|page| page.name == query)
+            && help_text::find_command(query).is_some()
+        {
+            pages.insert(0, ManPage {
+                name: query.to_string(),
+                section: HELP_SECTION.to_string(),
+                description: "no man page; shown from --help".to_string(),
+            });
+        }
+        pages
     }
     
-    /// Fallback: Load from man directories
-    fn load_from_man_directories(&mut self) -> io::Result<()> {
-        // Common man page commands to include
-        let common_commands = [
-            ("ls", "1", "list directory contents"),
-            ("cd", "1", "change directory"),
-            ("cp", "1", "copy files"),
-            ("mv", "1", "move files"),
-            ("rm", "1", "remove files"),
-            ("cat", "1", "concatenate files"),
-            ("grep", "1", "search text patterns"),
-            ("find", "1", "search for files"),
-            ("ps", "1", "show running processes"),
-            ("top", "1", "display running processes"),
-            ("kill", "1", "terminate processes"),
-            ("man", "1", "display manual pages"),
-            ("vim", "1", "text editor"),
-            ("nano", "1", "text editor"),
-            ("git", "1", "version control system"),
-            ("ssh", "1", "secure shell"),
-            ("wget", "1", "download files"),
-            ("curl", "1", "transfer data"),
-            ("tar", "1", "archive files"),
-            ("chmod", "1", "change file permissions"),
-            ("chown", "1", "change file ownership"),
-        ];
+    /// What the shell makes of the query, if it has been asked
+    fn query_shell_type(&self) -> Option<&ShellType> {
+        self.shell_types.get(self.search_query.trim()).and_then(Option::as_ref)
+    }
+    
+    /// Ask the shell about the query once it has settled, and take in
+    /// answers; returns whether the list changed
+    fn poll_shell_type(&mut self) -> bool {
+        let mut updated = false;
+        while let Ok((name, shell_type)) = self.shell_type_rx.try_recv() {
+            updated |= name == self.search_query.trim();
+            self.shell_types.insert(name, shell_type);
+        }
+        // A builtin's help replaces the man pages at the top of the list
+        let refiltered = updated && self.query_shell_type().is_some_and(|t| matches!(t.kind, Kind::Builtin | Kind::Keyword));
+        if refiltered {
+            self.update_filter();
+        }
         
-        for (name, section, desc) in common_commands.iter() {
-            // Check if man page actually exists
-            let check_output = Command::new("man")
-                .args(&["-w", name])
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status();
-            
-            if check_output.is_ok() {
-                self.man_pages.push(ManPage {
-                    name: name.to_string(),
-                    section: section.to_string(),
-                    description: desc.to_string(),
-                });
+        if self.query_changed.is_some_and(|changed| changed.elapsed() >= SHELL_TYPE_DELAY) {
+            self.query_changed = None;
+            let name = self.search_query.trim().to_string();
+            if name.is_empty() || name.contains(char::is_whitespace) || self.shell_types.contains_key(&name) {
+                return refiltered;
             }
+            // Nothing is asked twice, even while the answer is on its way
+            self.shell_types.insert(name.clone(), None);
+            let tx = self.shell_type_tx.clone();
+            thread::spawn(move || {
+                let shell_type = shell_type::lookup(&name);
+                let _ = tx.send((name, shell_type));
+            });
         }
-        
-        Ok(())
+        refiltered
     }
     
-    /// Update filtered man pages based on search query
+    /// Update filtered man pages based on search query and section
     fn update_filter(&mut self) {
-        if self.search_query.is_empty() {
-            self.filtered_pages = self.man_pages.clone();
-        } else {
-            let query = self.search_query.to_lowercase();
-            self.filtered_pages = self.man_pages
-                .iter()
-                .filter(|page| {
-                    page.name.to_lowercase().contains(&query) ||
-                    page.description.to_lowercase().contains(&query)
-                })
-                .cloned()
-                .collect();
-        }
+        self.query_changed = Some(Instant::now());
+        self.filtered_pages = self.filter_pages();
         
         // Reset selection
         if !self.filtered_pages.is_empty() {
@@ -178,200 +445,528 @@ impl ManPageBrowser {
             self.update_preview();
         } else {
             self.list_state.select(None);
-            self.preview_content.clear();
+            self.preview_content = Page::default();
         }
     }
     
     /// Update preview content for selected man page
     fn update_preview(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(page) = self.filtered_pages.get(selected) {
-                self.preview_content = self.load_man_page_preview(&page.name, &page.section);
-            }
+        let Some(page) = self.list_state.selected().and_then(|index| self.filtered_pages.get(index)).cloned() else {
+            return;
+        };
+        if self.preview_mode != PreviewMode::Man {
+            self.request_tldr(&page.name);
+        }
+        if self.preview_mode != PreviewMode::Tldr {
+            self.preview_content = self.load_page(&page, self.preview_width.max(20)).unwrap_or_else(|| loading(&page));
+            self.preview_loaded_width = self.preview_width;
         }
+        self.preview_scroll = 0;
+        self.preview_search = None;
     }
     
-    /// Load man page preview content
-    fn load_man_page_preview(&self, name: &str, section: &str) -> String {
-        // Try to get man page content
-        let output = Command::new("man")
-            .args(&[section, name])
-            .env("MANPAGER", "cat")  // Disable paging
-            .env("MANWIDTH", "80")   // Set width
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .output();
-        
-        match output {
-            Ok(output) if output.status.success() => {
-                let content = String::from_utf8_lossy(&output.stdout);
-                // Take first 50 lines for preview
-                let lines: Vec<&str> = content.lines().take(50).collect();
-                lines.join("\n")
-            }
-            _ => {
-                // Fallback: try whatis command for description
-                let whatis_output = Command::new("whatis")
-                    .arg(name)
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::null())
-                    .output();
-                
-                match whatis_output {
-                    Ok(output) if output.status.success() => {
-                        let description = String::from_utf8_lossy(&output.stdout);
-                        format!("Manual page for: {}\n\n{}\n\nUse 'man {}' to view the full manual page.", name, description.trim(), name)
-                    }
-                    _ => {
-                        format!("Manual page for: {}\nSection: {}\n\nNo preview available.\nUse 'man {}' to view the manual page.", name, section, name)
-                    }
+    /// Lay `page` out for `width` columns, from `man` or for a help entry
+    /// from the command's --help output; None while that runs in the
+    /// background, until [`Self::poll_page`] shows it
+    fn load_page(&mut self, page: &ManPage, width: u16) -> Option<Page> {
+        let key = (label(page), width);
+        if let Some(content) = self.pages.get(&key) {
+            return Some(content.clone());
+        }
+        if self.page_job.as_ref().is_some_and(|(loading, _)| *loading == key) {
+            return None;
+        }
+        let (name, section) = (page.name.clone(), page.section.clone());
+        // A new page replaces, and so cancels, one still being laid out
+        let job = self.notifications.jobs().spawn(format!("Formatting {}", key.0), move |task| {
+            let help = match section.as_str() {
+                HELP_SECTION => help_text::run(&name, width),
+                BUILTIN_SECTION => shell_type::builtin_help(&name),
+                _ => return load_man_page(task, &name, &section, width),
+            };
+            Ok(match help {
+                Ok(text) => help_text::parse(&text),
+                Err(e) => Page::parse(&format!("No man page for {}, and no help either:\n\n{}", name, e)),
+            })
+        });
+        self.page_job = Some((key, job));
+        None
+    }
+    
+    /// Keep the page laid out in the background once done, and show it if
+    /// it is still the one wanted; returns whether it came
+    fn poll_page(&mut self) -> bool {
+        let Some(result) = self.page_job.as_mut().and_then(|(_, job)| job.poll()) else {
+            return false;
+        };
+        let Some((key, _)) = self.page_job.take() else {
+            return false;
+        };
+        let content = match result {
+            Ok(content) => content,
+            Err(e) if jobs::is_cancelled(&e) => return true,
+            Err(e) => Page::parse(&format!("Manual page for: {}\n\n{}", key.0, e)),
+        };
+        if self.pages.len() >= PAGE_CACHE {
+            self.pages.clear();
+        }
+        self.pages.insert(key.clone(), content);
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Reader(mut reader) => {
+                if label(&reader.page) == key.0 {
+                    self.layout_reader(&mut reader);
+                }
+                self.mode = Mode::Reader(reader);
+            }
+            mode => {
+                self.mode = mode;
+                let selected = self.list_state.selected().and_then(|index| self.filtered_pages.get(index));
+                if selected.is_some_and(|page| label(page) == key.0) {
+                    self.update_preview();
                 }
             }
         }
+        true
     }
     
-    /// Open selected man page in full viewer
-    fn open_man_page(&mut self) -> io::Result<()> {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(page) = self.filtered_pages.get(selected) {
-                // Open man page in default pager
-                let status = Command::new("man")
-                    .args(&[&page.section, &page.name])
-                    .status();
-                
-                if status.is_ok() {
-                    self.should_quit = true;
-                } else {
-                    self.status_message = format!("Failed to open man page for {}", page.name);
-                }
+    /// Look up the tldr page for `name` in the background unless known
+    fn request_tldr(&mut self, name: &str) {
+        if self.tldr_pages.contains_key(name) || !self.pending_tldr.insert(name.to_string()) {
+            return;
+        }
+        let tx = self.tldr_tx.clone();
+        let name = name.to_string();
+        thread::spawn(move || {
+            let page = tldr::lookup(&name).map(|(markdown, source)| (tldr::parse(&markdown), source));
+            let _ = tx.send((name, page));
+        });
+    }
+    
+    /// Collect finished tldr lookups; returns whether any came
+    fn poll_tldr(&mut self) -> bool {
+        let mut received = false;
+        while let Ok((name, page)) = self.tldr_rx.try_recv() {
+            self.pending_tldr.remove(&name);
+            self.tldr_pages.insert(name, page);
+            received = true;
+        }
+        received
+    }
+    
+    /// Switch the preview between the man page, tldr and both
+    fn cycle_preview(&mut self) {
+        self.preview_mode = self.preview_mode.next();
+        self.update_preview();
+        let label = match self.preview_mode {
+            PreviewMode::Man => "Preview: man page",
+            PreviewMode::Tldr => "Preview: tldr examples",
+            PreviewMode::Both => "Preview: tldr examples and man page",
+        };
+        match state::set("man.preview", self.preview_mode.label()) {
+            Ok(()) => self.notifications.info(label),
+            Err(e) => self.notifications.error(format!("{} (not saved: {})", label, e)),
+        }
+    }
+    
+    /// Open the selected man page in the full-screen reader
+    fn open_man_page(&mut self) {
+        let Some(page) = self.list_state.selected().and_then(|index| self.filtered_pages.get(index)).cloned() else {
+            return;
+        };
+        let mut reader = Box::new(Reader {
+            content: loading(&page),
+            page,
+            scroll: 0,
+            search: None,
+            input: None,
+            flags: Vec::new(),
+            sidebar: None,
+            message: None,
+        });
+        // Lay the page out for the whole screen rather than the preview pane
+        self.layout_reader(&mut reader);
+        self.mode = Mode::Reader(reader);
+    }
+    
+    /// Show or hide the reader's option list, laying the page out again for
+    /// the width left beside it
+    fn toggle_sidebar(&mut self, reader: &mut Reader) {
+        if reader.sidebar.take().is_none() {
+            if reader.flags.is_empty() {
+                reader.message = Some("No options found on this page".to_string());
+                return;
             }
+            // Start from the first option at or below the top of the screen
+            let index = reader.flags.iter().position(|flag| flag.line >= reader.scroll).unwrap_or(0);
+            reader.sidebar = Some(ListState::default().with_selected(Some(index)));
+        }
+        self.layout_reader(reader);
+    }
+    
+    /// Lay the reader's page out for the width left beside the option list,
+    /// keeping about the same place in it; a page laid out in the background
+    /// is put in once done
+    fn layout_reader(&mut self, reader: &mut Reader) {
+        let mut width = self.screen_width.saturating_sub(2);
+        if reader.sidebar.is_some() {
+            width = width.saturating_sub(SIDEBAR_WIDTH);
+        }
+        let Some(content) = self.load_page(&reader.page, width.max(40)) else {
+            return;
+        };
+        let previous = reader.content.lines.len().max(1);
+        reader.content = content;
+        reader.flags = reader.content.flags();
+        reader.search = None;
+        // Keep roughly the same place in the reflowed page
+        reader.scroll = reader.scroll * reader.content.lines.len() / previous;
+        if let Some(index) = reader.sidebar.as_ref().and_then(ListState::selected) {
+            self.jump_to_flag(reader, index);
         }
-        Ok(())
+    }
+    
+    /// Scroll the reader to option `index`
+    fn jump_to_flag(&mut self, reader: &mut Reader, index: usize) {
+        let Some(flag) = reader.flags.get(index) else {
+            return;
+        };
+        let last = reader.content.lines.len().saturating_sub(self.page_height);
+        reader.scroll = flag.line.saturating_sub(1).min(last);
     }
     
     /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page down
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.filtered_pages.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page up
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.filtered_pages.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected > 0 {
-                                self.list_state.select(Some(selected - 1));
-                                self.update_preview();
-                            }
-                        }
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if !self.vim.typing(&key) && self.filter_debounce.take() {
+            self.update_filter();
+        }
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal_input(key.code, key.modifiers),
+            Mode::Reader(mut reader) => {
+                if self.handle_reader_input(&mut reader, key.code) {
+                    self.mode = Mode::Reader(reader);
+                }
+            }
+            Mode::PreviewSearch(mut input) => match key.code {
+                KeyCode::Esc => self.notifications.info("Cancelled"),
+                KeyCode::Enter => {
+                    let search = Search::new(&self.preview_content, input.value().to_string(), self.preview_scroll);
+                    if let Some(hit) = search.current() {
+                        self.preview_scroll = scroll_to(hit, usize::MAX);
                     }
-                    KeyCode::Down => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected + 1 < self.filtered_pages.len() {
-                                self.list_state.select(Some(selected + 1));
-                                self.update_preview();
-                            }
-                        } else if !self.filtered_pages.is_empty() {
-                            self.list_state.select(Some(0));
-                            self.update_preview();
-                        }
+                    self.notifications.info(search.status());
+                    self.preview_search = Some(search).filter(|search| !search.matches.is_empty());
+                }
+                code => {
+                    if !key.modifiers.contains(KeyModifiers::CONTROL) {
+                        input.handle_key(code);
                     }
-                    KeyCode::Enter => {
-                        self.open_man_page()?;
+                    self.mode = Mode::PreviewSearch(input);
+                }
+            },
+        }
+        Ok(())
+    }
+    
+    /// Handle keys in the reader; returns whether to stay in it
+    fn handle_reader_input(&mut self, reader: &mut Reader, code: KeyCode) -> bool {
+        reader.message = None;
+        let last = reader.content.lines.len().saturating_sub(self.page_height);
+        if let Some(mut input) = reader.input.take() {
+            match code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => {
+                    let search = Search::new(&reader.content, input.value().to_string(), reader.scroll);
+                    if let Some(hit) = search.current() {
+                        reader.scroll = scroll_to(hit, last);
                     }
-                    KeyCode::Char(c) => {
-                        self.search_query.push(c);
-                        self.update_filter();
+                    self.notifications.info(search.status());
+                    reader.search = Some(search).filter(|search| !search.matches.is_empty());
+                }
+                code => {
+                    input.handle_key(code);
+                    reader.input = Some(input);
+                }
+            }
+            return true;
+        }
+        if let Some(selected) = reader.sidebar.as_ref().and_then(ListState::selected) {
+            // Moving through the option list follows along in the page
+            let count = reader.flags.len();
+            let index = match code {
+                KeyCode::Up | KeyCode::Char('k') => Some(selected.saturating_sub(1)),
+                KeyCode::Down | KeyCode::Char('j') => Some((selected + 1).min(count.saturating_sub(1))),
+                KeyCode::Home | KeyCode::Char('g') => Some(0),
+                KeyCode::End | KeyCode::Char('G') => Some(count.saturating_sub(1)),
+                _ => None,
+            };
+            if let Some(index) = index {
+                if let Some(sidebar) = &mut reader.sidebar {
+                    sidebar.select(Some(index));
+                }
+                self.jump_to_flag(reader, index);
+                return true;
+            }
+            if matches!(code, KeyCode::Enter | KeyCode::Esc | KeyCode::Char('o')) {
+                // Enter keeps the page at the chosen option
+                self.toggle_sidebar(reader);
+                return true;
+            }
+        }
+        match code {
+            KeyCode::Char('o') => self.toggle_sidebar(reader),
+            KeyCode::Esc if reader.search.is_some() => reader.search = None,
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('/') => reader.input = Some(TextInput::default()),
+            KeyCode::Char(c @ ('n' | 'N')) => {
+                if let Some(search) = &mut reader.search {
+                    search.step(c == 'n');
+                    if let Some(hit) = search.current() {
+                        reader.scroll = scroll_to(hit, last);
                     }
-                    KeyCode::Backspace => {
-                        self.search_query.pop();
-                        self.update_filter();
+                    self.notifications.info(search.status());
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => reader.scroll = reader.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => reader.scroll = (reader.scroll + 1).min(last),
+            KeyCode::PageUp | KeyCode::Char('b') => reader.scroll = reader.scroll.saturating_sub(self.page_height),
+            KeyCode::PageDown | KeyCode::Char(' ') => reader.scroll = (reader.scroll + self.page_height).min(last),
+            KeyCode::Home | KeyCode::Char('g') => reader.scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => reader.scroll = last,
+            KeyCode::Tab | KeyCode::Char(']') => {
+                if let Some(line) = reader.content.next_section(reader.scroll) {
+                    reader.scroll = line.min(last);
+                }
+            }
+            KeyCode::BackTab | KeyCode::Char('[') => {
+                reader.scroll = reader.content.previous_section(reader.scroll).unwrap_or(0);
+            }
+            _ => {}
+        }
+        true
+    }
+    
+    /// Handle keys in the page list
+    fn handle_normal_input(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        // Esc clears a preview search and `/` starts one, in either mode
+        match code {
+            KeyCode::Esc if self.preview_search.is_some() => {
+                self.preview_search = None;
+                self.notifications.info("Search cleared");
+                return;
+            }
+            KeyCode::Char('/') if self.preview_mode != PreviewMode::Tldr => {
+                self.mode = Mode::PreviewSearch(TextInput::default());
+                return;
+            }
+            _ => {}
+        }
+        match self.vim.filter_key(&mut self.list_state, code, modifiers, self.filtered_pages.len(), self.settings.page_size) {
+            NavKey::Moved => {
+                self.update_preview();
+                return;
+            }
+            NavKey::Used => return,
+            NavKey::Unused => {}
+        }
+        
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            // While a preview search is active n/N move between its hits
+            KeyCode::Char(c @ ('n' | 'N')) if self.preview_search.is_some() => {
+                if let Some(search) = &mut self.preview_search {
+                    search.step(c == 'n');
+                    if let Some(hit) = search.current() {
+                        self.preview_scroll = scroll_to(hit, usize::MAX);
                     }
-                    _ => {}
+                    self.notifications.info(search.status());
                 }
             }
+            KeyCode::Enter => {
+                self.open_man_page();
+            }
+            KeyCode::Char('t') if ctrl => self.cycle_preview(),
+            KeyCode::Char(c @ '1'..='8') => {
+                self.section_filter = if self.section_filter == Some(c) { None } else { Some(c) };
+                self.update_filter();
+                self.notifications.info(match self.section_filter {
+                    Some(c) => format!("Section {}: {}", c, man_page::section_name(&c.to_string()).unwrap_or_default()),
+                    None => "All sections".to_string(),
+                });
+            }
+            KeyCode::Char('0') => {
+                self.section_filter = None;
+                self.update_filter();
+                self.notifications.info("All sections");
+            }
+            KeyCode::Char(c) if self.vim.inserting() => {
+                self.search_query.push(c);
+                self.filter_debounce.schedule();
+            }
+            KeyCode::Backspace if self.vim.inserting() => {
+                self.search_query.pop();
+                self.filter_debounce.schedule();
+            }
+            _ => {}
         }
-        Ok(())
     }
     
     /// Render the man page browser
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        self.screen_width = area.width;
+        if let Mode::Reader(reader) = &mut self.mode {
+            // Border and status bar take three rows
+            self.page_height = (area.height as usize).saturating_sub(3).max(1);
+            render_reader(f, area, reader, self.page_height);
+            return;
+        }
+        
+        // A name the shell runs as something other than a program is pointed
+        // out above everything else
+        let mut area = area;
+        if let Some(shell_type) = self.query_shell_type().filter(|t| t.shadows_program()) {
+            let note = match shell_type.kind {
+                Kind::Alias | Kind::Function => " - the man pages below describe the program, not this",
+                _ => " - its help is listed first",
+            };
+            let banner = Paragraph::new(format!(" ⓘ {}{}", shell_type.description, note))
+                .style(Style::default().bg(colors::secondary()).fg(colors::background()).add_modifier(Modifier::BOLD));
+            f.render_widget(banner, Rect { height: 1, ..area });
+            area = Rect { y: area.y + 1, height: area.height.saturating_sub(1), ..area };
+        }
+        
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(f.area());
+            .split(area);
         
+        self.preview_width = chunks[1].width.saturating_sub(2);
         self.render_man_page_list(f, chunks[0]);
-        self.render_man_page_preview(f, chunks[1]);
-        self.render_status_bar(f);
+        self.render_preview(f, chunks[1]);
+        self.render_status_bar(f, area);
+        
+        if let Mode::PreviewSearch(input) = &self.mode {
+            tui_common::render_input_popup(f, "Search Page", "Find:", input);
+        }
     }
     
     /// Render man page list
     fn render_man_page_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.filtered_pages
+        let items: Vec<Line> = self.filtered_pages
             .iter()
             .map(|page| {
                 let line = Line::from(vec![
                     Span::styled(
-                        format!("{}({})", page.name, page.section),
-                        Style::default().fg(colors::PRIMARY).add_modifier(Modifier::BOLD)
+                        label(page),
+                        Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)
                     ),
                     Span::raw(" - "),
                     Span::styled(
-                        page.description.chars().take(60).collect::<String>(),
-                        Style::default().fg(colors::TEXT)
+                        page.description.as_str(),
+                        Style::default().fg(colors::text())
                     ),
                 ]);
-                ListItem::new(line)
+                line
             })
             .collect();
         
-        let title = if self.search_query.is_empty() {
+        let mut title = if self.search_query.is_empty() {
             format!("Manual Pages ({})", self.filtered_pages.len())
         } else {
             format!("Manual Pages ({}) - Filter: '{}'", self.filtered_pages.len(), self.search_query)
         };
+        // Matches per main section, the filtered one in brackets
+        let mut counts = [0usize; 8];
+        for page in self.man_pages.iter().filter(|page| self.matches_query(page)) {
+            if let Some(digit) = page.section.chars().next().and_then(|c| c.to_digit(10)).filter(|d| (1..=8).contains(d)) {
+                counts[digit as usize - 1] += 1;
+            }
+        }
+        let counts: Vec<String> = counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(index, count)| {
+                let section = char::from_digit(index as u32 + 1, 10).unwrap_or('?');
+                if self.section_filter == Some(section) {
+                    format!("[{}:{}]", section, count)
+                } else {
+                    format!("{}:{}", section, count)
+                }
+            })
+            .collect();
+        if !counts.is_empty() {
+            title.push_str(&format!(" - {}", counts.join(" ")));
+        }
+        if self.index_rx.is_some() {
+            let frame = (self.loading_since.elapsed().as_millis() / 80) as usize % SPINNER.len();
+            title.push_str(&format!(" - {} Loading {}...", SPINNER[frame], self.man_pages.len()));
+        }
         
-        let list = List::new(items)
+        let list = SelectableList::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
+                .border_style(Style::default().fg(colors::primary())));
         
         f.render_stateful_widget(list, area, &mut self.list_state);
     }
     
+    /// Render the preview pane as chosen with Ctrl-T
+    fn render_preview(&self, f: &mut Frame, area: Rect) {
+        match self.preview_mode {
+            PreviewMode::Man => self.render_man_page_preview(f, area),
+            PreviewMode::Tldr => self.render_tldr_preview(f, area),
+            PreviewMode::Both => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(area);
+                self.render_tldr_preview(f, chunks[0]);
+                self.render_man_page_preview(f, chunks[1]);
+            }
+        }
+    }
+    
+    /// Render the tldr page of the selected command
+    fn render_tldr_preview(&self, f: &mut Frame, area: Rect) {
+        let name = self.list_state.selected()
+            .and_then(|index| self.filtered_pages.get(index))
+            .map(|page| page.name.as_str())
+            .unwrap_or_default();
+        let mut title = format!("tldr: {}", name);
+        let lines = match self.tldr_pages.get(name) {
+            Some(Ok((lines, source))) => {
+                match source {
+                    tldr::Source::Network | tldr::Source::Cache => {}
+                    tldr::Source::StaleCache => title.push_str(" (offline, cached copy)"),
+                }
+                tldr_lines(lines)
+            }
+            Some(Err(e)) => vec![Line::from(Span::styled(e.clone(), Style::default().fg(colors::muted())))],
+            None if self.pending_tldr.contains(name) => {
+                vec![Line::from(Span::styled("Fetching...", Style::default().fg(colors::muted())))]
+            }
+            None => Vec::new(),
+        };
+        
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(colors::secondary())))
+            .wrap(Wrap { trim: false });
+        
+        f.render_widget(paragraph, area);
+    }
+    
     /// Render man page preview
     fn render_man_page_preview(&self, f: &mut Frame, area: Rect) {
         let title = if let Some(selected) = self.list_state.selected() {
             if let Some(page) = self.filtered_pages.get(selected) {
-                format!("Preview: {}({})", page.name, page.section)
+                format!("Preview: {}", label(page))
             } else {
                 "Preview".to_string()
             }
@@ -379,57 +974,318 @@ impl ManPageBrowser {
             "Preview".to_string()
         };
         
-        let paragraph = Paragraph::new(self.preview_content.as_str())
+        // The page is laid out for the pane; style only the lines that fit
+        let visible = area.height.saturating_sub(2) as usize;
+        let lines = styled_lines(&self.preview_content, self.preview_scroll, visible, self.preview_search.as_ref());
+        let paragraph = Paragraph::new(lines)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::SECONDARY)))
-            .wrap(Wrap { trim: true });
+                .border_style(Style::default().fg(colors::secondary())));
         
         f.render_widget(paragraph, area);
     }
     
     /// Render status bar
-    fn render_status_bar(&self, f: &mut Frame) {
-        let area = Rect {
-            x: 0,
-            y: f.area().height - 1,
-            width: f.area().width,
-            height: 1,
-        };
-        
-        let help_text = "Type to filter • ↑↓ Navigate • Enter Open • Esc Quit";
-        let status_text = format!("{} | {}", self.status_message, help_text);
-        
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
         
-        f.render_widget(paragraph, area);
+        let help_text = if self.vim.inserting() {
+            "Type to filter • 1-8 Section • Enter Read • / Search page • Esc Normal mode • F1 Help"
+        } else {
+            "j/k Move • i Filter • 1-8 Section • Enter Read • / Search page • ? Help • q Quit"
+        };
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
     }
     
-    /// Run the man page browser
+    /// Run the man page browser, from where it was left in this directory
     pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
-        let result = self.run_app(&mut terminal);
-        tui_common::restore_terminal(&mut terminal)?;
-        result
+        session::resume(self, "man", tui_common::run_app)
     }
     
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            self.handle_input()?;
-            if self.should_quit {
-                break;
+    /// Print the matching pages instead of running the TUI, once the index
+    /// has been read
+    pub fn print(&mut self, format: plain::Format) -> io::Result<()> {
+        while self.index_rx.is_some() {
+            if !self.poll_index() {
+                thread::sleep(Duration::from_millis(50));
             }
         }
+        plain::print(format, self.filtered_pages.iter().map(|page| vec![
+            ("name", Value::from(page.name.as_str())),
+            ("section", Value::from(page.section.as_str())),
+            ("description", Value::from(page.description.as_str())),
+        ]))
+    }
+}
+
+impl App for ManPageBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick => {
+                let filtered = self.filter_debounce.ready();
+                if filtered {
+                    self.update_filter();
+                }
+                let changed = self.poll_index() | self.poll_tldr() | self.poll_shell_type() | self.poll_page();
+                if filtered || changed {
+                    return Ok(Action::Redraw);
+                }
+            }
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        match &self.mode {
+            Mode::Reader(reader) if reader.sidebar.is_some() => SIDEBAR_KEYMAP,
+            Mode::Reader(_) => READER_KEYMAP,
+            _ => LIST_KEYMAP,
+        }
+    }
+    
+    fn captures_text(&self) -> bool {
+        match &self.mode {
+            Mode::Reader(reader) => reader.input.is_some(),
+            Mode::PreviewSearch(_) => true,
+            Mode::Normal => self.vim.inserting(),
+        }
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn after_draw<B: ratatui::backend::Backend + io::Write>(&mut self, _terminal: &mut Terminal<B>) -> io::Result<()> {
+        // Lay the preview out again once the pane width is known or changes,
+        // staying about as far into the page
+        if self.preview_width != self.preview_loaded_width && self.preview_mode != PreviewMode::Tldr {
+            let scroll = self.preview_scroll;
+            self.update_preview();
+            self.preview_scroll = scroll.min(self.preview_content.lines.len().saturating_sub(1));
+        }
         Ok(())
     }
 }
 
-/// Run the man page browser
-pub fn run(search: Option<String>) -> io::Result<()> {
-    let mut browser = ManPageBrowser::new(search)?;
-    browser.run()
+/// Render `reader` over all of `area`, `height` lines of the page at a time
+fn render_reader(f: &mut Frame, area: Rect, reader: &mut Reader, height: usize) {
+    let body = Rect { height: area.height.saturating_sub(1), ..area };
+    let (page_area, sidebar_area) = if reader.sidebar.is_some() {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(SIDEBAR_WIDTH)])
+            .split(body);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (body, None)
+    };
+    let content = &reader.content;
+    let mut title = label(&reader.page);
+    if let Some(section) = content.section_at(reader.scroll) {
+        title.push_str(&format!(" - {}", section));
+    }
+    let total = content.lines.len().max(1);
+    title.push_str(&format!(" - {}%", ((reader.scroll + height).min(total) * 100) / total));
+    
+    let paragraph = Paragraph::new(styled_lines(content, reader.scroll, height, reader.search.as_ref()))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(colors::primary())));
+    f.render_widget(paragraph, page_area);
+    
+    if let (Some(area), Some(sidebar)) = (sidebar_area, reader.sidebar.as_mut()) {
+        let items: Vec<Text> = reader.flags
+            .iter()
+            .map(|flag| Text::from(vec![
+                Line::from(Span::styled(flag.names.clone(), Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD))),
+                Line::from(Span::styled(format!("  {}", flag.summary), Style::default().fg(colors::muted()))),
+            ]))
+            .collect();
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Options ({})", reader.flags.len()))
+                .border_style(Style::default().fg(colors::secondary())))
+            .overflow(Overflow::Wrap)
+            .highlight_style(Style::default().bg(colors::secondary()).fg(colors::background()));
+        f.render_stateful_widget(list, area, sidebar);
+    }
+    
+    let help_text = if reader.sidebar.is_some() {
+        "↑↓ Choose option • Enter Go • Esc Close list • ? Help"
+    } else {
+        "Tab Next section • o Options • / Search • ? Help • q Back"
+    };
+    let status_text = match (&reader.message, &reader.search) {
+        (Some(message), _) => format!("{} | {}", message, help_text),
+        (None, Some(search)) => format!("{} | {}", search.status(), help_text),
+        (None, None) if reader.sidebar.is_some() => help_text.to_string(),
+        (None, None) => {
+            let sections: Vec<&str> = content.sections.iter().map(|(name, _)| name.as_str()).collect();
+            format!("Sections: {} | {}", sections.join(" · "), help_text)
+        }
+    };
+    let status = Paragraph::new(status_text)
+        .style(Style::default().bg(colors::primary()).fg(colors::background()));
+    f.render_widget(status, Rect { y: area.height.saturating_sub(1), height: 1, ..area });
+    
+    if let Some(input) = &reader.input {
+        tui_common::render_input_popup(f, "Search Page", "Find:", input);
+    }
+}
+
+/// A tldr page styled for display: examples in green, placeholders to
+/// replace underlined
+fn tldr_lines(lines: &[tldr::Line]) -> Vec<Line<'static>> {
+    lines
+        .iter()
+        .map(|line| match line {
+            tldr::Line::Title(title) => Line::from(Span::styled(
+                title.clone(),
+                Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD),
+            )),
+            tldr::Line::Description(text) => Line::from(Span::styled(text.clone(), Style::default().fg(colors::muted()))),
+            tldr::Line::Example(text) => Line::from(Span::styled(format!("- {}", text), Style::default().fg(colors::success()))),
+            tldr::Line::Command(segments) => {
+                let mut spans = vec![Span::raw("  ")];
+                spans.extend(segments.iter().map(|(text, placeholder)| {
+                    if *placeholder {
+                        Span::styled(text.clone(), Style::default().fg(colors::secondary()).add_modifier(Modifier::UNDERLINED))
+                    } else {
+                        Span::styled(text.clone(), Style::default().add_modifier(Modifier::BOLD))
+                    }
+                }));
+                Line::from(spans)
+            }
+            tldr::Line::Blank => Line::from(""),
+        })
+        .collect()
+}
+
+/// Up to `count` lines of `page` from line `start`, styled for display
+fn styled_lines(page: &Page, start: usize, count: usize, search: Option<&Search>) -> Vec<Line<'static>> {
+    let current = search.and_then(Search::current);
+    page.lines
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(count)
+        .map(|(index, runs)| {
+            let hits = search.map(|search| search.on_line(index)).unwrap_or_default();
+            let mut spans = Vec::new();
+            let mut column = 0;
+            for (text, format) in runs {
+                let base = match format {
+                    Format::Plain => Style::default(),
+                    Format::Bold => Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD),
+                    Format::Underline => Style::default().fg(colors::secondary()).add_modifier(Modifier::UNDERLINED),
+                };
+                // Split the run where search hits start and end
+                let mut piece = String::new();
+                let mut piece_style = base;
+                for c in text.chars() {
+                    let style = match hits.iter().find(|hit| (hit.start..hit.start + hit.len).contains(&column)) {
+                        Some(hit) if Some(*hit) == current => base.bg(colors::secondary()).fg(colors::background()),
+                        Some(_) => base.add_modifier(Modifier::REVERSED),
+                        None => base,
+                    };
+                    if style != piece_style && !piece.is_empty() {
+                        spans.push(Span::styled(std::mem::take(&mut piece), piece_style));
+                    }
+                    piece_style = style;
+                    piece.push(c);
+                    column += 1;
+                }
+                if !piece.is_empty() {
+                    spans.push(Span::styled(piece, piece_style));
+                }
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Placeholder for `page` while it is laid out
+fn loading(page: &ManPage) -> Page {
+    Page::parse(&format!("Formatting {}...", label(page)))
+}
+
+/// Format the man page `name(section)` for a `width` column terminal,
+/// keeping bold and underline; pages that cannot be formatted get a short
+/// note instead
+fn load_man_page(task: &Task, name: &str, section: &str, width: u16) -> io::Result<Page> {
+    let output = task.output(
+        Command::new("man")
+            .args([section, name])
+            .env("MANPAGER", "cat")  // Disable paging
+            .env("MANWIDTH", width.to_string())
+            // Keep bold and underline as overstrikes even though stdout is a pipe
+            .env("MAN_KEEP_FORMATTING", "1")
+            .env("GROFF_NO_SGR", "1"),
+    );
+    
+    let text = match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Err(e) if jobs::is_cancelled(&e) => return Err(e),
+        _ => {
+            // Fallback: try whatis command for description
+            match task.output(Command::new("whatis").arg(name)) {
+                Ok(output) if output.status.success() => {
+                    let description = String::from_utf8_lossy(&output.stdout);
+                    format!("Manual page for: {}\n\n{}\n\nThe full page could not be formatted.", name, description.trim())
+                }
+                Err(e) if jobs::is_cancelled(&e) => return Err(e),
+                _ => {
+                    format!("Manual page for: {}\nSection: {}\n\nNo preview available.", name, section)
+                }
+            }
+        }
+    };
+    Ok(Page::parse(&text))
+}
+
+impl Resume for ManPageBrowser {
+    /// The query, and the selected page by its label with how far its
+    /// preview was scrolled
+    fn session(&self) -> Session {
+        let selected = self.list_state.selected().and_then(|index| self.filtered_pages.get(index));
+        Session {
+            query: Some(self.search_query.clone()),
+            selected: selected.map(label),
+            scroll: selected.map(|_| self.preview_scroll),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query.filter(|_| self.search_query.is_empty()) {
+            self.search_query = query;
+            self.update_filter();
+        }
+        let position = session.selected.and_then(|selected| {
+            self.filtered_pages.iter().position(|page| label(page) == selected)
+        });
+        if let Some(position) = position {
+            self.list_state.select(Some(position));
+            self.update_preview();
+            self.preview_scroll = session.scroll.unwrap_or(0).min(self.preview_content.lines.len().saturating_sub(1));
+        }
+    }
+}
+
+/// Run the man page browser, or print the matching pages in `output`
+pub fn run(search: Option<String>, section: Option<u8>, output: Option<plain::Format>, settings: Settings) -> io::Result<()> {
+    let section = section.and_then(|section| char::from_digit(section as u32, 10));
+    let mut browser = ManPageBrowser::new(search, section, settings)?;
+    match output {
+        Some(format) => browser.print(format),
+        None => browser.run(),
+    }
 }
\ No newline at end of file
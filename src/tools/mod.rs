@@ -10,10 +10,25 @@
 //! - [`kill`] - Interactive process manager
 //! - [`git`] - Git repository browser and operations
 //! - [`explore`] - File/directory explorer
+//! - [`trash`] - Trash bin viewer (restore or purge deleted files)
+//! - [`du`] - Disk usage analyzer
+//! - [`z`] - Frecency-ranked directory jumper
 //! - [`history`] - Command history browser
+//! - [`snip`] - Saved command snippets with placeholders
 //! - [`mod@env`] - Environment variable viewer
 //! - [`man`] - Manual page browser
+//! - [`log`] - Log file viewer with follow and filters
+//! - [`net`] - Listening ports and connections browser
+//! - [`systemd`] - systemd service browser and controller
+//! - [`json`] - JSON and YAML explorer
+//! - [`hex`] - Hex viewer for binary files
+//! - [`sys`] - System resource dashboard
+//! - [`pkg`] - Package search and info browser
+//! - [`todo`] - TODO/FIXME comment browser
+//! - [`diff`] - File and directory diff viewer
+//! - [`workspace`] - Several tools as tabs of one screen
 //! - [`recent`] - Recent files tracker
+//! - [`launcher`] - Menu of the tools, shown by a bare `tt`
 //!
 //! ## Design Patterns
 //!
@@ -21,17 +36,21 @@
 //!
 //! - **TUI Structure**: Each tool has a main struct containing state and a `run()` method
 //! - **Input Handling**: Consistent keyboard shortcuts across all tools
-//! - **Error Handling**: Graceful degradation with informative error messages
+//! - **Error Handling**: Graceful degradation, reporting problems as toasts through [`crate::notify`]
 //! - **Performance**: Optimized for large datasets with pagination and limiting
 //!
 //! ## Navigation
 //!
 //! All tools support the same navigation keys:
 //! - `↑/↓` or `j/k` for line-by-line navigation
-//! - `Ctrl-F/Ctrl-B` for page-by-page navigation
+//! - `PgDn/PgUp` (or `Ctrl-F/Ctrl-B`) for page-by-page navigation, `Ctrl-D/Ctrl-U` for half pages
+//! - `Home/End` or `gg/G` for the ends
 //! - `Enter` to select/execute items
 //! - `Esc` or `q` to quit
 //!
+//! Tools that filter as you type start in insert mode, where letters are text;
+//! `Esc` switches to normal mode for the vim keys and `/` or `i` switches back.
+//!
 //! ## Integration
 //!
 //! Tools integrate with external commands where beneficial:
@@ -48,4 +67,19 @@ pub mod explore;
 pub mod env;
 pub mod recent;
 pub mod man;
-pub mod search;
\ No newline at end of file
+pub mod log;
+pub mod net;
+pub mod systemd;
+pub mod json;
+pub mod hex;
+pub mod sys;
+pub mod pkg;
+pub mod todo;
+pub mod diff;
+pub mod workspace;
+pub mod search;
+pub mod trash;
+pub mod du;
+pub mod z;
+pub mod snip;
+pub mod launcher;
\ No newline at end of file
@@ -0,0 +1,609 @@
+//! Network browser: listening ports and established connections with the
+//! processes that own them.
+
+use crate::clipboard;
+use crate::config::Settings;
+use crate::notify::Notifications;
+use crate::palette::Context;
+use crate::plain::{self, Format};
+use crate::process_info::{self, Connection, ProcessInfo};
+use crate::session::{self, Resume, Session};
+use crate::text_width;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, NavKey, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    io,
+    time::{Duration, Instant},
+};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Filter by address, port, state, PID or process"),
+    ("Backspace", "Delete a filter character"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Tab", "Show all sockets, listening only or connected only"),
+    ("Enter", "Kill the owning process (Y to confirm)"),
+    ("r", "Refresh (normal mode)"),
+    ("p", "Pause refreshing (normal mode)"),
+    ("y", "Copy the local address (normal mode)"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
+/// Which sockets are listed, cycled with Tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    All,
+    Listening,
+    Connected,
+}
+
+impl View {
+    fn next(self) -> Self {
+        match self {
+            View::All => View::Listening,
+            View::Listening => View::Connected,
+            View::Connected => View::All,
+        }
+    }
+    
+    fn label(self) -> &'static str {
+        match self {
+            View::All => "All Sockets",
+            View::Listening => "Listening",
+            View::Connected => "Connections",
+        }
+    }
+    
+    fn shows(self, connection: &Connection) -> bool {
+        match self {
+            View::All => true,
+            View::Listening => connection.socket.is_listening(),
+            View::Connected => !connection.socket.is_listening(),
+        }
+    }
+}
+
+enum Mode {
+    Normal,
+    /// Asking before the owner of the selected socket is sent SIGTERM
+    ConfirmKill {
+        process: ProcessInfo,
+        /// Set when the process is protected or runs this session
+        reason: Option<String>,
+        /// Confirmations still required (2 for protected processes)
+        confirmations_left: u8,
+    },
+}
+
+/// An address and port as `ss` shows them: IPv6 in brackets, unspecified
+/// parts as `*`
+fn endpoint(addr: &str, port: u16) -> String {
+    let addr = match addr {
+        "" | "0.0.0.0" | "::" | "*" => "*".to_string(),
+        addr if addr.contains(':') => format!("[{}]", addr),
+        addr => addr.to_string(),
+    };
+    match port {
+        0 if addr == "*" => "*".to_string(),
+        0 => format!("{}:*", addr),
+        port => format!("{}:{}", addr, port),
+    }
+}
+
+pub struct NetBrowser {
+    connections: Vec<Connection>,
+    /// Owners of the sockets by PID, for names and command lines
+    processes: HashMap<u32, ProcessInfo>,
+    /// Indices into `connections` that pass the view and filter
+    filtered: Vec<usize>,
+    query: TextInput,
+    view: View,
+    mode: Mode,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    refresh_interval: Duration,
+    last_refresh: Instant,
+    paused: bool,
+    protected_names: Vec<String>,
+    /// PIDs of the shells and terminals `tt` is running under
+    session_pids: Vec<u32>,
+    settings: Settings,
+}
+
+impl NetBrowser {
+    /// Create a browser showing `view`, refreshed every `interval` seconds
+    /// (0 disables auto-refresh)
+    pub fn new(filter: Option<String>, view: View, interval: u64, settings: Settings) -> io::Result<Self> {
+        let mut browser = NetBrowser {
+            connections: Vec::new(),
+            processes: HashMap::new(),
+            filtered: Vec::new(),
+            query: TextInput::new(filter.unwrap_or_default()),
+            view,
+            mode: Mode::Normal,
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::with_status("Loading sockets..."),
+            refresh_interval: Duration::from_secs(interval.max(1)),
+            last_refresh: Instant::now(),
+            paused: interval == 0,
+            protected_names: process_info::protected_names(&settings.protected),
+            session_pids: Vec::new(),
+            settings,
+        };
+        
+        browser.load()?;
+        browser.update_filter();
+        
+        Ok(browser)
+    }
+    
+    /// Read the sockets and their owners
+    fn load(&mut self) -> io::Result<()> {
+        let processes = process_info::list_processes()?;
+        self.session_pids = process_info::ancestors(&processes, std::process::id());
+        self.processes = processes.into_iter().map(|process| (process.pid, process)).collect();
+        
+        let mut connections = process_info::connections();
+        // Listening sockets first by port, then connections by local port
+        connections.sort_by_key(|connection| {
+            let socket = &connection.socket;
+            (!socket.is_listening(), socket.local_port, socket.remote_port, connection.pid)
+        });
+        self.connections = connections;
+        self.last_refresh = Instant::now();
+        
+        let listening = self.connections.iter().filter(|connection| connection.socket.is_listening()).count();
+        let unowned = self.connections.iter().filter(|connection| connection.pid.is_none()).count();
+        self.notifications.set_status(if unowned > 0 {
+            format!("{} listening, {} connected • {} without a known owner", listening, self.connections.len() - listening, unowned)
+        } else {
+            format!("{} listening, {} connected", listening, self.connections.len() - listening)
+        });
+        Ok(())
+    }
+    
+    /// Reload the sockets while keeping the same one selected
+    fn refresh(&mut self) -> io::Result<()> {
+        let selected = self.current().map(|connection| (connection.pid, connection.socket.clone()));
+        
+        self.load()?;
+        self.update_filter();
+        
+        if let Some((pid, socket)) = selected {
+            let index = self.filtered.iter().position(|index| {
+                let connection = &self.connections[*index];
+                connection.pid == pid
+                    && connection.socket.protocol == socket.protocol
+                    && connection.socket.local_port == socket.local_port
+                    && connection.socket.remote_addr == socket.remote_addr
+                    && connection.socket.remote_port == socket.remote_port
+            });
+            if let Some(index) = index {
+                self.list_state.select(Some(index));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Refresh once the interval has elapsed; returns whether it did
+    fn auto_refresh(&mut self) -> bool {
+        if self.paused || !matches!(self.mode, Mode::Normal) || self.last_refresh.elapsed() < self.refresh_interval {
+            return false;
+        }
+        
+        if let Err(e) = self.refresh() {
+            self.notifications.error(format!("Refresh failed: {}", e));
+            self.last_refresh = Instant::now();
+        }
+        true
+    }
+    
+    fn owner(&self, connection: &Connection) -> Option<&ProcessInfo> {
+        connection.pid.and_then(|pid| self.processes.get(&pid))
+    }
+    
+    /// Whether `connection` matches the filter text
+    fn matches(&self, connection: &Connection, query: &str) -> bool {
+        let socket = &connection.socket;
+        let owner = self.owner(connection);
+        query.is_empty()
+            || endpoint(&socket.local_addr, socket.local_port).contains(query)
+            || endpoint(&socket.remote_addr, socket.remote_port).contains(query)
+            || socket.protocol.to_string().to_lowercase().contains(query)
+            || socket.state.to_lowercase().contains(query)
+            || connection.pid.is_some_and(|pid| pid.to_string().contains(query))
+            || owner.is_some_and(|process| process.name.to_lowercase().contains(query) || process.user.to_lowercase().contains(query))
+    }
+    
+    /// Recompute the visible sockets from the view and filter
+    fn update_filter(&mut self) {
+        let query = self.query.value().to_lowercase();
+        self.filtered = (0..self.connections.len())
+            .filter(|index| {
+                let connection = &self.connections[*index];
+                self.view.shows(connection) && self.matches(connection, &query)
+            })
+            .collect();
+        
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state.select(match self.filtered.len() {
+            0 => None,
+            len => Some(selected.min(len - 1)),
+        });
+    }
+    
+    fn current(&self) -> Option<&Connection> {
+        let selected = self.list_state.selected()?;
+        self.filtered.get(selected).map(|index| &self.connections[*index])
+    }
+    
+    /// Why a process should not be killed casually, if it is protected
+    fn protection_reason(&self, process: &ProcessInfo) -> Option<String> {
+        if self.session_pids.contains(&process.pid) {
+            Some("it is an ancestor of this terminal session".to_string())
+        } else if self.protected_names.contains(&process.name) {
+            Some(format!("'{}' is on the protected process list", process.name))
+        } else {
+            None
+        }
+    }
+    
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if matches!(self.mode, Mode::ConfirmKill { .. }) {
+            return self.handle_confirmation_input(key.code);
+        }
+        
+        match self.vim.filter_key(&mut self.list_state, key.code, key.modifiers, self.filtered.len(), self.settings.page_size) {
+            NavKey::Moved | NavKey::Used => return Ok(()),
+            NavKey::Unused => {}
+        }
+        let letters = !self.vim.inserting() && !key.modifiers.contains(KeyModifiers::CONTROL);
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('r') if letters => {
+                self.refresh()?;
+                self.notifications.info("Sockets refreshed");
+            }
+            KeyCode::Char('p') if letters => {
+                self.paused = !self.paused;
+                self.last_refresh = Instant::now();
+                self.notifications.info(if self.paused {
+                    "Auto-refresh paused".to_string()
+                } else {
+                    format!("Auto-refresh every {}s", self.refresh_interval.as_secs())
+                });
+            }
+            KeyCode::Char('y') if letters => {
+                if let Some(socket) = self.current().map(|connection| &connection.socket) {
+                    let address = endpoint(&socket.local_addr, socket.local_port);
+                    clipboard::copy_and_notify(&mut self.notifications, "the address", &address);
+                }
+            }
+            KeyCode::Tab => {
+                self.view = self.view.next();
+                self.update_filter();
+            }
+            KeyCode::Enter => self.confirm_kill(),
+            code if !key.modifiers.contains(KeyModifiers::CONTROL) && self.vim.inserting() && self.query.handle_key(code) => {
+                self.update_filter();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// Ask before killing the process that owns the selected socket
+    fn confirm_kill(&mut self) {
+        let Some(connection) = self.current() else {
+            return;
+        };
+        let Some(process) = self.owner(connection).cloned() else {
+            self.notifications.error("The owner of this socket is not known (try running as root)");
+            return;
+        };
+        let reason = self.protection_reason(&process);
+        let confirmations_left = if reason.is_some() { 2 } else { 1 };
+        self.mode = Mode::ConfirmKill { process, reason, confirmations_left };
+    }
+    
+    fn handle_confirmation_input(&mut self, key_code: KeyCode) -> io::Result<()> {
+        let Mode::ConfirmKill { process, confirmations_left, .. } = &mut self.mode else {
+            return Ok(());
+        };
+        match key_code {
+            // Protected processes must be confirmed explicitly with Y, twice
+            KeyCode::Enter if *confirmations_left > 1 => {}
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                *confirmations_left -= 1;
+                if *confirmations_left > 0 {
+                    return Ok(());
+                }
+                let pid = process.pid;
+                self.mode = Mode::Normal;
+                match process_info::send_signal(pid, "TERM") {
+                    Ok(()) => {
+                        self.notifications.success(format!("Process {} killed successfully", pid));
+                        self.refresh()?;
+                    }
+                    Err(e) => self.notifications.error(format!("Failed to kill process {}: {}", pid, e)),
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                self.notifications.info("Kill cancelled");
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(4), Constraint::Length(1)])
+            .split(area);
+        
+        self.render_filter(f, chunks[0]);
+        self.render_list(f, chunks[1]);
+        self.render_owner(f, chunks[2]);
+        self.render_status_bar(f, chunks[3]);
+        
+        if let Mode::ConfirmKill { process, reason, confirmations_left } = &self.mode {
+            let mut lines = vec![
+                Line::from(Span::styled(
+                    format!("Kill process {} (PID {})?", process.name, process.pid),
+                    Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD),
+                )),
+                Line::from(""),
+            ];
+            let width = (area.width.saturating_mul(3) / 5).saturating_sub(2) as usize;
+            let command = format!("Command: {}", process.command);
+            lines.extend(
+                text_width::wrap(&command, width)
+                    .into_iter()
+                    .take(3)
+                    .map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(colors::secondary())))),
+            );
+            lines.push(Line::from(""));
+            let prompt = match reason {
+                Some(reason) => {
+                    let warning = Style::default().fg(colors::text()).bg(colors::danger()).add_modifier(Modifier::BOLD);
+                    lines.insert(0, Line::from(Span::styled(format!(" ⚠ PROTECTED: {} ", reason), warning)));
+                    if *confirmations_left > 1 { "Press Y twice to kill • [N]o" } else { "Press Y again to kill • [N]o" }
+                }
+                None => "[Y]es / [N]o",
+            };
+            lines.push(Line::from(Span::styled(prompt, Style::default().fg(colors::text()).add_modifier(Modifier::BOLD))));
+            tui_common::render_message_popup(f, "Confirm Kill", lines, colors::danger());
+        }
+    }
+    
+    fn render_filter(&self, f: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.query.line())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Filter")
+                .border_style(Style::default().fg(colors::secondary())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    fn render_list(&mut self, f: &mut Frame, area: Rect) {
+        let items: Vec<Line> = self.filtered
+            .iter()
+            .map(|index| {
+                let connection = &self.connections[*index];
+                let socket = &connection.socket;
+                let state_color = match socket.state.as_str() {
+                    _ if socket.is_listening() => colors::success(),
+                    "ESTABLISHED" => colors::text(),
+                    "CLOSE_WAIT" | "SYN_SENT" => colors::warning(),
+                    _ => colors::muted(),
+                };
+                let pid = connection.pid.map(|pid| pid.to_string()).unwrap_or_else(|| "-".to_string());
+                let name = self.owner(connection).map(|process| process.name.as_str()).unwrap_or("");
+                Line::from(vec![
+                    Span::styled(format!("{:<5}", socket.protocol.to_string()), Style::default().fg(colors::text())),
+                    Span::raw(" "),
+                    Span::styled(
+                        text_width::pad(&endpoint(&socket.local_addr, socket.local_port), 28),
+                        Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        text_width::pad(&endpoint(&socket.remote_addr, socket.remote_port), 28),
+                        Style::default().fg(colors::text()),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(text_width::pad(&socket.state, 11), Style::default().fg(state_color)),
+                    Span::raw(" "),
+                    Span::styled(format!("{:>8}", pid), Style::default().fg(colors::secondary())),
+                    Span::raw("  "),
+                    Span::styled(name.to_string(), Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)),
+                ])
+            })
+            .collect();
+        
+        let refresh_state = if self.paused {
+            "⏸ paused".to_string()
+        } else {
+            format!("↻ {}s", self.refresh_interval.as_secs())
+        };
+        let title = format!("{} ({}) - {}", self.view.label(), self.filtered.len(), refresh_state);
+        
+        let header = Line::from(Span::styled(
+            format!("{:<5} {:<28} {:<28} {:<11} {:>8}  {}", "PROTO", "LOCAL", "REMOTE", "STATE", "PID", "PROCESS"),
+            Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD),
+        ));
+        
+        let list = SelectableList::new(items)
+            .header(header)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    /// The process owning the selected socket
+    fn render_owner(&self, f: &mut Frame, area: Rect) {
+        let lines = match self.current() {
+            Some(connection) => match self.owner(connection) {
+                Some(process) => vec![
+                    Line::from(vec![
+                        Span::styled(process.name.clone(), Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)),
+                        Span::styled(
+                            format!("  PID {} • User {} • {}", process.pid, process.user, process.state.label()),
+                            Style::default().fg(colors::text()),
+                        ),
+                    ]),
+                    Line::from(Span::styled(process.command.clone(), Style::default().fg(colors::muted()))),
+                ],
+                None => vec![Line::from(Span::styled(
+                    match connection.pid {
+                        Some(pid) => format!("PID {} (exited)", pid),
+                        None => "Owner unknown: another user's socket, or one closing down".to_string(),
+                    },
+                    Style::default().fg(colors::muted()),
+                ))],
+            },
+            None => vec![Line::from(Span::styled("No socket selected", Style::default().fg(colors::muted())))],
+        };
+        
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Owner")
+                .border_style(Style::default().fg(colors::muted())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let help_text = if matches!(self.mode, Mode::ConfirmKill { .. }) {
+            "Y/Enter Confirm • N/Esc Cancel"
+        } else if self.vim.inserting() {
+            "Type to filter • Tab View • Enter Kill owner • Esc Normal mode • F1 Help"
+        } else {
+            "j/k Move • / Filter • Tab View • Enter Kill owner • y Copy • r Refresh • ? Help • q Quit"
+        };
+        
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the network browser, from where it was left in this directory
+    pub fn run(&mut self) -> io::Result<()> {
+        session::resume(self, "net", tui_common::run_app)
+    }
+    
+    /// Print the visible sockets instead of running the TUI
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        plain::print(format, self.filtered.iter().map(|index| {
+            let connection = &self.connections[*index];
+            let socket = &connection.socket;
+            vec![
+                ("protocol", Value::from(socket.protocol.to_string())),
+                ("local_address", Value::from(socket.local_addr.as_str())),
+                ("local_port", Value::from(socket.local_port)),
+                ("remote_address", Value::from(socket.remote_addr.as_str())),
+                ("remote_port", Value::from(socket.remote_port)),
+                ("state", Value::from(socket.state.as_str())),
+                ("pid", connection.pid.map_or(Value::Null, Value::from)),
+                ("name", self.owner(connection).map_or(Value::Null, |process| Value::from(process.name.as_str()))),
+            ]
+        }))
+    }
+}
+
+impl App for NetBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick if self.auto_refresh() => return Ok(Action::Redraw),
+            AppEvent::Tick => {}
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        self.vim.inserting() && matches!(self.mode, Mode::Normal)
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn context(&self) -> Context {
+        Context {
+            process: self.current().and_then(|connection| self.owner(connection)).map(|process| process.name.clone()),
+            ..Context::default()
+        }
+    }
+}
+
+impl Resume for NetBrowser {
+    /// The filter, and the selected socket by its protocol and local end
+    fn session(&self) -> Session {
+        Session {
+            query: Some(self.query.value().to_string()),
+            selected: self.current().map(socket_key),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query.filter(|_| self.query.value().is_empty()) {
+            self.query = TextInput::new(query);
+            self.update_filter();
+        }
+        let position = session.selected.and_then(|selected| {
+            self.filtered.iter().position(|index| socket_key(&self.connections[*index]) == selected)
+        });
+        if position.is_some() {
+            self.list_state.select(position);
+        }
+    }
+}
+
+/// `TCP 127.0.0.1:8080`, to find a socket again in a later run
+fn socket_key(connection: &Connection) -> String {
+    let socket = &connection.socket;
+    format!("{} {}", socket.protocol, endpoint(&socket.local_addr, socket.local_port))
+}
+
+/// Run the network browser, or print the sockets in `output`
+pub fn run(filter: Option<String>, view: View, interval: u64, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut browser = NetBrowser::new(filter, view, interval, settings)?;
+    match output {
+        Some(format) => browser.print(format),
+        None => browser.run(),
+    }
+}
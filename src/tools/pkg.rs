@@ -0,0 +1,598 @@
+//! Package search and info browser for apt, dnf, pacman and Homebrew, with
+//! install and remove.
+
+use crate::clipboard;
+use crate::config::Settings;
+use crate::fuzzy;
+use crate::notify::Notifications;
+use crate::packages::{self, Info, Manager, Package, PackageAction};
+use crate::plain::{self, Format};
+use crate::platform;
+use crate::session::{self, Resume, Session};
+use crate::text_width;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Debounce, Keymap, NavKey, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph, Wrap},
+    Frame,
+};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Fuzzy search package names, then descriptions"),
+    ("Backspace", "Delete a search character"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Tab", "Show all packages or installed ones"),
+    ("Enter", "Install the package, or remove it when installed"),
+    ("R", "Reload the package lists (normal mode)"),
+    ("y", "Copy the package name (normal mode)"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Matches listed at a time; a search narrows the rest down
+const MAX_SHOWN: usize = 1000;
+
+/// Description matches rank below every name match
+const DESCRIPTION_SCORE: i64 = i64::MIN / 2;
+
+/// Which packages are listed, toggled with Tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    All,
+    Installed,
+}
+
+enum Mode {
+    Normal,
+    /// Asking before a package is installed or removed
+    Confirm(PackageAction, String),
+}
+
+pub struct PackageBrowser {
+    manager: Manager,
+    /// Sorted by name
+    packages: Vec<Package>,
+    /// Indices into `packages` matching the view and search, best first,
+    /// with the matched characters of the name
+    filtered: Vec<(usize, Vec<usize>)>,
+    query: TextInput,
+    filter_debounce: Debounce,
+    view: View,
+    mode: Mode,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    /// Package lists still being read in the background, and since when
+    load_rx: Option<Receiver<io::Result<Vec<Package>>>>,
+    loading_since: Instant,
+    /// Details by package name, once looked up; errors as text
+    infos: HashMap<String, Result<Info, String>>,
+    pending_info: HashSet<String>,
+    info_debounce: Debounce,
+    info_tx: Sender<(String, Result<Info, String>)>,
+    info_rx: Receiver<(String, Result<Info, String>)>,
+    /// An install or remove to run on the terminal
+    pending_command: Option<(String, String)>,
+    /// Package selected when the tool was left, to select again once the
+    /// lists are read
+    resume_selected: Option<String>,
+    settings: Settings,
+}
+
+impl PackageBrowser {
+    pub fn new(manager: Option<Manager>, query: Option<String>, view: View, settings: Settings) -> io::Result<Self> {
+        let manager = manager.or_else(Manager::detect).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no supported package manager found (apt, dnf, pacman or brew)")
+        })?;
+        let (info_tx, info_rx) = mpsc::channel();
+        let mut browser = PackageBrowser {
+            manager,
+            packages: Vec::new(),
+            filtered: Vec::new(),
+            query: TextInput::new(query.unwrap_or_default()),
+            filter_debounce: Debounce::default(),
+            view,
+            mode: Mode::Normal,
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::with_status(format!("Reading {} package lists...", manager.name())),
+            load_rx: None,
+            loading_since: Instant::now(),
+            infos: HashMap::new(),
+            pending_info: HashSet::new(),
+            info_debounce: Debounce::default(),
+            info_tx,
+            info_rx,
+            pending_command: None,
+            resume_selected: None,
+            settings,
+        };
+        browser.reload();
+        Ok(browser)
+    }
+    
+    /// Read the package lists again in the background
+    fn reload(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        let manager = self.manager;
+        thread::spawn(move || {
+            let _ = tx.send(packages::list(manager));
+        });
+        self.load_rx = Some(rx);
+        self.loading_since = Instant::now();
+    }
+    
+    /// Take the lists once read; returns whether they came
+    fn poll_load(&mut self) -> bool {
+        let Some(rx) = &self.load_rx else {
+            return false;
+        };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return false,
+            Err(TryRecvError::Disconnected) => Err(io::Error::other("the listing stopped")),
+        };
+        self.load_rx = None;
+        match result {
+            Ok(packages) => {
+                let selected = self.current().map(|package| package.name.clone()).or_else(|| self.resume_selected.take());
+                self.packages = packages;
+                self.update_filter();
+                if let Some(index) = selected.and_then(|name| self.filtered.iter().position(|(index, _)| self.packages[*index].name == name)) {
+                    self.list_state.select(Some(index));
+                }
+                self.set_summary();
+            }
+            Err(e) => {
+                self.notifications.set_status(format!("{} packages", self.manager.name()));
+                self.notifications.error(format!("Could not list {} packages: {}", self.manager.name(), e));
+            }
+        }
+        true
+    }
+    
+    fn set_summary(&mut self) {
+        let installed = self.packages.iter().filter(|package| package.installed).count();
+        self.notifications.set_status(format!("{} • {} packages • {} installed", self.manager.name(), self.packages.len(), installed));
+    }
+    
+    /// Recompute the listed packages from the view and search
+    fn update_filter(&mut self) {
+        self.filter_debounce.take();
+        let query = self.query.value().trim().to_string();
+        let lowercase = query.to_lowercase();
+        let mut scored: Vec<(i64, usize, Vec<usize>)> = self.packages
+            .iter()
+            .enumerate()
+            .filter(|(_, package)| self.view == View::All || package.installed)
+            .filter_map(|(index, package)| {
+                if query.is_empty() {
+                    return Some((0, index, Vec::new()));
+                }
+                match fuzzy::match_positions(&query, &package.name) {
+                    Some((score, positions)) => Some((score, index, positions)),
+                    None => package.description.to_lowercase().contains(&lowercase).then(|| (DESCRIPTION_SCORE, index, Vec::new())),
+                }
+            })
+            .collect();
+        // Shorter names first among equal scores, so `vim` comes before
+        // `vim-gtk3`; without a search the list stays alphabetical
+        let packages = &self.packages;
+        if !query.is_empty() {
+            scored.sort_by(|a, b| b.0.cmp(&a.0).then(packages[a.1].name.len().cmp(&packages[b.1].name.len())).then(a.1.cmp(&b.1)));
+        }
+        self.filtered = scored.into_iter().map(|(_, index, positions)| (index, positions)).collect();
+        self.list_state.select(if self.filtered.is_empty() { None } else { Some(0) });
+        self.info_debounce.schedule();
+    }
+    
+    /// Matches in the list, at most [`MAX_SHOWN`]
+    fn shown(&self) -> usize {
+        self.filtered.len().min(MAX_SHOWN)
+    }
+    
+    fn current(&self) -> Option<&Package> {
+        let selected = self.list_state.selected()?;
+        self.filtered.get(selected).map(|(index, _)| &self.packages[*index])
+    }
+    
+    fn is_installed(&self, name: &str) -> bool {
+        self.packages
+            .binary_search_by(|package| package.name.as_str().cmp(name))
+            .is_ok_and(|index| self.packages[index].installed)
+    }
+    
+    /// Look up the details of the selected package in the background
+    fn request_info(&mut self) {
+        self.info_debounce.take();
+        let Some(name) = self.current().map(|package| package.name.clone()) else {
+            return;
+        };
+        if self.infos.contains_key(&name) || !self.pending_info.insert(name.clone()) {
+            return;
+        }
+        let (tx, manager) = (self.info_tx.clone(), self.manager);
+        thread::spawn(move || {
+            let info = packages::info(manager, &name).map_err(|e| e.to_string());
+            let _ = tx.send((name, info));
+        });
+    }
+    
+    /// Collect finished lookups; returns whether any came
+    fn poll_info(&mut self) -> bool {
+        let mut received = false;
+        while let Ok((name, info)) = self.info_rx.try_recv() {
+            self.pending_info.remove(&name);
+            self.infos.insert(name, info);
+            received = true;
+        }
+        received
+    }
+    
+    fn handle_key(&mut self, key: KeyEvent) {
+        if let Mode::Confirm(action, name) = &self.mode {
+            let (action, name) = (*action, name.clone());
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                    self.mode = Mode::Normal;
+                    let command = action.command(self.manager, &name);
+                    self.pending_command = Some((name, command));
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    self.mode = Mode::Normal;
+                    self.notifications.info(format!("{} cancelled", action.verb()));
+                }
+                _ => {}
+            }
+            return;
+        }
+        
+        if !self.vim.typing(&key) && self.filter_debounce.take() {
+            self.update_filter();
+        }
+        let shown = self.shown();
+        match self.vim.filter_key(&mut self.list_state, key.code, key.modifiers, shown, self.settings.page_size) {
+            NavKey::Moved => {
+                self.info_debounce.schedule();
+                return;
+            }
+            NavKey::Used => return,
+            NavKey::Unused => {}
+        }
+        let letters = !self.vim.inserting() && !key.modifiers.contains(KeyModifiers::CONTROL);
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('R') if letters => {
+                self.infos.clear();
+                self.reload();
+            }
+            KeyCode::Char('y') if letters => {
+                if let Some(name) = self.current().map(|package| package.name.clone()) {
+                    clipboard::copy_and_notify(&mut self.notifications, "the package name", &name);
+                }
+            }
+            KeyCode::Tab => {
+                self.view = match self.view {
+                    View::All => View::Installed,
+                    View::Installed => View::All,
+                };
+                self.update_filter();
+            }
+            KeyCode::Enter => {
+                if let Some(package) = self.current() {
+                    let action = if package.installed { PackageAction::Remove } else { PackageAction::Install };
+                    self.mode = Mode::Confirm(action, package.name.clone());
+                }
+            }
+            code if !key.modifiers.contains(KeyModifiers::CONTROL) && self.vim.inserting() && self.query.handle_key(code) => {
+                self.filter_debounce.schedule();
+            }
+            _ => {}
+        }
+    }
+    
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[1]);
+        
+        self.render_search(f, chunks[0]);
+        self.render_list(f, panes[0]);
+        self.render_info(f, panes[1]);
+        self.render_status_bar(f, chunks[2]);
+        
+        if let Mode::Confirm(action, name) = &self.mode {
+            let mut verb = action.verb().to_string();
+            verb[..1].make_ascii_uppercase();
+            let lines = vec![
+                Line::from(Span::styled(
+                    format!("{} {}?", verb, name),
+                    Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD),
+                )),
+                Line::from(""),
+                Line::from(Span::styled(
+                    format!("$ {}", action.command(self.manager, name)),
+                    Style::default().fg(colors::secondary()),
+                )),
+                Line::from(""),
+                Line::from(Span::styled("[Y]es / [N]o", Style::default().fg(colors::text()).add_modifier(Modifier::BOLD))),
+            ];
+            let border = match action {
+                PackageAction::Remove => colors::danger(),
+                PackageAction::Install => colors::warning(),
+            };
+            tui_common::render_message_popup(f, "Confirm", lines, border);
+        }
+    }
+    
+    fn render_search(&self, f: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.query.line())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Search {} packages", self.manager.name()))
+                .border_style(Style::default().fg(colors::secondary())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    fn render_list(&mut self, f: &mut Frame, area: Rect) {
+        let shown = &self.filtered[..self.shown()];
+        let name_width = shown
+            .iter()
+            .map(|(index, _)| text_width::width(&self.packages[*index].name))
+            .max()
+            .unwrap_or(0)
+            .clamp(12, 28);
+        let highlight = Style::default().fg(colors::warning()).add_modifier(Modifier::BOLD);
+        
+        let items: Vec<Line> = shown
+            .iter()
+            .map(|(index, positions)| {
+                let package = &self.packages[*index];
+                let (marker, marker_style) = if package.installed {
+                    ("✓ ", Style::default().fg(colors::success()))
+                } else {
+                    ("  ", Style::default())
+                };
+                let name = text_width::pad(&text_width::truncate(&package.name, name_width), name_width);
+                let mut spans = vec![Span::styled(marker, marker_style)];
+                spans.extend(tui_common::highlight_spans(&name, positions, highlight));
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(package.description.clone(), Style::default().fg(colors::text())));
+                // The name takes the line's color where it is not highlighted
+                Line::from(spans).style(Style::default().fg(colors::primary()))
+            })
+            .collect();
+        
+        let view = match self.view {
+            View::All => "Packages",
+            View::Installed => "Installed",
+        };
+        let mut title = if self.filtered.len() > shown.len() {
+            format!("{} ({} of {})", view, shown.len(), self.filtered.len())
+        } else {
+            format!("{} ({})", view, self.filtered.len())
+        };
+        if self.load_rx.is_some() {
+            let frame = (self.loading_since.elapsed().as_millis() / 80) as usize % SPINNER.len();
+            title.push_str(&format!(" - {} Loading...", SPINNER[frame]));
+        }
+        
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    /// Details and dependencies of the selected package
+    fn render_info(&self, f: &mut Frame, area: Rect) {
+        let muted = Style::default().fg(colors::muted());
+        let Some(package) = self.current() else {
+            let block = Block::default().borders(Borders::ALL).title("Info").border_style(muted);
+            f.render_widget(Paragraph::new("").block(block), area);
+            return;
+        };
+        
+        let mut lines = Vec::new();
+        match self.infos.get(&package.name) {
+            None => lines.push(Line::from(Span::styled("Loading...", muted))),
+            Some(Err(e)) => lines.push(Line::from(Span::styled(format!("No details: {}", e), Style::default().fg(colors::danger())))),
+            Some(Ok(info)) => {
+                // Dependencies go first, on wrapped lines, since the details
+                // can run longer than the pane
+                let mut spans = vec![Span::styled(
+                    format!("Dependencies ({}):", info.dependencies.len()),
+                    Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD),
+                )];
+                for dependency in &info.dependencies {
+                    spans.push(Span::raw(" "));
+                    spans.push(if self.is_installed(dependency) {
+                        Span::styled(format!("✓{}", dependency), Style::default().fg(colors::success()))
+                    } else {
+                        Span::styled(dependency.clone(), Style::default().fg(colors::text()))
+                    });
+                }
+                lines.push(Line::from(spans));
+                lines.push(Line::from(""));
+                for line in &info.lines {
+                    // `Key: value` lines get their key colored
+                    let key = line
+                        .split_once(':')
+                        .filter(|(key, _)| !line.starts_with(char::is_whitespace) && !key.is_empty() && key.len() <= 20 && !key.contains("://"));
+                    lines.push(match key {
+                        Some((key, value)) => Line::from(vec![
+                            Span::styled(format!("{}:", key), Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD)),
+                            Span::styled(value.to_string(), Style::default().fg(colors::text())),
+                        ]),
+                        None if line.starts_with("==>") => Line::from(Span::styled(line.clone(), Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD))),
+                        None => Line::from(Span::styled(line.clone(), Style::default().fg(colors::text()))),
+                    });
+                }
+            }
+        }
+        
+        let title = match (package.installed, package.version.is_empty()) {
+            (true, _) => format!("{} {} (installed)", package.name, package.version),
+            (false, false) => format!("{} {}", package.name, package.version),
+            (false, true) => package.name.clone(),
+        };
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(muted));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let help_text = if matches!(self.mode, Mode::Confirm(..)) {
+            "Y/Enter Confirm • N/Esc Cancel"
+        } else if self.vim.inserting() {
+            "Type to search • Tab All/Installed • Enter Install/Remove • Esc Normal mode • F1 Help"
+        } else {
+            "Enter Install/Remove • Tab All/Installed • R Reload • y Copy • / Search • ? Help • q Quit"
+        };
+        
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the package browser, from where it was left in this directory
+    pub fn run(&mut self) -> io::Result<()> {
+        session::resume(self, "pkg", tui_common::run_app)
+    }
+    
+    /// Print the matching packages instead of running the TUI, once the
+    /// lists have been read
+    pub fn print(&mut self, format: Format) -> io::Result<()> {
+        while self.load_rx.is_some() {
+            if !self.poll_load() {
+                thread::sleep(Duration::from_millis(50));
+            }
+        }
+        plain::check(&self.notifications)?;
+        plain::print(format, self.filtered.iter().map(|(index, _)| {
+            let package = &self.packages[*index];
+            vec![
+                ("name", Value::from(package.name.as_str())),
+                ("version", Value::from(package.version.as_str())),
+                ("installed", Value::from(package.installed)),
+                ("description", Value::from(package.description.as_str())),
+            ]
+        }))
+    }
+}
+
+impl App for PackageBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key),
+            AppEvent::Tick => {
+                if self.filter_debounce.ready() {
+                    self.update_filter();
+                }
+                if self.info_debounce.ready() {
+                    self.request_info();
+                }
+                // The spinner turns while the lists load
+                if self.poll_load() | self.poll_info() || self.load_rx.is_some() {
+                    return Ok(Action::Redraw);
+                }
+            }
+        }
+        Ok(if self.should_quit {
+            Action::Quit
+        } else if self.pending_command.is_some() {
+            Action::Suspend
+        } else {
+            Action::Continue
+        })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        self.vim.inserting() && matches!(self.mode, Mode::Normal)
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn suspended(&mut self) -> io::Result<Action> {
+        if let Some((name, command)) = self.pending_command.take() {
+            match tui_common::run_and_wait(&command, &platform::user_shell(), "tt pkg") {
+                Ok(quit) => self.should_quit = quit,
+                Err(e) => self.notifications.error(format!("Could not run {}: {}", command, e)),
+            }
+            self.infos.remove(&name);
+            self.reload();
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+}
+
+impl Resume for PackageBrowser {
+    fn session(&self) -> Session {
+        let selected = self.current().map(|package| package.name.clone());
+        Session {
+            query: Some(self.query.value().to_string()),
+            selected: selected.or_else(|| self.resume_selected.clone()),
+            ..Session::default()
+        }
+    }
+    
+    /// Takes the search now and the selection once the lists are read
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query.filter(|_| self.query.value().is_empty()) {
+            self.query = TextInput::new(query);
+        }
+        self.resume_selected = session.selected;
+    }
+}
+
+/// Run the package browser over the packages of `manager` (detected when
+/// not given), or print the matches in `output`
+pub fn run(manager: Option<Manager>, query: Option<String>, view: View, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut browser = PackageBrowser::new(manager, query, view, settings)?;
+    match output {
+        Some(format) => browser.print(format),
+        None => browser.run(),
+    }
+}
@@ -1,48 +1,121 @@
 //! Recent files browser with MRU tracking.
 
-use crate::tui_common::{self, colors};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crate::config::Settings;
+use crate::editor_history::{self, Source};
+use crate::notify::Notifications;
+use crate::palette::Context;
+use crate::plain::{self, Format};
+use crate::preview::{Preview, PreviewEngine};
+use crate::session::{self, Resume, Session};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, NavKey, SelectableList, TextInput, VimNav};
+use crate::modified_files::{self, ScanEvent};
+use crate::{frecency, fuzzy, mru, platform, time_util};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
-    text::Line,
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
-    Frame, Terminal,
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph},
+    Frame,
 };
+use serde_json::Value;
 use std::{
     env,
-    fs,
     io,
     path::{Path, PathBuf},
-    process::Command,
+    sync::mpsc::{Receiver, TryRecvError},
+    thread,
     time::Duration,
 };
 
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Filter files"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Enter", "Open in the editor"),
+    ("Ctrl-T", "Pin or unpin"),
+    ("Delete, Ctrl-D while typing", "Remove from the list"),
+    ("Ctrl-X", "Remove missing files"),
+    ("Esc / q", "Clear the filter, then quit (normal mode)"),
+];
+
 #[derive(Debug, Clone)]
 pub struct RecentFile {
     pub path: PathBuf,
     pub display_name: String,
+    /// Histories that list the file; empty for recently modified files
+    pub sources: Vec<Source>,
+    /// Kept at the top of the list
+    pub pinned: bool,
+    /// When the file was last opened through tt
+    pub last_opened: Option<i64>,
+    /// Frecency of the file's opens
+    pub score: f64,
+}
+
+impl RecentFile {
+    fn new(path: PathBuf, sources: Vec<Source>, pinned: bool) -> Self {
+        RecentFile {
+            display_name: path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            path,
+            sources,
+            pinned,
+            last_opened: None,
+            score: 0.0,
+        }
+    }
+    
+    /// Name and directory as listed, which the filter matches against
+    fn label(&self) -> String {
+        format!("{} ({})", self.display_name, self.path.parent().unwrap_or_else(|| Path::new("/")).display())
+    }
 }
 
 pub struct RecentFileBrowser {
     files: Vec<RecentFile>,
+    /// Indices into `files` matching the filter, best match first, with
+    /// the matched character positions of their labels
+    filtered: Vec<(usize, Vec<usize>)>,
+    filter: TextInput,
+    /// Search for recently modified files, when there is no history
+    scan: Option<Receiver<ScanEvent>>,
+    /// Selection within `filtered`
     list_state: ListState,
+    vim: VimNav,
     should_quit: bool,
-    status_message: String,
-    preview_content: String,
+    notifications: Notifications,
+    /// Preview of the selected file
+    preview: Option<Preview>,
+    previews: PreviewEngine,
+    /// Columns and rows inside the preview border at the last draw
+    preview_size: (u16, u16),
     limit: usize,
+    settings: Settings,
 }
 
 impl RecentFileBrowser {
     /// Create a new recent file browser
-    pub fn new(limit: usize) -> io::Result<Self> {
+    pub fn new(limit: usize, settings: Settings) -> io::Result<Self> {
         let mut browser = RecentFileBrowser {
             files: Vec::new(),
+            filtered: Vec::new(),
+            filter: TextInput::default(),
+            scan: None,
             list_state: ListState::default(),
+            vim: VimNav::default(),
             should_quit: false,
-            status_message: "Loading recent files...".to_string(),
-            preview_content: String::new(),
+            notifications: Notifications::with_status("Loading recent files..."),
+            preview: None,
+            previews: PreviewEngine::new(&settings),
+            preview_size: (0, 0),
             limit,
+            settings,
         };
         
         browser.load_recent_files()?;
@@ -52,309 +125,472 @@ impl RecentFileBrowser {
     
     /// Load recent files from various sources
     fn load_recent_files(&mut self) -> io::Result<()> {
-        // Try to load from our MRU file (like the bash version)
-        if let Ok(home) = env::var("HOME") {
-            let mru_file = PathBuf::from(home).join(".cache/fzf-mru.txt");
-            if let Ok(content) = fs::read_to_string(mru_file) {
-                for line in content.lines().rev().take(self.limit) {
-                    let path = PathBuf::from(line.trim());
-                    if path.exists() {
-                        self.files.push(RecentFile {
-                            display_name: path.file_name()
-                                .unwrap_or_default()
-                                .to_string_lossy()
-                                .to_string(),
-                            path,
-                        });
-                    }
-                }
-            } else {
-                // Fallback: find recently modified files in common directories
-                self.load_recently_modified_files()?;
-            }
-        }
+        // Files opened through tt and editors; the editors are asked at once
+        // since Neovim takes a moment to start
+        let lists: Vec<(Source, Vec<PathBuf>)> = thread::scope(|scope| {
+            let loads: Vec<_> = Source::ALL
+                .iter()
+                .map(|source| scope.spawn(move || (*source, source.load())))
+                .collect();
+            loads.into_iter().filter_map(|load| load.join().ok()).collect()
+        });
+        let mut recorded: Vec<(PathBuf, Vec<Source>)> = editor_history::merge(lists);
         
-        if !self.files.is_empty() {
-            self.list_state.select(Some(0));
-            self.update_preview();
+        // Files rank by how often and how lately tt opened them; a file only
+        // an editor remembers counts as opened once, long ago
+        let opens = mru::opens();
+        let now = time_util::now_secs();
+        let ranked = |path: PathBuf, sources: Vec<Source>, pinned: bool| {
+            let mut file = RecentFile::new(path, sources, pinned);
+            let (rank, last_access) = match opens.get(&file.path) {
+                Some(entry) => (entry.rank, entry.last_access),
+                None => (1.0, 0),
+            };
+            file.last_opened = opens.contains_key(&file.path).then_some(last_access);
+            file.score = frecency::frecency(rank, last_access, now);
+            file
+        };
+        
+        // Pinned files come first whatever their score; removed ones stay out
+        let hidden = mru::hidden();
+        for path in mru::pinned().into_iter().filter(|path| path.is_file()) {
+            let sources = match recorded.iter().position(|(known, _)| *known == path) {
+                Some(index) => recorded.remove(index).1,
+                None => Vec::new(),
+            };
+            self.files.push(ranked(path, sources, true));
+        }
+        let mut recorded: Vec<RecentFile> = recorded
+            .into_iter()
+            .filter(|(path, _)| path.is_file() && !hidden.contains(path))
+            .map(|(path, sources)| ranked(path, sources, false))
+            .collect();
+        // Equal scores keep the histories' newest-first order
+        recorded.sort_by(|a, b| b.score.total_cmp(&a.score));
+        recorded.truncate(self.limit);
+        self.files.extend(recorded);
+        self.apply_filter();
+        if self.files.is_empty() {
+            // Fallback: find recently modified files in common directories
+            self.start_scan();
+            return Ok(());
         }
         
-        self.status_message = format!("Found {} recent files", self.files.len());
+        let counts: Vec<String> = Source::ALL
+            .iter()
+            .map(|source| (source, self.files.iter().filter(|file| file.sources.contains(source)).count()))
+            .filter(|(_, count)| *count > 0)
+            .map(|(source, count)| format!("{} {}", source.badge(), count))
+            .collect();
+        self.notifications.set_status(if counts.is_empty() {
+            format!("Found {} recent files", self.files.len())
+        } else {
+            format!("Found {} recent files ({})", self.files.len(), counts.join(" · "))
+        });
         Ok(())
     }
     
-    /// Load recently modified files as fallback
-    fn load_recently_modified_files(&mut self) -> io::Result<()> {
-        let dirs = [
-            env::current_dir().unwrap_or_default(),
-            PathBuf::from(env::var("HOME").unwrap_or_default()),
-        ];
+    /// Look for recently modified files in the background
+    fn start_scan(&mut self) {
+        let dirs: Vec<PathBuf> = [env::current_dir().ok(), platform::home_dir()]
+            .into_iter()
+            .flatten()
+            .filter(|dir| dir.is_dir())
+            .collect();
+        self.scan = Some(modified_files::spawn_scan(dirs, self.limit));
+        self.notifications.set_status("Looking for recently modified files...");
+    }
+    
+    /// Drain events from a running scan
+    fn poll_scan(&mut self) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
         
-        for dir in dirs.iter() {
-            if dir.exists() {
-                // Use find command to get recently modified files
-                let output = Command::new("find")
-                    .args(&[
-                        dir.to_str().unwrap_or("."),
-                        "-type", "f",
-                        "-not", "-path", "*/.*",
-                        "-mtime", "-7",
-                        "-printf", "%T@ %p\n"
-                    ])
-                    .output();
-                
-                if let Ok(output) = output {
-                    if output.status.success() {
-                        let mut files_with_time: Vec<(f64, PathBuf)> = Vec::new();
-                        let find_output = String::from_utf8_lossy(&output.stdout);
-                        
-                        for line in find_output.lines() {
-                            if let Some(space_pos) = line.find(' ') {
-                                if let Ok(time) = line[..space_pos].parse::<f64>() {
-                                    let path = PathBuf::from(&line[space_pos + 1..]);
-                                    files_with_time.push((time, path));
-                                }
-                            }
-                        }
-                        
-                        // Sort by modification time (newest first)
-                        files_with_time.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-                        
-                        for (_, path) in files_with_time.into_iter().take(self.limit) {
-                            self.files.push(RecentFile {
-                                display_name: path.file_name()
-                                    .unwrap_or_default()
-                                    .to_string_lossy()
-                                    .to_string(),
-                                path,
-                            });
-                        }
-                        break; // Only need one directory to succeed
-                    }
+        loop {
+            match scan.try_recv() {
+                Ok(ScanEvent::Progress(scanned)) => {
+                    self.notifications.set_status(format!("Looking for recently modified files... {} checked", scanned));
+                }
+                Ok(ScanEvent::Finished { files, truncated }) => {
+                    self.files = files.into_iter().map(|path| RecentFile::new(path, Vec::new(), false)).collect();
+                    self.apply_filter();
+                    self.notifications.set_status(format!(
+                        "No history yet; {} files modified in the last {} days{}",
+                        self.files.len(),
+                        modified_files::MAX_AGE.as_secs() / 86_400,
+                        if truncated { " (search stopped early)" } else { "" }
+                    ));
+                    self.scan = None;
+                    return;
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.scan = None;
+                    return;
                 }
             }
         }
-        
-        Ok(())
     }
     
-    /// Update preview content
-    fn update_preview(&mut self) {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(file) = self.files.get(selected) {
-                self.preview_content = self.load_file_preview(&file.path);
+    /// Recompute the fuzzy matches for the filter and select the best one;
+    /// equally good matches keep the ranking order
+    fn apply_filter(&mut self) {
+        self.refilter();
+        self.list_state.select(if self.filtered.is_empty() { None } else { Some(0) });
+        self.update_preview();
+    }
+    
+    fn refilter(&mut self) {
+        let pattern = self.filter.value();
+        self.filtered = if pattern.is_empty() {
+            (0..self.files.len()).map(|index| (index, Vec::new())).collect()
+        } else {
+            let mut scored: Vec<(i64, usize, Vec<usize>)> = self.files
+                .iter()
+                .enumerate()
+                .filter_map(|(index, file)| {
+                    fuzzy::match_positions(pattern, &file.label())
+                        .map(|(score, positions)| (score, index, positions))
+                })
+                .collect();
+            scored.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+            scored.into_iter().map(|(_, index, positions)| (index, positions)).collect()
+        };
+    }
+    
+    /// Index into `files` of the selected entry
+    fn selected_index(&self) -> Option<usize> {
+        self.list_state.selected().and_then(|row| self.filtered.get(row)).map(|(index, _)| *index)
+    }
+    
+    fn selected_file(&self) -> Option<&RecentFile> {
+        self.selected_index().map(|index| &self.files[index])
+    }
+    
+    /// The selected file, for another tool to act on
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        self.selected_file().map(|file| file.path.clone())
+    }
+    
+    /// Filter again after the list changed, selecting `path` if it is still
+    /// shown, otherwise the entry at `row`
+    fn reselect(&mut self, path: &Path, row: usize) {
+        self.refilter();
+        let row = self.filtered.iter().position(|(index, _)| self.files[*index].path == path).unwrap_or(row);
+        if self.filtered.is_empty() {
+            self.list_state.select(None);
+            self.preview = None;
+        } else {
+            self.list_state.select(Some(row.min(self.filtered.len() - 1)));
+            self.update_preview();
+        }
+    }
+    
+    /// Pin the selected file to the top, or unpin it
+    fn toggle_pin(&mut self) {
+        let (Some(row), Some(index)) = (self.list_state.selected(), self.selected_index()) else {
+            return;
+        };
+        let file = &mut self.files[index];
+        let pin = !file.pinned;
+        let path = file.path.clone();
+        match mru::set_pinned(&path, pin) {
+            Ok(()) => {
+                file.pinned = pin;
+                // Pinned files first, each group keeping its order
+                self.files.sort_by_key(|file| !file.pinned);
+                self.reselect(&path, row);
+                self.notifications.success(format!("{} {}", if pin { "Pinned" } else { "Unpinned" }, path.display()));
             }
+            Err(e) => self.notifications.error(format!("Could not save pins: {}", e)),
         }
     }
     
-    /// Load file preview
-    fn load_file_preview(&self, path: &Path) -> String {
-        match fs::read_to_string(path) {
-            Ok(content) => {
-                let lines: Vec<&str> = content.lines().take(50).collect();
-                lines.join("\n")
+    /// Take the selected file off the recent list
+    fn remove_selected(&mut self) {
+        let (Some(row), Some(index)) = (self.list_state.selected(), self.selected_index()) else {
+            return;
+        };
+        let path = self.files[index].path.clone();
+        match mru::remove(&path) {
+            Ok(()) => {
+                self.files.remove(index);
+                self.reselect(&path, row);
+                self.notifications.success(format!("Removed {}", path.display()));
             }
-            Err(_) => {
-                if let Ok(metadata) = fs::metadata(path) {
-                    format!(
-                        "File: {}\nSize: {} bytes\nModified: {:?}\n\n[Binary file or read error]",
-                        path.display(),
-                        metadata.len(),
-                        metadata.modified().ok()
-                    )
-                } else {
-                    "[Could not read file]".to_string()
-                }
+            Err(e) => self.notifications.error(format!("Could not remove {}: {}", path.display(), e)),
+        }
+    }
+    
+    /// Forget every listed or pinned file that no longer exists
+    fn purge_missing(&mut self) {
+        let selected = self.selected_file().map(|file| file.path.clone()).unwrap_or_default();
+        let row = self.list_state.selected().unwrap_or(0);
+        match mru::purge_missing() {
+            Ok(count) => {
+                self.files.retain(|file| file.path.exists());
+                self.reselect(&selected, row);
+                self.notifications.success(format!("Removed {} missing files", count));
             }
+            Err(e) => self.notifications.error(format!("Could not clean the recent list: {}", e)),
         }
     }
     
+    /// Update preview content
+    fn update_preview(&mut self) {
+        let (columns, rows) = self.preview_size;
+        self.preview = match self.selected_file().map(|file| file.path.clone()) {
+            Some(path) => Some(self.previews.preview(&path, columns, rows)),
+            None => None,
+        };
+    }
+    
     /// Open selected file
     fn open_file(&mut self) -> io::Result<()> {
-        if let Some(selected) = self.list_state.selected() {
-            if let Some(file) = self.files.get(selected) {
-                let editors = ["nvim", "vim", "nano", "code"];
-                
-                for editor in editors.iter() {
-                    let result = Command::new(editor)
-                        .arg(&file.path)
-                        .status();
-                        
-                    if result.is_ok() {
-                        self.should_quit = true;
-                        return Ok(());
-                    }
-                }
-                
-                println!("{}", file.path.display());
-                self.should_quit = true;
+        if let Some(path) = self.selected_file().map(|file| file.path.clone()) {
+            // Without an editor the path is printed instead
+            if !mru::open_in_editor(&self.settings, &path, None) {
+                println!("{}", path.display());
             }
+            self.should_quit = true;
         }
         Ok(())
     }
     
     /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page down
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.files.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page up
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.files.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected > 0 {
-                                self.list_state.select(Some(selected - 1));
-                                self.update_preview();
-                            }
-                        }
-                    }
-                    KeyCode::Down => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected + 1 < self.files.len() {
-                                self.list_state.select(Some(selected + 1));
-                                self.update_preview();
-                            }
-                        } else if !self.files.is_empty() {
-                            self.list_state.select(Some(0));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Enter => {
-                        self.open_file()?;
-                    }
-                    _ => {}
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        // Home and End move the cursor in the filter
+        let is_cursor_key = self.vim.inserting() && matches!(key.code, KeyCode::Home | KeyCode::End);
+        if !is_cursor_key {
+            match self.vim.filter_key(&mut self.list_state, key.code, key.modifiers, self.filtered.len(), self.settings.page_size) {
+                NavKey::Moved => {
+                    self.update_preview();
+                    return Ok(());
                 }
+                NavKey::Used => return Ok(()),
+                NavKey::Unused => {}
             }
         }
+        
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') if !self.vim.inserting() && !self.filter.value().is_empty() => {
+                self.filter = TextInput::default();
+                self.apply_filter();
+            }
+            KeyCode::Esc | KeyCode::Char('q') if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Enter => {
+                self.open_file()?;
+            }
+            KeyCode::Char('t') if ctrl => self.toggle_pin(),
+            KeyCode::Char('d') if ctrl => self.remove_selected(),
+            KeyCode::Delete => self.remove_selected(),
+            KeyCode::Char('x') if ctrl => self.purge_missing(),
+            code if !ctrl && self.vim.inserting() && self.filter.handle_key(code) => {
+                self.apply_filter();
+            }
+            _ => {}
+        }
         Ok(())
     }
     
     /// Render the recent files browser
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(f.area());
+            .split(area);
+        
+        let left = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(chunks[0]);
         
-        self.render_file_list(f, chunks[0]);
+        self.render_filter(f, left[0]);
+        self.render_file_list(f, left[1]);
         self.render_preview(f, chunks[1]);
-        self.render_status_bar(f);
+        self.render_status_bar(f, area);
     }
     
-    /// Render file list
+    /// Render the filter input
+    fn render_filter(&self, f: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.filter.line())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Filter")
+                .border_style(Style::default().fg(colors::secondary())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    /// Render file list, highlighting the characters the filter matched
     fn render_file_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.files
+        let highlight = Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD);
+        let now = time_util::now_secs();
+        let items: Vec<Line> = self.filtered
             .iter()
-            .map(|file| {
-                let line = Line::from(format!("{} ({})", 
-                    file.display_name,
-                    file.path.parent()
-                        .unwrap_or_else(|| Path::new("/"))
-                        .display()
-                ));
-                ListItem::new(line)
+            .map(|(index, positions)| {
+                let file = &self.files[*index];
+                let age = file.last_opened.map(|secs| time_util::format_age(secs, now)).unwrap_or_default();
+                let mut spans = vec![
+                    Span::styled(format!("{:>4} ", age), Style::default().fg(colors::muted())),
+                    Span::styled(if file.pinned { "★ " } else { "  " }, Style::default().fg(colors::secondary())),
+                ];
+                spans.extend(tui_common::highlight_spans(&file.label(), positions, highlight));
+                for source in &file.sources {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(format!("[{}]", source.badge()), Style::default().fg(colors::muted())));
+                }
+                Line::from(spans)
             })
             .collect();
         
-        let list = List::new(items)
+        let title = if self.filtered.len() == self.files.len() {
+            format!("Recent Files ({})", self.files.len())
+        } else {
+            format!("Recent Files ({} of {})", self.filtered.len(), self.files.len())
+        };
+        let list = SelectableList::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
-                .title(format!("Recent Files ({})", self.files.len()))
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
+                .title(title)
+                .border_style(Style::default().fg(colors::primary())));
         
         f.render_stateful_widget(list, area, &mut self.list_state);
     }
     
     /// Render preview
-    fn render_preview(&self, f: &mut Frame, area: Rect) {
-        let title = if let Some(selected) = self.list_state.selected() {
-            if let Some(file) = self.files.get(selected) {
-                format!("Preview: {}", file.display_name)
-            } else {
-                "Preview".to_string()
-            }
-        } else {
-            "Preview".to_string()
+    fn render_preview(&mut self, f: &mut Frame, area: Rect) {
+        // Image previews fill the pane, so follow its size
+        let size = (area.width.saturating_sub(2), area.height.saturating_sub(2));
+        if size != self.preview_size {
+            self.preview_size = size;
+            self.update_preview();
+        }
+        
+        let title = match (self.selected_file(), &self.preview) {
+            (Some(file), Some(preview)) => format!("Preview: {} ({})", file.display_name, preview.label()),
+            (Some(file), None) => format!("Preview: {}", file.display_name),
+            (None, _) => "Preview".to_string(),
         };
         
-        let paragraph = Paragraph::new(self.preview_content.as_str())
-            .block(Block::default()
-                .borders(Borders::ALL)
-                .title(title)
-                .border_style(Style::default().fg(colors::SECONDARY)))
-            .wrap(Wrap { trim: true });
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(colors::secondary()));
+        let paragraph = match &self.preview {
+            Some(preview) => preview.paragraph(block),
+            None => Paragraph::new("").block(block),
+        };
         
         f.render_widget(paragraph, area);
     }
     
     /// Render status bar
-    fn render_status_bar(&self, f: &mut Frame) {
-        let area = Rect {
-            x: 0,
-            y: f.area().height - 1,
-            width: f.area().width,
-            height: 1,
-        };
-        
-        let help_text = "↑↓ Navigate • Enter Open • Esc Quit";
-        let status_text = format!("{} | {}", self.status_message, help_text);
-        
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
         
-        f.render_widget(paragraph, area);
+        let help_text = if self.vim.inserting() {
+            "Type to filter • Enter Open • ^P Pin • F1 Help • Esc Normal mode"
+        } else {
+            "j/k Move • / Filter • Enter Open • ^P Pin • ? Help • q Clear/Quit"
+        };
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
     }
     
-    /// Run the recent files browser
+    /// Run the recent files browser, from where it was left in this
+    /// directory
     pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
-        let result = self.run_app(&mut terminal);
-        tui_common::restore_terminal(&mut terminal)?;
-        result
+        session::resume(self, "recent", tui_common::run_app)
     }
     
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            self.handle_input()?;
-            if self.should_quit {
-                break;
+    /// Print the files instead of running the TUI; without a history, once
+    /// the search for recently modified files is done
+    pub fn print(&mut self, format: Format) -> io::Result<()> {
+        while self.scan.is_some() {
+            thread::sleep(Duration::from_millis(50));
+            self.poll_scan();
+        }
+        plain::print(format, self.filtered.iter().map(|(index, _)| {
+            let file = &self.files[*index];
+            let sources: Vec<&str> = file.sources.iter().map(|source| source.badge()).collect();
+            vec![
+                ("path", Value::from(file.path.to_string_lossy())),
+                ("pinned", Value::from(file.pinned)),
+                ("sources", Value::from(sources.join(","))),
+                ("last_opened", Value::from(file.last_opened)),
+            ]
+        }))
+    }
+}
+
+impl App for RecentFileBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick => {
+                // The progress changes for as long as the scan runs
+                let scanning = self.scan.is_some();
+                self.poll_scan();
+                if self.previews.poll() {
+                    self.update_preview();
+                    return Ok(Action::Redraw);
+                }
+                if scanning {
+                    return Ok(Action::Redraw);
+                }
             }
         }
-        Ok(())
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        self.vim.inserting()
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn context(&self) -> Context {
+        Context { file: self.selected_path(), ..Context::default() }
+    }
+}
+
+impl Resume for RecentFileBrowser {
+    fn session(&self) -> Session {
+        Session {
+            query: Some(self.filter.value().to_string()),
+            selected: self.selected_file().map(|file| file.path.to_string_lossy().into_owned()),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query {
+            self.filter = TextInput::new(query);
+        }
+        let selected = session.selected.map(PathBuf::from).unwrap_or_default();
+        self.reselect(&selected, 0);
     }
 }
 
-/// Run the recent files browser
-pub fn run(limit: usize) -> io::Result<()> {
-    let mut browser = RecentFileBrowser::new(limit)?;
-    browser.run()
+/// Run the recent files browser, or print the files in `output`
+pub fn run(limit: usize, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut browser = RecentFileBrowser::new(limit, settings)?;
+    match output {
+        Some(format) => browser.print(format),
+        None => browser.run(),
+    }
 }
\ No newline at end of file
@@ -1,21 +1,59 @@
 //! Content search with ripgrep integration.
 
-use crate::tui_common::{self, colors};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+
+use crate::clipboard;
+use crate::config::Settings;
+use crate::jobs::{self, Job, Task};
+use crate::mru;
+use crate::notify::Notifications;
+use crate::palette::Context;
+use crate::plain::{self, Format};
+use crate::preview::{Preview, PreviewEngine};
+use crate::session::{self, Resume, Session};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Debounce, Keymap, NavKey, Overflow, SelectableList, VimNav};
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
-    Frame, Terminal,
+    widgets::{Block, Borders, ListState, Paragraph},
+    Frame,
 };
+use serde_json::Value;
 use std::{
     io,
     path::{Path, PathBuf},
-    process::{Command, Stdio},
-    time::Duration,
+    process::Command,
 };
 
+/// Lines shown before and after a match in the preview
+const CONTEXT_LINES: usize = 5;
+
+/// Keys of the results list, listed by the help popup
+const RESULTS_KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("Home / End, gg / G", "First / last item"),
+    ("Enter", "Open at the matching line"),
+    ("y", "Copy file:line"),
+    ("Esc / q", "Quit"),
+];
+
+/// Keys of the live search, listed by the help popup
+const LIVE_KEYMAP: Keymap = &[
+    ("Type", "Edit the pattern"),
+    ("Backspace", "Delete a pattern character"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Home / End", "First / last item"),
+    ("Enter", "Open at the matching line"),
+    ("y", "Copy file:line (normal mode)"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
 #[derive(Debug, Clone)]
 pub struct SearchResult {
     pub file_path: PathBuf,
@@ -25,17 +63,114 @@ pub struct SearchResult {
     pub matched_text: String,
 }
 
+impl SearchResult {
+    /// Parse a `file:line:text` line from ripgrep or grep, for a search of
+    /// `pattern`
+    pub fn parse(line: &str, pattern: &str) -> Option<SearchResult> {
+        let parts: Vec<&str> = line.splitn(3, ':').collect();
+        if parts.len() >= 3 {
+            let file_path = PathBuf::from(parts[0]);
+            if let Ok(line_number) = parts[1].parse::<u32>() {
+                let line_content = parts[2].to_string();
+                let matched_text = extract_match(&line_content, pattern);
+                
+                return Some(SearchResult {
+                    file_path,
+                    line_number,
+                    line_content,
+                    matched_text,
+                });
+            }
+        }
+        None
+    }
+    
+    /// `file:line`, as editors and compilers print it
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.file_path.display(), self.line_number)
+    }
+}
+
+/// Extract the matched portion of text
+fn extract_match(line_content: &str, pattern: &str) -> String {
+    // Simple case-insensitive match extraction
+    let pattern_lower = pattern.to_lowercase();
+    let content_lower = line_content.to_lowercase();
+    
+    if let Some(start) = content_lower.find(&pattern_lower) {
+        let end = start + pattern.len();
+        if end <= line_content.len() {
+            return line_content[start..end].to_string();
+        }
+    }
+    
+    pattern.to_string()
+}
+
+/// ripgrep printing `file:line:text` lines, kept out of ignored files
+pub fn rg_command(settings: &Settings) -> Command {
+    let mut cmd = Command::new("rg");
+    
+    // Basic ripgrep arguments
+    cmd.args([
+        "--line-number",  // Show line numbers
+        "--with-filename", // Show file names
+        "--no-heading",   // Don't group by file
+        "--color=never",  // Disable colors for parsing
+    ]);
+    
+    add_ignore_globs(&mut cmd, settings);
+    cmd
+}
+
+/// Recursive grep printing the same lines as [`rg_command`], for when
+/// ripgrep is not installed
+pub fn grep_command(settings: &Settings) -> Command {
+    let mut cmd = Command::new("grep");
+    
+    cmd.args(["-rn"]); // Recursive, line numbers
+    
+    for pattern in &settings.ignore {
+        cmd.arg(format!("--exclude={}", pattern));
+        cmd.arg(format!("--exclude-dir={}", pattern));
+    }
+    cmd
+}
+
+/// The lines around `result` in its file, with the match marked
+pub fn context_preview(previews: &mut PreviewEngine, result: &SearchResult) -> Preview {
+    let mark = Style::default().fg(colors::warning()).add_modifier(Modifier::BOLD);
+    previews.context(&result.file_path, result.line_number as usize, CONTEXT_LINES, mark)
+}
+
+/// Copy the location of `result` to the clipboard
+fn copy_location(notifications: &mut Notifications, result: Option<&SearchResult>) {
+    if let Some(result) = result {
+        let location = result.location();
+        clipboard::copy_and_notify(notifications, &location, &location);
+    }
+}
+
 #[allow(dead_code)]
 pub struct SearchBrowser {
     results: Vec<SearchResult>,
     list_state: ListState,
+    vim: VimNav,
     should_quit: bool,
-    status_message: String,
-    preview_content: String,
+    notifications: Notifications,
+    /// Context of the selected match
+    preview: Option<Preview>,
+    previews: PreviewEngine,
     pattern: String,
     search_path: PathBuf,
+    /// rg (or grep) running in the background
+    search: Option<Job<Found>>,
+    settings: Settings,
 }
 
+/// Matches found, and whether grep found them because ripgrep is missing
+type Found = (Vec<SearchResult>, bool);
+
 #[allow(dead_code)]
 impl SearchBrowser {
     /// Create a new search browser
@@ -44,216 +179,116 @@ impl SearchBrowser {
         path: PathBuf,
         file_type: Option<String>,
         ignore_case: bool,
+        settings: Settings,
     ) -> io::Result<Self> {
         let mut browser = SearchBrowser {
             results: Vec::new(),
             list_state: ListState::default(),
+            vim: VimNav::default(),
             should_quit: false,
-            status_message: format!("Searching for '{}'...", pattern),
-            preview_content: String::new(),
+            notifications: Notifications::with_status(format!("Searching for '{}'...", pattern)),
+            preview: None,
+            previews: PreviewEngine::new(&settings),
             pattern: pattern.clone(),
             search_path: path.clone(),
+            search: None,
+            settings,
         };
         
-        browser.perform_search(&pattern, &path, file_type, ignore_case)?;
+        browser.perform_search(&pattern, &path, file_type, ignore_case);
         
         Ok(browser)
     }
     
-    /// Perform ripgrep search
+    /// Start the ripgrep search in the background, falling back to grep;
+    /// [`SearchBrowser::poll_search`] shows the results
     fn perform_search(
         &mut self,
         pattern: &str,
         path: &Path,
         file_type: Option<String>,
         ignore_case: bool,
-    ) -> io::Result<()> {
-        let mut cmd = Command::new("rg");
-        
-        // Basic ripgrep arguments
-        cmd.args(&[
-            "--line-number",  // Show line numbers
-            "--with-filename", // Show file names
-            "--no-heading",   // Don't group by file
-            "--color=never",  // Disable colors for parsing
-        ]);
+    ) {
+        let mut rg = rg_command(&self.settings);
         
         // Add case insensitive flag
         if ignore_case {
-            cmd.arg("--ignore-case");
+            rg.arg("--ignore-case");
         }
         
         // Add file type filter
         if let Some(ft) = file_type {
-            cmd.args(&["--type", &ft]);
+            rg.args(["--type", &ft]);
         }
         
         // Add pattern and path
-        cmd.arg(pattern);
-        cmd.arg(path);
-        
-        let output = cmd.stdout(Stdio::piped()).output()?;
-        
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            if stderr.contains("ripgrep") || stderr.contains("not found") {
-                // Fallback to grep if ripgrep is not available
-                self.perform_grep_search(pattern, path, ignore_case)?;
-                return Ok(());
-            } else {
-                self.status_message = format!("Search error: {}", stderr.trim());
-                return Ok(());
-            }
-        }
-        
-        let search_output = String::from_utf8_lossy(&output.stdout);
-        
-        for line in search_output.lines() {
-            if let Some(result) = self.parse_ripgrep_line(line) {
-                self.results.push(result);
-            }
-        }
-        
-        if !self.results.is_empty() {
-            self.list_state.select(Some(0));
-            self.update_preview();
-        }
-        
-        self.status_message = format!("Found {} matches for '{}'", self.results.len(), pattern);
-        Ok(())
-    }
-    
-    /// Fallback to grep if ripgrep is not available
-    fn perform_grep_search(&mut self, pattern: &str, path: &Path, ignore_case: bool) -> io::Result<()> {
-        let mut cmd = Command::new("grep");
-        
-        cmd.args(&["-rn"]); // Recursive, line numbers
+        rg.arg(pattern);
+        rg.arg(path);
         
+        let mut grep = grep_command(&self.settings);
         if ignore_case {
-            cmd.arg("-i");
-        }
-        
-        cmd.arg(pattern);
-        cmd.arg(path);
-        
-        let output = cmd.stdout(Stdio::piped()).output()?;
-        
-        if output.status.success() {
-            let grep_output = String::from_utf8_lossy(&output.stdout);
-            
-            for line in grep_output.lines() {
-                if let Some(result) = self.parse_grep_line(line) {
-                    self.results.push(result);
-                }
-            }
+            grep.arg("-i");
         }
+        grep.arg(pattern);
+        grep.arg(path);
         
-        self.status_message = format!("Found {} matches using grep fallback", self.results.len());
-        Ok(())
+        let pattern = pattern.to_string();
+        let label = format!("rg {}", pattern);
+        self.search = Some(self.notifications.jobs().spawn(label, move |task| search_files(task, &mut rg, &mut grep, &pattern)));
     }
     
-    /// Parse ripgrep output line
-    fn parse_ripgrep_line(&self, line: &str) -> Option<SearchResult> {
-        // Format: filename:line_number:line_content
-        let parts: Vec<&str> = line.splitn(3, ':').collect();
-        if parts.len() >= 3 {
-            let file_path = PathBuf::from(parts[0]);
-            if let Ok(line_number) = parts[1].parse::<u32>() {
-                let line_content = parts[2].to_string();
-                let matched_text = self.extract_match(&line_content);
-                
-                return Some(SearchResult {
-                    file_path,
-                    line_number,
-                    line_content,
-                    matched_text,
-                });
-            }
-        }
-        None
-    }
-    
-    /// Parse grep output line
-    fn parse_grep_line(&self, line: &str) -> Option<SearchResult> {
-        // Similar format to ripgrep
-        self.parse_ripgrep_line(line)
+    /// Show the results once the search is done; returns whether it was
+    fn poll_search(&mut self) -> bool {
+        let Some(result) = self.search.as_mut().and_then(Job::poll) else {
+            return false;
+        };
+        self.search = None;
+        self.show_results(result);
+        true
     }
     
-    /// Extract the matched portion of text
-    fn extract_match(&self, line_content: &str) -> String {
-        // Simple case-insensitive match extraction
-        let pattern_lower = self.pattern.to_lowercase();
-        let content_lower = line_content.to_lowercase();
-        
-        if let Some(start) = content_lower.find(&pattern_lower) {
-            let end = start + self.pattern.len();
-            if end <= line_content.len() {
-                return line_content[start..end].to_string();
+    fn show_results(&mut self, result: io::Result<Found>) {
+        match result {
+            Ok((results, grep)) => {
+                self.results = results;
+                if !self.results.is_empty() {
+                    self.list_state.select(Some(0));
+                    self.update_preview();
+                }
+                if grep {
+                    self.notifications.set_status(format!("Found {} matches using grep fallback", self.results.len()));
+                } else {
+                    self.notifications.set_status(format!("Found {} matches for '{}'", self.results.len(), self.pattern));
+                }
+            }
+            Err(e) if jobs::is_cancelled(&e) => {
+                self.notifications.set_status(format!("Search for '{}' stopped", self.pattern));
+            }
+            Err(e) => {
+                self.notifications.set_status(format!("No matches for '{}'", self.pattern));
+                self.notifications.error(format!("Search error: {}", e));
             }
         }
-        
-        self.pattern.clone()
     }
     
     /// Update preview content for selected result
     fn update_preview(&mut self) {
         if let Some(selected) = self.list_state.selected() {
             if let Some(result) = self.results.get(selected) {
-                self.preview_content = self.load_file_context(&result.file_path, result.line_number);
+                self.preview = Some(context_preview(&mut self.previews, result));
             }
         }
     }
     
-    /// Load file context around the matched line
-    fn load_file_context(&self, file_path: &Path, line_number: u32) -> String {
-        match std::fs::read_to_string(file_path) {
-            Ok(content) => {
-                let lines: Vec<&str> = content.lines().collect();
-                let line_idx = (line_number as usize).saturating_sub(1);
-                
-                // Show context: 5 lines before and after
-                let start = line_idx.saturating_sub(5);
-                let end = std::cmp::min(line_idx + 6, lines.len());
-                
-                let mut context_lines = Vec::new();
-                for i in start..end {
-                    let marker = if i == line_idx { ">>>" } else { "   " };
-                    context_lines.push(format!("{} {:4}: {}", marker, i + 1, lines[i]));
-                }
-                
-                context_lines.join("\n")
-            }
-            Err(_) => format!("Could not read file: {}", file_path.display()),
-        }
-    }
-    
     /// Open file at specific line in editor
     fn open_file(&mut self) -> io::Result<()> {
         if let Some(selected) = self.list_state.selected() {
             if let Some(result) = self.results.get(selected) {
-                // Try to open with line number support
-                let editors_with_line = [
-                    ("nvim", format!("+{}", result.line_number)),
-                    ("vim", format!("+{}", result.line_number)),
-                    ("code", format!("--goto {}:{}", result.file_path.display(), result.line_number)),
-                ];
-                
-                for (editor, line_arg) in editors_with_line.iter() {
-                    let mut cmd = Command::new(editor);
-                    if editor == &"code" {
-                        cmd.arg(&line_arg);
-                    } else {
-                        cmd.arg(&line_arg).arg(&result.file_path);
-                    }
-                    
-                    if cmd.status().is_ok() {
-                        self.should_quit = true;
-                        return Ok(());
-                    }
+                // Try the configured editor, or common ones, at the matched line
+                if !mru::open_in_editor(&self.settings, &result.file_path, Some(result.line_number as usize)) {
+                    // Fallback to basic file opening
+                    println!("{}", result.file_path.display());
                 }
-                
-                // Fallback to basic file opening
-                println!("{}", result.file_path.display());
                 self.should_quit = true;
             }
         }
@@ -261,60 +296,42 @@ impl SearchBrowser {
     }
     
     /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected > 0 {
-                                self.list_state.select(Some(selected - 1));
-                                self.update_preview();
-                            }
-                        }
-                    }
-                    KeyCode::Down => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected + 1 < self.results.len() {
-                                self.list_state.select(Some(selected + 1));
-                                self.update_preview();
-                            }
-                        } else if !self.results.is_empty() {
-                            self.list_state.select(Some(0));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Enter => {
-                        self.open_file()?;
-                    }
-                    _ => {}
-                }
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if self.vim.navigate(&mut self.list_state, key.code, key.modifiers, self.results.len(), self.settings.page_size) {
+            self.update_preview();
+            return Ok(());
+        }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_quit = true;
+            }
+            KeyCode::Enter => {
+                self.open_file()?;
+            }
+            KeyCode::Char('y') => {
+                copy_location(&mut self.notifications, self.list_state.selected().and_then(|i| self.results.get(i)));
             }
+            _ => {}
         }
         Ok(())
     }
     
     /// Render the search browser
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
-            .split(f.area());
+            .split(area);
         
         self.render_results_list(f, chunks[0]);
         self.render_file_preview(f, chunks[1]);
-        self.render_status_bar(f);
+        self.render_status_bar(f, area);
     }
     
     /// Render search results list
     fn render_results_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.results
+        let items: Vec<Line> = self.results
             .iter()
             .map(|result| {
                 let file_name = result.file_path.file_name()
@@ -324,112 +341,137 @@ impl SearchBrowser {
                 let line = Line::from(vec![
                     Span::styled(
                         format!("{}", file_name),
-                        Style::default().fg(colors::PRIMARY).add_modifier(Modifier::BOLD)
+                        Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)
                     ),
                     Span::styled(
                         format!(":{}", result.line_number),
-                        Style::default().fg(colors::SECONDARY)
+                        Style::default().fg(colors::secondary())
                     ),
                     Span::raw(" "),
                     Span::styled(
                         result.line_content.trim(),
-                        Style::default().fg(colors::TEXT)
+                        Style::default().fg(colors::text())
                     ),
                 ]);
                 
-                ListItem::new(line)
+                line
             })
             .collect();
         
         let title = format!("Search Results for '{}' ({})", self.pattern, self.results.len());
         
-        let list = List::new(items)
+        let list = SelectableList::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
+                .border_style(Style::default().fg(colors::primary())))
+            .overflow(Overflow::Wrap);
         
         f.render_stateful_widget(list, area, &mut self.list_state);
     }
     
     /// Render file preview
     fn render_file_preview(&self, f: &mut Frame, area: Rect) {
-        let title = if let Some(selected) = self.list_state.selected() {
-            if let Some(result) = self.results.get(selected) {
-                format!("Context: {}", result.file_path.display())
-            } else {
-                "Context".to_string()
-            }
-        } else {
-            "Context".to_string()
+        let title = match self.list_state.selected().and_then(|selected| self.results.get(selected)) {
+            Some(result) => format!("Context: {}", result.file_path.display()),
+            None => "Context".to_string(),
         };
         
-        let paragraph = Paragraph::new(self.preview_content.as_str())
-            .block(Block::default()
-                .borders(Borders::ALL)
-                .title(title)
-                .border_style(Style::default().fg(colors::SECONDARY)))
-            .wrap(Wrap { trim: true });
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(colors::secondary()));
+        let paragraph = match &self.preview {
+            Some(preview) => preview.paragraph(block),
+            None => Paragraph::new("").block(block),
+        };
         
         f.render_widget(paragraph, area);
     }
     
     /// Render status bar
-    fn render_status_bar(&self, f: &mut Frame) {
-        let area = Rect {
-            x: 0,
-            y: f.area().height - 1,
-            width: f.area().width,
-            height: 1,
-        };
-        
-        let help_text = "↑↓ Navigate • Enter Open • Esc Quit";
-        let status_text = format!("{} | {}", self.status_message, help_text);
-        
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
         
-        f.render_widget(paragraph, area);
+        let help_text = "j/k Navigate • Enter Open • y Copy • ? Help • Esc Quit";
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
     }
     
     /// Run the search browser
     pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
-        let result = self.run_app(&mut terminal);
-        tui_common::restore_terminal(&mut terminal)?;
-        result
-    }
-    
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            self.handle_input()?;
-            if self.should_quit {
-                break;
+        tui_common::run_app(self)
+    }
+    
+    /// Print the matches instead of running the TUI, once the search is
+    /// done
+    pub fn print(&mut self, format: Format) -> io::Result<()> {
+        if let Some(search) = self.search.take() {
+            self.show_results(search.wait());
+        }
+        plain::check(&self.notifications)?;
+        plain::print(format, self.results.iter().map(|result| vec![
+            ("path", Value::from(result.file_path.to_string_lossy())),
+            ("line", Value::from(result.line_number)),
+            ("text", Value::from(result.line_content.as_str())),
+        ]))
+    }
+}
+
+impl App for SearchBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick => {
+                if self.poll_search() {
+                    return Ok(Action::Redraw);
+                }
             }
         }
-        Ok(())
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        RESULTS_KEYMAP
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn context(&self) -> Context {
+        Context {
+            dir: Some(self.search_path.clone()),
+            file: self.list_state.selected().and_then(|index| self.results.get(index)).map(|result| result.file_path.clone()),
+            ..Context::default()
+        }
     }
 }
 
 /// Live search browser with real-time ripgrep integration
 pub struct LiveSearchBrowser {
     search_query: String,
+    /// rg is started once typing pauses
+    search_debounce: Debounce,
     results: Vec<SearchResult>,
     list_state: ListState,
+    vim: VimNav,
     should_quit: bool,
-    status_message: String,
-    preview_content: String,
+    notifications: Notifications,
+    /// Context of the selected match
+    preview: Option<Preview>,
+    previews: PreviewEngine,
     search_path: PathBuf,
     file_type: Option<String>,
     ignore_case: bool,
-    is_searching: bool,
+    /// `rg` running in the background; replacing it cancels the old one
+    search: Option<Job<Vec<SearchResult>>>,
+    /// Location to select once the search restored from the session is done
+    resume_selected: Option<String>,
+    settings: Settings,
 }
 
 impl LiveSearchBrowser {
@@ -439,18 +481,24 @@ impl LiveSearchBrowser {
         path: PathBuf,
         file_type: Option<String>,
         ignore_case: bool,
+        settings: Settings,
     ) -> io::Result<Self> {
         let mut browser = LiveSearchBrowser {
             search_query: initial_pattern.unwrap_or_default(),
+            search_debounce: Debounce::default(),
             results: Vec::new(),
             list_state: ListState::default(),
+            vim: VimNav::default(),
             should_quit: false,
-            status_message: "Type to search with ripgrep...".to_string(),
-            preview_content: String::new(),
+            notifications: Notifications::with_status("Type to search with ripgrep..."),
+            preview: None,
+            previews: PreviewEngine::new(&settings),
             search_path: path,
             file_type,
             ignore_case,
-            is_searching: false,
+            search: None,
+            resume_selected: None,
+            settings,
         };
         
         // If we have an initial pattern, search immediately
@@ -461,129 +509,88 @@ impl LiveSearchBrowser {
         Ok(browser)
     }
     
-    /// Perform live search as user types
+    /// Start searching for the query in the background, cancelling the
+    /// search before; [`LiveSearchBrowser::poll_search`] shows the results
     fn perform_live_search(&mut self) -> io::Result<()> {
         if self.search_query.len() < 2 {
+            self.search = None;
             self.results.clear();
-            self.status_message = "Type at least 2 characters to search...".to_string();
+            self.notifications.set_status("Type at least 2 characters to search...");
             return Ok(());
         }
         
-        self.is_searching = true;
-        self.status_message = format!("Searching for '{}'...", self.search_query);
+        self.notifications.set_status(format!("Searching for '{}'...", self.search_query));
         
-        let mut cmd = Command::new("rg");
-        
-        // Basic ripgrep arguments for fast search
-        cmd.args(&[
-            "--line-number",
-            "--with-filename", 
-            "--no-heading",
-            "--color=never",
-            "--max-count=100", // Limit results for performance
-        ]);
+        let mut cmd = rg_command(&self.settings);
+        cmd.arg("--max-count=100"); // Limit results for performance
         
         if self.ignore_case {
             cmd.arg("--ignore-case");
         }
         
         if let Some(ref ft) = self.file_type {
-            cmd.args(&["--type", ft]);
+            cmd.args(["--type", ft]);
         }
         
         cmd.arg(&self.search_query);
         cmd.arg(&self.search_path);
         
-        let output = cmd.stdout(Stdio::piped()).output()?;
-        
-        self.results.clear();
-        
-        if output.status.success() {
-            let search_output = String::from_utf8_lossy(&output.stdout);
-            
-            for line in search_output.lines() {
-                if let Some(result) = self.parse_ripgrep_line(line) {
-                    self.results.push(result);
+        let query = self.search_query.clone();
+        let label = format!("rg {}", query);
+        self.search = Some(self.notifications.jobs().spawn(label, move |task| {
+            let output = task.output(&mut cmd)?;
+            // Exit code 1 only means nothing matched
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| SearchResult::parse(line, &query))
+                .collect())
+        }));
+        Ok(())
+    }
+    
+    /// Show the results once the search is done; returns whether it was
+    fn poll_search(&mut self) -> bool {
+        let Some(result) = self.search.as_mut().and_then(Job::poll) else {
+            return false;
+        };
+        self.search = None;
+        self.results = match result {
+            Ok(results) => results,
+            Err(e) => {
+                if !jobs::is_cancelled(&e) {
+                    self.notifications.error(format!("Search failed: {}", e));
                 }
+                self.notifications.set_status(format!("Search for '{}' stopped", self.search_query));
+                return true;
             }
-        }
+        };
         
+        let resumed = self.resume_selected.take().and_then(|selected| {
+            self.results.iter().position(|result| result.location() == selected)
+        });
         if !self.results.is_empty() {
-            self.list_state.select(Some(0));
+            self.list_state.select(Some(resumed.unwrap_or(0)));
             self.update_preview();
         } else {
             self.list_state.select(None);
-            self.preview_content.clear();
+            self.preview = None;
         }
         
-        self.status_message = format!("Found {} matches for '{}'", self.results.len(), self.search_query);
-        self.is_searching = false;
-        Ok(())
-    }
-    
-    /// Parse ripgrep output line
-    fn parse_ripgrep_line(&self, line: &str) -> Option<SearchResult> {
-        let parts: Vec<&str> = line.splitn(3, ':').collect();
-        if parts.len() >= 3 {
-            let file_path = PathBuf::from(parts[0]);
-            if let Ok(line_number) = parts[1].parse::<u32>() {
-                let line_content = parts[2].to_string();
-                let matched_text = self.extract_match(&line_content);
-                
-                return Some(SearchResult {
-                    file_path,
-                    line_number,
-                    line_content,
-                    matched_text,
-                });
-            }
-        }
-        None
+        self.notifications.set_status(format!("Found {} matches for '{}'", self.results.len(), self.search_query));
+        true
     }
     
-    /// Extract the matched portion of text
-    fn extract_match(&self, line_content: &str) -> String {
-        let pattern_lower = self.search_query.to_lowercase();
-        let content_lower = line_content.to_lowercase();
-        
-        if let Some(start) = content_lower.find(&pattern_lower) {
-            let end = start + self.search_query.len();
-            if end <= line_content.len() {
-                return line_content[start..end].to_string();
-            }
-        }
-        
-        self.search_query.clone()
+    /// File of the selected match, for another tool to act on
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        self.list_state.selected().and_then(|index| self.results.get(index)).map(|result| result.file_path.clone())
     }
     
     /// Update preview content
     fn update_preview(&mut self) {
         if let Some(selected) = self.list_state.selected() {
             if let Some(result) = self.results.get(selected) {
-                self.preview_content = self.load_file_context(&result.file_path, result.line_number);
-            }
-        }
-    }
-    
-    /// Load file context around matched line
-    fn load_file_context(&self, file_path: &Path, line_number: u32) -> String {
-        match std::fs::read_to_string(file_path) {
-            Ok(content) => {
-                let lines: Vec<&str> = content.lines().collect();
-                let line_idx = (line_number as usize).saturating_sub(1);
-                
-                let start = line_idx.saturating_sub(5);
-                let end = std::cmp::min(line_idx + 6, lines.len());
-                
-                let mut context_lines = Vec::new();
-                for i in start..end {
-                    let marker = if i == line_idx { ">>>" } else { "   " };
-                    context_lines.push(format!("{} {:4}: {}", marker, i + 1, lines[i]));
-                }
-                
-                context_lines.join("\n")
+                self.preview = Some(context_preview(&mut self.previews, result));
             }
-            Err(_) => format!("Could not read file: {}", file_path.display()),
         }
     }
     
@@ -591,27 +598,9 @@ impl LiveSearchBrowser {
     fn open_file(&mut self) -> io::Result<()> {
         if let Some(selected) = self.list_state.selected() {
             if let Some(result) = self.results.get(selected) {
-                let editors_with_line = [
-                    ("nvim", format!("+{}", result.line_number)),
-                    ("vim", format!("+{}", result.line_number)),
-                    ("code", format!("--goto {}:{}", result.file_path.display(), result.line_number)),
-                ];
-                
-                for (editor, line_arg) in editors_with_line.iter() {
-                    let mut cmd = Command::new(editor);
-                    if editor == &"code" {
-                        cmd.arg(&line_arg);
-                    } else {
-                        cmd.arg(&line_arg).arg(&result.file_path);
-                    }
-                    
-                    if cmd.status().is_ok() {
-                        self.should_quit = true;
-                        return Ok(());
-                    }
+                if !mru::open_in_editor(&self.settings, &result.file_path, Some(result.line_number as usize)) {
+                    println!("{}", result.file_path.display());
                 }
-                
-                println!("{}", result.file_path.display());
                 self.should_quit = true;
             }
         }
@@ -619,84 +608,57 @@ impl LiveSearchBrowser {
     }
     
     /// Handle keyboard input
-    fn handle_input(&mut self) -> io::Result<()> {
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        self.should_quit = true;
-                    }
-                    KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page down
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.results.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        // Page up
-                        if let Some(new_selection) = tui_common::handle_page_navigation(
-                            key.code, key.modifiers, self.list_state.selected(), self.results.len(), 10
-                        ) {
-                            self.list_state.select(Some(new_selection));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected > 0 {
-                                self.list_state.select(Some(selected - 1));
-                                self.update_preview();
-                            }
-                        }
-                    }
-                    KeyCode::Down => {
-                        if let Some(selected) = self.list_state.selected() {
-                            if selected + 1 < self.results.len() {
-                                self.list_state.select(Some(selected + 1));
-                                self.update_preview();
-                            }
-                        } else if !self.results.is_empty() {
-                            self.list_state.select(Some(0));
-                            self.update_preview();
-                        }
-                    }
-                    KeyCode::Enter => {
-                        self.open_file()?;
-                    }
-                    KeyCode::Char(c) => {
-                        self.search_query.push(c);
-                        self.perform_live_search()?;
-                    }
-                    KeyCode::Backspace => {
-                        self.search_query.pop();
-                        if self.search_query.is_empty() {
-                            self.results.clear();
-                            self.list_state.select(None);
-                            self.preview_content.clear();
-                            self.status_message = "Type to search with ripgrep...".to_string();
-                        } else {
-                            self.perform_live_search()?;
-                        }
-                    }
-                    _ => {}
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if !self.vim.typing(&key) && self.search_debounce.take() {
+            self.perform_live_search()?;
+        }
+        
+        match self.vim.filter_key(&mut self.list_state, key.code, key.modifiers, self.results.len(), self.settings.page_size) {
+            NavKey::Moved => {
+                self.update_preview();
+                return Ok(());
+            }
+            NavKey::Used => return Ok(()),
+            NavKey::Unused => {}
+        }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Enter => {
+                self.open_file()?;
+            }
+            KeyCode::Char('y') if !self.vim.inserting() => {
+                copy_location(&mut self.notifications, self.list_state.selected().and_then(|i| self.results.get(i)));
+            }
+            KeyCode::Char(c) if self.vim.inserting() => {
+                self.search_query.push(c);
+                self.search_debounce.schedule();
+            }
+            KeyCode::Backspace if self.vim.inserting() => {
+                self.search_query.pop();
+                if self.search_query.is_empty() {
+                    self.search_debounce.take();
+                    self.results.clear();
+                    self.list_state.select(None);
+                    self.preview = None;
+                    self.notifications.set_status("Type to search with ripgrep...");
+                } else {
+                    self.search_debounce.schedule();
                 }
             }
+            _ => {}
         }
         Ok(())
     }
     
     /// Render the live search browser
-    fn render(&mut self, f: &mut Frame) {
+    fn render(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Length(3), Constraint::Min(5), Constraint::Length(1)])
-            .split(f.area());
+            .split(area);
         
         // Search input
         self.render_search_input(f, chunks[0]);
@@ -716,7 +678,7 @@ impl LiveSearchBrowser {
     
     /// Render search input
     fn render_search_input(&self, f: &mut Frame, area: Rect) {
-        let search_text = if self.is_searching {
+        let search_text = if self.search.is_some() {
             format!("🔍 Searching: {}", self.search_query)
         } else {
             format!("🔍 Search: {}", self.search_query)
@@ -726,14 +688,14 @@ impl LiveSearchBrowser {
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title("Live Search (ripgrep)")
-                .border_style(Style::default().fg(colors::PRIMARY)));
+                .border_style(Style::default().fg(colors::primary())));
         
         f.render_widget(paragraph, area);
     }
     
     /// Render search results
     fn render_results_list(&mut self, f: &mut Frame, area: Rect) {
-        let items: Vec<ListItem> = self.results
+        let items: Vec<Line> = self.results
             .iter()
             .map(|result| {
                 let file_name = result.file_path.file_name()
@@ -743,100 +705,185 @@ impl LiveSearchBrowser {
                 let line = Line::from(vec![
                     Span::styled(
                         format!("{}", file_name),
-                        Style::default().fg(colors::PRIMARY).add_modifier(Modifier::BOLD)
+                        Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)
                     ),
                     Span::styled(
                         format!(":{}", result.line_number),
-                        Style::default().fg(colors::SECONDARY)
+                        Style::default().fg(colors::secondary())
                     ),
                     Span::raw(" "),
                     Span::styled(
                         result.line_content.trim(),
-                        Style::default().fg(colors::TEXT)
+                        Style::default().fg(colors::text())
                     ),
                 ]);
                 
-                ListItem::new(line)
+                line
             })
             .collect();
         
         let title = format!("Results ({})", self.results.len());
         
-        let list = List::new(items)
+        let list = SelectableList::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(Style::default().fg(colors::PRIMARY)))
-            .highlight_style(Style::default()
-                .bg(colors::PRIMARY)
-                .fg(colors::BACKGROUND)
-                .add_modifier(Modifier::BOLD))
-            .highlight_symbol("► ");
+                .border_style(Style::default().fg(colors::primary())))
+            .overflow(Overflow::Wrap);
         
         f.render_stateful_widget(list, area, &mut self.list_state);
     }
     
     /// Render file preview
     fn render_file_preview(&self, f: &mut Frame, area: Rect) {
-        let title = if let Some(selected) = self.list_state.selected() {
-            if let Some(result) = self.results.get(selected) {
-                format!("Context: {}", result.file_path.display())
-            } else {
-                "Context".to_string()
-            }
-        } else {
-            "Context".to_string()
+        let title = match self.list_state.selected().and_then(|selected| self.results.get(selected)) {
+            Some(result) => format!("Context: {}", result.file_path.display()),
+            None => "Context".to_string(),
         };
         
-        let paragraph = Paragraph::new(self.preview_content.as_str())
-            .block(Block::default()
-                .borders(Borders::ALL)
-                .title(title)
-                .border_style(Style::default().fg(colors::SECONDARY)))
-            .wrap(Wrap { trim: true });
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(colors::secondary()));
+        let paragraph = match &self.preview {
+            Some(preview) => preview.paragraph(block),
+            None => Paragraph::new("").block(block),
+        };
         
         f.render_widget(paragraph, area);
     }
     
     /// Render status bar
     fn render_status_bar(&self, f: &mut Frame, area: Rect) {
-        let help_text = "Type to search • ↑↓ Navigate • Ctrl-F/B Page • Enter Open • Esc Quit";
-        let status_text = format!("{} | {}", self.status_message, help_text);
-        
-        let paragraph = Paragraph::new(status_text)
-            .style(Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND));
-        
-        f.render_widget(paragraph, area);
+        let help_text = if self.vim.inserting() {
+            "Type to search • Enter Open • F1 Help • Esc Normal mode"
+        } else {
+            "j/k Move • / Search • Enter Open • y Copy • ? Help • q Quit"
+        };
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
     }
     
-    /// Run the live search browser
+    /// Run the live search browser, from where it was left in this
+    /// directory
     pub fn run(&mut self) -> io::Result<()> {
-        let mut terminal = tui_common::setup_terminal()?;
-        let result = self.run_app(&mut terminal);
-        tui_common::restore_terminal(&mut terminal)?;
-        result
-    }
-    
-    /// Main application loop
-    fn run_app<B: ratatui::backend::Backend + std::io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        loop {
-            terminal.draw(|f| self.render(f))?;
-            self.handle_input()?;
-            if self.should_quit {
-                break;
+        session::resume(self, "search", tui_common::run_app)
+    }
+}
+
+impl App for LiveSearchBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick if self.search_debounce.ready() => {
+                self.perform_live_search()?;
+                return Ok(Action::Redraw);
             }
+            AppEvent::Tick if self.poll_search() => return Ok(Action::Redraw),
+            AppEvent::Tick => {}
         }
-        Ok(())
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        LIVE_KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        self.vim.inserting()
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn context(&self) -> Context {
+        Context {
+            dir: Some(self.search_path.clone()),
+            file: self.selected_path(),
+            ..Context::default()
+        }
+    }
+}
+
+impl Resume for LiveSearchBrowser {
+    fn session(&self) -> Session {
+        let selected = self.list_state.selected().and_then(|index| self.results.get(index));
+        Session {
+            query: Some(self.search_query.clone()),
+            selected: selected.map(SearchResult::location),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query.filter(|query| self.search_query.is_empty() && !query.is_empty()) {
+            self.search_query = query;
+            if let Err(e) = self.perform_live_search() {
+                self.notifications.error(format!("Search failed: {}", e));
+            }
+        }
+        // The results come in the background
+        self.resume_selected = session.selected;
     }
 }
 
-/// Run the content search tool
+/// Run the content search tool, or print every match of `pattern` in
+/// `output`
 pub fn run(
     pattern: Option<String>,
     path: PathBuf,
     file_type: Option<String>,
     ignore_case: bool,
+    output: Option<Format>,
+    settings: Settings,
 ) -> io::Result<()> {
-    let mut browser = LiveSearchBrowser::new(pattern, path, file_type, ignore_case)?;
+    if let Some(format) = output {
+        let pattern = pattern.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "A search pattern is needed without the TUI")
+        })?;
+        let mut browser = SearchBrowser::new(pattern, path, file_type, ignore_case, settings)?;
+        return browser.print(format);
+    }
+    let mut browser = LiveSearchBrowser::new(pattern, path, file_type, ignore_case, settings)?;
     browser.run()
-}
\ No newline at end of file
+}
+/// Matches of `pattern` from `rg`, or from `grep` where ripgrep is not
+/// installed
+fn search_files(task: &Task, rg: &mut Command, grep: &mut Command, pattern: &str) -> io::Result<Found> {
+    let parse = |stdout: &[u8]| {
+        String::from_utf8_lossy(stdout).lines().filter_map(|line| SearchResult::parse(line, pattern)).collect()
+    };
+    let output = match task.output(rg) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        output => Some(output?),
+    };
+    if let Some(output) = output {
+        if output.status.success() {
+            return Ok((parse(&output.stdout), false));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.contains("ripgrep") && !stderr.contains("not found") {
+            // Nothing on stderr just means nothing matched
+            if stderr.trim().is_empty() {
+                return Ok((Vec::new(), false));
+            }
+            return Err(io::Error::other(stderr.trim().to_string()));
+        }
+    }
+    
+    // Fallback to grep if ripgrep is not available
+    let output = task.output(grep)?;
+    let results = if output.status.success() { parse(&output.stdout) } else { Vec::new() };
+    Ok((results, true))
+}
+
+/// Keep ripgrep out of files and directories matching the ignore patterns
+fn add_ignore_globs(cmd: &mut Command, settings: &Settings) {
+    for pattern in &settings.ignore {
+        cmd.arg("--glob").arg(format!("!{}", pattern));
+    }
+}
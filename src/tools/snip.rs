@@ -0,0 +1,464 @@
+//! Snippet library browser with placeholder prompts.
+
+use crate::config::Settings;
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::platform;
+use crate::session::{self, Resume, Session};
+use crate::snippets::{self, Placeholder, Snippet};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph, Wrap},
+    Frame,
+};
+use serde_json::Value;
+use std::io::{self, IsTerminal};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("Home / End, gg / G", "First / last item"),
+    ("Enter", "Run the snippet"),
+    ("p", "Print the snippet for the shell"),
+    ("e", "Edit the command"),
+    ("r", "Rename"),
+    ("D", "Edit the description"),
+    ("d / Delete", "Delete"),
+    ("Esc / q", "Quit"),
+];
+
+/// Field edited through the input popup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Command,
+    Name,
+    Description,
+}
+
+impl Field {
+    fn title(&self) -> &'static str {
+        match self {
+            Field::Command => "Edit Command",
+            Field::Name => "Rename Snippet",
+            Field::Description => "Describe Snippet",
+        }
+    }
+}
+
+/// What to do with a snippet once its placeholders are filled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Then {
+    Run,
+    Print,
+}
+
+enum Mode {
+    Normal,
+    Input(Field, TextInput),
+    /// Prompting for placeholder values one at a time
+    Fill {
+        command: String,
+        then: Then,
+        pending: Vec<Placeholder>,
+        values: Vec<(String, String)>,
+        input: TextInput,
+    },
+    ConfirmDelete,
+}
+
+pub struct SnippetBrowser {
+    snippets: Vec<Snippet>,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    mode: Mode,
+    /// Filled command to run once the terminal is released
+    run_requested: Option<String>,
+    /// Filled command printed to stdout on exit
+    output: Option<String>,
+    settings: Settings,
+}
+
+impl SnippetBrowser {
+    /// Create a browser over the saved snippets
+    pub fn new(settings: Settings) -> io::Result<Self> {
+        let snippets = snippets::load()?;
+        let mut browser = SnippetBrowser {
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::new(),
+            snippets,
+            mode: Mode::Normal,
+            run_requested: None,
+            output: None,
+            settings,
+        };
+        
+        if !browser.snippets.is_empty() {
+            browser.list_state.select(Some(0));
+        } else {
+            browser.notifications.set_status("No snippets yet: star commands in tt hist with Ctrl-T");
+        }
+        Ok(browser)
+    }
+    
+    fn selected_snippet(&self) -> Option<&Snippet> {
+        self.list_state.selected().and_then(|index| self.snippets.get(index))
+    }
+    
+    /// Persist the snippets, reporting `done` or the error
+    fn save(&mut self, done: &str) {
+        match snippets::save(&self.snippets) {
+            Ok(()) => self.notifications.success(done),
+            Err(e) => self.notifications.error(format!("Could not save snippets: {}", e)),
+        }
+    }
+    
+    /// Run or print the selected snippet, prompting for placeholders first
+    fn start(&mut self, then: Then) {
+        let Some(command) = self.selected_snippet().map(|snippet| snippet.command.clone()) else {
+            return;
+        };
+        let mut pending = snippets::placeholders(&command);
+        pending.reverse();
+        self.next_placeholder(command, then, pending, Vec::new());
+    }
+    
+    /// Prompt for the next placeholder, or finish once all are filled
+    fn next_placeholder(&mut self, command: String, then: Then, pending: Vec<Placeholder>, values: Vec<(String, String)>) {
+        match pending.last() {
+            Some(placeholder) => {
+                let input = TextInput::new(placeholder.default.as_str());
+                self.mode = Mode::Fill { command, then, pending, values, input };
+            }
+            None => {
+                self.mode = Mode::Normal;
+                let command = snippets::fill(&command, &values);
+                match then {
+                    Then::Run => self.run_requested = Some(command),
+                    Then::Print => {
+                        self.output = Some(command);
+                        self.should_quit = true;
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Open the input popup for a field of the selected snippet
+    fn start_input(&mut self, field: Field) {
+        let Some(snippet) = self.selected_snippet() else {
+            return;
+        };
+        let current = match field {
+            Field::Command => snippet.command.clone(),
+            Field::Name => snippet.name.clone().unwrap_or_default(),
+            Field::Description => snippet.description.clone().unwrap_or_default(),
+        };
+        self.mode = Mode::Input(field, TextInput::new(current));
+    }
+    
+    /// Store an edited field; empty names and descriptions are removed
+    fn apply_input(&mut self, field: Field, value: &str) {
+        let Some(index) = self.list_state.selected() else {
+            return;
+        };
+        let value = value.trim();
+        let optional = (!value.is_empty()).then(|| value.to_string());
+        let snippet = &mut self.snippets[index];
+        match field {
+            Field::Command if value.is_empty() => {
+                self.notifications.error("Command cannot be empty");
+                return;
+            }
+            Field::Command => snippet.command = value.to_string(),
+            Field::Name => snippet.name = optional,
+            Field::Description => snippet.description = optional,
+        }
+        self.save("Snippet saved");
+    }
+    
+    /// Delete the selected snippet
+    fn delete_selected(&mut self) {
+        let Some(index) = self.list_state.selected() else {
+            return;
+        };
+        self.snippets.remove(index);
+        if self.snippets.is_empty() {
+            self.list_state.select(None);
+        } else {
+            self.list_state.select(Some(index.min(self.snippets.len() - 1)));
+        }
+        self.save("Snippet deleted");
+    }
+    
+    /// Handle keyboard input
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+    
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal_input(key.code, key.modifiers),
+            Mode::Input(field, mut input) => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => self.apply_input(field, input.value()),
+                code => {
+                    input.handle_key(code);
+                    self.mode = Mode::Input(field, input);
+                }
+            },
+            Mode::Fill { command, then, mut pending, mut values, mut input } => match key.code {
+                KeyCode::Esc => self.notifications.info("Cancelled"),
+                KeyCode::Enter => {
+                    if let Some(placeholder) = pending.pop() {
+                        values.push((placeholder.name, input.value().to_string()));
+                    }
+                    self.next_placeholder(command, then, pending, values);
+                }
+                code => {
+                    input.handle_key(code);
+                    self.mode = Mode::Fill { command, then, pending, values, input };
+                }
+            },
+            Mode::ConfirmDelete => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => self.delete_selected(),
+                _ => self.notifications.info("Cancelled"),
+            },
+        }
+        Ok(())
+    }
+    
+    /// Handle keys in the snippet list
+    fn handle_normal_input(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        if self.vim.navigate(&mut self.list_state, code, modifiers, self.snippets.len(), self.settings.page_size) {
+            return;
+        }
+        
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_quit = true;
+            }
+            KeyCode::Enter => self.start(Then::Run),
+            KeyCode::Char('p') => self.start(Then::Print),
+            KeyCode::Char('e') => self.start_input(Field::Command),
+            KeyCode::Char('r') => self.start_input(Field::Name),
+            KeyCode::Char('D') => self.start_input(Field::Description),
+            KeyCode::Char('d') | KeyCode::Delete if self.selected_snippet().is_some() => {
+                self.mode = Mode::ConfirmDelete;
+            }
+            _ => {}
+        }
+    }
+    
+    /// Render the snippet browser
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        
+        self.render_snippet_list(f, chunks[0]);
+        self.render_details(f, chunks[1]);
+        self.render_status_bar(f, area);
+        
+        match &self.mode {
+            Mode::Normal => {}
+            Mode::Input(field, input) => {
+                let label = match field {
+                    Field::Command => "Command ({name} or {name:default} for placeholders):",
+                    Field::Name => "Name (empty to show the command):",
+                    Field::Description => "Description:",
+                };
+                tui_common::render_input_popup(f, field.title(), label, input);
+            }
+            Mode::Fill { pending, input, .. } => {
+                if let Some(placeholder) = pending.last() {
+                    let label = format!("Value for {{{}}}:", placeholder.name);
+                    tui_common::render_input_popup(f, "Fill Placeholder", &label, input);
+                }
+            }
+            Mode::ConfirmDelete => {
+                let name = self.selected_snippet().map(|snippet| snippet.title().to_string()).unwrap_or_default();
+                let lines = vec![
+                    Line::from(format!("Delete snippet '{}'?", name)),
+                    Line::from(""),
+                    Line::from(Span::styled("y Delete • any other key Cancel", Style::default().fg(colors::muted()))),
+                ];
+                tui_common::render_message_popup(f, "Delete Snippet", lines, colors::danger());
+            }
+        }
+    }
+    
+    /// Render the list of snippets
+    fn render_snippet_list(&mut self, f: &mut Frame, area: Rect) {
+        let items: Vec<Line> = self.snippets
+            .iter()
+            .map(|snippet| {
+                let mut spans = vec![Span::raw(tui_common::single_line(snippet.title()))];
+                if snippet.name.is_some() {
+                    spans.push(Span::styled(
+                        format!("  {}", tui_common::single_line(&snippet.command)),
+                        Style::default().fg(colors::muted()),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Snippets ({})", self.snippets.len()))
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    /// Render the selected snippet's details
+    fn render_details(&self, f: &mut Frame, area: Rect) {
+        let mut lines = Vec::new();
+        if let Some(snippet) = self.selected_snippet() {
+            if let Some(name) = &snippet.name {
+                lines.push(Line::from(Span::styled(name.clone(), Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD))));
+            }
+            if let Some(description) = &snippet.description {
+                lines.push(Line::from(description.clone()));
+            }
+            if !lines.is_empty() {
+                lines.push(Line::from(""));
+            }
+            lines.extend(snippet.command.lines().map(|line| Line::from(line.to_string())));
+            
+            let placeholders = snippets::placeholders(&snippet.command);
+            if !placeholders.is_empty() {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled("Placeholders:", Style::default().fg(colors::secondary()))));
+                for placeholder in placeholders {
+                    let default = if placeholder.default.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" (default: {})", placeholder.default)
+                    };
+                    lines.push(Line::from(format!("  {}{}", placeholder.name, default)));
+                }
+            }
+        }
+        
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Details")
+                .border_style(Style::default().fg(colors::secondary())))
+            .wrap(Wrap { trim: false });
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    /// Render status bar
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
+        
+        let help_text = "Enter Run • p Print • e Edit • d Delete • ? Help • Esc Quit";
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the snippet browser, from where it was left in this directory
+    pub fn run(&mut self) -> io::Result<()> {
+        // Keep stdout clean for a printed snippet when it is captured
+        let result = if io::stdout().is_terminal() {
+            session::resume(self, "snip", |browser| tui_common::run_app_on(browser, io::stdout()))
+        } else {
+            session::resume(self, "snip", |browser| tui_common::run_app_on(browser, io::stderr()))
+        };
+        
+        if let Some(command) = &self.output {
+            println!("{}", command);
+        }
+        result
+    }
+    
+    /// Print the snippets instead of running the TUI
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        plain::print(format, self.snippets.iter().map(|snippet| vec![
+            ("name", Value::from(snippet.name.as_deref())),
+            ("command", Value::from(snippet.command.as_str())),
+            ("description", Value::from(snippet.description.as_deref())),
+        ]))
+    }
+}
+
+impl App for SnippetBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        if let AppEvent::Key(key) = event {
+            self.handle_key(key)?;
+        }
+        Ok(if self.should_quit {
+            Action::Quit
+        } else if self.run_requested.is_some() {
+            Action::Suspend
+        } else {
+            Action::Continue
+        })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        matches!(self.mode, Mode::Input(..) | Mode::Fill { .. })
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn suspended(&mut self) -> io::Result<Action> {
+        if let Some(command) = self.run_requested.take() {
+            let shell = platform::user_shell();
+            match tui_common::run_and_wait(&command, &shell, "tt snip") {
+                Ok(quit) => self.should_quit = quit,
+                Err(e) => self.notifications.error(format!("Could not run command: {}", e)),
+            }
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+}
+
+impl Resume for SnippetBrowser {
+    /// The selected snippet, by its command since names are optional
+    fn session(&self) -> Session {
+        Session {
+            selected: self.selected_snippet().map(|snippet| snippet.command.clone()),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        let position = session.selected.and_then(|selected| {
+            self.snippets.iter().position(|snippet| snippet.command == selected)
+        });
+        if position.is_some() {
+            self.list_state.select(position);
+        }
+    }
+}
+
+/// Run the snippet browser, or print the snippets in `output`
+pub fn run(output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut browser = SnippetBrowser::new(settings)?;
+    match output {
+        Some(format) => browser.print(format),
+        None => browser.run(),
+    }
+}
@@ -0,0 +1,504 @@
+//! System dashboard: CPU per core, memory and swap, disks, network
+//! throughput and the busiest processes.
+
+use crate::clipboard;
+use crate::config::Settings;
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::process_info::{self, CpuSampler, ProcessInfo, SystemSummary};
+use crate::system_stats::{self, Disk, NetSampler};
+use crate::text_width;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, SelectableList, VimNav};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{Block, Borders, LineGauge, ListState, Paragraph, Sparkline},
+    Frame,
+};
+use serde_json::Value;
+use std::{
+    io, thread,
+    time::{Duration, Instant},
+};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move through the processes"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Home / End, gg / G", "First / last process"),
+    ("s", "Sort processes by CPU or memory"),
+    ("r", "Refresh now"),
+    ("p", "Pause or resume the auto-refresh"),
+    ("y", "Copy the selected PID"),
+    ("q / Esc", "Quit"),
+];
+
+/// Width of one core's gauge in the CPU panel
+const CORE_WIDTH: u16 = 22;
+
+/// Disks are asked for every this many refreshes, since `df` is a process
+const DISK_REFRESHES: u32 = 5;
+
+/// Disks listed at most
+const MAX_DISKS: usize = 8;
+
+/// What the process list is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sort {
+    Cpu,
+    Memory,
+}
+
+pub struct SysDashboard {
+    cpu_sampler: CpuSampler,
+    system: SystemSummary,
+    processes: Vec<ProcessInfo>,
+    disks: Vec<Disk>,
+    net: NetSampler,
+    sort: Sort,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    refresh_interval: Duration,
+    last_refresh: Instant,
+    paused: bool,
+    /// Refreshes since the disks were read
+    refreshes: u32,
+    settings: Settings,
+}
+
+/// Green, then yellow past half and red past 80%, as in `tt kill`
+fn usage_color(percent: f64) -> Color {
+    if percent > 80.0 {
+        colors::danger()
+    } else if percent > 50.0 {
+        colors::secondary()
+    } else {
+        colors::success()
+    }
+}
+
+/// A one-line gauge labelled `label`
+fn gauge(label: String, percent: f64) -> LineGauge<'static> {
+    LineGauge::default()
+        .label(label)
+        .ratio((percent / 100.0).clamp(0.0, 1.0))
+        .line_set(symbols::line::THICK)
+        .filled_style(Style::default().fg(usage_color(percent)))
+        .unfilled_style(Style::default().fg(colors::muted()))
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 { 0.0 } else { used as f64 / total as f64 * 100.0 }
+}
+
+fn rate(bytes: u64) -> String {
+    format!("{}/s", tui_common::format_size(bytes))
+}
+
+impl SysDashboard {
+    /// Create a dashboard refreshed every `interval` seconds (0 disables
+    /// auto-refresh)
+    pub fn new(interval: u64, settings: Settings) -> io::Result<Self> {
+        let mut dashboard = SysDashboard {
+            cpu_sampler: CpuSampler::default(),
+            system: SystemSummary::default(),
+            processes: Vec::new(),
+            disks: Vec::new(),
+            net: NetSampler::default(),
+            sort: Sort::Cpu,
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::with_status("Sampling..."),
+            refresh_interval: Duration::from_secs(interval.max(1)),
+            last_refresh: Instant::now(),
+            paused: interval == 0,
+            refreshes: 0,
+            settings,
+        };
+        
+        dashboard.refresh()?;
+        Ok(dashboard)
+    }
+    
+    /// Take a new sample of everything; usage figures need two
+    fn refresh(&mut self) -> io::Result<()> {
+        let selected = self.current().map(|process| process.pid);
+        
+        let mut processes = process_info::list_processes()?;
+        self.cpu_sampler.apply(&mut processes);
+        self.system = process_info::system_summary();
+        self.net.sample();
+        self.sort_processes(processes);
+        
+        if self.refreshes % DISK_REFRESHES == 0 {
+            match system_stats::disks() {
+                Ok(disks) => self.disks = disks,
+                Err(e) => self.notifications.error(format!("Cannot list disks: {}", e)),
+            }
+        }
+        self.refreshes += 1;
+        self.last_refresh = Instant::now();
+        
+        let index = selected.and_then(|pid| self.processes.iter().position(|process| process.pid == pid));
+        self.list_state.select(index.or(if self.processes.is_empty() { None } else { Some(0) }));
+        self.notifications.set_status(format!("{} processes", self.processes.len()));
+        Ok(())
+    }
+    
+    fn sort_processes(&mut self, mut processes: Vec<ProcessInfo>) {
+        match self.sort {
+            Sort::Cpu => processes.sort_by(|a, b| b.cpu.total_cmp(&a.cpu).then(b.rss_bytes.cmp(&a.rss_bytes))),
+            Sort::Memory => processes.sort_by_key(|process| std::cmp::Reverse(process.rss_bytes)),
+        }
+        self.processes = processes;
+    }
+    
+    /// Refresh once the interval has elapsed; returns whether it did
+    fn auto_refresh(&mut self) -> bool {
+        if self.paused || self.last_refresh.elapsed() < self.refresh_interval {
+            return false;
+        }
+        
+        if let Err(e) = self.refresh() {
+            self.notifications.error(format!("Refresh failed: {}", e));
+            self.last_refresh = Instant::now();
+        }
+        true
+    }
+    
+    fn current(&self) -> Option<&ProcessInfo> {
+        self.list_state.selected().and_then(|index| self.processes.get(index))
+    }
+    
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if self.vim.navigate(&mut self.list_state, key.code, key.modifiers, self.processes.len(), self.settings.page_size) {
+            return Ok(());
+        }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+            KeyCode::Char('s') => {
+                self.sort = match self.sort {
+                    Sort::Cpu => Sort::Memory,
+                    Sort::Memory => Sort::Cpu,
+                };
+                let processes = std::mem::take(&mut self.processes);
+                self.sort_processes(processes);
+                self.list_state.select(if self.processes.is_empty() { None } else { Some(0) });
+            }
+            KeyCode::Char('r') => {
+                if let Err(e) = self.refresh() {
+                    self.notifications.error(format!("Refresh failed: {}", e));
+                }
+            }
+            KeyCode::Char('p') => {
+                self.paused = !self.paused;
+                self.last_refresh = Instant::now();
+                self.notifications.info(if self.paused {
+                    "Auto-refresh paused".to_string()
+                } else {
+                    format!("Auto-refresh every {}s", self.refresh_interval.as_secs())
+                });
+            }
+            KeyCode::Char('y') => {
+                if let Some(pid) = self.current().map(|process| process.pid.to_string()) {
+                    clipboard::copy_and_notify(&mut self.notifications, "the PID", &pid);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let cores = self.cpu_sampler.core_usage().len().max(1) as u16;
+        let per_row = (area.width.saturating_sub(2) / CORE_WIDTH).max(1);
+        let cpu_height = cores.div_euclid(per_row) + u16::from(cores % per_row != 0) + 2;
+        let disk_height = self.disks.len().clamp(1, MAX_DISKS) as u16 + 2;
+        
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(cpu_height),
+                Constraint::Length(5),
+                Constraint::Length(disk_height),
+                Constraint::Min(5),
+                Constraint::Length(1),
+            ])
+            .split(area);
+        
+        self.render_cpu(f, chunks[0]);
+        self.render_memory_and_network(f, chunks[1]);
+        self.render_disks(f, chunks[2]);
+        self.render_processes(f, chunks[3]);
+        self.render_status_bar(f, chunks[4]);
+    }
+    
+    /// A gauge per core, as many to a row as fit
+    fn render_cpu(&self, f: &mut Frame, area: Rect) {
+        let total = self.cpu_sampler.system_usage();
+        let mut title = match total {
+            Some(usage) => format!("CPU {:.1}%", usage),
+            None => "CPU".to_string(),
+        };
+        if let Some([one, five, fifteen]) = self.system.load {
+            title.push_str(&format!(" • load {:.2} {:.2} {:.2}", one, five, fifteen));
+        }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(colors::primary()));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        
+        let cores = self.cpu_sampler.core_usage();
+        if cores.is_empty() {
+            let message = if total.is_some() || cfg!(not(target_os = "linux")) {
+                "Per-core usage needs /proc (Linux)"
+            } else {
+                "Sampling..."
+            };
+            f.render_widget(Paragraph::new(Span::styled(message, Style::default().fg(colors::muted()))), inner);
+            return;
+        }
+        let per_row = (inner.width / CORE_WIDTH).max(1) as usize;
+        let width = inner.width / per_row as u16;
+        for (index, usage) in cores.iter().enumerate() {
+            let cell = Rect {
+                x: inner.x + (index % per_row) as u16 * width,
+                y: inner.y + (index / per_row) as u16,
+                width: width.saturating_sub(1),
+                height: 1,
+            };
+            if cell.y >= inner.bottom() {
+                break;
+            }
+            f.render_widget(gauge(format!("{:>2} {:>3.0}%", index, usage), f64::from(*usage)), cell);
+        }
+    }
+    
+    /// Memory and swap gauges beside the throughput sparklines
+    fn render_memory_and_network(&self, f: &mut Frame, area: Rect) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(30), Constraint::Percentage(30)])
+            .split(area);
+        
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Memory")
+            .border_style(Style::default().fg(colors::primary()));
+        let inner = block.inner(columns[0]);
+        f.render_widget(block, columns[0]);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(1), Constraint::Length(1)])
+            .split(inner);
+        
+        let total = self.system.memory_total;
+        match self.system.memory_available {
+            Some(available) => {
+                let used = total.saturating_sub(available);
+                let label = format!("RAM  {} / {}", tui_common::format_size(used), tui_common::format_size(total));
+                f.render_widget(gauge(label, percent(used, total)), rows[0]);
+            }
+            None => {
+                let label = format!("RAM  {} total", tui_common::format_size(total));
+                f.render_widget(Paragraph::new(label), rows[0]);
+            }
+        }
+        match self.system.swap {
+            Some((0, _)) => f.render_widget(Paragraph::new(Span::styled("Swap none", Style::default().fg(colors::muted()))), rows[1]),
+            Some((swap_total, free)) => {
+                let used = swap_total.saturating_sub(free);
+                let label = format!("Swap {} / {}", tui_common::format_size(used), tui_common::format_size(swap_total));
+                f.render_widget(gauge(label, percent(used, swap_total)), rows[1]);
+            }
+            None => {}
+        }
+        
+        let history: Vec<_> = self.net.history.iter().collect();
+        let mut sparkline = |title: String, data: Vec<u64>, color: Color, area: Rect| {
+            let width = area.width.saturating_sub(2) as usize;
+            let data = data[data.len().saturating_sub(width)..].to_vec();
+            let sparkline = Sparkline::default()
+                .block(Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(Style::default().fg(colors::muted())))
+                .data(data)
+                .style(Style::default().fg(color));
+            f.render_widget(sparkline, area);
+        };
+        let current = self.net.current();
+        sparkline(
+            format!("↓ {}", rate(current.received)),
+            history.iter().map(|rates| rates.received).collect(),
+            colors::success(),
+            columns[1],
+        );
+        sparkline(
+            format!("↑ {}", rate(current.sent)),
+            history.iter().map(|rates| rates.sent).collect(),
+            colors::secondary(),
+            columns[2],
+        );
+    }
+    
+    /// A gauge per mounted filesystem
+    fn render_disks(&self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Disks")
+            .border_style(Style::default().fg(colors::primary()));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        
+        if self.disks.is_empty() {
+            f.render_widget(Paragraph::new(Span::styled("No disks found", Style::default().fg(colors::muted()))), inner);
+            return;
+        }
+        let mount_width = self.disks.iter().take(MAX_DISKS).map(|disk| text_width::width(&disk.mount)).max().unwrap_or(0).min(24);
+        for (index, disk) in self.disks.iter().take(inner.height as usize).enumerate() {
+            let label = format!(
+                "{}  {:>8} / {:<8}",
+                text_width::pad(&disk.mount, mount_width),
+                tui_common::format_size(disk.used),
+                tui_common::format_size(disk.total),
+            );
+            let row = Rect { y: inner.y + index as u16, height: 1, ..inner };
+            f.render_widget(gauge(label, disk.percent()), row);
+        }
+    }
+    
+    /// The busiest processes
+    fn render_processes(&mut self, f: &mut Frame, area: Rect) {
+        let items: Vec<Line> = self.processes
+            .iter()
+            .map(|process| {
+                Line::from(vec![
+                    Span::styled(format!("{:>8}", process.pid), Style::default().fg(colors::secondary())),
+                    Span::raw(" "),
+                    Span::styled(text_width::pad(&process.user, 10), Style::default().fg(colors::muted())),
+                    Span::raw(" "),
+                    Span::styled(format!("{:>6.1}", process.cpu), Style::default().fg(usage_color(f64::from(process.cpu)))),
+                    Span::raw(" "),
+                    Span::styled(format!("{:>6.1}", process.memory), Style::default().fg(usage_color(f64::from(process.memory)))),
+                    Span::raw(" "),
+                    Span::styled(format!("{:>9}", tui_common::format_size(process.rss_bytes)), Style::default().fg(colors::text())),
+                    Span::raw("  "),
+                    Span::styled(process.name.clone(), Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD)),
+                ])
+            })
+            .collect();
+        
+        let refresh_state = if self.paused {
+            "⏸ paused".to_string()
+        } else {
+            format!("↻ {}s", self.refresh_interval.as_secs())
+        };
+        let sort = match self.sort {
+            Sort::Cpu => "CPU",
+            Sort::Memory => "memory",
+        };
+        let title = format!("Processes by {} - {}", sort, refresh_state);
+        
+        let header = Line::from(Span::styled(
+            format!("{:>8} {:<10} {:>6} {:>6} {:>9}  {}", "PID", "USER", "CPU%", "MEM%", "RSS", "NAME"),
+            Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD),
+        ));
+        
+        let list = SelectableList::new(items)
+            .header(header)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let help_text = "j/k Move • s Sort • r Refresh • p Pause • y Copy PID • ? Help • q Quit";
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the dashboard
+    pub fn run(&mut self) -> io::Result<()> {
+        tui_common::run_app(self)
+    }
+    
+    /// Print one reading of every figure instead of running the TUI, after
+    /// a second sample one interval later, so usage and rates are current:
+    /// CPU in percent, memory, swap and disks in bytes used, network in bytes
+    /// per second
+    pub fn print(&mut self, format: Format) -> io::Result<()> {
+        thread::sleep(self.refresh_interval);
+        self.refresh()?;
+        plain::check(&self.notifications)?;
+        
+        let record = |metric: &'static str, name: String, value: Value, total: Value| {
+            vec![("metric", Value::from(metric)), ("name", Value::from(name)), ("value", value), ("total", total)]
+        };
+        let mut records = Vec::new();
+        if let Some(usage) = self.cpu_sampler.system_usage() {
+            records.push(record("cpu", "all".to_string(), plain::tenths(f64::from(usage)), Value::Null));
+        }
+        for (index, usage) in self.cpu_sampler.core_usage().iter().enumerate() {
+            records.push(record("cpu", format!("cpu{}", index), plain::tenths(f64::from(*usage)), Value::Null));
+        }
+        if let Some(available) = self.system.memory_available {
+            let total = self.system.memory_total;
+            records.push(record("memory", "ram".to_string(), Value::from(total.saturating_sub(available)), Value::from(total)));
+        }
+        if let Some((total, free)) = self.system.swap {
+            records.push(record("memory", "swap".to_string(), Value::from(total.saturating_sub(free)), Value::from(total)));
+        }
+        for disk in &self.disks {
+            records.push(record("disk", disk.mount.clone(), Value::from(disk.used), Value::from(disk.total)));
+        }
+        for (interface, rates) in &self.net.interfaces {
+            records.push(record("net_received", interface.clone(), Value::from(rates.received), Value::Null));
+            records.push(record("net_sent", interface.clone(), Value::from(rates.sent), Value::Null));
+        }
+        plain::print(format, records)
+    }
+}
+
+impl App for SysDashboard {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key)?,
+            AppEvent::Tick if self.auto_refresh() => return Ok(Action::Redraw),
+            AppEvent::Tick => {}
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+/// Run the dashboard, or print one reading of it in `output`
+pub fn run(interval: u64, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut dashboard = SysDashboard::new(interval, settings)?;
+    match output {
+        Some(format) => dashboard.print(format),
+        None => dashboard.run(),
+    }
+}
@@ -0,0 +1,580 @@
+//! systemd service browser with journal preview and start/stop/restart/
+//! enable/disable actions.
+
+use crate::clipboard;
+use crate::config::Settings;
+use crate::jobs::{self, Job};
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::platform;
+use crate::session::{self, Resume, Session};
+use crate::systemd::{self, Scope, Unit, UnitAction};
+use crate::text_width;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Debounce, Keymap, NavKey, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+use std::io;
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Filter by unit name or description"),
+    ("Backspace", "Delete a filter character"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Tab", "Show services, failed units or all units"),
+    ("s / x / r", "Start / stop / restart the unit (normal mode)"),
+    ("e / d", "Enable / disable the unit (normal mode)"),
+    ("Enter", "Open the whole journal of the unit in a pager"),
+    ("R", "Refresh (normal mode)"),
+    ("y", "Copy the unit name (normal mode)"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
+/// Journal lines read for the preview
+const JOURNAL_LINES: usize = 200;
+
+/// Which units are listed, cycled with Tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Services,
+    Failed,
+    All,
+}
+
+impl View {
+    fn next(self) -> Self {
+        match self {
+            View::Services => View::Failed,
+            View::Failed => View::All,
+            View::All => View::Services,
+        }
+    }
+    
+    fn label(self) -> &'static str {
+        match self {
+            View::Services => "Services",
+            View::Failed => "Failed Units",
+            View::All => "All Units",
+        }
+    }
+    
+    fn shows(self, unit: &Unit) -> bool {
+        match self {
+            View::Services => unit.kind() == "service",
+            View::Failed => unit.is_failed(),
+            View::All => true,
+        }
+    }
+}
+
+enum Mode {
+    Normal,
+    /// Asking before an action is run on a unit
+    Confirm(UnitAction, String),
+}
+
+/// Color of an active/sub state
+fn state_color(unit: &Unit) -> Color {
+    match (unit.active.as_str(), unit.sub.as_str()) {
+        ("failed", _) => colors::danger(),
+        (_, "running") => colors::success(),
+        ("activating" | "deactivating" | "reloading", _) => colors::warning(),
+        ("inactive", _) => colors::muted(),
+        _ => colors::text(),
+    }
+}
+
+/// Color of a unit file state
+fn enabled_color(state: &str) -> Color {
+    match state {
+        "enabled" | "enabled-runtime" => colors::success(),
+        "masked" | "masked-runtime" | "bad" => colors::danger(),
+        "disabled" => colors::muted(),
+        _ => colors::text(),
+    }
+}
+
+pub struct ServiceBrowser {
+    scope: Scope,
+    units: Vec<Unit>,
+    /// Indices into `units` that pass the view and filter
+    filtered: Vec<usize>,
+    query: TextInput,
+    view: View,
+    mode: Mode,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    /// `systemctl list-units` running in the background
+    loading: Option<Job<Vec<Unit>>>,
+    /// Unit to select once the units are loaded
+    resume_selected: Option<String>,
+    /// The unit the journal preview belongs to, and its last lines
+    journal: Option<(String, Vec<String>)>,
+    /// `journalctl` of the selected unit running in the background
+    journal_job: Option<Job<Vec<String>>>,
+    journal_debounce: Debounce,
+    /// A command to run on the terminal: a pager, or an action sudo needs a
+    /// password for
+    pending_command: Option<String>,
+    settings: Settings,
+}
+
+impl ServiceBrowser {
+    pub fn new(scope: Scope, filter: Option<String>, view: View, settings: Settings) -> io::Result<Self> {
+        let mut browser = ServiceBrowser {
+            scope,
+            units: Vec::new(),
+            filtered: Vec::new(),
+            query: TextInput::new(filter.unwrap_or_default()),
+            view,
+            mode: Mode::Normal,
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::with_status("Loading units..."),
+            loading: None,
+            resume_selected: None,
+            journal: None,
+            journal_job: None,
+            journal_debounce: Debounce::default(),
+            pending_command: None,
+            settings,
+        };
+        
+        browser.refresh();
+        
+        Ok(browser)
+    }
+    
+    fn set_summary(&mut self) {
+        let running = self.units.iter().filter(|unit| unit.sub == "running").count();
+        let failed = self.units.iter().filter(|unit| unit.is_failed()).count();
+        let scope = if self.scope == Scope::User { "user" } else { "system" };
+        self.notifications.set_status(format!("{} {} units • {} running • {} failed", self.units.len(), scope, running, failed));
+    }
+    
+    /// Start reloading the units in the background, keeping the same one
+    /// selected
+    fn refresh(&mut self) {
+        if let Some(unit) = self.current() {
+            self.resume_selected = Some(unit.name.clone());
+        }
+        let scope = self.scope;
+        self.loading = Some(self.notifications.jobs().spawn("systemctl list-units", move |task| systemd::list_units(task, scope)));
+    }
+    
+    /// Show the units once they are loaded; returns whether they were
+    fn poll_units(&mut self) -> bool {
+        let Some(result) = self.loading.as_mut().and_then(Job::poll) else {
+            return false;
+        };
+        self.loading = None;
+        match result {
+            Ok(units) => self.set_units(units),
+            Err(e) if jobs::is_cancelled(&e) => self.notifications.set_status("Loading cancelled"),
+            Err(e) => {
+                self.notifications.set_status("");
+                self.notifications.error(format!("Could not list units: {}", e));
+            }
+        }
+        true
+    }
+    
+    fn set_units(&mut self, units: Vec<Unit>) {
+        self.units = units;
+        self.update_filter();
+        self.set_summary();
+        
+        let selected = self.resume_selected.take();
+        if let Some(index) = selected.and_then(|name| self.filtered.iter().position(|index| self.units[*index].name == name)) {
+            self.list_state.select(Some(index));
+        }
+        self.load_journal();
+    }
+    
+    /// Recompute the visible units from the view and filter
+    fn update_filter(&mut self) {
+        let query = self.query.value().to_lowercase();
+        self.filtered = self.units
+            .iter()
+            .enumerate()
+            .filter(|(_, unit)| self.view.shows(unit))
+            .filter(|(_, unit)| {
+                query.is_empty() || unit.name.to_lowercase().contains(&query) || unit.description.to_lowercase().contains(&query)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        self.list_state.select(if self.filtered.is_empty() { None } else { Some(0) });
+        self.journal_debounce.schedule();
+    }
+    
+    fn current(&self) -> Option<&Unit> {
+        let selected = self.list_state.selected()?;
+        self.filtered.get(selected).map(|index| &self.units[*index])
+    }
+    
+    /// Start reading the journal of the selected unit, cancelling the read
+    /// before
+    fn load_journal(&mut self) {
+        self.journal_debounce.take();
+        let Some(name) = self.current().map(|unit| unit.name.clone()) else {
+            self.journal = None;
+            self.journal_job = None;
+            return;
+        };
+        let (scope, unit) = (self.scope, name.clone());
+        let label = format!("journalctl -u {}", name);
+        self.journal_job = Some(self.notifications.jobs().spawn(label, move |task| systemd::journal(task, scope, &unit, JOURNAL_LINES)));
+        self.journal = Some((name, vec!["Loading journal...".to_string()]));
+    }
+    
+    /// Show the journal once it is read; returns whether it was
+    fn poll_journal(&mut self) -> bool {
+        let Some(result) = self.journal_job.as_mut().and_then(Job::poll) else {
+            return false;
+        };
+        self.journal_job = None;
+        let lines = match result {
+            Ok(lines) => lines,
+            Err(e) if jobs::is_cancelled(&e) => vec!["Loading cancelled".to_string()],
+            Err(e) => vec![format!("Could not run journalctl: {}", e)],
+        };
+        if let Some((_, journal)) = self.journal.as_mut() {
+            *journal = lines;
+        }
+        true
+    }
+    
+    fn handle_key(&mut self, key: KeyEvent) {
+        if let Mode::Confirm(action, unit) = &self.mode {
+            let (action, unit) = (*action, unit.clone());
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                    self.mode = Mode::Normal;
+                    self.run_action(action, &unit);
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    self.mode = Mode::Normal;
+                    self.notifications.info(format!("{} cancelled", action.verb()));
+                }
+                _ => {}
+            }
+            return;
+        }
+        
+        match self.vim.filter_key(&mut self.list_state, key.code, key.modifiers, self.filtered.len(), self.settings.page_size) {
+            NavKey::Moved => {
+                self.journal_debounce.schedule();
+                return;
+            }
+            NavKey::Used => return,
+            NavKey::Unused => {}
+        }
+        let letters = !self.vim.inserting() && !key.modifiers.contains(KeyModifiers::CONTROL);
+        
+        let action = match key.code {
+            KeyCode::Char('s') if letters => Some(UnitAction::Start),
+            KeyCode::Char('x') if letters => Some(UnitAction::Stop),
+            KeyCode::Char('r') if letters => Some(UnitAction::Restart),
+            KeyCode::Char('e') if letters => Some(UnitAction::Enable),
+            KeyCode::Char('d') if letters => Some(UnitAction::Disable),
+            _ => None,
+        };
+        if let Some(action) = action {
+            if let Some(unit) = self.current() {
+                self.mode = Mode::Confirm(action, unit.name.clone());
+            }
+            return;
+        }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('R') if letters => {
+                self.refresh();
+            }
+            KeyCode::Char('y') if letters => {
+                if let Some(name) = self.current().map(|unit| unit.name.clone()) {
+                    clipboard::copy_and_notify(&mut self.notifications, "the unit name", &name);
+                }
+            }
+            KeyCode::Tab => {
+                self.view = self.view.next();
+                self.update_filter();
+            }
+            KeyCode::Enter => {
+                if let Some(unit) = self.current() {
+                    self.pending_command = Some(systemd::journal_command(self.scope, &unit.name));
+                }
+            }
+            code if !key.modifiers.contains(KeyModifiers::CONTROL) && self.vim.inserting() && self.query.handle_key(code) => {
+                self.update_filter();
+            }
+            _ => {}
+        }
+    }
+    
+    /// Run a confirmed action; when sudo needs a password it is run on the
+    /// terminal instead
+    fn run_action(&mut self, action: UnitAction, unit: &str) {
+        match action.run(self.scope, unit) {
+            Ok(()) => {
+                self.notifications.success(format!("{} {}", unit, action.done()));
+                self.refresh();
+            }
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                self.pending_command = Some(action.command(self.scope, unit));
+            }
+            Err(e) => self.notifications.error(format!("Could not {} {}: {}", action.verb(), unit, e)),
+        }
+    }
+    
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Percentage(55), Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        
+        self.render_filter(f, chunks[0]);
+        self.render_list(f, chunks[1]);
+        self.render_journal(f, chunks[2]);
+        self.render_status_bar(f, chunks[3]);
+        
+        if let Mode::Confirm(action, unit) = &self.mode {
+            let mut verb = action.verb().to_string();
+            verb[..1].make_ascii_uppercase();
+            let lines = vec![
+                Line::from(Span::styled(
+                    format!("{} {}?", verb, unit),
+                    Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD),
+                )),
+                Line::from(""),
+                Line::from(Span::styled(
+                    format!("$ {}", action.command(self.scope, unit)),
+                    Style::default().fg(colors::secondary()),
+                )),
+                Line::from(""),
+                Line::from(Span::styled("[Y]es / [N]o", Style::default().fg(colors::text()).add_modifier(Modifier::BOLD))),
+            ];
+            let border = match action {
+                UnitAction::Stop | UnitAction::Disable => colors::danger(),
+                _ => colors::warning(),
+            };
+            tui_common::render_message_popup(f, "Confirm", lines, border);
+        }
+    }
+    
+    fn render_filter(&self, f: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.query.line())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Filter")
+                .border_style(Style::default().fg(colors::secondary())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    fn render_list(&mut self, f: &mut Frame, area: Rect) {
+        let name_width = self.filtered
+            .iter()
+            .map(|index| text_width::width(&self.units[*index].name))
+            .max()
+            .unwrap_or(0)
+            .clamp(20, 40);
+        
+        let items: Vec<Line> = self.filtered
+            .iter()
+            .map(|index| {
+                let unit = &self.units[*index];
+                let enabled = unit.enabled.as_deref().unwrap_or("");
+                Line::from(vec![
+                    Span::styled(
+                        text_width::pad(&text_width::truncate(&unit.name, name_width), name_width),
+                        Style::default().fg(colors::primary()).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        text_width::pad(&format!("{}/{}", unit.active, unit.sub), 20),
+                        Style::default().fg(state_color(unit)),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(text_width::pad(enabled, 9), Style::default().fg(enabled_color(enabled))),
+                    Span::raw(" "),
+                    Span::styled(unit.description.clone(), Style::default().fg(colors::text())),
+                ])
+            })
+            .collect();
+        
+        let header = Line::from(Span::styled(
+            format!("{} {:<20} {:<9} {}", text_width::pad("UNIT", name_width), "STATE", "ENABLED", "DESCRIPTION"),
+            Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD),
+        ));
+        
+        let list = SelectableList::new(items)
+            .header(header)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} ({})", self.view.label(), self.filtered.len()))
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    /// The end of the selected unit's journal
+    fn render_journal(&self, f: &mut Frame, area: Rect) {
+        let (title, lines) = match &self.journal {
+            Some((name, lines)) => (format!("Journal: {}", name), lines.as_slice()),
+            None => ("Journal".to_string(), &[][..]),
+        };
+        let height = area.height.saturating_sub(2) as usize;
+        let lines: Vec<Line> = if lines.is_empty() {
+            vec![Line::from(Span::styled("No journal entries", Style::default().fg(colors::muted())))]
+        } else {
+            lines[lines.len().saturating_sub(height)..]
+                .iter()
+                .map(|line| Line::from(Span::styled(line.clone(), Style::default().fg(colors::text()))))
+                .collect()
+        };
+        
+        let paragraph = Paragraph::new(lines)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(colors::muted())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let help_text = if matches!(self.mode, Mode::Confirm(..)) {
+            "Y/Enter Confirm • N/Esc Cancel"
+        } else if self.vim.inserting() {
+            "Type to filter • Tab View • Enter Journal • Esc Normal mode (s/x/r/e/d act) • F1 Help"
+        } else {
+            "s Start • x Stop • r Restart • e Enable • d Disable • Enter Journal • Tab View • ? Help • q Quit"
+        };
+        
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the service browser, from where it was left in this directory
+    pub fn run(&mut self) -> io::Result<()> {
+        session::resume(self, "systemd", tui_common::run_app)
+    }
+    
+    /// Print the visible units instead of running the TUI
+    pub fn print(&mut self, format: Format) -> io::Result<()> {
+        if let Some(loading) = self.loading.take() {
+            self.units = loading.wait()?;
+            self.update_filter();
+        }
+        plain::print(format, self.filtered.iter().map(|index| {
+            let unit = &self.units[*index];
+            vec![
+                ("unit", Value::from(unit.name.as_str())),
+                ("load", Value::from(unit.load.as_str())),
+                ("active", Value::from(unit.active.as_str())),
+                ("sub", Value::from(unit.sub.as_str())),
+                ("enabled", unit.enabled.as_deref().map_or(Value::Null, Value::from)),
+                ("description", Value::from(unit.description.as_str())),
+            ]
+        }))
+    }
+}
+
+impl App for ServiceBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key),
+            AppEvent::Tick if self.journal_debounce.ready() => {
+                self.load_journal();
+                return Ok(Action::Redraw);
+            }
+            AppEvent::Tick if self.poll_units() | self.poll_journal() => return Ok(Action::Redraw),
+            AppEvent::Tick => {}
+        }
+        Ok(if self.should_quit {
+            Action::Quit
+        } else if self.pending_command.is_some() {
+            Action::Suspend
+        } else {
+            Action::Continue
+        })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        self.vim.inserting() && matches!(self.mode, Mode::Normal)
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn suspended(&mut self) -> io::Result<Action> {
+        if let Some(command) = self.pending_command.take() {
+            match tui_common::run_and_wait(&command, &platform::user_shell(), "tt systemd") {
+                Ok(quit) => self.should_quit = quit,
+                Err(e) => self.notifications.error(format!("Could not run {}: {}", command, e)),
+            }
+            self.refresh();
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+}
+
+impl Resume for ServiceBrowser {
+    fn session(&self) -> Session {
+        Session {
+            query: Some(self.query.value().to_string()),
+            selected: self.current().map(|unit| unit.name.clone()),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query.filter(|_| self.query.value().is_empty()) {
+            self.query = TextInput::new(query);
+            self.update_filter();
+        }
+        if self.loading.is_some() {
+            // The units come in the background
+            self.resume_selected = session.selected;
+            return;
+        }
+        let position = session.selected.and_then(|name| self.filtered.iter().position(|index| self.units[*index].name == name));
+        if position.is_some() {
+            self.list_state.select(position);
+        }
+        self.load_journal();
+    }
+}
+
+/// Run the service browser over the units of `scope`, or print them in
+/// `output`
+pub fn run(scope: Scope, filter: Option<String>, view: View, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut browser = ServiceBrowser::new(scope, filter, view, settings)?;
+    match output {
+        Some(format) => browser.print(format),
+        None => browser.run(),
+    }
+}
@@ -0,0 +1,538 @@
+//! TODO/FIXME/HACK/XXX browser, grouped by tag and file, with the code
+//! around each note and the editor a key away.
+
+use crate::clipboard;
+use crate::config::Settings;
+use crate::jobs::{self, Job, Task};
+use crate::mru;
+use crate::notify::Notifications;
+use crate::palette::Context;
+use crate::plain::{self, Format};
+use crate::preview::{Preview, PreviewEngine};
+use crate::session::{self, Resume, Session};
+use crate::todos::{self, Note, Tag};
+use crate::tools::search::{self, SearchResult};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, NavKey, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Filter by note, author or file"),
+    ("Backspace", "Delete a filter character"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Enter", "Open the editor at the note, or fold a tag or file"),
+    ("R", "Scan the tree again (normal mode)"),
+    ("y", "Copy file:line (normal mode)"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
+/// A comment found in the tree
+struct Todo {
+    result: SearchResult,
+    note: Note,
+}
+
+/// A group of notes that can be folded
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Group {
+    Tag(Tag),
+    File(Tag, PathBuf),
+}
+
+/// A row of the list
+enum Row {
+    /// A tag and its number of notes
+    Tag(Tag, usize),
+    /// A file under a tag and its number of notes
+    File(Tag, usize, usize),
+    /// A note, by index into `todos`
+    Note(usize),
+}
+
+/// A row told apart from the others across rescans: a group, or a note by
+/// its location
+#[derive(PartialEq, Eq)]
+enum RowKey {
+    Group(Group),
+    Note(String),
+}
+
+/// Color of a tag
+fn tag_color(tag: Tag) -> Color {
+    match tag {
+        Tag::Fixme => colors::danger(),
+        Tag::Xxx => colors::warning(),
+        Tag::Hack => colors::secondary(),
+        Tag::Todo => colors::primary(),
+    }
+}
+
+pub struct TodoBrowser {
+    path: PathBuf,
+    /// Only these tags, or all when empty
+    tags: Vec<Tag>,
+    /// Sorted by tag, file and line
+    todos: Vec<Todo>,
+    rows: Vec<Row>,
+    collapsed: HashSet<Group>,
+    query: TextInput,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    /// The note the preview shows, the context it was read with and the
+    /// lines around it
+    preview: Option<(usize, usize, Preview)>,
+    previews: PreviewEngine,
+    /// A note to open in the editor, once the terminal is free
+    pending_open: Option<usize>,
+    /// The search for notes running in the background
+    scan: Option<Job<Vec<Todo>>>,
+    /// Note to select once the scan restored from the session is done
+    resume_selected: Option<RowKey>,
+    settings: Settings,
+}
+
+impl TodoBrowser {
+    pub fn new(path: PathBuf, tags: Vec<Tag>, settings: Settings) -> io::Result<Self> {
+        let mut browser = TodoBrowser {
+            path,
+            tags,
+            todos: Vec::new(),
+            rows: Vec::new(),
+            collapsed: HashSet::new(),
+            query: TextInput::new(""),
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::new(),
+            preview: None,
+            previews: PreviewEngine::new(&settings),
+            pending_open: None,
+            scan: None,
+            resume_selected: None,
+            settings,
+        };
+        browser.scan();
+        Ok(browser)
+    }
+    
+    /// Search the tree for notes in the background; the notes found
+    /// replace the ones shown when [`TodoBrowser::poll_scan`] sees it done
+    fn scan(&mut self) {
+        let (path, tags, settings) = (self.path.clone(), self.tags.clone(), self.settings.clone());
+        self.notifications.set_status(format!("Scanning {}...", path.display()));
+        let label = format!("Scan {}", path.display());
+        self.scan = Some(self.notifications.jobs().spawn(label, move |task| find_todos(task, &path, &tags, &settings)));
+    }
+    
+    /// Show the notes once the scan is done; returns whether it was
+    fn poll_scan(&mut self) -> bool {
+        let Some(result) = self.scan.as_mut().and_then(Job::poll) else {
+            return false;
+        };
+        self.scan = None;
+        match result {
+            Ok(todos) => self.set_todos(todos),
+            Err(e) if jobs::is_cancelled(&e) => self.notifications.set_status("Scan cancelled"),
+            Err(e) => self.notifications.error(format!("Could not search: {}", e)),
+        }
+        true
+    }
+    
+    fn set_todos(&mut self, todos: Vec<Todo>) {
+        let selected = self.resume_selected.take().or_else(|| self.selected_key());
+        self.todos = todos;
+        
+        let files: HashSet<&Path> = self.todos.iter().map(|todo| todo.result.file_path.as_path()).collect();
+        self.notifications.set_status(format!("{} notes in {} files under {}", self.todos.len(), files.len(), self.path.display()));
+        self.update_rows(selected);
+    }
+    
+    /// Lay out the rows from the notes passing the filter, selecting the
+    /// row of `selected` again where it is still shown
+    fn update_rows(&mut self, selected: Option<RowKey>) {
+        let query = self.query.value().to_lowercase();
+        let matches = |todo: &Todo| {
+            query.is_empty()
+                || todo.note.text.to_lowercase().contains(&query)
+                || todo.note.author.as_deref().is_some_and(|author| author.to_lowercase().contains(&query))
+                || todo.result.file_path.to_string_lossy().to_lowercase().contains(&query)
+        };
+        let shown: Vec<usize> = (0..self.todos.len()).filter(|&index| matches(&self.todos[index])).collect();
+        
+        let mut rows = Vec::new();
+        for tag in Tag::ALL {
+            let in_tag: Vec<usize> = shown.iter().copied().filter(|&index| self.todos[index].note.tag == tag).collect();
+            if in_tag.is_empty() {
+                continue;
+            }
+            rows.push(Row::Tag(tag, in_tag.len()));
+            if self.collapsed.contains(&Group::Tag(tag)) {
+                continue;
+            }
+            for file in group_by_file(&in_tag, &self.todos) {
+                let path = self.todos[file[0]].result.file_path.clone();
+                rows.push(Row::File(tag, file[0], file.len()));
+                if !self.collapsed.contains(&Group::File(tag, path)) {
+                    rows.extend(file.iter().map(|&index| Row::Note(index)));
+                }
+            }
+        }
+        self.rows = rows;
+        
+        let position = selected.and_then(|key| self.rows.iter().position(|row| self.row_key(row) == key));
+        self.list_state.select(match position {
+            Some(position) => Some(position),
+            None if self.rows.is_empty() => None,
+            // Start on the first note rather than its headers
+            None => Some(self.rows.iter().position(|row| matches!(row, Row::Note(_))).unwrap_or(0)),
+        });
+    }
+    
+    /// What a row stands for, to find it again after the rows change
+    fn row_key(&self, row: &Row) -> RowKey {
+        match row {
+            Row::Tag(tag, _) => RowKey::Group(Group::Tag(*tag)),
+            Row::File(tag, first, _) => RowKey::Group(Group::File(*tag, self.todos[*first].result.file_path.clone())),
+            Row::Note(index) => RowKey::Note(self.todos[*index].result.location()),
+        }
+    }
+    
+    fn selected_key(&self) -> Option<RowKey> {
+        let row = self.rows.get(self.list_state.selected()?)?;
+        Some(self.row_key(row))
+    }
+    
+    /// The note selected, or the first one under a selected header
+    fn current(&self) -> Option<usize> {
+        let selected = self.list_state.selected()?;
+        match self.rows.get(selected)? {
+            Row::Note(index) | Row::File(_, index, _) => Some(*index),
+            Row::Tag(tag, _) => self.todos.iter().position(|todo| todo.note.tag == *tag),
+        }
+    }
+    
+    /// The file of the selected note, for another tool to act on
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        self.current().map(|index| self.todos[index].result.file_path.clone())
+    }
+    
+    fn handle_key(&mut self, key: KeyEvent) {
+        match self.vim.filter_key(&mut self.list_state, key.code, key.modifiers, self.rows.len(), self.settings.page_size) {
+            NavKey::Moved | NavKey::Used => return,
+            NavKey::Unused => {}
+        }
+        let letters = !self.vim.inserting() && !key.modifiers.contains(KeyModifiers::CONTROL);
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('R') if letters => self.scan(),
+            KeyCode::Char('y') if letters => {
+                if let Some(index) = self.current() {
+                    let location = self.todos[index].result.location();
+                    clipboard::copy_and_notify(&mut self.notifications, &location, &location);
+                }
+            }
+            KeyCode::Enter => {
+                let Some(row) = self.list_state.selected().and_then(|index| self.rows.get(index)) else {
+                    return;
+                };
+                let group = match row {
+                    Row::Note(index) => {
+                        self.pending_open = Some(*index);
+                        return;
+                    }
+                    Row::Tag(tag, _) => Group::Tag(*tag),
+                    Row::File(tag, first, _) => Group::File(*tag, self.todos[*first].result.file_path.clone()),
+                };
+                if !self.collapsed.remove(&group) {
+                    self.collapsed.insert(group);
+                }
+                self.update_rows(self.selected_key());
+            }
+            code if !key.modifiers.contains(KeyModifiers::CONTROL) && self.vim.inserting() && self.query.handle_key(code) => {
+                self.update_rows(self.selected_key());
+            }
+            _ => {}
+        }
+    }
+    
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[1]);
+        
+        self.render_filter(f, chunks[0]);
+        self.render_list(f, panes[0]);
+        self.render_preview(f, panes[1]);
+        self.render_status_bar(f, chunks[2]);
+    }
+    
+    fn render_filter(&self, f: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.query.line())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Filter")
+                .border_style(Style::default().fg(colors::secondary())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    fn render_list(&mut self, f: &mut Frame, area: Rect) {
+        let fold = |group: Group| if self.collapsed.contains(&group) { "▸" } else { "▾" };
+        let muted = Style::default().fg(colors::muted());
+        let items: Vec<Line> = self.rows
+            .iter()
+            .map(|row| match row {
+                Row::Tag(tag, count) => Line::from(vec![
+                    Span::styled(format!("{} ", fold(Group::Tag(*tag))), muted),
+                    Span::styled(tag.label(), Style::default().fg(tag_color(*tag)).add_modifier(Modifier::BOLD)),
+                    Span::styled(format!(" ({})", count), muted),
+                ]),
+                Row::File(tag, first, count) => {
+                    let path = &self.todos[*first].result.file_path;
+                    Line::from(vec![
+                        Span::styled(format!("  {} ", fold(Group::File(*tag, path.clone()))), muted),
+                        Span::styled(path.display().to_string(), Style::default().fg(colors::secondary())),
+                        Span::styled(format!(" ({})", count), muted),
+                    ])
+                }
+                Row::Note(index) => {
+                    let todo = &self.todos[*index];
+                    let mut spans = vec![Span::styled(format!("    {:>5} ", todo.result.line_number), muted)];
+                    if let Some(author) = &todo.note.author {
+                        spans.push(Span::styled(format!("({}) ", author), Style::default().fg(colors::secondary())));
+                    }
+                    spans.push(Span::styled(todo.note.text.clone(), Style::default().fg(colors::text())));
+                    Line::from(spans)
+                }
+            })
+            .collect();
+        
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Notes ({})", self.rows.iter().filter(|row| matches!(row, Row::Note(_))).count()))
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    /// The code around the selected note
+    fn render_preview(&mut self, f: &mut Frame, area: Rect) {
+        let block = Block::default().borders(Borders::ALL).border_style(Style::default().fg(colors::muted()));
+        let Some(index) = self.current() else {
+            f.render_widget(Paragraph::new("").block(block.title("Context")), area);
+            return;
+        };
+        // As much context as fills the pane
+        let context = (area.height.saturating_sub(3) / 2) as usize;
+        if !self.preview.as_ref().is_some_and(|(shown, lines, _)| *shown == index && *lines == context) {
+            let todo = &self.todos[index];
+            let highlight = Style::default().fg(tag_color(todo.note.tag)).add_modifier(Modifier::BOLD);
+            let mut preview = self.previews.context(&todo.result.file_path, todo.result.line_number as usize, context, highlight);
+            // Cut long lines so the note stays in the middle
+            preview.wrap = false;
+            self.preview = Some((index, context, preview));
+        }
+        
+        let block = block.title(self.todos[index].result.location());
+        if let Some((_, _, preview)) = &self.preview {
+            f.render_widget(preview.paragraph(block), area);
+        }
+    }
+    
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let help_text = if self.vim.inserting() {
+            "Type to filter • Enter Open/Fold • Esc Normal mode • F1 Help"
+        } else {
+            "Enter Open/Fold • R Rescan • y Copy location • / Filter • ? Help • q Quit"
+        };
+        
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the TODO browser, from where it was left in this directory
+    pub fn run(&mut self) -> io::Result<()> {
+        session::resume(self, "todo", tui_common::run_app)
+    }
+    
+    /// Print the notes instead of running the TUI
+    pub fn print(&mut self, format: Format) -> io::Result<()> {
+        if let Some(scan) = self.scan.take() {
+            let todos = scan.wait()?;
+            self.set_todos(todos);
+        }
+        plain::print(format, self.todos.iter().map(|todo| vec![
+            ("tag", Value::from(todo.note.tag.label())),
+            ("path", Value::from(todo.result.file_path.to_string_lossy())),
+            ("line", Value::from(todo.result.line_number)),
+            ("author", todo.note.author.as_deref().map_or(Value::Null, Value::from)),
+            ("text", Value::from(todo.note.text.as_str())),
+        ]))
+    }
+}
+
+/// Search `path` for notes with ripgrep or else grep (which also reads
+/// what .gitignore leaves out, but skips binary files), sorted by tag, file
+/// and line
+fn find_todos(task: &Task, path: &Path, tags: &[Tag], settings: &Settings) -> io::Result<Vec<Todo>> {
+    let mut command = search::rg_command(settings);
+    command.args(["--max-columns", "500"]);
+    let output = match task.output(command.arg("--regexp").arg(todos::PATTERN).arg(path)) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            task.output(search::grep_command(settings).args(["-I", "-E"]).arg(todos::PATTERN).arg(path))?
+        }
+        output => output?,
+    };
+    // Exit code 1 means nothing matched; 2 may still come with matches
+    // when some files were unreadable
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if output.status.code() == Some(2) && output.stdout.is_empty() {
+        return Err(io::Error::other(stderr.lines().next().unwrap_or("search failed").to_string()));
+    }
+    
+    let mut todos: Vec<Todo> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| SearchResult::parse(line, ""))
+        .filter_map(|result| {
+            let note = todos::parse(&result.line_content)?;
+            let result = SearchResult { file_path: tidy_path(&result.file_path), ..result };
+            Some(Todo { result, note })
+        })
+        .filter(|todo| tags.is_empty() || tags.contains(&todo.note.tag))
+        .collect();
+    todos.sort_by(|a, b| {
+        (a.note.tag, &a.result.file_path, a.result.line_number).cmp(&(b.note.tag, &b.result.file_path, b.result.line_number))
+    });
+    Ok(todos)
+}
+
+/// `./src/main.rs` as `src/main.rs`
+fn tidy_path(path: &Path) -> PathBuf {
+    path.strip_prefix(".").unwrap_or(path).to_path_buf()
+}
+
+/// `indices` of notes split into runs in the same file
+fn group_by_file(indices: &[usize], todos: &[Todo]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for &index in indices {
+        match groups.last_mut() {
+            Some(group) if todos[group[0]].result.file_path == todos[index].result.file_path => group.push(index),
+            _ => groups.push(vec![index]),
+        }
+    }
+    groups
+}
+
+impl App for TodoBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key),
+            AppEvent::Tick if self.poll_scan() => return Ok(Action::Redraw),
+            _ => {}
+        }
+        Ok(if self.should_quit {
+            Action::Quit
+        } else if self.pending_open.is_some() {
+            Action::Suspend
+        } else {
+            Action::Continue
+        })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        self.vim.inserting()
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+    
+    fn context(&self) -> Context {
+        Context {
+            dir: Some(self.path.clone()),
+            file: self.selected_path(),
+            ..Context::default()
+        }
+    }
+    
+    fn suspended(&mut self) -> io::Result<Action> {
+        if let Some(index) = self.pending_open.take() {
+            let result = &self.todos[index].result;
+            if !mru::open_in_editor(&self.settings, &result.file_path, Some(result.line_number as usize)) {
+                let message = format!("No editor could open {}; set one in config.toml", result.location());
+                self.notifications.error(message);
+            }
+            // The note may have been dealt with
+            self.preview = None;
+            self.scan();
+        }
+        Ok(Action::Continue)
+    }
+}
+
+impl Resume for TodoBrowser {
+    /// The filter, and the note under the selection by its location
+    fn session(&self) -> Session {
+        Session {
+            query: Some(self.query.value().to_string()),
+            selected: self.current().map(|index| self.todos[index].result.location()),
+            ..Session::default()
+        }
+    }
+    
+    fn restore(&mut self, session: Session) {
+        if let Some(query) = session.query {
+            self.query = TextInput::new(query);
+        }
+        let selected = session.selected.map(RowKey::Note);
+        if self.scan.is_some() {
+            // The notes come in the background
+            self.resume_selected = selected;
+        } else {
+            self.update_rows(selected);
+        }
+    }
+}
+
+/// Run the TODO browser over `path`, or print the notes in `output`
+pub fn run(path: PathBuf, tags: Vec<Tag>, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut browser = TodoBrowser::new(path, tags, settings)?;
+    match output {
+        Some(format) => browser.print(format),
+        None => browser.run(),
+    }
+}
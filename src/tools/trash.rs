@@ -0,0 +1,333 @@
+//! Trash bin viewer for restoring or purging deleted files.
+
+use crate::bounded_read;
+use crate::config::Settings;
+use crate::file_ops::{self, TrashItem};
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, SelectableList, VimNav};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph, Wrap},
+    Frame,
+};
+use serde_json::Value;
+use std::{
+    fs,
+    io,
+    path::PathBuf,
+};
+
+/// Bytes of a trashed file read for its preview
+const PREVIEW_BYTES: u64 = 64 * 1024;
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("↑ / ↓, j / k", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Ctrl-D / Ctrl-U", "Half a page down / up"),
+    ("Home / End, gg / G", "First / last item"),
+    ("Enter / r", "Restore to the original location"),
+    ("d / Delete", "Delete forever"),
+    ("E", "Empty the trash"),
+    ("Esc / q", "Quit"),
+];
+
+/// Pending confirmation for a destructive action
+enum Confirm {
+    /// Permanently delete the selected item
+    Purge,
+    /// Permanently delete everything listed
+    Empty,
+}
+
+pub struct TrashBrowser {
+    /// Trash directories shown: the home trash and `<path>/.tt-trash`
+    trash_dirs: Vec<PathBuf>,
+    items: Vec<TrashItem>,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    preview_content: String,
+    confirm: Option<Confirm>,
+    settings: Settings,
+}
+
+impl TrashBrowser {
+    /// Create a trash browser for the home trash, the trash of the
+    /// filesystem holding `path` and the fallback trash in `path`
+    pub fn new(path: PathBuf, settings: Settings) -> io::Result<Self> {
+        let path = path.canonicalize()?;
+        let mut trash_dirs: Vec<PathBuf> = file_ops::trash_dir().into_iter().collect();
+        trash_dirs.extend(file_ops::volume_trash_dirs(&path));
+        trash_dirs.push(path.join(".tt-trash"));
+        
+        let mut browser = TrashBrowser {
+            trash_dirs,
+            items: Vec::new(),
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::new(),
+            preview_content: String::new(),
+            confirm: None,
+            settings,
+        };
+        
+        browser.load_items();
+        Ok(browser)
+    }
+    
+    /// Reload the trash listing, keeping the selection position
+    fn load_items(&mut self) {
+        self.items = self.trash_dirs.iter().flat_map(|dir| file_ops::list_trash(dir)).collect();
+        self.items.sort_by(|a, b| b.deleted.cmp(&a.deleted));
+        
+        if self.items.is_empty() {
+            self.list_state.select(None);
+        } else {
+            let index = self.list_state.selected().unwrap_or(0);
+            self.list_state.select(Some(index.min(self.items.len() - 1)));
+        }
+        self.notifications.set_status(format!("{} item(s) in trash", self.items.len()));
+        self.update_preview();
+    }
+    
+    fn selected_item(&self) -> Option<&TrashItem> {
+        self.list_state.selected().and_then(|index| self.items.get(index))
+    }
+    
+    /// Update preview content for the selected item
+    fn update_preview(&mut self) {
+        self.preview_content = match self.selected_item() {
+            Some(item) => Self::item_preview(item),
+            None => String::new(),
+        };
+    }
+    
+    fn item_preview(item: &TrashItem) -> String {
+        let mut preview = format!(
+            "Original: {}\nDeleted:  {}\nStored:   {}\n",
+            item.original.display(),
+            item.deleted.replace('T', " "),
+            item.path.display()
+        );
+        
+        if item.path.is_dir() {
+            let size = file_ops::tree_size(&item.path);
+            preview.push_str(&format!("Size:     {}\n\n", tui_common::format_size(size)));
+            if let Ok(entries) = fs::read_dir(&item.path) {
+                let mut names: Vec<String> = entries
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .collect();
+                names.sort();
+                for name in names.iter().take(50) {
+                    preview.push_str(&format!("{}\n", name));
+                }
+            }
+        } else if let Ok(metadata) = item.path.symlink_metadata() {
+            preview.push_str(&format!("Size:     {}\n\n", tui_common::format_size(metadata.len())));
+            if let Ok(Some(head)) = bounded_read::head(&item.path, PREVIEW_BYTES) {
+                let lines: Vec<&str> = head.text.lines().take(50).collect();
+                preview.push_str(&lines.join("\n"));
+            } else {
+                preview.push_str("[Binary file]");
+            }
+        }
+        preview
+    }
+    
+    /// Move the selected item back to its original location
+    fn restore_selected(&mut self) {
+        let Some(item) = self.selected_item().cloned() else {
+            return;
+        };
+        match file_ops::restore(&item) {
+            Ok(()) => self.notifications.success(format!("Restored {}", item.original.display())),
+            Err(e) => self.notifications.error(format!("Could not restore {}: {}", item.original.display(), e)),
+        }
+        self.load_items();
+    }
+    
+    /// Carry out a confirmed purge or empty
+    fn apply_confirm(&mut self, confirm: Confirm) {
+        let targets: Vec<TrashItem> = match confirm {
+            Confirm::Purge => self.selected_item().cloned().into_iter().collect(),
+            Confirm::Empty => self.items.clone(),
+        };
+        
+        let mut errors = 0;
+        for item in &targets {
+            if file_ops::purge(item).is_err() {
+                errors += 1;
+            }
+        }
+        if errors == 0 {
+            self.notifications.success(format!("Permanently deleted {} item(s)", targets.len()));
+        } else {
+            self.notifications.error(format!("Deleted {} item(s), {} failed", targets.len() - errors, errors));
+        }
+        self.load_items();
+    }
+    
+    /// Handle keyboard input
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        if let Some(confirm) = self.confirm.take() {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => self.apply_confirm(confirm),
+                _ => self.notifications.info("Cancelled"),
+            }
+            return Ok(());
+        }
+        
+        if self.vim.navigate(&mut self.list_state, key.code, key.modifiers, self.items.len(), self.settings.page_size) {
+            self.update_preview();
+            return Ok(());
+        }
+        
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_quit = true;
+            }
+            KeyCode::Enter | KeyCode::Char('r') => self.restore_selected(),
+            KeyCode::Char('d') | KeyCode::Delete if self.selected_item().is_some() => {
+                self.confirm = Some(Confirm::Purge);
+            }
+            KeyCode::Char('E') if !self.items.is_empty() => {
+                self.confirm = Some(Confirm::Empty);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// Render the trash browser
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        
+        self.render_item_list(f, chunks[0]);
+        self.render_preview(f, chunks[1]);
+        self.render_status_bar(f, area);
+        
+        if let Some(confirm) = &self.confirm {
+            let question = match confirm {
+                Confirm::Purge => {
+                    let name = self.selected_item()
+                        .map(|item| item.original.display().to_string())
+                        .unwrap_or_default();
+                    format!("Permanently delete '{}'?", name)
+                }
+                Confirm::Empty => format!("Permanently delete all {} item(s)?", self.items.len()),
+            };
+            let lines = vec![
+                Line::from(question),
+                Line::from(""),
+                Line::from(Span::styled("y Delete • any other key Cancel", Style::default().fg(colors::muted()))),
+            ];
+            tui_common::render_message_popup(f, "Empty Trash", lines, colors::danger());
+        }
+    }
+    
+    /// Render the list of trashed items
+    fn render_item_list(&mut self, f: &mut Frame, area: Rect) {
+        let items: Vec<Line> = self.items
+            .iter()
+            .map(|item| {
+                let name = item.original
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                let suffix = if item.path.is_dir() { "/" } else { "" };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{}  ", item.deleted.replace('T', " ")),
+                        Style::default().fg(colors::muted()),
+                    ),
+                    Span::raw(format!("{}{}", name, suffix)),
+                ])
+            })
+            .collect();
+        
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Trash ({})", self.items.len()))
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    /// Render preview
+    fn render_preview(&self, f: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.preview_content.as_str())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Details")
+                .border_style(Style::default().fg(colors::secondary())))
+            .wrap(Wrap { trim: false });
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    /// Render status bar
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
+        
+        let help_text = "Enter/r Restore • d Delete Forever • E Empty Trash • ? Help • Esc Quit";
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the trash browser
+    pub fn run(&mut self) -> io::Result<()> {
+        tui_common::run_app(self)
+    }
+    
+    /// Print the trashed items, newest first, instead of running the TUI
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        plain::print(format, self.items.iter().map(|item| vec![
+            ("original", Value::from(item.original.to_string_lossy())),
+            ("deleted", Value::from(item.deleted.as_str())),
+            ("path", Value::from(item.path.to_string_lossy())),
+        ]))
+    }
+}
+
+impl App for TrashBrowser {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        if let AppEvent::Key(key) = event {
+            self.handle_key(key)?;
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+/// Run the trash browser for the home trash and `path/.tt-trash`, or print
+/// the items in `output`
+pub fn run(path: PathBuf, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    let mut browser = TrashBrowser::new(path, settings)?;
+    match output {
+        Some(format) => browser.print(format),
+        None => browser.run(),
+    }
+}
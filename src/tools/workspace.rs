@@ -0,0 +1,443 @@
+//! Several tools as tabs of one screen, switched with Alt-1..9, where the
+//! file selected in one tab can be shown in the explorer tab.
+//!
+//! Each tab is a whole tool, drawn straight into the screen but its bottom
+//! row, which holds the tab bar. Ticks reach every tab, so background loads
+//! go on in the tabs not shown.
+
+use crate::config::Config;
+use crate::notify::Notifications;
+use crate::palette::Context;
+use crate::plain::Format;
+use crate::session::{self, Resume, Session};
+use crate::tools::explore::FileExplorer;
+use crate::tools::find::FileFinder;
+use crate::tools::git::{GitDiffBrowser, GitLogBrowser};
+use crate::tools::recent::RecentFileBrowser;
+use crate::tools::search::LiveSearchBrowser;
+use crate::tools::todo::TodoBrowser;
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    Frame, Terminal,
+};
+use std::{io, path::PathBuf};
+
+/// Keys the workspace adds to those of every tab, listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Alt-1 … Alt-9", "Go to a tab"),
+    ("Alt-→ / Alt-←", "Next / previous tab"),
+    ("Alt-t", "Open a new tab"),
+    ("Alt-w", "Close the tab (quitting its tool does too)"),
+    ("Alt-e", "Show the selected file in the explorer tab"),
+];
+
+/// A tool a tab can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Dir,
+    Find,
+    Search,
+    GitLog,
+    GitDiff,
+    Recent,
+    Todo,
+}
+
+impl Kind {
+    /// In the order the new-tab popup offers them
+    pub const ALL: [Kind; 7] = [Kind::Dir, Kind::Find, Kind::Search, Kind::GitLog, Kind::GitDiff, Kind::Recent, Kind::Todo];
+    
+    /// Name in the tab bar, which is also the tool's session key
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Dir => "dir",
+            Kind::Find => "find",
+            Kind::Search => "search",
+            Kind::GitLog => "git log",
+            Kind::GitDiff => "git diff",
+            Kind::Recent => "recent",
+            Kind::Todo => "todo",
+        }
+    }
+    
+    /// Table of config.toml the tool's settings come from
+    fn config_name(self) -> &'static str {
+        match self {
+            Kind::GitLog | Kind::GitDiff => "git",
+            kind => kind.name(),
+        }
+    }
+}
+
+/// A tool running in a tab
+enum Tab {
+    Dir(Box<FileExplorer>),
+    Find(Box<FileFinder>),
+    Search(Box<LiveSearchBrowser>),
+    GitLog(Box<GitLogBrowser>),
+    GitDiff(Box<GitDiffBrowser>),
+    Recent(Box<RecentFileBrowser>),
+    Todo(Box<TodoBrowser>),
+}
+
+/// Evaluate `$body` with `$tool` bound to the tool in `$tab`, whichever it is
+macro_rules! with_tool {
+    ($tab:expr, $tool:ident => $body:expr) => {
+        match $tab {
+            Tab::Dir($tool) => $body,
+            Tab::Find($tool) => $body,
+            Tab::Search($tool) => $body,
+            Tab::GitLog($tool) => $body,
+            Tab::GitDiff($tool) => $body,
+            Tab::Recent($tool) => $body,
+            Tab::Todo($tool) => $body,
+        }
+    };
+}
+
+impl Tab {
+    /// Start a tool in the working directory, where it was last left
+    fn open(kind: Kind, config: &Config) -> io::Result<Tab> {
+        let settings = config.tool(kind.config_name());
+        let here = PathBuf::from(".");
+        let mut tab = match kind {
+            Kind::Dir => Tab::Dir(Box::new(FileExplorer::new(here, settings)?)),
+            Kind::Find => Tab::Find(Box::new(FileFinder::new(here, None, None, settings)?)),
+            Kind::Search => Tab::Search(Box::new(LiveSearchBrowser::new(None, here, None, false, settings)?)),
+            Kind::GitLog => Tab::GitLog(Box::new(GitLogBrowser::new(None, settings)?)),
+            Kind::GitDiff => Tab::GitDiff(Box::new(GitDiffBrowser::new()?)),
+            Kind::Recent => {
+                let limit = settings.limit.unwrap_or(10);
+                Tab::Recent(Box::new(RecentFileBrowser::new(limit, settings)?))
+            }
+            Kind::Todo => Tab::Todo(Box::new(TodoBrowser::new(here, Vec::new(), settings)?)),
+        };
+        if let Some(session) = session::load(kind.name()) {
+            with_tool!(&mut tab, tool => tool.restore(session));
+        }
+        Ok(tab)
+    }
+    
+    fn kind(&self) -> Kind {
+        match self {
+            Tab::Dir(_) => Kind::Dir,
+            Tab::Find(_) => Kind::Find,
+            Tab::Search(_) => Kind::Search,
+            Tab::GitLog(_) => Kind::GitLog,
+            Tab::GitDiff(_) => Kind::GitDiff,
+            Tab::Recent(_) => Kind::Recent,
+            Tab::Todo(_) => Kind::Todo,
+        }
+    }
+    
+    /// The file the selection is about, if the tool lists files
+    fn selected_path(&self) -> Option<PathBuf> {
+        match self {
+            Tab::Dir(tool) => tool.selected_path(),
+            Tab::Find(tool) => tool.selected_path(),
+            Tab::Search(tool) => tool.selected_path(),
+            Tab::Recent(tool) => tool.selected_path(),
+            Tab::Todo(tool) => tool.selected_path(),
+            Tab::GitLog(_) | Tab::GitDiff(_) => None,
+        }
+    }
+    
+    /// Remember where the tool was left, as running it alone would
+    fn save_session(&self) {
+        let session: Session = with_tool!(self, tool => tool.session());
+        // Losing the session is no reason to report the tool failed
+        let _ = session::save(self.kind().name(), session);
+    }
+}
+
+pub struct Workspace {
+    tabs: Vec<Tab>,
+    active: usize,
+    /// Tabs closed since the last frame, whose images must still come off
+    /// the screen
+    closed: Vec<Tab>,
+    /// Whether the new-tab popup is open
+    choosing: bool,
+    config: Config,
+}
+
+impl Workspace {
+    /// Open a tab for each of `kinds`; one that cannot start (such as git
+    /// log outside a repository) is left out and reported by the first tab
+    pub fn new(kinds: Vec<Kind>, config: &Config) -> io::Result<Self> {
+        let mut tabs = Vec::new();
+        let mut failed = Vec::new();
+        for kind in kinds {
+            match Tab::open(kind, config) {
+                Ok(tab) => tabs.push(tab),
+                Err(e) => failed.push((kind, e)),
+            }
+        }
+        if tabs.is_empty() {
+            return Err(match failed.into_iter().next() {
+                Some((kind, e)) => io::Error::new(e.kind(), format!("{}: {}", kind.name(), e)),
+                None => io::Error::new(io::ErrorKind::InvalidInput, "No tools to open as tabs"),
+            });
+        }
+        
+        let mut workspace = Workspace {
+            tabs,
+            active: 0,
+            closed: Vec::new(),
+            choosing: false,
+            config: config.clone(),
+        };
+        for (kind, e) in failed {
+            workspace.error(format!("Could not open {}: {}", kind.name(), e));
+        }
+        Ok(workspace)
+    }
+    
+    fn active_tab(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active]
+    }
+    
+    /// Report on the status bar of the tab shown
+    fn error(&mut self, message: String) {
+        if let Some(notifications) = with_tool!(self.active_tab(), tool => tool.notifications()) {
+            notifications.error(message);
+        }
+    }
+    
+    /// Open a tab with `kind` after the others and show it
+    fn open_tab(&mut self, kind: Kind) {
+        match Tab::open(kind, &self.config) {
+            Ok(tab) => {
+                self.tabs.push(tab);
+                self.active = self.tabs.len() - 1;
+            }
+            Err(e) => self.error(format!("Could not open {}: {}", kind.name(), e)),
+        }
+    }
+    
+    /// Close the tab shown; returns whether any are left
+    fn close_tab(&mut self) -> bool {
+        let tab = self.tabs.remove(self.active);
+        tab.save_session();
+        self.closed.push(tab);
+        self.active = self.active.min(self.tabs.len().saturating_sub(1));
+        !self.tabs.is_empty()
+    }
+    
+    /// Show the selected file of the tab shown in the explorer tab, opening
+    /// one if there is none
+    fn show_in_explorer(&mut self) {
+        let Some(path) = self.active_tab().selected_path() else {
+            self.error("Nothing selected to show in the explorer".to_string());
+            return;
+        };
+        match self.tabs.iter().position(|tab| tab.kind() == Kind::Dir) {
+            Some(index) => self.active = index,
+            None => {
+                self.open_tab(Kind::Dir);
+                if self.active_tab().kind() != Kind::Dir {
+                    return;
+                }
+            }
+        }
+        if let Tab::Dir(explorer) = self.active_tab() {
+            if let Err(e) = explorer.reveal(&path) {
+                self.error(format!("Could not show {}: {}", path.display(), e));
+            }
+        }
+    }
+    
+    /// Handle the workspace's own keys; returns None for keys meant for the
+    /// tab
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if self.choosing {
+            self.choosing = false;
+            let index = match key.code {
+                KeyCode::Char(c) => c.to_digit(10).and_then(|digit| (digit as usize).checked_sub(1)),
+                _ => None,
+            };
+            if let Some(kind) = index.and_then(|index| Kind::ALL.get(index)) {
+                self.open_tab(*kind);
+            }
+            return Some(Action::Continue);
+        }
+        if !key.modifiers.contains(KeyModifiers::ALT) {
+            return None;
+        }
+        
+        match key.code {
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                if index < self.tabs.len() {
+                    self.active = index;
+                }
+            }
+            KeyCode::Right => self.active = (self.active + 1) % self.tabs.len(),
+            KeyCode::Left => self.active = (self.active + self.tabs.len() - 1) % self.tabs.len(),
+            KeyCode::Char('t') => self.choosing = true,
+            KeyCode::Char('w') => {
+                if !self.close_tab() {
+                    return Some(Action::Quit);
+                }
+            }
+            KeyCode::Char('e') => self.show_in_explorer(),
+            _ => return None,
+        }
+        Some(Action::Continue)
+    }
+    
+    fn render_tab_bar(&self, f: &mut Frame, area: Rect) {
+        let mut spans = Vec::new();
+        for (index, tab) in self.tabs.iter().enumerate() {
+            let label = format!(" {} {} ", index + 1, tab.kind().name());
+            let style = if index == self.active {
+                Style::default().bg(colors::secondary()).fg(colors::background()).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(colors::muted())
+            };
+            spans.push(Span::styled(label, style));
+        }
+        
+        let hint = " Alt-1…9 Tab • Alt-t New • Alt-w Close • Alt-e Explore ";
+        let used: usize = spans.iter().map(|span| span.width()).sum();
+        let gap = (area.width as usize).saturating_sub(used + hint.chars().count());
+        if gap > 0 {
+            spans.push(Span::raw(" ".repeat(gap)));
+            spans.push(Span::styled(hint, Style::default().fg(colors::muted())));
+        }
+        f.render_widget(Line::from(spans), area);
+    }
+    
+    /// List the tools a new tab can hold, by number
+    fn render_choices(&self, f: &mut Frame) {
+        let lines = Kind::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, kind)| Line::from(vec![
+                Span::styled(format!(" {}  ", index + 1), Style::default().fg(colors::secondary()).add_modifier(Modifier::BOLD)),
+                Span::styled(kind.name(), Style::default().fg(colors::text())),
+            ]))
+            .collect();
+        tui_common::render_message_popup(f, "New tab (1-7, Esc cancels)", lines, colors::primary());
+    }
+    
+    /// Run the workspace; every tab's session is saved on the way out
+    pub fn run(&mut self) -> io::Result<()> {
+        let result = tui_common::run_app(self);
+        for tab in &self.tabs {
+            tab.save_session();
+        }
+        result
+    }
+}
+
+impl App for Workspace {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        let action = match event {
+            AppEvent::Key(key) => match self.handle_key(key) {
+                Some(action) => return Ok(action),
+                None => with_tool!(self.active_tab(), tool => tool.update(event))?,
+            },
+            AppEvent::Tick => {
+                // The tabs not shown only pick up background work
+                let active = self.active;
+                let mut action = Action::Continue;
+                for (index, tab) in self.tabs.iter_mut().enumerate() {
+                    let result = with_tool!(tab, tool => tool.update(AppEvent::Tick))?;
+                    if index == active {
+                        action = result;
+                    }
+                }
+                action
+            }
+        };
+        // A tool quitting closes its tab
+        if action == Action::Quit && self.close_tab() {
+            return Ok(Action::Redraw);
+        }
+        Ok(action)
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        let tool_area = Rect { height: area.height.saturating_sub(1), ..area };
+        with_tool!(&mut self.tabs[self.active], tool => tool.draw(f, tool_area));
+        self.render_tab_bar(f, tui_common::status_area(area));
+        if self.choosing {
+            self.render_choices(f);
+        }
+    }
+    
+    fn keymap(&self) -> Keymap {
+        with_tool!(&self.tabs[self.active], tool => tool.keymap())
+    }
+    
+    /// The keys of the tab shown, then the workspace's own
+    fn help_keys(&self) -> Vec<(&'static str, &'static str)> {
+        let mut keys = with_tool!(&self.tabs[self.active], tool => tool.help_keys());
+        keys.extend_from_slice(KEYMAP);
+        keys
+    }
+    
+    fn captures_text(&self) -> bool {
+        with_tool!(&self.tabs[self.active], tool => tool.captures_text())
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        with_tool!(self.active_tab(), tool => tool.notifications())
+    }
+    
+    fn context(&self) -> Context {
+        with_tool!(&self.tabs[self.active], tool => tool.context())
+    }
+    
+    fn suspended(&mut self) -> io::Result<Action> {
+        let action = with_tool!(self.active_tab(), tool => tool.suspended())?;
+        if action == Action::Quit && self.close_tab() {
+            return Ok(Action::Continue);
+        }
+        Ok(action)
+    }
+    
+    /// Take down the images of tabs no longer shown, then let the tab shown
+    /// draw its own
+    fn after_draw<B: Backend + io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        for mut tab in self.closed.drain(..) {
+            with_tool!(&mut tab, tool => tool.clear_overlay(terminal))?;
+        }
+        let active = self.active;
+        for (index, tab) in self.tabs.iter_mut().enumerate() {
+            if index != active {
+                with_tool!(tab, tool => tool.clear_overlay(terminal))?;
+            }
+        }
+        if self.choosing {
+            return with_tool!(self.active_tab(), tool => tool.clear_overlay(terminal));
+        }
+        with_tool!(self.active_tab(), tool => tool.after_draw(terminal))
+    }
+    
+    fn clear_overlay<B: Backend + io::Write>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        for mut tab in self.closed.drain(..) {
+            with_tool!(&mut tab, tool => tool.clear_overlay(terminal))?;
+        }
+        for tab in &mut self.tabs {
+            with_tool!(tab, tool => tool.clear_overlay(terminal))?;
+        }
+        Ok(())
+    }
+}
+
+/// Run `kinds` of tools as tabs; there is no plain output, which the tools
+/// give on their own
+pub fn run(kinds: Vec<Kind>, output: Option<Format>, config: &Config) -> io::Result<()> {
+    if output.is_some() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "The workspace has no plain output; run its tools alone with --plain"));
+    }
+    let mut workspace = Workspace::new(kinds, config)?;
+    workspace.run()
+}
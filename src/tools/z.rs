@@ -0,0 +1,311 @@
+//! Frecency-ranked directory jumper (zoxide-backed when available).
+
+use crate::config::Settings;
+use crate::frecency::{self, RankedDir, Source};
+use crate::notify::Notifications;
+use crate::plain::{self, Format};
+use crate::tui_common::{self, colors, Action, App, AppEvent, Keymap, NavKey, SelectableList, TextInput, VimNav};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, ListState, Paragraph, Wrap},
+    Frame,
+};
+use serde_json::Value;
+use std::{
+    fs,
+    io,
+    path::PathBuf,
+};
+
+/// Keys listed by the help popup
+const KEYMAP: Keymap = &[
+    ("Type", "Filter directories"),
+    ("Esc", "Normal mode: j / k, gg / G, Ctrl-D / Ctrl-U move"),
+    ("/ or i", "Back to typing (normal mode)"),
+    ("↑ / ↓", "Move the selection"),
+    ("PgDn / PgUp, Ctrl-F / Ctrl-B", "Page down / up"),
+    ("Enter", "Jump to the directory"),
+    ("Ctrl-D, Delete in normal mode", "Forget the directory"),
+    ("q / Esc", "Quit (normal mode)"),
+];
+
+pub struct DirJumper {
+    source: Source,
+    /// Every known directory, best first
+    dirs: Vec<RankedDir>,
+    /// Indices into `dirs` matching the query
+    matches: Vec<usize>,
+    query: TextInput,
+    list_state: ListState,
+    vim: VimNav,
+    should_quit: bool,
+    notifications: Notifications,
+    preview_content: String,
+    /// Directory chosen with Enter, printed on exit
+    chosen: Option<PathBuf>,
+    settings: Settings,
+}
+
+impl DirJumper {
+    /// Create a jumper over the zoxide database or tt's own store
+    pub fn new(query: String, settings: Settings) -> io::Result<Self> {
+        let source = Source::detect();
+        let mut jumper = DirJumper {
+            source,
+            dirs: frecency::load(source)?,
+            matches: Vec::new(),
+            query: TextInput::new(query),
+            list_state: ListState::default(),
+            vim: VimNav::default(),
+            should_quit: false,
+            notifications: Notifications::new(),
+            preview_content: String::new(),
+            chosen: None,
+            settings,
+        };
+        
+        jumper.apply_query();
+        Ok(jumper)
+    }
+    
+    /// Recompute the matching directories and reset the selection
+    fn apply_query(&mut self) {
+        let words: Vec<&str> = self.query.value().split_whitespace().collect();
+        self.matches = self.dirs
+            .iter()
+            .enumerate()
+            .filter(|(_, dir)| frecency::matches(&words, &dir.path))
+            .map(|(index, _)| index)
+            .collect();
+        self.list_state.select(if self.matches.is_empty() { None } else { Some(0) });
+        self.notifications.set_status(format!("{} of {} directories ({})", self.matches.len(), self.dirs.len(), self.source.name()));
+        self.update_preview();
+    }
+    
+    fn selected_dir(&self) -> Option<&RankedDir> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.matches.get(index))
+            .map(|&index| &self.dirs[index])
+    }
+    
+    /// Update the directory listing preview
+    fn update_preview(&mut self) {
+        self.preview_content = match self.selected_dir() {
+            Some(dir) => match fs::read_dir(&dir.path) {
+                Ok(entries) => {
+                    let mut names: Vec<String> = entries
+                        .flatten()
+                        .map(|entry| {
+                            let suffix = if entry.path().is_dir() { "/" } else { "" };
+                            format!("{}{}", entry.file_name().to_string_lossy(), suffix)
+                        })
+                        .collect();
+                    names.sort();
+                    names.truncate(100);
+                    names.join("\n")
+                }
+                Err(e) => format!("[Could not read directory: {}]", e),
+            },
+            None => String::new(),
+        };
+    }
+    
+    /// Quit with the selected directory; the shell hook of zoxide or of
+    /// `tt init` records the cd
+    fn choose_selected(&mut self) {
+        let Some(path) = self.selected_dir().map(|dir| dir.path.clone()) else {
+            return;
+        };
+        self.chosen = Some(path);
+        self.should_quit = true;
+    }
+    
+    /// Forget the selected directory
+    fn remove_selected(&mut self) {
+        let Some(path) = self.selected_dir().map(|dir| dir.path.clone()) else {
+            return;
+        };
+        match frecency::remove(self.source, &path) {
+            Ok(()) => {
+                self.dirs.retain(|dir| dir.path != path);
+                let selected = self.list_state.selected();
+                self.apply_query();
+                if let Some(index) = selected {
+                    if !self.matches.is_empty() {
+                        self.list_state.select(Some(index.min(self.matches.len() - 1)));
+                        self.update_preview();
+                    }
+                }
+                self.notifications.success(format!("Removed {}", path.display()));
+            }
+            Err(e) => self.notifications.error(format!("Could not remove {}: {}", path.display(), e)),
+        }
+    }
+    
+    /// Handle keyboard input; typing edits the query
+    fn handle_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        // Home and End move the cursor in the query
+        let is_cursor_key = self.vim.inserting() && matches!(key.code, KeyCode::Home | KeyCode::End);
+        if !is_cursor_key {
+            match self.vim.filter_key(&mut self.list_state, key.code, key.modifiers, self.matches.len(), self.settings.page_size) {
+                NavKey::Moved => {
+                    self.update_preview();
+                    return Ok(());
+                }
+                NavKey::Used => return Ok(()),
+                NavKey::Unused => {}
+            }
+        }
+        
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') if !self.vim.inserting() => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.remove_selected();
+            }
+            KeyCode::Delete if !self.vim.inserting() => self.remove_selected(),
+            KeyCode::Enter => self.choose_selected(),
+            code if !key.modifiers.contains(KeyModifiers::CONTROL) && self.vim.inserting() && self.query.handle_key(code) => {
+                self.apply_query();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    
+    /// Render the jumper
+    fn render(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(area);
+        
+        let left = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(chunks[0]);
+        
+        self.render_query(f, left[0]);
+        self.render_dir_list(f, left[1]);
+        self.render_preview(f, chunks[1]);
+        self.render_status_bar(f, area);
+    }
+    
+    /// Render the query input
+    fn render_query(&self, f: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.query.line())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Jump to")
+                .border_style(Style::default().fg(colors::secondary())));
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    /// Render the ranked directory list
+    fn render_dir_list(&mut self, f: &mut Frame, area: Rect) {
+        let items: Vec<Line> = self.matches
+            .iter()
+            .map(|&index| {
+                let dir = &self.dirs[index];
+                Line::from(vec![
+                    Span::styled(format!("{:>7.1}  ", dir.score), Style::default().fg(colors::muted())),
+                    Span::raw(dir.path.display().to_string()),
+                ])
+            })
+            .collect();
+        
+        let list = SelectableList::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Directories ({})", self.matches.len()))
+                .border_style(Style::default().fg(colors::primary())));
+        
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+    
+    /// Render preview
+    fn render_preview(&self, f: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.preview_content.as_str())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Contents")
+                .border_style(Style::default().fg(colors::secondary())))
+            .wrap(Wrap { trim: false });
+        
+        f.render_widget(paragraph, area);
+    }
+    
+    /// Render status bar
+    fn render_status_bar(&self, f: &mut Frame, area: Rect) {
+        let area = tui_common::status_area(area);
+        
+        let help_text = if self.vim.inserting() {
+            "Type to filter • Enter Jump • F1 Help • Esc Normal mode"
+        } else {
+            "j/k Move • / Filter • Enter Jump • ? Help • q Quit"
+        };
+        tui_common::render_status_bar(f, area, &self.notifications, help_text);
+    }
+    
+    /// Run the jumper, drawing on stderr so the chosen path can be captured
+    pub fn run(&mut self) -> io::Result<()> {
+        let result = tui_common::run_app_on(self, io::stderr());
+        
+        if let Some(path) = &self.chosen {
+            println!("{}", path.display());
+        }
+        result
+    }
+    
+    /// Print the matching directories, best first, instead of running the TUI
+    pub fn print(&self, format: Format) -> io::Result<()> {
+        plain::print(format, self.matches.iter().map(|&index| {
+            let dir = &self.dirs[index];
+            vec![("path", Value::from(dir.path.to_string_lossy())), ("score", plain::tenths(dir.score))]
+        }))
+    }
+}
+
+impl App for DirJumper {
+    fn update(&mut self, event: AppEvent) -> io::Result<Action> {
+        if let AppEvent::Key(key) = event {
+            self.handle_key(key)?;
+        }
+        Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+    }
+    
+    fn draw(&mut self, f: &mut Frame, area: Rect) {
+        self.render(f, area);
+    }
+    
+    fn keymap(&self) -> Keymap {
+        KEYMAP
+    }
+    
+    fn captures_text(&self) -> bool {
+        self.vim.inserting()
+    }
+    
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        Some(&mut self.notifications)
+    }
+}
+
+/// Run the directory jumper, or just record a visit with `add`; with
+/// `output`, print the matching directories instead
+pub fn run(query: Vec<String>, add: Option<PathBuf>, output: Option<Format>, settings: Settings) -> io::Result<()> {
+    if let Some(path) = add {
+        return frecency::add(Source::detect(), &path);
+    }
+    let mut jumper = DirJumper::new(query.join(" "), settings)?;
+    match output {
+        Some(format) => jumper.print(format),
+        None => jumper.run(),
+    }
+}
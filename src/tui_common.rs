@@ -10,53 +10,120 @@
 //! - **Color Scheme**: Consistent color palette across all tools
 //! - **Navigation**: Vim-style keyboard shortcuts with Ctrl-F/Ctrl-B paging
 //! - **Error Handling**: Robust terminal state management with cleanup guarantees
+//! - **Popups**: [`TextInput`] prompts and message dialogs centered over the UI
 //!
 //! ## Usage
 //!
-//! Most tools will use this module to set up their TUI environment:
+//! Tools implement the [`App`] trait and hand themselves to [`run_app`], which
+//! owns the terminal for the whole session:
 //!
 //! ```rust
-//! use crate::tui_common::{setup_terminal, restore_terminal};
+//! use crate::tui_common::{self, Action, App, AppEvent};
 //!
-//! fn run_tool() -> std::io::Result<()> {
-//!     let mut terminal = setup_terminal()?;
-//!     
-//!     // ... tool implementation ...
-//!     
-//!     restore_terminal(&mut terminal)?;
-//!     Ok(())
+//! impl App for MyTool {
+//!     fn update(&mut self, event: AppEvent) -> std::io::Result<Action> {
+//!         match event {
+//!             AppEvent::Key(key) => self.handle_key(key)?,
+//!             AppEvent::Tick => self.poll_background_work(),
+//!         }
+//!         Ok(if self.should_quit { Action::Quit } else { Action::Continue })
+//!     }
+//!
+//!     fn draw(&mut self, f: &mut Frame, area: Rect) {
+//!         self.render(f, area);
+//!     }
 //! }
+//!
+//! tui_common::run_app(&mut tool)?;
 //! ```
 //!
+//! The runner sets the terminal up, sends a [`AppEvent::Tick`] at least
+//! every [`TICK`] for background work, and restores the terminal however the
+//! loop ends, through a [`TerminalGuard`]. `main` installs
+//! [`install_panic_hook`] so a panic restores it too. `Ctrl-C` quits every
+//! tool.
+//!
+//! An idle tool costs next to nothing: the screen is drawn after input, when
+//! a tick returns [`Action::Redraw`] because background work changed
+//! something, when a toast comes or goes, and otherwise only every
+//! [`REDRAW_INTERVAL`] so ages and clocks stay current. Work that would run
+//! on every keystroke, such as filtering a long list or starting `rg`, waits
+//! for a pause in typing with a [`Debounce`].
+//!
+//! Below [`MIN_WIDTH`] x [`MIN_HEIGHT`] the runner shows a "terminal too
+//! small" notice instead of the tool, so layouts never get squeezed into
+//! unusable or empty areas, and only quitting keys work until the terminal
+//! grows. Every resize repaints the whole screen.
+//!
+//! `?` or `F1` opens a popup listing the keys from [`App::keymap`], so status
+//! bars only need to hint at the main ones. While a tool takes text
+//! ([`App::captures_text`]), `?` is typed like any other character and only
+//! `F1` opens the help.
+//!
+//! Status bars show the tool's [`Notifications`]: a status line, covered
+//! for a few seconds by toasts reporting what just happened. `Ctrl-L` lists
+//! the recent toasts, and [`App::notifications`] lets the runner show the
+//! ones background threads send. While [`crate::jobs`] run, a spinner leads
+//! the status bar and `Ctrl-J` lists them, where `x` cancels one.
+//!
+//! `Ctrl-P` opens the command palette ([`crate::palette`]): the actions of
+//! [`App::keymap`] and jumps to other tools on what [`App::context`] says
+//! the tool is on, filtered as you type.
+//!
 //! ## Navigation Patterns
 //!
 //! All tools implement consistent keyboard navigation:
 //! - `↑/↓` or `j/k` for line-by-line movement
-//! - `Ctrl-F/Ctrl-B` for page-by-page movement
+//! - `PgUp/PgDn` or `Ctrl-B/Ctrl-F` for page-by-page movement, `Ctrl-U/Ctrl-D`
+//!   for half pages
+//! - `Home/End` or `gg/G` for the first and last item
 //! - `Enter` to select or execute items
 //! - `Esc` or `q` to quit
 //!
-//! The [`handle_page_navigation`] function provides standardized page navigation logic
-//! that all tools can use to maintain consistency.
+//! Lists are drawn with [`SelectableList`], which keeps the selection in the
+//! middle, shows a scrollbar and truncates or wraps long items, and
+//! [`VimNav`] moves their selection so every tool pages alike. Lists filtered
+//! by typing start in insert mode, where letters go to the filter; `Esc`
+//! switches to normal mode for the vim keys, and `/` or `i` back.
 //!
 //! ## Color Scheme
 //!
 //! The [`colors`] module defines a cohesive color palette that ensures visual
 //! consistency across all tools while maintaining good readability in various
-//! terminal environments.
+//! terminal environments. The colors come from the [`crate::theme`] chosen at
+//! startup, so light terminals and custom palettes work everywhere.
 
+use crate::jobs::Jobs;
+use crate::notify::{Level, Notifications};
+use crate::palette::{self, Palette, Run, Step};
+use crate::{platform, text_width, theme, time_util};
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    cursor::Show,
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{disable_raw_mode, enable_raw_mode, is_raw_mode_enabled, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    Terminal,
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{
+        Block, Borders, Clear, ListState, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
+        Widget, Wrap,
+    },
+    Frame, Terminal,
+};
+use std::{
+    io::{self, IsTerminal},
+    ops::{Deref, DerefMut},
+    panic,
+    time::{Duration, Instant},
 };
-use std::io;
+use unicode_segmentation::UnicodeSegmentation;
 
-/// Set up terminal for TUI mode with proper state management.
+/// Set up the terminal for TUI mode, drawing on `writer`.
 ///
 /// This function prepares the terminal for TUI applications by:
 /// - Enabling raw mode for direct key capture
@@ -64,9 +131,9 @@ use std::io;
 /// - Enabling mouse capture for scroll events
 /// - Creating a ratatui Terminal instance
 ///
-/// # Returns
-///
-/// Returns a configured `Terminal` instance ready for TUI rendering.
+/// Tools usually draw on stdout. Tools that print a result for the shell
+/// (e.g. `tt dir --choose-dir` inside `$(...)`) draw on stderr so stdout only
+/// carries their output. [`run_app`] and [`run_app_on`] call this for them.
 ///
 /// # Errors
 ///
@@ -78,18 +145,17 @@ use std::io;
 /// # Examples
 ///
 /// ```rust,no_run
-/// use crate::tui_common::{setup_terminal, restore_terminal};
+/// use crate::tui_common::{setup_terminal_on, restore_terminal};
 ///
-/// let mut terminal = setup_terminal()?;
+/// let mut terminal = setup_terminal_on(std::io::stderr())?;
 /// // ... use terminal for TUI ...
 /// restore_terminal(&mut terminal)?;
 /// # Ok::<(), std::io::Error>(())
 /// ```
-pub fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<std::io::Stdout>>> {
-    let mut stdout = std::io::stdout();
+pub fn setup_terminal_on<W: io::Write>(mut writer: W) -> io::Result<Terminal<CrosstermBackend<W>>> {
     enable_raw_mode()?;
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    execute!(writer, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(writer);
     let terminal = Terminal::new(backend)?;
     Ok(terminal)
 }
@@ -102,9 +168,9 @@ pub fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<std::io::Stdout>
 /// - Disabling mouse capture
 /// - Showing the cursor again
 ///
-/// This function should **always** be called before a TUI application exits,
-/// preferably in a `Drop` implementation or cleanup handler to ensure the
-/// terminal is restored even if the application panics.
+/// This function should **always** be called before a TUI application exits;
+/// [`TerminalGuard`] calls it on drop and [`install_panic_hook`] covers
+/// panics.
 ///
 /// # Arguments
 ///
@@ -118,9 +184,9 @@ pub fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<std::io::Stdout>
 /// # Examples
 ///
 /// ```rust,no_run
-/// use crate::tui_common::{setup_terminal, restore_terminal};
+/// use crate::tui_common::{setup_terminal_on, restore_terminal};
 ///
-/// let mut terminal = setup_terminal()?;
+/// let mut terminal = setup_terminal_on(std::io::stdout())?;
 /// // ... TUI application logic ...
 /// restore_terminal(&mut terminal)?;
 /// # Ok::<(), std::io::Error>(())
@@ -136,11 +202,506 @@ pub fn restore_terminal<B: Backend + std::io::Write>(terminal: &mut Terminal<B>)
     Ok(())
 }
 
+/// The terminal of a running TUI, restored when the guard is dropped.
+///
+/// Every way out of [`run_app_on`] goes through here, including an early `?`
+/// return, so the shell never gets stuck in raw mode. Dereferences to the
+/// ratatui [`Terminal`].
+pub struct TerminalGuard<W: io::Write> {
+    terminal: Terminal<CrosstermBackend<W>>,
+    active: bool,
+}
+
+impl<W: io::Write> TerminalGuard<W> {
+    /// Set the terminal up on `writer` (see [`setup_terminal_on`])
+    pub fn new(writer: W) -> io::Result<Self> {
+        Ok(Self { terminal: setup_terminal_on(writer)?, active: true })
+    }
+    
+    /// Restore the terminal now, reporting any error the drop would ignore
+    pub fn restore(mut self) -> io::Result<()> {
+        self.active = false;
+        restore_terminal(&mut self.terminal)
+    }
+}
+
+impl<W: io::Write> Deref for TerminalGuard<W> {
+    type Target = Terminal<CrosstermBackend<W>>;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl<W: io::Write> DerefMut for TerminalGuard<W> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl<W: io::Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        if self.active {
+            let _ = restore_terminal(&mut self.terminal);
+        }
+    }
+}
+
+/// Restore the terminal before any panic message is printed.
+///
+/// Release builds abort on panic, so no destructor runs and a
+/// [`TerminalGuard`] never gets the chance; without this hook the message is
+/// written to the alternate screen and lost, and the shell is left in raw
+/// mode. Called once at startup.
+pub fn install_panic_hook() {
+    let print_panic = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore_after_panic();
+        print_panic(info);
+    }));
+}
+
+/// Leave TUI mode on whichever of stdout and stderr a tool was drawing on
+fn restore_after_panic() {
+    // Not inside a TUI, or handed to a child program
+    if matches!(is_raw_mode_enabled(), Ok(false)) {
+        return;
+    }
+    let _ = disable_raw_mode();
+    if io::stdout().is_terminal() {
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show);
+    }
+    if io::stderr().is_terminal() {
+        let _ = execute!(io::stderr(), LeaveAlternateScreen, DisableMouseCapture, Show);
+    }
+}
+
+/// Temporarily hand the terminal to a child program (shell, editor, pager).
+///
+/// Leaves the alternate screen and raw mode, runs `f`, then re-enters TUI
+/// mode and forces a full redraw so no output from the child lingers.
+pub fn suspend<B: Backend + std::io::Write, T>(terminal: &mut Terminal<B>, f: impl FnOnce() -> T) -> io::Result<T> {
+    restore_terminal(terminal)?;
+    let result = f();
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+    Ok(result)
+}
+
+/// How long the runner waits for a key before the next tick
+pub const TICK: Duration = Duration::from_millis(50);
+
+/// How often the screen is drawn when nothing happens, for clocks and ages
+pub const REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pause in typing after which a [`Debounce`] fires
+pub const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Smallest terminal the tools are laid out for; below it the runner shows
+/// a notice instead of the tool
+pub const MIN_WIDTH: u16 = 60;
+pub const MIN_HEIGHT: u16 = 15;
+
+/// Input delivered to an [`App`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppEvent {
+    /// A key was pressed
+    Key(KeyEvent),
+    /// Sent at least every [`TICK`]; the time to pick up the results of
+    /// background work. The screen is only drawn again after input, or when
+    /// the tick returns [`Action::Redraw`].
+    Tick,
+}
+
+/// What the runner should do after an [`App`] handled an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+    /// Continue, drawing the screen again; for ticks that changed the state
+    Redraw,
+    Quit,
+    /// Hand the terminal back to the shell and call [`App::suspended`]
+    Suspend,
+}
+
+/// Keys of a screen and what they do, as `(keys, action)` pairs
+pub type Keymap = &'static [(&'static str, &'static str)];
+
+/// Keys the runner handles for every tool, listed after the tool's own
+const SHARED_KEYS: Keymap = &[
+    ("? / F1", "Show this help (F1 while typing)"),
+    ("Ctrl-L", "Show recent messages"),
+    ("Ctrl-J", "Show running jobs; x cancels one"),
+    ("Ctrl-P", "Command palette: this tool's actions and other tools"),
+    ("Ctrl-C", "Quit"),
+];
+
+/// A full-screen tool driven by [`run_app`]
+pub trait App {
+    /// React to a key or a tick
+    fn update(&mut self, event: AppEvent) -> io::Result<Action>;
+    
+    /// Draw the tool in `area`: the whole screen, or its tab's part of it
+    /// in a workspace
+    fn draw(&mut self, f: &mut Frame, area: Rect);
+    
+    /// Keys of the current screen
+    fn keymap(&self) -> Keymap {
+        &[]
+    }
+    
+    /// Keys the help popup and the command palette list; those of
+    /// [`App::keymap`] unless the app adds its own to a tool's
+    fn help_keys(&self) -> Vec<(&'static str, &'static str)> {
+        self.keymap().to_vec()
+    }
+    
+    /// Whether typed characters currently edit text, in which case `?` is
+    /// passed on and only `F1` opens the help
+    fn captures_text(&self) -> bool {
+        false
+    }
+    
+    /// The tool's status line and toasts; the runner shows what background
+    /// threads send and lists past toasts on `Ctrl-L`
+    fn notifications(&mut self) -> Option<&mut Notifications> {
+        None
+    }
+    
+    /// What the tool is on, which the command palette offers to open in
+    /// other tools
+    fn context(&self) -> palette::Context {
+        palette::Context::default()
+    }
+    
+    /// Run a child program (shell, command) on the plain terminal after
+    /// [`Action::Suspend`]; the TUI comes back afterwards unless this quits
+    fn suspended(&mut self) -> io::Result<Action> {
+        Ok(Action::Continue)
+    }
+    
+    /// Write to the terminal outside ratatui after each frame, e.g. images
+    fn after_draw<B: Backend + io::Write>(&mut self, _terminal: &mut Terminal<B>) -> io::Result<()> {
+        Ok(())
+    }
+    
+    /// Remove what [`App::after_draw`] wrote before the screen is left
+    fn clear_overlay<B: Backend + io::Write>(&mut self, _terminal: &mut Terminal<B>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Run `app` on stdout until it quits
+pub fn run_app<A: App>(app: &mut A) -> io::Result<()> {
+    run_app_on(app, io::stdout())
+}
+
+/// Run `app` drawing on `writer` (see [`setup_terminal_on`]) until it quits
+pub fn run_app_on<A: App, W: io::Write>(app: &mut A, writer: W) -> io::Result<()> {
+    // Resolving the theme may query the terminal, which needs cooked mode
+    theme::current();
+    let mut terminal = TerminalGuard::new(writer)?;
+    let result = event_loop(app, &mut terminal);
+    let cleared = app.clear_overlay(&mut terminal);
+    terminal.restore()?;
+    result.and(cleared)
+}
+
+/// Popup the runner draws over the tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Popup {
+    Help,
+    Messages,
+    /// Running jobs; the number kept with it is the selected one
+    Jobs,
+}
+
+/// Terminal input the runner reacts to
+enum Input {
+    Key(KeyEvent),
+    Resize,
+}
+
+fn event_loop<A: App, B: Backend + io::Write>(app: &mut A, terminal: &mut Terminal<B>) -> io::Result<()> {
+    // The open popup and its scroll offset
+    let mut popup: Option<(Popup, usize)> = None;
+    let mut command_palette: Option<Palette> = None;
+    // Whether the screen is out of date, and what the last frame showed
+    let mut dirty = true;
+    let mut fits = true;
+    let mut toast_shown = false;
+    let mut last_draw = Instant::now();
+    loop {
+        if let Some(notifications) = app.notifications() {
+            dirty |= notifications.poll();
+            dirty |= notifications.current().is_some() != toast_shown;
+        }
+        let mut action = app.update(AppEvent::Tick)?;
+        if action == Action::Redraw {
+            dirty = true;
+            action = Action::Continue;
+        }
+        if action == Action::Continue && (dirty || last_draw.elapsed() >= REDRAW_INTERVAL) {
+            terminal.draw(|f| {
+                fits = fits_screen(f.area());
+                if !fits {
+                    render_too_small(f);
+                    return;
+                }
+                app.draw(f, f.area());
+                match popup.as_mut() {
+                    Some((Popup::Help, scroll)) => *scroll = render_help_popup(f, &app.help_keys(), *scroll),
+                    Some((Popup::Messages, scroll)) => {
+                        if let Some(notifications) = app.notifications() {
+                            *scroll = render_messages_popup(f, notifications, *scroll);
+                        }
+                    }
+                    Some((Popup::Jobs, selected)) => {
+                        if let Some(notifications) = app.notifications() {
+                            *selected = render_jobs_popup(f, notifications.jobs(), *selected);
+                        }
+                    }
+                    None => {}
+                }
+                if let Some(command_palette) = command_palette.as_mut() {
+                    command_palette.render(f);
+                }
+            })?;
+            // Overlays such as images would cover the popup
+            if popup.is_some() || command_palette.is_some() || !fits {
+                app.clear_overlay(terminal)?;
+            } else {
+                app.after_draw(terminal)?;
+            }
+            dirty = false;
+            toast_shown = app.notifications().is_some_and(|notifications| notifications.current().is_some());
+            last_draw = Instant::now();
+        }
+        if action == Action::Continue {
+            let input = next_input()?;
+            dirty |= input.is_some();
+            action = match input {
+                Some(Input::Resize) => {
+                    // Repaint everything; overlays are placed again after the next frame
+                    app.clear_overlay(terminal)?;
+                    terminal.clear()?;
+                    Action::Continue
+                }
+                Some(Input::Key(key)) if is_interrupt(&key) => Action::Quit,
+                // The tool is hidden, so only quitting makes sense
+                Some(Input::Key(key)) if !fits => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+                    _ => Action::Continue,
+                },
+                Some(Input::Key(key)) if popup.is_some() => {
+                    match popup {
+                        Some((Popup::Jobs, selected)) if matches!(key.code, KeyCode::Char('x') | KeyCode::Delete) => {
+                            if let Some(notifications) = app.notifications() {
+                                let jobs = notifications.jobs();
+                                if let Some(job) = jobs.list().get(selected) {
+                                    jobs.cancel(job.id);
+                                }
+                            }
+                        }
+                        _ => popup = popup.and_then(|(kind, scroll)| Some((kind, scroll_help(scroll, key.code)?))),
+                    }
+                    Action::Continue
+                }
+                Some(Input::Key(key)) if command_palette.is_some() => {
+                    match command_palette.as_mut().map_or(Step::Close, |command_palette| command_palette.handle_key(key)) {
+                        Step::Stay => Action::Continue,
+                        Step::Close => {
+                            command_palette = None;
+                            Action::Continue
+                        }
+                        Step::Run(run) => {
+                            command_palette = None;
+                            run_command(app, terminal, run)?
+                        }
+                    }
+                }
+                Some(Input::Key(key)) if is_palette_key(&key) => {
+                    let commands = palette::key_commands(&app.help_keys(), app.captures_text())
+                        .into_iter()
+                        .chain(palette::jumps(&app.context()))
+                        .collect();
+                    command_palette = Some(Palette::new(commands));
+                    Action::Continue
+                }
+                Some(Input::Key(key)) if is_help_key(&key, app.captures_text()) => {
+                    popup = Some((Popup::Help, 0));
+                    Action::Continue
+                }
+                Some(Input::Key(key)) if is_messages_key(&key) && app.notifications().is_some() => {
+                    popup = Some((Popup::Messages, 0));
+                    Action::Continue
+                }
+                Some(Input::Key(key)) if is_jobs_key(&key) && app.notifications().is_some() => {
+                    popup = Some((Popup::Jobs, 0));
+                    Action::Continue
+                }
+                Some(Input::Key(key)) => app.update(AppEvent::Key(key))?,
+                None => Action::Continue,
+            };
+        }
+        if action == Action::Suspend {
+            app.clear_overlay(terminal)?;
+            action = suspend(terminal, || app.suspended())??;
+            dirty = true;
+        }
+        if action == Action::Quit {
+            return Ok(());
+        }
+    }
+}
+
+/// The next key press or resize within [`TICK`], if any
+fn next_input() -> io::Result<Option<Input>> {
+    if !event::poll(TICK)? {
+        return Ok(None);
+    }
+    match event::read()? {
+        // Some platforms also report releases and repeats
+        Event::Key(key) if key.kind != KeyEventKind::Release => Ok(Some(Input::Key(key))),
+        Event::Resize(..) => Ok(Some(Input::Resize)),
+        _ => Ok(None),
+    }
+}
+
+/// Whether `area` is at least [`MIN_WIDTH`] x [`MIN_HEIGHT`]
+fn fits_screen(area: Rect) -> bool {
+    area.width >= MIN_WIDTH && area.height >= MIN_HEIGHT
+}
+
+/// Explain that the terminal is too small, in place of the tool
+fn render_too_small(f: &mut Frame) {
+    let area = f.area();
+    let text = vec![
+        Line::from(Span::styled("Terminal too small", Style::default().fg(colors::danger()).add_modifier(Modifier::BOLD))),
+        Line::from(format!("need {}x{}, have {}x{}", MIN_WIDTH, MIN_HEIGHT, area.width, area.height)),
+        Line::from(Span::styled("Enlarge the window or press q to quit", Style::default().fg(colors::muted()))),
+    ];
+    let height = (text.len() as u16).min(area.height);
+    let area = Rect { y: area.y + (area.height - height) / 2, height, ..area };
+    f.render_widget(Paragraph::new(text).alignment(Alignment::Center).wrap(Wrap { trim: true }), area);
+}
+
+/// Whether `key` is the `Ctrl-C` that quits every tool
+fn is_interrupt(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Whether `key` opens the help popup; `?` is text while the tool takes text
+fn is_help_key(key: &KeyEvent, captures_text: bool) -> bool {
+    key.code == KeyCode::F(1) || (key.code == KeyCode::Char('?') && !captures_text)
+}
+
+/// Whether `key` opens the list of recent messages
+fn is_messages_key(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('l') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Whether `key` opens the list of running jobs
+fn is_jobs_key(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('j') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Whether `key` opens the command palette
+fn is_palette_key(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Carry out a command chosen in the palette: press its keys in `app`, or
+/// run the other tool on the plain terminal
+fn run_command<A: App, B: Backend + io::Write>(app: &mut A, terminal: &mut Terminal<B>, run: Run) -> io::Result<Action> {
+    match run {
+        Run::Keys(keys) => {
+            let mut action = Action::Continue;
+            for key in keys {
+                action = app.update(AppEvent::Key(key))?;
+                if !matches!(action, Action::Continue | Action::Redraw) {
+                    break;
+                }
+            }
+            Ok(action)
+        }
+        Run::Tool(args) => {
+            app.clear_overlay(terminal)?;
+            if let Err(e) = suspend(terminal, || palette::launch(&args))? {
+                if let Some(notifications) = app.notifications() {
+                    notifications.error(format!("Could not run tt {}: {}", args.join(" "), e));
+                }
+            }
+            Ok(Action::Continue)
+        }
+    }
+}
+
+/// New scroll offset of a popup after `code`, or None to close it
+fn scroll_help(scroll: usize, code: KeyCode) -> Option<usize> {
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => Some(scroll.saturating_sub(1)),
+        KeyCode::Down | KeyCode::Char('j') => Some(scroll + 1),
+        KeyCode::PageUp => Some(scroll.saturating_sub(10)),
+        KeyCode::PageDown => Some(scroll + 10),
+        _ => None,
+    }
+}
+
+/// Area of the status bar on the last row of `area`
+pub fn status_area(area: Rect) -> Rect {
+    Rect {
+        x: area.x,
+        y: area.y + area.height.saturating_sub(1),
+        width: area.width,
+        height: 1,
+    }
+}
+
+/// Render the status bar: the current toast, or else the status line, then
+/// the tool's key help
+pub fn render_status_bar(f: &mut Frame, area: Rect, notifications: &Notifications, help: &str) {
+    let mut spans = Vec::new();
+    let jobs = notifications.jobs();
+    if let Some(frame) = jobs.spinner() {
+        let running = jobs.list();
+        let label = match &running[..] {
+            [job] => format!(" {} {} ", frame, job.label),
+            _ => format!(" {} {} jobs ", frame, running.len()),
+        };
+        spans.push(Span::styled(label, Style::default().add_modifier(Modifier::BOLD)));
+    }
+    match notifications.current() {
+        Some(toast) => {
+            spans.push(Span::styled(format!(" {} {} ", toast.level.symbol(), toast.text), toast_style(toast.level)));
+            spans.push(Span::raw(" "));
+        }
+        None if !notifications.status().is_empty() => spans.push(Span::raw(format!("{} | ", notifications.status()))),
+        None => {}
+    }
+    spans.push(Span::raw(help));
+    let paragraph = Paragraph::new(Line::from(spans))
+        .style(Style::default().bg(colors::primary()).fg(colors::background()));
+    f.render_widget(paragraph, area);
+}
+
+/// Colors of a toast in the status bar
+fn toast_style(level: Level) -> Style {
+    let style = Style::default().fg(colors::background()).add_modifier(Modifier::BOLD);
+    match level {
+        Level::Info => style,
+        Level::Success => style.bg(colors::success()),
+        Level::Error => style.bg(colors::danger()),
+    }
+}
+
 /// Consistent color scheme used across all terminal tools.
 ///
-/// This module defines a cohesive color palette that ensures visual consistency
-/// across all tools while maintaining good readability in various terminal environments.
-/// The colors are chosen to work well with both light and dark terminal themes.
+/// Each function returns the color of one role in the current
+/// [`Theme`](crate::theme::Theme), which is picked from `--theme` and
+/// `config.toml` when the tool starts (see [`crate::theme`]). In the default
+/// dark theme:
 ///
 /// # Design Principles
 ///
@@ -158,24 +719,44 @@ pub fn restore_terminal<B: Backend + std::io::Write>(terminal: &mut Terminal<B>)
 /// use ratatui::style::{Style, Stylize};
 /// use crate::tui_common::colors;
 ///
-/// let header_style = Style::default().fg(colors::PRIMARY);
-/// let selected_style = Style::default().bg(colors::PRIMARY).fg(colors::BACKGROUND);
+/// let header_style = Style::default().fg(colors::primary());
+/// let selected_style = Style::default().bg(colors::primary()).fg(colors::background());
 /// ```
 pub mod colors {
+    use crate::theme;
     use ratatui::style::Color;
     
-    pub const PRIMARY: Color = Color::Cyan;
-    pub const SECONDARY: Color = Color::Yellow;
-    #[allow(dead_code)]
-    pub const SUCCESS: Color = Color::Green;
-    #[allow(dead_code)]
-    pub const DANGER: Color = Color::Red;
-    #[allow(dead_code)]
-    pub const WARNING: Color = Color::Magenta;
-    #[allow(dead_code)]
-    pub const MUTED: Color = Color::DarkGray;
-    pub const BACKGROUND: Color = Color::Black;
-    pub const TEXT: Color = Color::White;
+    pub fn primary() -> Color {
+        theme::current().primary
+    }
+    
+    pub fn secondary() -> Color {
+        theme::current().secondary
+    }
+    
+    pub fn success() -> Color {
+        theme::current().success
+    }
+    
+    pub fn danger() -> Color {
+        theme::current().danger
+    }
+    
+    pub fn warning() -> Color {
+        theme::current().warning
+    }
+    
+    pub fn muted() -> Color {
+        theme::current().muted
+    }
+    
+    pub fn background() -> Color {
+        theme::current().background
+    }
+    
+    pub fn text() -> Color {
+        theme::current().text
+    }
 }
 
 /// Common key bindings help text
@@ -189,6 +770,24 @@ pub fn common_help_text() -> Vec<&'static str> {
     ]
 }
 
+/// Format a byte count in human readable form (e.g. `512B`, `1.5KB`, `2.0GB`)
+pub fn format_size(size: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = size as f64;
+    let mut unit_index = 0;
+    
+    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_index += 1;
+    }
+    
+    if unit_index == 0 {
+        format!("{:.0}{}", size, UNITS[unit_index])
+    } else {
+        format!("{:.1}{}", size, UNITS[unit_index])
+    }
+}
+
 /// Handle standardized page navigation with Ctrl-F/Ctrl-B shortcuts.
 ///
 /// This function implements consistent page-by-page navigation that all tools
@@ -257,14 +856,697 @@ pub fn handle_page_navigation(
         }
         KeyCode::Char('b') if modifiers.contains(KeyModifiers::CONTROL) => {
             // Page up
-            if let Some(selected) = current_selection {
-                Some(selected.saturating_sub(page_size))
+            current_selection.map(|selected| selected.saturating_sub(page_size))
+        }
+        _ => current_selection,
+    }
+}
+
+/// Move the selection of a `len` item list on a navigation key: `↑`/`↓` by
+/// one, `PgUp`/`PgDn` and `Ctrl-B`/`Ctrl-F` by `page_size`, `Home`/`End` to
+/// the ends; a list without a selection gets its first item. Returns whether
+/// `key` was one of them.
+pub fn navigate_list(state: &mut ListState, key_code: KeyCode, modifiers: KeyModifiers, len: usize, page_size: usize) -> bool {
+    let is_navigation = match key_code {
+        KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End => true,
+        KeyCode::Char('b') | KeyCode::Char('f') => modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    };
+    if !is_navigation {
+        return false;
+    }
+    let Some(last) = len.checked_sub(1) else {
+        return true;
+    };
+    let current = state.selected().map(|selected| selected.min(last));
+    let up = |step: usize| current.map_or(0, |selected| selected.saturating_sub(step));
+    let down = |step: usize| current.map_or(0, |selected| (selected + step).min(last));
+    let selected = match key_code {
+        KeyCode::Up => up(1),
+        KeyCode::Down => down(1),
+        KeyCode::PageUp => up(page_size),
+        KeyCode::PageDown => down(page_size),
+        KeyCode::Home => 0,
+        KeyCode::End => last,
+        _ => handle_page_navigation(key_code, modifiers, current, len, page_size).unwrap_or(0),
+    };
+    state.select(Some(selected));
+    true
+}
+
+/// Runs an expensive update once typing pauses for [`DEBOUNCE`]: typing
+/// keys call [`Debounce::schedule`] and the tool's tick applies the update
+/// when [`Debounce::ready`] says so. Other keys apply a pending update first
+/// (see [`Debounce::take`]), so Enter acts on the results of the whole query.
+#[derive(Debug, Clone, Default)]
+pub struct Debounce {
+    due: Option<Instant>,
+}
+
+impl Debounce {
+    /// Ask for the update, putting off one already asked for
+    pub fn schedule(&mut self) {
+        self.due = Some(Instant::now() + DEBOUNCE);
+    }
+    
+    /// Whether the update is due now; true once per [`Debounce::schedule`]
+    pub fn ready(&mut self) -> bool {
+        self.ready_at(Instant::now())
+    }
+    
+    fn ready_at(&mut self, now: Instant) -> bool {
+        let ready = self.due.is_some_and(|due| now >= due);
+        if ready {
+            self.due = None;
+        }
+        ready
+    }
+    
+    /// Cancel a pending update, returning whether there was one; for keys
+    /// that need the update applied right away
+    pub fn take(&mut self) -> bool {
+        self.due.take().is_some()
+    }
+}
+
+/// Whether a type-to-filter list takes text or moves like vim
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputMode {
+    /// Letters edit the filter; lists start here
+    #[default]
+    Insert,
+    /// Letters move the selection
+    Normal,
+}
+
+/// What [`VimNav::filter_key`] did with a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavKey {
+    /// A movement key; the selection may have changed
+    Moved,
+    /// Used for something else, such as switching modes
+    Used,
+    /// Left to the tool
+    Unused,
+}
+
+/// Vim-style movement in a list: `j`/`k`, `gg`/`G` and half pages with
+/// `Ctrl-D`/`Ctrl-U`, on top of the keys of [`navigate_list`].
+///
+/// Lists that take text start in [`InputMode::Insert`]; `Esc` switches to
+/// [`InputMode::Normal`], where letters move, and `/` or `i` switch back.
+#[derive(Debug, Clone, Default)]
+pub struct VimNav {
+    pub mode: InputMode,
+    /// `g` was pressed and waits for the second one of `gg`
+    pending_g: bool,
+}
+
+impl VimNav {
+    pub fn inserting(&self) -> bool {
+        self.mode == InputMode::Insert
+    }
+    
+    /// Whether `key` edits the filter text: a plain character or Backspace
+    /// in insert mode
+    pub fn typing(&self, key: &KeyEvent) -> bool {
+        self.inserting()
+            && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            && matches!(key.code, KeyCode::Char(_) | KeyCode::Backspace)
+    }
+    
+    /// Move the selection of a list of `len` items for `key_code`; returns
+    /// whether it was a movement key
+    pub fn navigate(&mut self, state: &mut ListState, key_code: KeyCode, modifiers: KeyModifiers, len: usize, page_size: usize) -> bool {
+        let pending_g = std::mem::take(&mut self.pending_g);
+        let plain = !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let (code, page_size) = match key_code {
+            KeyCode::Char('j') if plain => (KeyCode::Down, page_size),
+            KeyCode::Char('k') if plain => (KeyCode::Up, page_size),
+            KeyCode::Char('G') if plain => (KeyCode::End, page_size),
+            KeyCode::Char('g') if plain && pending_g => (KeyCode::Home, page_size),
+            KeyCode::Char('g') if plain => {
+                self.pending_g = true;
+                return true;
+            }
+            KeyCode::Char('d') if ctrl => (KeyCode::PageDown, (page_size / 2).max(1)),
+            KeyCode::Char('u') if ctrl => (KeyCode::PageUp, (page_size / 2).max(1)),
+            _ => return navigate_list(state, key_code, modifiers, len, page_size),
+        };
+        navigate_list(state, code, KeyModifiers::NONE, len, page_size)
+    }
+    
+    /// Handle a key of a type-to-filter list. In insert mode only the keys
+    /// of [`navigate_list`] move and `Esc` switches to normal mode; in normal
+    /// mode every key of [`VimNav::navigate`] moves. Tools whose filter has a
+    /// cursor keep `Home`/`End` for it while inserting.
+    pub fn filter_key(&mut self, state: &mut ListState, key_code: KeyCode, modifiers: KeyModifiers, len: usize, page_size: usize) -> NavKey {
+        let plain = !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let moved = match self.mode {
+            InputMode::Insert if key_code == KeyCode::Esc => {
+                self.mode = InputMode::Normal;
+                return NavKey::Used;
+            }
+            InputMode::Insert => navigate_list(state, key_code, modifiers, len, page_size),
+            InputMode::Normal if plain && matches!(key_code, KeyCode::Char('/') | KeyCode::Char('i')) => {
+                self.pending_g = false;
+                self.mode = InputMode::Insert;
+                return NavKey::Used;
+            }
+            InputMode::Normal => self.navigate(state, key_code, modifiers, len, page_size),
+        };
+        if moved { NavKey::Moved } else { NavKey::Unused }
+    }
+}
+
+/// How a [`SelectableList`] shows items wider than the list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Cut off with `…`
+    #[default]
+    Truncate,
+    /// Continued on the following rows
+    Wrap,
+}
+
+/// Marker in front of the selected item
+const HIGHLIGHT_SYMBOL: &str = "► ";
+
+/// The list every tool shows.
+///
+/// Items sit in an optional block, below an optional header row that stays
+/// put. The selected item is highlighted and kept in the middle of the list
+/// while it scrolls, a scrollbar on the right border shows where the view is
+/// when the items don't fit, and items wider than the list are truncated or
+/// wrapped ([`Overflow`]). Render it with `f.render_stateful_widget` on a
+/// [`ListState`], whose offset is set to the first item shown, and move the
+/// selection with [`list_navigation`].
+pub struct SelectableList<'a> {
+    items: Vec<Text<'a>>,
+    block: Option<Block<'a>>,
+    header: Option<Line<'a>>,
+    overflow: Overflow,
+    highlight_style: Style,
+}
+
+impl<'a> SelectableList<'a> {
+    pub fn new<T: Into<Text<'a>>>(items: impl IntoIterator<Item = T>) -> Self {
+        SelectableList {
+            items: items.into_iter().map(Into::into).collect(),
+            block: None,
+            header: None,
+            overflow: Overflow::default(),
+            highlight_style: Style::default()
+                .bg(colors::primary())
+                .fg(colors::background())
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+    
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+    
+    /// Row above the items that doesn't scroll, such as column titles
+    pub fn header(mut self, header: Line<'a>) -> Self {
+        self.header = Some(header);
+        self
+    }
+    
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+    
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+    
+    /// Screen rows of `item` at `width` columns
+    fn rows(&self, item: &Text<'a>, width: usize) -> Vec<Line<'a>> {
+        match self.overflow {
+            Overflow::Truncate => item.lines.iter().map(|line| truncate_line(line, width)).collect(),
+            Overflow::Wrap => item.lines.iter().flat_map(|line| wrap_line(line, width)).collect(),
+        }
+    }
+    
+    fn height(&self, item: &Text<'a>, width: usize) -> usize {
+        match self.overflow {
+            Overflow::Truncate => item.lines.len().max(1),
+            Overflow::Wrap if item.width() <= width => item.lines.len().max(1),
+            Overflow::Wrap => self.rows(item, width).len().max(1),
+        }
+    }
+}
+
+impl StatefulWidget for SelectableList<'_> {
+    type State = ListState;
+    
+    fn render(mut self, area: Rect, buf: &mut Buffer, state: &mut ListState) {
+        let (mut inner, bordered) = match self.block.take() {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                (inner, true)
+            }
+            None => (area, false),
+        };
+        let symbol_width = text_width::width(HIGHLIGHT_SYMBOL) as u16;
+        if let Some(header) = self.header.take().filter(|_| inner.height > 0) {
+            let width = inner.width.saturating_sub(symbol_width);
+            buf.set_line(inner.x + symbol_width.min(inner.width), inner.y, &truncate_line(&header, width as usize), width);
+            inner.y += 1;
+            inner.height -= 1;
+        }
+        if inner.height == 0 || inner.width <= symbol_width + 1 {
+            return;
+        }
+        
+        // Without a border the scrollbar needs a column of its own
+        let view = inner.height as usize;
+        let mut width = (inner.width - symbol_width) as usize;
+        let mut heights: Vec<usize> = self.items.iter().map(|item| self.height(item, width)).collect();
+        if !bordered && heights.iter().sum::<usize>() > view {
+            width -= 1;
+            heights = self.items.iter().map(|item| self.height(item, width)).collect();
+        }
+        let starts: Vec<usize> = heights
+            .iter()
+            .scan(0, |row, height| {
+                let start = *row;
+                *row += height;
+                Some(start)
+            })
+            .collect();
+        let total: usize = heights.iter().sum();
+        
+        // Center the selection; without one, keep the offset
+        let selected = state.selected().filter(|&selected| selected < self.items.len());
+        let top = match selected {
+            Some(selected) => (starts[selected] + heights[selected] / 2).saturating_sub(view / 2),
+            None => starts.get(state.offset()).copied().unwrap_or(0),
+        };
+        let top = top.min(total.saturating_sub(view));
+        let first = starts.partition_point(|&start| start <= top).saturating_sub(1);
+        *state.offset_mut() = first;
+        
+        let mut y = inner.y;
+        let mut skip = top.saturating_sub(starts.get(first).copied().unwrap_or(0));
+        for (index, item) in self.items.iter().enumerate().skip(first) {
+            if y >= inner.bottom() {
+                break;
+            }
+            let is_selected = selected == Some(index);
+            for (row, line) in self.rows(item, width).iter().enumerate().skip(skip) {
+                if y >= inner.bottom() {
+                    break;
+                }
+                let row_area = Rect { x: inner.x, y, width: symbol_width + width as u16, height: 1 };
+                if is_selected && row == 0 {
+                    buf.set_string(inner.x, y, HIGHLIGHT_SYMBOL, Style::default());
+                }
+                buf.set_line(inner.x + symbol_width, y, line, width as u16);
+                if is_selected {
+                    buf.set_style(row_area, self.highlight_style);
+                }
+                y += 1;
+            }
+            skip = 0;
+        }
+        
+        if total > view {
+            let scrollbar_area = if bordered {
+                Rect { x: area.x, y: inner.y, width: area.width, height: inner.height }
             } else {
-                None
+                inner
+            };
+            let mut scrollbar_state = ScrollbarState::new(total - view).position(top).viewport_content_length(view);
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .track_symbol(Some("│"))
+                .track_style(Style::default().fg(colors::muted()))
+                .thumb_style(Style::default().fg(colors::primary()))
+                .render(scrollbar_area, buf, &mut scrollbar_state);
+        }
+    }
+}
+
+/// `line` cut to `width` columns, ending in `…` when anything was cut
+fn truncate_line<'a>(line: &Line<'a>, width: usize) -> Line<'a> {
+    if line.width() <= width {
+        return line.clone();
+    }
+    let mut spans = Vec::new();
+    let mut left = width.saturating_sub(1);
+    for span in &line.spans {
+        let (head, rest, used) = text_width::split_at_width(&span.content, left);
+        spans.push(Span::styled(head.to_string(), span.style));
+        if !rest.is_empty() {
+            break;
+        }
+        left -= used;
+    }
+    let style = spans.last().map(|span| span.style).unwrap_or_default();
+    if width > 0 {
+        spans.push(Span::styled(text_width::ELLIPSIS, style));
+    }
+    Line::from(spans).style(line.style)
+}
+
+/// `line` broken into rows of at most `width` columns
+fn wrap_line<'a>(line: &Line<'a>, width: usize) -> Vec<Line<'a>> {
+    if line.width() <= width || width == 0 {
+        return vec![line.clone()];
+    }
+    let mut rows = Vec::new();
+    let mut row: Vec<Span<'a>> = Vec::new();
+    let mut used = 0;
+    for span in &line.spans {
+        let mut content = String::new();
+        for (grapheme, grapheme_width) in text_width::graphemes(&span.content) {
+            if used + grapheme_width > width && used > 0 {
+                row.push(Span::styled(std::mem::take(&mut content), span.style));
+                rows.push(Line::from(std::mem::take(&mut row)).style(line.style));
+                used = 0;
             }
+            used += grapheme_width;
+            content.push_str(grapheme);
         }
-        _ => current_selection,
+        row.push(Span::styled(content, span.style));
+    }
+    rows.push(Line::from(row).style(line.style));
+    rows
+}
+
+/// Single-line text input with a movable cursor.
+///
+/// Used for rename prompts, command prompts and other popups that need a
+/// small editable field. The cursor is tracked as a byte offset and moves
+/// and deletes a grapheme (one character as drawn) at a time, so an accent or
+/// an emoji sequence is never split.
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    value: String,
+    cursor: usize,
+}
+
+impl TextInput {
+    /// Create an input pre-filled with `value`, cursor at the end
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        let cursor = value.len();
+        TextInput { value, cursor }
+    }
+    
+    /// Current contents of the input
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+    
+    /// Apply an editing key. Returns `false` for keys the input does not handle
+    /// (e.g. `Enter`, `Esc`) so callers can act on them.
+    pub fn handle_key(&mut self, key_code: crossterm::event::KeyCode) -> bool {
+        use crossterm::event::KeyCode;
+        
+        match key_code {
+            KeyCode::Char(c) => {
+                self.value.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+            }
+            KeyCode::Backspace => {
+                let start = self.previous_boundary();
+                self.value.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            }
+            KeyCode::Delete => {
+                let end = self.next_boundary();
+                self.value.replace_range(self.cursor..end, "");
+            }
+            KeyCode::Left => self.cursor = self.previous_boundary(),
+            KeyCode::Right => self.cursor = self.next_boundary(),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.value.len(),
+            _ => return false,
+        }
+        true
+    }
+    
+    /// Start of the grapheme before the cursor
+    fn previous_boundary(&self) -> usize {
+        self.value[..self.cursor].graphemes(true).next_back().map_or(self.cursor, |g| self.cursor - g.len())
+    }
+    
+    /// End of the grapheme after the cursor
+    fn next_boundary(&self) -> usize {
+        self.value[self.cursor..].graphemes(true).next().map_or(self.cursor, |g| self.cursor + g.len())
+    }
+    
+    /// Render the input as a line with a block cursor; line breaks in the
+    /// value show as [`NEWLINE_MARKER`]
+    pub fn line(&self) -> Line<'_> {
+        let (before, after) = self.value.split_at(self.cursor);
+        let (under_cursor, rest) = after.split_at(self.next_boundary() - self.cursor);
+        let under_cursor = if under_cursor.is_empty() { " ".to_string() } else { single_line(under_cursor) };
+        Line::from(vec![
+            Span::raw(single_line(before)),
+            Span::styled(under_cursor, Style::default().add_modifier(Modifier::REVERSED)),
+            Span::raw(single_line(rest)),
+        ])
+    }
+}
+
+/// Run `command` with `shell` on the plain terminal (use inside
+/// [`suspend`]), streaming its output, then ask whether to return to `tool`.
+/// Returns `true` when the user chose to quit instead.
+pub fn run_and_wait(command: &str, shell: &str, tool: &str) -> io::Result<bool> {
+    use std::io::{BufRead, Write};
+    
+    println!("$ {}", command);
+    let status = platform::shell_command(shell, command).status();
+    let status = match status {
+        Ok(status) => match status.code() {
+            Some(0) => "ok".to_string(),
+            Some(code) => format!("exit {}", code),
+            None => "killed".to_string(),
+        },
+        Err(e) => format!("failed to start {}: {}", shell, e),
+    };
+    
+    print!("\n[{}] Enter to return to {}, q to quit: ", status, tool);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("q"))
+}
+
+/// Shown in place of line breaks when multi-line text is drawn on one row
+pub const NEWLINE_MARKER: char = '⏎';
+
+/// `text` on a single row with each line break shown as [`NEWLINE_MARKER`].
+/// Replaces char for char, so match positions into `text` still apply.
+pub fn single_line(text: &str) -> String {
+    text.chars().map(|c| if c == '\n' { NEWLINE_MARKER } else { c }).collect()
+}
+
+/// Split `text` into spans, applying `highlight` to the chars at `positions`
+/// (char indices in ascending order, as returned by [`crate::fuzzy::match_positions`])
+pub fn highlight_spans(text: &str, positions: &[usize], highlight: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut current_matched = false;
+    let mut positions = positions.iter().peekable();
+    for (index, c) in text.chars().enumerate() {
+        let matched = positions.next_if_eq(&&index).is_some();
+        if matched != current_matched && !current.is_empty() {
+            let style = if current_matched { highlight } else { Style::default() };
+            spans.push(Span::styled(std::mem::take(&mut current), style));
+        }
+        current_matched = matched;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        let style = if current_matched { highlight } else { Style::default() };
+        spans.push(Span::styled(current, style));
+    }
+    spans
+}
+
+/// Rectangle of at most `width` x `height` cells centered in `area`
+pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Render a centered prompt popup with a label and an editable input
+pub fn render_input_popup(f: &mut Frame, title: &str, label: &str, input: &TextInput) {
+    let area = centered_rect(f.area().width.saturating_mul(3) / 5, 5, f.area());
+    let text = vec![
+        Line::from(Span::styled(label.to_string(), Style::default().fg(colors::secondary()))),
+        input.line(),
+        Line::from(Span::styled("Enter Confirm • Esc Cancel", Style::default().fg(colors::muted()))),
+    ];
+    
+    let paragraph = Paragraph::new(text)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(title.to_string())
+            .border_style(Style::default().fg(colors::primary())));
+    
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render a centered message popup (errors, confirmations, summaries)
+pub fn render_message_popup(f: &mut Frame, title: &str, lines: Vec<Line>, border: Color) {
+    let height = (lines.len() as u16).saturating_add(2);
+    let area = centered_rect(f.area().width.saturating_mul(3) / 5, height, f.area());
+    
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(title.to_string())
+            .border_style(Style::default().fg(border)))
+        .wrap(Wrap { trim: false });
+    
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Render the help popup listing `keymap` and the keys every tool shares,
+/// starting `scroll` rows down; returns the scroll offset actually used
+pub fn render_help_popup(f: &mut Frame, keymap: &[(&str, &str)], scroll: usize) -> usize {
+    let entries: Vec<&(&str, &str)> = keymap.iter().chain(SHARED_KEYS).collect();
+    let key_width = entries.iter().map(|(keys, _)| text_width::width(keys)).max().unwrap_or(0);
+    let width = entries
+        .iter()
+        .map(|(_, action)| key_width + 3 + text_width::width(action))
+        .max()
+        .unwrap_or(0) as u16;
+    let area = centered_rect(width.saturating_add(4), entries.len() as u16 + 2, f.area());
+    
+    let visible = area.height.saturating_sub(2) as usize;
+    let scroll = scroll.min(entries.len().saturating_sub(visible));
+    let lines: Vec<Line> = entries
+        .iter()
+        .skip(scroll)
+        .take(visible)
+        .map(|(keys, action)| Line::from(vec![
+            Span::styled(format!(" {}  ", text_width::pad(keys, key_width)), Style::default().fg(colors::secondary())),
+            Span::styled(action.to_string(), Style::default().fg(colors::text())),
+        ]))
+        .collect();
+    
+    let hint = if visible < entries.len() { " ↑↓ Scroll • Esc Close " } else { " Esc Close " };
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(" Keys ")
+            .title_bottom(Line::from(hint).right_aligned())
+            .border_style(Style::default().fg(colors::primary())));
+    
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+    scroll
+}
+
+/// Render the running jobs with their progress, highlighting the `selected`
+/// one; returns the selection actually used
+pub fn render_jobs_popup(f: &mut Frame, jobs: &Jobs, selected: usize) -> usize {
+    let running = jobs.list();
+    let selected = selected.min(running.len().saturating_sub(1));
+    let mut lines: Vec<Line> = running
+        .iter()
+        .enumerate()
+        .map(|(index, job)| {
+            let progress = match (job.fraction(), job.progress) {
+                (Some(fraction), _) => format!("{:>3}%", (fraction * 100.0) as u32),
+                (None, Some((done, _))) => done.to_string(),
+                (None, None) => String::new(),
+            };
+            let state = if job.cancelled { "cancelling" } else { "" };
+            let style = if index == selected {
+                Style::default().bg(colors::primary()).fg(colors::background()).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(colors::text())
+            };
+            Line::from(vec![
+                Span::styled(format!(" {:>5}s ", job.elapsed.as_secs()), Style::default().fg(colors::muted())),
+                Span::styled(format!(" {} ", job.label), style),
+                Span::styled(format!(" {} {}", progress, state), Style::default().fg(colors::secondary())),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::styled(" No jobs running", Style::default().fg(colors::muted())));
+    }
+    
+    let width = f.area().width.saturating_mul(4) / 5;
+    let area = centered_rect(width, lines.len() as u16 + 2, f.area());
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (selected + 1).saturating_sub(visible);
+    let paragraph = Paragraph::new(lines.into_iter().skip(skip).take(visible).collect::<Vec<_>>())
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(" Jobs ")
+            .title_bottom(Line::from(" x Cancel • Esc Close ").right_aligned())
+            .border_style(Style::default().fg(colors::primary())));
+    
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+    selected
+}
+
+/// Render the list of recent toasts, newest first, starting `scroll` rows
+/// down; returns the scroll offset actually used
+pub fn render_messages_popup(f: &mut Frame, notifications: &Notifications, scroll: usize) -> usize {
+    let mut lines: Vec<Line> = notifications
+        .history()
+        .rev()
+        .map(|toast| {
+            let color = match toast.level {
+                Level::Info => colors::text(),
+                Level::Success => colors::success(),
+                Level::Error => colors::danger(),
+            };
+            let time = time_util::DateTime::from_local(toast.time);
+            Line::from(vec![
+                Span::styled(
+                    format!(" {:02}:{:02}:{:02} ", time.hour, time.minute, time.second),
+                    Style::default().fg(colors::muted()),
+                ),
+                Span::styled(format!("{} {}", toast.level.symbol(), toast.text), Style::default().fg(color)),
+            ])
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::styled(" No messages yet", Style::default().fg(colors::muted())));
     }
+    
+    let width = f.area().width.saturating_mul(4) / 5;
+    let area = centered_rect(width, lines.len() as u16 + 2, f.area());
+    let visible = area.height.saturating_sub(2) as usize;
+    let scroll = scroll.min(lines.len().saturating_sub(visible));
+    let hint = if visible < lines.len() { " ↑↓ Scroll • Esc Close " } else { " Esc Close " };
+    let paragraph = Paragraph::new(lines.into_iter().skip(scroll).take(visible).collect::<Vec<_>>())
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(" Messages ")
+            .title_bottom(Line::from(hint).right_aligned())
+            .border_style(Style::default().fg(colors::primary())));
+    
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+    scroll
 }
 
 #[cfg(test)]
@@ -395,6 +1677,170 @@ mod tests {
         assert_eq!(result, Some(5)); // No change
     }
 
+    #[test]
+    fn test_text_input_editing() {
+        let mut input = TextInput::new("héllo");
+        input.handle_key(KeyCode::Left);
+        input.handle_key(KeyCode::Backspace);
+        input.handle_key(KeyCode::Home);
+        input.handle_key(KeyCode::Char('>'));
+        assert_eq!(input.value(), ">hélo");
+        assert!(!input.handle_key(KeyCode::Enter));
+
+        // An accented letter made of two chars goes in one step
+        let mut input = TextInput::new("cafe\u{301}!");
+        input.handle_key(KeyCode::Left);
+        input.handle_key(KeyCode::Backspace);
+        assert_eq!(input.value(), "caf!");
+    }
+
+    #[test]
+    fn test_centered_rect() {
+        let area = Rect { x: 0, y: 0, width: 100, height: 40 };
+        assert_eq!(centered_rect(50, 10, area), Rect { x: 25, y: 15, width: 50, height: 10 });
+        assert_eq!(centered_rect(200, 10, area).width, 100);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512B");
+        assert_eq!(format_size(1536), "1.5KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0MB");
+    }
+
+    #[test]
+    fn test_navigate_list() {
+        let mut state = ListState::default();
+        assert!(navigate_list(&mut state, KeyCode::Down, KeyModifiers::NONE, 20, 5));
+        assert_eq!(state.selected(), Some(0));
+        navigate_list(&mut state, KeyCode::PageDown, KeyModifiers::NONE, 20, 5);
+        assert_eq!(state.selected(), Some(5));
+        navigate_list(&mut state, KeyCode::Char('f'), KeyModifiers::CONTROL, 20, 5);
+        assert_eq!(state.selected(), Some(10));
+        navigate_list(&mut state, KeyCode::End, KeyModifiers::NONE, 20, 5);
+        assert_eq!(state.selected(), Some(19));
+        navigate_list(&mut state, KeyCode::Down, KeyModifiers::NONE, 20, 5);
+        assert_eq!(state.selected(), Some(19));
+        navigate_list(&mut state, KeyCode::Home, KeyModifiers::NONE, 20, 5);
+        assert_eq!(state.selected(), Some(0));
+        assert!(!navigate_list(&mut state, KeyCode::Char('f'), KeyModifiers::NONE, 20, 5));
+        assert!(navigate_list(&mut ListState::default(), KeyCode::Up, KeyModifiers::NONE, 0, 5));
+    }
+
+    #[test]
+    fn test_debounce() {
+        let mut debounce = Debounce::default();
+        assert!(!debounce.ready());
+        debounce.schedule();
+        let now = Instant::now();
+        assert!(!debounce.ready_at(now));
+        assert!(debounce.ready_at(now + DEBOUNCE));
+        assert!(!debounce.ready_at(now + DEBOUNCE));
+        debounce.schedule();
+        assert!(debounce.take());
+        assert!(!debounce.take());
+    }
+
+    #[test]
+    fn test_vim_nav() {
+        let mut vim = VimNav::default();
+        let mut state = ListState::default().with_selected(Some(0));
+        let mut key = |vim: &mut VimNav, code, modifiers| vim.filter_key(&mut state, code, modifiers, 20, 10);
+        // Letters are text until Esc switches to normal mode
+        assert_eq!(key(&mut vim, KeyCode::Char('j'), KeyModifiers::NONE), NavKey::Unused);
+        assert_eq!(key(&mut vim, KeyCode::Esc, KeyModifiers::NONE), NavKey::Used);
+        assert!(!vim.inserting());
+        assert_eq!(key(&mut vim, KeyCode::Char('j'), KeyModifiers::NONE), NavKey::Moved);
+        assert_eq!(key(&mut vim, KeyCode::Char('d'), KeyModifiers::CONTROL), NavKey::Moved);
+        assert_eq!(key(&mut vim, KeyCode::Char('G'), KeyModifiers::NONE), NavKey::Moved);
+        assert_eq!(key(&mut vim, KeyCode::Char('x'), KeyModifiers::NONE), NavKey::Unused);
+        assert_eq!(key(&mut vim, KeyCode::Char('i'), KeyModifiers::NONE), NavKey::Used);
+        assert!(vim.inserting());
+
+        let mut state = ListState::default().with_selected(Some(0));
+        let mut vim = VimNav { mode: InputMode::Normal, ..VimNav::default() };
+        vim.navigate(&mut state, KeyCode::Char('j'), KeyModifiers::NONE, 20, 10);
+        vim.navigate(&mut state, KeyCode::Char('d'), KeyModifiers::CONTROL, 20, 10);
+        assert_eq!(state.selected(), Some(6));
+        vim.navigate(&mut state, KeyCode::Char('G'), KeyModifiers::NONE, 20, 10);
+        assert_eq!(state.selected(), Some(19));
+        // A single g waits for the second one
+        vim.navigate(&mut state, KeyCode::Char('g'), KeyModifiers::NONE, 20, 10);
+        assert_eq!(state.selected(), Some(19));
+        vim.navigate(&mut state, KeyCode::Char('g'), KeyModifiers::NONE, 20, 10);
+        assert_eq!(state.selected(), Some(0));
+        vim.navigate(&mut state, KeyCode::Char('g'), KeyModifiers::NONE, 20, 10);
+        vim.navigate(&mut state, KeyCode::Char('j'), KeyModifiers::NONE, 20, 10);
+        vim.navigate(&mut state, KeyCode::Char('g'), KeyModifiers::NONE, 20, 10);
+        assert_eq!(state.selected(), Some(1));
+    }
+
+    #[test]
+    fn test_selectable_list() {
+        use ratatui::backend::TestBackend;
+
+        let items: Vec<Line> = (0..20).map(|i| Line::from(format!("item {} {}", i, "x".repeat(i)))).collect();
+        let mut terminal = Terminal::new(TestBackend::new(20, 7)).unwrap();
+        let mut state = ListState::default().with_selected(Some(10));
+        terminal
+            .draw(|f| {
+                let list = SelectableList::new(items.clone())
+                    .header(Line::from("NAME"))
+                    .block(Block::default().borders(Borders::ALL));
+                f.render_stateful_widget(list, f.area(), &mut state);
+            })
+            .unwrap();
+        let row = |y: u16| {
+            let buffer = terminal.backend().buffer();
+            (0..20).map(|x| buffer[(x, y)].symbol()).collect::<String>()
+        };
+        // The header stays, the selection sits in the middle and long items end in `…`
+        assert_eq!(row(1), "│  NAME            │");
+        assert_eq!(row(4), "│► item 10 xxxxxxx…█");
+        assert_eq!(state.offset(), 8);
+
+        let wrapped = wrap_line(&Line::from(vec![Span::raw("abcd"), Span::raw("efg")]), 3);
+        let rows: Vec<String> = wrapped.iter().map(|line| line.to_string()).collect();
+        assert_eq!(rows, vec!["abc", "def", "g"]);
+        assert_eq!(truncate_line(&Line::from("abcdef"), 4).to_string(), "abc…");
+        assert_eq!(truncate_line(&Line::from("abc"), 4).to_string(), "abc");
+        assert_eq!(truncate_line(&Line::from(vec![Span::raw("日"), Span::raw("本語")]), 5).to_string(), "日本…");
+        let rows: Vec<String> = wrap_line(&Line::from("日本語"), 5).iter().map(|line| line.to_string()).collect();
+        assert_eq!(rows, vec!["日本", "語"]);
+    }
+
+    #[test]
+    fn test_help_keys() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert!(is_help_key(&key(KeyCode::Char('?')), false));
+        assert!(!is_help_key(&key(KeyCode::Char('?')), true));
+        assert!(is_help_key(&key(KeyCode::F(1)), true));
+        assert!(is_messages_key(&KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL)));
+        assert!(!is_messages_key(&key(KeyCode::Char('l'))));
+        assert_eq!(scroll_help(0, KeyCode::Up), Some(0));
+        assert_eq!(scroll_help(3, KeyCode::Char('j')), Some(4));
+        assert_eq!(scroll_help(3, KeyCode::Esc), None);
+    }
+
+    #[test]
+    fn test_too_small() {
+        use ratatui::backend::TestBackend;
+
+        assert!(fits_screen(Rect::new(0, 0, MIN_WIDTH, MIN_HEIGHT)));
+        assert!(!fits_screen(Rect::new(0, 0, MIN_WIDTH - 1, 40)));
+        assert!(!fits_screen(Rect::new(0, 0, 200, MIN_HEIGHT - 1)));
+
+        for (width, height) in [(30, 5), (10, 1), (1, 1)] {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal.draw(render_too_small).unwrap();
+            if width == 30 {
+                let buffer = terminal.backend().buffer();
+                let row: String = (0..width).map(|x| buffer[(x, 1)].symbol()).collect();
+                assert_eq!(row.trim(), "Terminal too small");
+            }
+        }
+    }
+
     #[test]
     fn test_common_help_text() {
         let help = common_help_text();
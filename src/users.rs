@@ -0,0 +1,108 @@
+//! User and group name resolution for Unix systems.
+//!
+//! Resolves numeric user and group IDs to names by reading `/etc/passwd` and
+//! `/etc/group` once and caching the result for the lifetime of the process. Lookups never fail:
+//! unknown IDs fall back to their numeric representation, which mirrors what
+//! `ps` and `ls` print when a name cannot be resolved.
+
+use std::{collections::HashMap, env, fs, process::Command, sync::OnceLock};
+
+/// Parse `/etc/passwd`- or `/etc/group`-style content into an ID → name map.
+///
+/// Each line has the form `name:password:id:...`. Comment lines and
+/// malformed entries are skipped.
+fn parse_id_file(content: &str) -> HashMap<u32, String> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((id, name.to_string()))
+        })
+        .collect()
+}
+
+fn passwd() -> &'static HashMap<u32, String> {
+    static USERS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+    USERS.get_or_init(|| {
+        fs::read_to_string("/etc/passwd")
+            .map(|content| parse_id_file(&content))
+            .unwrap_or_default()
+    })
+}
+
+fn groups() -> &'static HashMap<u32, String> {
+    static GROUPS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+    GROUPS.get_or_init(|| {
+        fs::read_to_string("/etc/group")
+            .map(|content| parse_id_file(&content))
+            .unwrap_or_default()
+    })
+}
+
+/// Resolve a user ID to its login name, falling back to the numeric ID
+pub fn user_name(uid: u32) -> String {
+    passwd()
+        .get(&uid)
+        .cloned()
+        .unwrap_or_else(|| uid.to_string())
+}
+
+/// Resolve a group ID to its name, falling back to the numeric ID
+pub fn group_name(gid: u32) -> String {
+    groups()
+        .get(&gid)
+        .cloned()
+        .unwrap_or_else(|| gid.to_string())
+}
+
+/// Login name of the user running `tt`
+///
+/// Prefers `$USER`/`$LOGNAME` (`%USERNAME%` on Windows) and falls back to
+/// `id -un` when none is set.
+pub fn current_user_name() -> String {
+    static CURRENT: OnceLock<String> = OnceLock::new();
+    CURRENT
+        .get_or_init(|| {
+            env::var("USER")
+                .or_else(|_| env::var("LOGNAME"))
+                .or_else(|_| env::var("USERNAME"))
+                .ok()
+                .filter(|name| !name.is_empty())
+                .or_else(|| {
+                    let output = Command::new("id").arg("-un").output().ok()?;
+                    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                    (!name.is_empty()).then_some(name)
+                })
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+/// Whether `tt` runs with root privileges
+#[cfg(unix)]
+pub fn is_root() -> bool {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn is_root() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_id_file() {
+        let content = "# comment\nroot:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/zsh\nbroken\n";
+        let map = parse_id_file(content);
+        assert_eq!(map.get(&0).map(String::as_str), Some("root"));
+        assert_eq!(map.get(&1000).map(String::as_str), Some("alice"));
+        assert_eq!(map.len(), 2);
+    }
+}